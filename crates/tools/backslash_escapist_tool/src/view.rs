@@ -1,10 +1,14 @@
-use dev_utility_core::converter::{escape_backslash, unescape_backslash};
+use dev_utility_core::converter::{
+    escape_backslash, unescape_backslash, EscapeOptions, EscapeProfile,
+};
+use dev_utility_core::error::Diagnostic;
 use gpui::prelude::FluentBuilder;
 use gpui::*;
+use gpui_component::button::{Button, ButtonVariants};
 use gpui_component::input::{Input, InputState};
 use gpui_component::scroll::ScrollableElement;
-use gpui_component::{h_flex, v_flex, ActiveTheme};
-use ui::{error_box, Segment, SegmentedControl};
+use gpui_component::{h_flex, v_flex, ActiveTheme, Sizable};
+use ui::{diagnostic_box, error_box, Segment, SegmentedControl};
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum EscapeMode {
@@ -14,8 +18,10 @@ pub enum EscapeMode {
 
 pub struct BackslashEscapistView {
     mode: EscapeMode,
+    options: EscapeOptions,
     input_state: Entity<InputState>,
     output: String,
+    diagnostic: Option<Diagnostic>,
     error: Option<String>,
 }
 
@@ -32,13 +38,16 @@ impl BackslashEscapistView {
 
         Self {
             mode: EscapeMode::Escape,
+            options: EscapeOptions::default(),
             input_state,
             output: String::new(),
+            diagnostic: None,
             error: None,
         }
     }
 
     fn process(&mut self, cx: &mut Context<Self>) {
+        self.diagnostic = None;
         self.error = None;
         let input = self.input_state.read(cx).text().to_string();
         if input.is_empty() {
@@ -46,15 +55,17 @@ impl BackslashEscapistView {
             cx.notify();
             return;
         }
-        match self.mode {
-            EscapeMode::Escape => self.output = escape_backslash(&input),
-            EscapeMode::Unescape => match unescape_backslash(&input) {
-                Ok(s) => self.output = s,
-                Err(e) => {
-                    self.error = Some(e.to_string());
-                    self.output.clear();
-                }
-            },
+        let result = match self.mode {
+            EscapeMode::Escape => escape_backslash(&input, Some(self.options.clone())),
+            EscapeMode::Unescape => unescape_backslash(&input, self.options.profile),
+        };
+        match result {
+            Ok(s) => self.output = s,
+            Err(e) => {
+                self.diagnostic = e.diagnostic().cloned();
+                self.error = Some(e.to_string());
+                self.output.clear();
+            }
         }
         cx.notify();
     }
@@ -63,12 +74,49 @@ impl BackslashEscapistView {
         self.mode = mode;
         self.process(cx);
     }
+
+    fn set_profile(&mut self, profile: EscapeProfile, cx: &mut Context<Self>) {
+        self.options.profile = profile;
+        self.process(cx);
+    }
+
+    fn toggle_unicode(&mut self, cx: &mut Context<Self>) {
+        self.options.unicode = !self.options.unicode;
+        self.process(cx);
+    }
+
+    fn toggle_hex(&mut self, cx: &mut Context<Self>) {
+        self.options.hex = !self.options.hex;
+        self.process(cx);
+    }
 }
 
 impl Render for BackslashEscapistView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let theme = cx.theme();
         let mode = self.mode;
+        let options = self.options.clone();
+
+        let mut profiles = h_flex().flex_wrap().gap_1();
+        for (i, profile) in EscapeProfile::ALL.into_iter().enumerate() {
+            profiles = profiles.child(
+                toggle(("profile", i), profile.label(), options.profile == profile).on_click(
+                    cx.listener(move |this, _, _window, cx| this.set_profile(profile, cx)),
+                ),
+            );
+        }
+        // The options only shape escaping; unescaping reads every form.
+        let toggles = h_flex()
+            .gap_1()
+            .child(
+                toggle("unicode", "Escape non-ASCII", options.unicode)
+                    .on_click(cx.listener(|this, _, _window, cx| this.toggle_unicode(cx))),
+            )
+            .child(
+                toggle("hex", "Hex escapes", options.hex)
+                    .on_click(cx.listener(|this, _, _window, cx| this.toggle_hex(cx))),
+            );
+
+        let theme = cx.theme();
 
         v_flex()
             .size_full()
@@ -90,6 +138,8 @@ impl Render for BackslashEscapistView {
                         }),
                     )),
             )
+            .child(profiles)
+            .when(mode == EscapeMode::Escape, |this| this.child(toggles))
             .child(
                 h_flex()
                     .flex_1()
@@ -150,8 +200,20 @@ impl Render for BackslashEscapistView {
                             ),
                     ),
             )
-            .when_some(self.error.clone(), |this, error| {
-                this.child(error_box(error, theme))
+            .map(|this| match (&self.diagnostic, &self.error) {
+                (Some(diagnostic), _) => this.child(diagnostic_box(diagnostic, theme)),
+                (None, Some(error)) => this.child(error_box(error.clone(), theme)),
+                (None, None) => this,
             })
     }
 }
+
+fn toggle(id: impl Into<ElementId>, label: &'static str, selected: bool) -> Button {
+    Button::new(id).label(label).small().map(|button| {
+        if selected {
+            button.primary()
+        } else {
+            button.ghost()
+        }
+    })
+}
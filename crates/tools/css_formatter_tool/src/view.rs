@@ -1,41 +1,97 @@
-use dev_utility_core::formatter::format_css;
+use dev_utility_core::error::Diagnostic;
+use dev_utility_core::formatter::{format_css, CssFormatOptions, CssFormatResult, IndentStyle};
 use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::button::{Button, ButtonVariants};
 use gpui_component::input::{Input, InputState};
 use gpui_component::scroll::ScrollableElement;
 use gpui_component::{h_flex, v_flex, ActiveTheme, Disableable, Sizable};
-use ui::error_box;
+use ui::{
+    diagnostic_box, error_box, labelled_input, underline_diagnostic, Segment, SegmentedControl,
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IndentChoice {
+    TwoSpaces,
+    FourSpaces,
+    Tabs,
+    Minified,
+}
+
+impl IndentChoice {
+    fn to_style(self) -> IndentStyle {
+        match self {
+            IndentChoice::TwoSpaces => IndentStyle::Spaces(2),
+            IndentChoice::FourSpaces => IndentStyle::Spaces(4),
+            IndentChoice::Tabs => IndentStyle::Tabs,
+            IndentChoice::Minified => IndentStyle::Minified,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            IndentChoice::TwoSpaces => "2 spaces",
+            IndentChoice::FourSpaces => "4 spaces",
+            IndentChoice::Tabs => "Tabs",
+            IndentChoice::Minified => "Minified",
+        }
+    }
+}
 
 pub struct CssFormatterView {
     input_state: Entity<InputState>,
+    // Browser queries to compile for; empty keeps the CSS as written
+    targets_state: Entity<InputState>,
+    indent: IndentChoice,
+    css_modules: bool,
     output: String,
+    // Rules skipped by error recovery, one per line
+    warnings: Vec<String>,
+    // CSS modules class map, original -> hashed
+    exports: Option<String>,
     error: Option<String>,
+    diagnostic: Option<Diagnostic>,
 }
 
 impl CssFormatterView {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let input_state = cx.new(|cx| {
             InputState::new(window, cx)
-                .multi_line(true)
+                .code_editor("css")
                 .placeholder("Paste CSS here...")
         });
 
-        cx.observe(&input_state, |this, _, cx| {
-            this.process(cx);
-        })
-        .detach();
+        let targets_state =
+            cx.new(|cx| InputState::new(window, cx).placeholder("chrome 90, safari >= 14"));
+
+        for state in [&input_state, &targets_state] {
+            cx.observe(state, |this, _, cx| {
+                this.process(cx);
+            })
+            .detach();
+        }
 
         Self {
             input_state,
+            targets_state,
+            indent: IndentChoice::TwoSpaces,
+            css_modules: false,
             output: String::new(),
+            warnings: Vec::new(),
+            exports: None,
             error: None,
+            diagnostic: None,
         }
     }
 
     fn process(&mut self, cx: &mut Context<Self>) {
         self.error = None;
+        self.diagnostic = None;
+        underline_diagnostic(&self.input_state, None, cx);
+        self.warnings.clear();
+        self.exports = None;
         let input = self.input_state.read(cx).text().to_string();
+        let targets = self.targets_state.read(cx).text().to_string();
 
         if input.trim().is_empty() {
             self.output.clear();
@@ -43,9 +99,23 @@ impl CssFormatterView {
             return;
         }
 
-        match format_css(input) {
-            Ok(s) => self.output = s,
+        let options = CssFormatOptions {
+            targets: Some(targets),
+            css_modules: self.css_modules,
+        };
+        match format_css(&input, self.indent.to_style(), Some(options)) {
+            Ok(result) => {
+                self.warnings = result
+                    .warnings
+                    .iter()
+                    .map(|w| format!("Line {}: {}", w.line, w.message))
+                    .collect();
+                self.exports = summarize_exports(&result);
+                self.output = result.code;
+            }
             Err(e) => {
+                self.diagnostic = e.diagnostic().cloned();
+                underline_diagnostic(&self.input_state, self.diagnostic.as_ref(), cx);
                 self.error = Some(e.to_string());
                 self.output.clear();
             }
@@ -53,6 +123,16 @@ impl CssFormatterView {
         cx.notify();
     }
 
+    fn set_indent(&mut self, indent: IndentChoice, cx: &mut Context<Self>) {
+        self.indent = indent;
+        self.process(cx);
+    }
+
+    fn toggle_css_modules(&mut self, cx: &mut Context<Self>) {
+        self.css_modules = !self.css_modules;
+        self.process(cx);
+    }
+
     fn copy_output(&self, cx: &mut Context<Self>) {
         if !self.output.is_empty() {
             cx.write_to_clipboard(ClipboardItem::new_string(self.output.clone()));
@@ -60,25 +140,88 @@ impl CssFormatterView {
     }
 }
 
+fn summarize_exports(result: &CssFormatResult) -> Option<String> {
+    if result.exports.is_empty() {
+        return None;
+    }
+    let classes: Vec<String> = result
+        .exports
+        .iter()
+        .map(|class| {
+            if class.composes.is_empty() {
+                format!("{} → {}", class.name, class.local)
+            } else {
+                format!(
+                    "{} → {} {}",
+                    class.name,
+                    class.local,
+                    class.composes.join(" ")
+                )
+            }
+        })
+        .collect();
+    Some(classes.join(", "))
+}
+
 impl Render for CssFormatterView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let indent = self.indent;
+        let mut tab_bar = SegmentedControl::new("indent");
+        for choice in [
+            IndentChoice::TwoSpaces,
+            IndentChoice::FourSpaces,
+            IndentChoice::Tabs,
+            IndentChoice::Minified,
+        ] {
+            tab_bar = tab_bar.segment(Segment::new(
+                choice.label(),
+                indent == choice,
+                cx.listener(move |this, _, _window, cx| this.set_indent(choice, cx)),
+            ));
+        }
+
+        let css_modules = Button::new("css-modules")
+            .label("CSS modules")
+            .small()
+            .map(|button| {
+                if self.css_modules {
+                    button.primary()
+                } else {
+                    button.ghost()
+                }
+            })
+            .on_click(cx.listener(|this, _, _window, cx| this.toggle_css_modules(cx)));
+
         let theme = cx.theme();
 
         v_flex()
             .size_full()
             .gap_4()
             .child(
-                h_flex().items_center().justify_end().child(
-                    Button::new("copy")
-                        .label("Copy")
-                        .small()
-                        .ghost()
-                        .disabled(self.output.is_empty())
-                        .on_click(cx.listener(|this, _, _window, cx| {
-                            this.copy_output(cx);
-                        })),
-                ),
+                h_flex()
+                    .items_center()
+                    .justify_between()
+                    .child(h_flex().gap_3().child(tab_bar).child(css_modules))
+                    .child(
+                        Button::new("copy")
+                            .label("Copy")
+                            .small()
+                            .ghost()
+                            .disabled(self.output.is_empty())
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.copy_output(cx);
+                            })),
+                    ),
             )
+            .child(labelled_input("Targets", &self.targets_state, theme))
+            .when_some(self.exports.clone(), |this, exports| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(theme.muted_foreground)
+                        .child(exports),
+                )
+            })
             .child(
                 div()
                     .flex_1()
@@ -141,8 +284,25 @@ impl Render for CssFormatterView {
                             ),
                     ),
             )
-            .when_some(self.error.clone(), |this, error| {
-                this.child(error_box(error, theme))
+            .when(!self.warnings.is_empty(), |this| {
+                this.child(
+                    v_flex()
+                        .gap_1()
+                        .px_3()
+                        .py_2()
+                        .rounded_md()
+                        .bg(theme.warning.opacity(0.1))
+                        .border_1()
+                        .border_color(theme.warning)
+                        .text_xs()
+                        .text_color(theme.warning)
+                        .children(self.warnings.clone()),
+                )
+            })
+            .map(|this| match (&self.diagnostic, &self.error) {
+                (Some(diagnostic), _) => this.child(diagnostic_box(diagnostic, theme)),
+                (None, Some(error)) => this.child(error_box(error.clone(), theme)),
+                (None, None) => this,
             })
     }
 }
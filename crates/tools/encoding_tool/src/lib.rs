@@ -0,0 +1,10 @@
+//! Character encoding converter.
+//!
+//! `Decode` turns bytes written as hex or Base64, or read from a file, into
+//! text through `dev_utility_core::codec::decode_text_bytes`, detecting
+//! the encoding when it is left on `Auto`. `Encode` goes the other way and
+//! `Fix mojibake` repairs UTF-8 that was misread as Windows-1252.
+
+mod view;
+
+pub use view::{ByteSource, EncodingMode, EncodingView};
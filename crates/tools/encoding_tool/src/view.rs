@@ -0,0 +1,448 @@
+use dev_utility_core::codec::{
+    decode_text, decode_text_bytes, encode_text, fix_mojibake, ByteFormat, DecodedText,
+    EncodedText, MojibakeFix, TextEncoding,
+};
+use dev_utility_core::error::Diagnostic;
+use gpui::prelude::FluentBuilder;
+use gpui::*;
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::InputState;
+use gpui_component::{h_flex, v_flex, ActiveTheme, Disableable, Sizable};
+use ui::{
+    diagnostic_box, error_box, labelled_input, row_with_copy, section, Segment, SegmentedControl,
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EncodingMode {
+    Decode,
+    Encode,
+    FixMojibake,
+}
+
+impl EncodingMode {
+    fn label(self) -> &'static str {
+        match self {
+            EncodingMode::Decode => "Decode",
+            EncodingMode::Encode => "Encode",
+            EncodingMode::FixMojibake => "Fix mojibake",
+        }
+    }
+}
+
+/// Where the bytes to decode come from.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ByteSource {
+    Hex,
+    Base64,
+    File,
+}
+
+impl ByteSource {
+    fn label(self) -> &'static str {
+        match self {
+            ByteSource::Hex => "Hex",
+            ByteSource::Base64 => "Base64",
+            ByteSource::File => "File",
+        }
+    }
+}
+
+pub struct EncodingView {
+    bytes_state: Entity<InputState>,
+    text_state: Entity<InputState>,
+    path_state: Entity<InputState>,
+    mode: EncodingMode,
+    source: ByteSource,
+    decode_as: Option<TextEncoding>, // None detects the encoding
+    encode_as: TextEncoding,
+    bom: bool,
+    file_bytes: Option<Vec<u8>>,
+    loading: bool,
+    decoded: Option<DecodedText>,
+    encoded: Option<EncodedText>,
+    fixed: Option<MojibakeFix>,
+    diagnostic: Option<Diagnostic>,
+    error: Option<String>,
+    _load_task: Option<Task<()>>,
+}
+
+impl EncodingView {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let bytes_state = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .placeholder("43 61 66 C3 A9")
+        });
+        let text_state = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .placeholder("Text to encode or repair...")
+        });
+        let path_state = cx.new(|cx| InputState::new(window, cx).placeholder("/path/to/file.txt"));
+
+        for state in [&bytes_state, &text_state] {
+            cx.observe(state, |this, _, cx| this.process(cx)).detach();
+        }
+
+        Self {
+            bytes_state,
+            text_state,
+            path_state,
+            mode: EncodingMode::Decode,
+            source: ByteSource::Hex,
+            decode_as: None,
+            encode_as: TextEncoding::Utf8,
+            bom: false,
+            file_bytes: None,
+            loading: false,
+            decoded: None,
+            encoded: None,
+            fixed: None,
+            diagnostic: None,
+            error: None,
+            _load_task: None,
+        }
+    }
+
+    fn process(&mut self, cx: &mut Context<Self>) {
+        self.decoded = None;
+        self.encoded = None;
+        self.fixed = None;
+        self.diagnostic = None;
+        self.error = None;
+
+        match self.mode {
+            EncodingMode::Decode => {
+                let result = match self.source {
+                    ByteSource::File => match &self.file_bytes {
+                        Some(bytes) => Ok(decode_text_bytes(bytes.clone(), self.decode_as)),
+                        None => {
+                            cx.notify();
+                            return;
+                        }
+                    },
+                    source => {
+                        let input = self.bytes_state.read(cx).text().to_string();
+                        if input.trim().is_empty() {
+                            cx.notify();
+                            return;
+                        }
+                        let format = if source == ByteSource::Hex {
+                            ByteFormat::Hex
+                        } else {
+                            ByteFormat::Base64
+                        };
+                        decode_text(&input, format, self.decode_as)
+                            .map_err(|e| (e.diagnostic().cloned(), e.to_string()))
+                    }
+                };
+                match result {
+                    Ok(decoded) => self.decoded = Some(decoded),
+                    Err((diagnostic, error)) => {
+                        self.diagnostic = diagnostic;
+                        self.error = Some(error);
+                    }
+                }
+            }
+            EncodingMode::Encode => {
+                let input = self.text_state.read(cx).text().to_string();
+                match encode_text(&input, self.encode_as, self.bom) {
+                    Ok(encoded) => self.encoded = Some(encoded),
+                    Err(e) => {
+                        self.diagnostic = e.diagnostic().cloned();
+                        self.error = Some(e.to_string());
+                    }
+                }
+            }
+            EncodingMode::FixMojibake => {
+                let input = self.text_state.read(cx).text().to_string();
+                if !input.is_empty() {
+                    self.fixed = Some(fix_mojibake(&input));
+                }
+            }
+        }
+        cx.notify();
+    }
+
+    fn set_mode(&mut self, mode: EncodingMode, cx: &mut Context<Self>) {
+        self.mode = mode;
+        self.process(cx);
+    }
+
+    fn set_source(&mut self, source: ByteSource, cx: &mut Context<Self>) {
+        self.source = source;
+        self.process(cx);
+    }
+
+    fn set_decode_as(&mut self, encoding: Option<TextEncoding>, cx: &mut Context<Self>) {
+        self.decode_as = encoding;
+        self.process(cx);
+    }
+
+    fn set_encode_as(&mut self, encoding: TextEncoding, cx: &mut Context<Self>) {
+        self.encode_as = encoding;
+        self.process(cx);
+    }
+
+    fn toggle_bom(&mut self, cx: &mut Context<Self>) {
+        self.bom = !self.bom;
+        self.process(cx);
+    }
+
+    fn open_file(&mut self, cx: &mut Context<Self>) {
+        if self.loading {
+            return;
+        }
+        let path = self.path_state.read(cx).text().trim().to_string();
+        if path.is_empty() {
+            self.error = Some("File path is required".to_string());
+            cx.notify();
+            return;
+        }
+        self.loading = true;
+        cx.notify();
+        let task = cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { std::fs::read(&path) })
+                .await;
+            let _ = this.update(cx, |this, cx| {
+                this.loading = false;
+                match result {
+                    Ok(bytes) => {
+                        this.file_bytes = Some(bytes);
+                        this.process(cx);
+                    }
+                    Err(e) => {
+                        this.file_bytes = None;
+                        this.decoded = None;
+                        this.error = Some(format!("Cannot read file: {}", e));
+                        cx.notify();
+                    }
+                }
+            });
+        });
+        self._load_task = Some(task);
+    }
+
+    fn copy(&self, value: String, cx: &mut Context<Self>) {
+        if !value.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(value));
+        }
+    }
+}
+
+fn choice(id: impl Into<ElementId>, label: &'static str, selected: bool) -> Button {
+    Button::new(id).label(label).small().map(|button| {
+        if selected {
+            button.primary()
+        } else {
+            button.ghost()
+        }
+    })
+}
+
+fn summary(decoded: &DecodedText) -> String {
+    let mut parts = vec![if !decoded.detected {
+        decoded.encoding.label().to_string()
+    } else if decoded.confident {
+        format!("Detected {}", decoded.encoding.label())
+    } else {
+        format!("Best guess {}", decoded.encoding.label())
+    }];
+    parts.push(format!("{} bytes", decoded.byte_count));
+    if decoded.bom {
+        parts.push("byte order mark removed".to_string());
+    }
+    if decoded.malformed {
+        parts.push("invalid bytes replaced with U+FFFD".to_string());
+    }
+    parts.join(" · ")
+}
+
+impl Render for EncodingView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mode = self.mode;
+        let mut mode_bar = SegmentedControl::new("encoding-mode");
+        for value in [
+            EncodingMode::Decode,
+            EncodingMode::Encode,
+            EncodingMode::FixMojibake,
+        ] {
+            mode_bar = mode_bar.segment(Segment::new(
+                value.label(),
+                mode == value,
+                cx.listener(move |this, _, _window, cx| this.set_mode(value, cx)),
+            ));
+        }
+
+        let source = self.source;
+        let mut source_bar = SegmentedControl::new("encoding-source");
+        for value in [ByteSource::Hex, ByteSource::Base64, ByteSource::File] {
+            source_bar = source_bar.segment(Segment::new(
+                value.label(),
+                source == value,
+                cx.listener(move |this, _, _window, cx| this.set_source(value, cx)),
+            ));
+        }
+
+        let mut encodings = h_flex().flex_wrap().gap_1();
+        if mode == EncodingMode::Decode {
+            encodings = encodings.child(
+                choice("decode-auto", "Auto", self.decode_as.is_none())
+                    .on_click(cx.listener(|this, _, _window, cx| this.set_decode_as(None, cx))),
+            );
+        }
+        for (i, encoding) in TextEncoding::ALL.into_iter().enumerate() {
+            encodings = encodings.child(match mode {
+                EncodingMode::Decode => choice(
+                    ("decode-as", i),
+                    encoding.label(),
+                    self.decode_as == Some(encoding),
+                )
+                .on_click(
+                    cx.listener(move |this, _, _window, cx| this.set_decode_as(Some(encoding), cx)),
+                ),
+                _ => choice(
+                    ("encode-as", i),
+                    encoding.label(),
+                    self.encode_as == encoding,
+                )
+                .on_click(
+                    cx.listener(move |this, _, _window, cx| this.set_encode_as(encoding, cx)),
+                ),
+            });
+        }
+
+        let theme = cx.theme();
+
+        let input = match (mode, source) {
+            (EncodingMode::Decode, ByteSource::File) => h_flex()
+                .items_end()
+                .gap_2()
+                .child(
+                    div()
+                        .flex_1()
+                        .child(labelled_input("File", &self.path_state, theme)),
+                )
+                .child(
+                    Button::new("open")
+                        .label(if self.loading { "Reading..." } else { "Open" })
+                        .small()
+                        .primary()
+                        .disabled(self.loading)
+                        .on_click(cx.listener(|this, _, _window, cx| this.open_file(cx))),
+                ),
+            (EncodingMode::Decode, _) => {
+                div().child(labelled_input("Bytes", &self.bytes_state, theme))
+            }
+            _ => div().child(labelled_input("Text", &self.text_state, theme)),
+        };
+
+        v_flex()
+            .size_full()
+            .gap_4()
+            .child(
+                h_flex()
+                    .items_center()
+                    .gap_3()
+                    .child(mode_bar)
+                    .when(mode == EncodingMode::Decode, |this| this.child(source_bar))
+                    .when(mode == EncodingMode::Encode, |this| {
+                        this.child(
+                            choice("bom", "Byte order mark", self.bom)
+                                .on_click(cx.listener(|this, _, _window, cx| this.toggle_bom(cx))),
+                        )
+                    }),
+            )
+            .when(mode != EncodingMode::FixMojibake, |this| {
+                this.child(encodings)
+            })
+            .child(input)
+            .map(|this| match (&self.diagnostic, &self.error) {
+                (Some(diagnostic), _) => this.child(diagnostic_box(diagnostic, theme)),
+                (None, Some(error)) => this.child(error_box(error.clone(), theme)),
+                (None, None) => this,
+            })
+            .when_some(self.decoded.as_ref(), |this, decoded| {
+                let text = decoded.text.clone();
+                this.child(
+                    h_flex()
+                        .justify_between()
+                        .child(
+                            div()
+                                .text_sm()
+                                .text_color(theme.muted_foreground)
+                                .child(summary(decoded)),
+                        )
+                        .child(
+                            Button::new("copy-text")
+                                .label("Copy")
+                                .small()
+                                .ghost()
+                                .on_click(cx.listener(move |this, _, _window, cx| {
+                                    this.copy(text.clone(), cx)
+                                })),
+                        ),
+                )
+                .child(section("Text", decoded.text.clone(), theme))
+            })
+            .when_some(self.encoded.as_ref(), |this, encoded| {
+                let hex = encoded.hex.clone();
+                let base64 = encoded.base64.clone();
+                this.child(
+                    v_flex()
+                        .gap_2()
+                        .child(
+                            div()
+                                .text_sm()
+                                .text_color(theme.muted_foreground)
+                                .child(format!("{} bytes", encoded.byte_count)),
+                        )
+                        .child(row_with_copy(
+                            "Hex",
+                            encoded.hex.clone(),
+                            "copy-hex",
+                            cx.listener(move |this, _, _window, cx| this.copy(hex.clone(), cx)),
+                            theme,
+                        ))
+                        .child(row_with_copy(
+                            "Base64",
+                            encoded.base64.clone(),
+                            "copy-base64",
+                            cx.listener(move |this, _, _window, cx| this.copy(base64.clone(), cx)),
+                            theme,
+                        )),
+                )
+            })
+            .when_some(self.fixed.as_ref(), |this, fixed| {
+                let text = fixed.text.clone();
+                let note = match fixed.rounds {
+                    0 => "No double-encoded UTF-8 found".to_string(),
+                    1 => "Undid one layer of double encoding".to_string(),
+                    n => format!("Undid {} layers of double encoding", n),
+                };
+                this.child(
+                    h_flex()
+                        .justify_between()
+                        .child(
+                            div()
+                                .text_sm()
+                                .text_color(theme.muted_foreground)
+                                .child(note),
+                        )
+                        .child(
+                            Button::new("copy-fixed")
+                                .label("Copy")
+                                .small()
+                                .ghost()
+                                .disabled(fixed.rounds == 0)
+                                .on_click(cx.listener(move |this, _, _window, cx| {
+                                    this.copy(text.clone(), cx)
+                                })),
+                        ),
+                )
+                .child(section("Repaired", fixed.text.clone(), theme))
+            })
+    }
+}
@@ -1,6 +1,7 @@
 //! HTML formatter.
 //!
-//! Backed by `dev_utility_core::formatter::format_html` (desktop-only).
+//! Backed by `dev_utility_core::formatter::format_html`, which parses with
+//! html5ever.
 
 mod view;
 
@@ -1,23 +1,57 @@
-use dev_utility_core::formatter::format_html;
+use dev_utility_core::error::Diagnostic;
+use dev_utility_core::formatter::{format_html, HtmlFormatOptions, IndentStyle};
 use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::button::{Button, ButtonVariants};
 use gpui_component::input::{Input, InputState};
 use gpui_component::scroll::ScrollableElement;
 use gpui_component::{h_flex, v_flex, ActiveTheme, Disableable, Sizable};
-use ui::error_box;
+use ui::{diagnostic_box, error_box, underline_diagnostic, Segment, SegmentedControl};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IndentChoice {
+    TwoSpaces,
+    FourSpaces,
+    Tabs,
+    Minified,
+}
+
+impl IndentChoice {
+    fn to_style(self) -> IndentStyle {
+        match self {
+            IndentChoice::TwoSpaces => IndentStyle::Spaces(2),
+            IndentChoice::FourSpaces => IndentStyle::Spaces(4),
+            IndentChoice::Tabs => IndentStyle::Tabs,
+            IndentChoice::Minified => IndentStyle::Minified,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            IndentChoice::TwoSpaces => "2 spaces",
+            IndentChoice::FourSpaces => "4 spaces",
+            IndentChoice::Tabs => "Tabs",
+            IndentChoice::Minified => "Minified",
+        }
+    }
+}
 
 pub struct HtmlFormatterView {
     input_state: Entity<InputState>,
+    indent: IndentChoice,
+    options: HtmlFormatOptions,
     output: String,
+    // Parse errors the parser recovered from, one per line
+    warnings: Vec<String>,
     error: Option<String>,
+    diagnostic: Option<Diagnostic>,
 }
 
 impl HtmlFormatterView {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let input_state = cx.new(|cx| {
             InputState::new(window, cx)
-                .multi_line(true)
+                .code_editor("html")
                 .placeholder("Paste HTML here...")
         });
 
@@ -26,22 +60,38 @@ impl HtmlFormatterView {
 
         Self {
             input_state,
+            indent: IndentChoice::TwoSpaces,
+            options: HtmlFormatOptions::default(),
             output: String::new(),
+            warnings: Vec::new(),
             error: None,
+            diagnostic: None,
         }
     }
 
     fn process(&mut self, cx: &mut Context<Self>) {
         self.error = None;
+        self.diagnostic = None;
+        underline_diagnostic(&self.input_state, None, cx);
+        self.warnings.clear();
         let input = self.input_state.read(cx).text().to_string();
         if input.trim().is_empty() {
             self.output.clear();
             cx.notify();
             return;
         }
-        match format_html(&input) {
-            Ok(s) => self.output = s,
+        match format_html(&input, self.indent.to_style(), Some(self.options.clone())) {
+            Ok(result) => {
+                self.warnings = result
+                    .errors
+                    .iter()
+                    .map(|d| format!("Line {}: {}", d.line, d.message))
+                    .collect();
+                self.output = result.output;
+            }
             Err(e) => {
+                self.diagnostic = e.diagnostic().cloned();
+                underline_diagnostic(&self.input_state, self.diagnostic.as_ref(), cx);
                 self.error = Some(e.to_string());
                 self.output.clear();
             }
@@ -49,6 +99,16 @@ impl HtmlFormatterView {
         cx.notify();
     }
 
+    fn set_indent(&mut self, indent: IndentChoice, cx: &mut Context<Self>) {
+        self.indent = indent;
+        self.process(cx);
+    }
+
+    fn toggle_embedded(&mut self, cx: &mut Context<Self>) {
+        self.options.format_embedded = !self.options.format_embedded;
+        self.process(cx);
+    }
+
     fn copy_output(&self, cx: &mut Context<Self>) {
         if !self.output.is_empty() {
             cx.write_to_clipboard(ClipboardItem::new_string(self.output.clone()));
@@ -58,22 +118,53 @@ impl HtmlFormatterView {
 
 impl Render for HtmlFormatterView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let indent = self.indent;
+        let mut tab_bar = SegmentedControl::new("indent");
+        for choice in [
+            IndentChoice::TwoSpaces,
+            IndentChoice::FourSpaces,
+            IndentChoice::Tabs,
+            IndentChoice::Minified,
+        ] {
+            tab_bar = tab_bar.segment(Segment::new(
+                choice.label(),
+                indent == choice,
+                cx.listener(move |this, _, _window, cx| this.set_indent(choice, cx)),
+            ));
+        }
+
+        let embedded = Button::new("format-embedded")
+            .label("Format CSS & JSON")
+            .small()
+            .map(|button| {
+                if self.options.format_embedded {
+                    button.primary()
+                } else {
+                    button.ghost()
+                }
+            })
+            .on_click(cx.listener(|this, _, _window, cx| this.toggle_embedded(cx)));
+
         let theme = cx.theme();
 
         v_flex()
             .size_full()
             .gap_4()
             .child(
-                h_flex().items_center().justify_end().child(
-                    Button::new("copy")
-                        .label("Copy")
-                        .small()
-                        .ghost()
-                        .disabled(self.output.is_empty())
-                        .on_click(cx.listener(|this, _, _window, cx| {
-                            this.copy_output(cx);
-                        })),
-                ),
+                h_flex()
+                    .items_center()
+                    .justify_between()
+                    .child(h_flex().gap_3().child(tab_bar).child(embedded))
+                    .child(
+                        Button::new("copy")
+                            .label("Copy")
+                            .small()
+                            .ghost()
+                            .disabled(self.output.is_empty())
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.copy_output(cx);
+                            })),
+                    ),
             )
             .child(
                 h_flex()
@@ -135,8 +226,25 @@ impl Render for HtmlFormatterView {
                             ),
                     ),
             )
-            .when_some(self.error.clone(), |this, error| {
-                this.child(error_box(error, theme))
+            .when(!self.warnings.is_empty(), |this| {
+                this.child(
+                    v_flex()
+                        .gap_1()
+                        .px_3()
+                        .py_2()
+                        .rounded_md()
+                        .bg(theme.warning.opacity(0.1))
+                        .border_1()
+                        .border_color(theme.warning)
+                        .text_xs()
+                        .text_color(theme.warning)
+                        .children(self.warnings.clone()),
+                )
+            })
+            .map(|this| match (&self.diagnostic, &self.error) {
+                (Some(diagnostic), _) => this.child(diagnostic_box(diagnostic, theme)),
+                (None, Some(error)) => this.child(error_box(error.clone(), theme)),
+                (None, None) => this,
             })
     }
 }
@@ -0,0 +1,339 @@
+use dev_utility_core::error::Diagnostic;
+use dev_utility_core::formatter::{diff_json, JsonChange, JsonChangeKind, JsonDiffOptions};
+use gpui::prelude::FluentBuilder;
+use gpui::*;
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputState};
+use gpui_component::scroll::ScrollableElement;
+use gpui_component::{h_flex, v_flex, ActiveTheme, Disableable, Sizable};
+use ui::{diagnostic_box, error_box, labelled_input, success, Segment, SegmentedControl};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DiffOutput {
+    Changes,
+    JsonPatch,
+    MergePatch,
+}
+
+impl DiffOutput {
+    fn label(self) -> &'static str {
+        match self {
+            DiffOutput::Changes => "Changes",
+            DiffOutput::JsonPatch => "JSON Patch",
+            DiffOutput::MergePatch => "Merge Patch",
+        }
+    }
+}
+
+pub struct JsonDiffView {
+    left_state: Entity<InputState>,
+    right_state: Entity<InputState>,
+    // Field that identifies array items, e.g. "id"
+    array_key_state: Entity<InputState>,
+    // Comma-separated JSON Pointers, e.g. "/meta/updatedAt, /items/*/etag"
+    ignore_paths_state: Entity<InputState>,
+    ignore_order: bool,
+    output: DiffOutput,
+    changes: Vec<JsonChange>,
+    json_patch: String,
+    merge_patch: String,
+    error: Option<String>,
+    diagnostic: Option<Diagnostic>,
+}
+
+impl JsonDiffView {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let left_state = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .placeholder("Original JSON...")
+        });
+        let right_state = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .placeholder("Changed JSON...")
+        });
+        let array_key_state = cx.new(|cx| InputState::new(window, cx).placeholder("id"));
+        let ignore_paths_state =
+            cx.new(|cx| InputState::new(window, cx).placeholder("/meta/updatedAt, /items/*/etag"));
+
+        for state in [
+            &left_state,
+            &right_state,
+            &array_key_state,
+            &ignore_paths_state,
+        ] {
+            cx.observe(state, |this, _, cx| {
+                this.process(cx);
+            })
+            .detach();
+        }
+
+        Self {
+            left_state,
+            right_state,
+            array_key_state,
+            ignore_paths_state,
+            ignore_order: false,
+            output: DiffOutput::Changes,
+            changes: Vec::new(),
+            json_patch: String::new(),
+            merge_patch: String::new(),
+            error: None,
+            diagnostic: None,
+        }
+    }
+
+    fn process(&mut self, cx: &mut Context<Self>) {
+        self.error = None;
+        self.diagnostic = None;
+        self.changes.clear();
+        self.json_patch.clear();
+        self.merge_patch.clear();
+        let left = self.left_state.read(cx).text().to_string();
+        let right = self.right_state.read(cx).text().to_string();
+
+        if left.trim().is_empty() || right.trim().is_empty() {
+            cx.notify();
+            return;
+        }
+
+        let array_key = self.array_key_state.read(cx).text().trim().to_string();
+        let options = JsonDiffOptions {
+            array_key: (!array_key.is_empty()).then_some(array_key),
+            ignore_order: self.ignore_order,
+            ignore_paths: self
+                .ignore_paths_state
+                .read(cx)
+                .text()
+                .split(',')
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty())
+                .collect(),
+        };
+        match diff_json(&left, &right, Some(options)) {
+            Ok(result) => {
+                self.changes = result.changes;
+                self.json_patch = result.json_patch;
+                self.merge_patch = result.merge_patch;
+            }
+            Err(e) => {
+                self.diagnostic = e.diagnostic().cloned();
+                self.error = Some(e.to_string());
+            }
+        }
+        cx.notify();
+    }
+
+    fn set_output(&mut self, output: DiffOutput, cx: &mut Context<Self>) {
+        self.output = output;
+        cx.notify();
+    }
+
+    fn toggle_ignore_order(&mut self, cx: &mut Context<Self>) {
+        self.ignore_order = !self.ignore_order;
+        self.process(cx);
+    }
+
+    fn output_text(&self) -> String {
+        match self.output {
+            DiffOutput::Changes => String::new(),
+            DiffOutput::JsonPatch => self.json_patch.clone(),
+            DiffOutput::MergePatch => self.merge_patch.clone(),
+        }
+    }
+
+    fn copy_output(&self, cx: &mut Context<Self>) {
+        let text = match self.output {
+            DiffOutput::Changes => self
+                .changes
+                .iter()
+                .map(describe)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => self.output_text(),
+        };
+        if !text.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(text));
+        }
+    }
+}
+
+/// One change as a line, e.g. `~ /name: "a" → "b"`.
+fn describe(change: &JsonChange) -> String {
+    let value = |v: &Option<String>| v.clone().unwrap_or_default();
+    match change.kind {
+        JsonChangeKind::Added => format!("+ {}: {}", change.path, value(&change.right)),
+        JsonChangeKind::Removed => format!("- {}: {}", change.path, value(&change.left)),
+        JsonChangeKind::Changed => format!(
+            "~ {}: {} → {}",
+            change.path,
+            value(&change.left),
+            value(&change.right)
+        ),
+        JsonChangeKind::Moved => format!(
+            "↷ {} → {}",
+            change.from.as_deref().unwrap_or_default(),
+            change.path
+        ),
+    }
+}
+
+fn pane(
+    label: &'static str,
+    state: &Entity<InputState>,
+    theme: &gpui_component::theme::Theme,
+) -> Div {
+    v_flex()
+        .flex_1()
+        .gap_2()
+        .overflow_hidden()
+        .child(
+            div()
+                .text_sm()
+                .font_weight(FontWeight::MEDIUM)
+                .text_color(theme.muted_foreground)
+                .child(label),
+        )
+        .child(
+            div()
+                .flex_1()
+                .rounded_lg()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.background)
+                .p_2()
+                .overflow_hidden()
+                .child(Input::new(state).appearance(false)),
+        )
+}
+
+impl Render for JsonDiffView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let current = self.output;
+        let mut output_bar = SegmentedControl::new("diff-output");
+        for value in [
+            DiffOutput::Changes,
+            DiffOutput::JsonPatch,
+            DiffOutput::MergePatch,
+        ] {
+            output_bar = output_bar.segment(Segment::new(
+                value.label(),
+                current == value,
+                cx.listener(move |this, _, _window, cx| this.set_output(value, cx)),
+            ));
+        }
+
+        let theme = cx.theme();
+        let summary = match self.changes.len() {
+            0 => "No differences".to_string(),
+            1 => "1 difference".to_string(),
+            n => format!("{} differences", n),
+        };
+
+        let body = match self.output {
+            DiffOutput::Changes => v_flex().gap_1().children(self.changes.iter().map(|change| {
+                let color = match change.kind {
+                    JsonChangeKind::Added => success(),
+                    JsonChangeKind::Removed => theme.danger,
+                    JsonChangeKind::Changed => theme.warning,
+                    JsonChangeKind::Moved => theme.muted_foreground,
+                };
+                div().text_color(color).child(describe(change))
+            })),
+            _ => v_flex().child(self.output_text()),
+        };
+
+        v_flex()
+            .size_full()
+            .gap_4()
+            .child(
+                h_flex()
+                    .items_center()
+                    .justify_between()
+                    .child(
+                        h_flex().gap_3().child(output_bar).child(
+                            Button::new("ignore-order")
+                                .label("Ignore order")
+                                .small()
+                                .map(|button| {
+                                    if self.ignore_order {
+                                        button.primary()
+                                    } else {
+                                        button.ghost()
+                                    }
+                                })
+                                .on_click(
+                                    cx.listener(|this, _, _window, cx| {
+                                        this.toggle_ignore_order(cx)
+                                    }),
+                                ),
+                        ),
+                    )
+                    .child(
+                        Button::new("copy")
+                            .label("Copy")
+                            .small()
+                            .ghost()
+                            .disabled(self.changes.is_empty())
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.copy_output(cx);
+                            })),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_3()
+                    .child(div().w(px(160.)).child(labelled_input(
+                        "Array key",
+                        &self.array_key_state,
+                        theme,
+                    )))
+                    .child(div().flex_1().child(labelled_input(
+                        "Ignore paths",
+                        &self.ignore_paths_state,
+                        theme,
+                    ))),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .flex()
+                    .flex_row()
+                    .gap_4()
+                    .overflow_hidden()
+                    .child(pane("Left", &self.left_state, theme))
+                    .child(pane("Right", &self.right_state, theme)),
+            )
+            .map(|this| match (&self.diagnostic, &self.error) {
+                (Some(diagnostic), _) => this.child(diagnostic_box(diagnostic, theme)),
+                (None, Some(error)) => this.child(error_box(error.clone(), theme)),
+                (None, None) => this,
+            })
+            .when(self.error.is_none(), |this| {
+                this.child(
+                    v_flex()
+                        .h(px(220.))
+                        .gap_2()
+                        .child(
+                            div()
+                                .text_sm()
+                                .font_weight(FontWeight::MEDIUM)
+                                .text_color(theme.muted_foreground)
+                                .child(summary),
+                        )
+                        .child(
+                            div()
+                                .flex_1()
+                                .rounded_lg()
+                                .border_1()
+                                .border_color(theme.border)
+                                .bg(theme.background)
+                                .p_2()
+                                .overflow_y_scrollbar()
+                                .child(body.size_full().text_sm().font_family("monospace")),
+                        ),
+                )
+            })
+    }
+}
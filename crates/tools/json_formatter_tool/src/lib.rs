@@ -1,8 +1,19 @@
 //! JSON formatter / minifier.
 //!
-//! Backed by `dev_utility_core::formatter::format_json`, which uses
-//! `sonic-rs` on native builds and `serde_json` on wasm.
+//! Backed by `dev_utility_core::formatter::format_json`, a single-pass
+//! printer that keeps key order and numbers as written; the toolbar
+//! toggles key sorting, compact arrays, ASCII escaping and a final
+//! newline. "Repair" runs `repair_json` instead, accepting JSON5, JSONC,
+//! Python literals and truncated input and listing every fix it made.
+//! The query bar narrows the output with `query_json`
+//! (JSONPath, JMESPath or jq) as you type.
+//!
+//! `JsonDiffView` compares two documents side by side with `diff_json`,
+//! listing added, removed, changed and moved paths or the equivalent
+//! JSON Patch / Merge Patch.
 
+mod diff_view;
 mod view;
 
+pub use diff_view::JsonDiffView;
 pub use view::JsonFormatterView;
@@ -1,11 +1,17 @@
-use dev_utility_core::formatter::{format_json, IndentStyle};
+use dev_utility_core::error::{Diagnostic, UtilityError};
+use dev_utility_core::formatter::{
+    format_json, query_json, repair_json, IndentStyle, JsonFormatOptions, JsonQueryResult,
+    JsonRepairResult, QueryLanguage,
+};
 use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::button::{Button, ButtonVariants};
 use gpui_component::input::{Input, InputState};
 use gpui_component::scroll::ScrollableElement;
 use gpui_component::{h_flex, v_flex, ActiveTheme, Disableable, Sizable};
-use ui::{error_box, Segment, SegmentedControl};
+use ui::{
+    diagnostic_box, error_box, labelled_input, underline_diagnostic, Segment, SegmentedControl,
+};
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum IndentChoice {
@@ -38,34 +44,67 @@ impl IndentChoice {
 pub struct JsonFormatterView {
     input_state: Entity<InputState>,
     indent: IndentChoice,
+    options: JsonFormatOptions,
+    // Accept JSON5, Python literals and truncated input, listing each fix
+    repair: bool,
+    repair_summary: Option<String>,
     output: String,
     error: Option<String>,
+    // Parse failure location and the input line it points into
+    diagnostic: Option<Diagnostic>,
+    // Query bar; an empty expression shows the whole document
+    query_state: Entity<InputState>,
+    query_language: QueryLanguage,
+    query_summary: Option<String>,
+    query_error: Option<String>,
+    _query_task: Option<Task<()>>,
 }
 
 impl JsonFormatterView {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let input_state = cx.new(|cx| {
             InputState::new(window, cx)
-                .multi_line(true)
+                .code_editor("json")
                 .placeholder("Paste JSON here...")
         });
 
-        cx.observe(&input_state, |this, _, cx| {
-            this.process(cx);
-        })
-        .detach();
+        let query_state = cx
+            .new(|cx| InputState::new(window, cx).placeholder("$.store.book[?@.price < 10].title"));
+
+        for state in [&input_state, &query_state] {
+            cx.observe(state, |this, _, cx| {
+                this.process(cx);
+            })
+            .detach();
+        }
 
         Self {
             input_state,
             indent: IndentChoice::TwoSpaces,
+            options: JsonFormatOptions::default(),
+            repair: false,
+            repair_summary: None,
             output: String::new(),
             error: None,
+            diagnostic: None,
+            query_state,
+            query_language: QueryLanguage::JsonPath,
+            query_summary: None,
+            query_error: None,
+            _query_task: None,
         }
     }
 
     fn process(&mut self, cx: &mut Context<Self>) {
         self.error = None;
+        self.diagnostic = None;
+        underline_diagnostic(&self.input_state, None, cx);
+        self.repair_summary = None;
+        self.query_summary = None;
+        self.query_error = None;
+        self._query_task = None;
         let input = self.input_state.read(cx).text().to_string();
+        let query = self.query_state.read(cx).text().to_string();
 
         if input.trim().is_empty() {
             self.output.clear();
@@ -73,21 +112,96 @@ impl JsonFormatterView {
             return;
         }
 
-        match format_json(&input, self.indent.to_style()) {
+        let formatted = if self.repair {
+            repair_json(&input, self.indent.to_style(), Some(self.options.clone())).map(|r| {
+                self.repair_summary = summarize_fixes(&r);
+                r.output
+            })
+        } else {
+            format_json(&input, self.indent.to_style(), Some(self.options.clone()))
+        };
+        match formatted {
             Ok(s) => self.output = s,
             Err(e) => {
+                self.diagnostic = e.diagnostic().cloned();
+                underline_diagnostic(&self.input_state, self.diagnostic.as_ref(), cx);
                 self.error = Some(e.to_string());
                 self.output.clear();
+                cx.notify();
+                return;
             }
         }
+
+        // A query that does not parse yet (mid-typing) leaves the full
+        // document in place. Queries run in the background, as a jq filter
+        // such as `[range(1e12)]` may never finish; the next edit replaces
+        // the task, so a stale result is never shown.
+        if !query.trim().is_empty() {
+            // Repaired input is only valid JSON after the rewrite.
+            let document = if self.repair {
+                self.output.clone()
+            } else {
+                input
+            };
+            let language = self.query_language;
+            let style = self.indent.to_style();
+            let options = self.options.clone();
+            let inner = cx.background_executor().spawn(async move {
+                query_json(&document, &query, language)
+                    .and_then(|r| Ok((format_json(&r.output, style, Some(options))?, r)))
+            });
+            self.query_summary = Some("Running query...".to_string());
+            self._query_task = Some(cx.spawn(async move |this, cx| {
+                let filtered = inner.await;
+                let _ = this.update(cx, |this, cx| {
+                    this.show_query_result(filtered);
+                    cx.notify();
+                });
+            }));
+        }
         cx.notify();
     }
 
+    fn show_query_result(&mut self, filtered: Result<(String, JsonQueryResult), UtilityError>) {
+        self.query_summary = None;
+        match filtered {
+            Ok((output, result)) => {
+                self.output = output;
+                let paths: Vec<&str> = result
+                    .matches
+                    .iter()
+                    .filter_map(|m| m.path.as_deref())
+                    .collect();
+                self.query_summary = Some(match (result.matches.len(), paths.as_slice()) {
+                    (1, _) => "1 match".to_string(),
+                    (n, []) => format!("{} matches", n),
+                    (n, paths) => format!("{} matches: {}", n, paths.join(", ")),
+                });
+            }
+            Err(e) => self.query_error = Some(e.to_string()),
+        }
+    }
+
+    fn set_query_language(&mut self, language: QueryLanguage, cx: &mut Context<Self>) {
+        self.query_language = language;
+        self.process(cx);
+    }
+
     fn set_indent(&mut self, indent: IndentChoice, cx: &mut Context<Self>) {
         self.indent = indent;
         self.process(cx);
     }
 
+    fn toggle_option(&mut self, toggle: fn(&mut JsonFormatOptions), cx: &mut Context<Self>) {
+        toggle(&mut self.options);
+        self.process(cx);
+    }
+
+    fn toggle_repair(&mut self, cx: &mut Context<Self>) {
+        self.repair = !self.repair;
+        self.process(cx);
+    }
+
     fn copy_output(&self, cx: &mut Context<Self>) {
         if !self.output.is_empty() {
             cx.write_to_clipboard(ClipboardItem::new_string(self.output.clone()));
@@ -95,6 +209,19 @@ impl JsonFormatterView {
     }
 }
 
+fn summarize_fixes(result: &JsonRepairResult) -> Option<String> {
+    let fixes: Vec<String> = result
+        .fixes
+        .iter()
+        .map(|d| format!("{} (line {})", d.message, d.line))
+        .collect();
+    match fixes.len() {
+        0 => None,
+        1 => Some(format!("1 fix: {}", fixes[0])),
+        n => Some(format!("{} fixes: {}", n, fixes.join(", "))),
+    }
+}
+
 impl Render for JsonFormatterView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let indent = self.indent;
@@ -115,6 +242,66 @@ impl Render for JsonFormatterView {
             .segment(make_seg(IndentChoice::Tabs, cx))
             .segment(make_seg(IndentChoice::Minified, cx));
 
+        let options = &self.options;
+        let toggles: [(&str, &str, bool, fn(&mut JsonFormatOptions)); 4] = [
+            ("sort-keys", "Sort keys", options.sort_keys, |o| {
+                o.sort_keys = !o.sort_keys
+            }),
+            (
+                "compact-arrays",
+                "Compact arrays",
+                options.compact_arrays,
+                |o| o.compact_arrays = !o.compact_arrays,
+            ),
+            ("ascii", "ASCII only", options.ascii, |o| o.ascii = !o.ascii),
+            (
+                "trailing-newline",
+                "Final newline",
+                options.trailing_newline,
+                |o| o.trailing_newline = !o.trailing_newline,
+            ),
+        ];
+        let mut option_bar = h_flex().gap_1();
+        for (id, label, on, toggle) in toggles {
+            option_bar = option_bar.child(
+                Button::new(id)
+                    .label(label)
+                    .small()
+                    .map(|button| if on { button.primary() } else { button.ghost() })
+                    .on_click(cx.listener(move |this, _, _window, cx| {
+                        this.toggle_option(toggle, cx);
+                    })),
+            );
+        }
+
+        option_bar = option_bar.child(
+            Button::new("repair")
+                .label("Repair")
+                .small()
+                .map(|button| {
+                    if self.repair {
+                        button.primary()
+                    } else {
+                        button.ghost()
+                    }
+                })
+                .on_click(cx.listener(|this, _, _window, cx| this.toggle_repair(cx))),
+        );
+
+        let language = self.query_language;
+        let mut language_bar = SegmentedControl::new("query-language");
+        for (label, value) in [
+            ("JSONPath", QueryLanguage::JsonPath),
+            ("JMESPath", QueryLanguage::JmesPath),
+            ("jq", QueryLanguage::Jq),
+        ] {
+            language_bar = language_bar.segment(Segment::new(
+                label,
+                language == value,
+                cx.listener(move |this, _, _window, cx| this.set_query_language(value, cx)),
+            ));
+        }
+
         let theme = cx.theme();
 
         v_flex()
@@ -124,7 +311,7 @@ impl Render for JsonFormatterView {
                 h_flex()
                     .items_center()
                     .justify_between()
-                    .child(tab_bar)
+                    .child(h_flex().gap_3().child(tab_bar).child(option_bar))
                     .child(
                         Button::new("copy")
                             .label("Copy")
@@ -136,6 +323,32 @@ impl Render for JsonFormatterView {
                             })),
                     ),
             )
+            .when_some(self.repair_summary.clone(), |this, summary| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(theme.muted_foreground)
+                        .child(summary),
+                )
+            })
+            .child(
+                h_flex().items_end().gap_3().child(language_bar).child(
+                    div()
+                        .flex_1()
+                        .child(labelled_input("Query", &self.query_state, theme)),
+                ),
+            )
+            .when_some(self.query_error.clone(), |this, error| {
+                this.child(error_box(error, theme))
+            })
+            .when_some(self.query_summary.clone(), |this, summary| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(theme.muted_foreground)
+                        .child(summary),
+                )
+            })
             .child(
                 div()
                     .flex_1()
@@ -198,8 +411,10 @@ impl Render for JsonFormatterView {
                             ),
                     ),
             )
-            .when_some(self.error.clone(), |this, error| {
-                this.child(error_box(error, theme))
+            .map(|this| match (&self.diagnostic, &self.error) {
+                (Some(diagnostic), _) => this.child(diagnostic_box(diagnostic, theme)),
+                (None, Some(error)) => this.child(error_box(error.clone(), theme)),
+                (None, None) => this,
             })
     }
 }
@@ -0,0 +1,11 @@
+//! Markdown preview and converter.
+//!
+//! `Preview` renders GitHub Flavored Markdown next to the sanitized HTML
+//! from `dev_utility_core::formatter::render_markdown`, with the heading
+//! outline underneath. `From HTML` goes the other way, and `Tables`
+//! realigns every pipe table in the document. The preview pane always
+//! shows the Markdown the current mode produced.
+
+mod view;
+
+pub use view::{MarkdownMode, MarkdownView};
@@ -0,0 +1,268 @@
+use dev_utility_core::formatter::{
+    format_markdown_tables, html_to_markdown, markdown_toc, render_markdown, MarkdownOptions,
+    TocEntry,
+};
+use gpui::prelude::FluentBuilder;
+use gpui::*;
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputState};
+use gpui_component::scroll::ScrollableElement;
+use gpui_component::text::TextView;
+use gpui_component::{h_flex, v_flex, ActiveTheme, Disableable, Sizable};
+use ui::{error_box, Segment, SegmentedControl};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MarkdownMode {
+    Preview,
+    FromHtml,
+    Tables,
+}
+
+impl MarkdownMode {
+    fn label(self) -> &'static str {
+        match self {
+            MarkdownMode::Preview => "Preview",
+            MarkdownMode::FromHtml => "From HTML",
+            MarkdownMode::Tables => "Tables",
+        }
+    }
+
+    fn placeholder(self) -> &'static str {
+        match self {
+            MarkdownMode::FromHtml => "Paste HTML here...",
+            MarkdownMode::Preview | MarkdownMode::Tables => "Paste Markdown here...",
+        }
+    }
+
+    fn output_label(self) -> &'static str {
+        match self {
+            MarkdownMode::Preview => "HTML",
+            MarkdownMode::FromHtml | MarkdownMode::Tables => "Markdown",
+        }
+    }
+}
+
+pub struct MarkdownView {
+    mode: MarkdownMode,
+    input_state: Entity<InputState>,
+    options: MarkdownOptions,
+    output: String,
+    // Markdown shown in the preview pane
+    markdown: SharedString,
+    toc: Vec<TocEntry>,
+    error: Option<String>,
+}
+
+impl MarkdownView {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let mode = MarkdownMode::Preview;
+        let input_state = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .placeholder(mode.placeholder())
+        });
+
+        cx.observe(&input_state, |this, _, cx| this.process(cx))
+            .detach();
+
+        Self {
+            mode,
+            input_state,
+            options: MarkdownOptions::default(),
+            output: String::new(),
+            markdown: SharedString::default(),
+            toc: Vec::new(),
+            error: None,
+        }
+    }
+
+    fn process(&mut self, cx: &mut Context<Self>) {
+        self.error = None;
+        self.toc.clear();
+        let input = self.input_state.read(cx).text().to_string();
+        if input.trim().is_empty() {
+            self.output.clear();
+            self.markdown = SharedString::default();
+            cx.notify();
+            return;
+        }
+        let result = match self.mode {
+            MarkdownMode::Preview => render_markdown(&input, Some(self.options.clone()))
+                .and_then(|html| {
+                    self.toc = markdown_toc(&input)?;
+                    Ok((html, input))
+                }),
+            MarkdownMode::FromHtml => html_to_markdown(&input).map(|md| (md.clone(), md)),
+            MarkdownMode::Tables => format_markdown_tables(&input).map(|md| (md.clone(), md)),
+        };
+        match result {
+            Ok((output, markdown)) => {
+                self.output = output;
+                self.markdown = markdown.into();
+            }
+            Err(e) => {
+                self.error = Some(e.to_string());
+                self.output.clear();
+                self.markdown = SharedString::default();
+            }
+        }
+        cx.notify();
+    }
+
+    fn set_mode(&mut self, mode: MarkdownMode, window: &mut Window, cx: &mut Context<Self>) {
+        self.mode = mode;
+        self.input_state.update(cx, |state, cx| {
+            state.set_placeholder(mode.placeholder(), window, cx);
+        });
+        self.process(cx);
+    }
+
+    fn toggle_sanitize(&mut self, cx: &mut Context<Self>) {
+        self.options.sanitize = !self.options.sanitize;
+        self.process(cx);
+    }
+
+    fn copy_output(&self, cx: &mut Context<Self>) {
+        if !self.output.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(self.output.clone()));
+        }
+    }
+}
+
+fn pane(label: &'static str, body: impl IntoElement, cx: &App) -> Div {
+    let theme = cx.theme();
+    v_flex()
+        .flex_1()
+        .gap_2()
+        .overflow_hidden()
+        .child(
+            div()
+                .text_sm()
+                .font_weight(FontWeight::MEDIUM)
+                .text_color(theme.muted_foreground)
+                .child(label),
+        )
+        .child(
+            div()
+                .flex_1()
+                .rounded_lg()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.background)
+                .p_2()
+                .overflow_hidden()
+                .child(body),
+        )
+}
+
+impl Render for MarkdownView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mode = self.mode;
+        let mut tab_bar = SegmentedControl::new("markdown-mode");
+        for choice in [
+            MarkdownMode::Preview,
+            MarkdownMode::FromHtml,
+            MarkdownMode::Tables,
+        ] {
+            tab_bar = tab_bar.segment(Segment::new(
+                choice.label(),
+                mode == choice,
+                cx.listener(move |this, _, window, cx| this.set_mode(choice, window, cx)),
+            ));
+        }
+
+        let sanitize = Button::new("sanitize")
+            .label("Sanitize")
+            .small()
+            .map(|button| {
+                if self.options.sanitize {
+                    button.primary()
+                } else {
+                    button.ghost()
+                }
+            })
+            .on_click(cx.listener(|this, _, _window, cx| this.toggle_sanitize(cx)));
+
+        let output = div().size_full().overflow_y_scrollbar().child(
+            div()
+                .text_sm()
+                .font_family("monospace")
+                .child(self.output.clone()),
+        );
+        let preview = TextView::markdown("markdown-preview", self.markdown.clone())
+            .selectable(true)
+            .scrollable(true);
+
+        let theme = cx.theme();
+        let toc = (mode == MarkdownMode::Preview && !self.toc.is_empty()).then(|| {
+            v_flex()
+                .gap_1()
+                .px_3()
+                .py_2()
+                .rounded_md()
+                .border_1()
+                .border_color(theme.border)
+                .text_xs()
+                .child(
+                    div()
+                        .font_weight(FontWeight::MEDIUM)
+                        .text_color(theme.muted_foreground)
+                        .child("Outline"),
+                )
+                .children(self.toc.iter().map(|entry| {
+                    h_flex()
+                        .gap_2()
+                        .pl(px(12. * (entry.level - 1) as f32))
+                        .child(entry.text.clone())
+                        .child(
+                            div()
+                                .text_color(theme.muted_foreground)
+                                .font_family("monospace")
+                                .child(format!("#{}", entry.id)),
+                        )
+                }))
+        });
+
+        v_flex()
+            .size_full()
+            .gap_4()
+            .child(
+                h_flex()
+                    .items_center()
+                    .justify_between()
+                    .child(
+                        h_flex()
+                            .gap_3()
+                            .child(tab_bar)
+                            .when(mode == MarkdownMode::Preview, |this| this.child(sanitize)),
+                    )
+                    .child(
+                        Button::new("copy")
+                            .label("Copy")
+                            .small()
+                            .ghost()
+                            .disabled(self.output.is_empty())
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.copy_output(cx);
+                            })),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .flex_1()
+                    .gap_4()
+                    .overflow_hidden()
+                    .child(pane(
+                        "Input",
+                        Input::new(&self.input_state).appearance(false),
+                        cx,
+                    ))
+                    .child(pane(mode.output_label(), output, cx))
+                    .child(pane("Preview", preview, cx)),
+            )
+            .children(toc)
+            .when_some(self.error.clone(), |this, error| {
+                this.child(error_box(error, theme))
+            })
+    }
+}
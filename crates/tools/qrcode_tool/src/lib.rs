@@ -0,0 +1,13 @@
+//! QR code and barcode generator + decoder.
+//!
+//! `Generate` builds a payload (plain text, Wi-Fi, MeCard contact, geo
+//! location or mailto) and renders it as QR, Micro QR, Code 128, EAN-13
+//! or UPC-A. The SVG output is fed straight into a gpui `img`, so the
+//! preview is always exactly what gets copied.
+//!
+//! `Decode` reads a PNG or JPEG from disk on the background executor and
+//! lists every QR symbol found in it.
+
+mod view;
+
+pub use view::{QrCodeMode, QrCodeView};
@@ -0,0 +1,627 @@
+use std::sync::Arc;
+
+use dev_utility_core::generator::{
+    build_qr_payload, decode_qr_image_file, generate_barcode, generate_qr_code_with_options,
+    BarcodeFormat, QrCodeOptions, QrDecodeResult, QrErrorCorrection, QrPayload, WifiSecurity,
+};
+use gpui::prelude::FluentBuilder;
+use gpui::*;
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::InputState;
+use gpui_component::scroll::ScrollableElement;
+use gpui_component::{h_flex, v_flex, ActiveTheme, Disableable, Sizable};
+use ui::{error_box, labelled_input, row_with_copy, Segment, SegmentedControl};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum QrCodeMode {
+    Generate,
+    Decode,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Symbology {
+    Qr,
+    MicroQr,
+    Code128,
+    Ean13,
+    UpcA,
+}
+
+impl Symbology {
+    fn is_qr(self) -> bool {
+        matches!(self, Symbology::Qr | Symbology::MicroQr)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum PayloadKind {
+    Text,
+    Wifi,
+    Contact,
+    Geo,
+    Email,
+}
+
+/// Minimum edge length, in pixels, requested from the encoder.
+const IMAGE_SIZE: u32 = 256;
+
+pub struct QrCodeView {
+    mode: QrCodeMode,
+    // Generator state
+    symbology: Symbology,
+    payload_kind: PayloadKind,
+    error_correction: QrErrorCorrection,
+    wifi_security: WifiSecurity,
+    text_input: Entity<InputState>,
+    ssid_input: Entity<InputState>,
+    wifi_password_input: Entity<InputState>,
+    name_input: Entity<InputState>,
+    phone_input: Entity<InputState>,
+    email_input: Entity<InputState>,
+    latitude_input: Entity<InputState>,
+    longitude_input: Entity<InputState>,
+    mail_to_input: Entity<InputState>,
+    subject_input: Entity<InputState>,
+    body_input: Entity<InputState>,
+    version_input: Entity<InputState>,
+    mask_input: Entity<InputState>,
+    svg: String,
+    image: Option<Arc<Image>>,
+    summary: String,
+    generator_error: Option<String>,
+    // Decoder state
+    path_input: Entity<InputState>,
+    decoding: bool,
+    decoded: Option<QrDecodeResult>,
+    decoder_error: Option<String>,
+    _decode_task: Option<Task<()>>,
+}
+
+impl QrCodeView {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let text_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .placeholder("Text or URL to encode...")
+        });
+        let ssid_input = cx.new(|cx| InputState::new(window, cx).placeholder("Network name"));
+        let wifi_password_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Password"));
+        let name_input = cx.new(|cx| InputState::new(window, cx).placeholder("Doe,John"));
+        let phone_input = cx.new(|cx| InputState::new(window, cx).placeholder("+1 555 0100"));
+        let email_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("alice@example.com"));
+        let latitude_input = cx.new(|cx| InputState::new(window, cx).placeholder("37.7749"));
+        let longitude_input = cx.new(|cx| InputState::new(window, cx).placeholder("-122.4194"));
+        let mail_to_input =
+            cx.new(|cx| InputState::new(window, cx).placeholder("alice@example.com"));
+        let subject_input = cx.new(|cx| InputState::new(window, cx).placeholder("Subject"));
+        let body_input = cx.new(|cx| InputState::new(window, cx).placeholder("Message"));
+        let version_input = cx.new(|cx| InputState::new(window, cx).placeholder("Auto"));
+        let mask_input = cx.new(|cx| InputState::new(window, cx).placeholder("Auto"));
+        let path_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("/path/to/image.png")
+        });
+
+        for input in [
+            &text_input,
+            &ssid_input,
+            &wifi_password_input,
+            &name_input,
+            &phone_input,
+            &email_input,
+            &latitude_input,
+            &longitude_input,
+            &mail_to_input,
+            &subject_input,
+            &body_input,
+            &version_input,
+            &mask_input,
+        ] {
+            cx.observe(input, |this, _, cx| this.generate(cx)).detach();
+        }
+
+        Self {
+            mode: QrCodeMode::Generate,
+            symbology: Symbology::Qr,
+            payload_kind: PayloadKind::Text,
+            error_correction: QrErrorCorrection::Medium,
+            wifi_security: WifiSecurity::Wpa,
+            text_input,
+            ssid_input,
+            wifi_password_input,
+            name_input,
+            phone_input,
+            email_input,
+            latitude_input,
+            longitude_input,
+            mail_to_input,
+            subject_input,
+            body_input,
+            version_input,
+            mask_input,
+            svg: String::new(),
+            image: None,
+            summary: String::new(),
+            generator_error: None,
+            path_input,
+            decoding: false,
+            decoded: None,
+            decoder_error: None,
+            _decode_task: None,
+        }
+    }
+
+    fn set_mode(&mut self, mode: QrCodeMode, cx: &mut Context<Self>) {
+        self.mode = mode;
+        cx.notify();
+    }
+
+    fn set_symbology(&mut self, symbology: Symbology, cx: &mut Context<Self>) {
+        self.symbology = symbology;
+        self.generate(cx);
+    }
+
+    fn set_payload_kind(&mut self, kind: PayloadKind, cx: &mut Context<Self>) {
+        self.payload_kind = kind;
+        self.generate(cx);
+    }
+
+    fn set_error_correction(&mut self, level: QrErrorCorrection, cx: &mut Context<Self>) {
+        self.error_correction = level;
+        self.generate(cx);
+    }
+
+    fn set_wifi_security(&mut self, security: WifiSecurity, cx: &mut Context<Self>) {
+        self.wifi_security = security;
+        self.generate(cx);
+    }
+
+    fn text(&self, input: &Entity<InputState>, cx: &Context<Self>) -> String {
+        input.read(cx).text().to_string()
+    }
+
+    fn optional(&self, input: &Entity<InputState>, cx: &Context<Self>) -> Option<String> {
+        Some(self.text(input, cx)).filter(|s| !s.trim().is_empty())
+    }
+
+    fn build_payload(&self, cx: &Context<Self>) -> Result<String, String> {
+        // Linear barcodes carry the raw text only.
+        let kind = if self.symbology.is_qr() {
+            self.payload_kind
+        } else {
+            PayloadKind::Text
+        };
+        let payload = match kind {
+            PayloadKind::Text => QrPayload::Text {
+                text: self.text(&self.text_input, cx),
+            },
+            PayloadKind::Wifi => QrPayload::Wifi {
+                ssid: self.text(&self.ssid_input, cx),
+                password: self.text(&self.wifi_password_input, cx),
+                security: self.wifi_security,
+                hidden: false,
+            },
+            PayloadKind::Contact => QrPayload::MeCard {
+                name: self.text(&self.name_input, cx),
+                phone: self.optional(&self.phone_input, cx),
+                email: self.optional(&self.email_input, cx),
+                url: None,
+                address: None,
+                note: None,
+            },
+            PayloadKind::Geo => {
+                let parse = |input: &Entity<InputState>, label: &str| {
+                    let raw = self.text(input, cx);
+                    raw.trim()
+                        .parse::<f64>()
+                        .map_err(|_| format!("{} must be a number", label))
+                };
+                QrPayload::Geo {
+                    latitude: parse(&self.latitude_input, "Latitude")?,
+                    longitude: parse(&self.longitude_input, "Longitude")?,
+                    query: None,
+                }
+            }
+            PayloadKind::Email => QrPayload::Mailto {
+                to: self.text(&self.mail_to_input, cx).trim().to_string(),
+                subject: self.optional(&self.subject_input, cx),
+                body: self.optional(&self.body_input, cx),
+            },
+        };
+        Ok(build_qr_payload(payload))
+    }
+
+    fn parse_optional_u8(
+        &self,
+        input: &Entity<InputState>,
+        label: &str,
+        cx: &Context<Self>,
+    ) -> Result<Option<u8>, String> {
+        match self.optional(input, cx) {
+            None => Ok(None),
+            Some(raw) => raw
+                .trim()
+                .parse::<u8>()
+                .map(Some)
+                .map_err(|_| format!("{} must be a whole number", label)),
+        }
+    }
+
+    fn generate(&mut self, cx: &mut Context<Self>) {
+        let result = self.render_symbol(cx);
+        match result {
+            Ok(Some((svg, summary))) => {
+                self.image = Some(Arc::new(Image::from_bytes(
+                    ImageFormat::Svg,
+                    svg.clone().into_bytes(),
+                )));
+                self.svg = svg;
+                self.summary = summary;
+                self.generator_error = None;
+            }
+            Ok(None) => {
+                self.image = None;
+                self.svg.clear();
+                self.summary.clear();
+                self.generator_error = None;
+            }
+            Err(e) => {
+                self.image = None;
+                self.svg.clear();
+                self.summary.clear();
+                self.generator_error = Some(e);
+            }
+        }
+        cx.notify();
+    }
+
+    /// Returns `Ok(None)` when there is nothing to encode yet.
+    fn render_symbol(&self, cx: &Context<Self>) -> Result<Option<(String, String)>, String> {
+        let data = self.build_payload(cx)?;
+        if data.is_empty() {
+            return Ok(None);
+        }
+
+        let format = match self.symbology {
+            Symbology::Qr | Symbology::MicroQr => {
+                let options = QrCodeOptions {
+                    error_correction: self.error_correction,
+                    micro: self.symbology == Symbology::MicroQr,
+                    version: self.parse_optional_u8(&self.version_input, "Version", cx)?,
+                    mask: self.parse_optional_u8(&self.mask_input, "Mask", cx)?,
+                    size: IMAGE_SIZE,
+                };
+                let image = generate_qr_code_with_options(&data, options)
+                    .map_err(|e| e.to_string())?;
+                let summary = format!("{} × {} modules", image.modules, image.modules);
+                return Ok(Some((image.svg, summary)));
+            }
+            Symbology::Code128 => BarcodeFormat::Code128,
+            Symbology::Ean13 => BarcodeFormat::Ean13,
+            Symbology::UpcA => BarcodeFormat::UpcA,
+        };
+        let image = generate_barcode(data.trim(), format, IMAGE_SIZE * 2)
+            .map_err(|e| e.to_string())?;
+        let summary = format!("{} — {} modules", image.text, image.modules);
+        Ok(Some((image.svg, summary)))
+    }
+
+    fn decode(&mut self, cx: &mut Context<Self>) {
+        if self.decoding {
+            return;
+        }
+        let path = self.path_input.read(cx).text().trim().to_string();
+        if path.is_empty() {
+            self.decoder_error = Some("Image path is required".to_string());
+            self.decoded = None;
+            cx.notify();
+            return;
+        }
+        self.decoding = true;
+        cx.notify();
+        let task = cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { decode_qr_image_file(path) })
+                .await;
+            let _ = this.update(cx, |this, cx| {
+                this.decoding = false;
+                match result {
+                    Ok(r) => {
+                        this.decoded = Some(r);
+                        this.decoder_error = None;
+                    }
+                    Err(e) => {
+                        this.decoder_error = Some(e.to_string());
+                        this.decoded = None;
+                    }
+                }
+                cx.notify();
+            });
+        });
+        self._decode_task = Some(task);
+    }
+
+    fn copy(&self, value: String, cx: &mut Context<Self>) {
+        if !value.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(value));
+        }
+    }
+}
+
+impl Render for QrCodeView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mode = self.mode;
+
+        let mode_bar = SegmentedControl::new("qr-mode")
+            .segment(Segment::new(
+                "Generate",
+                mode == QrCodeMode::Generate,
+                cx.listener(|this, _, _window, cx| this.set_mode(QrCodeMode::Generate, cx)),
+            ))
+            .segment(Segment::new(
+                "Decode",
+                mode == QrCodeMode::Decode,
+                cx.listener(|this, _, _window, cx| this.set_mode(QrCodeMode::Decode, cx)),
+            ));
+
+        let content: AnyElement = match mode {
+            QrCodeMode::Generate => self.render_generate(cx).into_any_element(),
+            QrCodeMode::Decode => self.render_decode(cx).into_any_element(),
+        };
+
+        v_flex()
+            .size_full()
+            .gap_4()
+            .child(h_flex().items_center().gap_3().child(mode_bar))
+            .child(content)
+    }
+}
+
+impl QrCodeView {
+    fn render_generate(&self, cx: &Context<Self>) -> Div {
+        let theme = cx.theme();
+        let symbology = self.symbology;
+        let kind = self.payload_kind;
+        let level = self.error_correction;
+
+        let mut symbology_bar = SegmentedControl::new("qr-symbology");
+        for (label, value) in [
+            ("QR", Symbology::Qr),
+            ("Micro QR", Symbology::MicroQr),
+            ("Code 128", Symbology::Code128),
+            ("EAN-13", Symbology::Ean13),
+            ("UPC-A", Symbology::UpcA),
+        ] {
+            symbology_bar = symbology_bar.segment(Segment::new(
+                label,
+                symbology == value,
+                cx.listener(move |this, _, _window, cx| this.set_symbology(value, cx)),
+            ));
+        }
+
+        let mut settings = h_flex()
+            .items_center()
+            .gap_3()
+            .child(symbology_bar);
+
+        if symbology.is_qr() {
+            let mut ecc_bar = SegmentedControl::new("qr-ecc");
+            for (label, value) in [
+                ("L", QrErrorCorrection::Low),
+                ("M", QrErrorCorrection::Medium),
+                ("Q", QrErrorCorrection::Quartile),
+                ("H", QrErrorCorrection::High),
+            ] {
+                ecc_bar = ecc_bar.segment(Segment::new(
+                    label,
+                    level == value,
+                    cx.listener(move |this, _, _window, cx| this.set_error_correction(value, cx)),
+                ));
+            }
+            settings = settings
+                .child(
+                    div()
+                        .text_xs()
+                        .text_color(theme.muted_foreground)
+                        .child("ECC"),
+                )
+                .child(ecc_bar);
+        }
+
+        let mut form = v_flex().gap_3();
+        if symbology.is_qr() {
+            let mut kind_bar = SegmentedControl::new("qr-payload");
+            for (label, value) in [
+                ("Text", PayloadKind::Text),
+                ("Wi-Fi", PayloadKind::Wifi),
+                ("Contact", PayloadKind::Contact),
+                ("Geo", PayloadKind::Geo),
+                ("Email", PayloadKind::Email),
+            ] {
+                kind_bar = kind_bar.segment(Segment::new(
+                    label,
+                    kind == value,
+                    cx.listener(move |this, _, _window, cx| this.set_payload_kind(value, cx)),
+                ));
+            }
+            form = form.child(kind_bar).child(self.render_payload_fields(cx)).child(
+                h_flex()
+                    .gap_3()
+                    .child(div().w(px(120.)).child(labelled_input(
+                        "Version",
+                        &self.version_input,
+                        theme,
+                    )))
+                    .child(div().w(px(120.)).child(labelled_input(
+                        "Mask",
+                        &self.mask_input,
+                        theme,
+                    ))),
+            );
+        } else {
+            let label = match symbology {
+                Symbology::Ean13 => "Digits (12, or 13 with check digit)",
+                Symbology::UpcA => "Digits (11, or 12 with check digit)",
+                _ => "Text (ASCII)",
+            };
+            form = form.child(labelled_input(label, &self.text_input, theme));
+        }
+
+        let svg_for_copy = self.svg.clone();
+        // Linear barcodes are rendered at twice the width and a third of
+        // that in height.
+        let (image_width, image_height) = if symbology.is_qr() {
+            (px(IMAGE_SIZE as f32), px(IMAGE_SIZE as f32))
+        } else {
+            (px((IMAGE_SIZE * 2) as f32), px((IMAGE_SIZE * 2 / 3) as f32))
+        };
+
+        v_flex()
+            .flex_1()
+            .gap_4()
+            .overflow_y_scrollbar()
+            .child(settings)
+            .child(form)
+            .when_some(self.generator_error.clone(), |this, e| {
+                this.child(error_box(e, theme))
+            })
+            .when_some(self.image.clone(), |this, image| {
+                this.child(
+                    v_flex()
+                        .items_center()
+                        .gap_2()
+                        .child(
+                            div()
+                                .p_2()
+                                .rounded_lg()
+                                .border_1()
+                                .border_color(theme.border)
+                                .bg(gpui::white())
+                                .child(img(image).w(image_width).h(image_height)),
+                        )
+                        .child(
+                            h_flex()
+                                .items_center()
+                                .gap_3()
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(theme.muted_foreground)
+                                        .child(self.summary.clone()),
+                                )
+                                .child(
+                                    Button::new("copy-svg")
+                                        .label("Copy SVG")
+                                        .small()
+                                        .ghost()
+                                        .on_click(cx.listener(move |this, _, _window, cx| {
+                                            this.copy(svg_for_copy.clone(), cx)
+                                        })),
+                                ),
+                        ),
+                )
+            })
+    }
+
+    fn render_payload_fields(&self, cx: &Context<Self>) -> Div {
+        let theme = cx.theme();
+        match self.payload_kind {
+            PayloadKind::Text => v_flex().child(labelled_input("Text", &self.text_input, theme)),
+            PayloadKind::Wifi => {
+                let security = self.wifi_security;
+                let mut security_bar = SegmentedControl::new("qr-wifi-security");
+                for (label, value) in [
+                    ("WPA", WifiSecurity::Wpa),
+                    ("WEP", WifiSecurity::Wep),
+                    ("Open", WifiSecurity::Nopass),
+                ] {
+                    security_bar = security_bar.segment(Segment::new(
+                        label,
+                        security == value,
+                        cx.listener(move |this, _, _window, cx| this.set_wifi_security(value, cx)),
+                    ));
+                }
+                v_flex()
+                    .gap_3()
+                    .child(security_bar)
+                    .child(labelled_input("SSID", &self.ssid_input, theme))
+                    .when(security != WifiSecurity::Nopass, |this| {
+                        this.child(labelled_input("Password", &self.wifi_password_input, theme))
+                    })
+            }
+            PayloadKind::Contact => v_flex()
+                .gap_3()
+                .child(labelled_input("Name", &self.name_input, theme))
+                .child(labelled_input("Phone", &self.phone_input, theme))
+                .child(labelled_input("Email", &self.email_input, theme)),
+            PayloadKind::Geo => h_flex()
+                .gap_3()
+                .child(
+                    div()
+                        .flex_1()
+                        .child(labelled_input("Latitude", &self.latitude_input, theme)),
+                )
+                .child(
+                    div()
+                        .flex_1()
+                        .child(labelled_input("Longitude", &self.longitude_input, theme)),
+                ),
+            PayloadKind::Email => v_flex()
+                .gap_3()
+                .child(labelled_input("To", &self.mail_to_input, theme))
+                .child(labelled_input("Subject", &self.subject_input, theme))
+                .child(labelled_input("Body", &self.body_input, theme)),
+        }
+    }
+
+    fn render_decode(&self, cx: &Context<Self>) -> Div {
+        let theme = cx.theme();
+
+        v_flex()
+            .flex_1()
+            .gap_4()
+            .child(labelled_input("Image file (PNG or JPEG)", &self.path_input, theme))
+            .child(
+                Button::new("decode")
+                    .label(if self.decoding { "Decoding..." } else { "Decode" })
+                    .small()
+                    .primary()
+                    .disabled(self.decoding)
+                    .on_click(cx.listener(|this, _, _window, cx| this.decode(cx))),
+            )
+            .when_some(self.decoder_error.clone(), |this, e| {
+                this.child(error_box(e, theme))
+            })
+            .when_some(self.decoded.as_ref(), |this, result| {
+                let mut list = v_flex().gap_2();
+                for (i, code) in result.codes.iter().enumerate() {
+                    let content = code.content.clone();
+                    let label = format!(
+                        "v{} · ECC {:?} · mask {}",
+                        code.version, code.error_correction, code.mask
+                    );
+                    list = list.child(row_with_copy(
+                        label,
+                        code.content.clone(),
+                        ("copy-decoded", i),
+                        cx.listener(move |this, _, _window, cx| this.copy(content.clone(), cx)),
+                        theme,
+                    ));
+                }
+                if result.failed > 0 {
+                    list = list.child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.muted_foreground)
+                            .child(format!(
+                                "{} more symbol(s) found but could not be decoded",
+                                result.failed
+                            )),
+                    );
+                }
+                this.child(list)
+            })
+    }
+}
@@ -0,0 +1,11 @@
+//! Regex tester.
+//!
+//! The pattern runs on every edit through
+//! `dev_utility_core::converter::test_regex`; matches are highlighted in
+//! a copy of the text and listed with their groups underneath. The
+//! `Replace` tab previews `replace_regex` and `Explain` lists the
+//! pattern's tokens with a plain-English description of each.
+
+mod view;
+
+pub use view::{RegexPanel, RegexTesterView};
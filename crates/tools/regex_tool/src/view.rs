@@ -0,0 +1,481 @@
+use dev_utility_core::converter::{
+    explain_regex, replace_regex, test_regex, RegexEngine, RegexMatch, RegexOptions, RegexToken,
+    RegexTokenKind,
+};
+use dev_utility_core::error::Diagnostic;
+use gpui::prelude::FluentBuilder;
+use gpui::*;
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputState};
+use gpui_component::scroll::ScrollableElement;
+use gpui_component::{h_flex, v_flex, ActiveTheme, Disableable, Sizable};
+use ui::{diagnostic_box, error_box, labelled_input, success, Segment, SegmentedControl};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RegexPanel {
+    Matches,
+    Replace,
+    Explain,
+}
+
+impl RegexPanel {
+    fn label(self) -> &'static str {
+        match self {
+            RegexPanel::Matches => "Matches",
+            RegexPanel::Replace => "Replace",
+            RegexPanel::Explain => "Explain",
+        }
+    }
+}
+
+fn engine_label(engine: RegexEngine) -> &'static str {
+    match engine {
+        RegexEngine::Auto => "Auto",
+        RegexEngine::Standard => "Standard",
+        RegexEngine::Fancy => "Fancy",
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Flag {
+    CaseInsensitive,
+    MultiLine,
+    DotAll,
+    Verbose,
+    Lazy,
+}
+
+impl Flag {
+    const ALL: [Flag; 5] = [
+        Flag::CaseInsensitive,
+        Flag::MultiLine,
+        Flag::DotAll,
+        Flag::Verbose,
+        Flag::Lazy,
+    ];
+
+    fn id(self) -> &'static str {
+        match self {
+            Flag::CaseInsensitive => "flag-i",
+            Flag::MultiLine => "flag-m",
+            Flag::DotAll => "flag-s",
+            Flag::Verbose => "flag-x",
+            Flag::Lazy => "flag-u",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Flag::CaseInsensitive => "i  Ignore case",
+            Flag::MultiLine => "m  Multi-line",
+            Flag::DotAll => "s  Dot all",
+            Flag::Verbose => "x  Verbose",
+            Flag::Lazy => "U  Lazy",
+        }
+    }
+
+    fn is_on(self, options: &RegexOptions) -> bool {
+        match self {
+            Flag::CaseInsensitive => options.case_insensitive,
+            Flag::MultiLine => options.multi_line,
+            Flag::DotAll => options.dot_matches_new_line,
+            Flag::Verbose => options.ignore_whitespace,
+            Flag::Lazy => options.swap_greed,
+        }
+    }
+
+    fn toggle(self, options: &mut RegexOptions) {
+        let value = match self {
+            Flag::CaseInsensitive => &mut options.case_insensitive,
+            Flag::MultiLine => &mut options.multi_line,
+            Flag::DotAll => &mut options.dot_matches_new_line,
+            Flag::Verbose => &mut options.ignore_whitespace,
+            Flag::Lazy => &mut options.swap_greed,
+        };
+        *value = !*value;
+    }
+}
+
+pub struct RegexTesterView {
+    pattern_state: Entity<InputState>,
+    text_state: Entity<InputState>,
+    replacement_state: Entity<InputState>,
+    options: RegexOptions,
+    panel: RegexPanel,
+    matches: Vec<RegexMatch>,
+    // The engine that ran, shown when Auto picked one
+    engine: Option<RegexEngine>,
+    truncated: bool,
+    replaced: String,
+    replacements: usize,
+    tokens: Vec<RegexToken>,
+    error: Option<String>,
+    diagnostic: Option<Diagnostic>,
+}
+
+impl RegexTesterView {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let pattern_state =
+            cx.new(|cx| InputState::new(window, cx).placeholder(r"(?<key>\w+)=(\d+)"));
+        let text_state = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .placeholder("Text to search...")
+        });
+        let replacement_state = cx.new(|cx| InputState::new(window, cx).placeholder("${key}: $2"));
+
+        for state in [&pattern_state, &text_state, &replacement_state] {
+            cx.observe(state, |this, _, cx| this.process(cx)).detach();
+        }
+
+        Self {
+            pattern_state,
+            text_state,
+            replacement_state,
+            options: RegexOptions::default(),
+            panel: RegexPanel::Matches,
+            matches: Vec::new(),
+            engine: None,
+            truncated: false,
+            replaced: String::new(),
+            replacements: 0,
+            tokens: Vec::new(),
+            error: None,
+            diagnostic: None,
+        }
+    }
+
+    fn process(&mut self, cx: &mut Context<Self>) {
+        self.error = None;
+        self.diagnostic = None;
+        self.matches.clear();
+        self.engine = None;
+        self.truncated = false;
+        self.replaced.clear();
+        self.replacements = 0;
+        self.tokens.clear();
+        let pattern = self.pattern_state.read(cx).text().to_string();
+        let text = self.text_state.read(cx).text().to_string();
+        let replacement = self.replacement_state.read(cx).text().to_string();
+
+        if pattern.is_empty() {
+            cx.notify();
+            return;
+        }
+
+        let options = Some(self.options.clone());
+        let result = test_regex(&pattern, &text, options.clone()).and_then(|result| {
+            let replaced = replace_regex(&pattern, &text, &replacement, options.clone())?;
+            let tokens = explain_regex(&pattern, options)?;
+            Ok((result, replaced, tokens))
+        });
+        match result {
+            Ok((result, replaced, tokens)) => {
+                self.matches = result.matches;
+                self.engine = Some(result.engine);
+                self.truncated = result.truncated;
+                self.replaced = replaced.output;
+                self.replacements = replaced.replacements;
+                self.tokens = tokens;
+            }
+            Err(e) => {
+                self.diagnostic = e.diagnostic().cloned();
+                self.error = Some(e.to_string());
+            }
+        }
+        cx.notify();
+    }
+
+    fn set_engine(&mut self, engine: RegexEngine, cx: &mut Context<Self>) {
+        self.options.engine = engine;
+        self.process(cx);
+    }
+
+    fn toggle_flag(&mut self, flag: Flag, cx: &mut Context<Self>) {
+        flag.toggle(&mut self.options);
+        self.process(cx);
+    }
+
+    fn set_panel(&mut self, panel: RegexPanel, cx: &mut Context<Self>) {
+        self.panel = panel;
+        cx.notify();
+    }
+
+    fn copy_output(&self, cx: &mut Context<Self>) {
+        let text = match self.panel {
+            RegexPanel::Matches => self
+                .matches
+                .iter()
+                .map(|m| m.text.clone())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            RegexPanel::Replace => self.replaced.clone(),
+            RegexPanel::Explain => self
+                .tokens
+                .iter()
+                .map(|t| format!("{}{}  {}", "  ".repeat(t.depth), t.text, t.description))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+        if !text.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(text));
+        }
+    }
+}
+
+fn pane(label: &'static str, body: impl IntoElement, cx: &App) -> Div {
+    let theme = cx.theme();
+    v_flex()
+        .flex_1()
+        .gap_2()
+        .overflow_hidden()
+        .child(
+            div()
+                .text_sm()
+                .font_weight(FontWeight::MEDIUM)
+                .text_color(theme.muted_foreground)
+                .child(label),
+        )
+        .child(
+            div()
+                .flex_1()
+                .rounded_lg()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.background)
+                .p_2()
+                .overflow_hidden()
+                .child(body),
+        )
+}
+
+/// The text with every match highlighted, alternating two colors so that
+/// adjacent matches stay apart.
+fn highlighted(
+    text: String,
+    matches: &[RegexMatch],
+    theme: &gpui_component::theme::Theme,
+) -> StyledText {
+    let colors = [theme.primary.opacity(0.25), theme.warning.opacity(0.3)];
+    let highlights: Vec<_> = matches
+        .iter()
+        .filter(|m| m.end > m.start)
+        .enumerate()
+        .map(|(i, m)| {
+            (
+                m.start..m.end,
+                HighlightStyle {
+                    background_color: Some(colors[i % 2]),
+                    ..Default::default()
+                },
+            )
+        })
+        .collect();
+    StyledText::new(text).with_highlights(highlights)
+}
+
+fn token_color(kind: RegexTokenKind, theme: &gpui_component::theme::Theme) -> Hsla {
+    match kind {
+        RegexTokenKind::Literal => theme.foreground,
+        RegexTokenKind::CharacterType | RegexTokenKind::Class => theme.primary,
+        RegexTokenKind::Anchor | RegexTokenKind::Backreference => theme.warning,
+        RegexTokenKind::Group | RegexTokenKind::Flags => success(),
+        RegexTokenKind::Quantifier | RegexTokenKind::Alternation => theme.danger,
+        RegexTokenKind::Comment => theme.muted_foreground,
+    }
+}
+
+impl Render for RegexTesterView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let current = self.options.engine;
+        let mut engine_bar = SegmentedControl::new("regex-engine");
+        for engine in [RegexEngine::Auto, RegexEngine::Standard, RegexEngine::Fancy] {
+            engine_bar = engine_bar.segment(Segment::new(
+                engine_label(engine),
+                current == engine,
+                cx.listener(move |this, _, _window, cx| this.set_engine(engine, cx)),
+            ));
+        }
+
+        let panel = self.panel;
+        let mut panel_bar = SegmentedControl::new("regex-panel");
+        for value in [
+            RegexPanel::Matches,
+            RegexPanel::Replace,
+            RegexPanel::Explain,
+        ] {
+            panel_bar = panel_bar.segment(Segment::new(
+                value.label(),
+                panel == value,
+                cx.listener(move |this, _, _window, cx| this.set_panel(value, cx)),
+            ));
+        }
+
+        let flags = Flag::ALL.map(|flag| {
+            Button::new(flag.id())
+                .label(flag.label())
+                .small()
+                .map(|button| {
+                    if flag.is_on(&self.options) {
+                        button.primary()
+                    } else {
+                        button.ghost()
+                    }
+                })
+                .on_click(cx.listener(move |this, _, _window, cx| this.toggle_flag(flag, cx)))
+        });
+
+        let text = self.text_state.read(cx).text().to_string();
+        let theme = cx.theme();
+        let preview = div().size_full().overflow_y_scrollbar().child(
+            div()
+                .text_sm()
+                .font_family("monospace")
+                .child(highlighted(text, &self.matches, theme)),
+        );
+
+        let summary = match (self.matches.len(), self.truncated) {
+            (_, true) => format!("First {} matches", self.matches.len()),
+            (0, _) => "No matches".to_string(),
+            (1, _) => "1 match".to_string(),
+            (n, _) => format!("{} matches", n),
+        };
+        let summary = match (self.options.engine, self.engine) {
+            (RegexEngine::Auto, Some(engine)) => {
+                format!(
+                    "{} · {} engine",
+                    summary,
+                    engine_label(engine).to_lowercase()
+                )
+            }
+            _ => summary,
+        };
+
+        let body = match self.panel {
+            RegexPanel::Matches => v_flex()
+                .gap_2()
+                .children(self.matches.iter().enumerate().map(|(i, m)| {
+                    v_flex()
+                        .gap_1()
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(div().text_color(theme.muted_foreground).child(format!(
+                                    "#{} [{}..{}]",
+                                    i + 1,
+                                    m.char_start,
+                                    m.char_end
+                                )))
+                                .child(m.text.clone()),
+                        )
+                        .children(m.groups.iter().map(|group| {
+                            let label = match &group.name {
+                                Some(name) => format!("{} <{}>", group.index, name),
+                                None => group.index.to_string(),
+                            };
+                            h_flex()
+                                .gap_2()
+                                .pl_4()
+                                .child(div().text_color(theme.muted_foreground).child(label))
+                                .child(match &group.text {
+                                    Some(text) => div().child(text.clone()),
+                                    None => {
+                                        div().text_color(theme.muted_foreground).child("(no match)")
+                                    }
+                                })
+                        }))
+                })),
+            RegexPanel::Replace => {
+                v_flex()
+                    .gap_3()
+                    .child(labelled_input(
+                        "Replacement",
+                        &self.replacement_state,
+                        theme,
+                    ))
+                    .child(div().text_color(theme.muted_foreground).child(
+                        match self.replacements {
+                            1 => "1 replacement".to_string(),
+                            n => format!("{} replacements", n),
+                        },
+                    ))
+                    .child(self.replaced.clone())
+            }
+            RegexPanel::Explain => v_flex().gap_1().children(self.tokens.iter().map(|token| {
+                h_flex()
+                    .gap_3()
+                    .pl(px(16. * token.depth as f32))
+                    .child(
+                        div()
+                            .min_w(px(80.))
+                            .text_color(token_color(token.kind, theme))
+                            .child(token.text.clone()),
+                    )
+                    .child(token.description.clone())
+            })),
+        };
+
+        v_flex()
+            .size_full()
+            .gap_4()
+            .child(
+                h_flex()
+                    .items_center()
+                    .justify_between()
+                    .child(h_flex().gap_3().child(engine_bar).children(flags))
+                    .child(
+                        Button::new("copy")
+                            .label("Copy")
+                            .small()
+                            .ghost()
+                            .disabled(self.error.is_some())
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.copy_output(cx);
+                            })),
+                    ),
+            )
+            .child(labelled_input("Pattern", &self.pattern_state, theme))
+            .map(|this| match (&self.diagnostic, &self.error) {
+                (Some(diagnostic), _) => this.child(diagnostic_box(diagnostic, theme)),
+                (None, Some(error)) => this.child(error_box(error.clone(), theme)),
+                (None, None) => this,
+            })
+            .child(
+                h_flex()
+                    .h(px(240.))
+                    .gap_4()
+                    .overflow_hidden()
+                    .child(pane(
+                        "Text",
+                        Input::new(&self.text_state).appearance(false),
+                        cx,
+                    ))
+                    .child(pane("Highlighted", preview, cx)),
+            )
+            .child(
+                v_flex()
+                    .flex_1()
+                    .gap_2()
+                    .overflow_hidden()
+                    .child(
+                        h_flex().gap_3().items_center().child(panel_bar).child(
+                            div()
+                                .text_sm()
+                                .text_color(theme.muted_foreground)
+                                .child(summary),
+                        ),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .rounded_lg()
+                            .border_1()
+                            .border_color(theme.border)
+                            .bg(theme.background)
+                            .p_2()
+                            .overflow_y_scrollbar()
+                            .child(body.w_full().text_sm().font_family("monospace")),
+                    ),
+            )
+    }
+}
@@ -1,4 +1,5 @@
-//! String inspector — char / word / line / byte counts.
+//! String inspector — counts, per-character Unicode details, hidden and
+//! confusable character warnings, and normalization forms.
 
 mod view;
 
@@ -1,10 +1,56 @@
-use dev_utility_core::converter::inspect_string;
+use dev_utility_core::converter::{inspect_string, CharInfo, NormalizationForm, StringInspection};
+use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::input::{Input, InputState};
+use gpui_component::scroll::ScrollableElement;
 use gpui_component::{h_flex, v_flex, ActiveTheme};
+use ui::{row_with_copy, Segment, SegmentedControl};
+
+/// Rows rendered in the character table; the counts above still cover the
+/// whole input.
+const MAX_ROWS: usize = 2000;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InspectorPanel {
+    Characters,
+    Normalization,
+}
+
+impl InspectorPanel {
+    fn label(self) -> &'static str {
+        match self {
+            InspectorPanel::Characters => "Characters",
+            InspectorPanel::Normalization => "Normalization",
+        }
+    }
+}
+
+fn form_label(form: NormalizationForm) -> &'static str {
+    match form {
+        NormalizationForm::Nfc => "NFC",
+        NormalizationForm::Nfd => "NFD",
+        NormalizationForm::Nfkc => "NFKC",
+        NormalizationForm::Nfkd => "NFKD",
+    }
+}
+
+/// Column label and width of the character table.
+const COLUMNS: [(&str, f32); 9] = [
+    ("Char", 48.),
+    ("Code point", 88.),
+    ("Name", 0.),
+    ("Category", 56.),
+    ("Script", 88.),
+    ("Bidi", 48.),
+    ("UTF-8", 104.),
+    ("UTF-16", 88.),
+    ("Width", 48.),
+];
 
 pub struct StringInspectorView {
     input_state: Entity<InputState>,
+    inspection: StringInspection,
+    panel: InspectorPanel,
 }
 
 impl StringInspectorView {
@@ -15,17 +61,87 @@ impl StringInspectorView {
                 .placeholder("The quick brown fox")
         });
 
-        cx.observe(&input_state, |_, _, cx| cx.notify()).detach();
+        cx.observe(&input_state, |this, _, cx| this.process(cx))
+            .detach();
+
+        Self {
+            input_state,
+            inspection: inspect_string(""),
+            panel: InspectorPanel::Characters,
+        }
+    }
+
+    fn process(&mut self, cx: &mut Context<Self>) {
+        let input = self.input_state.read(cx).text().to_string();
+        self.inspection = inspect_string(&input);
+        cx.notify();
+    }
 
-        Self { input_state }
+    fn set_panel(&mut self, panel: InspectorPanel, cx: &mut Context<Self>) {
+        self.panel = panel;
+        cx.notify();
     }
 }
 
+fn table_row(cells: [SharedString; 9], theme: &gpui_component::theme::Theme) -> Div {
+    h_flex()
+        .w_full()
+        .gap_2()
+        .px_2()
+        .py_1()
+        .text_color(theme.foreground)
+        .children(cells.into_iter().zip(COLUMNS).map(|(cell, (_, width))| {
+            div()
+                .when(width > 0., |this| this.w(px(width)).flex_none())
+                .when(width == 0., |this| this.flex_1())
+                .overflow_hidden()
+                .whitespace_nowrap()
+                .child(cell)
+        }))
+}
+
+fn char_row(info: &CharInfo, theme: &gpui_component::theme::Theme) -> Div {
+    // Invisible characters would leave an empty cell.
+    let glyph = if info.invisible || info.character.trim().is_empty() {
+        "·".to_string()
+    } else if info.category.starts_with('M') {
+        format!("◌{}", info.character)
+    } else {
+        info.character.clone()
+    };
+    table_row(
+        [
+            glyph.into(),
+            info.code_point.clone().into(),
+            info.name.clone().unwrap_or_default().into(),
+            info.category.clone().into(),
+            info.script.clone().into(),
+            info.bidi_class.clone().into(),
+            info.utf8.clone().into(),
+            info.utf16.clone().into(),
+            info.width.to_string().into(),
+        ],
+        theme,
+    )
+    // Shade alternate grapheme clusters so their boundaries show.
+    .when(info.grapheme % 2 == 1, |this| this.bg(theme.secondary))
+    .when(info.invisible, |this| this.text_color(theme.danger))
+}
+
 impl Render for StringInspectorView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let panel = self.panel;
+        let mut panel_bar = SegmentedControl::new("inspector-panel");
+        for value in [InspectorPanel::Characters, InspectorPanel::Normalization] {
+            panel_bar = panel_bar.segment(Segment::new(
+                value.label(),
+                panel == value,
+                cx.listener(move |this, _, _window, cx| this.set_panel(value, cx)),
+            ));
+        }
+
         let theme = cx.theme();
-        let input = self.input_state.read(cx).text().to_string();
-        let stats = inspect_string(&input);
+        let stats = &self.inspection;
 
         let stat = |label: &'static str, value: String| {
             v_flex()
@@ -53,6 +169,58 @@ impl Render for StringInspectorView {
                 )
         };
 
+        let body = match self.panel {
+            InspectorPanel::Characters => {
+                let header = table_row(COLUMNS.map(|(label, _)| label.into()), theme)
+                    .font_weight(FontWeight::MEDIUM)
+                    .border_b_1()
+                    .border_color(theme.border);
+                let hidden = stats.characters.len().saturating_sub(MAX_ROWS);
+                v_flex()
+                    .child(header)
+                    .children(
+                        stats
+                            .characters
+                            .iter()
+                            .take(MAX_ROWS)
+                            .map(|info| char_row(info, theme)),
+                    )
+                    .when(hidden > 0, |this| {
+                        this.child(
+                            div()
+                                .px_2()
+                                .py_1()
+                                .text_color(theme.muted_foreground)
+                                .child(format!("{} more characters not shown", hidden)),
+                        )
+                    })
+            }
+            InspectorPanel::Normalization => {
+                v_flex()
+                    .gap_2()
+                    .p_2()
+                    .children(stats.normalizations.iter().map(|n| {
+                        let label = format!(
+                            "{} · {} chars · {} bytes{}",
+                            form_label(n.form),
+                            n.char_count,
+                            n.byte_count,
+                            if n.changed { "" } else { " · unchanged" }
+                        );
+                        let text = n.text.clone();
+                        row_with_copy(
+                            label,
+                            n.text.clone(),
+                            SharedString::from(format!("copy-{}", form_label(n.form))),
+                            move |_, _, cx| {
+                                cx.write_to_clipboard(ClipboardItem::new_string(text.clone()))
+                            },
+                            theme,
+                        )
+                    }))
+            }
+        };
+
         v_flex()
             .size_full()
             .gap_4()
@@ -60,13 +228,15 @@ impl Render for StringInspectorView {
                 h_flex()
                     .gap_3()
                     .child(stat("Characters", stats.char_count.to_string()))
+                    .child(stat("Graphemes", stats.grapheme_count.to_string()))
                     .child(stat("Words", stats.word_count.to_string()))
                     .child(stat("Lines", stats.line_count.to_string()))
-                    .child(stat("Bytes", stats.byte_count.to_string())),
+                    .child(stat("Bytes", stats.byte_count.to_string()))
+                    .child(stat("UTF-16 units", stats.utf16_count.to_string())),
             )
             .child(
                 v_flex()
-                    .flex_1()
+                    .h(px(160.))
                     .gap_2()
                     .overflow_hidden()
                     .child(
@@ -88,5 +258,42 @@ impl Render for StringInspectorView {
                             .child(Input::new(&self.input_state).appearance(false)),
                     ),
             )
+            .when(!stats.warnings.is_empty(), |this| {
+                this.child(
+                    v_flex()
+                        .gap_1()
+                        .px_3()
+                        .py_2()
+                        .rounded_lg()
+                        .bg(theme.danger.opacity(0.1))
+                        .border_1()
+                        .border_color(theme.danger)
+                        .text_sm()
+                        .text_color(theme.danger)
+                        .children(
+                            stats
+                                .warnings
+                                .iter()
+                                .map(|warning| div().child(format!("• {}", warning.message))),
+                        ),
+                )
+            })
+            .child(
+                v_flex()
+                    .flex_1()
+                    .gap_2()
+                    .overflow_hidden()
+                    .child(panel_bar)
+                    .child(
+                        div()
+                            .flex_1()
+                            .rounded_lg()
+                            .border_1()
+                            .border_color(theme.border)
+                            .bg(theme.background)
+                            .overflow_y_scrollbar()
+                            .child(body.w_full().text_sm().font_family("monospace")),
+                    ),
+            )
     }
 }
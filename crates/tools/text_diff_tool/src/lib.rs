@@ -0,0 +1,11 @@
+//! Side-by-side text diff.
+//!
+//! Both inputs are compared with `dev_utility_core::converter::diff_text`
+//! and shown as aligned rows, with changed lines highlighted whole, by
+//! word or by character. The unified view shows the patch that Copy puts
+//! on the clipboard. Apply patch mode runs the right pane, a unified
+//! diff, against the left with `apply_text_patch`.
+
+mod view;
+
+pub use view::{DiffLayout, DiffMode, TextDiffView};
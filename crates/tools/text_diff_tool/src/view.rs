@@ -0,0 +1,465 @@
+use dev_utility_core::converter::{
+    apply_text_patch, diff_text, DiffGranularity, DiffHunk, DiffRow, DiffRowKind, DiffSpan,
+    DiffTag, TextDiffOptions,
+};
+use dev_utility_core::error::Diagnostic;
+use gpui::prelude::FluentBuilder;
+use gpui::*;
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputState};
+use gpui_component::scroll::ScrollableElement;
+use gpui_component::{h_flex, v_flex, ActiveTheme, Disableable, Sizable};
+use ui::{diagnostic_box, error_box, success, Segment, SegmentedControl};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DiffMode {
+    Compare,
+    // Apply the unified diff in the right pane to the left pane
+    ApplyPatch,
+}
+
+impl DiffMode {
+    fn label(self) -> &'static str {
+        match self {
+            DiffMode::Compare => "Compare",
+            DiffMode::ApplyPatch => "Apply patch",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DiffLayout {
+    SideBySide,
+    Unified,
+}
+
+impl DiffLayout {
+    fn label(self) -> &'static str {
+        match self {
+            DiffLayout::SideBySide => "Side by side",
+            DiffLayout::Unified => "Unified",
+        }
+    }
+}
+
+fn granularity_label(granularity: DiffGranularity) -> &'static str {
+    match granularity {
+        DiffGranularity::Line => "Line",
+        DiffGranularity::Word => "Word",
+        DiffGranularity::Char => "Char",
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Toggle {
+    Whitespace,
+    Case,
+    LineEnding,
+}
+
+pub struct TextDiffView {
+    left_state: Entity<InputState>,
+    right_state: Entity<InputState>,
+    mode: DiffMode,
+    options: TextDiffOptions,
+    layout: DiffLayout,
+    hunks: Vec<DiffHunk>,
+    unified: String,
+    additions: usize,
+    deletions: usize,
+    // Left pane with the patch applied, in `DiffMode::ApplyPatch`
+    patched: String,
+    error: Option<String>,
+    diagnostic: Option<Diagnostic>,
+}
+
+impl TextDiffView {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let left_state = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .placeholder("Original text...")
+        });
+        let right_state = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .placeholder("Changed text...")
+        });
+
+        for state in [&left_state, &right_state] {
+            cx.observe(state, |this, _, cx| this.process(cx)).detach();
+        }
+
+        Self {
+            left_state,
+            right_state,
+            mode: DiffMode::Compare,
+            options: TextDiffOptions::default(),
+            layout: DiffLayout::SideBySide,
+            hunks: Vec::new(),
+            unified: String::new(),
+            additions: 0,
+            deletions: 0,
+            patched: String::new(),
+            error: None,
+            diagnostic: None,
+        }
+    }
+
+    fn process(&mut self, cx: &mut Context<Self>) {
+        self.error = None;
+        self.diagnostic = None;
+        self.hunks.clear();
+        self.unified.clear();
+        self.additions = 0;
+        self.deletions = 0;
+        self.patched.clear();
+        let left = self.left_state.read(cx).text().to_string();
+        let right = self.right_state.read(cx).text().to_string();
+
+        if left.is_empty() && right.is_empty() {
+            cx.notify();
+            return;
+        }
+
+        let result = match self.mode {
+            DiffMode::Compare => {
+                diff_text(&left, &right, Some(self.options.clone())).map(|result| {
+                    self.hunks = result.hunks;
+                    self.unified = result.unified;
+                    self.additions = result.additions;
+                    self.deletions = result.deletions;
+                })
+            }
+            DiffMode::ApplyPatch => {
+                apply_text_patch(&left, &right).map(|patched| self.patched = patched)
+            }
+        };
+        if let Err(e) = result {
+            self.diagnostic = e.diagnostic().cloned();
+            self.error = Some(e.to_string());
+        }
+        cx.notify();
+    }
+
+    fn set_mode(&mut self, mode: DiffMode, cx: &mut Context<Self>) {
+        self.mode = mode;
+        self.process(cx);
+    }
+
+    fn set_granularity(&mut self, granularity: DiffGranularity, cx: &mut Context<Self>) {
+        self.options.granularity = granularity;
+        self.process(cx);
+    }
+
+    fn set_layout(&mut self, layout: DiffLayout, cx: &mut Context<Self>) {
+        self.layout = layout;
+        cx.notify();
+    }
+
+    fn toggle(&mut self, toggle: Toggle, cx: &mut Context<Self>) {
+        let flag = match toggle {
+            Toggle::Whitespace => &mut self.options.ignore_whitespace,
+            Toggle::Case => &mut self.options.ignore_case,
+            Toggle::LineEnding => &mut self.options.ignore_line_ending,
+        };
+        *flag = !*flag;
+        self.process(cx);
+    }
+
+    fn is_on(&self, toggle: Toggle) -> bool {
+        match toggle {
+            Toggle::Whitespace => self.options.ignore_whitespace,
+            Toggle::Case => self.options.ignore_case,
+            Toggle::LineEnding => self.options.ignore_line_ending,
+        }
+    }
+
+    /// The patch when comparing, the patched text when applying.
+    fn output(&self) -> &str {
+        match self.mode {
+            DiffMode::Compare => &self.unified,
+            DiffMode::ApplyPatch => &self.patched,
+        }
+    }
+
+    fn copy_output(&self, cx: &mut Context<Self>) {
+        if !self.output().is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(self.output().to_string()));
+        }
+    }
+}
+
+fn pane(
+    label: &'static str,
+    state: &Entity<InputState>,
+    theme: &gpui_component::theme::Theme,
+) -> Div {
+    v_flex()
+        .flex_1()
+        .gap_2()
+        .overflow_hidden()
+        .child(
+            div()
+                .text_sm()
+                .font_weight(FontWeight::MEDIUM)
+                .text_color(theme.muted_foreground)
+                .child(label),
+        )
+        .child(
+            div()
+                .flex_1()
+                .rounded_lg()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.background)
+                .p_2()
+                .overflow_hidden()
+                .child(Input::new(state).appearance(false)),
+        )
+}
+
+/// One side of a row: the line number and the text, with changed spans
+/// highlighted when only part of the line changed.
+fn cell(
+    line: Option<usize>,
+    spans: &[DiffSpan],
+    changed: Option<Hsla>,
+    theme: &gpui_component::theme::Theme,
+) -> Div {
+    let mut text = String::new();
+    let mut highlights = Vec::new();
+    for span in spans {
+        let start = text.len();
+        text.push_str(&span.text);
+        if let (Some(color), DiffTag::Insert | DiffTag::Delete) = (changed, span.tag) {
+            if spans.len() > 1 {
+                highlights.push((
+                    start..text.len(),
+                    HighlightStyle {
+                        background_color: Some(color.opacity(0.35)),
+                        ..Default::default()
+                    },
+                ));
+            }
+        }
+    }
+
+    h_flex()
+        .flex_1()
+        .min_h(px(20.))
+        .gap_2()
+        .px_2()
+        .overflow_hidden()
+        .when_some(changed, |this, color| this.bg(color.opacity(0.1)))
+        .child(
+            div()
+                .w(px(36.))
+                .flex_none()
+                .text_right()
+                .text_color(theme.muted_foreground)
+                .child(line.map(|n| n.to_string()).unwrap_or_default()),
+        )
+        .child(
+            div()
+                .flex_1()
+                .whitespace_nowrap()
+                .child(StyledText::new(text).with_highlights(highlights)),
+        )
+}
+
+fn row(row: &DiffRow, theme: &gpui_component::theme::Theme) -> Div {
+    let (left, right) = match row.kind {
+        DiffRowKind::Equal => (None, None),
+        DiffRowKind::Added => (None, Some(success())),
+        DiffRowKind::Removed => (Some(theme.danger), None),
+        DiffRowKind::Changed => (Some(theme.danger), Some(success())),
+    };
+    h_flex()
+        .w_full()
+        .child(cell(row.left_line, &row.left, left, theme))
+        .child(div().w(px(1.)).h_full().bg(theme.border))
+        .child(cell(row.right_line, &row.right, right, theme))
+}
+
+impl Render for TextDiffView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mode = self.mode;
+        let mut mode_bar = SegmentedControl::new("diff-mode");
+        for value in [DiffMode::Compare, DiffMode::ApplyPatch] {
+            mode_bar = mode_bar.segment(Segment::new(
+                value.label(),
+                mode == value,
+                cx.listener(move |this, _, _window, cx| this.set_mode(value, cx)),
+            ));
+        }
+
+        let current = self.options.granularity;
+        let mut granularity_bar = SegmentedControl::new("diff-granularity");
+        for granularity in [
+            DiffGranularity::Line,
+            DiffGranularity::Word,
+            DiffGranularity::Char,
+        ] {
+            granularity_bar = granularity_bar.segment(Segment::new(
+                granularity_label(granularity),
+                current == granularity,
+                cx.listener(move |this, _, _window, cx| this.set_granularity(granularity, cx)),
+            ));
+        }
+
+        let layout = self.layout;
+        let mut layout_bar = SegmentedControl::new("diff-layout");
+        for value in [DiffLayout::SideBySide, DiffLayout::Unified] {
+            layout_bar = layout_bar.segment(Segment::new(
+                value.label(),
+                layout == value,
+                cx.listener(move |this, _, _window, cx| this.set_layout(value, cx)),
+            ));
+        }
+
+        let toggles = [
+            ("ignore-whitespace", "Ignore whitespace", Toggle::Whitespace),
+            ("ignore-case", "Ignore case", Toggle::Case),
+            (
+                "ignore-line-ending",
+                "Ignore line endings",
+                Toggle::LineEnding,
+            ),
+        ]
+        .map(|(id, label, toggle)| {
+            Button::new(id)
+                .label(label)
+                .small()
+                .map(|button| {
+                    if self.is_on(toggle) {
+                        button.primary()
+                    } else {
+                        button.ghost()
+                    }
+                })
+                .on_click(cx.listener(move |this, _, _window, cx| this.toggle(toggle, cx)))
+        });
+
+        let theme = cx.theme();
+        let summary = match mode {
+            DiffMode::ApplyPatch => "Patched text".to_string(),
+            DiffMode::Compare if self.hunks.is_empty() => "No differences".to_string(),
+            DiffMode::Compare => format!("+{} −{}", self.additions, self.deletions),
+        };
+
+        let body = match (mode, self.layout) {
+            (DiffMode::ApplyPatch, _) => v_flex().children(
+                self.patched
+                    .lines()
+                    .map(|line| div().whitespace_nowrap().child(line.to_string())),
+            ),
+            (DiffMode::Compare, DiffLayout::SideBySide) => {
+                v_flex().children(self.hunks.iter().map(|hunk| {
+                    v_flex()
+                        .child(
+                            div()
+                                .px_2()
+                                .py_1()
+                                .bg(theme.muted)
+                                .text_color(theme.muted_foreground)
+                                .child(hunk.header.clone()),
+                        )
+                        .children(hunk.rows.iter().map(|r| row(r, theme)))
+                }))
+            }
+            (DiffMode::Compare, DiffLayout::Unified) => {
+                v_flex().children(self.unified.lines().map(|line| {
+                    let color = match line.as_bytes().first() {
+                        Some(b'+') if !line.starts_with("+++") => success(),
+                        Some(b'-') if !line.starts_with("---") => theme.danger,
+                        Some(b'@') => theme.muted_foreground,
+                        _ => theme.foreground,
+                    };
+                    div()
+                        .whitespace_nowrap()
+                        .text_color(color)
+                        .child(line.to_string())
+                }))
+            }
+        };
+
+        v_flex()
+            .size_full()
+            .gap_4()
+            .child(
+                h_flex()
+                    .items_center()
+                    .justify_between()
+                    .child(h_flex().gap_3().child(mode_bar).when(
+                        mode == DiffMode::Compare,
+                        |this| {
+                            this.child(granularity_bar)
+                                .child(layout_bar)
+                                .children(toggles)
+                        },
+                    ))
+                    .child(
+                        Button::new("copy")
+                            .label(match mode {
+                                DiffMode::Compare => "Copy patch",
+                                DiffMode::ApplyPatch => "Copy result",
+                            })
+                            .small()
+                            .ghost()
+                            .disabled(self.output().is_empty())
+                            .on_click(cx.listener(|this, _, _window, cx| {
+                                this.copy_output(cx);
+                            })),
+                    ),
+            )
+            .child(
+                div()
+                    .h(px(220.))
+                    .flex()
+                    .flex_row()
+                    .gap_4()
+                    .overflow_hidden()
+                    .child(pane("Left", &self.left_state, theme))
+                    .child(pane(
+                        match mode {
+                            DiffMode::Compare => "Right",
+                            DiffMode::ApplyPatch => "Patch",
+                        },
+                        &self.right_state,
+                        theme,
+                    )),
+            )
+            .map(|this| match (&self.diagnostic, &self.error) {
+                (Some(diagnostic), _) => this.child(diagnostic_box(diagnostic, theme)),
+                (None, Some(error)) => this.child(error_box(error.clone(), theme)),
+                (None, None) => this,
+            })
+            .when(self.error.is_none(), |this| {
+                this.child(
+                    v_flex()
+                        .flex_1()
+                        .gap_2()
+                        .overflow_hidden()
+                        .child(
+                            div()
+                                .text_sm()
+                                .font_weight(FontWeight::MEDIUM)
+                                .text_color(theme.muted_foreground)
+                                .child(summary),
+                        )
+                        .child(
+                            div()
+                                .flex_1()
+                                .rounded_lg()
+                                .border_1()
+                                .border_color(theme.border)
+                                .bg(theme.background)
+                                .py_1()
+                                .overflow_y_scrollbar()
+                                .child(body.w_full().text_sm().font_family("monospace")),
+                        ),
+                )
+            })
+    }
+}
@@ -2,12 +2,17 @@
 //!
 //! Three modes: generate a new secret + otpauth URI, live-tick a code
 //! from an existing secret, and validate a user-entered code against
-//! the current time window.
+//! the current time window. Validate can also lay out every code in a
+//! ±N-minute timeline to diagnose clock drift. A fourth mode keeps
+//! accounts in a password-encrypted vault file with live codes,
+//! Aegis / andOTP / otpauth import and export, and auto-lock.
 //!
 //! The live ticker drives `cx.notify()` once per second via the
 //! background executor's `timer`, then the render pass re-computes the
 //! current code synchronously from the secret.
 
+mod vault;
 mod view;
 
+pub use vault::VaultView;
 pub use view::{TotpMode, TotpView};
@@ -0,0 +1,469 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use dev_utility_core::cryptography::oath::{
+    import_otp_accounts, OtpAccountCode, OtpExportFormat, OtpKind, VaultSession,
+};
+use gpui::prelude::FluentBuilder;
+use gpui::*;
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::input::{Input, InputState};
+use gpui_component::scroll::ScrollableElement;
+use gpui_component::{h_flex, v_flex, ActiveTheme, Disableable, Sizable};
+use ui::{error_box, labelled_input, Segment, SegmentedControl};
+
+/// Lock the vault after this long without interaction.
+const AUTO_LOCK_AFTER: Duration = Duration::from_secs(5 * 60);
+
+/// Encrypted account store: unlock a vault file, list live codes, import
+/// from other authenticators and export back out.
+pub struct VaultView {
+    path_input: Entity<InputState>,
+    password_input: Entity<InputState>,
+    import_input: Entity<InputState>,
+    session: Option<VaultSession>,
+    codes: Vec<OtpAccountCode>,
+    export_format: OtpExportFormat,
+    error: Option<String>,
+    notice: Option<String>,
+    unlocking: bool,
+    _unlock_task: Option<Task<()>>,
+    // Refreshes codes and enforces auto-lock once per second
+    _tick_task: Option<Task<()>>,
+}
+
+impl VaultView {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let default_path = std::env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".devutility-otp-vault.json"))
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        let path_input = cx.new(|cx| {
+            let mut state = InputState::new(window, cx).placeholder("Vault file path");
+            state.set_value(default_path, window, cx);
+            state
+        });
+        let password_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .masked(true)
+                .placeholder("Master password")
+        });
+        let import_input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .placeholder("otpauth:// URIs, Aegis or andOTP JSON...")
+        });
+
+        let tick_task = cx.spawn(async move |this, cx| loop {
+            cx.background_executor()
+                .timer(Duration::from_secs(1))
+                .await;
+            if this.update(cx, |this, cx| this.tick(cx)).is_err() {
+                break;
+            }
+        });
+
+        Self {
+            path_input,
+            password_input,
+            import_input,
+            session: None,
+            codes: Vec::new(),
+            export_format: OtpExportFormat::OtpauthUri,
+            error: None,
+            notice: None,
+            unlocking: false,
+            _unlock_task: None,
+            _tick_task: Some(tick_task),
+        }
+    }
+
+    fn tick(&mut self, cx: &mut Context<Self>) {
+        let Some(session) = &self.session else {
+            return;
+        };
+        if session.should_lock(AUTO_LOCK_AFTER) {
+            self.lock(cx);
+            self.notice = Some("Locked after inactivity".to_string());
+        } else {
+            self.codes = session.codes();
+        }
+        cx.notify();
+    }
+
+    fn open(&mut self, create: bool, window: &mut Window, cx: &mut Context<Self>) {
+        if self.unlocking {
+            return;
+        }
+        let path = self.path_input.read(cx).text().trim().to_string();
+        let password = self.password_input.read(cx).text().to_string();
+        if path.is_empty() || password.is_empty() {
+            self.error = Some("Both path and password are required".to_string());
+            cx.notify();
+            return;
+        }
+        self.password_input.update(cx, |state, cx| {
+            state.set_value("", window, cx);
+        });
+        self.unlocking = true;
+        self.error = None;
+        self.notice = None;
+        cx.notify();
+
+        // Argon2 is deliberately slow; keep it off the UI thread.
+        let task = cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move {
+                    if create {
+                        VaultSession::create(path, &password)
+                    } else {
+                        VaultSession::unlock(path, &password)
+                    }
+                })
+                .await;
+            let _ = this.update(cx, |this, cx| {
+                this.unlocking = false;
+                match result {
+                    Ok(session) => {
+                        this.codes = session.codes();
+                        this.session = Some(session);
+                    }
+                    Err(e) => this.error = Some(e.to_string()),
+                }
+                cx.notify();
+            });
+        });
+        self._unlock_task = Some(task);
+    }
+
+    fn lock(&mut self, cx: &mut Context<Self>) {
+        self.session = None;
+        self.codes.clear();
+        self.error = None;
+        self.notice = None;
+        cx.notify();
+    }
+
+    fn import(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        let data = self.import_input.read(cx).text().to_string();
+        let result = import_otp_accounts(data).and_then(|v| session.add_accounts(v.accounts));
+        match result {
+            Ok(added) => {
+                self.notice = Some(format!("Imported {} account(s)", added));
+                self.error = None;
+                self.import_input.update(cx, |state, cx| {
+                    state.set_value("", window, cx);
+                });
+                self.codes = session.codes();
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        cx.notify();
+    }
+
+    fn next_hotp(&mut self, id: String, cx: &mut Context<Self>) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        match session.next_hotp_code(&id) {
+            Ok(_) => self.codes = session.codes(),
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        cx.notify();
+    }
+
+    fn remove(&mut self, id: String, cx: &mut Context<Self>) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        match session.remove_account(&id) {
+            Ok(()) => self.codes = session.codes(),
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        cx.notify();
+    }
+
+    fn set_export_format(&mut self, format: OtpExportFormat, cx: &mut Context<Self>) {
+        self.export_format = format;
+        if let Some(session) = self.session.as_mut() {
+            session.touch();
+        }
+        cx.notify();
+    }
+
+    fn export(&mut self, cx: &mut Context<Self>) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        match session.export(self.export_format) {
+            Ok(data) => {
+                cx.write_to_clipboard(ClipboardItem::new_string(data));
+                self.notice = Some("Export copied to clipboard".to_string());
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        cx.notify();
+    }
+
+    fn copy_code(&mut self, code: String, cx: &mut Context<Self>) {
+        if let Some(session) = self.session.as_mut() {
+            session.touch();
+        }
+        if !code.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(code));
+        }
+    }
+}
+
+impl Render for VaultView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+        let body: AnyElement = if self.session.is_some() {
+            self.render_unlocked(cx).into_any_element()
+        } else {
+            self.render_locked(cx).into_any_element()
+        };
+
+        v_flex()
+            .flex_1()
+            .gap_4()
+            .overflow_hidden()
+            .child(body)
+            .when_some(self.error.clone(), |this, e| {
+                this.child(error_box(e, theme))
+            })
+            .when_some(self.notice.clone(), |this, notice| {
+                this.child(
+                    div()
+                        .text_sm()
+                        .text_color(theme.muted_foreground)
+                        .child(notice),
+                )
+            })
+    }
+}
+
+impl VaultView {
+    fn render_locked(&self, cx: &Context<Self>) -> Div {
+        let theme = cx.theme();
+        v_flex()
+            .gap_4()
+            .child(labelled_input("Vault file", &self.path_input, theme))
+            .child(labelled_input("Master password", &self.password_input, theme))
+            .child(
+                h_flex()
+                    .gap_3()
+                    .child(
+                        Button::new("vault-unlock")
+                            .label("Unlock")
+                            .small()
+                            .primary()
+                            .disabled(self.unlocking)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.open(false, window, cx)
+                            })),
+                    )
+                    .child(
+                        Button::new("vault-create")
+                            .label("Create new vault")
+                            .small()
+                            .ghost()
+                            .disabled(self.unlocking)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.open(true, window, cx)
+                            })),
+                    ),
+            )
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(theme.muted_foreground)
+                    .child(format!(
+                        "Argon2id + XChaCha20-Poly1305. Locks after {} minutes of inactivity.",
+                        AUTO_LOCK_AFTER.as_secs() / 60
+                    )),
+            )
+    }
+
+    fn render_unlocked(&self, cx: &Context<Self>) -> Div {
+        let theme = cx.theme();
+        let Some(session) = &self.session else {
+            return div();
+        };
+        let format = self.export_format;
+
+        let mut format_bar = SegmentedControl::new("vault-export-format");
+        for (label, value) in [
+            ("otpauth URIs", OtpExportFormat::OtpauthUri),
+            ("Aegis", OtpExportFormat::Aegis),
+            ("andOTP", OtpExportFormat::AndOtp),
+        ] {
+            format_bar = format_bar.segment(Segment::new(
+                label,
+                format == value,
+                cx.listener(move |this, _, _window, cx| this.set_export_format(value, cx)),
+            ));
+        }
+
+        let rows = session
+            .accounts()
+            .iter()
+            .zip(self.codes.iter())
+            .enumerate()
+            .map(|(ix, (account, code))| {
+                let copy_value = code.code.clone();
+                let next_id = account.id.clone();
+                let remove_id = account.id.clone();
+                let detail = match (&code.error, account.kind) {
+                    (Some(e), _) => e.clone(),
+                    (None, OtpKind::Totp) => {
+                        format!("{}s left", code.time_remaining.unwrap_or_default())
+                    }
+                    (None, OtpKind::Hotp) => format!("counter {}", account.counter),
+                };
+                h_flex()
+                    .items_center()
+                    .gap_3()
+                    .px_3()
+                    .py_2()
+                    .border_b_1()
+                    .border_color(theme.border)
+                    .child(
+                        v_flex()
+                            .flex_1()
+                            .child(div().text_sm().child(account.display_name()))
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(if code.error.is_some() {
+                                        theme.danger
+                                    } else {
+                                        theme.muted_foreground
+                                    })
+                                    .child(detail),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .text_xl()
+                            .font_family("monospace")
+                            .font_weight(FontWeight::BOLD)
+                            .child(crate::view::format_code(&code.code)),
+                    )
+                    .child(
+                        Button::new(("vault-copy", ix))
+                            .label("Copy")
+                            .small()
+                            .ghost()
+                            .disabled(code.code.is_empty())
+                            .on_click(cx.listener(move |this, _, _window, cx| {
+                                this.copy_code(copy_value.clone(), cx)
+                            })),
+                    )
+                    .when(account.kind == OtpKind::Hotp, |row| {
+                        row.child(
+                            Button::new(("vault-next", ix))
+                                .label("Next")
+                                .small()
+                                .ghost()
+                                .on_click(cx.listener(move |this, _, _window, cx| {
+                                    this.next_hotp(next_id.clone(), cx)
+                                })),
+                        )
+                    })
+                    .child(
+                        Button::new(("vault-remove", ix))
+                            .label("Remove")
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(move |this, _, _window, cx| {
+                                this.remove(remove_id.clone(), cx)
+                            })),
+                    )
+            });
+
+        v_flex()
+            .flex_1()
+            .gap_4()
+            .overflow_hidden()
+            .child(
+                h_flex()
+                    .items_center()
+                    .gap_3()
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_sm()
+                            .text_color(theme.muted_foreground)
+                            .child(session.path().display().to_string()),
+                    )
+                    .child(
+                        Button::new("vault-lock")
+                            .label("Lock")
+                            .small()
+                            .on_click(cx.listener(|this, _, _window, cx| this.lock(cx))),
+                    ),
+            )
+            .child(
+                v_flex()
+                    .flex_1()
+                    .rounded_lg()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.background)
+                    .overflow_y_scrollbar()
+                    .children(rows)
+                    .when(self.codes.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .p_3()
+                                .text_sm()
+                                .text_color(theme.muted_foreground)
+                                .child("No accounts yet — import some below."),
+                        )
+                    }),
+            )
+            .child(
+                div()
+                    .h(px(96.))
+                    .rounded_lg()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.background)
+                    .p_2()
+                    .overflow_hidden()
+                    .child(Input::new(&self.import_input).appearance(false)),
+            )
+            .child(
+                h_flex()
+                    .items_center()
+                    .gap_3()
+                    .child(
+                        Button::new("vault-import")
+                            .label("Import")
+                            .small()
+                            .primary()
+                            .on_click(cx.listener(|this, _, window, cx| this.import(window, cx))),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.muted_foreground)
+                            .child("Export as"),
+                    )
+                    .child(format_bar)
+                    .child(
+                        Button::new("vault-export")
+                            .label("Copy export")
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(|this, _, _window, cx| this.export(cx))),
+                    ),
+            )
+    }
+}
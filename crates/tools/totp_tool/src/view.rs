@@ -1,33 +1,45 @@
+use std::sync::Arc;
 use std::time::Duration;
 
+use chrono::{TimeZone, Utc};
 use dev_utility_core::cryptography::oath::{
-    generate_totp_code, generate_totp_secret, validate_totp_code, HashAlgorithm,
-    TotpSecretResult, TotpValidationResult,
+    diagnose_totp_drift, generate_otp_code, generate_totp_secret, validate_otp_code,
+    HashAlgorithm, OtpParameters, OtpVariant, SyncStatus, TotpDriftReport, TotpSecretResult,
+    TotpValidationResult,
 };
+use dev_utility_core::generator::{QrCodeOptions, QrErrorCorrection};
 use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::button::{Button, ButtonVariants};
 use gpui_component::input::InputState;
+use gpui_component::scroll::ScrollableElement;
 use gpui_component::{h_flex, v_flex, ActiveTheme, Disableable, Sizable};
 use ui::{error_box, labelled_input, row_with_copy, Segment, SegmentedControl};
 
+use crate::vault::VaultView;
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum TotpMode {
     Secret,
     Live,
     Validate,
+    Vault,
 }
 
 pub struct TotpView {
     mode: TotpMode,
     // Shared settings
+    variant: OtpVariant,
     algorithm: HashAlgorithm,
     digits: u32,
     period: u32,
+    pin_input: Entity<InputState>,
     // Secret builder
     issuer_input: Entity<InputState>,
     account_input: Entity<InputState>,
+    qr_error_correction: QrErrorCorrection,
     secret_result: Option<TotpSecretResult>,
+    secret_qr: Option<Arc<Image>>,
     secret_error: Option<String>,
     building: bool,
     _build_task: Option<Task<()>>,
@@ -44,6 +56,14 @@ pub struct TotpView {
     validation_error: Option<String>,
     validating: bool,
     _validate_task: Option<Task<()>>,
+    // Drift timeline (shares the validator's inputs)
+    drift_range: u32,
+    drift_report: Option<TotpDriftReport>,
+    drift_error: Option<String>,
+    diagnosing: bool,
+    _drift_task: Option<Task<()>>,
+    // Encrypted account store
+    vault: Entity<VaultView>,
     // Ticker task — dropped when view drops, loop exits on update() error
     _tick_task: Option<Task<()>>,
 }
@@ -65,13 +85,19 @@ impl TotpView {
         let validate_code_input = cx.new(|cx| {
             InputState::new(window, cx).placeholder("6-digit code...")
         });
+        let pin_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("PIN (Yandex / mOTP)")
+        });
+        let vault = cx.new(|cx| VaultView::new(window, cx));
 
-        cx.observe(&live_secret_input, |this, _, cx| {
-            if matches!(this.mode, TotpMode::Live) {
-                this.refresh_live_code(cx);
-            }
-        })
-        .detach();
+        for input in [&live_secret_input, &pin_input] {
+            cx.observe(input, |this, _, cx| {
+                if matches!(this.mode, TotpMode::Live) {
+                    this.refresh_live_code(cx);
+                }
+            })
+            .detach();
+        }
 
         let tick_task = cx.spawn(async move |this, cx| {
             loop {
@@ -93,12 +119,16 @@ impl TotpView {
 
         Self {
             mode: TotpMode::Secret,
+            variant: OtpVariant::Rfc6238,
             algorithm: HashAlgorithm::SHA1,
             digits: 6,
             period: 30,
+            pin_input,
             issuer_input,
             account_input,
+            qr_error_correction: QrErrorCorrection::Medium,
             secret_result: None,
+            secret_qr: None,
             secret_error: None,
             building: false,
             _build_task: None,
@@ -113,13 +143,37 @@ impl TotpView {
             validation_error: None,
             validating: false,
             _validate_task: None,
+            drift_range: 5,
+            drift_report: None,
+            drift_error: None,
+            diagnosing: false,
+            _drift_task: None,
+            vault,
             _tick_task: Some(tick_task),
         }
     }
 
+    /// Code parameters for the selected variant. Only RFC 6238 honours the
+    /// algorithm / digits / period settings; the others have fixed values.
+    fn otp_params(&self, cx: &Context<Self>) -> OtpParameters {
+        match self.variant {
+            OtpVariant::Rfc6238 => OtpParameters {
+                algorithm: self.algorithm.clone(),
+                digits: self.digits,
+                period: self.period,
+                ..Default::default()
+            },
+            variant => OtpParameters {
+                pin: Some(self.pin_input.read(cx).text().to_string()),
+                ..OtpParameters::preset(variant)
+            },
+        }
+    }
+
     fn refresh_live_code(&mut self, cx: &mut Context<Self>) {
         let secret = self.live_secret_input.read(cx).text().to_string();
-        let digits = self.digits;
+        let params = self.otp_params(cx);
+        let digits = params.digits;
         if secret.trim().is_empty() {
             self.live_cached_code = placeholder_dashes(digits);
             self.live_cached_remaining = 0;
@@ -127,18 +181,11 @@ impl TotpView {
             cx.notify();
             return;
         }
-        let algorithm = self.algorithm.clone();
-        let period = self.period;
         let secret_trimmed = secret.trim().to_string();
         let task = cx.spawn(async move |this, cx| {
             let result = cx
                 .background_executor()
-                .spawn(generate_totp_code(
-                    secret_trimmed,
-                    algorithm,
-                    digits,
-                    period,
-                ))
+                .spawn(async move { generate_otp_code(secret_trimmed, params) })
                 .await;
             let _ = this.update(cx, |this, cx| {
                 match result {
@@ -148,7 +195,7 @@ impl TotpView {
                         this.live_cached_error = None;
                     }
                     Err(e) => {
-                        this.live_cached_code = placeholder_dashes(this.digits);
+                        this.live_cached_code = placeholder_dashes(digits);
                         this.live_cached_remaining = 0;
                         this.live_cached_error = Some(e.to_string());
                     }
@@ -167,6 +214,13 @@ impl TotpView {
         }
     }
 
+    fn set_variant(&mut self, variant: OtpVariant, cx: &mut Context<Self>) {
+        self.variant = variant;
+        self.live_cached_remaining = 0;
+        cx.notify();
+        self.refresh_live_code(cx);
+    }
+
     fn set_algorithm(&mut self, alg: HashAlgorithm, cx: &mut Context<Self>) {
         self.algorithm = alg;
         cx.notify();
@@ -186,6 +240,11 @@ impl TotpView {
         self.refresh_live_code(cx);
     }
 
+    fn set_qr_error_correction(&mut self, level: QrErrorCorrection, cx: &mut Context<Self>) {
+        self.qr_error_correction = level;
+        cx.notify();
+    }
+
     fn build_secret(&mut self, cx: &mut Context<Self>) {
         if self.building {
             return;
@@ -201,6 +260,11 @@ impl TotpView {
         let algorithm = self.algorithm.clone();
         let digits = self.digits;
         let period = self.period;
+        let qr = QrCodeOptions {
+            error_correction: self.qr_error_correction,
+            size: QR_SIZE,
+            ..Default::default()
+        };
         self.building = true;
         let task = cx.spawn(async move |this, cx| {
             let result = cx
@@ -213,18 +277,24 @@ impl TotpView {
                     period,
                     None,
                     true,
+                    Some(qr),
                 ))
                 .await;
             let _ = this.update(cx, |this, cx| {
                 this.building = false;
                 match result {
                     Ok(r) => {
+                        this.secret_qr = Some(Arc::new(Image::from_bytes(
+                            ImageFormat::Svg,
+                            r.qr_code.svg.clone().into_bytes(),
+                        )));
                         this.secret_result = Some(r);
                         this.secret_error = None;
                     }
                     Err(e) => {
                         this.secret_error = Some(e.to_string());
                         this.secret_result = None;
+                        this.secret_qr = None;
                     }
                 }
                 cx.notify();
@@ -245,21 +315,12 @@ impl TotpView {
             cx.notify();
             return;
         }
-        let algorithm = self.algorithm.clone();
-        let digits = self.digits;
-        let period = self.period;
+        let params = self.otp_params(cx);
         self.validating = true;
         let task = cx.spawn(async move |this, cx| {
             let result = cx
                 .background_executor()
-                .spawn(validate_totp_code(
-                    secret,
-                    code,
-                    algorithm,
-                    digits,
-                    period,
-                    1,
-                ))
+                .spawn(async move { validate_otp_code(secret, code, params, 1) })
                 .await;
             let _ = this.update(cx, |this, cx| {
                 this.validating = false;
@@ -279,6 +340,49 @@ impl TotpView {
         self._validate_task = Some(task);
     }
 
+    fn set_drift_range(&mut self, minutes: u32, cx: &mut Context<Self>) {
+        self.drift_range = minutes;
+        cx.notify();
+    }
+
+    fn run_diagnosis(&mut self, cx: &mut Context<Self>) {
+        if self.diagnosing {
+            return;
+        }
+        let secret = self.validate_secret_input.read(cx).text().trim().to_string();
+        let code = self.validate_code_input.read(cx).text().to_string();
+        if secret.is_empty() || code.trim().is_empty() {
+            self.drift_error = Some("Both secret and code are required".to_string());
+            self.drift_report = None;
+            cx.notify();
+            return;
+        }
+        let params = self.otp_params(cx);
+        let range = self.drift_range;
+        self.diagnosing = true;
+        let task = cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { diagnose_totp_drift(secret, code, params, range) })
+                .await;
+            let _ = this.update(cx, |this, cx| {
+                this.diagnosing = false;
+                match result {
+                    Ok(r) => {
+                        this.drift_report = Some(r);
+                        this.drift_error = None;
+                    }
+                    Err(e) => {
+                        this.drift_error = Some(e.to_string());
+                        this.drift_report = None;
+                    }
+                }
+                cx.notify();
+            });
+        });
+        self._drift_task = Some(task);
+    }
+
     fn copy(&self, value: String, cx: &mut Context<Self>) {
         if !value.is_empty() {
             cx.write_to_clipboard(ClipboardItem::new_string(value));
@@ -305,20 +409,33 @@ impl Render for TotpView {
                 "Validate",
                 mode == TotpMode::Validate,
                 cx.listener(|this, _, _window, cx| this.set_mode(TotpMode::Validate, cx)),
+            ))
+            .segment(Segment::new(
+                "Vault",
+                mode == TotpMode::Vault,
+                cx.listener(|this, _, _window, cx| this.set_mode(TotpMode::Vault, cx)),
             ));
 
-        let settings_bar = self.render_settings(cx);
+        // Vault accounts carry their own parameters.
+        let settings_bar = (mode != TotpMode::Vault).then(|| self.render_settings(cx));
 
         let content: AnyElement = match mode {
             TotpMode::Secret => self.render_secret(cx).into_any_element(),
             TotpMode::Live => self.render_live(cx).into_any_element(),
             TotpMode::Validate => self.render_validate(cx).into_any_element(),
+            TotpMode::Vault => self.vault.clone().into_any_element(),
         };
 
         v_flex()
             .size_full()
             .gap_4()
-            .child(h_flex().items_center().gap_3().child(mode_bar).child(settings_bar))
+            .child(
+                h_flex()
+                    .items_center()
+                    .gap_3()
+                    .child(mode_bar)
+                    .children(settings_bar),
+            )
             .child(content)
     }
 }
@@ -362,6 +479,11 @@ impl TotpView {
                 "8",
                 digits == 8,
                 cx.listener(|this, _, _window, cx| this.set_digits(8, cx)),
+            ))
+            .segment(Segment::new(
+                "10",
+                digits == 10,
+                cx.listener(|this, _, _window, cx| this.set_digits(10, cx)),
             ));
 
         let period_bar = SegmentedControl::new("totp-period")
@@ -402,6 +524,44 @@ impl TotpView {
             .child(period_bar)
     }
 
+    fn render_variant(&self, cx: &Context<Self>) -> Div {
+        let theme = cx.theme();
+        let variant = self.variant;
+
+        let mut variant_bar = SegmentedControl::new("totp-variant");
+        for (label, value) in [
+            ("RFC 6238", OtpVariant::Rfc6238),
+            ("Steam", OtpVariant::Steam),
+            ("Yandex", OtpVariant::Yandex),
+            ("mOTP", OtpVariant::Motp),
+        ] {
+            variant_bar = variant_bar.segment(Segment::new(
+                label,
+                variant == value,
+                cx.listener(move |this, _, _window, cx| this.set_variant(value, cx)),
+            ));
+        }
+
+        v_flex()
+            .gap_4()
+            .child(
+                h_flex()
+                    .items_center()
+                    .gap_3()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.muted_foreground)
+                            .child("Variant"),
+                    )
+                    .child(variant_bar),
+            )
+            .when(
+                matches!(variant, OtpVariant::Yandex | OtpVariant::Motp),
+                |this| this.child(labelled_input("PIN", &self.pin_input, theme)),
+            )
+    }
+
     fn render_secret(&self, cx: &Context<Self>) -> Div {
         let theme = cx.theme();
         let uri = self
@@ -418,17 +578,60 @@ impl TotpView {
         let secret_for_copy = secret.clone();
         let uri_for_copy = uri.clone();
 
+        let level = self.qr_error_correction;
+        let ecc_bar = SegmentedControl::new("totp-qr-ecc")
+            .segment(Segment::new(
+                "L",
+                level == QrErrorCorrection::Low,
+                cx.listener(|this, _, _window, cx| {
+                    this.set_qr_error_correction(QrErrorCorrection::Low, cx)
+                }),
+            ))
+            .segment(Segment::new(
+                "M",
+                level == QrErrorCorrection::Medium,
+                cx.listener(|this, _, _window, cx| {
+                    this.set_qr_error_correction(QrErrorCorrection::Medium, cx)
+                }),
+            ))
+            .segment(Segment::new(
+                "Q",
+                level == QrErrorCorrection::Quartile,
+                cx.listener(|this, _, _window, cx| {
+                    this.set_qr_error_correction(QrErrorCorrection::Quartile, cx)
+                }),
+            ))
+            .segment(Segment::new(
+                "H",
+                level == QrErrorCorrection::High,
+                cx.listener(|this, _, _window, cx| {
+                    this.set_qr_error_correction(QrErrorCorrection::High, cx)
+                }),
+            ));
+
         v_flex()
             .flex_1()
             .gap_4()
             .child(labelled_input("Issuer", &self.issuer_input, theme))
             .child(labelled_input("Account", &self.account_input, theme))
             .child(
-                Button::new("generate-secret")
-                    .label("Generate")
-                    .small()
-                    .primary()
-                    .on_click(cx.listener(|this, _, _window, cx| this.build_secret(cx))),
+                h_flex()
+                    .items_center()
+                    .gap_3()
+                    .child(
+                        Button::new("generate-secret")
+                            .label("Generate")
+                            .small()
+                            .primary()
+                            .on_click(cx.listener(|this, _, _window, cx| this.build_secret(cx))),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.muted_foreground)
+                            .child("QR error correction"),
+                    )
+                    .child(ecc_bar),
             )
             .when_some(self.secret_error.clone(), |this, e| {
                 this.child(error_box(e, theme))
@@ -461,6 +664,19 @@ impl TotpView {
                         ),
                 )
             })
+            .when_some(self.secret_qr.clone(), |this, qr| {
+                this.child(
+                    h_flex().justify_center().child(
+                        div()
+                            .p_2()
+                            .rounded_lg()
+                            .border_1()
+                            .border_color(theme.border)
+                            .bg(gpui::white())
+                            .child(img(qr).size(px(QR_SIZE as f32))),
+                    ),
+                )
+            })
     }
 
     fn render_live(&self, cx: &Context<Self>) -> Div {
@@ -469,7 +685,7 @@ impl TotpView {
 
         let code = self.live_cached_code.clone();
         let remaining = self.live_cached_remaining;
-        let period = self.period;
+        let period = self.otp_params(cx).period;
 
         let pct = if period > 0 && self.live_cached_error.is_none() && !secret.trim().is_empty() {
             let elapsed = (period as u64).saturating_sub(remaining);
@@ -484,6 +700,7 @@ impl TotpView {
         v_flex()
             .flex_1()
             .gap_4()
+            .child(self.render_variant(cx))
             .child(labelled_input("Secret", &self.live_secret_input, theme))
             .child(
                 v_flex()
@@ -540,18 +757,44 @@ impl TotpView {
 
     fn render_validate(&self, cx: &Context<Self>) -> Div {
         let theme = cx.theme();
+        let range = self.drift_range;
+
+        let mut range_bar = SegmentedControl::new("totp-drift-range");
+        for minutes in [2, 5, 15, 60] {
+            range_bar = range_bar.segment(Segment::new(
+                format!("±{}m", minutes),
+                range == minutes,
+                cx.listener(move |this, _, _window, cx| this.set_drift_range(minutes, cx)),
+            ));
+        }
 
         v_flex()
             .flex_1()
             .gap_4()
+            .overflow_hidden()
+            .child(self.render_variant(cx))
             .child(labelled_input("Secret", &self.validate_secret_input, theme))
             .child(labelled_input("Code", &self.validate_code_input, theme))
             .child(
-                Button::new("validate")
-                    .label("Validate")
-                    .small()
-                    .primary()
-                    .on_click(cx.listener(|this, _, _window, cx| this.run_validation(cx))),
+                h_flex()
+                    .items_center()
+                    .gap_3()
+                    .child(
+                        Button::new("validate")
+                            .label("Validate")
+                            .small()
+                            .primary()
+                            .on_click(cx.listener(|this, _, _window, cx| this.run_validation(cx))),
+                    )
+                    .child(
+                        Button::new("diagnose")
+                            .label("Timeline")
+                            .small()
+                            .ghost()
+                            .disabled(self.diagnosing)
+                            .on_click(cx.listener(|this, _, _window, cx| this.run_diagnosis(cx))),
+                    )
+                    .child(range_bar),
             )
             .when_some(self.validation_error.clone(), |this, e| {
                 this.child(error_box(e, theme))
@@ -575,9 +818,97 @@ impl TotpView {
                         .child(format!("{} — {}", label, v.message)),
                 )
             })
+            .when_some(self.drift_error.clone(), |this, e| {
+                this.child(error_box(e, theme))
+            })
+            .when_some(self.drift_report.as_ref(), |this, report| {
+                this.child(self.render_drift(report, cx))
+            })
+    }
+
+    fn render_drift(&self, report: &TotpDriftReport, cx: &Context<Self>) -> Div {
+        let theme = cx.theme();
+        let color = match report.status {
+            SyncStatus::Synchronized => ui::success(),
+            SyncStatus::DriftDetected => theme.warning,
+            SyncStatus::OutOfSync => theme.danger,
+        };
+        let verdict = match report.status {
+            SyncStatus::Synchronized => "Synchronized",
+            SyncStatus::DriftDetected => "Drift detected",
+            SyncStatus::OutOfSync => "Out of sync",
+        };
+
+        let header = |label: &'static str, width: f32| {
+            div()
+                .w(px(width))
+                .text_xs()
+                .font_weight(FontWeight::MEDIUM)
+                .text_color(theme.muted_foreground)
+                .child(label)
+        };
+
+        let rows = report.timeline.iter().map(|entry| {
+            let time = Utc
+                .timestamp_opt(entry.starts_at as i64, 0)
+                .single()
+                .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default();
+            h_flex()
+                .px_3()
+                .py_1()
+                .gap_2()
+                .text_sm()
+                .font_family("monospace")
+                .when(entry.offset == 0, |row| row.font_weight(FontWeight::BOLD))
+                .when(entry.matches, |row| row.bg(color.opacity(0.15)))
+                .child(div().w(px(64.)).child(format!("{:+}", entry.offset)))
+                .child(div().w(px(110.)).child(entry.step.to_string()))
+                .child(div().w(px(170.)).child(time))
+                .child(div().flex_1().child(format_code(&entry.code)))
+        });
+
+        v_flex()
+            .flex_1()
+            .gap_2()
+            .overflow_hidden()
+            .child(
+                div()
+                    .px_3()
+                    .py_2()
+                    .rounded_md()
+                    .bg(color.opacity(0.1))
+                    .border_1()
+                    .border_color(color)
+                    .text_sm()
+                    .text_color(color)
+                    .child(format!("{} — {}", verdict, report.message)),
+            )
+            .child(
+                h_flex()
+                    .px_3()
+                    .gap_2()
+                    .child(header("Offset", 64.))
+                    .child(header("Step", 110.))
+                    .child(header("Starts (UTC)", 170.))
+                    .child(header("Code", 80.)),
+            )
+            .child(
+                v_flex()
+                    .flex_1()
+                    .rounded_lg()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.background)
+                    .overflow_y_scrollbar()
+                    .children(rows),
+            )
     }
 }
 
+/// Edge length, in pixels, of the provisioning QR code.
+const QR_SIZE: u32 = 200;
+
 fn placeholder_dashes(digits: u32) -> String {
     std::iter::repeat("—")
         .take(digits as usize)
@@ -585,8 +916,9 @@ fn placeholder_dashes(digits: u32) -> String {
         .join(" ")
 }
 
-fn format_code(code: &str) -> String {
-    // Explicit grouping for the three supported digit counts.
+pub(crate) fn format_code(code: &str) -> String {
+    // Explicit grouping for the supported digit counts; Steam's five
+    // symbols are shown as-is.
     match code.len() {
         6 => {
             let (l, r) = code.split_at(3);
@@ -600,6 +932,10 @@ fn format_code(code: &str) -> String {
             let (l, r) = code.split_at(4);
             format!("{} {}", l, r)
         }
+        10 => {
+            let (l, r) = code.split_at(5);
+            format!("{} {}", l, r)
+        }
         _ => code.to_string(),
     }
 }
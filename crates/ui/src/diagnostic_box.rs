@@ -0,0 +1,85 @@
+use dev_utility_core::error::{Diagnostic, Severity};
+use gpui::prelude::FluentBuilder;
+use gpui::*;
+use gpui_component::highlighter::{self, DiagnosticSeverity};
+use gpui_component::input::{InputState, RopeExt};
+use gpui_component::v_flex;
+
+/// Danger callout with the diagnostic's message, location and hint. The
+/// span itself is marked in the editor by [`underline_diagnostic`].
+pub fn diagnostic_box(diagnostic: &Diagnostic, theme: &gpui_component::theme::Theme) -> Div {
+    v_flex()
+        .gap_1()
+        .px_3()
+        .py_2()
+        .rounded_md()
+        .bg(theme.danger.opacity(0.1))
+        .border_1()
+        .border_color(theme.danger)
+        .text_sm()
+        .text_color(theme.danger)
+        .child(format!(
+            "{} (line {}, column {})",
+            diagnostic.message, diagnostic.line, diagnostic.column
+        ))
+        .when_some(diagnostic.hint.clone(), |this, hint| {
+            this.child(
+                div()
+                    .text_xs()
+                    .text_color(theme.muted_foreground)
+                    .child(format!("Hint: {}", hint)),
+            )
+        })
+}
+
+/// Underline the span `diagnostic` points at in a code editor input,
+/// replacing any earlier one; `None` only clears it.
+///
+/// The input is deliberately not notified: views call this while handling
+/// a change to that same input, and their own `cx.notify()` repaints it.
+pub fn underline_diagnostic(
+    input: &Entity<InputState>,
+    diagnostic: Option<&Diagnostic>,
+    cx: &mut App,
+) {
+    input.update(cx, |state, _| {
+        let text = state.text().clone();
+        let Some(set) = state.diagnostics_mut() else {
+            return;
+        };
+        set.clear();
+        let Some(diagnostic) = diagnostic else {
+            return;
+        };
+
+        let source = text.to_string();
+        let start = diagnostic.offset.min(source.len());
+        let mut range = start..(start + diagnostic.length).min(source.len());
+        // Errors at the end of the input have no span; mark the character
+        // next to them so there is something to see.
+        if range.is_empty() {
+            match source[start..].chars().next() {
+                Some(c) => range.end += c.len_utf8(),
+                None => {
+                    range.start -= source[..start]
+                        .chars()
+                        .next_back()
+                        .map_or(0, char::len_utf8)
+                }
+            }
+        }
+
+        let severity = match diagnostic.severity {
+            Severity::Error => DiagnosticSeverity::Error,
+            Severity::Warning => DiagnosticSeverity::Warning,
+            Severity::Info => DiagnosticSeverity::Info,
+        };
+        set.push(
+            highlighter::Diagnostic::new(
+                text.offset_to_position(range.start)..text.offset_to_position(range.end),
+                diagnostic.message.clone(),
+            )
+            .with_severity(severity),
+        );
+    });
+}
@@ -2,6 +2,8 @@
 //!
 //! Current exports:
 //! - `error_box` — danger callout box used by tool views to surface errors
+//! - `diagnostic_box` — `error_box` for a located diagnostic, with its hint
+//! - `underline_diagnostic` — marks a diagnostic's span in a code editor input
 //! - `Segment` — a single selectable item for `SegmentedControl`
 //! - `SegmentedControl` — horizontal toggle bar used by tool views
 //! - `success` — semantic success color (green, works in light and dark themes)
@@ -11,11 +13,13 @@
 //! - `pem_panel` — multi-line scrollable key panel with a trailing Copy button
 
 mod colors;
+mod diagnostic_box;
 mod error_box;
 mod rows;
 mod segmented_control;
 
 pub use colors::success;
+pub use diagnostic_box::{diagnostic_box, underline_diagnostic};
 pub use error_box::error_box;
 pub use rows::{labelled_input, pem_panel, row_with_copy, section};
 pub use segmented_control::{Segment, SegmentedControl};
@@ -1,6 +1,7 @@
 use backslash_escapist_tool::BackslashEscapistView;
 use base64_tool::Base64View;
 use css_formatter_tool::CssFormatterView;
+use encoding_tool::EncodingView;
 use gpui::*;
 use gpui_component::{h_flex, ActiveTheme};
 use hash_tool::HashView;
@@ -8,11 +9,15 @@ use html_formatter_tool::HtmlFormatterView;
 use id_tool::IdGeneratorView;
 use ip_calculator_tool::IpCalculatorView;
 use ip_info_tool::IpInfoView;
-use json_formatter_tool::JsonFormatterView;
+use json_formatter_tool::{JsonDiffView, JsonFormatterView};
 use jwt_tool::JwtView;
+use markdown_tool::MarkdownView;
 use number_base_tool::NumberBaseView;
+use qrcode_tool::QrCodeView;
+use regex_tool::RegexTesterView;
 use rsa_tool::RsaView;
 use string_inspector_tool::StringInspectorView;
+use text_diff_tool::TextDiffView;
 use totp_tool::TotpView;
 use unix_time_tool::UnixTimeView;
 
@@ -21,18 +26,24 @@ use crate::layout::sidebar::{Sidebar, SidebarGroup, SidebarItem};
 #[derive(Clone, PartialEq, Debug)]
 pub enum Route {
     Base64,
+    Encoding,
     Jwt,
     Hash,
     IdGenerator,
+    QrCode,
     Totp,
     Rsa,
     JsonFormatter,
+    JsonDiff,
     CssFormatter,
     HtmlFormatter,
+    Markdown,
     NumberBase,
     UnixTime,
     StringInspector,
     BackslashEscapist,
+    TextDiff,
+    RegexTester,
     IpCalculator,
     IpInfo,
 }
@@ -41,18 +52,24 @@ impl Route {
     pub fn title(&self) -> &'static str {
         match self {
             Route::Base64 => "Base64 Encoder/Decoder",
+            Route::Encoding => "Text Encoding",
             Route::Jwt => "JWT Decoder",
             Route::Hash => "Hash Generator",
             Route::IdGenerator => "UUID/ULID Generator",
+            Route::QrCode => "QR Code / Barcode",
             Route::Totp => "TOTP Debugger",
             Route::Rsa => "RSA Key Tool",
             Route::JsonFormatter => "JSON Formatter",
+            Route::JsonDiff => "JSON Diff",
             Route::CssFormatter => "CSS Formatter",
             Route::HtmlFormatter => "HTML Formatter",
+            Route::Markdown => "Markdown Preview",
             Route::NumberBase => "Number Base Converter",
             Route::UnixTime => "Unix Timestamp",
             Route::StringInspector => "String Inspector",
             Route::BackslashEscapist => "Backslash Escapist",
+            Route::TextDiff => "Text Diff",
+            Route::RegexTester => "Regex Tester",
             Route::IpCalculator => "IP Calculator",
             Route::IpInfo => "IP Info",
         }
@@ -70,6 +87,11 @@ pub fn sidebar_groups() -> Vec<SidebarGroup> {
                     label: "Base64",
                     route: Route::Base64,
                 },
+                SidebarItem {
+                    key: "encoding",
+                    label: "Text Encoding",
+                    route: Route::Encoding,
+                },
                 SidebarItem {
                     key: "jwt",
                     label: "JWT Decoder",
@@ -91,6 +113,11 @@ pub fn sidebar_groups() -> Vec<SidebarGroup> {
                     label: "Hash Generator",
                     route: Route::Hash,
                 },
+                SidebarItem {
+                    key: "qrcode",
+                    label: "QR Code / Barcode",
+                    route: Route::QrCode,
+                },
             ],
         },
         SidebarGroup {
@@ -118,6 +145,11 @@ pub fn sidebar_groups() -> Vec<SidebarGroup> {
                     label: "JSON",
                     route: Route::JsonFormatter,
                 },
+                SidebarItem {
+                    key: "json-diff",
+                    label: "JSON Diff",
+                    route: Route::JsonDiff,
+                },
                 SidebarItem {
                     key: "css",
                     label: "CSS",
@@ -128,6 +160,11 @@ pub fn sidebar_groups() -> Vec<SidebarGroup> {
                     label: "HTML",
                     route: Route::HtmlFormatter,
                 },
+                SidebarItem {
+                    key: "markdown",
+                    label: "Markdown",
+                    route: Route::Markdown,
+                },
             ],
         },
         SidebarGroup {
@@ -154,6 +191,16 @@ pub fn sidebar_groups() -> Vec<SidebarGroup> {
                     label: "Backslash Escapist",
                     route: Route::BackslashEscapist,
                 },
+                SidebarItem {
+                    key: "text-diff",
+                    label: "Text Diff",
+                    route: Route::TextDiff,
+                },
+                SidebarItem {
+                    key: "regex",
+                    label: "Regex Tester",
+                    route: Route::RegexTester,
+                },
             ],
         },
         SidebarGroup {
@@ -179,10 +226,12 @@ pub struct DevUtilityApp {
     current_route: Route,
     sidebar: Entity<Sidebar>,
     base64_view: Entity<Base64View>,
+    encoding_view: Entity<EncodingView>,
     id_generator_view: Entity<IdGeneratorView>,
     hash_view: Entity<HashView>,
     jwt_view: Entity<JwtView>,
     json_formatter_view: Entity<JsonFormatterView>,
+    json_diff_view: Entity<JsonDiffView>,
     css_formatter_view: Entity<CssFormatterView>,
     number_base_view: Entity<NumberBaseView>,
     unix_time_view: Entity<UnixTimeView>,
@@ -193,6 +242,10 @@ pub struct DevUtilityApp {
     totp_view: Entity<TotpView>,
     ip_info_view: Entity<IpInfoView>,
     html_formatter_view: Entity<HtmlFormatterView>,
+    markdown_view: Entity<MarkdownView>,
+    qrcode_view: Entity<QrCodeView>,
+    text_diff_view: Entity<TextDiffView>,
+    regex_tester_view: Entity<RegexTesterView>,
 }
 
 impl DevUtilityApp {
@@ -209,10 +262,12 @@ impl DevUtilityApp {
         .detach();
 
         let base64_view = cx.new(|cx| Base64View::new(window, cx));
+        let encoding_view = cx.new(|cx| EncodingView::new(window, cx));
         let id_generator_view = cx.new(|cx| IdGeneratorView::new(window, cx));
         let hash_view = cx.new(|cx| HashView::new(window, cx));
         let jwt_view = cx.new(|cx| JwtView::new(window, cx));
         let json_formatter_view = cx.new(|cx| JsonFormatterView::new(window, cx));
+        let json_diff_view = cx.new(|cx| JsonDiffView::new(window, cx));
         let css_formatter_view = cx.new(|cx| CssFormatterView::new(window, cx));
         let number_base_view = cx.new(|cx| NumberBaseView::new(window, cx));
         let unix_time_view = cx.new(|cx| UnixTimeView::new(window, cx));
@@ -223,15 +278,21 @@ impl DevUtilityApp {
         let totp_view = cx.new(|cx| TotpView::new(window, cx));
         let ip_info_view = cx.new(|cx| IpInfoView::new(window, cx));
         let html_formatter_view = cx.new(|cx| HtmlFormatterView::new(window, cx));
+        let markdown_view = cx.new(|cx| MarkdownView::new(window, cx));
+        let qrcode_view = cx.new(|cx| QrCodeView::new(window, cx));
+        let text_diff_view = cx.new(|cx| TextDiffView::new(window, cx));
+        let regex_tester_view = cx.new(|cx| RegexTesterView::new(window, cx));
 
         Self {
             current_route,
             sidebar,
             base64_view,
+            encoding_view,
             id_generator_view,
             hash_view,
             jwt_view,
             json_formatter_view,
+            json_diff_view,
             css_formatter_view,
             number_base_view,
             unix_time_view,
@@ -242,6 +303,10 @@ impl DevUtilityApp {
             totp_view,
             ip_info_view,
             html_formatter_view,
+            markdown_view,
+            qrcode_view,
+            text_diff_view,
+            regex_tester_view,
         }
     }
 
@@ -256,10 +321,12 @@ impl DevUtilityApp {
     fn render_content(&self, _window: &mut Window, _cx: &mut Context<Self>) -> AnyElement {
         match &self.current_route {
             Route::Base64 => self.base64_view.clone().into_any_element(),
+            Route::Encoding => self.encoding_view.clone().into_any_element(),
             Route::IdGenerator => self.id_generator_view.clone().into_any_element(),
             Route::Hash => self.hash_view.clone().into_any_element(),
             Route::Jwt => self.jwt_view.clone().into_any_element(),
             Route::JsonFormatter => self.json_formatter_view.clone().into_any_element(),
+            Route::JsonDiff => self.json_diff_view.clone().into_any_element(),
             Route::CssFormatter => self.css_formatter_view.clone().into_any_element(),
             Route::NumberBase => self.number_base_view.clone().into_any_element(),
             Route::UnixTime => self.unix_time_view.clone().into_any_element(),
@@ -270,6 +337,10 @@ impl DevUtilityApp {
             Route::Totp => self.totp_view.clone().into_any_element(),
             Route::IpInfo => self.ip_info_view.clone().into_any_element(),
             Route::HtmlFormatter => self.html_formatter_view.clone().into_any_element(),
+            Route::Markdown => self.markdown_view.clone().into_any_element(),
+            Route::QrCode => self.qrcode_view.clone().into_any_element(),
+            Route::TextDiff => self.text_diff_view.clone().into_any_element(),
+            Route::RegexTester => self.regex_tester_view.clone().into_any_element(),
         }
     }
 }
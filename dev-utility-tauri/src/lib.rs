@@ -113,6 +113,11 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             dev_utility_core::codec::decode_base64,
             dev_utility_core::codec::encode_base64,
+            dev_utility_core::codec::decode_text,
+            dev_utility_core::codec::decode_text_bytes,
+            dev_utility_core::codec::decode_text_file,
+            dev_utility_core::codec::encode_text,
+            dev_utility_core::codec::fix_mojibake,
             dev_utility_core::codec::decode_jwt,
             dev_utility_core::cryptography::generate_rsa_key,
             dev_utility_core::cryptography::analyze_rsa_key,
@@ -120,6 +125,20 @@ pub fn run() {
             dev_utility_core::cryptography::generate_totp_secret,
             dev_utility_core::cryptography::generate_totp_code,
             dev_utility_core::cryptography::validate_totp_code,
+            dev_utility_core::cryptography::generate_hotp_code,
+            dev_utility_core::cryptography::generate_otp_code,
+            dev_utility_core::cryptography::generate_otp_code_for_time,
+            dev_utility_core::cryptography::validate_otp_code,
+            dev_utility_core::cryptography::diagnose_totp_drift,
+            dev_utility_core::cryptography::parse_ocra_suite,
+            dev_utility_core::cryptography::generate_ocra_response,
+            dev_utility_core::cryptography::encrypt_otp_vault,
+            dev_utility_core::cryptography::decrypt_otp_vault,
+            dev_utility_core::cryptography::otp_vault_codes,
+            dev_utility_core::cryptography::use_hotp_account,
+            dev_utility_core::cryptography::import_otp_accounts,
+            dev_utility_core::cryptography::export_otp_accounts,
+            dev_utility_core::cryptography::parse_otpauth_uri,
             dev_utility_core::generator::analyze_uuid,
             dev_utility_core::generator::generate_uuid_v1,
             dev_utility_core::generator::generate_uuid_v3,
@@ -128,8 +147,40 @@ pub fn run() {
             dev_utility_core::generator::generate_uuid_v7,
             dev_utility_core::generator::generate_ulid,
             dev_utility_core::generator::generate_nanoid,
+            dev_utility_core::generator::generate_qr_code,
+            dev_utility_core::generator::generate_qr_code_with_options,
+            dev_utility_core::generator::generate_barcode,
+            dev_utility_core::generator::build_qr_payload,
+            dev_utility_core::generator::decode_qr_image,
+            dev_utility_core::generator::decode_qr_image_file,
+            dev_utility_core::generator::generate_code,
             dev_utility_core::formatter::format_json,
+            dev_utility_core::formatter::repair_json,
+            dev_utility_core::formatter::diff_json,
+            dev_utility_core::formatter::apply_json_patch,
+            dev_utility_core::formatter::validate_json_schema,
+            dev_utility_core::formatter::infer_json_schema,
+            dev_utility_core::formatter::query_json,
             dev_utility_core::formatter::format_css,
+            dev_utility_core::formatter::format_html,
+            dev_utility_core::formatter::format_xml,
+            dev_utility_core::formatter::query_xml,
+            dev_utility_core::formatter::canonicalize_xml,
+            dev_utility_core::formatter::format_sql,
+            dev_utility_core::formatter::render_markdown,
+            dev_utility_core::formatter::markdown_toc,
+            dev_utility_core::formatter::html_to_markdown,
+            dev_utility_core::formatter::format_markdown_tables,
+            dev_utility_core::formatter::format_yaml,
+            dev_utility_core::formatter::format_toml,
+            dev_utility_core::converter::convert_data,
+            dev_utility_core::converter::diff_text,
+            dev_utility_core::converter::apply_text_patch,
+            dev_utility_core::converter::test_regex,
+            dev_utility_core::converter::replace_regex,
+            dev_utility_core::converter::explain_regex,
+            dev_utility_core::converter::transform_text,
+            dev_utility_core::converter::convert_case,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,673 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Character encodings.
+//!
+//! Converts between text and bytes in legacy and Unicode encodings. Bytes
+//! travel as hex or Base64 text, or are read from a file, so input that is
+//! not valid UTF-8 can still be inspected.
+
+use crate::error::{Diagnostic, UtilityError};
+use base64::{
+    engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD},
+    Engine as _,
+};
+use encoding_rs::Encoding;
+use serde::{Deserialize, Serialize};
+use universal_function_macro::universal_function;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum TextEncoding {
+    Utf8,
+    Utf16le,
+    Utf16be,
+    Utf32le,
+    Utf32be,
+    Latin1, // ISO-8859-1, every byte is the code point of the same value
+    Windows1252,
+    ShiftJis,
+    Gbk,
+    Gb18030,
+    Big5,
+    EucKr,
+    Koi8r,
+}
+
+impl TextEncoding {
+    pub const ALL: [TextEncoding; 13] = [
+        TextEncoding::Utf8,
+        TextEncoding::Utf16le,
+        TextEncoding::Utf16be,
+        TextEncoding::Utf32le,
+        TextEncoding::Utf32be,
+        TextEncoding::Latin1,
+        TextEncoding::Windows1252,
+        TextEncoding::ShiftJis,
+        TextEncoding::Gbk,
+        TextEncoding::Gb18030,
+        TextEncoding::Big5,
+        TextEncoding::EucKr,
+        TextEncoding::Koi8r,
+    ];
+
+    /// The encoding's usual label.
+    pub fn label(self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf16le => "UTF-16LE",
+            TextEncoding::Utf16be => "UTF-16BE",
+            TextEncoding::Utf32le => "UTF-32LE",
+            TextEncoding::Utf32be => "UTF-32BE",
+            TextEncoding::Latin1 => "ISO-8859-1",
+            TextEncoding::Windows1252 => "Windows-1252",
+            TextEncoding::ShiftJis => "Shift_JIS",
+            TextEncoding::Gbk => "GBK",
+            TextEncoding::Gb18030 => "GB18030",
+            TextEncoding::Big5 => "Big5",
+            TextEncoding::EucKr => "EUC-KR",
+            TextEncoding::Koi8r => "KOI8-R",
+        }
+    }
+
+    /// The encoding_rs codec, for the encodings it implements faithfully.
+    /// UTF-32 is not in the Encoding Standard, and its ISO-8859-1 label
+    /// means Windows-1252.
+    fn codec(self) -> Option<&'static Encoding> {
+        match self {
+            TextEncoding::Utf8 => Some(encoding_rs::UTF_8),
+            TextEncoding::Utf16le => Some(encoding_rs::UTF_16LE),
+            TextEncoding::Utf16be => Some(encoding_rs::UTF_16BE),
+            TextEncoding::Windows1252 => Some(encoding_rs::WINDOWS_1252),
+            TextEncoding::ShiftJis => Some(encoding_rs::SHIFT_JIS),
+            TextEncoding::Gbk => Some(encoding_rs::GBK),
+            TextEncoding::Gb18030 => Some(encoding_rs::GB18030),
+            TextEncoding::Big5 => Some(encoding_rs::BIG5),
+            TextEncoding::EucKr => Some(encoding_rs::EUC_KR),
+            TextEncoding::Koi8r => Some(encoding_rs::KOI8_R),
+            TextEncoding::Utf32le | TextEncoding::Utf32be | TextEncoding::Latin1 => None,
+        }
+    }
+
+    fn bom(self) -> &'static [u8] {
+        match self {
+            TextEncoding::Utf8 => b"\xEF\xBB\xBF",
+            TextEncoding::Utf16le => b"\xFF\xFE",
+            TextEncoding::Utf16be => b"\xFE\xFF",
+            TextEncoding::Utf32le => b"\xFF\xFE\x00\x00",
+            TextEncoding::Utf32be => b"\x00\x00\xFE\xFF",
+            _ => b"",
+        }
+    }
+}
+
+/// How bytes are written as text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum ByteFormat {
+    Hex,
+    Base64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct DecodedText {
+    pub text: String,
+    pub encoding: TextEncoding, // the encoding used, given or detected
+    pub detected: bool,         // the encoding was not given
+    pub confident: bool,        // false when detection was a best guess
+    pub bom: bool,              // a byte order mark was found and removed
+    pub malformed: bool,        // some bytes did not decode and became U+FFFD
+    pub byte_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct EncodedText {
+    pub hex: String, // space-separated bytes
+    pub base64: String,
+    pub byte_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct MojibakeFix {
+    pub text: String,
+    pub rounds: usize, // layers of double encoding undone; 0 when unchanged
+}
+
+/// Decode bytes written as hex or Base64. Without an encoding, it is taken
+/// from the byte order mark or guessed.
+#[universal_function]
+pub fn decode_text(
+    input: &str,
+    format: ByteFormat,
+    encoding: Option<TextEncoding>,
+) -> Result<DecodedText, UtilityError> {
+    let bytes = match format {
+        ByteFormat::Hex => parse_hex(input)?,
+        ByteFormat::Base64 => parse_base64(input)?,
+    };
+    Ok(decode_text_bytes(bytes, encoding))
+}
+
+/// Decode raw bytes. Without an encoding, it is taken from the byte order
+/// mark or guessed.
+#[universal_function]
+pub fn decode_text_bytes(bytes: Vec<u8>, encoding: Option<TextEncoding>) -> DecodedText {
+    let bom = sniff_bom(&bytes);
+    let (encoding, detected, confident) = match (encoding, bom) {
+        (Some(encoding), _) => (encoding, false, true),
+        (None, Some(bom)) => (bom, true, true),
+        (None, None) => {
+            let (encoding, confident) = guess(&bytes);
+            (encoding, true, confident)
+        }
+    };
+    let has_bom = bom == Some(encoding);
+    let body = if has_bom {
+        &bytes[encoding.bom().len()..]
+    } else {
+        &bytes[..]
+    };
+
+    let (text, malformed) = match encoding {
+        TextEncoding::Latin1 => (body.iter().map(|&b| b as char).collect(), false),
+        TextEncoding::Utf32le => decode_utf32(body, u32::from_le_bytes),
+        TextEncoding::Utf32be => decode_utf32(body, u32::from_be_bytes),
+        _ => {
+            let codec = encoding.codec().expect("every other encoding has a codec");
+            let (text, malformed) = codec.decode_without_bom_handling(body);
+            (text.into_owned(), malformed)
+        }
+    };
+    DecodedText {
+        text,
+        encoding,
+        detected,
+        confident,
+        bom: has_bom,
+        malformed,
+        byte_count: bytes.len(),
+    }
+}
+
+/// Read a file and decode it. Without an encoding, it is taken from the
+/// byte order mark or guessed.
+#[universal_function(desktop_only)]
+pub fn decode_text_file(
+    path: String,
+    encoding: Option<TextEncoding>,
+) -> Result<DecodedText, UtilityError> {
+    Ok(decode_text_bytes(std::fs::read(&path)?, encoding))
+}
+
+/// Encode text, optionally led by a byte order mark (UTF encodings only).
+/// Fails at the first character the encoding cannot represent.
+#[universal_function]
+pub fn encode_text(
+    input: &str,
+    encoding: TextEncoding,
+    bom: bool,
+) -> Result<EncodedText, UtilityError> {
+    let mut bytes = if bom {
+        encoding.bom().to_vec()
+    } else {
+        Vec::new()
+    };
+    match encoding {
+        TextEncoding::Utf8 => bytes.extend_from_slice(input.as_bytes()),
+        TextEncoding::Utf16le => bytes.extend(input.encode_utf16().flat_map(u16::to_le_bytes)),
+        TextEncoding::Utf16be => bytes.extend(input.encode_utf16().flat_map(u16::to_be_bytes)),
+        TextEncoding::Utf32le => bytes.extend(input.chars().flat_map(|c| (c as u32).to_le_bytes())),
+        TextEncoding::Utf32be => bytes.extend(input.chars().flat_map(|c| (c as u32).to_be_bytes())),
+        TextEncoding::Latin1 => {
+            for (offset, c) in input.char_indices() {
+                match u8::try_from(c as u32) {
+                    Ok(b) => bytes.push(b),
+                    Err(_) => return Err(unmappable(input, offset, c, encoding)),
+                }
+            }
+        }
+        _ => {
+            let codec = encoding.codec().expect("every other encoding has a codec");
+            let (encoded, _, had_errors) = codec.encode(input);
+            if had_errors {
+                // Find the culprit; these encodings are stateless, so each
+                // character can be tried on its own.
+                for (offset, c) in input.char_indices() {
+                    if codec.encode(c.encode_utf8(&mut [0; 4])).2 {
+                        return Err(unmappable(input, offset, c, encoding));
+                    }
+                }
+            }
+            bytes.extend_from_slice(&encoded);
+        }
+    }
+    Ok(EncodedText {
+        hex: bytes
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(" "),
+        base64: STANDARD.encode(&bytes),
+        byte_count: bytes.len(),
+    })
+}
+
+/// Undo UTF-8 that was decoded as Windows-1252 or Latin-1 and encoded
+/// again, as in `CafÃ©` for `Café`, repeating for text that went through
+/// the round trip more than once. Runs that were not mangled are kept.
+#[universal_function]
+pub fn fix_mojibake(input: &str) -> MojibakeFix {
+    let mut text = input.to_string();
+    let mut rounds = 0;
+    // Each round shrinks the text, so this ends; the cap is a safeguard.
+    while rounds < 8 {
+        let fixed = unmangle(&text);
+        if fixed == text {
+            break;
+        }
+        text = fixed;
+        rounds += 1;
+    }
+    MojibakeFix { text, rounds }
+}
+
+fn unmappable(input: &str, offset: usize, c: char, encoding: TextEncoding) -> UtilityError {
+    Diagnostic::new(
+        input,
+        offset,
+        format!(
+            "`{}` (U+{:04X}) cannot be represented in {}",
+            c,
+            c as u32,
+            encoding.label()
+        ),
+    )
+    .with_length(c.len_utf8())
+    .into()
+}
+
+/// Bytes written as hex. Whitespace and `,` `:` `-` separators are
+/// ignored, as are `0x` and `\x` prefixes.
+fn parse_hex(input: &str) -> Result<Vec<u8>, UtilityError> {
+    let mut bytes = Vec::new();
+    let mut high: Option<(usize, u8)> = None;
+    let mut chars = input.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        let prefix = matches!(c, '0' | '\\')
+            && high.is_none()
+            && matches!(chars.peek(), Some((_, 'x' | 'X')));
+        if prefix {
+            chars.next();
+            continue;
+        }
+        if c.is_whitespace() || matches!(c, ',' | ':' | '-') {
+            continue;
+        }
+        let Some(nibble) = c.to_digit(16) else {
+            return Err(
+                Diagnostic::new(input, offset, format!("`{}` is not a hex digit", c))
+                    .with_length(c.len_utf8())
+                    .into(),
+            );
+        };
+        match high.take() {
+            Some((_, h)) => bytes.push(h << 4 | nibble as u8),
+            None => high = Some((offset, nibble as u8)),
+        }
+    }
+    if let Some((offset, _)) = high {
+        return Err(Diagnostic::new(
+            input,
+            offset,
+            "Odd number of hex digits; the last byte is incomplete",
+        )
+        .with_length(1)
+        .into());
+    }
+    Ok(bytes)
+}
+
+/// Bytes written as Base64, in either alphabet, with or without padding.
+fn parse_base64(input: &str) -> Result<Vec<u8>, UtilityError> {
+    let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    let standard = STANDARD.decode(&compact);
+    if let Ok(bytes) = standard {
+        return Ok(bytes);
+    }
+    [STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD]
+        .iter()
+        .find_map(|engine| engine.decode(&compact).ok())
+        .ok_or_else(|| UtilityError::DecodeError(standard.unwrap_err().to_string()))
+}
+
+fn sniff_bom(bytes: &[u8]) -> Option<TextEncoding> {
+    // UTF-32LE's mark starts with UTF-16LE's, so it is checked first.
+    [
+        TextEncoding::Utf32le,
+        TextEncoding::Utf32be,
+        TextEncoding::Utf8,
+        TextEncoding::Utf16le,
+        TextEncoding::Utf16be,
+    ]
+    .into_iter()
+    .find(|encoding| bytes.starts_with(encoding.bom()))
+}
+
+/// Guess the encoding of bytes without a byte order mark, and whether the
+/// guess is reliable.
+fn guess(bytes: &[u8]) -> (TextEncoding, bool) {
+    // Zero bytes are valid UTF-8, so the wide encodings go first.
+    if let Some(encoding) = guess_wide(bytes) {
+        return (encoding, true);
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return (TextEncoding::Utf8, true);
+    }
+
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    let (guessed, confident) = detector.guess_assess(None, true);
+    // KOI8-U only adds Ukrainian letters to KOI8-R.
+    let guessed = if guessed == encoding_rs::KOI8_U {
+        encoding_rs::KOI8_R
+    } else {
+        guessed
+    };
+    match TextEncoding::ALL
+        .into_iter()
+        .find(|encoding| encoding.codec() == Some(guessed))
+    {
+        Some(encoding) => (encoding, confident),
+        // The detector knows more encodings than are offered here.
+        None => (TextEncoding::Windows1252, false),
+    }
+}
+
+/// UTF-16 and UTF-32 without a mark give themselves away by the zero bytes
+/// in mostly-ASCII or mostly-BMP text.
+fn guess_wide(bytes: &[u8]) -> Option<TextEncoding> {
+    let valid_utf32 = |read: fn([u8; 4]) -> u32| {
+        bytes.len().is_multiple_of(4)
+            && bytes.chunks_exact(4).all(|unit| {
+                let unit = read(unit.try_into().expect("chunks of four"));
+                unit != 0 && char::from_u32(unit).is_some()
+            })
+    };
+    if !bytes.is_empty() && valid_utf32(u32::from_le_bytes) {
+        return Some(TextEncoding::Utf32le);
+    }
+    if !bytes.is_empty() && valid_utf32(u32::from_be_bytes) {
+        return Some(TextEncoding::Utf32be);
+    }
+    if bytes.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let units = bytes.len() / 2;
+    let zeros = |parity: usize| {
+        bytes
+            .iter()
+            .skip(parity)
+            .step_by(2)
+            .filter(|&&b| b == 0)
+            .count()
+    };
+    let (even, odd) = (zeros(0), zeros(1));
+    if odd * 3 > units && even * 20 < units {
+        Some(TextEncoding::Utf16le)
+    } else if even * 3 > units && odd * 20 < units {
+        Some(TextEncoding::Utf16be)
+    } else {
+        None
+    }
+}
+
+fn decode_utf32(bytes: &[u8], read: fn([u8; 4]) -> u32) -> (String, bool) {
+    let chunks = bytes.chunks_exact(4);
+    let truncated = !chunks.remainder().is_empty();
+    let mut malformed = truncated;
+    let mut text: String = chunks
+        .map(|unit| {
+            char::from_u32(read(unit.try_into().expect("chunks of four"))).unwrap_or_else(|| {
+                malformed = true;
+                char::REPLACEMENT_CHARACTER
+            })
+        })
+        .collect();
+    if truncated {
+        text.push(char::REPLACEMENT_CHARACTER);
+    }
+    (text, malformed)
+}
+
+/// The byte `c` came from if UTF-8 was misread as Windows-1252 (or as
+/// Latin-1, for the bytes Windows-1252 leaves undefined).
+fn misread_byte(c: char) -> Option<u8> {
+    if let Ok(b) = u8::try_from(c as u32) {
+        return Some(b);
+    }
+    let mut buffer = [0; 4];
+    match encoding_rs::WINDOWS_1252.encode(c.encode_utf8(&mut buffer)) {
+        (bytes, _, false) => Some(bytes[0]),
+        _ => None,
+    }
+}
+
+/// One round of repair: map each run of misread characters back to bytes
+/// and decode the valid multi-byte UTF-8 sequences among them.
+fn unmangle(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let Some(lead) = misread_byte(chars[i]).filter(|&b| b >= 0xC2) else {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        };
+        let width = match lead {
+            0xC2..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF4 => 4,
+            _ => 1,
+        };
+        let sequence: Option<Vec<u8>> = chars
+            .get(i..i + width)
+            .and_then(|run| run.iter().map(|&c| misread_byte(c)).collect());
+        match sequence
+            .as_deref()
+            .and_then(|bytes| std::str::from_utf8(bytes).ok())
+        {
+            Some(decoded) if width > 1 => {
+                out.push_str(decoded);
+                i += width;
+            }
+            _ => {
+                out.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(text: &str, encoding: TextEncoding) -> DecodedText {
+        let encoded = encode_text(text, encoding, false).unwrap();
+        decode_text(&encoded.hex, ByteFormat::Hex, Some(encoding)).unwrap()
+    }
+
+    #[test]
+    fn round_trips_every_encoding() {
+        let samples = [
+            (TextEncoding::Latin1, "Crème brûlée"),
+            (TextEncoding::Windows1252, "“Smart” quotes — €5"),
+            (TextEncoding::ShiftJis, "日本語のテキスト"),
+            (TextEncoding::Gbk, "简体中文"),
+            (TextEncoding::Gb18030, "简体中文 😀"),
+            (TextEncoding::Big5, "繁體中文"),
+            (TextEncoding::EucKr, "한국어 텍스트"),
+            (TextEncoding::Koi8r, "Привет, мир"),
+        ];
+        for (encoding, text) in samples {
+            assert_eq!(
+                round_trip(text, encoding).text,
+                text,
+                "{}",
+                encoding.label()
+            );
+        }
+        for encoding in [
+            TextEncoding::Utf8,
+            TextEncoding::Utf16le,
+            TextEncoding::Utf16be,
+            TextEncoding::Utf32le,
+            TextEncoding::Utf32be,
+        ] {
+            assert_eq!(round_trip("añ 😀", encoding).text, "añ 😀");
+        }
+    }
+
+    #[test]
+    fn encodes_bytes() {
+        let encoded = encode_text("hé", TextEncoding::Utf16be, true).unwrap();
+        assert_eq!(encoded.hex, "FE FF 00 68 00 E9");
+        assert_eq!(encoded.base64, "/v8AaADp");
+        assert_eq!(encoded.byte_count, 6);
+        assert_eq!(
+            encode_text("hы", TextEncoding::Koi8r, true).unwrap().hex,
+            "68 D9"
+        );
+    }
+
+    #[test]
+    fn rejects_unmappable_characters() {
+        let err = encode_text("price: €5", TextEncoding::Latin1, false).unwrap_err();
+        let diagnostic = err.diagnostic().unwrap();
+        assert_eq!(
+            diagnostic.message,
+            "`€` (U+20AC) cannot be represented in ISO-8859-1"
+        );
+        assert_eq!(diagnostic.offset, 7);
+
+        let err = encode_text("ok 😀", TextEncoding::ShiftJis, false).unwrap_err();
+        assert_eq!(err.diagnostic().unwrap().offset, 3);
+    }
+
+    #[test]
+    fn parses_byte_formats() {
+        assert_eq!(
+            parse_hex("0x48, 0x69 \\x21 c3:a9-00").unwrap(),
+            [0x48, 0x69, 0x21, 0xC3, 0xA9, 0x00]
+        );
+        assert_eq!(
+            parse_hex("48 6").unwrap_err().diagnostic().unwrap().offset,
+            3
+        );
+        assert_eq!(parse_hex("4g").unwrap_err().diagnostic().unwrap().offset, 1);
+        assert_eq!(parse_base64("aGk_\n").unwrap(), b"hi?");
+        assert!(parse_base64("***").is_err());
+    }
+
+    #[test]
+    fn detects_byte_order_marks() {
+        let decoded = decode_text("FF FE 00 00 41 00 00 00", ByteFormat::Hex, None).unwrap();
+        assert_eq!(decoded.text, "A");
+        assert_eq!(decoded.encoding, TextEncoding::Utf32le);
+        assert!(decoded.bom && decoded.detected);
+
+        let decoded = decode_text("77u/aGk=", ByteFormat::Base64, None).unwrap();
+        assert_eq!((decoded.text.as_str(), decoded.bom), ("hi", true));
+
+        // A mark for another encoding is left alone when one is given.
+        let decoded = decode_text_bytes(vec![0xEF, 0xBB, 0xBF, 0x41], Some(TextEncoding::Latin1));
+        assert_eq!(decoded.text, "ï»¿A");
+        assert!(!decoded.bom);
+    }
+
+    #[test]
+    fn guesses_encodings() {
+        let guess_of = |text: &str, encoding: TextEncoding| {
+            let bytes = encode_text(text, encoding, false).unwrap();
+            let decoded = decode_text(&bytes.base64, ByteFormat::Base64, None).unwrap();
+            assert_eq!(decoded.text, text);
+            decoded.encoding
+        };
+        assert_eq!(
+            guess_of("plain ascii", TextEncoding::Utf8),
+            TextEncoding::Utf8
+        );
+        assert_eq!(
+            guess_of("wide text", TextEncoding::Utf16le),
+            TextEncoding::Utf16le
+        );
+        assert_eq!(
+            guess_of("wide text", TextEncoding::Utf16be),
+            TextEncoding::Utf16be
+        );
+        assert_eq!(
+            guess_of("wider", TextEncoding::Utf32be),
+            TextEncoding::Utf32be
+        );
+        assert_eq!(
+            guess_of(
+                "日本語のテキストをエンコードして、文字コードを推測します。",
+                TextEncoding::ShiftJis
+            ),
+            TextEncoding::ShiftJis
+        );
+        assert_eq!(
+            guess_of(
+                "Съешь же ещё этих мягких французских булок, да выпей чаю.",
+                TextEncoding::Koi8r
+            ),
+            TextEncoding::Koi8r
+        );
+
+        let decoded = decode_text_bytes(vec![0x61, 0xFF, 0x62], Some(TextEncoding::Utf8));
+        assert_eq!(
+            (decoded.text.as_str(), decoded.malformed),
+            ("a\u{FFFD}b", true)
+        );
+    }
+
+    #[test]
+    fn fixes_mojibake() {
+        assert_eq!(
+            fix_mojibake("CafÃ© â€œlatteâ€\u{9D} â€” Ã\u{A0} la carte"),
+            MojibakeFix {
+                text: "Café “latte” — à la carte".into(),
+                rounds: 1
+            }
+        );
+        assert_eq!(fix_mojibake("ÃƒÂ©tÃƒÂ©").text, "été");
+        assert_eq!(fix_mojibake("ÃƒÂ©tÃƒÂ©").rounds, 2);
+
+        // Correct text, including Latin-1 letters, is left alone.
+        let clean = fix_mojibake("Ãbc naïve déjà vu ©");
+        assert_eq!(
+            (clean.text.as_str(), clean.rounds),
+            ("Ãbc naïve déjà vu ©", 0)
+        );
+    }
+}
@@ -4,4 +4,6 @@ pub mod jwt;
 pub use jwt::*;
 
 pub mod base64;
-pub use base64::*;
\ No newline at end of file
+pub use base64::*;
+pub mod encoding;
+pub use encoding::*;
@@ -1,75 +1,344 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! String literal escaping for programming languages and text formats.
+//!
+//! Escaping produces the body of a literal, without the surrounding quotes,
+//! and unescaping reads one back. Each profile follows its language's own
+//! rules, so an escape the language rejects is an error here too, while
+//! languages that keep unknown escapes (Python, shells) or drop the
+//! backslash (JavaScript) do the same.
+
+mod entities;
+mod escape;
+mod unescape;
+
 use crate::error::UtilityError;
+use serde::{Deserialize, Serialize};
 use universal_function_macro::universal_function;
 
-/// Escape common control characters into backslash sequences.
-///
-/// Covers `\n`, `\r`, `\t`, `\0`, `\\`, and `\"`.
-#[universal_function]
-pub fn escape_backslash(input: &str) -> String {
-    let mut out = String::with_capacity(input.len());
-    for ch in input.chars() {
-        match ch {
-            '\\' => out.push_str("\\\\"),
-            '\n' => out.push_str("\\n"),
-            '\r' => out.push_str("\\r"),
-            '\t' => out.push_str("\\t"),
-            '\0' => out.push_str("\\0"),
-            '"' => out.push_str("\\\""),
-            c => out.push(c),
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum EscapeProfile {
+    #[default]
+    Json,
+    JavaScript,
+    Rust,
+    C, // C and C++
+    Java,
+    Python,
+    PythonRaw,   // r"..."
+    PythonBytes, // b"..."
+    Go,
+    CSharp,
+    Sql,         // Standard SQL: quotes are doubled
+    ShellSingle, // '...'
+    ShellDouble, // "..."
+    ShellAnsiC,  // $'...'
+    Regex,
+    Csv,  // A single field
+    Html, // XML and HTML character references
+    Url,  // Percent-encoding
+}
+
+impl EscapeProfile {
+    pub const ALL: [EscapeProfile; 18] = [
+        EscapeProfile::Json,
+        EscapeProfile::JavaScript,
+        EscapeProfile::Rust,
+        EscapeProfile::C,
+        EscapeProfile::Java,
+        EscapeProfile::Python,
+        EscapeProfile::PythonRaw,
+        EscapeProfile::PythonBytes,
+        EscapeProfile::Go,
+        EscapeProfile::CSharp,
+        EscapeProfile::Sql,
+        EscapeProfile::ShellSingle,
+        EscapeProfile::ShellDouble,
+        EscapeProfile::ShellAnsiC,
+        EscapeProfile::Regex,
+        EscapeProfile::Csv,
+        EscapeProfile::Html,
+        EscapeProfile::Url,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EscapeProfile::Json => "JSON",
+            EscapeProfile::JavaScript => "JavaScript",
+            EscapeProfile::Rust => "Rust",
+            EscapeProfile::C => "C/C++",
+            EscapeProfile::Java => "Java",
+            EscapeProfile::Python => "Python",
+            EscapeProfile::PythonRaw => "Python raw",
+            EscapeProfile::PythonBytes => "Python bytes",
+            EscapeProfile::Go => "Go",
+            EscapeProfile::CSharp => "C#",
+            EscapeProfile::Sql => "SQL",
+            EscapeProfile::ShellSingle => "Shell '...'",
+            EscapeProfile::ShellDouble => "Shell \"...\"",
+            EscapeProfile::ShellAnsiC => "Shell $'...'",
+            EscapeProfile::Regex => "Regex",
+            EscapeProfile::Csv => "CSV",
+            EscapeProfile::Html => "XML/HTML",
+            EscapeProfile::Url => "URL",
         }
     }
-    out
 }
 
-/// Inverse of `escape_backslash`. Supports the same sequences plus a
-/// conservative subset — unknown escapes are returned as an error.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct EscapeOptions {
+    pub profile: EscapeProfile,
+    pub unicode: bool, // Escape non-ASCII characters too, where the profile can
+    pub hex: bool,     // Numeric escapes instead of mnemonic ones: `\x0a` rather than `\n`
+}
+
+/// Escape `input` as the body of a string literal.
+///
+/// Control characters are always escaped. Profiles whose literals cannot
+/// hold some character at all, such as a bare `"` in a Python raw string,
+/// return an error pointing at it.
 #[universal_function]
-pub fn unescape_backslash(input: &str) -> Result<String, UtilityError> {
-    let mut out = String::with_capacity(input.len());
-    let mut chars = input.chars();
-    while let Some(c) = chars.next() {
-        if c != '\\' {
-            out.push(c);
-            continue;
-        }
-        match chars.next() {
-            Some('n') => out.push('\n'),
-            Some('r') => out.push('\r'),
-            Some('t') => out.push('\t'),
-            Some('0') => out.push('\0'),
-            Some('\\') => out.push('\\'),
-            Some('"') => out.push('"'),
-            Some('\'') => out.push('\''),
-            Some(other) => {
-                return Err(UtilityError::InvalidInput(format!(
-                    "Unknown escape sequence: \\{}",
-                    other
-                )))
-            }
-            None => {
-                return Err(UtilityError::InvalidInput(
-                    "Trailing backslash".to_string(),
-                ))
-            }
-        }
-    }
-    Ok(out)
+pub fn escape_backslash(
+    input: &str,
+    options: Option<EscapeOptions>,
+) -> Result<String, UtilityError> {
+    escape::escape(input, &options.unwrap_or_default())
+}
+
+/// Inverse of `escape_backslash`: read the body of a string literal.
+///
+/// Accepts every escape the profile's language does, not only the ones
+/// `escape_backslash` writes, so `\x41`, `\u{1F600}` or octal escapes decode
+/// wherever the language has them.
+#[universal_function]
+pub fn unescape_backslash(input: &str, profile: EscapeProfile) -> Result<String, UtilityError> {
+    unescape::unescape(input, profile)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn escape(input: &str, profile: EscapeProfile) -> String {
+        escape_backslash(
+            input,
+            Some(EscapeOptions {
+                profile,
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    fn escape_with(input: &str, profile: EscapeProfile, unicode: bool, hex: bool) -> String {
+        escape_backslash(
+            input,
+            Some(EscapeOptions {
+                profile,
+                unicode,
+                hex,
+            }),
+        )
+        .unwrap()
+    }
+
+    fn unescape(input: &str, profile: EscapeProfile) -> String {
+        unescape_backslash(input, profile).unwrap()
+    }
+
     #[test]
     fn round_trip() {
         let original = "line one\nline two\ttabbed \"quoted\" \\slash";
-        let escaped = escape_backslash(original);
-        let back = unescape_backslash(&escaped).unwrap();
+        let escaped = escape_backslash(original, None).unwrap();
+        let back = unescape_backslash(&escaped, EscapeProfile::Json).unwrap();
         assert_eq!(back, original);
     }
 
     #[test]
     fn rejects_unknown() {
-        assert!(unescape_backslash("\\q").is_err());
+        assert!(unescape_backslash("\\q", EscapeProfile::Json).is_err());
+    }
+
+    #[test]
+    fn round_trips_every_profile() {
+        let original = "tab\there \"quoted\" 'single' \\ $HOME `cmd` 50% a+b é 😀 \u{1}\u{7f}";
+        for profile in EscapeProfile::ALL {
+            let samples = match profile {
+                // Raw strings cannot hold a bare quote; bytes cannot hold
+                // non-ASCII text, only its UTF-8 encoding.
+                EscapeProfile::PythonRaw => vec!["C:\\path\\to\\file", "a\\\"b"],
+                _ => vec![original, "", "\0", "line\r\nbreak"],
+            };
+            for sample in samples {
+                for (unicode, hex) in [(false, false), (true, false), (false, true), (true, true)] {
+                    let escaped = escape_with(sample, profile, unicode, hex);
+                    assert_eq!(
+                        unescape_backslash(&escaped, profile).unwrap(),
+                        sample,
+                        "{:?} unicode={} hex={} via {:?}",
+                        profile,
+                        unicode,
+                        hex,
+                        escaped
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn escapes_per_language() {
+        use EscapeProfile::*;
+        let input = "é\n\u{1b}😀\"";
+        assert_eq!(escape(input, Json), "é\\n\\u001b😀\\\"");
+        assert_eq!(
+            escape_with(input, Json, true, false),
+            "\\u00e9\\n\\u001b\\ud83d\\ude00\\\""
+        );
+        assert_eq!(
+            escape_with(input, JavaScript, true, false),
+            "\\u00e9\\n\\x1b\\u{1f600}\\\""
+        );
+        assert_eq!(
+            escape_with(input, Rust, true, false),
+            "\\u{e9}\\n\\x1b\\u{1f600}\\\""
+        );
+        assert_eq!(
+            escape_with(input, C, true, false),
+            "\\u00e9\\n\\033\\U0001f600\\\""
+        );
+        assert_eq!(
+            escape_with(input, Python, true, false),
+            "\\xe9\\n\\x1b\\U0001f600\\\""
+        );
+        assert_eq!(
+            escape(input, PythonBytes),
+            "\\xc3\\xa9\\n\\x1b\\xf0\\x9f\\x98\\x80\\\""
+        );
+        assert_eq!(
+            escape_with(input, Java, true, true),
+            "\\u00e9\\n\\u001b\\ud83d\\ude00\\\""
+        );
+        assert_eq!(
+            escape_with(input, CSharp, false, true),
+            "é\\x000a\\x001b😀\\\""
+        );
+        assert_eq!(escape(input, ShellAnsiC), "é\\n\\e😀\"");
+        assert_eq!(escape("it's", Sql), "it''s");
+        assert_eq!(escape("it's", ShellSingle), "it'\\''s");
+        assert_eq!(escape("$HOME \"x\"", ShellDouble), "\\$HOME \\\"x\\\"");
+        assert_eq!(escape("a.b*c", Regex), "a\\.b\\*c");
+        assert_eq!(escape("a,\"b\"", Csv), "\"a,\"\"b\"\"\"");
+        assert_eq!(escape("plain", Csv), "plain");
+        assert_eq!(
+            escape("<a href='x'>é</a>", Html),
+            "&lt;a href=&#39;x&#39;&gt;é&lt;/a&gt;"
+        );
+        assert_eq!(escape_with("é", Html, true, true), "&#xE9;");
+        assert_eq!(escape_with("a b/é", Url, true, false), "a%20b%2F%C3%A9");
+    }
+
+    #[test]
+    fn hex_escapes_do_not_swallow_following_digits() {
+        assert_eq!(
+            escape_with("\u{1}A", EscapeProfile::C, false, true),
+            "\\x01\"\"A"
+        );
+        assert_eq!(escape("\u{1}7", EscapeProfile::C), "\\0017");
+        assert_eq!(
+            escape_with("\u{1}A", EscapeProfile::ShellAnsiC, false, true),
+            "\\x01'$'A"
+        );
+        assert_eq!(escape("\u{0}1", EscapeProfile::JavaScript), "\\x001");
+    }
+
+    #[test]
+    fn unescapes_numeric_escapes() {
+        use EscapeProfile::*;
+        assert_eq!(unescape("\\u00e9\\ud83d\\ude00", Json), "é😀");
+        assert_eq!(unescape("\\x41\\u{1F600}\\101", JavaScript), "A😀A");
+        assert_eq!(unescape("\\q", JavaScript), "q");
+        assert_eq!(unescape("\\x41\\u{1F6_00}", Rust), "A😀");
+        assert_eq!(unescape("\\303\\251\\xC3\\xA9\\u00e9", C), "ééé");
+        assert_eq!(unescape("\\uuu0041\\101\\s", Java), "AA ");
+        assert_eq!(
+            unescape("\\xe9\\N{LATIN SMALL LETTER E WITH ACUTE}\\d", Python),
+            "éé\\d"
+        );
+        assert_eq!(unescape("\\xc3\\xa9\\u00e9", PythonBytes), "é\\u00e9");
+        assert_eq!(unescape("\\303\\251\\U0001F600", Go), "é😀");
+        assert_eq!(unescape("\\x41\\x0041B", CSharp), "AAB");
+        assert_eq!(unescape("\\cA\\x41\\u263A\\q", ShellAnsiC), "\u{1}A☺\\q");
+        assert_eq!(unescape("a\\$b\\c", ShellDouble), "a$b\\c");
+        assert_eq!(unescape("\\x{1F600}\\.", Regex), "😀.");
+        assert_eq!(
+            unescape("&lt;&#233;&#xE9;&eacute;&nbsp;", Html),
+            "<ééé\u{a0}"
+        );
+        assert_eq!(unescape("a &b", Html), "a &b");
+        assert_eq!(unescape("%C3%A9+", Url), "é+");
+        assert_eq!(unescape("r\\d", PythonRaw), "r\\d");
+    }
+
+    #[test]
+    fn line_continuations() {
+        assert_eq!(unescape("a\\\nb", EscapeProfile::Python), "ab");
+        assert_eq!(unescape("a\\\n    b", EscapeProfile::Rust), "ab");
+        assert_eq!(unescape("a\\\r\nb", EscapeProfile::JavaScript), "ab");
+    }
+
+    #[test]
+    fn reports_errors_with_location() {
+        use EscapeProfile::*;
+        let cases = [
+            ("ab\\x41", Json, 2, "`\\x` is not a JSON escape"),
+            ("\\ud83d", Json, 0, "Unpaired surrogate"),
+            ("\\x80", Rust, 0, "out of range"),
+            ("x\\xC3z", C, 1, "not valid UTF-8"),
+            ("\\'", Go, 0, "`\\'` is not a Go escape"),
+            ("it's", Sql, 2, "Unescaped quote"),
+            ("\\d", Regex, 0, "character class"),
+            ("a.b", Regex, 1, "metacharacter"),
+            ("&bogus;", Html, 0, "Unknown entity"),
+            ("%G1", Url, 0, "Invalid percent escape"),
+            ("é", PythonBytes, 0, "ASCII"),
+            ("abc\\", Rust, 3, "Trailing backslash"),
+        ];
+        for (input, profile, offset, message) in cases {
+            let err = unescape_backslash(input, profile).unwrap_err();
+            let diagnostic = err.diagnostic().expect("diagnostic");
+            assert_eq!(diagnostic.offset, offset, "{:?} {}", profile, input);
+            assert!(
+                diagnostic.message.contains(message),
+                "{:?}: {}",
+                profile,
+                diagnostic.message
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unrepresentable() {
+        let err = escape_backslash(
+            "say \"hi\"",
+            Some(EscapeOptions {
+                profile: EscapeProfile::PythonRaw,
+                ..Default::default()
+            }),
+        )
+        .unwrap_err();
+        assert_eq!(err.diagnostic().unwrap().offset, 4);
     }
 }
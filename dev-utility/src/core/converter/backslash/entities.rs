@@ -0,0 +1,56 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Named character references.
+//!
+//! The full HTML5 list, shared with the HTML formatter's tokenizer. It also
+//! covers the five XML entities.
+
+use html5ever::data::NAMED_ENTITIES;
+
+/// The text `&name;` stands for.
+pub(super) fn lookup(name: &str) -> Option<String> {
+    let &(first, second) = NAMED_ENTITIES.get(format!("{};", name).as_str())?;
+    // Every prefix of a name is in the map too, standing for nothing.
+    if first == 0 {
+        return None;
+    }
+    [first, second]
+        .into_iter()
+        .filter(|&cp| cp != 0)
+        .map(char::from_u32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_up_the_html5_list() {
+        let found: Vec<Option<String>> = ["amp", "apos", "eacute", "Eacute", "hearts", "ThinSpace"]
+            .into_iter()
+            .map(lookup)
+            .collect();
+        assert_eq!(
+            found,
+            ["&", "'", "é", "É", "♥", "\u{2009}"].map(|s| Some(s.to_string()))
+        );
+        // Some stand for two characters.
+        assert_eq!(lookup("fjlig").as_deref(), Some("fj"));
+        assert_eq!(lookup("NotEqualTilde").as_deref(), Some("\u{2242}\u{338}"));
+    }
+
+    #[test]
+    fn rejects_unknown_names() {
+        // Names are case-sensitive, and a prefix of a name is not one.
+        for name in ["", "Amp", "am", "ampx", "amp;", "é"] {
+            assert_eq!(lookup(name), None, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,448 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+use std::fmt::Write;
+
+use super::{EscapeOptions, EscapeProfile};
+use crate::error::{Diagnostic, UtilityError};
+
+use EscapeProfile::*;
+
+pub(super) fn escape(input: &str, options: &EscapeOptions) -> Result<String, UtilityError> {
+    Ok(match options.profile {
+        Sql => input.replace('\'', "''"),
+        ShellSingle => input.replace('\'', "'\\''"),
+        ShellDouble => shell_double(input),
+        PythonRaw => python_raw(input)?,
+        Csv => csv(input),
+        Regex => regex(input, options),
+        Html => html(input, options),
+        Url => url(input, options),
+        _ => backslash(input, options),
+    })
+}
+
+/// Languages with C-style backslash escapes.
+fn backslash(input: &str, options: &EscapeOptions) -> String {
+    let profile = options.profile;
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        let next = chars.peek().copied();
+        if c == '\\' || c == quote(profile) {
+            out.push('\\');
+            out.push(c);
+            continue;
+        }
+        if let Some(m) = mnemonic(profile, c, next) {
+            // A Java `\u000a` is turned into a real line break before the
+            // literal is even read, so those two keep their mnemonics.
+            if !options.hex || (profile == Java && matches!(c, '\n' | '\r')) {
+                out.push('\\');
+                out.push(m);
+                continue;
+            }
+        }
+        let special =
+            c.is_control() || (profile == JavaScript && matches!(c, '\u{2028}' | '\u{2029}'));
+        // Non-ASCII text stays as it is unless asked for, except in bytes
+        // literals, which cannot hold it.
+        let literal = !special && (c.is_ascii() || (!options.unicode && profile != PythonBytes));
+        if literal {
+            out.push(c);
+        } else {
+            numeric(profile, c, next, options.hex, &mut out);
+        }
+    }
+    out
+}
+
+/// The quote that delimits the profile's literals.
+fn quote(profile: EscapeProfile) -> char {
+    match profile {
+        ShellAnsiC => '\'',
+        _ => '"',
+    }
+}
+
+/// The letter of the mnemonic escape for `c`, such as `n` for `\n`.
+fn mnemonic(profile: EscapeProfile, c: char, next: Option<char>) -> Option<char> {
+    let m = match c {
+        '\n' => 'n',
+        '\r' => 'r',
+        '\t' => 't',
+        '\u{8}' if profile != Rust => 'b',
+        '\u{c}' if profile != Rust => 'f',
+        '\u{b}' if !matches!(profile, Json | Java | Rust) => 'v',
+        '\u{7}' if matches!(profile, C | Python | PythonBytes | Go | CSharp | ShellAnsiC) => 'a',
+        '\u{1b}' if profile == ShellAnsiC => 'e',
+        // `\0` followed by a digit would read as an octal escape.
+        '\0' if matches!(profile, Rust | CSharp) => '0',
+        '\0' if profile == JavaScript && !next.is_some_and(|n| n.is_ascii_digit()) => '0',
+        '\0' if profile == C && !next.is_some_and(|n| ('0'..='7').contains(&n)) => '0',
+        _ => return None,
+    };
+    Some(m)
+}
+
+/// Write `c` as a numeric escape in the profile's syntax.
+fn numeric(profile: EscapeProfile, c: char, next: Option<char>, hex: bool, out: &mut String) {
+    let cp = c as u32;
+    let hex_follows = next.is_some_and(|n| n.is_ascii_hexdigit());
+    match profile {
+        Json | Java => utf16(c, out),
+        JavaScript if cp <= 0xFF && c.is_control() => push(out, format_args!("\\x{:02x}", cp)),
+        JavaScript if cp <= 0xFFFF => push(out, format_args!("\\u{:04x}", cp)),
+        JavaScript | Rust if cp >= 0x80 => push(out, format_args!("\\u{{{:x}}}", cp)),
+        Rust => push(out, format_args!("\\x{:02x}", cp)),
+        Python if cp <= 0xFF => push(out, format_args!("\\x{:02x}", cp)),
+        Go if cp < 0x80 => push(out, format_args!("\\x{:02x}", cp)),
+        CSharp if hex && c.is_control() => push(out, format_args!("\\x{:04x}", cp)),
+        Python | Go | CSharp => wide(c, out),
+        PythonBytes => {
+            for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                push(out, format_args!("\\x{:02x}", byte));
+            }
+        }
+        // UCNs cannot name C1 controls, so those go out as their bytes.
+        C if cp >= 0xA0 => wide(c, out),
+        ShellAnsiC if cp >= 0x80 => wide(c, out),
+        C | ShellAnsiC => {
+            let mut buf = [0; 4];
+            let bytes = c.encode_utf8(&mut buf).as_bytes();
+            for (i, byte) in bytes.iter().enumerate() {
+                if hex {
+                    push(out, format_args!("\\x{:02x}", byte));
+                    // Hex escapes run on for as many digits as follow, so
+                    // close the literal and open a new one.
+                    if hex_follows && i == bytes.len() - 1 {
+                        out.push_str(if profile == C { "\"\"" } else { "'$'" });
+                    }
+                } else {
+                    push(out, format_args!("\\{:03o}", byte));
+                }
+            }
+        }
+        _ => out.push(c),
+    }
+}
+
+/// `\uXXXX`, with astral characters as a surrogate pair.
+fn utf16(c: char, out: &mut String) {
+    for unit in c.encode_utf16(&mut [0; 2]) {
+        push(out, format_args!("\\u{:04x}", unit));
+    }
+}
+
+/// `\uXXXX` or `\UXXXXXXXX`.
+fn wide(c: char, out: &mut String) {
+    let cp = c as u32;
+    if cp <= 0xFFFF {
+        push(out, format_args!("\\u{:04x}", cp));
+    } else {
+        push(out, format_args!("\\U{:08x}", cp));
+    }
+}
+
+fn push(out: &mut String, args: std::fmt::Arguments) {
+    let _ = out.write_fmt(args);
+}
+
+fn shell_double(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '\\' | '"' | '$' | '`') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Raw strings have no escapes; a quote only fits after a backslash, which
+/// then stays in the string.
+fn python_raw(input: &str) -> Result<String, UtilityError> {
+    let mut backslashes = 0;
+    for (i, c) in input.char_indices() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' if backslashes % 2 == 0 => {
+                return Err(
+                    Diagnostic::new(input, i, "A raw string cannot contain a bare `\"`")
+                        .with_hint("Use a regular string, or single quotes around the raw string")
+                        .into(),
+                )
+            }
+            '\n' | '\r' => {
+                return Err(Diagnostic::new(input, i, "A raw string cannot span lines")
+                    .with_hint("Use a triple-quoted raw string")
+                    .into())
+            }
+            _ => backslashes = 0,
+        }
+    }
+    if backslashes % 2 == 1 {
+        return Err(Diagnostic::new(
+            input,
+            input.len() - 1,
+            "A raw string cannot end with an odd number of backslashes",
+        )
+        .into());
+    }
+    Ok(input.to_string())
+}
+
+fn csv(input: &str) -> String {
+    let needs_quotes =
+        input.contains([',', '"', '\n', '\r']) || input.starts_with(' ') || input.ends_with(' ');
+    if needs_quotes {
+        format!("\"{}\"", input.replace('"', "\"\""))
+    } else {
+        input.to_string()
+    }
+}
+
+fn regex(input: &str, options: &EscapeOptions) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        let cp = c as u32;
+        let m = match c {
+            '\n' => Some('n'),
+            '\r' => Some('r'),
+            '\t' => Some('t'),
+            '\u{7}' => Some('a'),
+            '\u{b}' => Some('v'),
+            '\u{c}' => Some('f'),
+            _ => None,
+        };
+        if is_regex_meta(c) || c == '#' || c == '&' || c == '-' || c == '~' {
+            out.push('\\');
+            out.push(c);
+        } else if let Some(m) = m.filter(|_| !options.hex) {
+            out.push('\\');
+            out.push(m);
+        } else if c.is_ascii_control() {
+            push(&mut out, format_args!("\\x{:02x}", cp));
+        } else if !c.is_ascii() && (c.is_control() || options.unicode) {
+            push(&mut out, format_args!("\\x{{{:x}}}", cp));
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Characters with a meaning of their own outside a character class.
+pub(super) fn is_regex_meta(c: char) -> bool {
+    matches!(
+        c,
+        '\\' | '.' | '+' | '*' | '?' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$'
+    )
+}
+
+fn html(input: &str, options: &EscapeOptions) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            // `&apos;` is not an HTML 4 entity.
+            c if c == '\'' || (!c.is_ascii() && options.unicode) => {
+                if options.hex {
+                    push(&mut out, format_args!("&#x{:X};", c as u32));
+                } else {
+                    push(&mut out, format_args!("&#{};", c as u32));
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn url(input: &str, options: &EscapeOptions) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        let unreserved = c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~');
+        // Without `unicode`, non-ASCII text stays readable, IRI style.
+        if unreserved || (!c.is_ascii() && !c.is_control() && !options.unicode) {
+            out.push(c);
+        } else {
+            for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                push(&mut out, format_args!("%{:02X}", byte));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(input: &str, profile: EscapeProfile, unicode: bool, hex: bool) -> String {
+        let options = EscapeOptions {
+            profile,
+            unicode,
+            hex,
+        };
+        escape(input, &options).unwrap()
+    }
+
+    const SAMPLE: &str = "\"'\\\t\u{7}\u{b}\u{1b}\0é😀";
+
+    #[test]
+    fn mnemonics_per_profile() {
+        let cases = [
+            (Json, "\\\"'\\\\\\t\\u0007\\u000b\\u001b\\u0000é😀"),
+            (JavaScript, "\\\"'\\\\\\t\\x07\\v\\x1b\\0é😀"),
+            (Rust, "\\\"'\\\\\\t\\x07\\x0b\\x1b\\0é😀"),
+            (C, "\\\"'\\\\\\t\\a\\v\\033\\0é😀"),
+            (Java, "\\\"'\\\\\\t\\u0007\\u000b\\u001b\\u0000é😀"),
+            (Python, "\\\"'\\\\\\t\\a\\v\\x1b\\x00é😀"),
+            (
+                PythonBytes,
+                "\\\"'\\\\\\t\\a\\v\\x1b\\x00\\xc3\\xa9\\xf0\\x9f\\x98\\x80",
+            ),
+            (Go, "\\\"'\\\\\\t\\a\\v\\x1b\\x00é😀"),
+            (CSharp, "\\\"'\\\\\\t\\a\\v\\u001b\\0é😀"),
+            (ShellAnsiC, "\"\\'\\\\\\t\\a\\v\\e\\000é😀"),
+            (Regex, "\"'\\\\\\t\\a\\v\\x1b\\x00é😀"),
+        ];
+        for (profile, expected) in cases {
+            assert_eq!(
+                run(SAMPLE, profile, false, false),
+                expected,
+                "{:?}",
+                profile
+            );
+        }
+    }
+
+    #[test]
+    fn numeric_escapes_per_profile() {
+        let cases = [
+            (
+                Json,
+                "\\\"'\\\\\\u0009\\u0007\\u000b\\u001b\\u0000\\u00e9\\ud83d\\ude00",
+            ),
+            (
+                JavaScript,
+                "\\\"'\\\\\\x09\\x07\\x0b\\x1b\\x00\\u00e9\\u{1f600}",
+            ),
+            (Rust, "\\\"'\\\\\\x09\\x07\\x0b\\x1b\\x00\\u{e9}\\u{1f600}"),
+            (C, "\\\"'\\\\\\x09\\x07\\x0b\\x1b\\x00\\u00e9\\U0001f600"),
+            (Python, "\\\"'\\\\\\x09\\x07\\x0b\\x1b\\x00\\xe9\\U0001f600"),
+            (Go, "\\\"'\\\\\\x09\\x07\\x0b\\x1b\\x00\\u00e9\\U0001f600"),
+            (
+                CSharp,
+                "\\\"'\\\\\\x0009\\x0007\\x000b\\x001b\\x0000\\u00e9\\U0001f600",
+            ),
+            (
+                ShellAnsiC,
+                "\"\\'\\\\\\x09\\x07\\x0b\\x1b\\x00\\u00e9\\U0001f600",
+            ),
+            (Regex, "\"'\\\\\\x09\\x07\\x0b\\x1b\\x00\\x{e9}\\x{1f600}"),
+        ];
+        for (profile, expected) in cases {
+            assert_eq!(run(SAMPLE, profile, true, true), expected, "{:?}", profile);
+        }
+        // Java keeps mnemonics for line breaks, which `\u000a` would end.
+        assert_eq!(run("\n\t", Java, false, true), "\\n\\u0009");
+    }
+
+    #[test]
+    fn digits_after_escapes() {
+        let input = "\u{0}7\u{1}a";
+        assert_eq!(run(input, C, false, false), "\\0007\\001a");
+        assert_eq!(run(input, C, false, true), "\\x00\"\"7\\x01\"\"a");
+        assert_eq!(run(input, ShellAnsiC, false, false), "\\0007\\001a");
+        assert_eq!(run(input, ShellAnsiC, false, true), "\\x00'$'7\\x01'$'a");
+        // Neither language has octal escapes, so a plain `\0` is safe.
+        assert_eq!(run(input, Rust, false, false), "\\07\\x01a");
+        assert_eq!(run(input, CSharp, false, false), "\\07\\u0001a");
+        assert_eq!(run(input, JavaScript, false, false), "\\x007\\x01a");
+    }
+
+    #[test]
+    fn line_separators_and_c1_controls() {
+        let input = "\u{85}\u{2028}";
+        assert_eq!(run(input, JavaScript, false, false), "\\x85\\u2028");
+        assert_eq!(run(input, Json, false, false), "\\u0085\u{2028}");
+        assert_eq!(run(input, C, false, false), "\\302\\205\u{2028}");
+        assert_eq!(run(input, C, true, true), "\\xc2\\x85\\u2028");
+        assert_eq!(run(input, Rust, false, false), "\\u{85}\u{2028}");
+        assert_eq!(run(input, Regex, true, false), "\\x{85}\\x{2028}");
+    }
+
+    #[test]
+    fn quoting_profiles() {
+        assert_eq!(
+            run(SAMPLE, Sql, true, true),
+            "\"''\\\t\u{7}\u{b}\u{1b}\0é😀"
+        );
+        assert_eq!(run("it's", ShellSingle, false, false), "it'\\''s");
+        assert_eq!(
+            run("\\$`\"'!", ShellDouble, false, false),
+            "\\\\\\$\\`\\\"'!"
+        );
+        assert_eq!(run(" a", Csv, false, false), "\" a\"");
+        assert_eq!(run("a\r", Csv, false, false), "\"a\r\"");
+        assert_eq!(run("a;b", Csv, false, false), "a;b");
+        assert_eq!(run("C:\\dir\\f", PythonRaw, false, false), "C:\\dir\\f");
+    }
+
+    #[test]
+    fn raw_strings_reject_what_they_cannot_hold() {
+        let error = |input: &str| {
+            let options = EscapeOptions {
+                profile: PythonRaw,
+                ..Default::default()
+            };
+            let err = escape(input, &options).unwrap_err();
+            let d = err.diagnostic().unwrap();
+            (d.offset, d.message.clone())
+        };
+        assert_eq!(error("a\"").0, 1);
+        assert_eq!(error("a\nb").1, "A raw string cannot span lines");
+        assert_eq!(
+            error("a\\\\\\").1,
+            "A raw string cannot end with an odd number of backslashes"
+        );
+        // An escaped quote keeps its backslash but is allowed.
+        assert_eq!(run("a\\\"", PythonRaw, false, false), "a\\\"");
+    }
+
+    #[test]
+    fn markup_and_percent_encoding() {
+        assert_eq!(
+            run("<'\"&>é😀", Html, false, false),
+            "&lt;&#39;&quot;&amp;&gt;é😀"
+        );
+        assert_eq!(
+            run("<'\"&>é😀", Html, true, true),
+            "&lt;&#x27;&quot;&amp;&gt;&#xE9;&#x1F600;"
+        );
+        assert_eq!(run("é😀", Html, true, false), "&#233;&#128512;");
+
+        let input = "a b/?#%é~-_.+";
+        assert_eq!(run(input, Url, false, false), "a%20b%2F%3F%23%25é~-_.%2B");
+        assert_eq!(
+            run(input, Url, true, false),
+            "a%20b%2F%3F%23%25%C3%A9~-_.%2B"
+        );
+        // Controls are encoded even when other text is left readable.
+        assert_eq!(
+            run("\0\u{85}\u{2028}", Url, false, false),
+            "%00%C2%85\u{2028}"
+        );
+        assert_eq!(run("", Url, true, true), "");
+    }
+}
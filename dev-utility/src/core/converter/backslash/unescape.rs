@@ -0,0 +1,834 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+use super::entities;
+use super::escape::is_regex_meta;
+use super::EscapeProfile;
+use crate::core::converter::string_inspector::names;
+use crate::error::{Diagnostic, UtilityError};
+
+use EscapeProfile::*;
+
+pub(super) fn unescape(input: &str, profile: EscapeProfile) -> Result<String, UtilityError> {
+    let mut reader = Reader::new(input);
+    match profile {
+        PythonRaw => return Ok(input.to_string()),
+        Sql => sql(&mut reader)?,
+        Csv => csv(&mut reader)?,
+        ShellSingle => shell_single(&mut reader)?,
+        Html => html(&mut reader)?,
+        Url => url(&mut reader)?,
+        _ => backslash(&mut reader, profile)?,
+    }
+    reader.finish()
+}
+
+/// Walks the input, collecting output bytes. Escapes that stand for raw
+/// bytes (C `\xff`, Go octal, percent-encoding) may split a UTF-8
+/// sequence, so the output is only checked once it is complete.
+struct Reader<'a> {
+    src: &'a str,
+    pos: usize,
+    out: Vec<u8>,
+    bytes: Vec<(usize, usize)>, // Output and input offsets of byte escapes
+}
+
+impl<'a> Reader<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            pos: 0,
+            out: Vec::with_capacity(src.len()),
+            bytes: Vec::new(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let matched = self.peek() == Some(c);
+        if matched {
+            self.pos += c.len_utf8();
+        }
+        matched
+    }
+
+    fn push(&mut self, c: char) {
+        self.out
+            .extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+
+    fn push_str(&mut self, s: &str) {
+        self.out.extend_from_slice(s.as_bytes());
+    }
+
+    fn push_byte(&mut self, byte: u8, at: usize) {
+        self.bytes.push((self.out.len(), at));
+        self.out.push(byte);
+    }
+
+    /// An error spanning from `at` to the current position.
+    fn error(&self, at: usize, message: impl Into<String>) -> UtilityError {
+        Diagnostic::new(self.src, at, message)
+            .with_length(self.pos.max(at + 1).min(self.src.len()) - at)
+            .into()
+    }
+
+    /// Between `min` and `max` digits in `radix`.
+    fn digits(
+        &mut self,
+        radix: u32,
+        min: usize,
+        max: usize,
+        at: usize,
+    ) -> Result<u32, UtilityError> {
+        let mut value = 0u32;
+        let mut count = 0;
+        while count < max {
+            match self.peek().and_then(|c| c.to_digit(radix)) {
+                Some(digit) => {
+                    value = value.saturating_mul(radix).saturating_add(digit);
+                    count += 1;
+                    self.pos += 1;
+                }
+                None => break,
+            }
+        }
+        if count < min {
+            let kind = if radix == 16 { "hex" } else { "octal" };
+            let message = if min == max {
+                format!("Expected {} {} digits", min, kind)
+            } else {
+                format!("Expected at least {} {} digit", min, kind)
+            };
+            return Err(self.error(at, message));
+        }
+        Ok(value)
+    }
+
+    /// Hex digits between braces, as in `\u{1F600}`. Rust also allows
+    /// underscores after the first digit.
+    fn braced(&mut self, at: usize, underscores: bool) -> Result<u32, UtilityError> {
+        let start = self.pos;
+        let end = self.src[start..]
+            .find('}')
+            .map(|i| start + i)
+            .ok_or_else(|| self.error(at, "Unterminated `{` in escape"))?;
+        let digits: String = self.src[start..end]
+            .chars()
+            .filter(|&c| !(underscores && c == '_'))
+            .collect();
+        self.pos = end + 1;
+        if digits.is_empty() || digits.len() > 6 || self.src[start..].starts_with('_') {
+            return Err(self.error(at, "Expected 1 to 6 hex digits between braces"));
+        }
+        u32::from_str_radix(&digits, 16).map_err(|_| self.error(at, "Invalid hex digits in escape"))
+    }
+
+    /// A character name between braces, as in `\N{BULLET}`.
+    fn braced_name(&mut self, at: usize) -> Result<char, UtilityError> {
+        let start = self.pos;
+        let end = self.src[start..]
+            .find('}')
+            .map(|i| start + i)
+            .ok_or_else(|| self.error(at, "Unterminated `{` in escape"))?;
+        let name = &self.src[start..end];
+        self.pos = end + 1;
+        names::lookup(name)
+            .ok_or_else(|| self.error(at, format!("Unknown character name `{}`", name)))
+    }
+
+    fn code_point(&mut self, cp: u32, at: usize) -> Result<(), UtilityError> {
+        match char::from_u32(cp) {
+            Some(c) => {
+                self.push(c);
+                Ok(())
+            }
+            None if (0xD800..=0xDFFF).contains(&cp) => {
+                Err(self.error(at, format!("Unpaired surrogate U+{:04X}", cp)))
+            }
+            None => Err(self.error(at, format!("U+{:X} is not a valid code point", cp))),
+        }
+    }
+
+    /// A UTF-16 code unit, pairing a high surrogate with the `\uXXXX` that
+    /// follows it.
+    fn utf16(&mut self, unit: u32, at: usize, repeat_u: bool) -> Result<(), UtilityError> {
+        if !(0xD800..=0xDBFF).contains(&unit) {
+            return self.code_point(unit, at);
+        }
+        let save = self.pos;
+        if self.eat('\\') && self.eat('u') {
+            while repeat_u && self.eat('u') {}
+            if let Ok(low @ 0xDC00..=0xDFFF) = self.digits(16, 4, 4, at) {
+                let cp = 0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00);
+                return self.code_point(cp, at);
+            }
+        }
+        self.pos = save;
+        Err(self.error(at, format!("Unpaired surrogate U+{:04X}", unit)))
+    }
+
+    fn finish(self) -> Result<String, UtilityError> {
+        match String::from_utf8(self.out) {
+            Ok(text) => Ok(text),
+            Err(e) => {
+                let bad = e.utf8_error().valid_up_to();
+                let at = self
+                    .bytes
+                    .iter()
+                    .find(|&&(out, _)| out >= bad)
+                    .map_or(0, |&(_, at)| at);
+                Err(
+                    Diagnostic::new(self.src, at, "Escaped bytes are not valid UTF-8")
+                        .with_hint("The escapes spell out bytes that do not decode as UTF-8 text")
+                        .into(),
+                )
+            }
+        }
+    }
+}
+
+/// Languages with C-style backslash escapes.
+fn backslash(r: &mut Reader, profile: EscapeProfile) -> Result<(), UtilityError> {
+    while let Some(c) = r.bump() {
+        let at = r.pos - c.len_utf8();
+        // Adjacent literals concatenate; `escape_backslash` splits one
+        // after a hex escape that would otherwise run into the next digit.
+        if (profile == C && c == '"' && r.eat('"'))
+            || (profile == ShellAnsiC && c == '\'' && r.src[r.pos..].starts_with("$'"))
+        {
+            if profile == ShellAnsiC {
+                r.pos += 2;
+            }
+            continue;
+        }
+        if c != '\\' {
+            if profile == PythonBytes && !c.is_ascii() {
+                return Err(r.error(at, "Bytes literals can only contain ASCII characters"));
+            }
+            if profile == Regex && is_regex_meta(c) {
+                return Err(r.error(
+                    at,
+                    format!("`{}` is a regex metacharacter and does not match itself", c),
+                ));
+            }
+            r.push(c);
+            continue;
+        }
+        let Some(e) = r.bump() else {
+            if profile == ShellDouble {
+                r.push('\\');
+                break;
+            }
+            return Err(r.error(at, "Trailing backslash"));
+        };
+        escape_sequence(r, profile, e, at)?;
+    }
+    Ok(())
+}
+
+/// The escape after a backslash at `at`, whose first character `e` has
+/// been read.
+fn escape_sequence(
+    r: &mut Reader,
+    profile: EscapeProfile,
+    e: char,
+    at: usize,
+) -> Result<(), UtilityError> {
+    if let Some(c) = simple(profile, e, r.peek()) {
+        r.push(c);
+        return Ok(());
+    }
+    match (profile, e) {
+        // Line continuations
+        (JavaScript | C | Java | Python | PythonBytes | ShellDouble, '\n') => {}
+        (JavaScript | C | Java | Python | PythonBytes | ShellDouble, '\r') => {
+            r.eat('\n');
+        }
+        (Rust, '\n' | '\r') => {
+            while r.peek().is_some_and(char::is_whitespace) {
+                r.bump();
+            }
+        }
+
+        (JavaScript | Python, 'x') => {
+            let cp = r.digits(16, 2, 2, at)?;
+            r.code_point(cp, at)?;
+        }
+        // `\x7F`, `\u007F`, `\U0000007F`, or any of them with braces.
+        (Regex, 'x' | 'u' | 'U') => {
+            let cp = if r.eat('{') {
+                r.braced(at, false)?
+            } else {
+                let n = match e {
+                    'x' => 2,
+                    'u' => 4,
+                    _ => 8,
+                };
+                r.digits(16, n, n, at)?
+            };
+            r.code_point(cp, at)?;
+        }
+        (Rust, 'x') => {
+            let cp = r.digits(16, 2, 2, at)?;
+            if cp > 0x7F {
+                return Err(r.error(
+                    at,
+                    format!(
+                        "`\\x{:02x}` is out of range; Rust allows `\\x00` to `\\x7f`",
+                        cp
+                    ),
+                ));
+            }
+            r.code_point(cp, at)?;
+        }
+        (C, 'x') => {
+            let value = r.digits(16, 1, usize::MAX, at)?;
+            let byte = u8::try_from(value)
+                .map_err(|_| r.error(at, "Hex escape is out of range for a byte"))?;
+            r.push_byte(byte, at);
+        }
+        (PythonBytes | Go, 'x') => {
+            let byte = r.digits(16, 2, 2, at)?;
+            r.push_byte(byte as u8, at);
+        }
+        (ShellAnsiC, 'x') => {
+            let byte = r.digits(16, 1, 2, at)?;
+            r.push_byte(byte as u8, at);
+        }
+        (CSharp, 'x') => {
+            let unit = r.digits(16, 1, 4, at)?;
+            r.utf16(unit, at, false)?;
+        }
+
+        (JavaScript, 'u') if r.eat('{') => {
+            let cp = r.braced(at, false)?;
+            r.code_point(cp, at)?;
+        }
+        (Rust, 'u') => {
+            if !r.eat('{') {
+                return Err(r.error(at, "Expected `{` after `\\u`"));
+            }
+            let cp = r.braced(at, true)?;
+            r.code_point(cp, at)?;
+        }
+        (Json | JavaScript | CSharp, 'u') => {
+            let unit = r.digits(16, 4, 4, at)?;
+            r.utf16(unit, at, false)?;
+        }
+        (Java, 'u') => {
+            while r.eat('u') {}
+            let unit = r.digits(16, 4, 4, at)?;
+            r.utf16(unit, at, true)?;
+        }
+        (C | Python | Go, 'u') => {
+            let cp = r.digits(16, 4, 4, at)?;
+            r.code_point(cp, at)?;
+        }
+        (C | Python | Go | CSharp, 'U') => {
+            let cp = r.digits(16, 8, 8, at)?;
+            r.code_point(cp, at)?;
+        }
+        (ShellAnsiC, 'u') => {
+            let cp = r.digits(16, 1, 4, at)?;
+            r.code_point(cp, at)?;
+        }
+        (ShellAnsiC, 'U') => {
+            let cp = r.digits(16, 1, 8, at)?;
+            r.code_point(cp, at)?;
+        }
+        (Python, 'N') => {
+            if !r.eat('{') {
+                return Err(r.error(at, "Expected `{` after `\\N`"));
+            }
+            let c = r.braced_name(at)?;
+            r.push(c);
+        }
+        (ShellAnsiC, 'c') => match r.bump() {
+            Some(c) if c.is_ascii() => r.push(char::from(c as u8 & 0x1F)),
+            _ => return Err(r.error(at, "Expected an ASCII character after `\\c`")),
+        },
+
+        // Octal
+        (C | PythonBytes | ShellAnsiC, '0'..='7') => {
+            r.pos -= 1;
+            let value = r.digits(8, 1, 3, at)?;
+            let byte = u8::try_from(value)
+                .map_err(|_| r.error(at, "Octal escape is out of range for a byte"))?;
+            r.push_byte(byte, at);
+        }
+        (Go, '0'..='7') => {
+            r.pos -= 1;
+            let value = r.digits(8, 3, 3, at)?;
+            let byte = u8::try_from(value)
+                .map_err(|_| r.error(at, "Octal escape is out of range for a byte"))?;
+            r.push_byte(byte, at);
+        }
+        (Python, '0'..='7') => {
+            r.pos -= 1;
+            let cp = r.digits(8, 1, 3, at)?;
+            r.code_point(cp, at)?;
+        }
+        // Java and legacy JavaScript: up to `\377`.
+        (Java | JavaScript, '0'..='7') => {
+            r.pos -= 1;
+            let max = if e <= '3' { 3 } else { 2 };
+            let cp = r.digits(8, 1, max, at)?;
+            r.code_point(cp, at)?;
+        }
+
+        (Regex, _) if e.is_ascii_punctuation() => r.push(e),
+        (Regex, _) if e.is_ascii_alphanumeric() => {
+            return Err(r.error(
+                at,
+                format!(
+                    "`\\{}` matches a character class or position, not a literal character",
+                    e
+                ),
+            ))
+        }
+        // JavaScript drops the backslash from unknown escapes.
+        (JavaScript, _) => r.push(e),
+        // These keep it.
+        (Python | PythonBytes | ShellDouble | ShellAnsiC, _) => {
+            r.push('\\');
+            r.push(e);
+        }
+        _ => return Err(r.error(at, format!("`\\{}` is not a {} escape", e, profile.label()))),
+    }
+    Ok(())
+}
+
+/// Escapes that stand for a single fixed character.
+fn simple(profile: EscapeProfile, e: char, next: Option<char>) -> Option<char> {
+    // Inside double quotes only these lose their backslash.
+    if profile == ShellDouble {
+        return matches!(e, '\\' | '"' | '$' | '`').then_some(e);
+    }
+    let c = match e {
+        '\\' => '\\',
+        '"' => '"',
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        '\'' if !matches!(profile, Json | Go) => '\'',
+        'b' if !matches!(profile, Rust | Regex) => '\u{8}',
+        'f' if profile != Rust => '\u{c}',
+        'v' if !matches!(profile, Json | Java | Rust) => '\u{b}',
+        'a' if matches!(
+            profile,
+            C | Python | PythonBytes | Go | CSharp | ShellAnsiC | Regex
+        ) =>
+        {
+            '\u{7}'
+        }
+        'e' if matches!(profile, CSharp | ShellAnsiC) => '\u{1b}',
+        'E' if profile == ShellAnsiC => '\u{1b}',
+        '?' if matches!(profile, C | ShellAnsiC) => '?',
+        '/' if profile == Json => '/',
+        's' if profile == Java => ' ',
+        '0' if matches!(profile, Rust | CSharp) => '\0',
+        '0' if profile == JavaScript && !next.is_some_and(|n| n.is_ascii_digit()) => '\0',
+        _ => return None,
+    };
+    Some(c)
+}
+
+/// SQL: the quote is written twice.
+fn sql(r: &mut Reader) -> Result<(), UtilityError> {
+    while let Some(c) = r.bump() {
+        if c == '\'' && !r.eat('\'') {
+            let at = r.pos - 1;
+            return Err(Diagnostic::new(r.src, at, "Unescaped quote")
+                .with_hint("Write it as `''`")
+                .into());
+        }
+        r.push(c);
+    }
+    Ok(())
+}
+
+/// Single-quoted shell words: `'\''` closes the quote, adds an escaped
+/// quote and reopens it.
+fn shell_single(r: &mut Reader) -> Result<(), UtilityError> {
+    while let Some(c) = r.bump() {
+        if c == '\'' {
+            let at = r.pos - 1;
+            if !r.src[r.pos..].starts_with("\\''") {
+                return Err(Diagnostic::new(r.src, at, "Unescaped quote")
+                    .with_hint("Write it as `'\\''`")
+                    .into());
+            }
+            r.pos += 3;
+        }
+        r.push(c);
+    }
+    Ok(())
+}
+
+/// A CSV field, quoted or not.
+fn csv(r: &mut Reader) -> Result<(), UtilityError> {
+    if !r.eat('"') {
+        r.push_str(r.src);
+        r.pos = r.src.len();
+        return Ok(());
+    }
+    while let Some(c) = r.bump() {
+        if c == '"' {
+            if r.eat('"') {
+                r.push('"');
+            } else if r.peek().is_some() {
+                let at = r.pos - 1;
+                return Err(
+                    Diagnostic::new(r.src, at, "Unescaped quote inside a quoted field")
+                        .with_hint("Write it as `\"\"`")
+                        .into(),
+                );
+            } else {
+                return Ok(());
+            }
+        } else {
+            r.push(c);
+        }
+    }
+    Err(Diagnostic::new(r.src, 0, "Quoted field is not closed").into())
+}
+
+/// Character references. An `&` that does not start one is kept, as HTML
+/// parsers do.
+fn html(r: &mut Reader) -> Result<(), UtilityError> {
+    while let Some(c) = r.bump() {
+        let at = r.pos - 1;
+        if c != '&' {
+            r.push(c);
+            continue;
+        }
+        let rest = &r.src[r.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '#'))
+            .unwrap_or(rest.len());
+        if len == 0 || !rest[len..].starts_with(';') {
+            r.push('&');
+            continue;
+        }
+        let name = &rest[..len];
+        r.pos += len + 1;
+        let numeric = name
+            .strip_prefix('#')
+            .map(|n| match n.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16),
+                None => n.parse(),
+            });
+        match numeric {
+            Some(Ok(cp)) => r.code_point(cp, at)?,
+            Some(Err(_)) => return Err(r.error(at, "Invalid numeric character reference")),
+            None => match entities::lookup(name) {
+                Some(text) => r.push_str(&text),
+                None => return Err(r.error(at, format!("Unknown entity `&{};`", name))),
+            },
+        }
+    }
+    Ok(())
+}
+
+fn url(r: &mut Reader) -> Result<(), UtilityError> {
+    while let Some(c) = r.bump() {
+        let at = r.pos - 1;
+        if c != '%' {
+            r.push(c);
+            continue;
+        }
+        let hex = r.src.get(r.pos..r.pos + 2).unwrap_or_default();
+        match u8::from_str_radix(hex, 16) {
+            Ok(byte) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                r.pos += 2;
+                r.push_byte(byte, at);
+            }
+            _ => {
+                return Err(Diagnostic::new(r.src, at, "Invalid percent escape")
+                    .with_hint(
+                        "`%` must be followed by two hex digits; write a literal `%` as `%25`",
+                    )
+                    .into())
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(input: &str, profile: EscapeProfile) -> String {
+        unescape(input, profile).unwrap()
+    }
+
+    /// Offset, length and message of the error.
+    fn error(input: &str, profile: EscapeProfile) -> (usize, usize, String) {
+        let err = unescape(input, profile).unwrap_err();
+        let d = err.diagnostic().expect("diagnostic");
+        (d.offset, d.length, d.message.clone())
+    }
+
+    #[test]
+    fn octal_escapes() {
+        // These write bytes, which then have to spell UTF-8...
+        for profile in [C, PythonBytes, ShellAnsiC] {
+            assert_eq!(run("\\101\\0\\7\\303\\251", profile), "A\0\u{7}é");
+            assert_eq!(
+                error("\\377", profile).2,
+                "Escaped bytes are not valid UTF-8"
+            );
+        }
+        // ...and these code points.
+        for profile in [Python, Java, JavaScript] {
+            assert_eq!(run("\\101\\0\\7\\377", profile), "A\0\u{7}ÿ");
+        }
+        // Bytes above `\377` do not exist; Python reads code points.
+        assert_eq!(
+            error("\\400", C),
+            (0, 4, "Octal escape is out of range for a byte".to_string())
+        );
+        assert_eq!(error("\\400", PythonBytes).2, error("\\400", C).2);
+        assert_eq!(run("\\400\\777", Python), "Āǿ");
+        // Java and JavaScript stop at two digits after `\4` to `\7`.
+        assert_eq!(run("\\400\\08", Java), " 0\08");
+        assert_eq!(run("\\400", JavaScript), " 0");
+        // Go wants exactly three digits.
+        assert_eq!(run("\\101\\000", Go), "A\0");
+        assert_eq!(
+            error("\\0", Go),
+            (0, 2, "Expected 3 octal digits".to_string())
+        );
+        assert_eq!(run("\\1a\\18", C), "\u{1}a\u{1}8");
+    }
+
+    #[test]
+    fn braced_code_points() {
+        for profile in [Rust, JavaScript, Regex] {
+            assert_eq!(run("\\u{1F600}\\u{41}", profile), "😀A", "{:?}", profile);
+            assert_eq!(
+                error("a\\u{}", profile),
+                (
+                    1,
+                    4,
+                    "Expected 1 to 6 hex digits between braces".to_string()
+                )
+            );
+            assert_eq!(
+                error("\\u{110000}", profile).2,
+                "U+110000 is not a valid code point"
+            );
+            assert_eq!(error("\\u{D800}", profile).2, "Unpaired surrogate U+D800");
+        }
+        assert_eq!(run("\\u{1_F6_00}", Rust), "😀");
+        assert_eq!(
+            error("\\u{1_F600}", JavaScript).2,
+            "Invalid hex digits in escape"
+        );
+        assert_eq!(
+            error("\\u{_1}", Rust).2,
+            "Expected 1 to 6 hex digits between braces"
+        );
+        assert_eq!(
+            error("\\u{41", Rust),
+            (0, 3, "Unterminated `{` in escape".to_string())
+        );
+        assert_eq!(error("\\u0041", Rust).2, "Expected `{` after `\\u`");
+        assert_eq!(run("\\x{41}\\x{1F600}", Regex), "A😀");
+    }
+
+    #[test]
+    fn fixed_width_code_points() {
+        for profile in [C, Python, Go, CSharp, Regex] {
+            assert_eq!(run("\\u0041\\U0001F600", profile), "A😀", "{:?}", profile);
+            assert_eq!(
+                error("\\U41", profile),
+                (0, 4, "Expected 8 hex digits".to_string())
+            );
+        }
+        // Bash takes as many digits as there are, up to the maximum.
+        assert_eq!(run("\\u41\\U1F600\\u00e9", ShellAnsiC), "A😀é");
+        assert_eq!(error("\\U0001F600", Json).2, "`\\U` is not a JSON escape");
+
+        for profile in [Json, Java, CSharp, JavaScript] {
+            assert_eq!(run("\\uD83D\\uDE00", profile), "😀");
+            assert_eq!(
+                error("\\uD83Dx", profile),
+                (0, 6, "Unpaired surrogate U+D83D".to_string())
+            );
+        }
+        assert_eq!(run("\\uuuD83D\\uuDE00", Java), "😀");
+    }
+
+    #[test]
+    fn hex_escapes() {
+        assert_eq!(run("\\x4\\x41\\x414", ShellAnsiC), "\u{4}AA4");
+        assert_eq!(run("\\x4\\x41\\x414", CSharp), "\u{4}AД");
+        assert_eq!(run("\\x4g", C), "\u{4}g");
+        assert_eq!(
+            error("\\x414", C),
+            (0, 5, "Hex escape is out of range for a byte".to_string())
+        );
+        for profile in [Go, Python, JavaScript, Rust, PythonBytes, Regex] {
+            assert_eq!(
+                error("\\x4", profile),
+                (0, 3, "Expected 2 hex digits".to_string()),
+                "{:?}",
+                profile
+            );
+        }
+        // Byte escapes are decoded together, so they can spell UTF-8.
+        assert_eq!(run("\\xc3\\xa9", Go), "é");
+        assert_eq!(error("a\\xc3", Go).2, "Escaped bytes are not valid UTF-8");
+        assert_eq!(error("a\\xc3", Go).0, 1);
+    }
+
+    #[test]
+    fn python_character_names() {
+        assert_eq!(
+            run(
+                "\\N{GRINNING FACE}\\N{hebrew letter alef}\\N{LINE FEED}",
+                Python
+            ),
+            "😀א\n"
+        );
+        assert_eq!(run("\\N{CJK UNIFIED IDEOGRAPH-4E2D}", Python), "中");
+        assert_eq!(
+            error("\\N{NOPE}", Python),
+            (0, 8, "Unknown character name `NOPE`".to_string())
+        );
+        assert_eq!(
+            error("\\N{GRINNING FACE", Python).2,
+            "Unterminated `{` in escape"
+        );
+        assert_eq!(error("\\NX", Python).2, "Expected `{` after `\\N`");
+        // Bytes literals have no `\N`.
+        assert_eq!(run("\\N{BULLET}", PythonBytes), "\\N{BULLET}");
+    }
+
+    #[test]
+    fn ansi_c_quoting() {
+        // `escape_backslash` splits `$'...'` words after hex escapes.
+        assert_eq!(run("a'$'b\\x41'$'c", ShellAnsiC), "abAc");
+        assert_eq!(
+            run("\\cA\\cz\\e\\E\\?\\'", ShellAnsiC),
+            "\u{1}\u{1a}\u{1b}\u{1b}?'"
+        );
+        assert_eq!(
+            error("\\cé", ShellAnsiC),
+            (0, 4, "Expected an ASCII character after `\\c`".to_string())
+        );
+        assert_eq!(
+            error("x\\c", ShellAnsiC).2,
+            "Expected an ASCII character after `\\c`"
+        );
+        assert_eq!(run("a\\\\b\\q", ShellAnsiC), "a\\b\\q");
+        // Only C joins adjacent literals.
+        assert_eq!(run("a\"\"b", C), "ab");
+        assert_eq!(run("a\"\"b", Json), "a\"\"b");
+    }
+
+    #[test]
+    fn unknown_escapes_per_profile() {
+        let input = "\\'\\\"\\?\\/\\e\\a\\v\\b\\f";
+        assert_eq!(run(input, JavaScript), "'\"?/ea\u{b}\u{8}\u{c}");
+        assert_eq!(run(input, Python), "'\"\\?\\/\\e\u{7}\u{b}\u{8}\u{c}");
+        assert_eq!(run(input, ShellAnsiC), "'\"?\\/\u{1b}\u{7}\u{b}\u{8}\u{c}");
+        let cases = [
+            (Json, 0, "`\\'` is not a JSON escape"),
+            (Go, 0, "`\\'` is not a Go escape"),
+            (Rust, 4, "`\\?` is not a Rust escape"),
+            (Java, 4, "`\\?` is not a Java escape"),
+            (CSharp, 4, "`\\?` is not a C# escape"),
+            (C, 6, "`\\/` is not a C/C++ escape"),
+        ];
+        for (profile, offset, message) in cases {
+            assert_eq!(error(input, profile), (offset, 2, message.to_string()));
+        }
+        assert_eq!(run("\\s", Java), " ");
+        assert_eq!(run("\\$\\`\\\"\\\\\\n\\", ShellDouble), "$`\"\\\\n\\");
+        assert_eq!(
+            error("\\.\\d", Regex),
+            (
+                2,
+                2,
+                "`\\d` matches a character class or position, not a literal character".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn quote_doubling_profiles() {
+        assert_eq!(run("it''s", Sql), "it's");
+        assert_eq!(error("it''s'", Sql), (5, 1, "Unescaped quote".to_string()));
+        assert_eq!(run("it'\\''s", ShellSingle), "it's");
+        assert_eq!(error("it's", ShellSingle).0, 2);
+        assert_eq!(run("\"a,\"\"b\"", Csv), "a,\"b");
+        assert_eq!(run("a\"b", Csv), "a\"b");
+        assert_eq!(
+            error("\"a\"b\"", Csv),
+            (2, 1, "Unescaped quote inside a quoted field".to_string())
+        );
+        assert_eq!(error("\"open", Csv).2, "Quoted field is not closed");
+        assert_eq!(run("C:\\d\\", PythonRaw), "C:\\d\\");
+    }
+
+    #[test]
+    fn percent_encoding() {
+        assert_eq!(run("%41%c3%A9a+b%2B", Url), "Aéa+b+");
+        for input in ["%", "%4", "%4g", "%é", "%%41"] {
+            assert_eq!(
+                error(input, Url),
+                (0, 1, "Invalid percent escape".to_string()),
+                "{}",
+                input
+            );
+        }
+        assert_eq!(error("100%", Url).0, 3);
+        assert_eq!(
+            error("ok%C3", Url),
+            (2, 1, "Escaped bytes are not valid UTF-8".to_string())
+        );
+        assert_eq!(error("%41%FF", Url).0, 3);
+    }
+
+    #[test]
+    fn character_references() {
+        assert_eq!(
+            run(
+                "&amp;&AMP;&apos;&NotNestedGreaterGreater;&fjlig;&#128512;&#x1f600;",
+                Html
+            ),
+            "&&'\u{2aa2}\u{338}fj😀😀"
+        );
+        // An `&` that does not start a reference stays.
+        assert_eq!(run("&amp &; &ampx a&b", Html), "&amp &; &ampx a&b");
+        assert_eq!(
+            error("&Amp;", Html),
+            (0, 5, "Unknown entity `&Amp;`".to_string())
+        );
+        assert_eq!(error("&#;", Html).2, "Invalid numeric character reference");
+        assert_eq!(error("&#x;", Html).2, "Invalid numeric character reference");
+        assert_eq!(error("&#xD800;", Html).2, "Unpaired surrogate U+D800");
+        assert_eq!(
+            error("&#1114112;", Html).2,
+            "U+110000 is not a valid code point"
+        );
+    }
+}
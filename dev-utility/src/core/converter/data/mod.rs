@@ -0,0 +1,514 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Structured data conversion through a `serde_json::Value` pivot.
+//!
+//! Every reader produces a JSON value and every writer consumes one, so
+//! any pair of formats converts. Writers check up front that the value
+//! fits the target format and name the offending path when it does not.
+
+mod query_string;
+mod tabular;
+mod xml;
+
+use crate::error::{char_offset, strip_location, Diagnostic, UtilityError};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use universal_function_macro::universal_function;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum DataFormat {
+    Json,
+    Json5,
+    Yaml,
+    Toml,
+    Xml,
+    Csv,
+    Tsv,
+    QueryString,
+    MessagePack, // Binary, carried as hex or Base64 text
+    Cbor,        // Binary, carried as hex or Base64 text
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum BinaryEncoding {
+    Hex,
+    Base64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct ConvertOptions {
+    pub indent: usize,                   // Spaces per level; 0 = compact where possible
+    pub attribute_prefix: String,        // XML attributes become keys with this prefix
+    pub text_key: String,                // Key for XML text next to attributes/children
+    pub xml_root: String,                // Root element when the value has no single key
+    pub flatten_separator: String,       // Joins nested keys into CSV/TSV headers
+    pub infer_types: bool,               // Turn "42", "true", "" into numbers, bools, null
+    pub binary_encoding: BinaryEncoding, // Text form of MessagePack/CBOR bytes
+    pub yaml_multi_document: bool,       // Write a top-level array as `---` documents
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            attribute_prefix: "@".to_string(),
+            text_key: "#text".to_string(),
+            xml_root: "root".to_string(),
+            flatten_separator: ".".to_string(),
+            infer_types: true,
+            binary_encoding: BinaryEncoding::Hex,
+            yaml_multi_document: false,
+        }
+    }
+}
+
+/// Convert `input` from one data format to another.
+///
+/// A YAML stream with several documents reads as an array of them. CSV,
+/// TSV and query strings are untyped, so their values stay strings unless
+/// `infer_types` is set.
+#[universal_function]
+pub fn convert_data(
+    input: &str,
+    from: DataFormat,
+    to: DataFormat,
+    options: Option<ConvertOptions>,
+) -> Result<String, UtilityError> {
+    let options = options.unwrap_or_default();
+    let value = read(input, from, &options)?;
+    write(&value, to, &options)
+}
+
+/// Parse `input` in any readable format with default options.
+pub(crate) fn parse_document(input: &str, format: DataFormat) -> Result<Value, UtilityError> {
+    read(input, format, &ConvertOptions::default())
+}
+
+fn read(input: &str, format: DataFormat, options: &ConvertOptions) -> Result<Value, UtilityError> {
+    let parse_error = |name: &str, e: &dyn std::fmt::Display| {
+        UtilityError::ParseError(format!("Invalid {}: {}", name, e))
+    };
+    match format {
+        DataFormat::Json => serde_json::from_str(input).map_err(|e| {
+            let message = format!("Invalid JSON: {}", strip_location(&e.to_string()));
+            Diagnostic::at_line_column(input, e.line(), e.column(), message).into()
+        }),
+        DataFormat::Json5 => json5::from_str(input).map_err(|e| match e {
+            json5::Error::Message {
+                msg,
+                location: Some(location),
+            } => {
+                // pest renders a source excerpt; keep only its "= reason" line.
+                let reason = msg
+                    .lines()
+                    .find_map(|line| line.trim().strip_prefix("= "))
+                    .unwrap_or(&msg);
+                let offset = char_offset(input, location.line, location.column);
+                Diagnostic::new(input, offset, format!("Invalid JSON5: {}", reason)).into()
+            }
+            e => parse_error("JSON5", &e),
+        }),
+        DataFormat::Yaml => {
+            let mut documents = Vec::new();
+            for document in serde_yml::Deserializer::from_str(input) {
+                let document = Value::deserialize(document).map_err(|e| match e.location() {
+                    Some(location) => {
+                        let message = format!("Invalid YAML: {}", strip_location(&e.to_string()));
+                        Diagnostic::new(input, location.index(), message).into()
+                    }
+                    None => parse_error("YAML", &e),
+                })?;
+                documents.push(document);
+            }
+            Ok(match documents.len() {
+                0 => Value::Null,
+                1 => documents.remove(0),
+                _ => Value::Array(documents),
+            })
+        }
+        DataFormat::Toml => basic_toml::from_str(input).map_err(|e| match e.line_col() {
+            Some((line, column)) => {
+                let message = format!("Invalid TOML: {}", strip_location(&e.to_string()));
+                Diagnostic::at_line_column(input, line + 1, column + 1, message).into()
+            }
+            None => parse_error("TOML", &e),
+        }),
+        DataFormat::Xml => xml::read(input, options),
+        DataFormat::Csv => tabular::read(input, b',', options),
+        DataFormat::Tsv => tabular::read(input, b'\t', options),
+        DataFormat::QueryString => Ok(query_string::read(input, options)),
+        DataFormat::MessagePack => {
+            let bytes = decode_binary(input, options.binary_encoding)?;
+            rmp_serde::from_slice(&bytes).map_err(|e| parse_error("MessagePack", &e))
+        }
+        DataFormat::Cbor => {
+            let bytes = decode_binary(input, options.binary_encoding)?;
+            ciborium::from_reader(bytes.as_slice()).map_err(|e| parse_error("CBOR", &e))
+        }
+    }
+}
+
+fn write(
+    value: &Value,
+    format: DataFormat,
+    options: &ConvertOptions,
+) -> Result<String, UtilityError> {
+    let runtime = |e: &dyn std::fmt::Display| UtilityError::Runtime(e.to_string());
+    match format {
+        DataFormat::Json if options.indent == 0 => {
+            serde_json::to_string(value).map_err(|e| runtime(&e))
+        }
+        DataFormat::Json => {
+            let indent = " ".repeat(options.indent);
+            let mut out = Vec::new();
+            let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+            let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+            value.serialize(&mut serializer).map_err(|e| runtime(&e))?;
+            String::from_utf8(out).map_err(|e| runtime(&e))
+        }
+        DataFormat::Json5 => Ok(write_json5(value, options.indent)),
+        DataFormat::Yaml => match value {
+            Value::Array(documents) if options.yaml_multi_document => {
+                let mut out = String::new();
+                for document in documents {
+                    out.push_str("---\n");
+                    out.push_str(&serde_yml::to_string(document).map_err(|e| runtime(&e))?);
+                }
+                Ok(out)
+            }
+            _ => serde_yml::to_string(value).map_err(|e| runtime(&e)),
+        },
+        DataFormat::Toml => {
+            if !value.is_object() {
+                return Err(unrepresentable(
+                    "TOML",
+                    "$",
+                    "the top level must be a table",
+                ));
+            }
+            check_no_null(value, "$", "TOML")?;
+            basic_toml::to_string(&toml_order(value)).map_err(|e| runtime(&e))
+        }
+        DataFormat::Xml => xml::write(value, options),
+        DataFormat::Csv => tabular::write(value, b',', options),
+        DataFormat::Tsv => tabular::write(value, b'\t', options),
+        DataFormat::QueryString => query_string::write(value),
+        DataFormat::MessagePack => {
+            let bytes = rmp_serde::to_vec_named(value).map_err(|e| runtime(&e))?;
+            Ok(encode_binary(&bytes, options.binary_encoding))
+        }
+        DataFormat::Cbor => {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(value, &mut bytes).map_err(|e| runtime(&e))?;
+            Ok(encode_binary(&bytes, options.binary_encoding))
+        }
+    }
+}
+
+pub(crate) fn unrepresentable(format: &str, path: &str, reason: &str) -> UtilityError {
+    UtilityError::InvalidInput(format!("{} cannot represent {}: {}", format, path, reason))
+}
+
+/// Path segment in the `$.a[0]` style used in error messages.
+pub(crate) fn child_path(parent: &str, key: &str) -> String {
+    let simple = !key.is_empty()
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !key.starts_with(|c: char| c.is_ascii_digit());
+    if simple {
+        format!("{}.{}", parent, key)
+    } else {
+        format!("{}[{}]", parent, Value::String(key.to_string()))
+    }
+}
+
+fn check_no_null(value: &Value, path: &str, format: &str) -> Result<(), UtilityError> {
+    match value {
+        Value::Null => Err(unrepresentable(format, path, "it has no null value")),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, item)| check_no_null(item, &format!("{}[{}]", path, i), format)),
+        Value::Object(map) => map
+            .iter()
+            .try_for_each(|(key, item)| check_no_null(item, &child_path(path, key), format)),
+        _ => Ok(()),
+    }
+}
+
+/// TOML writes a table's plain keys before its sub-tables, so move nested
+/// tables (and arrays of tables) after everything else.
+fn toml_order(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let is_table = |v: &Value| match v {
+                Value::Object(_) => true,
+                Value::Array(items) => !items.is_empty() && items.iter().all(Value::is_object),
+                _ => false,
+            };
+            let mut ordered = Map::new();
+            for pass_tables in [false, true] {
+                for (key, item) in map {
+                    if is_table(item) == pass_tables {
+                        ordered.insert(key.clone(), toml_order(item));
+                    }
+                }
+            }
+            Value::Object(ordered)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(toml_order).collect()),
+        other => other.clone(),
+    }
+}
+
+fn write_json5(value: &Value, indent: usize) -> String {
+    fn key(name: &str) -> String {
+        let identifier = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+        if identifier {
+            name.to_string()
+        } else {
+            Value::String(name.to_string()).to_string()
+        }
+    }
+
+    fn walk(value: &Value, indent: usize, depth: usize, out: &mut String) {
+        let (open, close, items): (char, char, Vec<(Option<&String>, &Value)>) = match value {
+            Value::Array(items) => ('[', ']', items.iter().map(|v| (None, v)).collect()),
+            Value::Object(map) => ('{', '}', map.iter().map(|(k, v)| (Some(k), v)).collect()),
+            scalar => {
+                out.push_str(&scalar.to_string());
+                return;
+            }
+        };
+        out.push(open);
+        if items.is_empty() {
+            out.push(close);
+            return;
+        }
+        let pad = |depth: usize| " ".repeat(indent * depth);
+        for (i, (name, item)) in items.iter().enumerate() {
+            if indent > 0 {
+                out.push('\n');
+                out.push_str(&pad(depth + 1));
+            } else if i > 0 {
+                out.push(' ');
+            }
+            if let Some(name) = name {
+                out.push_str(&key(name));
+                out.push_str(": ");
+            }
+            walk(item, indent, depth + 1, out);
+            // JSON5 allows a trailing comma, which keeps diffs one line.
+            if indent > 0 || i + 1 < items.len() {
+                out.push(',');
+            }
+        }
+        if indent > 0 {
+            out.push('\n');
+            out.push_str(&pad(depth));
+        }
+        out.push(close);
+    }
+
+    let mut out = String::new();
+    walk(value, indent, 0, &mut out);
+    out
+}
+
+fn encode_binary(bytes: &[u8], encoding: BinaryEncoding) -> String {
+    match encoding {
+        BinaryEncoding::Hex => hex::encode(bytes),
+        BinaryEncoding::Base64 => BASE64.encode(bytes),
+    }
+}
+
+fn decode_binary(input: &str, encoding: BinaryEncoding) -> Result<Vec<u8>, UtilityError> {
+    let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    match encoding {
+        BinaryEncoding::Hex => hex::decode(compact.trim_start_matches("0x"))
+            .map_err(|e| UtilityError::DecodeError(format!("Invalid hex: {}", e))),
+        BinaryEncoding::Base64 => BASE64
+            .decode(compact)
+            .map_err(|e| UtilityError::DecodeError(format!("Invalid Base64: {}", e))),
+    }
+}
+
+/// Parse an untyped scalar the way a person would read it.
+pub(crate) fn infer_scalar(text: &str) -> Value {
+    match text {
+        "" | "null" => Value::Null,
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        // JSON number syntax keeps leading-zero IDs and ZIP codes as strings.
+        _ => match serde_json::from_str::<Value>(text) {
+            Ok(n @ Value::Number(_)) => n,
+            _ => Value::String(text.to_string()),
+        },
+    }
+}
+
+/// Text of a scalar for the untyped formats.
+pub(crate) fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(input: &str, from: DataFormat, to: DataFormat) -> Result<String, UtilityError> {
+        convert_data(input, from, to, None)
+    }
+
+    #[test]
+    fn json_yaml_toml_round_trip() {
+        let json = r#"{"name":"svc","port":8080,"tags":["a","b"],"db":{"host":"x","pool":5}}"#;
+        let yaml = convert(json, DataFormat::Json, DataFormat::Yaml).unwrap();
+        assert!(yaml.contains("port: 8080"));
+        let toml = convert(&yaml, DataFormat::Yaml, DataFormat::Toml).unwrap();
+        assert!(toml.contains("[db]"));
+        let back = convert_data(
+            &toml,
+            DataFormat::Toml,
+            DataFormat::Json,
+            Some(ConvertOptions {
+                indent: 0,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert_eq!(back, json);
+    }
+
+    #[test]
+    fn toml_rejects_null_with_path() {
+        let err = convert(
+            r#"{"a":{"b":[1,null]}}"#,
+            DataFormat::Json,
+            DataFormat::Toml,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("$.a.b[1]"), "{}", err);
+        assert!(convert("[1]", DataFormat::Json, DataFormat::Toml).is_err());
+    }
+
+    #[test]
+    fn parse_errors_are_located() {
+        let locate = |input: &str, from: DataFormat| {
+            let err = convert(input, from, DataFormat::Json).unwrap_err();
+            let diagnostic = err.diagnostic().cloned().expect("located error");
+            (diagnostic.line, diagnostic.column)
+        };
+        assert_eq!(locate("{\"a\": x}", DataFormat::Json), (1, 7));
+        assert_eq!(locate("{a: 1,\n b: @}", DataFormat::Json5), (2, 5));
+        assert_eq!(locate("a: 1\nb: [1, 2\n", DataFormat::Yaml).0, 3);
+        assert_eq!(locate("a = 1\nb = = 2", DataFormat::Toml), (2, 5));
+        assert_eq!(locate("<a>\n  <b></c>\n</a>", DataFormat::Xml).0, 2);
+    }
+
+    #[test]
+    fn toml_tables_follow_plain_keys() {
+        let toml = convert(
+            r#"{"server":{"port":1},"name":"x"}"#,
+            DataFormat::Json,
+            DataFormat::Toml,
+        )
+        .unwrap();
+        assert!(toml.starts_with("name = \"x\""), "{}", toml);
+    }
+
+    #[test]
+    fn yaml_multi_document() {
+        let yaml = "kind: A\n---\nkind: B\n";
+        let json = convert_data(
+            yaml,
+            DataFormat::Yaml,
+            DataFormat::Json,
+            Some(ConvertOptions {
+                indent: 0,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert_eq!(json, r#"[{"kind":"A"},{"kind":"B"}]"#);
+
+        let options = ConvertOptions {
+            yaml_multi_document: true,
+            ..Default::default()
+        };
+        let yaml = convert_data(&json, DataFormat::Json, DataFormat::Yaml, Some(options)).unwrap();
+        assert_eq!(yaml.matches("---").count(), 2);
+    }
+
+    #[test]
+    fn json5_both_ways() {
+        let value = convert(
+            "{unquoted: 'single', trailing: [1, 2,],}",
+            DataFormat::Json5,
+            DataFormat::Json5,
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            "{\n  unquoted: \"single\",\n  trailing: [\n    1,\n    2,\n  ],\n}"
+        );
+    }
+
+    #[test]
+    fn binary_formats() {
+        let json = r#"{"a":1,"b":[true,"x"]}"#;
+        for format in [DataFormat::MessagePack, DataFormat::Cbor] {
+            for encoding in [BinaryEncoding::Hex, BinaryEncoding::Base64] {
+                let options = ConvertOptions {
+                    indent: 0,
+                    binary_encoding: encoding,
+                    ..Default::default()
+                };
+                let encoded =
+                    convert_data(json, DataFormat::Json, format, Some(options.clone())).unwrap();
+                let back = convert_data(&encoded, format, DataFormat::Json, Some(options)).unwrap();
+                assert_eq!(back, json);
+            }
+        }
+        assert_eq!(
+            convert("{\"a\":1}", DataFormat::Json, DataFormat::MessagePack).unwrap(),
+            "81a16101"
+        );
+        assert!(convert("zz", DataFormat::Cbor, DataFormat::Json).is_err());
+    }
+
+    #[test]
+    fn infers_scalars() {
+        assert_eq!(infer_scalar("42"), Value::from(42));
+        assert_eq!(infer_scalar("1.5"), Value::from(1.5));
+        assert_eq!(infer_scalar("007"), Value::from("007"));
+        assert_eq!(infer_scalar(""), Value::Null);
+        assert_eq!(infer_scalar("yes"), Value::from("yes"));
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! `application/x-www-form-urlencoded` with the bracket convention used by
+//! Rails, PHP and `qs`: `a[b]=1` nests, `a[]=1` and repeated keys append.
+
+use super::tabular::arrays_from_indices;
+use super::{infer_scalar, scalar_text, unrepresentable, ConvertOptions};
+use crate::error::UtilityError;
+use serde_json::{Map, Value};
+
+pub(super) fn read(input: &str, options: &ConvertOptions) -> Value {
+    let query = input.trim();
+    let query = query.split_once('?').map_or(query, |(_, q)| q);
+    let query = query.split_once('#').map_or(query, |(q, _)| q);
+
+    let mut root = Map::new();
+    for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+        let value = if options.infer_types {
+            infer_scalar(&value)
+        } else {
+            Value::String(value.into_owned())
+        };
+        let (name, rest) = key.split_once('[').unwrap_or((&key, ""));
+        let mut segments = vec![name.to_string()];
+        segments.extend(
+            rest.trim_end_matches(']')
+                .split("][")
+                .filter(|_| !rest.is_empty())
+                .map(str::to_string),
+        );
+        insert(&mut root, &segments, value);
+    }
+    arrays_from_indices(Value::Object(root))
+}
+
+fn insert(map: &mut Map<String, Value>, segments: &[String], value: Value) {
+    let (key, rest) = segments.split_first().expect("at least the name");
+    match rest.first().map(String::as_str) {
+        // Leaf: a repeated key turns into an array.
+        None => match map.get_mut(key) {
+            Some(Value::Array(items)) => items.push(value),
+            Some(existing) => {
+                let first = existing.take();
+                *existing = Value::Array(vec![first, value]);
+            }
+            None => {
+                map.insert(key.clone(), value);
+            }
+        },
+        Some("") => {
+            let entry = map
+                .entry(key.clone())
+                .or_insert_with(|| Value::Array(Vec::new()));
+            if let Value::Array(items) = entry {
+                items.push(value);
+            }
+        }
+        Some(_) => {
+            let entry = map
+                .entry(key.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(child) = entry {
+                insert(child, rest, value);
+            }
+        }
+    }
+}
+
+pub(super) fn write(value: &Value) -> Result<String, UtilityError> {
+    let Value::Object(map) = value else {
+        return Err(unrepresentable(
+            "A query string",
+            "$",
+            "the top level must be an object",
+        ));
+    };
+    let mut pairs = Vec::new();
+    for (key, item) in map {
+        collect(key.clone(), item, &mut pairs);
+    }
+    let mut serializer = form_urlencoded::Serializer::new(String::new());
+    for (key, value) in pairs {
+        match value {
+            Some(value) => serializer.append_pair(&key, &value),
+            None => serializer.append_key_only(&key),
+        };
+    }
+    Ok(serializer.finish())
+}
+
+fn collect(key: String, value: &Value, pairs: &mut Vec<(String, Option<String>)>) {
+    match value {
+        Value::Null => pairs.push((key, None)),
+        Value::Object(map) => {
+            for (child, item) in map {
+                collect(format!("{}[{}]", key, child), item, pairs);
+            }
+        }
+        // Scalars repeat the key; containers need an index to stay apart.
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                let key = if item.is_object() || item.is_array() {
+                    format!("{}[{}]", key, i)
+                } else {
+                    key.clone()
+                };
+                collect(key, item, pairs);
+            }
+        }
+        scalar => pairs.push((key, Some(scalar_text(scalar)))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{convert_data, ConvertOptions, DataFormat};
+
+    fn compact() -> Option<ConvertOptions> {
+        Some(ConvertOptions {
+            indent: 0,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn reads_nested_and_repeated_keys() {
+        let json = convert_data(
+            "https://x.test/?q=rust+lang&tag=a&tag=b&page=2&f[size]=10&ids[]=7",
+            DataFormat::QueryString,
+            DataFormat::Json,
+            compact(),
+        )
+        .unwrap();
+        assert_eq!(
+            json,
+            r#"{"q":"rust lang","tag":["a","b"],"page":2,"f":{"size":10},"ids":[7]}"#
+        );
+    }
+
+    #[test]
+    fn writes_brackets() {
+        let query = convert_data(
+            r#"{"q":"a&b","tags":["x","y"],"filter":{"min":1},"rows":[{"id":1}],"flag":null}"#,
+            DataFormat::Json,
+            DataFormat::QueryString,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            query,
+            "q=a%26b&tags=x&tags=y&filter%5Bmin%5D=1&rows%5B0%5D%5Bid%5D=1&flag"
+        );
+        let back =
+            convert_data(&query, DataFormat::QueryString, DataFormat::Json, compact()).unwrap();
+        assert_eq!(
+            back,
+            r#"{"q":"a&b","tags":["x","y"],"filter":{"min":1},"rows":[{"id":1}],"flag":null}"#
+        );
+        assert!(convert_data("[1]", DataFormat::Json, DataFormat::QueryString, None).is_err());
+    }
+}
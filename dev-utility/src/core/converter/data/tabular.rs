@@ -0,0 +1,229 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! CSV and TSV. Nested values are flattened into `parent.child` headers on
+//! the way out and rebuilt from them on the way in.
+
+use super::{infer_scalar, scalar_text, unrepresentable, ConvertOptions};
+use crate::error::{Diagnostic, UtilityError};
+use serde_json::{Map, Value};
+
+pub(super) fn read(
+    input: &str,
+    delimiter: u8,
+    options: &ConvertOptions,
+) -> Result<Value, UtilityError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(input.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| csv_error(input, &e))?
+        .iter()
+        .map(str::to_string)
+        .collect();
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| csv_error(input, &e))?;
+        let mut row = Map::new();
+        for (header, field) in headers.iter().zip(record.iter()) {
+            let value = if options.infer_types {
+                infer_scalar(field)
+            } else {
+                Value::String(field.to_string())
+            };
+            insert_path(&mut row, header, &options.flatten_separator, value);
+        }
+        rows.push(arrays_from_indices(Value::Object(row)));
+    }
+    Ok(Value::Array(rows))
+}
+
+fn csv_error(input: &str, error: &csv::Error) -> UtilityError {
+    let message = format!("Invalid CSV: {}", error);
+    match error.position() {
+        Some(position) => Diagnostic::new(input, position.byte() as usize, message).into(),
+        None => UtilityError::ParseError(message),
+    }
+}
+
+pub(super) fn write(
+    value: &Value,
+    delimiter: u8,
+    options: &ConvertOptions,
+) -> Result<String, UtilityError> {
+    let format = if delimiter == b'\t' { "TSV" } else { "CSV" };
+    let rows: Vec<&Value> = match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(_) => vec![value],
+        _ => {
+            return Err(unrepresentable(
+                format,
+                "$",
+                "expected an array of objects or a single object",
+            ))
+        }
+    };
+
+    let mut headers: Vec<String> = Vec::new();
+    let mut flat_rows = Vec::with_capacity(rows.len());
+    for (i, row) in rows.iter().enumerate() {
+        if !row.is_object() {
+            return Err(unrepresentable(
+                format,
+                &format!("$[{}]", i),
+                "every row must be an object",
+            ));
+        }
+        let mut flat = Vec::new();
+        flatten(row, String::new(), &options.flatten_separator, &mut flat);
+        for (key, _) in &flat {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+        flat_rows.push(flat);
+    }
+
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(Vec::new());
+    let write_error = |e: csv::Error| UtilityError::Runtime(e.to_string());
+    writer.write_record(&headers).map_err(write_error)?;
+    for flat in flat_rows {
+        let record = headers.iter().map(|header| {
+            flat.iter()
+                .find(|(key, _)| key == header)
+                .map(|(_, value)| scalar_text(value))
+                .unwrap_or_default()
+        });
+        writer.write_record(record).map_err(write_error)?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| UtilityError::Runtime(e.to_string()))?;
+    String::from_utf8(bytes).map_err(|e| UtilityError::Runtime(e.to_string()))
+}
+
+/// Collect `(joined.key, scalar)` pairs. Array elements use their index as
+/// the key segment; empty containers are kept as JSON text.
+fn flatten(value: &Value, prefix: String, separator: &str, out: &mut Vec<(String, Value)>) {
+    let join = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}{}{}", prefix, separator, key)
+        }
+    };
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, item) in map {
+                flatten(item, join(key), separator, out);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (i, item) in items.iter().enumerate() {
+                flatten(item, join(&i.to_string()), separator, out);
+            }
+        }
+        Value::Object(_) | Value::Array(_) => out.push((prefix, Value::String(value.to_string()))),
+        scalar => out.push((prefix, scalar.clone())),
+    }
+}
+
+fn insert_path(row: &mut Map<String, Value>, header: &str, separator: &str, value: Value) {
+    let segments: Vec<&str> = if separator.is_empty() {
+        vec![header]
+    } else {
+        header.split(separator).collect()
+    };
+    // A scalar column and a nested column can share a prefix ("a" and
+    // "a.b"); keep such headers verbatim rather than drop either value.
+    if !fits(row, &segments) {
+        row.insert(header.to_string(), value);
+        return;
+    }
+    let (last, parents) = segments.split_last().expect("split yields a segment");
+    let mut target = row;
+    for segment in parents {
+        target = target
+            .entry(segment.to_string())
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .expect("checked by fits");
+    }
+    target.insert(last.to_string(), value);
+}
+
+fn fits(map: &Map<String, Value>, segments: &[&str]) -> bool {
+    match segments {
+        [first, rest @ ..] if !rest.is_empty() => match map.get(*first) {
+            None => true,
+            Some(Value::Object(child)) => fits(child, rest),
+            Some(_) => false,
+        },
+        _ => true,
+    }
+}
+
+/// Objects whose keys are exactly 0..n came from arrays.
+pub(super) fn arrays_from_indices(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let is_array = !map.is_empty()
+                && map
+                    .keys()
+                    .enumerate()
+                    .all(|(i, key)| key.parse::<usize>() == Ok(i));
+            let items = map.into_iter().map(|(k, v)| (k, arrays_from_indices(v)));
+            if is_array {
+                Value::Array(items.map(|(_, v)| v).collect())
+            } else {
+                Value::Object(items.collect())
+            }
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{convert_data, DataFormat};
+
+    #[test]
+    fn flattens_and_rebuilds_nested_rows() {
+        let json = r#"[{"id":1,"user":{"name":"a","roles":["x","y"]}},{"id":2,"extra":true}]"#;
+        let csv = convert_data(json, DataFormat::Json, DataFormat::Csv, None).unwrap();
+        assert_eq!(
+            csv,
+            "id,user.name,user.roles.0,user.roles.1,extra\n1,a,x,y,\n2,,,,true\n"
+        );
+
+        let back = convert_data(&csv, DataFormat::Csv, DataFormat::Json, None).unwrap();
+        let back: serde_json::Value = serde_json::from_str(&back).unwrap();
+        assert_eq!(back[0]["user"]["roles"][1], "y");
+        assert_eq!(back[0]["id"], 1);
+        assert_eq!(back[1]["extra"], true);
+    }
+
+    #[test]
+    fn tsv_and_errors() {
+        let tsv = convert_data(
+            r#"{"a":"x y","b":2}"#,
+            DataFormat::Json,
+            DataFormat::Tsv,
+            None,
+        )
+        .unwrap();
+        assert_eq!(tsv, "a\tb\nx y\t2\n");
+        assert!(convert_data("[1,2]", DataFormat::Json, DataFormat::Csv, None).is_err());
+        assert!(convert_data("42", DataFormat::Json, DataFormat::Csv, None).is_err());
+    }
+}
@@ -0,0 +1,370 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! XML in the "Parker with attributes" convention: an element becomes a key,
+//! attributes become `@name` keys, text next to attributes or children goes
+//! under `#text`, and repeated siblings collapse into an array.
+
+use super::{child_path, infer_scalar, scalar_text, unrepresentable, ConvertOptions};
+use crate::error::{Diagnostic, UtilityError};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde_json::{Map, Value};
+
+struct Frame {
+    name: String,
+    fields: Map<String, Value>,
+    text: String,
+}
+
+pub(super) fn read(input: &str, options: &ConvertOptions) -> Result<Value, UtilityError> {
+    let xml_error =
+        |e: &dyn std::fmt::Display| UtilityError::ParseError(format!("Invalid XML: {}", e));
+    let mut reader = Reader::from_str(input);
+    reader.config_mut().trim_text(true);
+
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut root: Option<(String, Value)> = None;
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            let message = format!("Invalid XML: {}", e);
+            Diagnostic::new(input, reader.error_position() as usize, message)
+        })?;
+        match event {
+            Event::Start(e) => stack.push(open(&e, options)?),
+            Event::Empty(e) => {
+                let frame = open(&e, options)?;
+                close(frame, &mut stack, &mut root, options)?;
+            }
+            Event::End(_) => {
+                let frame = stack
+                    .pop()
+                    .ok_or_else(|| xml_error(&"unexpected closing tag"))?;
+                close(frame, &mut stack, &mut root, options)?;
+            }
+            Event::Text(e) => {
+                if let Some(frame) = stack.last_mut() {
+                    frame
+                        .text
+                        .push_str(&e.unescape().map_err(|e| xml_error(&e))?);
+                }
+            }
+            Event::CData(e) => {
+                if let Some(frame) = stack.last_mut() {
+                    frame
+                        .text
+                        .push_str(&String::from_utf8_lossy(&e.into_inner()));
+                }
+            }
+            Event::Eof => break,
+            // Declarations, comments, processing instructions and doctypes
+            // carry no data.
+            _ => {}
+        }
+    }
+
+    if !stack.is_empty() {
+        return Err(xml_error(&format!("unclosed element <{}>", stack[0].name)));
+    }
+    let (name, value) = root.ok_or_else(|| xml_error(&"no root element"))?;
+    let mut document = Map::new();
+    document.insert(name, value);
+    Ok(Value::Object(document))
+}
+
+fn open(start: &BytesStart, options: &ConvertOptions) -> Result<Frame, UtilityError> {
+    let mut fields = Map::new();
+    for attribute in start.attributes() {
+        let attribute = attribute
+            .map_err(|e| UtilityError::ParseError(format!("Invalid XML attribute: {}", e)))?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+        let value = attribute
+            .unescape_value()
+            .map_err(|e| UtilityError::ParseError(format!("Invalid XML attribute: {}", e)))?;
+        fields.insert(
+            format!("{}{}", options.attribute_prefix, key),
+            scalar(&value, options),
+        );
+    }
+    Ok(Frame {
+        name: String::from_utf8_lossy(start.name().as_ref()).into_owned(),
+        fields,
+        text: String::new(),
+    })
+}
+
+fn close(
+    frame: Frame,
+    stack: &mut [Frame],
+    root: &mut Option<(String, Value)>,
+    options: &ConvertOptions,
+) -> Result<(), UtilityError> {
+    let Frame {
+        name,
+        mut fields,
+        text,
+    } = frame;
+    let value = match (fields.is_empty(), text.is_empty()) {
+        (true, true) => Value::Null,
+        (true, false) => scalar(&text, options),
+        (false, true) => Value::Object(fields),
+        (false, false) => {
+            fields.insert(options.text_key.clone(), scalar(&text, options));
+            Value::Object(fields)
+        }
+    };
+
+    let Some(parent) = stack.last_mut() else {
+        if root.is_some() {
+            return Err(UtilityError::ParseError(
+                "Invalid XML: more than one root element".to_string(),
+            ));
+        }
+        *root = Some((name, value));
+        return Ok(());
+    };
+    match parent.fields.get_mut(&name) {
+        Some(Value::Array(siblings)) => siblings.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+        None => {
+            parent.fields.insert(name, value);
+        }
+    }
+    Ok(())
+}
+
+fn scalar(text: &str, options: &ConvertOptions) -> Value {
+    if options.infer_types {
+        infer_scalar(text)
+    } else {
+        Value::String(text.to_string())
+    }
+}
+
+pub(super) fn write(value: &Value, options: &ConvertOptions) -> Result<String, UtilityError> {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let mut writer = Writer {
+        out: &mut out,
+        options,
+    };
+    match value {
+        // A single non-array key is the root element itself.
+        Value::Object(map)
+            if map.len() == 1
+                && !map.values().all(Value::is_array)
+                && !map.keys().all(|k| k.starts_with(&options.attribute_prefix)) =>
+        {
+            let (name, item) = map.iter().next().expect("one entry");
+            writer.element(name, item, 0, &child_path("$", name))?;
+        }
+        Value::Array(items) => {
+            let wrapped: Map<String, Value> = [("item".to_string(), Value::Array(items.clone()))]
+                .into_iter()
+                .collect();
+            writer.element(&options.xml_root, &Value::Object(wrapped), 0, "$")?;
+        }
+        other => writer.element(&options.xml_root, other, 0, "$")?,
+    }
+    if options.indent == 0 {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+struct Writer<'a> {
+    out: &'a mut String,
+    options: &'a ConvertOptions,
+}
+
+impl Writer<'_> {
+    fn element(
+        &mut self,
+        name: &str,
+        value: &Value,
+        depth: usize,
+        path: &str,
+    ) -> Result<(), UtilityError> {
+        check_name(name, path)?;
+        if let Value::Array(items) = value {
+            for (i, item) in items.iter().enumerate() {
+                let item_path = format!("{}[{}]", path, i);
+                if item.is_array() {
+                    return Err(unrepresentable(
+                        "XML",
+                        &item_path,
+                        "nested arrays have no element name",
+                    ));
+                }
+                self.element(name, item, depth, &item_path)?;
+            }
+            return Ok(());
+        }
+
+        self.pad(depth);
+        self.out.push('<');
+        self.out.push_str(name);
+        let Value::Object(map) = value else {
+            if value.is_null() {
+                self.out.push_str("/>");
+            } else {
+                self.out.push('>');
+                self.out.push_str(&escape(scalar_text(value)));
+                self.out.push_str(&format!("</{}>", name));
+            }
+            self.newline();
+            return Ok(());
+        };
+
+        let prefix = &self.options.attribute_prefix;
+        let mut text = None;
+        let mut children = Vec::new();
+        for (key, item) in map {
+            match key.strip_prefix(prefix.as_str()) {
+                Some(attribute) if !prefix.is_empty() => {
+                    let attribute_path = child_path(path, key);
+                    check_name(attribute, &attribute_path)?;
+                    if item.is_object() || item.is_array() {
+                        return Err(unrepresentable(
+                            "XML",
+                            &attribute_path,
+                            "attribute values must be scalars",
+                        ));
+                    }
+                    self.out
+                        .push_str(&format!(" {}=\"{}\"", attribute, escape(scalar_text(item))));
+                }
+                _ if *key == self.options.text_key => text = Some(scalar_text(item)),
+                _ => children.push((key, item)),
+            }
+        }
+
+        match (children.is_empty(), text) {
+            (true, None) => self.out.push_str("/>"),
+            (true, Some(text)) => {
+                self.out.push('>');
+                self.out.push_str(&escape(text));
+                self.out.push_str(&format!("</{}>", name));
+            }
+            (false, text) => {
+                self.out.push('>');
+                self.newline();
+                if let Some(text) = text {
+                    self.pad(depth + 1);
+                    self.out.push_str(&escape(text));
+                    self.newline();
+                }
+                for (key, item) in children {
+                    self.element(key, item, depth + 1, &child_path(path, key))?;
+                }
+                self.pad(depth);
+                self.out.push_str(&format!("</{}>", name));
+            }
+        }
+        self.newline();
+        Ok(())
+    }
+
+    fn pad(&mut self, depth: usize) {
+        self.out.push_str(&" ".repeat(self.options.indent * depth));
+    }
+
+    fn newline(&mut self) {
+        if self.options.indent > 0 {
+            self.out.push('\n');
+        }
+    }
+}
+
+fn check_name(name: &str, path: &str) -> Result<(), UtilityError> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '-' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(unrepresentable(
+            "XML",
+            path,
+            &format!("'{}' is not a valid XML name", name),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{convert_data, ConvertOptions, DataFormat};
+
+    fn compact() -> Option<ConvertOptions> {
+        Some(ConvertOptions {
+            indent: 0,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn reads_attributes_text_and_repeats() {
+        let xml = r#"<?xml version="1.0"?>
+            <catalog version="2">
+                <book id="b1"><title>Rust &amp; You</title><price>9.5</price></book>
+                <book id="b2"><title><![CDATA[<XML>]]></title></book>
+                <note lang="en">hi</note>
+                <empty/>
+            </catalog>"#;
+        let json = convert_data(xml, DataFormat::Xml, DataFormat::Json, compact()).unwrap();
+        assert_eq!(
+            json,
+            r##"{"catalog":{"@version":2,"book":[{"@id":"b1","title":"Rust & You","price":9.5},{"@id":"b2","title":"<XML>"}],"note":{"@lang":"en","#text":"hi"},"empty":null}}"##
+        );
+    }
+
+    #[test]
+    fn writes_and_round_trips() {
+        let json = r##"{"catalog":{"@version":2,"book":[{"@id":"b1","title":"A < B"},{"@id":"b2","title":"C"}],"note":{"@lang":"en","#text":"hi"},"empty":null}}"##;
+        let xml = convert_data(json, DataFormat::Json, DataFormat::Xml, None).unwrap();
+        assert_eq!(
+            xml,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <catalog version=\"2\">\n  \
+               <book id=\"b1\">\n    <title>A &lt; B</title>\n  </book>\n  \
+               <book id=\"b2\">\n    <title>C</title>\n  </book>\n  \
+               <note lang=\"en\">hi</note>\n  \
+               <empty/>\n\
+             </catalog>\n"
+        );
+        let back = convert_data(&xml, DataFormat::Xml, DataFormat::Json, compact()).unwrap();
+        assert_eq!(back, json);
+    }
+
+    #[test]
+    fn wraps_and_rejects() {
+        let xml = convert_data("[1,2]", DataFormat::Json, DataFormat::Xml, compact()).unwrap();
+        assert!(
+            xml.ends_with("<root><item>1</item><item>2</item></root>\n"),
+            "{}",
+            xml
+        );
+
+        let err = convert_data(
+            r#"{"a":{"bad key":1}}"#,
+            DataFormat::Json,
+            DataFormat::Xml,
+            None,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("$.a[\"bad key\"]"), "{}", err);
+        assert!(convert_data("<a><b></a>", DataFormat::Xml, DataFormat::Json, None).is_err());
+        assert!(convert_data("<a/><b/>", DataFormat::Xml, DataFormat::Json, None).is_err());
+    }
+}
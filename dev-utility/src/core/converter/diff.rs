@@ -0,0 +1,705 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Line diff between two texts, as a unified diff and as side-by-side
+//! hunks with word or character highlights inside changed lines, plus
+//! application of unified diffs.
+
+mod myers;
+
+use crate::error::{Diagnostic, UtilityError};
+use myers::Edit;
+use serde::{Deserialize, Serialize};
+use universal_function_macro::universal_function;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum DiffGranularity {
+    #[default]
+    Line, // Changed lines are highlighted whole
+    Word, // Runs of letters and digits, runs of spaces, single symbols
+    Char,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct TextDiffOptions {
+    pub granularity: DiffGranularity,
+    pub ignore_whitespace: bool, // Compare with all whitespace removed
+    pub ignore_case: bool,
+    pub ignore_line_ending: bool, // CRLF, LF and a missing final newline compare equal
+    pub context: usize,           // Unchanged lines around each hunk
+}
+
+impl Default for TextDiffOptions {
+    fn default() -> Self {
+        Self {
+            granularity: DiffGranularity::Line,
+            ignore_whitespace: false,
+            ignore_case: false,
+            ignore_line_ending: false,
+            context: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum DiffTag {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct DiffSpan {
+    pub tag: DiffTag,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum DiffRowKind {
+    Equal,
+    Added,
+    Removed,
+    Changed, // A removed line paired with the line that replaced it
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct DiffRow {
+    pub kind: DiffRowKind,
+    pub left_line: Option<usize>,  // 1-based; None on an added row
+    pub right_line: Option<usize>, // 1-based; None on a removed row
+    pub left: Vec<DiffSpan>,       // Line without its ending; empty on an added row
+    pub right: Vec<DiffSpan>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct DiffHunk {
+    pub header: String, // e.g. `@@ -3,7 +3,8 @@`
+    pub rows: Vec<DiffRow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct TextDiffResult {
+    pub unified: String, // Empty when the texts compare equal
+    pub hunks: Vec<DiffHunk>,
+    pub additions: usize, // Added lines
+    pub deletions: usize, // Removed lines
+}
+
+/// Compare two texts line by line.
+///
+/// The unified diff is always line based and applies to `left` with
+/// `apply_text_patch`; `granularity` only decides how changed lines are
+/// highlighted in the hunks. The ignore options change what counts as
+/// equal, not what is printed: unchanged lines show the left-hand text.
+#[universal_function]
+pub fn diff_text(
+    left: &str,
+    right: &str,
+    options: Option<TextDiffOptions>,
+) -> Result<TextDiffResult, UtilityError> {
+    let options = options.unwrap_or_default();
+    let left_lines: Vec<&str> = left.split_inclusive('\n').collect();
+    let right_lines: Vec<&str> = right.split_inclusive('\n').collect();
+    let left_keys: Vec<String> = left_lines.iter().map(|l| line_key(l, &options)).collect();
+    let right_keys: Vec<String> = right_lines.iter().map(|l| line_key(l, &options)).collect();
+    let edits = myers::diff(&left_keys, &right_keys);
+
+    let mut result = TextDiffResult {
+        unified: String::new(),
+        hunks: Vec::new(),
+        additions: 0,
+        deletions: 0,
+    };
+    for edit in &edits {
+        match edit {
+            Edit::Insert(_) => result.additions += 1,
+            Edit::Delete(_) => result.deletions += 1,
+            Edit::Equal(..) => {}
+        }
+    }
+    if result.additions + result.deletions == 0 {
+        return Ok(result);
+    }
+
+    result.unified.push_str("--- original\n+++ modified\n");
+    for range in hunk_ranges(&edits, options.context) {
+        let (left_start, right_start) = position(&edits[..range.start]);
+        let edits = &edits[range];
+        let left_len = edits
+            .iter()
+            .filter(|e| !matches!(e, Edit::Insert(_)))
+            .count();
+        let right_len = edits
+            .iter()
+            .filter(|e| !matches!(e, Edit::Delete(_)))
+            .count();
+        let header = format!(
+            "@@ -{} +{} @@",
+            hunk_range(left_start, left_len),
+            hunk_range(right_start, right_len)
+        );
+        result.unified.push_str(&header);
+        result.unified.push('\n');
+
+        let mut rows = Vec::new();
+        let mut i = 0;
+        while i < edits.len() {
+            if let Edit::Equal(x, y) = edits[i] {
+                push_line(&mut result.unified, ' ', left_lines[x]);
+                let text = body(left_lines[x]);
+                rows.push(DiffRow {
+                    kind: DiffRowKind::Equal,
+                    left_line: Some(x + 1),
+                    right_line: Some(y + 1),
+                    left: vec![span(DiffTag::Equal, text)],
+                    right: vec![span(DiffTag::Equal, body(right_lines[y]))],
+                });
+                i += 1;
+                continue;
+            }
+            let end = edits[i..]
+                .iter()
+                .position(|e| matches!(e, Edit::Equal(..)))
+                .map_or(edits.len(), |n| i + n);
+            let mut removed = Vec::new();
+            let mut added = Vec::new();
+            for edit in &edits[i..end] {
+                match *edit {
+                    Edit::Delete(x) => removed.push(x),
+                    Edit::Insert(y) => added.push(y),
+                    Edit::Equal(..) => {}
+                }
+            }
+            for &x in &removed {
+                push_line(&mut result.unified, '-', left_lines[x]);
+            }
+            for &y in &added {
+                push_line(&mut result.unified, '+', right_lines[y]);
+            }
+            for n in 0..removed.len().max(added.len()) {
+                rows.push(match (removed.get(n), added.get(n)) {
+                    (Some(&x), Some(&y)) => {
+                        let (left, right) =
+                            inline(body(left_lines[x]), body(right_lines[y]), &options);
+                        DiffRow {
+                            kind: DiffRowKind::Changed,
+                            left_line: Some(x + 1),
+                            right_line: Some(y + 1),
+                            left,
+                            right,
+                        }
+                    }
+                    (Some(&x), None) => DiffRow {
+                        kind: DiffRowKind::Removed,
+                        left_line: Some(x + 1),
+                        right_line: None,
+                        left: vec![span(DiffTag::Delete, body(left_lines[x]))],
+                        right: Vec::new(),
+                    },
+                    (None, Some(&y)) => DiffRow {
+                        kind: DiffRowKind::Added,
+                        left_line: None,
+                        right_line: Some(y + 1),
+                        left: Vec::new(),
+                        right: vec![span(DiffTag::Insert, body(right_lines[y]))],
+                    },
+                    (None, None) => unreachable!(),
+                });
+            }
+            i = end;
+        }
+        result.hunks.push(DiffHunk { header, rows });
+    }
+    Ok(result)
+}
+
+/// Apply a unified diff to `input`.
+///
+/// File headers and anything outside hunks are skipped, so the output of
+/// `git diff` for a single file applies as is. A hunk whose lines moved
+/// is found by searching outward from where its header says it starts;
+/// context is compared without line endings, and kept lines keep the
+/// ending they have in `input`. An empty patch, as printed for equal
+/// texts, returns `input` unchanged.
+#[universal_function]
+pub fn apply_text_patch(input: &str, patch: &str) -> Result<String, UtilityError> {
+    let hunks = parse_patch(patch)?;
+    if hunks.is_empty() && !patch.trim().is_empty() {
+        return Err(UtilityError::InvalidInput(
+            "The patch has no hunks (lines starting with @@)".to_string(),
+        ));
+    }
+
+    let lines: Vec<&str> = input.split_inclusive('\n').collect();
+    let mut out = String::with_capacity(input.len());
+    let mut cursor = 0;
+    let mut shift: isize = 0;
+    for (n, hunk) in hunks.iter().enumerate() {
+        let old: Vec<&str> = hunk
+            .lines
+            .iter()
+            .filter(|(kind, _)| *kind != '+')
+            .map(|(_, text)| body(text))
+            .collect();
+        let start = if hunk.old_len == 0 {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        let wanted = (start as isize + shift).max(cursor as isize) as usize;
+        let fits = |at: usize| {
+            at + old.len() <= lines.len()
+                && old
+                    .iter()
+                    .zip(&lines[at..])
+                    .all(|(want, have)| *want == body(have))
+        };
+        let found = (0..=lines.len()).find_map(|distance| {
+            let before = wanted
+                .checked_sub(distance)
+                .filter(|at| distance > 0 && *at >= cursor);
+            [Some(wanted + distance), before]
+                .into_iter()
+                .flatten()
+                .find(|at| fits(*at))
+        });
+        let Some(at) = found else {
+            let line = if hunk.old_len == 0 {
+                hunk.old_start + 1
+            } else {
+                hunk.old_start
+            };
+            return Err(Diagnostic::new(
+                patch,
+                hunk.offset,
+                format!("Hunk {} does not apply", n + 1),
+            )
+            .with_hint(format!(
+                "The text near line {} does not match the hunk's context and removed lines",
+                line
+            ))
+            .into());
+        };
+
+        out.extend(lines[cursor..at].iter().copied());
+        let mut kept = at;
+        for (kind, text) in &hunk.lines {
+            match kind {
+                ' ' => {
+                    out.push_str(lines[kept]);
+                    kept += 1;
+                }
+                '-' => kept += 1,
+                _ => out.push_str(text),
+            }
+        }
+        shift = at as isize - start as isize;
+        cursor = at + old.len();
+    }
+    out.extend(lines[cursor..].iter().copied());
+    Ok(out)
+}
+
+/// What a line is compared by.
+fn line_key(line: &str, options: &TextDiffOptions) -> String {
+    let line = if options.ignore_line_ending {
+        body(line)
+    } else {
+        line
+    };
+    token_key(line, options)
+}
+
+fn token_key(text: &str, options: &TextDiffOptions) -> String {
+    let text: String = if options.ignore_whitespace {
+        text.chars().filter(|c| !c.is_whitespace()).collect()
+    } else {
+        text.to_string()
+    };
+    if options.ignore_case {
+        text.to_lowercase()
+    } else {
+        text
+    }
+}
+
+/// A line without its `\n` or `\r\n`.
+fn body(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+fn span(tag: DiffTag, text: &str) -> DiffSpan {
+    DiffSpan {
+        tag,
+        text: text.to_string(),
+    }
+}
+
+fn push_line(out: &mut String, prefix: char, line: &str) {
+    out.push(prefix);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// Ranges of `edits` that make up each hunk: every change with up to
+/// `context` unchanged lines around it, merged when they touch.
+fn hunk_ranges(edits: &[Edit], context: usize) -> Vec<std::ops::Range<usize>> {
+    let mut ranges: Vec<std::ops::Range<usize>> = Vec::new();
+    for (i, _) in edits
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !matches!(e, Edit::Equal(..)))
+    {
+        let start = i.saturating_sub(context);
+        let end = (i + 1 + context).min(edits.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
+
+/// 0-based left and right line numbers after `edits`.
+fn position(edits: &[Edit]) -> (usize, usize) {
+    edits.iter().fold((0, 0), |(x, y), edit| match edit {
+        Edit::Equal(..) => (x + 1, y + 1),
+        Edit::Delete(_) => (x + 1, y),
+        Edit::Insert(_) => (x, y + 1),
+    })
+}
+
+/// `start,len` as printed in a hunk header; an empty range names the line
+/// before it.
+fn hunk_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, len),
+    }
+}
+
+/// Highlights for a changed pair of lines.
+fn inline(left: &str, right: &str, options: &TextDiffOptions) -> (Vec<DiffSpan>, Vec<DiffSpan>) {
+    if options.granularity == DiffGranularity::Line {
+        return (
+            vec![span(DiffTag::Delete, left)],
+            vec![span(DiffTag::Insert, right)],
+        );
+    }
+    let split = |text| match options.granularity {
+        DiffGranularity::Word => words(text),
+        _ => chars(text),
+    };
+    let (left_tokens, right_tokens) = (split(left), split(right));
+    let keys =
+        |tokens: &[&str]| -> Vec<String> { tokens.iter().map(|t| token_key(t, options)).collect() };
+    let edits = myers::diff(&keys(&left_tokens), &keys(&right_tokens));
+
+    let mut left_spans: Vec<DiffSpan> = Vec::new();
+    let mut right_spans: Vec<DiffSpan> = Vec::new();
+    for edit in edits {
+        match edit {
+            Edit::Equal(x, y) => {
+                extend(&mut left_spans, DiffTag::Equal, left_tokens[x]);
+                extend(&mut right_spans, DiffTag::Equal, right_tokens[y]);
+            }
+            Edit::Delete(x) => extend(&mut left_spans, DiffTag::Delete, left_tokens[x]),
+            Edit::Insert(y) => extend(&mut right_spans, DiffTag::Insert, right_tokens[y]),
+        }
+    }
+    (left_spans, right_spans)
+}
+
+fn extend(spans: &mut Vec<DiffSpan>, tag: DiffTag, text: &str) {
+    match spans.last_mut() {
+        Some(last) if last.tag == tag => last.text.push_str(text),
+        _ => spans.push(span(tag, text)),
+    }
+}
+
+fn words(text: &str) -> Vec<&str> {
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            0
+        } else if c.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut prev = None;
+    for (i, c) in text.char_indices() {
+        let current = class(c);
+        if i > start && (prev != Some(current) || current == 2) {
+            tokens.push(&text[start..i]);
+            start = i;
+        }
+        prev = Some(current);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+fn chars(text: &str) -> Vec<&str> {
+    text.char_indices()
+        .map(|(i, c)| &text[i..i + c.len_utf8()])
+        .collect()
+}
+
+struct Hunk {
+    offset: usize, // Byte offset of the `@@` line in the patch
+    old_start: usize,
+    old_len: usize,
+    lines: Vec<(char, String)>, // Kind and text with its ending
+}
+
+fn parse_patch(patch: &str) -> Result<Vec<Hunk>, UtilityError> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    // Lines still expected in the current hunk, old side and new side.
+    let mut remaining: (usize, usize) = (0, 0);
+    let mut offset = 0;
+    for line in patch.split_inclusive('\n') {
+        let at = offset;
+        offset += line.len();
+        if line.starts_with('\\') {
+            // `\ No newline at end of file` belongs to the line before.
+            if let Some((_, text)) = hunks.last_mut().and_then(|h| h.lines.last_mut()) {
+                let trimmed = body(text).len();
+                text.truncate(trimmed);
+            }
+            continue;
+        }
+        if remaining != (0, 0) {
+            let (kind, text) = match line.chars().next() {
+                Some(kind @ (' ' | '-' | '+')) => (kind, &line[1..]),
+                // Editors often strip the space from empty context lines.
+                Some('\n' | '\r') => (' ', line),
+                _ => {
+                    return Err(Diagnostic::new(
+                        patch,
+                        at,
+                        "Hunk ends early; expected a line starting with a space, - or +",
+                    )
+                    .with_hint("Check the line counts in the @@ header")
+                    .into())
+                }
+            };
+            match kind {
+                ' ' => remaining = (remaining.0.saturating_sub(1), remaining.1.saturating_sub(1)),
+                '-' => remaining.0 = remaining.0.saturating_sub(1),
+                _ => remaining.1 = remaining.1.saturating_sub(1),
+            }
+            if let Some(hunk) = hunks.last_mut() {
+                hunk.lines.push((kind, text.to_string()));
+            }
+            continue;
+        }
+        if line.starts_with("@@") {
+            let (old, new) = parse_header(line).ok_or_else(|| {
+                UtilityError::from(
+                    Diagnostic::new(patch, at, "Malformed hunk header")
+                        .with_hint("Expected @@ -start,count +start,count @@"),
+                )
+            })?;
+            remaining = (old.1, new.1);
+            hunks.push(Hunk {
+                offset: at,
+                old_start: old.0,
+                old_len: old.1,
+                lines: Vec::new(),
+            });
+        }
+    }
+    if remaining != (0, 0) {
+        return Err(
+            Diagnostic::new(patch, patch.len(), "The last hunk is cut short")
+                .with_hint("Check the line counts in the @@ header")
+                .into(),
+        );
+    }
+    Ok(hunks)
+}
+
+/// The old and new `(start, len)` of `@@ -a,b +c,d @@`.
+fn parse_header(line: &str) -> Option<((usize, usize), (usize, usize))> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, _) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    let range = |text: &str| -> Option<(usize, usize)> {
+        match text.split_once(',') {
+            Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+            None => Some((text.parse().ok()?, 1)),
+        }
+    };
+    Some((range(old)?, range(new)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(f: impl FnOnce(&mut TextDiffOptions)) -> Option<TextDiffOptions> {
+        let mut options = TextDiffOptions::default();
+        f(&mut options);
+        Some(options)
+    }
+
+    #[test]
+    fn prints_unified_hunks() {
+        let left = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let right = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\n";
+        let result = diff_text(left, right, options(|o| o.context = 1)).unwrap();
+        assert_eq!(
+            result.unified,
+            "--- original\n+++ modified\n\
+             @@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n\
+             @@ -10 +10,2 @@\n j\n+k\n"
+        );
+        assert_eq!((result.additions, result.deletions), (2, 1));
+        assert_eq!(result.hunks.len(), 2);
+        assert_eq!(result.hunks[0].rows[1].kind, DiffRowKind::Changed);
+
+        let result = diff_text("a\nb", "a\nb\n", None).unwrap();
+        assert!(result
+            .unified
+            .contains("-b\n\\ No newline at end of file\n+b\n"));
+        assert!(
+            diff_text("a\r\nb", "a\nb\n", options(|o| o.ignore_line_ending = true))
+                .unwrap()
+                .hunks
+                .is_empty()
+        );
+        assert!(diff_text("", "", None).unwrap().unified.is_empty());
+    }
+
+    #[test]
+    fn ignores_whitespace_and_case() {
+        let left = "fn main() {\n    Run( 1 );\n}\n";
+        let right = "fn main() {\n\trun(1);\n}\n";
+        assert_eq!(diff_text(left, right, None).unwrap().deletions, 1);
+        let ignoring = options(|o| {
+            o.ignore_whitespace = true;
+            o.ignore_case = true;
+        });
+        assert!(diff_text(left, right, ignoring).unwrap().unified.is_empty());
+    }
+
+    #[test]
+    fn highlights_words_and_chars() {
+        let row = |granularity| {
+            let result = diff_text(
+                "let total = price * qty;\n",
+                "let total = price * quantity;\n",
+                options(|o| o.granularity = granularity),
+            )
+            .unwrap();
+            result.hunks[0].rows[0].clone()
+        };
+        let word = row(DiffGranularity::Word);
+        assert_eq!(word.left[1], span(DiffTag::Delete, "qty"));
+        assert_eq!(word.right[1], span(DiffTag::Insert, "quantity"));
+        assert_eq!(word.right[2], span(DiffTag::Equal, ";"));
+
+        let char = row(DiffGranularity::Char);
+        let inserted: String = char
+            .right
+            .iter()
+            .filter(|s| s.tag == DiffTag::Insert)
+            .map(|s| s.text.as_str())
+            .collect();
+        assert_eq!(inserted, "uanti");
+        assert_eq!(row(DiffGranularity::Line).left.len(), 1);
+    }
+
+    #[test]
+    fn diffs_large_unrelated_texts() {
+        let left: String = (0..20_000).map(|i| format!("left {}\n", i)).collect();
+        let right: String = (0..20_000).map(|i| format!("right {}\n", i)).collect();
+        let result = diff_text(&left, &right, None).unwrap();
+        assert_eq!((result.additions, result.deletions), (20_000, 20_000));
+        assert_eq!(apply_text_patch(&left, &result.unified).unwrap(), right);
+
+        let left = "ab".repeat(5_000);
+        let right = "cd".repeat(5_000);
+        let result = diff_text(
+            &left,
+            &right,
+            options(|o| o.granularity = DiffGranularity::Char),
+        );
+        let row = &result.unwrap().hunks[0].rows[0];
+        assert_eq!(row.left, [span(DiffTag::Delete, &left)]);
+        assert_eq!(row.right, [span(DiffTag::Insert, &right)]);
+    }
+
+    #[test]
+    fn applies_its_own_patches() {
+        let cases = [
+            ("a\nb\nc\n", "a\nc\nd\n"),
+            ("", "new\nfile\n"),
+            ("x\ny", "x\ny\n"),
+            (
+                "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\n",
+                "zero\none\ntwo\nthree\nfour\nfive\nsix\nseven\nEIGHT\nnine\n",
+            ),
+            ("keep\r\nme\r\n", "keep\r\nyou\r\n"),
+        ];
+        for (left, right) in cases {
+            let patch = diff_text(left, right, options(|o| o.context = 1))
+                .unwrap()
+                .unified;
+            assert_eq!(apply_text_patch(left, &patch).unwrap(), right, "{}", patch);
+        }
+    }
+
+    #[test]
+    fn finds_moved_hunks_and_reports_conflicts() {
+        let patch = "--- a/f\n+++ b/f\n@@ -2,2 +2,2 @@\n b\n-c\n+C\n";
+        assert_eq!(
+            apply_text_patch("new\nlines\na\nb\nc\n", patch).unwrap(),
+            "new\nlines\na\nb\nC\n"
+        );
+        let err = apply_text_patch("a\nb\nx\n", patch)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Hunk 1 does not apply"), "{}", err);
+        assert!(apply_text_patch("a\n", "not a patch").is_err());
+        assert!(apply_text_patch("a\n", "@@ -1,2 +1,2 @@\n a\n").is_err());
+    }
+}
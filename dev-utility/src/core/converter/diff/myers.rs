@@ -0,0 +1,261 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Myers' O(ND) shortest edit script, in linear space.
+//!
+//! Each range is split where its shortest path crosses the middle, found by
+//! searching from both ends at once, and the two halves are diffed in turn,
+//! so only two frontiers are ever kept. A range whose middle is not found
+//! within `MAX_COST` rounds is split at the furthest point reached instead,
+//! as GNU diff does, trading a minimal script for bounded time.
+
+use std::ops::Range;
+
+/// Search rounds per split; scripts of up to twice as many edits are
+/// always minimal.
+const MAX_COST: isize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Edit {
+    Equal(usize, usize), // Left index, right index
+    Delete(usize),       // Left index
+    Insert(usize),       // Right index
+}
+
+enum Task {
+    Diff(Range<usize>, Range<usize>),
+    Equal(usize, usize, usize), // Left start, right start, length
+}
+
+pub(super) fn diff<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    // Tasks run in document order, so the later of two is pushed first.
+    let mut tasks = vec![Task::Diff(0..a.len(), 0..b.len())];
+    while let Some(task) = tasks.pop() {
+        let (mut xs, mut ys) = match task {
+            Task::Equal(x, y, len) => {
+                edits.extend((0..len).map(|i| Edit::Equal(x + i, y + i)));
+                continue;
+            }
+            Task::Diff(xs, ys) => (xs, ys),
+        };
+        while !xs.is_empty() && !ys.is_empty() && a[xs.start] == b[ys.start] {
+            edits.push(Edit::Equal(xs.start, ys.start));
+            xs.start += 1;
+            ys.start += 1;
+        }
+        let mut suffix = 0;
+        while !xs.is_empty() && !ys.is_empty() && a[xs.end - 1] == b[ys.end - 1] {
+            xs.end -= 1;
+            ys.end -= 1;
+            suffix += 1;
+        }
+        tasks.push(Task::Equal(xs.end, ys.end, suffix));
+
+        match split(&a[xs.clone()], &b[ys.clone()]) {
+            Some((x, y)) => {
+                tasks.push(Task::Diff(xs.start + x..xs.end, ys.start + y..ys.end));
+                tasks.push(Task::Diff(xs.start..xs.start + x, ys.start..ys.start + y));
+            }
+            None => edits.extend(xs.map(Edit::Delete).chain(ys.map(Edit::Insert))),
+        }
+    }
+    // Halves can leave insertions before deletions; put each change's
+    // deletions first, as diff output does.
+    for run in edits.split_mut(|edit| matches!(edit, Edit::Equal(..))) {
+        run.sort_by_key(|edit| matches!(edit, Edit::Insert(_)));
+    }
+    edits
+}
+
+/// A point on a shortest path through `a` and `b`, other than either end,
+/// for inputs that differ at both ends. `None` if one of them is empty,
+/// leaving nothing to do but delete one and insert the other.
+fn split<T: PartialEq>(a: &[T], b: &[T]) -> Option<(usize, usize)> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let delta = n - m;
+    // The paths meet on a forward round when delta is odd, else on a
+    // backward one.
+    let front = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    let offset = max + 1;
+    // forward[k + offset] is the furthest x reached on diagonal k = x - y,
+    // and backward the same from the far corner; -1 where not reached.
+    let mut forward = vec![-1isize; 2 * offset as usize + 1];
+    let mut backward = forward.clone();
+    forward[offset as usize + 1] = 0;
+    backward[offset as usize + 1] = 0;
+    let at = |v: &[isize], k: isize| {
+        usize::try_from(k + offset)
+            .ok()
+            .and_then(|i| v.get(i).copied())
+    };
+    // Diagonals trimmed from each end once they ran off the grid.
+    let (mut forward_trim, mut backward_trim) = ((0, 0), (0, 0));
+
+    let rounds = max.min(MAX_COST);
+    for d in 0..=rounds {
+        for k in (-d + forward_trim.0..=d - forward_trim.1).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && forward[i - 1] < forward[i + 1]) {
+                forward[i + 1]
+            } else {
+                forward[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[i] = x;
+            if x > n {
+                forward_trim.1 += 2;
+            } else if y > m {
+                forward_trim.0 += 2;
+            } else if front {
+                if let Some(back) = at(&backward, delta - k).filter(|&back| back != -1) {
+                    if x >= n - back {
+                        return Some((x as usize, y as usize));
+                    }
+                }
+            }
+        }
+
+        for k in (-d + backward_trim.0..=d - backward_trim.1).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && backward[i - 1] < backward[i + 1]) {
+                backward[i + 1]
+            } else {
+                backward[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[(n - x - 1) as usize] == b[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[i] = x;
+            if x > n {
+                backward_trim.1 += 2;
+            } else if y > m {
+                backward_trim.0 += 2;
+            } else if !front {
+                let k = delta - k;
+                if let Some(front) = at(&forward, k).filter(|&front| front != -1) {
+                    if front >= n - x {
+                        return Some((front as usize, (front - k) as usize));
+                    }
+                }
+            }
+        }
+    }
+
+    // Over budget: settle for the forward point furthest along.
+    let d = rounds;
+    (-d + forward_trim.0..=d - forward_trim.1)
+        .step_by(2)
+        .map(|k| (forward[(k + offset) as usize], k))
+        .filter(|&(x, k)| x <= n && x - k <= m)
+        .max_by_key(|&(x, k)| 2 * x - k)
+        .map(|(x, k)| (x as usize, (x - k) as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(a: &str, b: &str) -> String {
+        let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+        diff(&a, &b)
+            .into_iter()
+            .map(|edit| match edit {
+                Edit::Equal(x, _) => a[x].to_string(),
+                Edit::Delete(x) => format!("-{}", a[x]),
+                Edit::Insert(y) => format!("+{}", b[y]),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_a_shortest_script() {
+        assert_eq!(script("abc", "abc"), "abc");
+        assert_eq!(script("", "ab"), "+a+b");
+        assert_eq!(script("abcabba", "cbabac").matches(['-', '+']).count(), 5);
+        assert_eq!(script("kitten", "sitting"), "-k+sitt-e+in+g");
+    }
+
+    /// Check that `edits` turns `a` into `b`, and return how many changes
+    /// it makes.
+    fn changes<T: PartialEq>(a: &[T], b: &[T], edits: &[Edit]) -> usize {
+        let (mut x, mut y) = (0, 0);
+        for &edit in edits {
+            match edit {
+                Edit::Equal(i, j) => {
+                    assert!((i, j) == (x, y) && a[i] == b[j], "{:?}", edit);
+                    (x, y) = (x + 1, y + 1);
+                }
+                Edit::Delete(i) => {
+                    assert_eq!(i, x);
+                    x += 1;
+                }
+                Edit::Insert(j) => {
+                    assert_eq!(j, y);
+                    y += 1;
+                }
+            }
+        }
+        assert_eq!((x, y), (a.len(), b.len()));
+        edits
+            .iter()
+            .filter(|edit| !matches!(edit, Edit::Equal(..)))
+            .count()
+    }
+
+    #[test]
+    fn matches_the_longest_common_subsequence() {
+        let mut seed = 7u32;
+        let mut random = |len: u32| -> Vec<u32> {
+            (0..len)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    (seed >> 16) % 3
+                })
+                .collect()
+        };
+        for round in 0..500 {
+            let (a, b) = (random(round % 13), random(round % 17));
+            let mut lcs = vec![vec![0; b.len() + 1]; a.len() + 1];
+            for i in (0..a.len()).rev() {
+                for j in (0..b.len()).rev() {
+                    lcs[i][j] = if a[i] == b[j] {
+                        lcs[i + 1][j + 1] + 1
+                    } else {
+                        lcs[i + 1][j].max(lcs[i][j + 1])
+                    };
+                }
+            }
+            let expected = a.len() + b.len() - 2 * lcs[0][0];
+            assert_eq!(changes(&a, &b, &diff(&a, &b)), expected, "{:?} {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn handles_large_inputs() {
+        // Unrelated inputs are far over budget, yet still get a valid script.
+        let (a, b): (Vec<u32>, Vec<u32>) = ((0..20_000).collect(), (20_000..40_000).collect());
+        assert_eq!(changes(&a, &b, &diff(&a, &b)), 40_000);
+        // Scattered changes stay within budget, so the script is minimal.
+        let b: Vec<u32> = (0..20_000)
+            .map(|i| if i % 97 == 0 { i + 1 } else { i })
+            .collect();
+        let changed = (0..20_000).filter(|i| i % 97 == 0).count();
+        assert_eq!(changes(&a, &b, &diff(&a, &b)), 2 * changed);
+    }
+}
@@ -7,9 +7,17 @@
 // See LICENSE file for details or contact admin@aprilnea.com
 
 pub mod backslash;
+pub mod data;
+pub mod diff;
 pub mod number_base;
+pub mod regex_tester;
 pub mod string_inspector;
+pub mod text;
 
 pub use backslash::*;
+pub use data::*;
+pub use diff::*;
 pub use number_base::*;
+pub use regex_tester::*;
 pub use string_inspector::*;
+pub use text::*;
@@ -1,4 +1,4 @@
-use crate::error::UtilityError;
+use crate::error::{Diagnostic, UtilityError};
 use universal_function_macro::universal_function;
 
 /// Convert an integer literal between arbitrary bases (2..=36).
@@ -23,8 +23,19 @@ pub fn number_base_convert(
         return Ok(String::new());
     }
 
-    let value = i128::from_str_radix(trimmed, from_base)
-        .map_err(|e| UtilityError::ParseError(e.to_string()))?;
+    let value = i128::from_str_radix(trimmed, from_base).map_err(|e| {
+        let start = input.len() - input.trim_start().len();
+        let digits = trimmed.strip_prefix(['-', '+']).unwrap_or(trimmed);
+        let sign = trimmed.len() - digits.len();
+        match digits.char_indices().find(|(_, c)| !c.is_digit(from_base)) {
+            Some((i, c)) => Diagnostic::new(
+                input,
+                start + sign + i,
+                format!("'{}' is not a base-{} digit", c, from_base),
+            ),
+            None => Diagnostic::new(input, start, e.to_string()).with_length(trimmed.len()),
+        }
+    })?;
 
     Ok(render_in_base(value, to_base))
 }
@@ -77,4 +88,12 @@ mod tests {
         assert!(number_base_convert("10", 1, 10).is_err());
         assert!(number_base_convert("10", 10, 37).is_err());
     }
+
+    #[test]
+    fn points_at_the_bad_digit() {
+        let err = number_base_convert("  -1012", 2, 10).unwrap_err();
+        let diagnostic = err.diagnostic().unwrap();
+        assert_eq!(diagnostic.offset, 6);
+        assert_eq!(diagnostic.message, "'2' is not a base-2 digit");
+    }
 }
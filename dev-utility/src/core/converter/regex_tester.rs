@@ -0,0 +1,489 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Regular expression tester: matches with capture groups, replacement
+//! and a token-by-token explanation of the pattern.
+//!
+//! Patterns run on the `regex` crate, which guarantees linear time, or on
+//! `fancy-regex` when they need look-around, back-references or atomic
+//! groups. `fancy-regex` hands every part it can to `regex`, so the two
+//! agree wherever both accept a pattern.
+
+mod explain;
+
+use std::ops::Range;
+
+use crate::error::{Diagnostic, UtilityError};
+use serde::{Deserialize, Serialize};
+use universal_function_macro::universal_function;
+
+pub use explain::{RegexToken, RegexTokenKind};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum RegexEngine {
+    #[default]
+    Auto, // Standard, or Fancy when the pattern needs it
+    Standard, // `regex`: linear time, no look-around or back-references
+    Fancy,    // `fancy-regex`: backtracking where needed
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct RegexOptions {
+    pub engine: RegexEngine,
+    pub case_insensitive: bool,     // i
+    pub multi_line: bool,           // m: ^ and $ match at line breaks
+    pub dot_matches_new_line: bool, // s
+    pub ignore_whitespace: bool,    // x: whitespace and # comments in the pattern are ignored
+    pub swap_greed: bool,           // U: quantifiers are lazy unless followed by ?
+    pub limit: usize,               // Most matches to return
+}
+
+impl Default for RegexOptions {
+    fn default() -> Self {
+        Self {
+            engine: RegexEngine::Auto,
+            case_insensitive: false,
+            multi_line: false,
+            dot_matches_new_line: false,
+            ignore_whitespace: false,
+            swap_greed: false,
+            limit: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct RegexGroup {
+    pub index: usize,
+    pub name: Option<String>,
+    pub text: Option<String>, // None when the group did not take part in the match
+    pub start: Option<usize>, // Byte offsets into the text
+    pub end: Option<usize>,
+    pub char_start: Option<usize>, // Character offsets into the text
+    pub char_end: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct RegexMatch {
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+    pub groups: Vec<RegexGroup>, // Numbered groups from 1, in pattern order
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct RegexTestResult {
+    pub engine: RegexEngine, // The engine that ran; never Auto
+    pub matches: Vec<RegexMatch>,
+    pub truncated: bool, // More matches than `limit`
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct RegexReplaceResult {
+    pub output: String,
+    pub replacements: usize,
+}
+
+/// Run `pattern` over `text` and return every match with its groups.
+#[universal_function]
+pub fn test_regex(
+    pattern: &str,
+    text: &str,
+    options: Option<RegexOptions>,
+) -> Result<RegexTestResult, UtilityError> {
+    let options = options.unwrap_or_default();
+    let compiled = Compiled::new(pattern, &options)?;
+    let names = compiled.names();
+
+    let mut matches = Vec::new();
+    let mut truncated = false;
+    // Characters before `cursor.0`, which is always a match start.
+    let mut cursor = (0, 0);
+    compiled.each(text, |spans| {
+        if matches.len() == options.limit {
+            truncated = true;
+            return false;
+        }
+        let whole = spans[0].clone().unwrap_or_default();
+        cursor.1 += text[cursor.0..whole.start].chars().count();
+        cursor.0 = whole.start;
+        let chars_to = |at: usize| cursor.1 + text[whole.start..at].chars().count();
+        let groups = spans
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(index, span)| RegexGroup {
+                index,
+                name: names[index].clone(),
+                text: span.clone().map(|span| text[span].to_string()),
+                start: span.as_ref().map(|span| span.start),
+                end: span.as_ref().map(|span| span.end),
+                char_start: span.as_ref().map(|span| chars_to(span.start)),
+                char_end: span.as_ref().map(|span| chars_to(span.end)),
+            })
+            .collect();
+        matches.push(RegexMatch {
+            text: text[whole.clone()].to_string(),
+            start: whole.start,
+            end: whole.end,
+            char_start: cursor.1,
+            char_end: chars_to(whole.end),
+            groups,
+        });
+        true
+    })?;
+
+    Ok(RegexTestResult {
+        engine: compiled.engine(),
+        matches,
+        truncated,
+    })
+}
+
+/// Replace every match of `pattern` in `text`.
+///
+/// `replacement` may refer to groups as `$1`, `$name`, `${1}` or
+/// `${name}`, and `$$` is a literal dollar sign. A reference takes the
+/// longest name it can, so `$1a` means the group named `1a`; write `${1}a`
+/// for group 1 followed by `a`. Unknown groups expand to nothing.
+#[universal_function]
+pub fn replace_regex(
+    pattern: &str,
+    text: &str,
+    replacement: &str,
+    options: Option<RegexOptions>,
+) -> Result<RegexReplaceResult, UtilityError> {
+    let options = options.unwrap_or_default();
+    let compiled = Compiled::new(pattern, &options)?;
+    let names = compiled.names();
+
+    let mut output = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut replacements = 0;
+    compiled.each(text, |spans| {
+        let whole = spans[0].clone().unwrap_or_default();
+        output.push_str(&text[last..whole.start]);
+        expand(replacement, text, spans, &names, &mut output);
+        last = whole.end;
+        replacements += 1;
+        true
+    })?;
+    output.push_str(&text[last..]);
+
+    Ok(RegexReplaceResult {
+        output,
+        replacements,
+    })
+}
+
+/// Break `pattern` into tokens and describe each one.
+///
+/// The pattern is compiled first, so an invalid pattern is reported the
+/// same way `test_regex` reports it.
+#[universal_function]
+pub fn explain_regex(
+    pattern: &str,
+    options: Option<RegexOptions>,
+) -> Result<Vec<RegexToken>, UtilityError> {
+    let options = options.unwrap_or_default();
+    Compiled::new(pattern, &options)?;
+    Ok(explain::explain(pattern, &options))
+}
+
+enum Compiled {
+    Standard(regex::Regex),
+    Fancy(fancy_regex::Regex),
+}
+
+impl Compiled {
+    fn new(pattern: &str, options: &RegexOptions) -> Result<Self, UtilityError> {
+        match options.engine {
+            RegexEngine::Standard => Self::standard(pattern, options),
+            RegexEngine::Fancy => Self::fancy(pattern, options),
+            RegexEngine::Auto => Self::standard(pattern, options)
+                .or_else(|error| Self::fancy(pattern, options).map_err(|_| error)),
+        }
+    }
+
+    fn standard(pattern: &str, options: &RegexOptions) -> Result<Self, UtilityError> {
+        regex::RegexBuilder::new(pattern)
+            .case_insensitive(options.case_insensitive)
+            .multi_line(options.multi_line)
+            .dot_matches_new_line(options.dot_matches_new_line)
+            .ignore_whitespace(options.ignore_whitespace)
+            .swap_greed(options.swap_greed)
+            .build()
+            .map(Compiled::Standard)
+            .map_err(|error| match error {
+                regex::Error::Syntax(message) => syntax_error(pattern, options, message),
+                other => UtilityError::InvalidInput(other.to_string()),
+            })
+    }
+
+    fn fancy(pattern: &str, options: &RegexOptions) -> Result<Self, UtilityError> {
+        // fancy-regex only has a builder option for case; the other flags
+        // go in front of the pattern and are subtracted from positions.
+        let flags: String = [
+            (options.multi_line, 'm'),
+            (options.dot_matches_new_line, 's'),
+            (options.ignore_whitespace, 'x'),
+            (options.swap_greed, 'U'),
+        ]
+        .iter()
+        .filter(|(on, _)| *on)
+        .map(|(_, flag)| *flag)
+        .collect();
+        let prefix = if flags.is_empty() {
+            String::new()
+        } else {
+            format!("(?{})", flags)
+        };
+        fancy_regex::RegexBuilder::new(&format!("{}{}", prefix, pattern))
+            .case_insensitive(options.case_insensitive)
+            .build()
+            .map(Compiled::Fancy)
+            .map_err(|error| match error {
+                fancy_regex::Error::ParseError(at, kind) => Diagnostic::new(
+                    pattern,
+                    at.saturating_sub(prefix.len()),
+                    capitalize(&kind.to_string()),
+                )
+                .into(),
+                other => UtilityError::InvalidInput(capitalize(&other.to_string())),
+            })
+    }
+
+    fn engine(&self) -> RegexEngine {
+        match self {
+            Compiled::Standard(_) => RegexEngine::Standard,
+            Compiled::Fancy(_) => RegexEngine::Fancy,
+        }
+    }
+
+    /// Group names by index; index 0 is the whole match.
+    fn names(&self) -> Vec<Option<String>> {
+        match self {
+            Compiled::Standard(re) => re
+                .capture_names()
+                .map(|name| name.map(str::to_string))
+                .collect(),
+            Compiled::Fancy(re) => re
+                .capture_names()
+                .map(|name| name.map(str::to_string))
+                .collect(),
+        }
+    }
+
+    /// Call `f` with the group spans of each match until it returns false.
+    fn each(
+        &self,
+        text: &str,
+        mut f: impl FnMut(&[Option<Range<usize>>]) -> bool,
+    ) -> Result<(), UtilityError> {
+        match self {
+            Compiled::Standard(re) => {
+                for captures in re.captures_iter(text) {
+                    let spans: Vec<_> = captures.iter().map(|m| m.map(|m| m.range())).collect();
+                    if !f(&spans) {
+                        break;
+                    }
+                }
+            }
+            Compiled::Fancy(re) => {
+                for captures in re.captures_iter(text) {
+                    let captures =
+                        captures.map_err(|e| UtilityError::Runtime(capitalize(&e.to_string())))?;
+                    let spans: Vec<_> = captures.iter().map(|m| m.map(|m| m.range())).collect();
+                    if !f(&spans) {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Locate a `regex` syntax error by parsing again with `regex-syntax`,
+/// whose errors carry a span.
+fn syntax_error(pattern: &str, options: &RegexOptions, message: String) -> UtilityError {
+    let parsed = regex_syntax::ParserBuilder::new()
+        .case_insensitive(options.case_insensitive)
+        .multi_line(options.multi_line)
+        .dot_matches_new_line(options.dot_matches_new_line)
+        .ignore_whitespace(options.ignore_whitespace)
+        .swap_greed(options.swap_greed)
+        .build()
+        .parse(pattern);
+    let (kind, span) = match parsed {
+        Err(regex_syntax::Error::Parse(e)) => (e.kind().to_string(), *e.span()),
+        Err(regex_syntax::Error::Translate(e)) => (e.kind().to_string(), *e.span()),
+        _ => return UtilityError::InvalidInput(message),
+    };
+    let mut diagnostic = Diagnostic::new(pattern, span.start.offset, capitalize(&kind))
+        .with_length(span.end.offset - span.start.offset);
+    if options.engine == RegexEngine::Standard
+        && (kind.contains("look-around") || kind.contains("backreferences"))
+    {
+        diagnostic = diagnostic.with_hint("The fancy engine supports this");
+    }
+    diagnostic.into()
+}
+
+fn capitalize(message: &str) -> String {
+    let mut chars = message.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Append `template` with its group references filled in from `spans`.
+fn expand(
+    template: &str,
+    text: &str,
+    spans: &[Option<Range<usize>>],
+    names: &[Option<String>],
+    out: &mut String,
+) {
+    let group = |name: &str| -> Option<&str> {
+        let index = match name.parse::<usize>() {
+            Ok(index) => index,
+            Err(_) => names.iter().position(|n| n.as_deref() == Some(name))?,
+        };
+        spans.get(index)?.clone().map(|span| &text[span])
+    };
+    let mut rest = template;
+    while let Some(at) = rest.find('$') {
+        out.push_str(&rest[..at]);
+        rest = &rest[at + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            out.push('$');
+            rest = after;
+        } else if let Some((name, after)) = rest
+            .strip_prefix('{')
+            .and_then(|braced| braced.split_once('}'))
+        {
+            out.push_str(group(name).unwrap_or_default());
+            rest = after;
+        } else {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            if len == 0 {
+                out.push('$');
+            } else {
+                out.push_str(group(&rest[..len]).unwrap_or_default());
+            }
+            rest = &rest[len..];
+        }
+    }
+    out.push_str(rest);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with(f: impl FnOnce(&mut RegexOptions)) -> Option<RegexOptions> {
+        let mut options = RegexOptions::default();
+        f(&mut options);
+        Some(options)
+    }
+
+    #[test]
+    fn reports_matches_with_groups() {
+        let result = test_regex(r"(?<key>\w+)=(\d+)?", "é=1 b= c=3", None).unwrap();
+        assert_eq!(result.engine, RegexEngine::Standard);
+        assert_eq!(result.matches.len(), 3);
+        let first = &result.matches[0];
+        assert_eq!((first.start, first.end), (0, 4));
+        assert_eq!((first.char_start, first.char_end), (0, 3));
+        assert_eq!(first.groups[0].name.as_deref(), Some("key"));
+        assert_eq!(first.groups[0].text.as_deref(), Some("é"));
+        assert_eq!(first.groups[1].char_start, Some(2));
+        assert_eq!(result.matches[1].groups[1].text, None);
+        assert_eq!(result.matches[2].char_start, 7);
+
+        let limited = test_regex("a", "aaa", with(|o| o.limit = 2)).unwrap();
+        assert!(limited.truncated);
+        assert_eq!(limited.matches.len(), 2);
+    }
+
+    #[test]
+    fn applies_flags_on_both_engines() {
+        for engine in [RegexEngine::Standard, RegexEngine::Fancy] {
+            let options = with(|o| {
+                o.engine = engine;
+                o.case_insensitive = true;
+                o.multi_line = true;
+            });
+            let result = test_regex("^ab$", "AB\nab\nabc", options).unwrap();
+            assert_eq!(result.matches.len(), 2, "{:?}", engine);
+        }
+    }
+
+    #[test]
+    fn falls_back_to_fancy_for_lookaround() {
+        let result = test_regex(r"(\w)\1(?=!)", "aa! bb cc!", None).unwrap();
+        assert_eq!(result.engine, RegexEngine::Fancy);
+        let found: Vec<_> = result.matches.iter().map(|m| m.text.as_str()).collect();
+        assert_eq!(found, ["aa", "cc"]);
+
+        let err =
+            test_regex(r"a(?=b)", "", with(|o| o.engine = RegexEngine::Standard)).unwrap_err();
+        let diagnostic = err.diagnostic().unwrap();
+        assert_eq!(diagnostic.offset, 1);
+        assert_eq!(
+            diagnostic.hint.as_deref(),
+            Some("The fancy engine supports this")
+        );
+    }
+
+    #[test]
+    fn locates_syntax_errors() {
+        let err = test_regex("ab(c", "", None).unwrap_err();
+        assert_eq!(err.diagnostic().unwrap().offset, 2);
+        let err = test_regex("a{2,1}", "", None).unwrap_err();
+        assert!(err.diagnostic().is_some(), "{}", err);
+    }
+
+    #[test]
+    fn expands_replacement_templates() {
+        let replace =
+            |pattern, text, template| replace_regex(pattern, text, template, None).unwrap().output;
+        assert_eq!(
+            replace(r"(?<y>\d{4})-(\d{2})", "2024-05 and 1999-12", "$2/${y}"),
+            "05/2024 and 12/1999"
+        );
+        assert_eq!(replace(r"(\d)", "1 2", "${1}a$$"), "1a$ 2a$");
+        assert_eq!(replace(r"(\d)", "1", "$1a|$x|$"), "||$");
+        let result = replace_regex("o", "foo", "0", None).unwrap();
+        assert_eq!(result.replacements, 2);
+    }
+}
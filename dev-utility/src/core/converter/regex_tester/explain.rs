@@ -0,0 +1,739 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Plain-English breakdown of a pattern, one token at a time.
+//!
+//! The pattern has already compiled, so the scanner is lenient: anything
+//! it does not recognise is described as a literal. Inline flags apply
+//! until the end of the group they appear in, as they do when matching.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+use serde::{Deserialize, Serialize};
+
+use super::{capitalize, RegexOptions};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum RegexTokenKind {
+    Literal,
+    CharacterType, // `.`, `\d`, `\p{Greek}` and the like
+    Class,         // `[...]`
+    Anchor,        // `^`, `$`, `\b` and other zero-width assertions
+    Group,         // Opening or closing parenthesis
+    Quantifier,
+    Alternation,
+    Backreference,
+    Flags, // `(?i)`
+    Comment,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct RegexToken {
+    pub kind: RegexTokenKind,
+    pub text: String,
+    pub start: usize, // Byte offsets into the pattern
+    pub end: usize,
+    pub depth: usize, // Group nesting; a group's parentheses sit at the outer depth
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Flags {
+    multi_line: bool,
+    dot_all: bool,
+    extended: bool,
+    swap_greed: bool,
+}
+
+struct Scanner<'p> {
+    pattern: &'p str,
+    pos: usize,
+    flags: Flags,
+    groups: usize,
+    // Closing description and the flags to restore, per open group
+    stack: Vec<(String, Flags)>,
+    tokens: Vec<RegexToken>,
+}
+
+pub(super) fn explain(pattern: &str, options: &RegexOptions) -> Vec<RegexToken> {
+    let mut scanner = Scanner {
+        pattern,
+        pos: 0,
+        flags: Flags {
+            multi_line: options.multi_line,
+            dot_all: options.dot_matches_new_line,
+            extended: options.ignore_whitespace,
+            swap_greed: options.swap_greed,
+        },
+        groups: 0,
+        stack: Vec::new(),
+        tokens: Vec::new(),
+    };
+    scanner.run();
+    scanner.tokens
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<char> {
+        self.pattern[self.pos..].chars().next()
+    }
+
+    fn rest(&self) -> &str {
+        &self.pattern[self.pos..]
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    /// Advance past `text` if the pattern continues with it.
+    fn eat(&mut self, text: &str) -> bool {
+        let found = self.rest().starts_with(text);
+        if found {
+            self.pos += text.len();
+        }
+        found
+    }
+
+    /// Advance past the next `close` and return what came before it.
+    fn until(&mut self, close: char) -> &str {
+        let start = self.pos;
+        let end = self
+            .rest()
+            .find(close)
+            .map_or(self.pattern.len(), |n| start + n);
+        self.pos = (end + close.len_utf8()).min(self.pattern.len());
+        &self.pattern[start..end]
+    }
+
+    fn push(&mut self, kind: RegexTokenKind, start: usize, description: impl Into<String>) {
+        self.tokens.push(RegexToken {
+            kind,
+            text: self.pattern[start..self.pos].to_string(),
+            start,
+            end: self.pos,
+            depth: self.stack.len(),
+            description: description.into(),
+        });
+    }
+
+    fn run(&mut self) {
+        while let Some(c) = self.peek() {
+            let start = self.pos;
+            if self.flags.extended && c.is_whitespace() {
+                self.bump();
+                continue;
+            }
+            if self.flags.extended && c == '#' {
+                self.until('\n');
+                let text = self.pattern[start..self.pos].trim_end().to_string();
+                self.pos = start + text.len();
+                self.push(RegexTokenKind::Comment, start, "Comment");
+                continue;
+            }
+            self.bump();
+            match c {
+                '\\' => self.escape(start),
+                '[' => self.class(start),
+                '(' => self.open_group(start),
+                ')' => match self.stack.pop() {
+                    Some((description, flags)) => {
+                        self.flags = flags;
+                        self.push(RegexTokenKind::Group, start, description);
+                    }
+                    None => self.literal(start, c),
+                },
+                '|' => self.push(RegexTokenKind::Alternation, start, "Or"),
+                '*' | '+' | '?' => self.quantifier(start, c),
+                '{' if self.repeat_range().is_some() => self.quantifier(start, c),
+                '.' => {
+                    let description = if self.flags.dot_all {
+                        "Any character"
+                    } else {
+                        "Any character except a newline"
+                    };
+                    self.push(RegexTokenKind::CharacterType, start, description);
+                }
+                '^' => {
+                    let description = if self.flags.multi_line {
+                        "The start of a line"
+                    } else {
+                        "The start of the text"
+                    };
+                    self.push(RegexTokenKind::Anchor, start, description);
+                }
+                '$' => {
+                    let description = if self.flags.multi_line {
+                        "The end of a line"
+                    } else {
+                        "The end of the text"
+                    };
+                    self.push(RegexTokenKind::Anchor, start, description);
+                }
+                c => self.literal(start, c),
+            }
+        }
+    }
+
+    /// A plain character, merged into the literal just before it.
+    fn literal(&mut self, start: usize, c: char) {
+        if let Some(last) = self.tokens.last_mut() {
+            let plain = !last.text.starts_with('\\');
+            if last.kind == RegexTokenKind::Literal && last.end == start && plain {
+                last.text.push(c);
+                last.end = self.pos;
+                last.description = describe_text(&last.text);
+                return;
+            }
+        }
+        self.push(
+            RegexTokenKind::Literal,
+            start,
+            describe_text(&c.to_string()),
+        );
+    }
+
+    fn escape(&mut self, start: usize) {
+        use RegexTokenKind::*;
+        let Some(c) = self.bump() else {
+            return self.push(Literal, start, "A backslash");
+        };
+        let (kind, description) = match c {
+            'd' => (CharacterType, "Any digit".to_string()),
+            'D' => (CharacterType, "Any character except a digit".to_string()),
+            'w' => (
+                CharacterType,
+                "Any word character (letter, digit or underscore)".to_string(),
+            ),
+            'W' => (
+                CharacterType,
+                "Any character except a word character".to_string(),
+            ),
+            's' => (CharacterType, "Any whitespace character".to_string()),
+            'S' => (CharacterType, "Any character except whitespace".to_string()),
+            'b' if self.rest().starts_with('{') => {
+                let which = self.until('}').trim_start_matches('{').to_string();
+                let description = match which.as_str() {
+                    "start" => "The start of a word",
+                    "end" => "The end of a word",
+                    "start-half" => "A position not preceded by a word character",
+                    "end-half" => "A position not followed by a word character",
+                    _ => "A word boundary",
+                };
+                (Anchor, description.to_string())
+            }
+            'b' => (Anchor, "A word boundary".to_string()),
+            'B' => (Anchor, "A position that is not a word boundary".to_string()),
+            '<' => (Anchor, "The start of a word".to_string()),
+            '>' => (Anchor, "The end of a word".to_string()),
+            'A' => (Anchor, "The start of the text".to_string()),
+            'z' => (Anchor, "The end of the text".to_string()),
+            'Z' => (
+                Anchor,
+                "The end of the text, or before a final newline".to_string(),
+            ),
+            'G' => (Anchor, "Where the previous match ended".to_string()),
+            'K' => (
+                Anchor,
+                "Drop everything matched so far from the reported match".to_string(),
+            ),
+            'n' => (Literal, "A newline".to_string()),
+            'r' => (Literal, "A carriage return".to_string()),
+            't' => (Literal, "A tab".to_string()),
+            'f' => (Literal, "A form feed".to_string()),
+            'v' => (Literal, "A vertical tab".to_string()),
+            'a' => (Literal, "A bell character".to_string()),
+            'e' => (Literal, "An escape character".to_string()),
+            'x' | 'u' | 'U' => {
+                let digits = match (c, self.eat("{")) {
+                    (_, true) => self.until('}').to_string(),
+                    ('x', false) => self.take_hex(2),
+                    ('u', false) => self.take_hex(4),
+                    _ => self.take_hex(8),
+                };
+                let description = u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .map_or_else(
+                        || format!("The code point U+{}", digits.to_uppercase()),
+                        |ch| format!("{} (U+{:04X})", describe_text(&ch.to_string()), ch as u32),
+                    );
+                (Literal, description)
+            }
+            'p' | 'P' => {
+                let name = if self.eat("{") {
+                    self.until('}').to_string()
+                } else {
+                    self.bump().map(String::from).unwrap_or_default()
+                };
+                let negated = (c == 'P') != name.starts_with('^');
+                let name = name.trim_start_matches('^');
+                let description = if negated {
+                    format!("Any character not in the Unicode class {}", name)
+                } else {
+                    format!("Any character in the Unicode class {}", name)
+                };
+                (CharacterType, description)
+            }
+            '1'..='9' => {
+                let mut number = c.to_string();
+                while let Some(d) = self.peek().filter(char::is_ascii_digit) {
+                    number.push(d);
+                    self.bump();
+                }
+                (
+                    Backreference,
+                    format!("The same text group {} matched", number),
+                )
+            }
+            'k' => {
+                let name = match self.bump() {
+                    Some('<') => self.until('>').to_string(),
+                    Some('{') => self.until('}').to_string(),
+                    Some('\'') => self.until('\'').to_string(),
+                    _ => String::new(),
+                };
+                (
+                    Backreference,
+                    format!("The same text group `{}` matched", name),
+                )
+            }
+            c => (Literal, describe_text(&c.to_string())),
+        };
+        self.push(kind, start, description);
+    }
+
+    fn take_hex(&mut self, max: usize) -> String {
+        let digits: String = self
+            .rest()
+            .chars()
+            .take(max)
+            .take_while(char::is_ascii_hexdigit)
+            .collect();
+        self.pos += digits.len();
+        digits
+    }
+
+    fn class(&mut self, start: usize) {
+        let negated = self.eat("^");
+        let body_start = self.pos;
+        // A `]` right after the opening bracket is a literal.
+        let mut depth = 1;
+        let mut first = true;
+        let mut nested = false;
+        while let Some(c) = self.bump() {
+            match c {
+                '\\' => {
+                    self.bump();
+                }
+                '[' if self.rest().starts_with(':') => {
+                    self.until(']');
+                }
+                '[' => {
+                    depth += 1;
+                    nested = true;
+                }
+                ']' if !first => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            first = false;
+        }
+        let body = &self.pattern[body_start..self.pos.saturating_sub(1).max(body_start)];
+        let items = if nested || body.contains("&&") || body.contains("--") || body.contains("~~") {
+            None
+        } else {
+            class_items(body)
+        };
+        let description = match (items, negated) {
+            (Some(items), false) => format!("One character from: {}", items.join(", ")),
+            (Some(items), true) => format!("One character except: {}", items.join(", ")),
+            (None, false) => "One character from the set".to_string(),
+            (None, true) => "One character outside the set".to_string(),
+        };
+        self.push(RegexTokenKind::Class, start, description);
+    }
+
+    fn open_group(&mut self, start: usize) {
+        let saved = self.flags;
+        if !self.eat("?") {
+            self.groups += 1;
+            let description = format!("Start of capture group {}", self.groups);
+            self.push(RegexTokenKind::Group, start, description);
+            self.stack
+                .push((format!("End of capture group {}", self.groups), saved));
+            return;
+        }
+        let (description, closing) = if self.eat(":") {
+            (
+                "Start of a non-capturing group".to_string(),
+                "End of the non-capturing group",
+            )
+        } else if self.eat("=") {
+            (
+                "Start of a lookahead: what follows must match".to_string(),
+                "End of the lookahead",
+            )
+        } else if self.eat("!") {
+            (
+                "Start of a negative lookahead: what follows must not match".to_string(),
+                "End of the negative lookahead",
+            )
+        } else if self.eat("<=") {
+            (
+                "Start of a lookbehind: what precedes must match".to_string(),
+                "End of the lookbehind",
+            )
+        } else if self.eat("<!") {
+            (
+                "Start of a negative lookbehind: what precedes must not match".to_string(),
+                "End of the negative lookbehind",
+            )
+        } else if self.eat(">") {
+            (
+                "Start of an atomic group: once it matches, it is never retried".to_string(),
+                "End of the atomic group",
+            )
+        } else if self.eat("P=") {
+            let name = self.until(')').to_string();
+            return self.push(
+                RegexTokenKind::Backreference,
+                start,
+                format!("The same text group `{}` matched", name),
+            );
+        } else if self.eat("P<") || self.eat("<") {
+            let name = self.until('>').to_string();
+            self.groups += 1;
+            let description = format!("Start of capture group {}, named `{}`", self.groups, name);
+            self.push(RegexTokenKind::Group, start, description);
+            self.stack
+                .push((format!("End of capture group {}", self.groups), saved));
+            return;
+        } else if self.rest().starts_with('(') {
+            (
+                "Start of a conditional group".to_string(),
+                "End of the conditional group",
+            )
+        } else {
+            let end = self
+                .rest()
+                .find([':', ')'])
+                .map_or(self.pattern.len(), |n| self.pos + n);
+            let flags = &self.pattern[self.pos..end];
+            let description = describe_flags(flags);
+            self.set_flags(flags);
+            self.pos = end;
+            if self.bump() == Some(')') {
+                // Applies to the rest of the enclosing group.
+                return self.push(
+                    RegexTokenKind::Flags,
+                    start,
+                    format!("{} until the end of the group", description),
+                );
+            }
+            self.push(
+                RegexTokenKind::Group,
+                start,
+                format!(
+                    "Start of a non-capturing group: {}",
+                    lowercase(&description)
+                ),
+            );
+            self.stack
+                .push(("End of the non-capturing group".to_string(), saved));
+            return;
+        };
+        self.push(RegexTokenKind::Group, start, description);
+        self.stack.push((closing.to_string(), saved));
+    }
+
+    fn set_flags(&mut self, flags: &str) {
+        let mut on = true;
+        for flag in flags.chars() {
+            match flag {
+                '-' => on = false,
+                'm' => self.flags.multi_line = on,
+                's' => self.flags.dot_all = on,
+                'x' => self.flags.extended = on,
+                'U' => self.flags.swap_greed = on,
+                _ => {}
+            }
+        }
+    }
+
+    /// `{n}`, `{n,}`, `{n,m}` or `{,m}` right after an opening brace.
+    fn repeat_range(&self) -> Option<(Option<u32>, Option<u32>, bool)> {
+        let (inside, _) = self.rest().split_once('}')?;
+        let inside = inside.trim();
+        let number = |text: &str| -> Option<Option<u32>> {
+            let text = text.trim();
+            if text.is_empty() {
+                Some(None)
+            } else {
+                text.parse().ok().map(Some)
+            }
+        };
+        match inside.split_once(',') {
+            Some((min, max)) => {
+                let (min, max) = (number(min)?, number(max)?);
+                (min.is_some() || max.is_some()).then_some((min, max, true))
+            }
+            None => Some((Some(inside.parse().ok()?), None, false)),
+        }
+    }
+
+    fn quantifier(&mut self, start: usize, c: char) {
+        // A quantifier binds to the last character of a literal run.
+        if let Some(last) = self.tokens.last_mut() {
+            let chars = last.text.chars().count();
+            if last.kind == RegexTokenKind::Literal && !last.text.starts_with('\\') && chars > 1 {
+                let tail = last.text.pop().unwrap_or_default();
+                last.end -= tail.len_utf8();
+                last.description = describe_text(&last.text);
+                let (end, depth) = (last.end, last.depth);
+                self.tokens.push(RegexToken {
+                    kind: RegexTokenKind::Literal,
+                    text: tail.to_string(),
+                    start: end,
+                    end: end + tail.len_utf8(),
+                    depth,
+                    description: describe_text(&tail.to_string()),
+                });
+            }
+        }
+
+        let times = match c {
+            '*' => "zero or more times".to_string(),
+            '+' => "one or more times".to_string(),
+            '?' => "optionally".to_string(),
+            _ => {
+                let range = self.repeat_range();
+                self.until('}');
+                match range {
+                    Some((Some(n), None, false)) => format!("exactly {} times", n),
+                    Some((Some(n), None, true)) => format!("{} or more times", n),
+                    Some((Some(n), Some(m), _)) => format!("between {} and {} times", n, m),
+                    Some((None, Some(m), _)) => format!("at most {} times", m),
+                    _ => "a number of times".to_string(),
+                }
+            }
+        };
+        let exact = !self.pattern[start..self.pos].contains(',') && c == '{';
+        let how = if self.eat("+") {
+            ", without giving any back"
+        } else if exact {
+            self.eat("?");
+            ""
+        } else if self.eat("?") != self.flags.swap_greed {
+            ", as few as possible"
+        } else {
+            ", as many as possible"
+        };
+        self.push(
+            RegexTokenKind::Quantifier,
+            start,
+            format!("The previous item, {}{}", times, how),
+        );
+    }
+}
+
+fn class_items(body: &str) -> Option<Vec<String>> {
+    let mut items = Vec::new();
+    let mut chars = body.chars().peekable();
+    while chars.peek().is_some() {
+        let (low, text) = class_atom(&mut chars)?;
+        let mut ahead = chars.clone();
+        if low.is_some() && ahead.next() == Some('-') && ahead.peek().is_some() {
+            chars.next();
+            let (_, high) = class_atom(&mut chars)?;
+            items.push(format!("{} to {}", text, high));
+        } else {
+            items.push(text);
+        }
+    }
+    Some(items)
+}
+
+/// One class member: the character, if it can start a range, and how it
+/// reads.
+fn class_atom(chars: &mut Peekable<Chars>) -> Option<(Option<char>, String)> {
+    let c = chars.next()?;
+    if c == '[' {
+        // POSIX class such as [:alpha:]
+        let name: String = chars.by_ref().take_while(|c| *c != ']').collect();
+        return Some((None, format!("[{}]", name)));
+    }
+    if c != '\\' {
+        return Some((Some(c), describe_char(c)));
+    }
+    let escaped = chars.next()?;
+    let named = match escaped {
+        'd' => "digits",
+        'D' => "non-digits",
+        'w' => "word characters",
+        'W' => "non-word characters",
+        's' => "whitespace",
+        'S' => "non-whitespace",
+        'n' => return Some((Some('\n'), "a newline".to_string())),
+        't' => return Some((Some('\t'), "a tab".to_string())),
+        'r' => return Some((Some('\r'), "a carriage return".to_string())),
+        'x' | 'u' | 'p' | 'P' => {
+            let mut text = format!("\\{}", escaped);
+            if chars.peek() == Some(&'{') {
+                for c in chars.by_ref() {
+                    text.push(c);
+                    if c == '}' {
+                        break;
+                    }
+                }
+            } else {
+                let len = match escaped {
+                    'x' => 2,
+                    'u' => 4,
+                    _ => 1,
+                };
+                for _ in 0..len {
+                    text.extend(chars.next());
+                }
+            }
+            return Some((None, format!("`{}`", text)));
+        }
+        c => return Some((Some(c), describe_char(c))),
+    };
+    Some((None, named.to_string()))
+}
+
+fn describe_char(c: char) -> String {
+    match c {
+        ' ' => "a space".to_string(),
+        '\n' => "a newline".to_string(),
+        '\t' => "a tab".to_string(),
+        c if c.is_control() => format!("U+{:04X}", c as u32),
+        c => format!("`{}`", c),
+    }
+}
+
+fn describe_text(text: &str) -> String {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => match describe_char(c) {
+            quoted if quoted.starts_with('`') => format!("The character {}", quoted),
+            named => capitalize(&named),
+        },
+        _ => format!("The text `{}`", text),
+    }
+}
+
+fn describe_flags(flags: &str) -> String {
+    let name = |flag| match flag {
+        'i' => "case-insensitive matching",
+        'm' => "multi-line mode (^ and $ match at line breaks)",
+        's' => "`.` matching newlines",
+        'x' => "verbose mode (whitespace and # comments ignored)",
+        'U' => "lazy quantifiers by default",
+        'u' => "Unicode mode",
+        'R' => "CRLF mode",
+        _ => "an unknown flag",
+    };
+    let (on, off) = flags.split_once('-').unwrap_or((flags, ""));
+    let list = |flags: &str| flags.chars().map(name).collect::<Vec<_>>().join(" and ");
+    match (on.is_empty(), off.is_empty()) {
+        (false, true) => format!("Turn on {}", list(on)),
+        (true, false) => format!("Turn off {}", list(off)),
+        (false, false) => format!("Turn on {}; turn off {}", list(on), list(off)),
+        (true, true) => "No flag changes".to_string(),
+    }
+}
+
+fn lowercase(description: &str) -> String {
+    let mut chars = description.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn explained(pattern: &str) -> Vec<(String, String)> {
+        explain(pattern, &RegexOptions::default())
+            .into_iter()
+            .map(|token| (token.text, token.description))
+            .collect()
+    }
+
+    #[test]
+    fn describes_each_token() {
+        let tokens = explained(r"^(?<year>\d{4})-ab+[^a-z_]*?$");
+        let texts: Vec<&str> = tokens.iter().map(|(text, _)| text.as_str()).collect();
+        assert_eq!(
+            texts,
+            ["^", "(?<year>", r"\d", "{4}", ")", "-a", "b", "+", "[^a-z_]", "*?", "$"]
+        );
+        assert_eq!(tokens[1].1, "Start of capture group 1, named `year`");
+        assert_eq!(tokens[3].1, "The previous item, exactly 4 times");
+        assert_eq!(tokens[4].1, "End of capture group 1");
+        assert_eq!(tokens[5].1, "The text `-a`");
+        assert_eq!(
+            tokens[7].1,
+            "The previous item, one or more times, as many as possible"
+        );
+        assert_eq!(tokens[8].1, "One character except: `a` to `z`, `_`");
+        assert_eq!(
+            tokens[9].1,
+            "The previous item, zero or more times, as few as possible"
+        );
+    }
+
+    #[test]
+    fn scopes_inline_flags_and_lookaround() {
+        let tokens = explained(r"(?=x)((?m)^)^\x41(?i:a)\1");
+        assert_eq!(tokens[0].1, "Start of a lookahead: what follows must match");
+        assert_eq!(tokens[4].0, "(?m)");
+        assert_eq!(tokens[5].1, "The start of a line");
+        assert_eq!(tokens[7].1, "The start of the text");
+        assert_eq!(tokens[8].1, "The character `A` (U+0041)");
+        assert_eq!(
+            tokens[9].1,
+            "Start of a non-capturing group: turn on case-insensitive matching"
+        );
+        assert_eq!(tokens[12].1, "The same text group 1 matched");
+
+        let options = RegexOptions {
+            ignore_whitespace: true,
+            ..Default::default()
+        };
+        let tokens = explain("a b  # note\nc", &options);
+        let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                RegexTokenKind::Literal,
+                RegexTokenKind::Literal,
+                RegexTokenKind::Comment,
+                RegexTokenKind::Literal
+            ]
+        );
+        assert_eq!(tokens[2].text, "# note");
+    }
+}
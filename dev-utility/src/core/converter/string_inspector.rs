@@ -1,4 +1,17 @@
+mod confusables;
+pub(crate) mod names;
+
+use std::collections::{BTreeSet, HashSet};
+
+use icu_normalizer::{ComposingNormalizerBorrowed, DecomposingNormalizerBorrowed};
+use icu_properties::props::{
+    BidiClass, BidiControl, DefaultIgnorableCodePoint, ExtendedPictographic, GeneralCategory,
+    Script, WhiteSpace,
+};
+use icu_properties::{CodePointMapData, CodePointSetData, PropertyNamesLong, PropertyNamesShort};
 use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
 use universal_function_macro::universal_function;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -9,12 +22,82 @@ pub struct StringInspection {
     pub word_count: usize,
     pub line_count: usize,
     pub byte_count: usize,
+    pub grapheme_count: usize,
+    pub utf16_count: usize, // UTF-16 code units, as JavaScript's `length` counts
+    pub characters: Vec<CharInfo>,
+    pub warnings: Vec<InspectionWarning>,
+    pub normalizations: Vec<Normalization>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct CharInfo {
+    pub character: String,
+    pub code_point: String, // U+200B
+    pub name: Option<String>,
+    pub category: String,      // general category, e.g. Lu
+    pub category_name: String, // Uppercase Letter
+    pub script: String,
+    pub bidi_class: String, // e.g. L, R, AL
+    pub utf8: String,       // hex bytes, e.g. E2 80 8B
+    pub utf16: String,      // hex code units, e.g. D83D DE00
+    pub width: usize,       // display columns
+    pub byte_offset: usize,
+    pub grapheme: usize, // index of the grapheme cluster it belongs to
+    pub invisible: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum WarningKind {
+    Invisible,   // zero-width, format and control characters
+    Whitespace,  // spaces other than U+0020
+    Bidi,        // bidi overrides, embeddings, isolates and marks
+    Confusable,  // letters that look like Latin ones
+    MixedScript, // words mixing scripts
+    Replacement, // U+FFFD from a failed decode
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct InspectionWarning {
+    pub kind: WarningKind,
+    pub start: usize, // byte range in the input
+    pub end: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum NormalizationForm {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct Normalization {
+    pub form: NormalizationForm,
+    pub text: String,
+    pub char_count: usize,
+    pub byte_count: usize,
+    pub changed: bool, // the input is not already in this form
 }
 
 #[universal_function]
 pub fn inspect_string(input: &str) -> StringInspection {
+    let characters = characters(input);
     StringInspection {
-        char_count: input.chars().count(),
+        char_count: characters.len(),
         word_count: input.split_whitespace().count(),
         // `.lines()` drops a trailing empty line; count explicit newlines
         // to match the intuition of "how many lines are there" including
@@ -25,13 +108,300 @@ pub fn inspect_string(input: &str) -> StringInspection {
             input.matches('\n').count() + 1
         },
         byte_count: input.len(),
+        grapheme_count: input.graphemes(true).count(),
+        utf16_count: input.encode_utf16().count(),
+        warnings: warnings(input),
+        normalizations: normalizations(input),
+        characters,
+    }
+}
+
+fn hex(units: impl IntoIterator<Item = impl std::fmt::UpperHex>, width: usize) -> String {
+    units
+        .into_iter()
+        .map(|unit| format!("{:0width$X}", unit))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `NAME (U+XXXX)`, or just the code point when the name is not known.
+fn describe(c: char) -> String {
+    match names::name(c) {
+        Some(name) => format!("{} (U+{:04X})", name, c as u32),
+        None => format!("U+{:04X}", c as u32),
+    }
+}
+
+fn script_name(c: char) -> &'static str {
+    let script = CodePointMapData::<Script>::new().get(c);
+    PropertyNamesLong::<Script>::new()
+        .get(script)
+        .unwrap_or("Unknown")
+}
+
+fn is_invisible(c: char) -> bool {
+    let category = CodePointMapData::<GeneralCategory>::new().get(c);
+    CodePointSetData::new::<DefaultIgnorableCodePoint>().contains(c)
+        || CodePointSetData::new::<BidiControl>().contains(c)
+        || category == GeneralCategory::Format
+        || (category == GeneralCategory::Control && !matches!(c, '\t' | '\n' | '\r'))
+}
+
+fn characters(input: &str) -> Vec<CharInfo> {
+    let categories = CodePointMapData::<GeneralCategory>::new();
+    let bidi = CodePointMapData::<BidiClass>::new();
+    let mut characters = Vec::new();
+    for (grapheme, (offset, cluster)) in input.grapheme_indices(true).enumerate() {
+        for (i, c) in cluster.char_indices() {
+            let category = categories.get(c);
+            let mut utf16 = [0u16; 2];
+            characters.push(CharInfo {
+                character: c.to_string(),
+                code_point: format!("U+{:04X}", c as u32),
+                name: names::name(c),
+                category: PropertyNamesShort::<GeneralCategory>::new()
+                    .get(category)
+                    .unwrap_or_default()
+                    .to_string(),
+                category_name: PropertyNamesLong::<GeneralCategory>::new()
+                    .get(category)
+                    .unwrap_or_default()
+                    .replace('_', " "),
+                script: script_name(c).replace('_', " "),
+                bidi_class: PropertyNamesShort::<BidiClass>::new()
+                    .get(bidi.get(c))
+                    .unwrap_or_default()
+                    .to_string(),
+                utf8: hex(c.to_string().bytes(), 2),
+                utf16: hex(c.encode_utf16(&mut utf16).iter().copied(), 4),
+                width: c.width().unwrap_or(0),
+                byte_offset: offset + i,
+                grapheme,
+                invisible: is_invisible(c),
+            });
+        }
     }
+    characters
+}
+
+/// Flag characters that hide or disguise text, merging runs of the same
+/// character into one warning.
+fn warnings(input: &str) -> Vec<InspectionWarning> {
+    let mut flagged: Vec<(WarningKind, char, usize, usize, usize)> = Vec::new();
+    let unterminated = unterminated_bidi(input);
+    let pictographic = CodePointSetData::new::<ExtendedPictographic>();
+
+    for (offset, cluster) in input.grapheme_indices(true) {
+        // Joiners and variation selectors are part of emoji sequences.
+        let emoji = cluster.chars().any(|c| pictographic.contains(c));
+        for (i, c) in cluster.char_indices() {
+            let start = offset + i;
+            let kind = if CodePointSetData::new::<BidiControl>().contains(c) {
+                WarningKind::Bidi
+            } else if c == '\u{FFFD}' {
+                WarningKind::Replacement
+            } else if is_invisible(c) && !emoji {
+                WarningKind::Invisible
+            } else if CodePointSetData::new::<WhiteSpace>().contains(c)
+                && !matches!(c, ' ' | '\t' | '\n' | '\r')
+            {
+                WarningKind::Whitespace
+            } else {
+                continue;
+            };
+            match flagged.last_mut() {
+                Some(last)
+                    if last.0 == kind && last.1 == c && last.3 == start
+                        // An unterminated control always stands alone.
+                        && !unterminated.contains(&start) =>
+                {
+                    last.3 = start + c.len_utf8();
+                    last.4 += 1;
+                }
+                _ => flagged.push((kind, c, start, start + c.len_utf8(), 1)),
+            }
+        }
+    }
+
+    let mut warnings: Vec<InspectionWarning> = flagged
+        .into_iter()
+        .map(|(kind, c, start, end, count)| {
+            let subject = if count > 1 {
+                format!("{} × {}", count, describe(c))
+            } else {
+                describe(c)
+            };
+            let message = match kind {
+                WarningKind::Bidi if unterminated.contains(&start) => format!(
+                    "{} reorders the text after it and is not closed on its line",
+                    subject
+                ),
+                WarningKind::Bidi => format!("{} changes the display order of text", subject),
+                WarningKind::Replacement => format!(
+                    "{} marks bytes that failed to decode; the original text is lost",
+                    subject
+                ),
+                WarningKind::Whitespace => format!("{} looks like an ordinary space", subject),
+                _ => format!("{} is invisible", subject),
+            };
+            InspectionWarning {
+                kind,
+                start,
+                end,
+                message,
+            }
+        })
+        .collect();
+    warnings.extend(spoofing(input));
+    warnings.sort_by_key(|w| w.start);
+    warnings
+}
+
+/// Byte offsets of embeddings, overrides and isolates that are still open at
+/// the end of their line, which is how Trojan Source attacks hide code.
+fn unterminated_bidi(input: &str) -> HashSet<usize> {
+    let mut unterminated = HashSet::new();
+    let mut offset = 0;
+    for line in input.split('\n') {
+        let mut embeddings: Vec<usize> = Vec::new();
+        let mut isolates: Vec<usize> = Vec::new();
+        for (i, c) in line.char_indices() {
+            match c {
+                '\u{202A}'..='\u{202E}' if c != '\u{202C}' => embeddings.push(offset + i),
+                '\u{202C}' => {
+                    embeddings.pop();
+                }
+                '\u{2066}'..='\u{2068}' => isolates.push(offset + i),
+                '\u{2069}' => {
+                    isolates.pop();
+                }
+                _ => {}
+            }
+        }
+        unterminated.extend(embeddings);
+        unterminated.extend(isolates);
+        offset += line.len() + 1;
+    }
+    unterminated
+}
+
+/// Whether a word mixes scripts, allowing the combinations Chinese,
+/// Japanese and Korean are normally written in.
+fn mixed_scripts(scripts: &BTreeSet<&'static str>) -> bool {
+    let mut scripts = scripts.clone();
+    if ["Hiragana", "Katakana", "Hangul", "Bopomofo"]
+        .iter()
+        .any(|s| scripts.contains(s))
+    {
+        scripts.remove("Han");
+    }
+    if scripts.contains("Hiragana") {
+        scripts.remove("Katakana");
+    }
+    scripts.len() > 1
+}
+
+/// Confusable letters and words mixing scripts.
+fn spoofing(input: &str) -> Vec<InspectionWarning> {
+    let has_latin = input.chars().any(|c| script_name(c) == "Latin");
+    let mut warnings = Vec::new();
+    for (start, word) in input.split_word_bound_indices() {
+        if !word.chars().any(char::is_alphanumeric) {
+            continue;
+        }
+        let scripts: BTreeSet<&'static str> = word
+            .chars()
+            .map(script_name)
+            .filter(|s| !matches!(*s, "Common" | "Inherited" | "Unknown"))
+            .collect();
+        let mixed = mixed_scripts(&scripts);
+        let lookalike: String = word
+            .chars()
+            .map(|c| confusables::latin_lookalike(c).unwrap_or(c))
+            .collect();
+        // A word written entirely in lookalikes, like Cyrillic "сор",
+        // only matters next to Latin text.
+        let whole_script = has_latin
+            && !scripts.contains("Latin")
+            && word
+                .chars()
+                .filter(|c| c.is_alphabetic())
+                .all(|c| confusables::latin_lookalike(c).is_some());
+
+        if mixed {
+            let names: Vec<&str> = scripts.iter().copied().collect();
+            let mut message = format!("`{}` mixes {}", word, names.join(" and "));
+            if lookalike != word {
+                message.push_str(&format!("; it reads as `{}`", lookalike));
+            }
+            warnings.push(InspectionWarning {
+                kind: WarningKind::MixedScript,
+                start,
+                end: start + word.len(),
+                message,
+            });
+        }
+        for (i, c) in word.char_indices() {
+            let Some(latin) = confusables::latin_lookalike(c) else {
+                continue;
+            };
+            let fullwidth = ('\u{FF01}'..='\u{FF5E}').contains(&c);
+            if fullwidth || (mixed && script_name(c) != "Latin") || whole_script {
+                warnings.push(InspectionWarning {
+                    kind: WarningKind::Confusable,
+                    start: start + i,
+                    end: start + i + c.len_utf8(),
+                    message: format!("{} looks like Latin `{}`", describe(c), latin),
+                });
+            }
+        }
+    }
+    warnings
+}
+
+fn normalizations(input: &str) -> Vec<Normalization> {
+    let forms = [
+        (
+            NormalizationForm::Nfc,
+            ComposingNormalizerBorrowed::new_nfc().normalize(input),
+        ),
+        (
+            NormalizationForm::Nfd,
+            DecomposingNormalizerBorrowed::new_nfd().normalize(input),
+        ),
+        (
+            NormalizationForm::Nfkc,
+            ComposingNormalizerBorrowed::new_nfkc().normalize(input),
+        ),
+        (
+            NormalizationForm::Nfkd,
+            DecomposingNormalizerBorrowed::new_nfkd().normalize(input),
+        ),
+    ];
+    forms
+        .into_iter()
+        .map(|(form, text)| Normalization {
+            form,
+            char_count: text.chars().count(),
+            byte_count: text.len(),
+            changed: text != input,
+            text: text.into_owned(),
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn kinds(input: &str) -> Vec<WarningKind> {
+        inspect_string(input)
+            .warnings
+            .iter()
+            .map(|w| w.kind)
+            .collect()
+    }
+
     #[test]
     fn empty() {
         let r = inspect_string("");
@@ -54,4 +424,109 @@ mod tests {
         assert_eq!(r.word_count, 4);
         assert_eq!(r.line_count, 2);
     }
+
+    #[test]
+    fn character_details() {
+        let r = inspect_string("A😀e\u{0301}");
+        assert_eq!((r.char_count, r.grapheme_count, r.utf16_count), (4, 3, 5));
+
+        let a = &r.characters[0];
+        assert_eq!(a.name.as_deref(), Some("LATIN CAPITAL LETTER A"));
+        assert_eq!(
+            (a.category.as_str(), a.category_name.as_str()),
+            ("Lu", "Uppercase Letter")
+        );
+        assert_eq!((a.script.as_str(), a.bidi_class.as_str()), ("Latin", "L"));
+
+        let emoji = &r.characters[1];
+        assert_eq!(emoji.code_point, "U+1F600");
+        assert_eq!(emoji.utf8, "F0 9F 98 80");
+        assert_eq!(emoji.utf16, "D83D DE00");
+        assert_eq!((emoji.width, emoji.byte_offset), (2, 1));
+
+        let accent = &r.characters[3];
+        assert_eq!(accent.name.as_deref(), Some("COMBINING ACUTE ACCENT"));
+        assert_eq!(
+            (accent.category.as_str(), accent.script.as_str()),
+            ("Mn", "Inherited")
+        );
+        assert_eq!((accent.grapheme, accent.width), (2, 0));
+
+        assert_eq!(names::name('가').as_deref(), Some("HANGUL SYLLABLE GA"));
+        assert_eq!(names::name('힣').as_deref(), Some("HANGUL SYLLABLE HIH"));
+        assert_eq!(
+            names::name('中').as_deref(),
+            Some("CJK UNIFIED IDEOGRAPH-4E2D")
+        );
+    }
+
+    #[test]
+    fn flags_hidden_characters() {
+        let r = inspect_string("pass\u{200B}\u{200B}word\u{00A0}x\u{FFFD}");
+        assert_eq!(
+            kinds("pass\u{200B}\u{200B}word\u{00A0}x\u{FFFD}"),
+            [
+                WarningKind::Invisible,
+                WarningKind::Whitespace,
+                WarningKind::Replacement
+            ]
+        );
+        assert_eq!(
+            r.warnings[0].message,
+            "2 × ZERO WIDTH SPACE (U+200B) is invisible"
+        );
+        assert_eq!((r.warnings[0].start, r.warnings[0].end), (4, 10));
+        assert!(r.characters[4].invisible);
+
+        // A joiner inside an emoji sequence is expected.
+        assert!(kinds("👩\u{200D}💻").is_empty());
+    }
+
+    #[test]
+    fn flags_bidi_controls() {
+        let r = inspect_string("if admin \u{202E} }\u{2066}\u{2069}\nok");
+        assert_eq!(r.warnings.len(), 3);
+        assert!(r.warnings[0].message.contains("is not closed"));
+        assert!(r.warnings[1]
+            .message
+            .ends_with("changes the display order of text"));
+    }
+
+    #[test]
+    fn flags_spoofing() {
+        let r = inspect_string("login to pаypal");
+        let messages: Vec<&str> = r.warnings.iter().map(|w| w.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "`pаypal` mixes Cyrillic and Latin; it reads as `paypal`",
+                "CYRILLIC SMALL LETTER A (U+0430) looks like Latin `a`"
+            ]
+        );
+        assert_eq!(kinds("visit the сор"), [WarningKind::Confusable; 3]);
+        assert_eq!(kinds("сор.com")[0], WarningKind::MixedScript);
+        assert_eq!(kinds("ＡＢＣ"), [WarningKind::Confusable; 3]);
+        assert!(kinds("привет мир").is_empty());
+        assert!(kinds("日本語のテキスト").is_empty());
+    }
+
+    #[test]
+    fn normalizes() {
+        let r = inspect_string("ﬁe\u{0301}");
+        let counts: Vec<(NormalizationForm, usize, bool)> = r
+            .normalizations
+            .iter()
+            .map(|n| (n.form, n.char_count, n.changed))
+            .collect();
+        assert_eq!(
+            counts,
+            [
+                (NormalizationForm::Nfc, 2, true),
+                (NormalizationForm::Nfd, 3, false),
+                (NormalizationForm::Nfkc, 3, true),
+                (NormalizationForm::Nfkd, 4, true),
+            ]
+        );
+        assert_eq!(r.normalizations[2].text, "fié");
+    }
 }
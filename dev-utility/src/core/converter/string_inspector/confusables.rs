@@ -0,0 +1,77 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Latin lookalikes.
+//!
+//! A hand-picked subset of the Unicode confusables list: the Cyrillic,
+//! Greek and other letters that are commonly used to spoof Latin text, plus
+//! fullwidth ASCII.
+
+#[rustfmt::skip]
+const LOOKALIKES: &[(char, char)] = &[
+    // Latin letters that pass for others
+    ('ı', 'i'), ('ɑ', 'a'), ('ɡ', 'g'), ('ǀ', 'l'),
+    // Greek
+    ('Α', 'A'), ('Β', 'B'), ('Ε', 'E'), ('Ζ', 'Z'), ('Η', 'H'), ('Ι', 'I'), ('Κ', 'K'),
+    ('Μ', 'M'), ('Ν', 'N'), ('Ο', 'O'), ('Ρ', 'P'), ('Τ', 'T'), ('Υ', 'Y'), ('Χ', 'X'),
+    ('α', 'a'), ('ι', 'i'), ('ν', 'v'), ('ο', 'o'), ('ρ', 'p'), ('υ', 'u'), ('\u{037E}', ';'),
+    // Cyrillic
+    ('А', 'A'), ('В', 'B'), ('Е', 'E'), ('З', '3'), ('К', 'K'), ('М', 'M'), ('Н', 'H'),
+    ('О', 'O'), ('Р', 'P'), ('С', 'C'), ('Т', 'T'), ('Х', 'X'), ('Ѕ', 'S'), ('І', 'I'),
+    ('Ј', 'J'), ('а', 'a'), ('е', 'e'), ('о', 'o'), ('р', 'p'), ('с', 'c'), ('у', 'y'),
+    ('х', 'x'), ('ѕ', 's'), ('і', 'i'), ('ј', 'j'), ('һ', 'h'), ('ԁ', 'd'), ('ԛ', 'q'),
+    ('ԝ', 'w'), ('ӏ', 'l'),
+    // Armenian and Cherokee
+    ('օ', 'o'), ('ս', 'u'), ('ց', 'g'), ('Ꭺ', 'A'), ('Ꭼ', 'E'), ('Ꮃ', 'W'),
+];
+
+/// The ASCII character `c` can be mistaken for.
+pub(super) fn latin_lookalike(c: char) -> Option<char> {
+    if let '\u{FF01}'..='\u{FF5E}' = c {
+        return char::from_u32(c as u32 - 0xFEE0);
+    }
+    LOOKALIKES
+        .iter()
+        .find(|&&(from, _)| from == c)
+        .map(|&(_, to)| to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_lookalikes_to_ascii() {
+        let found: String = "АЕОРаеоріјһԁΑΟοꭺօ"
+            .chars()
+            .map(|c| latin_lookalike(c).unwrap_or('?'))
+            .collect();
+        assert_eq!(found, "AEOPaeopijhdAOo?o");
+        assert_eq!(latin_lookalike('ｐ'), Some('p'));
+        assert_eq!(latin_lookalike('！'), Some('!'));
+        assert_eq!(latin_lookalike('～'), Some('~'));
+    }
+
+    #[test]
+    fn leaves_other_characters_alone() {
+        for c in [
+            'a', 'Z', '0', ' ', 'é', 'Ж', 'λ', '\u{FF00}', '\u{FF5F}', '中',
+        ] {
+            assert_eq!(latin_lookalike(c), None, "{c}");
+        }
+    }
+
+    #[test]
+    fn table_is_consistent() {
+        let mut seen = std::collections::HashSet::new();
+        for &(from, to) in LOOKALIKES {
+            assert!(!from.is_ascii() && to.is_ascii_graphic(), "{from} -> {to}");
+            assert!(seen.insert(from), "{from} is listed twice");
+        }
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Character names.
+//!
+//! Names come from the full Unicode name list in `unicode_names2`, which
+//! also derives the CJK ideograph and Hangul syllable names. Controls have
+//! no name of their own, so they are given their Unicode name aliases.
+
+/// The name of `c`, when it has one.
+pub(super) fn name(c: char) -> Option<String> {
+    if let Some(name) = unicode_names2::name(c) {
+        return Some(name.to_string());
+    }
+    CONTROLS
+        .binary_search_by_key(&(c as u32), |&(cp, _)| cp)
+        .ok()
+        .map(|i| CONTROLS[i].1.to_string())
+}
+
+/// The character called `wanted`, ignoring case. Name aliases such as
+/// `LINE FEED` or `BYTE ORDER MARK` are accepted too.
+pub(crate) fn lookup(wanted: &str) -> Option<char> {
+    unicode_names2::character(wanted.trim())
+}
+
+#[rustfmt::skip]
+const CONTROLS: &[(u32, &str)] = &[
+    (0x0000, "NULL"),
+    (0x0001, "START OF HEADING"),
+    (0x0002, "START OF TEXT"),
+    (0x0003, "END OF TEXT"),
+    (0x0004, "END OF TRANSMISSION"),
+    (0x0005, "ENQUIRY"),
+    (0x0006, "ACKNOWLEDGE"),
+    (0x0007, "ALERT"),
+    (0x0008, "BACKSPACE"),
+    (0x0009, "CHARACTER TABULATION"),
+    (0x000A, "LINE FEED"),
+    (0x000B, "LINE TABULATION"),
+    (0x000C, "FORM FEED"),
+    (0x000D, "CARRIAGE RETURN"),
+    (0x000E, "SHIFT OUT"),
+    (0x000F, "SHIFT IN"),
+    (0x0010, "DATA LINK ESCAPE"),
+    (0x0011, "DEVICE CONTROL ONE"),
+    (0x0012, "DEVICE CONTROL TWO"),
+    (0x0013, "DEVICE CONTROL THREE"),
+    (0x0014, "DEVICE CONTROL FOUR"),
+    (0x0015, "NEGATIVE ACKNOWLEDGE"),
+    (0x0016, "SYNCHRONOUS IDLE"),
+    (0x0017, "END OF TRANSMISSION BLOCK"),
+    (0x0018, "CANCEL"),
+    (0x0019, "END OF MEDIUM"),
+    (0x001A, "SUBSTITUTE"),
+    (0x001B, "ESCAPE"),
+    (0x001C, "INFORMATION SEPARATOR FOUR"),
+    (0x001D, "INFORMATION SEPARATOR THREE"),
+    (0x001E, "INFORMATION SEPARATOR TWO"),
+    (0x001F, "INFORMATION SEPARATOR ONE"),
+    (0x007F, "DELETE"),
+    (0x0080, "PADDING CHARACTER"),
+    (0x0081, "HIGH OCTET PRESET"),
+    (0x0082, "BREAK PERMITTED HERE"),
+    (0x0083, "NO BREAK HERE"),
+    (0x0084, "INDEX"),
+    (0x0085, "NEXT LINE"),
+    (0x0086, "START OF SELECTED AREA"),
+    (0x0087, "END OF SELECTED AREA"),
+    (0x0088, "CHARACTER TABULATION SET"),
+    (0x0089, "CHARACTER TABULATION WITH JUSTIFICATION"),
+    (0x008A, "LINE TABULATION SET"),
+    (0x008B, "PARTIAL LINE FORWARD"),
+    (0x008C, "PARTIAL LINE BACKWARD"),
+    (0x008D, "REVERSE LINE FEED"),
+    (0x008E, "SINGLE SHIFT TWO"),
+    (0x008F, "SINGLE SHIFT THREE"),
+    (0x0090, "DEVICE CONTROL STRING"),
+    (0x0091, "PRIVATE USE ONE"),
+    (0x0092, "PRIVATE USE TWO"),
+    (0x0093, "SET TRANSMIT STATE"),
+    (0x0094, "CANCEL CHARACTER"),
+    (0x0095, "MESSAGE WAITING"),
+    (0x0096, "START OF GUARDED AREA"),
+    (0x0097, "END OF GUARDED AREA"),
+    (0x0098, "START OF STRING"),
+    (0x0099, "SINGLE GRAPHIC CHARACTER INTRODUCER"),
+    (0x009A, "SINGLE CHARACTER INTRODUCER"),
+    (0x009B, "CONTROL SEQUENCE INTRODUCER"),
+    (0x009C, "STRING TERMINATOR"),
+    (0x009D, "OPERATING SYSTEM COMMAND"),
+    (0x009E, "PRIVACY MESSAGE"),
+    (0x009F, "APPLICATION PROGRAM COMMAND"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_every_script() {
+        let names: Vec<Option<String>> = ['א', 'ب', 'Ꭰ', 'Ա', '😀', '∑', '𝔸', 'é', '\u{202E}']
+            .into_iter()
+            .map(name)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "HEBREW LETTER ALEF",
+                "ARABIC LETTER BEH",
+                "CHEROKEE LETTER A",
+                "ARMENIAN CAPITAL LETTER AYB",
+                "GRINNING FACE",
+                "N-ARY SUMMATION",
+                "MATHEMATICAL DOUBLE-STRUCK CAPITAL A",
+                "LATIN SMALL LETTER E WITH ACUTE",
+                "RIGHT-TO-LEFT OVERRIDE",
+            ]
+            .map(|n| Some(n.to_string()))
+        );
+    }
+
+    #[test]
+    fn derived_names_and_controls() {
+        assert_eq!(name('中').as_deref(), Some("CJK UNIFIED IDEOGRAPH-4E2D"));
+        assert_eq!(
+            name('\u{2A700}').as_deref(),
+            Some("CJK UNIFIED IDEOGRAPH-2A700")
+        );
+        assert_eq!(name('각').as_deref(), Some("HANGUL SYLLABLE GAG"));
+        assert_eq!(name('\u{E0100}').as_deref(), Some("VARIATION SELECTOR-17"));
+        assert_eq!(name('\0').as_deref(), Some("NULL"));
+        assert_eq!(name('\n').as_deref(), Some("LINE FEED"));
+        assert_eq!(name('\u{7F}').as_deref(), Some("DELETE"));
+        assert_eq!(name('\u{85}').as_deref(), Some("NEXT LINE"));
+        // Unassigned and private use code points have no name.
+        assert_eq!(name('\u{378}'), None);
+        assert_eq!(name('\u{E000}'), None);
+    }
+
+    #[test]
+    fn looks_up_names_and_aliases() {
+        assert_eq!(lookup("GRINNING FACE"), Some('😀'));
+        assert_eq!(lookup(" hebrew letter alef "), Some('א'));
+        assert_eq!(lookup("cjk unified ideograph-4e2d"), Some('中'));
+        assert_eq!(lookup("HANGUL SYLLABLE HIH"), Some('힣'));
+        assert_eq!(lookup("VARIATION SELECTOR-256"), Some('\u{E01EF}'));
+        assert_eq!(lookup("LINE FEED"), Some('\n'));
+        assert_eq!(lookup("NEW LINE"), Some('\n'));
+        assert_eq!(lookup("BYTE ORDER MARK"), Some('\u{FEFF}'));
+        for missing in [
+            "",
+            "NOT A NAME",
+            "CJK UNIFIED IDEOGRAPH-0041",
+            "HANGUL SYLLABLE X",
+        ] {
+            assert_eq!(lookup(missing), None, "{missing}");
+        }
+        assert_eq!(lookup(&"A".repeat(200)), None);
+    }
+
+    #[test]
+    fn round_trips() {
+        for c in [
+            'a',
+            '\t',
+            '\u{9F}',
+            'ß',
+            'Ω',
+            'ж',
+            'ñ',
+            '\u{FE0F}',
+            '🇺',
+            '\u{10FFFD}',
+        ] {
+            if let Some(n) = name(c) {
+                assert_eq!(lookup(&n), Some(c), "{n}");
+            }
+        }
+    }
+}
@@ -0,0 +1,590 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Text transformations.
+//!
+//! A transformation is an ordered list of [`TextStep`]s applied line by line,
+//! so a recipe can be serialized, saved and replayed on new input. The input's
+//! line ending and trailing newline are kept.
+
+mod case;
+
+pub use case::TextCase;
+
+use std::cmp::Ordering;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+use universal_function_macro::universal_function;
+
+use crate::error::UtilityError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum SortOrder {
+    Lexical,
+    Natural, // file2 before file10
+    Numeric, // by the first number on the line; lines without one go last
+    Length,  // by display width
+    Random,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum TrimSide {
+    Both,
+    Start,
+    End,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum ReverseUnit {
+    Lines,      // the order of lines
+    Words,      // the order of words on each line
+    Characters, // the characters of each line
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum TextStep {
+    Case {
+        case: TextCase,
+        preserve_acronyms: bool, // keep XML as XML in Pascal, Title and sentence case
+    },
+    Sort {
+        order: SortOrder,
+        descending: bool,
+        ignore_case: bool,
+        seed: Option<u64>, // for random order; set it to replay the same shuffle
+    },
+    Dedupe {
+        ignore_case: bool,
+        adjacent_only: bool, // like `uniq`
+    },
+    Trim {
+        side: TrimSide,
+    },
+    Reverse {
+        unit: ReverseUnit,
+    },
+    Wrap {
+        width: usize, // in display columns
+    },
+    Unwrap,
+    Prefix {
+        text: String,
+    },
+    Suffix {
+        text: String,
+    },
+    NumberLines {
+        start: usize,
+        separator: String,
+        pad: bool, // right-align the numbers
+    },
+    RemoveBlankLines,
+}
+
+impl TextStep {
+    fn name(&self) -> &'static str {
+        match self {
+            TextStep::Case { .. } => "case",
+            TextStep::Sort { .. } => "sort",
+            TextStep::Dedupe { .. } => "dedupe",
+            TextStep::Trim { .. } => "trim",
+            TextStep::Reverse { .. } => "reverse",
+            TextStep::Wrap { .. } => "wrap",
+            TextStep::Unwrap => "unwrap",
+            TextStep::Prefix { .. } => "prefix",
+            TextStep::Suffix { .. } => "suffix",
+            TextStep::NumberLines { .. } => "number lines",
+            TextStep::RemoveBlankLines => "remove blank lines",
+        }
+    }
+}
+
+/// Run `steps` over `input` in order.
+#[universal_function]
+pub fn transform_text(input: &str, steps: Vec<TextStep>) -> Result<String, UtilityError> {
+    let newline = if input.contains("\r\n") { "\r\n" } else { "\n" };
+    let body = input.strip_suffix(newline);
+    let trailing = body.is_some();
+    let body = body.unwrap_or(input);
+
+    let mut lines: Vec<String> = if body.is_empty() && !trailing {
+        Vec::new()
+    } else {
+        body.split(newline).map(str::to_string).collect()
+    };
+    for (n, step) in steps.iter().enumerate() {
+        lines = apply(step, lines).map_err(|message| {
+            UtilityError::InvalidInput(format!("Step {} ({}): {}", n + 1, step.name(), message))
+        })?;
+    }
+
+    let mut output = lines.join(newline);
+    if trailing {
+        output.push_str(newline);
+    }
+    Ok(output)
+}
+
+/// Convert every line of `input` to `case`.
+#[universal_function]
+pub fn convert_case(input: &str, case: TextCase, preserve_acronyms: bool) -> String {
+    if case == TextCase::Sentence {
+        // Sentences run across line breaks.
+        return case::convert(input, case, preserve_acronyms);
+    }
+    input
+        .split('\n')
+        .map(|line| match line.strip_suffix('\r') {
+            Some(line) => case::convert(line, case, preserve_acronyms) + "\r",
+            None => case::convert(line, case, preserve_acronyms),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn apply(step: &TextStep, mut lines: Vec<String>) -> Result<Vec<String>, String> {
+    match step {
+        TextStep::Case {
+            case,
+            preserve_acronyms,
+        } => {
+            if *case == TextCase::Sentence {
+                let text = case::convert(&lines.join("\n"), *case, *preserve_acronyms);
+                return Ok(text.split('\n').map(str::to_string).collect());
+            }
+            for line in &mut lines {
+                *line = case::convert(line, *case, *preserve_acronyms);
+            }
+        }
+        TextStep::Sort {
+            order,
+            descending,
+            ignore_case,
+            seed,
+        } => sort(&mut lines, *order, *descending, *ignore_case, *seed)?,
+        TextStep::Dedupe {
+            ignore_case,
+            adjacent_only,
+        } => {
+            let key = |line: &str| {
+                if *ignore_case {
+                    line.to_lowercase()
+                } else {
+                    line.to_string()
+                }
+            };
+            if *adjacent_only {
+                lines.dedup_by(|b, a| key(a) == key(b));
+            } else {
+                let mut seen = std::collections::HashSet::new();
+                lines.retain(|line| seen.insert(key(line)));
+            }
+        }
+        TextStep::Trim { side } => {
+            for line in &mut lines {
+                let trimmed = match side {
+                    TrimSide::Both => line.trim(),
+                    TrimSide::Start => line.trim_start(),
+                    TrimSide::End => line.trim_end(),
+                };
+                *line = trimmed.to_string();
+            }
+        }
+        TextStep::Reverse { unit } => match unit {
+            ReverseUnit::Lines => lines.reverse(),
+            ReverseUnit::Words => {
+                for line in &mut lines {
+                    *line = line.split_whitespace().rev().collect::<Vec<_>>().join(" ");
+                }
+            }
+            ReverseUnit::Characters => {
+                for line in &mut lines {
+                    *line = line.graphemes(true).rev().collect();
+                }
+            }
+        },
+        TextStep::Wrap { width } => {
+            if *width == 0 {
+                return Err("width must be at least 1".into());
+            }
+            return Ok(lines.iter().flat_map(|line| wrap(line, *width)).collect());
+        }
+        TextStep::Unwrap => return Ok(unwrap(&lines)),
+        TextStep::Prefix { text } => {
+            for line in &mut lines {
+                line.insert_str(0, text);
+            }
+        }
+        TextStep::Suffix { text } => {
+            for line in &mut lines {
+                line.push_str(text);
+            }
+        }
+        TextStep::NumberLines {
+            start,
+            separator,
+            pad,
+        } => {
+            let last = start.saturating_add(lines.len().saturating_sub(1));
+            let digits = if *pad { last.to_string().len() } else { 0 };
+            for (n, line) in lines.iter_mut().enumerate() {
+                *line = format!("{:>digits$}{}{}", start.saturating_add(n), separator, line);
+            }
+        }
+        TextStep::RemoveBlankLines => lines.retain(|line| !line.trim().is_empty()),
+    }
+    Ok(lines)
+}
+
+fn sort(
+    lines: &mut [String],
+    order: SortOrder,
+    descending: bool,
+    ignore_case: bool,
+    seed: Option<u64>,
+) -> Result<(), String> {
+    let fold = |line: &str| {
+        if ignore_case {
+            line.to_lowercase()
+        } else {
+            line.to_string()
+        }
+    };
+    let directed = |ordering: Ordering| {
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    };
+    match order {
+        SortOrder::Lexical => lines.sort_by_cached_key(|line| fold(line)),
+        SortOrder::Natural => lines.sort_by(|a, b| natural_cmp(&fold(a), &fold(b))),
+        SortOrder::Length => lines.sort_by_cached_key(|line| line.width()),
+        SortOrder::Numeric => {
+            lines.sort_by(|a, b| match (leading_number(a), leading_number(b)) {
+                (Some(x), Some(y)) => directed(x.total_cmp(&y)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            });
+            return Ok(());
+        }
+        SortOrder::Random => {
+            let seed = match seed {
+                Some(seed) => seed,
+                None => getrandom::u64().map_err(|e| format!("RNG error: {}", e))?,
+            };
+            lines.shuffle(&mut StdRng::seed_from_u64(seed));
+            return Ok(());
+        }
+    }
+    if descending {
+        lines.reverse();
+    }
+    Ok(())
+}
+
+/// The first number on a line, allowing a sign, a decimal point and
+/// thousands separators.
+fn leading_number(line: &str) -> Option<f64> {
+    let bytes = line.as_bytes();
+    let start = bytes.iter().position(u8::is_ascii_digit)?;
+    let negative = start > 0 && bytes[start - 1] == b'-';
+    let mut number = String::new();
+    let mut seen_point = false;
+    for (i, &b) in bytes.iter().enumerate().skip(start) {
+        match b {
+            b'0'..=b'9' => number.push(b as char),
+            b'.' if !seen_point && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) => {
+                seen_point = true;
+                number.push('.');
+            }
+            b',' if bytes.get(i + 1).is_some_and(u8::is_ascii_digit) => {}
+            _ => break,
+        }
+    }
+    let value: f64 = number.parse().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// Compare runs of digits by value and everything else character by
+/// character.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(x), Some(y)) = (a.chars().next(), b.chars().next()) else {
+            return a.len().cmp(&b.len());
+        };
+        if x.is_ascii_digit() && y.is_ascii_digit() {
+            let (da, ra) = split_digits(a);
+            let (db, rb) = split_digits(b);
+            let (va, vb) = (da.trim_start_matches('0'), db.trim_start_matches('0'));
+            let ordering = va
+                .len()
+                .cmp(&vb.len())
+                .then_with(|| va.cmp(vb))
+                // Fewer leading zeros first: 1 before 01.
+                .then_with(|| da.len().cmp(&db.len()));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            (a, b) = (ra, rb);
+        } else {
+            if x != y {
+                return x.cmp(&y);
+            }
+            (a, b) = (&a[x.len_utf8()..], &b[y.len_utf8()..]);
+        }
+    }
+}
+
+fn split_digits(s: &str) -> (&str, &str) {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s.split_at(end)
+}
+
+/// Greedily fill lines up to `width` columns, breaking at whitespace and
+/// repeating the line's indentation. A word longer than the width stays on
+/// a line of its own.
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0;
+    for word in line.split_whitespace() {
+        let word_width = word.width();
+        if !current.is_empty() && current_width + 1 + word_width > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if current.is_empty() {
+            current.push_str(indent);
+            current.push_str(word);
+            current_width = indent.width() + word_width;
+        } else {
+            current.push(' ');
+            current.push_str(word);
+            current_width += 1 + word_width;
+        }
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(if current.is_empty() {
+            line.to_string()
+        } else {
+            current
+        });
+    }
+    lines
+}
+
+/// Join each paragraph into a single line. Blank lines separate paragraphs
+/// and are kept.
+fn unwrap(lines: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut open = false;
+    for line in lines {
+        if line.trim().is_empty() {
+            out.push(line.clone());
+            open = false;
+        } else if open {
+            let last = out.last_mut().expect("an open paragraph has a line");
+            last.truncate(last.trim_end().len());
+            last.push(' ');
+            last.push_str(line.trim());
+        } else {
+            out.push(line.trim_end().to_string());
+            open = true;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(input: &str, steps: Vec<TextStep>) -> String {
+        transform_text(input, steps).unwrap()
+    }
+
+    fn sort_step(order: SortOrder, descending: bool) -> TextStep {
+        TextStep::Sort {
+            order,
+            descending,
+            ignore_case: false,
+            seed: None,
+        }
+    }
+
+    #[test]
+    fn keeps_line_endings() {
+        let steps = || {
+            vec![TextStep::Reverse {
+                unit: ReverseUnit::Lines,
+            }]
+        };
+        assert_eq!(run("a\r\nb\r\n", steps()), "b\r\na\r\n");
+        assert_eq!(run("a\nb", steps()), "b\na");
+        assert_eq!(run("", steps()), "");
+    }
+
+    #[test]
+    fn sorts() {
+        let input = "file10\nfile2\nFile1\nfile1";
+        assert_eq!(
+            run(input, vec![sort_step(SortOrder::Lexical, false)]),
+            "File1\nfile1\nfile10\nfile2"
+        );
+        assert_eq!(
+            run(input, vec![sort_step(SortOrder::Natural, false)]),
+            "File1\nfile1\nfile2\nfile10"
+        );
+        assert_eq!(
+            run(
+                "b\n-3 apples\n1,200\n2.5\n",
+                vec![sort_step(SortOrder::Numeric, true)]
+            ),
+            "1,200\n2.5\n-3 apples\nb\n"
+        );
+        assert_eq!(
+            run("ccc\na\nbb", vec![sort_step(SortOrder::Length, true)]),
+            "ccc\nbb\na"
+        );
+
+        let shuffle = || {
+            vec![TextStep::Sort {
+                order: SortOrder::Random,
+                descending: false,
+                ignore_case: false,
+                seed: Some(7),
+            }]
+        };
+        let input = "1\n2\n3\n4\n5\n6\n7\n8";
+        assert_eq!(run(input, shuffle()), run(input, shuffle()));
+        assert_ne!(run(input, shuffle()), input);
+    }
+
+    #[test]
+    fn dedupes() {
+        let dedupe = |adjacent_only| {
+            vec![TextStep::Dedupe {
+                ignore_case: true,
+                adjacent_only,
+            }]
+        };
+        assert_eq!(run("a\nA\nb\na", dedupe(false)), "a\nb");
+        assert_eq!(run("a\nA\nb\na", dedupe(true)), "a\nb\na");
+    }
+
+    #[test]
+    fn wraps_and_unwraps() {
+        let text = "  the quick brown fox jumps over the lazy dog\n\nend";
+        let wrapped = run(text, vec![TextStep::Wrap { width: 16 }]);
+        assert_eq!(
+            wrapped,
+            "  the quick\n  brown fox\n  jumps over the\n  lazy dog\n\nend"
+        );
+        assert_eq!(
+            run(&wrapped, vec![TextStep::Unwrap]),
+            "  the quick brown fox jumps over the lazy dog\n\nend"
+        );
+        assert_eq!(
+            run("supercalifragilistic", vec![TextStep::Wrap { width: 5 }]),
+            "supercalifragilistic"
+        );
+    }
+
+    #[test]
+    fn runs_a_recipe() {
+        let recipe: Vec<TextStep> = serde_json::from_str(
+            r#"[
+                {"type": "trim", "side": "both"},
+                {"type": "removeBlankLines"},
+                {"type": "case", "case": "snake", "preserveAcronyms": false},
+                {"type": "dedupe", "ignoreCase": false, "adjacentOnly": false},
+                {"type": "prefix", "text": "- "},
+                {"type": "numberLines", "start": 9, "separator": ". ", "pad": true}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            run("  userId\n\nXMLHttpRequest \nuser_id\n", recipe),
+            " 9. - user_id\n10. - xml_http_request\n"
+        );
+    }
+
+    #[test]
+    fn numbers_lines_up_to_the_largest_number() {
+        let step = TextStep::NumberLines {
+            start: usize::MAX - 1,
+            separator: " ".into(),
+            pad: false,
+        };
+        let max = usize::MAX;
+        assert_eq!(
+            run("a\nb\nc", vec![step]),
+            format!("{} a\n{} b\n{} c", max - 1, max, max)
+        );
+    }
+
+    #[test]
+    fn reverses_and_converts() {
+        assert_eq!(
+            run(
+                "noe\u{0308}l\nsee you",
+                vec![
+                    TextStep::Reverse {
+                        unit: ReverseUnit::Characters
+                    },
+                    TextStep::Suffix { text: "!".into() },
+                ]
+            ),
+            "le\u{0308}on!\nuoy ees!"
+        );
+        assert_eq!(
+            convert_case("first line. second\r\nline", TextCase::Sentence, false),
+            "First line. Second\r\nline"
+        );
+        assert_eq!(
+            convert_case("get_user\r\nset_user", TextCase::Camel, false),
+            "getUser\r\nsetUser"
+        );
+    }
+
+    #[test]
+    fn names_the_failing_step() {
+        let err = transform_text("text", vec![TextStep::Unwrap, TextStep::Wrap { width: 0 }])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            UtilityError::InvalidInput("Step 2 (wrap): width must be at least 1".into())
+                .to_string()
+        );
+    }
+}
@@ -0,0 +1,270 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Case conversion.
+//!
+//! Identifier cases split each line into words at separators and case
+//! changes, reading `XMLHttpRequest` as `XML Http Request`, and join them
+//! again. Title and sentence case rewrite letters in place so punctuation
+//! and spacing survive.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum TextCase {
+    Lower,
+    Upper,
+    Camel,          // parseHttpResponse
+    Pascal,         // ParseHttpResponse
+    Snake,          // parse_http_response
+    ScreamingSnake, // PARSE_HTTP_RESPONSE
+    Kebab,          // parse-http-response
+    Title,          // Parse the HTTP Response
+    Sentence,       // Parse the HTTP response. Then retry.
+}
+
+/// Words that stay lowercase inside a title.
+const MINOR_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "in", "nor", "of", "on", "or", "so", "the",
+    "to", "up", "via", "vs", "yet",
+];
+
+pub(super) fn convert(line: &str, case: TextCase, keep_acronyms: bool) -> String {
+    match case {
+        TextCase::Lower => line.to_lowercase(),
+        TextCase::Upper => line.to_uppercase(),
+        TextCase::Title => title(line, keep_acronyms),
+        TextCase::Sentence => sentence(line, keep_acronyms),
+        _ => identifier(line, case, keep_acronyms),
+    }
+}
+
+fn is_acronym(word: &str) -> bool {
+    word.chars().filter(|c| c.is_alphabetic()).count() > 1 && !word.chars().any(char::is_lowercase)
+}
+
+fn capitalized(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// Split an identifier or phrase into words.
+pub(super) fn words(text: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut words = Vec::new();
+    let mut start: Option<usize> = None;
+    for (n, &(i, c)) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if let Some(s) = start.take() {
+                words.push(&text[s..i]);
+            }
+            continue;
+        }
+        let Some(s) = start else {
+            start = Some(i);
+            continue;
+        };
+        let prev = chars[n - 1].1;
+        let next = chars.get(n + 1).map(|&(_, c)| c);
+        let boundary = c.is_uppercase()
+            && (prev.is_lowercase()
+                || prev.is_numeric()
+                // The last capital of a run starts the next word: XMLHttp.
+                || (prev.is_uppercase() && next.is_some_and(char::is_lowercase)));
+        if boundary {
+            words.push(&text[s..i]);
+            start = Some(i);
+        }
+    }
+    if let Some(s) = start {
+        words.push(&text[s..]);
+    }
+    words
+}
+
+fn identifier(line: &str, case: TextCase, keep_acronyms: bool) -> String {
+    // Keep indentation so converting a block of names stays aligned.
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let words = words(line);
+    let joined = match case {
+        TextCase::Snake | TextCase::Kebab | TextCase::ScreamingSnake => {
+            let separator = if case == TextCase::Kebab { "-" } else { "_" };
+            let words: Vec<String> = words
+                .iter()
+                .map(|w| {
+                    if case == TextCase::ScreamingSnake {
+                        w.to_uppercase()
+                    } else {
+                        w.to_lowercase()
+                    }
+                })
+                .collect();
+            words.join(separator)
+        }
+        _ => words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                if i == 0 && case == TextCase::Camel {
+                    w.to_lowercase()
+                } else if keep_acronyms && is_acronym(w) {
+                    w.to_string()
+                } else {
+                    capitalized(w)
+                }
+            })
+            .collect(),
+    };
+    format!("{}{}", indent, joined)
+}
+
+/// Byte ranges of the words of running text, apostrophes included.
+fn prose_words(line: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for (i, c) in line.char_indices() {
+        let inside = c.is_alphanumeric() || (start.is_some() && (c == '\'' || c == '’'));
+        match (inside, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                ranges.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        ranges.push((s, line.len()));
+    }
+    ranges
+}
+
+fn title(line: &str, keep_acronyms: bool) -> String {
+    let ranges = prose_words(line);
+    let mut out = String::with_capacity(line.len());
+    let mut last = 0;
+    for (n, &(start, end)) in ranges.iter().enumerate() {
+        out.push_str(&line[last..start]);
+        let word = &line[start..end];
+        let lower = word.to_lowercase();
+        let edge = n == 0 || n + 1 == ranges.len();
+        // A word after a colon starts a subtitle.
+        let after_colon = line[last..start].contains(':');
+        if keep_acronyms && is_acronym(word) {
+            out.push_str(word);
+        } else if !edge && !after_colon && MINOR_WORDS.contains(&lower.as_str()) {
+            out.push_str(&lower);
+        } else {
+            out.push_str(&capitalized(word));
+        }
+        last = end;
+    }
+    out.push_str(&line[last..]);
+    out
+}
+
+fn sentence(line: &str, keep_acronyms: bool) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut last = 0;
+    let mut starts_sentence = true;
+    for (start, end) in prose_words(line) {
+        let gap = &line[last..start];
+        if gap.contains(['.', '!', '?']) {
+            starts_sentence = true;
+        }
+        out.push_str(gap);
+        let word = &line[start..end];
+        if keep_acronyms && is_acronym(word) || word == "I" {
+            out.push_str(word);
+        } else if starts_sentence {
+            out.push_str(&capitalized(word));
+        } else {
+            out.push_str(&word.to_lowercase());
+        }
+        starts_sentence = false;
+        last = end;
+    }
+    out.push_str(&line[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_identifiers() {
+        assert_eq!(words("XMLHttpRequest"), ["XML", "Http", "Request"]);
+        assert_eq!(words("userID2FA"), ["user", "ID2", "FA"]);
+        assert_eq!(
+            words("base64_encode-v2 now"),
+            ["base64", "encode", "v2", "now"]
+        );
+        assert_eq!(words("getHTTPSUrl"), ["get", "HTTPS", "Url"]);
+    }
+
+    #[test]
+    fn converts_identifiers() {
+        let all = |input: &str, keep| {
+            [
+                TextCase::Camel,
+                TextCase::Pascal,
+                TextCase::Snake,
+                TextCase::ScreamingSnake,
+                TextCase::Kebab,
+            ]
+            .map(|case| convert(input, case, keep))
+        };
+        assert_eq!(
+            all("parse XML http-response", false),
+            [
+                "parseXmlHttpResponse",
+                "ParseXmlHttpResponse",
+                "parse_xml_http_response",
+                "PARSE_XML_HTTP_RESPONSE",
+                "parse-xml-http-response"
+            ]
+        );
+        assert_eq!(all("XMLHttpRequest", true)[0], "xmlHttpRequest");
+        assert_eq!(all("XMLHttpRequest", true)[1], "XMLHttpRequest");
+        assert_eq!(convert("  user_id", TextCase::Camel, false), "  userId");
+    }
+
+    #[test]
+    fn converts_prose() {
+        assert_eq!(
+            convert(
+                "the lord OF the rings: the return of the KING",
+                TextCase::Title,
+                false
+            ),
+            "The Lord of the Rings: The Return of the King"
+        );
+        assert_eq!(
+            convert("using the NASA API in go", TextCase::Title, true),
+            "Using the NASA API in Go"
+        );
+        assert_eq!(
+            convert(
+                "HELLO THERE. it's ME, I think! ok",
+                TextCase::Sentence,
+                false
+            ),
+            "Hello there. It's me, I think! Ok"
+        );
+    }
+}
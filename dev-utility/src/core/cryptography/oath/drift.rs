@@ -0,0 +1,205 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+use super::{codes_match, generate_otp_code_for_time, unix_now, OtpParameters, SyncStatus};
+use crate::error::UtilityError;
+use serde::{Deserialize, Serialize};
+use universal_function_macro::universal_function;
+
+/// Widest range the timeline will cover on either side of now.
+pub const MAX_DRIFT_RANGE_MINUTES: u32 = 24 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct TotpTimelineEntry {
+    pub step: u64,      // Absolute time step
+    pub offset: i64,    // Steps relative to the current one
+    pub starts_at: u64, // Unix time the step begins
+    pub code: String,   // Code valid during the step
+    pub matches: bool,  // Whether the entered code equals this one
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct TotpDriftReport {
+    pub timeline: Vec<TotpTimelineEntry>, // Every step in range, oldest first
+    pub matched_steps: Vec<u64>,          // Steps whose code equals the entered one
+    pub current_step: u64,                // Step containing `now`
+    pub now: u64,                         // Unix time the report was computed for
+    pub skew_seconds: Option<i64>,        // Device clock minus ours; + means ahead
+    pub status: SyncStatus,               // Verdict
+    pub message: String,                  // Human-readable summary
+}
+
+// Diagnose why a code is rejected by listing every code in ±range_minutes
+#[universal_function]
+pub fn diagnose_totp_drift(
+    secret: String,
+    code: String,
+    params: OtpParameters,
+    range_minutes: u32,
+) -> Result<TotpDriftReport, UtilityError> {
+    diagnose_totp_drift_at(secret, code, params, range_minutes, unix_now()?)
+}
+
+pub(crate) fn diagnose_totp_drift_at(
+    secret: String,
+    code: String,
+    params: OtpParameters,
+    range_minutes: u32,
+    now: u64,
+) -> Result<TotpDriftReport, UtilityError> {
+    if range_minutes > MAX_DRIFT_RANGE_MINUTES {
+        return Err(UtilityError::InvalidInput(format!(
+            "Range must be at most {} minutes",
+            MAX_DRIFT_RANGE_MINUTES
+        )));
+    }
+    let current = generate_otp_code_for_time(secret.clone(), params.clone(), now)?;
+    let current_step = current.time_used;
+    let period = params.period as u64;
+    let span = (range_minutes as u64 * 60).div_ceil(period) as i64;
+
+    let mut timeline = Vec::with_capacity(span as usize * 2 + 1);
+    for offset in -span..=span {
+        let Some(step) = current_step.checked_add_signed(offset) else {
+            continue;
+        };
+        let starts_at = params.t0 + step * period;
+        let generated = generate_otp_code_for_time(secret.clone(), params.clone(), starts_at)?;
+        timeline.push(TotpTimelineEntry {
+            step,
+            offset,
+            starts_at,
+            matches: codes_match(&generated.code, &code, &params),
+            code: generated.code,
+        });
+    }
+
+    let matched_steps: Vec<u64> = timeline
+        .iter()
+        .filter(|e| e.matches)
+        .map(|e| e.step)
+        .collect();
+    // Codes can repeat by chance; the nearest match is the likeliest one.
+    let nearest = timeline
+        .iter()
+        .filter(|e| e.matches)
+        .min_by_key(|e| e.offset.abs())
+        .map(|e| e.offset);
+
+    let (status, skew_seconds, message) = match nearest {
+        Some(0) => (
+            SyncStatus::Synchronized,
+            Some(0),
+            "Code matches the current step; clocks agree".to_string(),
+        ),
+        Some(offset) => {
+            let skew = offset * period as i64;
+            let direction = if skew > 0 { "ahead" } else { "behind" };
+            // Most verifiers accept one step either side.
+            let status = if offset.abs() <= 1 {
+                SyncStatus::DriftDetected
+            } else {
+                SyncStatus::OutOfSync
+            };
+            (
+                status,
+                Some(skew),
+                format!(
+                    "Code matches step {:+}; the device clock is about {}s {}",
+                    offset,
+                    skew.abs(),
+                    direction
+                ),
+            )
+        }
+        None => (
+            SyncStatus::OutOfSync,
+            None,
+            format!(
+                "Code does not match any step within ±{} minutes; check the secret and parameters",
+                range_minutes
+            ),
+        ),
+    };
+
+    Ok(TotpDriftReport {
+        timeline,
+        matched_steps,
+        current_step,
+        now,
+        skew_seconds,
+        status,
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+    const NOW: u64 = 1_700_000_015;
+
+    fn code_at(timestamp: u64) -> String {
+        generate_otp_code_for_time(SECRET.to_string(), OtpParameters::default(), timestamp)
+            .unwrap()
+            .code
+    }
+
+    fn diagnose(code: String) -> TotpDriftReport {
+        diagnose_totp_drift_at(SECRET.to_string(), code, OtpParameters::default(), 5, NOW).unwrap()
+    }
+
+    #[test]
+    fn timeline_covers_range() {
+        let report = diagnose(code_at(NOW));
+        assert_eq!(report.timeline.len(), 21);
+        assert_eq!(report.timeline[0].offset, -10);
+        assert_eq!(report.timeline[10].step, report.current_step);
+        assert!(matches!(report.status, SyncStatus::Synchronized));
+        assert_eq!(report.skew_seconds, Some(0));
+    }
+
+    #[test]
+    fn one_step_is_drift() {
+        let report = diagnose(code_at(NOW + 30));
+        assert!(matches!(report.status, SyncStatus::DriftDetected));
+        assert_eq!(report.skew_seconds, Some(30));
+        assert!(report.matched_steps.contains(&(report.current_step + 1)));
+    }
+
+    #[test]
+    fn far_skew_is_out_of_sync() {
+        let report = diagnose(code_at(NOW - 4 * 60));
+        assert!(matches!(report.status, SyncStatus::OutOfSync));
+        assert_eq!(report.skew_seconds, Some(-240));
+    }
+
+    #[test]
+    fn unmatched_code_has_no_skew() {
+        // Never a decimal code, so it cannot collide with any step.
+        let report = diagnose("abcdef".to_string());
+        assert!(report.matched_steps.is_empty());
+        assert!(matches!(report.status, SyncStatus::OutOfSync));
+        assert_eq!(report.skew_seconds, None);
+        assert!(diagnose_totp_drift_at(
+            SECRET.to_string(),
+            String::new(),
+            OtpParameters::default(),
+            MAX_DRIFT_RANGE_MINUTES + 1,
+            NOW
+        )
+        .is_err());
+    }
+}
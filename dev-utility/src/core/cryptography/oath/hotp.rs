@@ -12,7 +12,14 @@
 // See LICENSE file for details or contact admin@aprilnea.com
 
 use super::{HashAlgorithm, SyncStatus};
+use crate::error::UtilityError;
+use base32::Alphabet;
+use hmac::digest::KeyInit;
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use universal_function_macro::universal_function;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HotpConfig {
@@ -20,7 +27,7 @@ pub struct HotpConfig {
     pub issuer: String,           // Service name
     pub account: String,          // Account identifier
     pub algorithm: HashAlgorithm, // HMAC algorithm
-    pub digits: u32,              // Code length (4-8 digits)
+    pub digits: u32,              // Code length (1-10 digits)
     pub counter: u64,             // Current counter value
     pub initial_counter: u64,     // Initial counter value
     pub label: Option<String>,    // Custom label for UI
@@ -29,6 +36,9 @@ pub struct HotpConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
 pub struct HotpResult {
     pub code: String,             // Generated HOTP code
     pub counter_used: u64,        // Counter value used for generation
@@ -54,4 +64,147 @@ pub struct CounterInfo {
     pub total_generated: u64,        // Total codes generated
     pub last_increment: Option<u64>, // Last increment timestamp
     pub sync_status: SyncStatus,     // Synchronization status
-}
\ No newline at end of file
+}
+
+/// Longest code a 31-bit truncated HMAC can fill without leading zeros
+/// being forced.
+pub const MAX_OTP_DIGITS: u32 = 10;
+
+// Generate HOTP code for a counter value
+#[universal_function]
+pub fn generate_hotp_code(
+    secret: String,
+    counter: u64,
+    algorithm: HashAlgorithm,
+    digits: u32,
+) -> Result<HotpResult, UtilityError> {
+    let key = decode_secret(&secret)?;
+    let digest = hmac_digest(&algorithm, &key, &counter.to_be_bytes())?;
+    let code = encode_decimal(dynamic_truncate(&digest) as u64, digits)?;
+
+    Ok(HotpResult {
+        code,
+        counter_used: counter,
+        next_counter: counter.wrapping_add(1),
+        algorithm,
+        digits,
+    })
+}
+
+/// Decode an unpadded RFC 4648 base32 secret.
+pub(crate) fn decode_secret(secret: &str) -> Result<Vec<u8>, UtilityError> {
+    base32::decode(Alphabet::Rfc4648 { padding: false }, secret)
+        .ok_or_else(|| UtilityError::InvalidInput("Invalid base32 secret".to_string()))
+}
+
+pub(crate) fn hmac_digest(
+    algorithm: &HashAlgorithm,
+    key: &[u8],
+    message: &[u8],
+) -> Result<Vec<u8>, UtilityError> {
+    fn run<M: Mac + KeyInit>(key: &[u8], message: &[u8]) -> Result<Vec<u8>, UtilityError> {
+        let mut mac = <M as Mac>::new_from_slice(key)
+            .map_err(|e| UtilityError::Runtime(format!("HMAC error: {}", e)))?;
+        mac.update(message);
+        Ok(mac.finalize().into_bytes().to_vec())
+    }
+
+    match algorithm {
+        HashAlgorithm::SHA1 => run::<Hmac<Sha1>>(key, message),
+        HashAlgorithm::SHA256 => run::<Hmac<Sha256>>(key, message),
+        HashAlgorithm::SHA512 => run::<Hmac<Sha512>>(key, message),
+    }
+}
+
+/// RFC 4226 §5.3 dynamic truncation to a 31-bit integer.
+pub(crate) fn dynamic_truncate(digest: &[u8]) -> u32 {
+    let offset = (digest[digest.len() - 1] & 0xf) as usize;
+    ((digest[offset] & 0x7f) as u32) << 24
+        | (digest[offset + 1] as u32) << 16
+        | (digest[offset + 2] as u32) << 8
+        | (digest[offset + 3] as u32)
+}
+
+pub(crate) fn check_digits(digits: u32) -> Result<(), UtilityError> {
+    if (1..=MAX_OTP_DIGITS).contains(&digits) {
+        Ok(())
+    } else {
+        Err(UtilityError::InvalidInput(format!(
+            "Digits must be between 1 and {}",
+            MAX_OTP_DIGITS
+        )))
+    }
+}
+
+/// Reduce `value` modulo 10^digits and zero-pad it.
+pub(crate) fn encode_decimal(value: u64, digits: u32) -> Result<String, UtilityError> {
+    check_digits(digits)?;
+    let code = value % 10_u64.pow(digits);
+    Ok(format!("{:0width$}", code, width = digits as usize))
+}
+
+/// Spell `value` in a custom alphabet, least significant symbol first
+/// (the Steam Guard convention).
+pub(crate) fn encode_alphabet(
+    mut value: u64,
+    digits: u32,
+    alphabet: &str,
+) -> Result<String, UtilityError> {
+    check_digits(digits)?;
+    let symbols: Vec<char> = alphabet.chars().collect();
+    let mut unique = symbols.clone();
+    unique.sort_unstable();
+    unique.dedup();
+    if symbols.len() < 2 || unique.len() != symbols.len() {
+        return Err(UtilityError::InvalidInput(
+            "Alphabet needs at least two distinct symbols".to_string(),
+        ));
+    }
+
+    let base = symbols.len() as u64;
+    let mut code = String::with_capacity(digits as usize);
+    for _ in 0..digits {
+        code.push(symbols[(value % base) as usize]);
+        value /= base;
+    }
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4226 Appendix D, secret "12345678901234567890".
+    const SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn matches_rfc4226_vectors() {
+        let expected = [
+            "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583",
+            "399871", "520489",
+        ];
+        for (counter, code) in expected.iter().enumerate() {
+            let r = generate_hotp_code(SECRET.to_string(), counter as u64, HashAlgorithm::SHA1, 6)
+                .unwrap();
+            assert_eq!(&r.code, code);
+            assert_eq!(r.next_counter, counter as u64 + 1);
+        }
+    }
+
+    #[test]
+    fn supports_ten_digits_and_rejects_more() {
+        let r = generate_hotp_code(SECRET.to_string(), 0, HashAlgorithm::SHA1, 10).unwrap();
+        // Truncated value from RFC 4226 Appendix D, zero-padded to ten digits.
+        assert_eq!(r.code, "1284755224");
+
+        assert!(generate_hotp_code(SECRET.to_string(), 0, HashAlgorithm::SHA1, 11).is_err());
+        assert!(generate_hotp_code(SECRET.to_string(), 0, HashAlgorithm::SHA1, 0).is_err());
+    }
+
+    #[test]
+    fn alphabet_must_be_distinct() {
+        assert!(encode_alphabet(42, 5, "AAB").is_err());
+        assert!(encode_alphabet(42, 5, "A").is_err());
+        assert_eq!(encode_alphabet(5, 3, "01").unwrap(), "101");
+    }
+}
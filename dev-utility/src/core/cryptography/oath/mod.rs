@@ -11,12 +11,24 @@
 //
 // See LICENSE file for details or contact admin@aprilnea.com
 
+pub mod drift;
+pub use drift::*;
+
 pub mod hotp;
 pub use hotp::*;
 
+pub mod ocra;
+pub use ocra::*;
+
 pub mod totp;
 pub use totp::*;
 
+pub mod variant;
+pub use variant::*;
+
+pub mod vault;
+pub use vault::*;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -29,9 +41,11 @@ pub enum HashAlgorithm {
     SHA512,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
 pub enum SyncStatus {
-    Synchronized,
-    DriftDetected,
-    OutOfSync,
+    Synchronized,  // Matches the current step
+    DriftDetected, // Matches an adjacent step, still accepted by most verifiers
+    OutOfSync,     // Matches a distant step, or none at all
 }
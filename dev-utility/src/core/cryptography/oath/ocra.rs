@@ -0,0 +1,398 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! OCRA (RFC 6287) challenge-response codes.
+
+use super::{dynamic_truncate, encode_decimal, hmac_digest, unix_now, HashAlgorithm};
+use crate::error::UtilityError;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use universal_function_macro::universal_function;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum OcraChallengeFormat {
+    Alphanumeric, // QA: ASCII text
+    Numeric,      // QN: decimal number
+    Hex,          // QH: hex string
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct OcraSuite {
+    pub algorithm: HashAlgorithm,              // HMAC function
+    pub digits: u32,                           // 0 (no truncation) or 4-10
+    pub counter: bool,                         // C: 8-byte counter included
+    pub challenge_format: OcraChallengeFormat, // Q: challenge encoding
+    pub challenge_length: u32,                 // Q: maximum challenge length (4-64)
+    pub pin_hash: Option<HashAlgorithm>,       // P: hash applied to the PIN
+    pub session_length: Option<u32>,           // S: session information length in bytes
+    pub time_step: Option<u64>,                // T: time step in seconds
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct OcraInput {
+    pub challenge: String,       // Challenge question in the suite's format
+    pub counter: Option<u64>,    // Required when the suite has C
+    pub pin: Option<String>,     // Plain PIN, hashed per the suite; required with P
+    pub session: Option<String>, // Hex session information; required with S
+    pub timestamp: Option<u64>,  // Unix seconds for T; None uses the current time
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct OcraResult {
+    pub response: String,   // OCRA response
+    pub suite: OcraSuite,   // Parsed suite
+    pub data_input: String, // Hex of the HMAC message, for debugging
+}
+
+/// Parse an OCRASuite string such as `OCRA-1:HOTP-SHA1-6:C-QN08-PSHA1`.
+#[universal_function]
+pub fn parse_ocra_suite(suite: &str) -> Result<OcraSuite, UtilityError> {
+    let invalid =
+        |detail: &str| UtilityError::ParseError(format!("Invalid OCRA suite: {}", detail));
+
+    // Every tag below is sliced by byte, which only holds for ASCII.
+    if !suite.is_ascii() {
+        return Err(invalid("only ASCII characters are allowed"));
+    }
+
+    let parts: Vec<&str> = suite.trim().split(':').collect();
+    let [version, function, data_input] = parts.as_slice() else {
+        return Err(invalid("expected <version>:<function>:<data input>"));
+    };
+    if !version.eq_ignore_ascii_case("OCRA-1") {
+        return Err(invalid("only OCRA-1 is supported"));
+    }
+
+    let function: Vec<&str> = function.split('-').collect();
+    let [hotp, hash, digits] = function.as_slice() else {
+        return Err(invalid("crypto function must be HOTP-<hash>-<digits>"));
+    };
+    if !hotp.eq_ignore_ascii_case("HOTP") {
+        return Err(invalid("crypto function must be HOTP"));
+    }
+    let algorithm = parse_hash(hash).ok_or_else(|| invalid("unknown hash"))?;
+    let digits: u32 = digits
+        .parse()
+        .map_err(|_| invalid("digits must be a number"))?;
+    if digits != 0 && !(4..=10).contains(&digits) {
+        return Err(invalid("digits must be 0 or between 4 and 10"));
+    }
+
+    let mut parsed = OcraSuite {
+        algorithm,
+        digits,
+        counter: false,
+        challenge_format: OcraChallengeFormat::Numeric,
+        challenge_length: 0,
+        pin_hash: None,
+        session_length: None,
+        time_step: None,
+    };
+
+    for component in data_input.split('-') {
+        let upper = component.to_ascii_uppercase();
+        let (tag, rest) = upper.split_at(1.min(upper.len()));
+        match tag {
+            "C" if rest.is_empty() => parsed.counter = true,
+            "Q" if rest.len() == 3 => {
+                parsed.challenge_format = match &rest[..1] {
+                    "A" => OcraChallengeFormat::Alphanumeric,
+                    "N" => OcraChallengeFormat::Numeric,
+                    "H" => OcraChallengeFormat::Hex,
+                    _ => return Err(invalid("challenge format must be A, N or H")),
+                };
+                parsed.challenge_length =
+                    rest[1..]
+                        .parse()
+                        .ok()
+                        .filter(|n| (4..=64).contains(n))
+                        .ok_or_else(|| invalid("challenge length must be between 04 and 64"))?;
+            }
+            "P" => {
+                parsed.pin_hash = Some(parse_hash(rest).ok_or_else(|| invalid("unknown PIN hash"))?)
+            }
+            "S" if rest.len() == 3 => {
+                parsed.session_length = Some(
+                    rest.parse()
+                        .map_err(|_| invalid("session length must be three digits"))?,
+                )
+            }
+            "T" if rest.len() >= 2 => {
+                let (n, unit) = rest.split_at(rest.len() - 1);
+                let n: u64 = n
+                    .parse()
+                    .map_err(|_| invalid("time step must be a number"))?;
+                let step = match unit {
+                    "S" if (1..=59).contains(&n) => n,
+                    "M" if (1..=59).contains(&n) => n * 60,
+                    "H" if n <= 48 => n * 3600,
+                    _ => return Err(invalid("time step must be 1-59S, 1-59M or 0-48H")),
+                };
+                parsed.time_step = Some(step.max(1));
+            }
+            _ => return Err(invalid(&format!("unknown data input '{}'", component))),
+        }
+    }
+
+    if parsed.challenge_length == 0 {
+        return Err(invalid("data input must include a challenge (Q)"));
+    }
+    Ok(parsed)
+}
+
+/// Compute an OCRA response. `key` is the shared secret as hex.
+#[universal_function]
+pub fn generate_ocra_response(
+    key: String,
+    suite: String,
+    input: OcraInput,
+) -> Result<OcraResult, UtilityError> {
+    let parsed = parse_ocra_suite(&suite)?;
+    let key = hex::decode(key.trim())
+        .map_err(|e| UtilityError::InvalidInput(format!("Invalid hex key: {}", e)))?;
+
+    // OCRASuite || 0x00 || C || Q || P || S || T
+    let mut message = suite.trim().as_bytes().to_vec();
+    message.push(0);
+
+    if parsed.counter {
+        let counter = input.counter.ok_or_else(|| missing("a counter"))?;
+        message.extend_from_slice(&counter.to_be_bytes());
+    }
+
+    message.extend_from_slice(&encode_challenge(&parsed, &input.challenge)?);
+
+    if let Some(hash) = &parsed.pin_hash {
+        let pin = input.pin.as_deref().ok_or_else(|| missing("a PIN"))?;
+        message.extend_from_slice(&hash_pin(hash, pin.as_bytes()));
+    }
+
+    if let Some(length) = parsed.session_length {
+        let session = input
+            .session
+            .as_deref()
+            .ok_or_else(|| missing("session information"))?;
+        let bytes = hex::decode(session.trim()).map_err(|e| {
+            UtilityError::InvalidInput(format!("Session information must be hex: {}", e))
+        })?;
+        let length = length as usize;
+        if bytes.len() > length {
+            return Err(UtilityError::InvalidInput(format!(
+                "Session information is longer than {} bytes",
+                length
+            )));
+        }
+        // Left-padded with zeros to the declared length.
+        message.extend(std::iter::repeat_n(0, length - bytes.len()));
+        message.extend_from_slice(&bytes);
+    }
+
+    if let Some(step) = parsed.time_step {
+        let timestamp = match input.timestamp {
+            Some(t) => t,
+            None => unix_now()?,
+        };
+        message.extend_from_slice(&(timestamp / step).to_be_bytes());
+    }
+
+    let digest = hmac_digest(&parsed.algorithm, &key, &message)?;
+    let response = if parsed.digits == 0 {
+        hex::encode(&digest)
+    } else {
+        encode_decimal(dynamic_truncate(&digest) as u64, parsed.digits)?
+    };
+
+    Ok(OcraResult {
+        response,
+        suite: parsed,
+        data_input: hex::encode(&message),
+    })
+}
+
+fn missing(what: &str) -> UtilityError {
+    UtilityError::InvalidInput(format!("This OCRA suite requires {}", what))
+}
+
+fn parse_hash(name: &str) -> Option<HashAlgorithm> {
+    match name.to_ascii_uppercase().as_str() {
+        "SHA1" => Some(HashAlgorithm::SHA1),
+        "SHA256" => Some(HashAlgorithm::SHA256),
+        "SHA512" => Some(HashAlgorithm::SHA512),
+        _ => None,
+    }
+}
+
+fn hash_pin(algorithm: &HashAlgorithm, pin: &[u8]) -> Vec<u8> {
+    match algorithm {
+        HashAlgorithm::SHA1 => Sha1::digest(pin).to_vec(),
+        HashAlgorithm::SHA256 => Sha256::digest(pin).to_vec(),
+        HashAlgorithm::SHA512 => Sha512::digest(pin).to_vec(),
+    }
+}
+
+/// Encode the challenge as the fixed 128-byte Q field: converted to hex,
+/// then right-padded with zero nibbles.
+fn encode_challenge(suite: &OcraSuite, challenge: &str) -> Result<Vec<u8>, UtilityError> {
+    let challenge = challenge.trim();
+    if challenge.is_empty() || challenge.chars().count() > suite.challenge_length as usize {
+        return Err(UtilityError::InvalidInput(format!(
+            "Challenge must be 1-{} characters",
+            suite.challenge_length
+        )));
+    }
+
+    let mut nibbles = match suite.challenge_format {
+        OcraChallengeFormat::Alphanumeric => {
+            if !challenge.is_ascii() {
+                return Err(UtilityError::InvalidInput(
+                    "Alphanumeric challenge must be ASCII".to_string(),
+                ));
+            }
+            hex::encode(challenge)
+        }
+        OcraChallengeFormat::Numeric => BigUint::parse_bytes(challenge.as_bytes(), 10)
+            .ok_or_else(|| {
+                UtilityError::InvalidInput("Numeric challenge must be decimal digits".to_string())
+            })?
+            .to_str_radix(16),
+        OcraChallengeFormat::Hex => {
+            if !challenge.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(UtilityError::InvalidInput(
+                    "Hex challenge must be hex digits".to_string(),
+                ));
+            }
+            challenge.to_string()
+        }
+    };
+
+    nibbles.extend(std::iter::repeat_n('0', 256 - nibbles.len()));
+    hex::decode(nibbles).map_err(|e| UtilityError::InvalidInput(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 6287 Appendix C keys.
+    const KEY20: &str = "3132333435363738393031323334353637383930";
+    const KEY32: &str = "3132333435363738393031323334353637383930313233343536373839303132";
+    const KEY64: &str = "31323334353637383930313233343536373839303132333435363738393031323334353637383930313233343536373839303132333435363738393031323334";
+
+    fn respond(key: &str, suite: &str, input: OcraInput) -> String {
+        generate_ocra_response(key.to_string(), suite.to_string(), input)
+            .unwrap()
+            .response
+    }
+
+    fn challenge(q: &str) -> OcraInput {
+        OcraInput {
+            challenge: q.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn one_way_numeric_challenge() {
+        let expected = [
+            ("00000000", "237653"),
+            ("11111111", "243178"),
+            ("22222222", "653583"),
+            ("33333333", "740991"),
+            ("99999999", "294470"),
+        ];
+        for (q, r) in expected {
+            assert_eq!(respond(KEY20, "OCRA-1:HOTP-SHA1-6:QN08", challenge(q)), r);
+        }
+    }
+
+    #[test]
+    fn counter_and_pin() {
+        let suite = "OCRA-1:HOTP-SHA256-8:C-QN08-PSHA1";
+        for (c, r) in [(0, "65347737"), (1, "86775851"), (2, "78192410")] {
+            let input = OcraInput {
+                counter: Some(c),
+                pin: Some("1234".to_string()),
+                ..challenge("12345678")
+            };
+            assert_eq!(respond(KEY32, suite, input), r);
+        }
+
+        let input = OcraInput {
+            pin: Some("1234".to_string()),
+            ..challenge("11111111")
+        };
+        assert_eq!(
+            respond(KEY32, "OCRA-1:HOTP-SHA256-8:QN08-PSHA1", input),
+            "01501458"
+        );
+    }
+
+    #[test]
+    fn counter_and_time() {
+        let input = OcraInput {
+            counter: Some(0),
+            ..challenge("00000000")
+        };
+        assert_eq!(
+            respond(KEY64, "OCRA-1:HOTP-SHA512-8:C-QN08", input),
+            "07016083"
+        );
+
+        // T = 0x132d0b6 minutes.
+        let input = OcraInput {
+            timestamp: Some(0x132d0b6 * 60),
+            ..challenge("00000000")
+        };
+        assert_eq!(
+            respond(KEY64, "OCRA-1:HOTP-SHA512-8:QN08-T1M", input),
+            "95209754"
+        );
+    }
+
+    #[test]
+    fn parses_suite_components() {
+        let suite = parse_ocra_suite("OCRA-1:HOTP-SHA512-8:C-QH40-PSHA256-S064-T30S").unwrap();
+        assert!(suite.counter);
+        assert_eq!(suite.challenge_format, OcraChallengeFormat::Hex);
+        assert_eq!(suite.challenge_length, 40);
+        assert!(matches!(suite.pin_hash, Some(HashAlgorithm::SHA256)));
+        assert_eq!(suite.session_length, Some(64));
+        assert_eq!(suite.time_step, Some(30));
+
+        assert!(parse_ocra_suite("OCRA-2:HOTP-SHA1-6:QN08").is_err());
+        assert!(parse_ocra_suite("OCRA-1:HOTP-SHA1-3:QN08").is_err());
+        assert!(parse_ocra_suite("OCRA-1:HOTP-SHA1-6:C").is_err());
+        assert!(parse_ocra_suite("OCRA-1:HOTP-SHA1-6:é").is_err());
+        assert!(parse_ocra_suite("OCRA-1:HOTP-SHA1-6:Qé12").is_err());
+        assert!(parse_ocra_suite("OCRA-1:HOTP-SHA1-6:QN08-T1é").is_err());
+    }
+
+    #[test]
+    fn missing_inputs_are_reported() {
+        let err = generate_ocra_response(
+            KEY20.to_string(),
+            "OCRA-1:HOTP-SHA1-6:C-QN08".to_string(),
+            challenge("1234"),
+        );
+        assert!(err.is_err());
+    }
+}
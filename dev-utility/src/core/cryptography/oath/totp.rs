@@ -11,19 +11,15 @@
 //
 // See LICENSE file for details or contact admin@aprilnea.com
 
-#[cfg(not(target_arch = "wasm32"))]
-use std::time::{SystemTime, UNIX_EPOCH};
-#[cfg(target_arch = "wasm32")]
-use web_time::{SystemTime, UNIX_EPOCH};
-
-use super::HashAlgorithm;
+use super::{
+    check_digits, generate_otp_code_for_time, unix_now, validate_otp_code, HashAlgorithm,
+    OtpParameters,
+};
 use crate::error::UtilityError;
+use crate::generator::{generate_qr_code_with_options, QrCodeImage, QrCodeOptions};
 use base32::Alphabet;
-use hmac::{Hmac, Mac};
 
 use serde::{Deserialize, Serialize};
-use sha1::Sha1;
-use sha2::{Sha256, Sha512};
 use universal_function_macro::universal_function;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -32,7 +28,7 @@ use universal_function_macro::universal_function;
 #[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
 pub struct TotpSecretResult {
     pub secret: String,           // Base32 encoded secret
-    pub qr_code_url: String,      // QR code URL for provisioning
+    pub qr_code: QrCodeImage,     // Locally rendered QR code of the URI
     pub provisioning_uri: String, // Full provisioning URI
 }
 
@@ -61,6 +57,9 @@ pub struct TotpValidationResult {
     pub message: String,          // Validation message
 }
 
+// The first seven mirror the otpauth URI parameters; everything about the
+// QR rendering itself lives in `qr`.
+#[allow(clippy::too_many_arguments)]
 #[universal_function]
 pub async fn generate_totp_secret(
     issuer: String,
@@ -70,7 +69,10 @@ pub async fn generate_totp_secret(
     period: u32,
     image: Option<String>,
     add_issuer_prefix: bool,
+    qr: Option<QrCodeOptions>,
 ) -> Result<TotpSecretResult, UtilityError> {
+    check_digits(digits)?;
+
     // Generate 32 bytes of random data for the secret
     let mut secret_bytes = [0u8; 32];
     #[cfg(target_arch = "wasm32")]
@@ -116,14 +118,13 @@ pub async fn generate_totp_secret(
         uri.push_str(&format!("&image={}", urlencoding::encode(img)));
     }
 
-    let qr_code_url = format!(
-        "https://api.qrserver.com/v1/create-qr-code/?size=300x300&data={}",
-        urlencoding::encode(&uri)
-    );
+    // Rendered in-process: the URI carries the secret, so it must never be
+    // handed to a third-party QR service.
+    let qr_code = generate_qr_code_with_options(&uri, qr.unwrap_or_default())?;
 
     Ok(TotpSecretResult {
         secret,
-        qr_code_url,
+        qr_code,
         provisioning_uri: uri,
     })
 }
@@ -136,12 +137,7 @@ pub async fn generate_totp_code(
     digits: u32,
     period: u32,
 ) -> Result<TotpCodeResult, UtilityError> {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map_err(|e| UtilityError::Runtime(format!("Time error: {}", e)))?
-        .as_secs();
-
-    generate_totp_code_for_time(secret, algorithm, digits, period, now).await
+    generate_totp_code_for_time(secret, algorithm, digits, period, unix_now()?).await
 }
 
 // Generate TOTP code for specific time
@@ -153,59 +149,16 @@ pub async fn generate_totp_code_for_time(
     period: u32,
     timestamp: u64,
 ) -> Result<TotpCodeResult, UtilityError> {
-    // Decode base32 secret
-    let secret_bytes = base32::decode(Alphabet::Rfc4648 { padding: false }, &secret)
-        .ok_or_else(|| UtilityError::InvalidInput("Invalid base32 secret".to_string()))?;
-
-    // Calculate time counter
-    let time_counter = timestamp / period as u64;
-    let time_remaining = period as u64 - (timestamp % period as u64);
-
-    // Convert counter to bytes (big endian)
-    let counter_bytes = time_counter.to_be_bytes();
-
-    // Calculate HMAC
-    let hmac_result = match algorithm {
-        HashAlgorithm::SHA1 => {
-            let mut mac = Hmac::<Sha1>::new_from_slice(&secret_bytes)
-                .map_err(|e| UtilityError::Runtime(format!("HMAC error: {}", e)))?;
-            mac.update(&counter_bytes);
-            mac.finalize().into_bytes().to_vec()
-        }
-        HashAlgorithm::SHA256 => {
-            let mut mac = Hmac::<Sha256>::new_from_slice(&secret_bytes)
-                .map_err(|e| UtilityError::Runtime(format!("HMAC error: {}", e)))?;
-            mac.update(&counter_bytes);
-            mac.finalize().into_bytes().to_vec()
-        }
-        HashAlgorithm::SHA512 => {
-            let mut mac = Hmac::<Sha512>::new_from_slice(&secret_bytes)
-                .map_err(|e| UtilityError::Runtime(format!("HMAC error: {}", e)))?;
-            mac.update(&counter_bytes);
-            mac.finalize().into_bytes().to_vec()
-        }
-    };
-
-    // Dynamic truncation
-    let offset = (hmac_result[hmac_result.len() - 1] & 0xf) as usize;
-    let binary = ((hmac_result[offset] & 0x7f) as u32) << 24
-        | (hmac_result[offset + 1] as u32) << 16
-        | (hmac_result[offset + 2] as u32) << 8
-        | (hmac_result[offset + 3] as u32);
-
-    // Generate code
-    let modulo = 10_u32.pow(digits);
-    let code = binary % modulo;
-    let code_str = format!("{:0width$}", code, width = digits as usize);
-
-    Ok(TotpCodeResult {
-        code: code_str,
-        time_remaining,
-        time_used: time_counter,
-        algorithm,
-        digits,
-        period,
-    })
+    generate_otp_code_for_time(
+        secret,
+        OtpParameters {
+            algorithm,
+            digits,
+            period,
+            ..Default::default()
+        },
+        timestamp,
+    )
 }
 
 // Validate TOTP code
@@ -218,77 +171,15 @@ pub async fn validate_totp_code(
     period: u32,
     window: u32, // Number of time windows to check (before and after current)
 ) -> Result<TotpValidationResult, UtilityError> {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map_err(|e| UtilityError::Runtime(format!("Time error: {}", e)))?
-        .as_secs();
-
-    let current_time_window = now / period as u64;
-
-    // Check current window first
-    let current_result =
-        generate_totp_code_for_time(secret.clone(), algorithm.clone(), digits, period, now).await?;
-
-    if current_result.code == code {
-        return Ok(TotpValidationResult {
-            is_valid: true,
-            time_offset: 0,
-            used_time_window: current_time_window,
-            current_time_window,
-            message: "Code is valid for current time window".to_string(),
-        });
-    }
-
-    // Check windows before and after
-    for offset in 1..=window as i64 {
-        // Check past windows
-        if let Ok(past_result) = generate_totp_code_for_time(
-            secret.clone(),
-            algorithm.clone(),
-            digits,
-            period,
-            (current_time_window as i64 - offset) as u64 * period as u64,
-        )
-        .await
-        {
-            if past_result.code == code {
-                return Ok(TotpValidationResult {
-                    is_valid: true,
-                    time_offset: -offset as i32,
-                    used_time_window: (current_time_window as i64 - offset) as u64,
-                    current_time_window,
-                    message: format!("Code is valid for past time window (offset: {})", -offset),
-                });
-            }
-        }
-
-        // Check future windows
-        if let Ok(future_result) = generate_totp_code_for_time(
-            secret.clone(),
-            algorithm.clone(),
+    validate_otp_code(
+        secret,
+        code,
+        OtpParameters {
+            algorithm,
             digits,
             period,
-            (current_time_window + offset as u64) * period as u64,
-        )
-        .await
-        {
-            if future_result.code == code {
-                return Ok(TotpValidationResult {
-                    is_valid: true,
-                    time_offset: offset as i32,
-                    used_time_window: current_time_window + offset as u64,
-                    current_time_window,
-                    message: format!("Code is valid for future time window (offset: +{})", offset),
-                });
-            }
-        }
-    }
-
-    Ok(TotpValidationResult {
-        is_valid: false,
-        time_offset: 0,
-        used_time_window: current_time_window,
-        current_time_window,
-        message: "Code is not valid for any checked time window".to_string(),
-    })
+            ..Default::default()
+        },
+        window,
+    )
 }
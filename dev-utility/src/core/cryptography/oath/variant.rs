@@ -0,0 +1,396 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(target_arch = "wasm32")]
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use super::{
+    check_digits, decode_secret, dynamic_truncate, encode_alphabet, encode_decimal, hmac_digest,
+    HashAlgorithm, TotpCodeResult, TotpValidationResult,
+};
+use crate::error::UtilityError;
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use universal_function_macro::universal_function;
+
+/// Symbols used by Steam Guard mobile authenticator codes.
+pub const STEAM_ALPHABET: &str = "23456789BCDFGHJKMNPQRTVWXY";
+
+/// Yandex.Key spells codes in lowercase Latin letters.
+const YANDEX_ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum OtpVariant {
+    Rfc6238, // HMAC + dynamic truncation, decimal or custom alphabet
+    Steam,   // HMAC-SHA1, 30s, 5 symbols from STEAM_ALPHABET
+    Yandex,  // Yandex.Key: PIN-derived key, HMAC-SHA256, 8 letters
+    Motp,    // Mobile-OTP: MD5(time/10 + secret + PIN), 6 hex digits
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct OtpParameters {
+    pub variant: OtpVariant,
+    pub algorithm: HashAlgorithm, // Only used by Rfc6238
+    pub digits: u32,              // Code length (1-10)
+    pub period: u32,              // Time step in seconds
+    pub t0: u64,                  // Unix time at which step 0 begins
+    pub alphabet: Option<String>, // Custom symbols for Rfc6238; None = decimal
+    pub pin: Option<String>,      // Required by Yandex and mOTP
+}
+
+impl Default for OtpParameters {
+    fn default() -> Self {
+        Self {
+            variant: OtpVariant::Rfc6238,
+            algorithm: HashAlgorithm::SHA1,
+            digits: 6,
+            period: 30,
+            t0: 0,
+            alphabet: None,
+            pin: None,
+        }
+    }
+}
+
+impl OtpParameters {
+    /// The fixed parameters a variant is defined with.
+    pub fn preset(variant: OtpVariant) -> Self {
+        match variant {
+            OtpVariant::Rfc6238 => Self::default(),
+            OtpVariant::Steam => Self {
+                variant,
+                digits: 5,
+                alphabet: Some(STEAM_ALPHABET.to_string()),
+                ..Self::default()
+            },
+            OtpVariant::Yandex => Self {
+                variant,
+                algorithm: HashAlgorithm::SHA256,
+                digits: 8,
+                ..Self::default()
+            },
+            OtpVariant::Motp => Self {
+                variant,
+                period: 10,
+                ..Self::default()
+            },
+        }
+    }
+}
+
+// Generate a code for the current time under any supported variant
+#[universal_function]
+pub fn generate_otp_code(
+    secret: String,
+    params: OtpParameters,
+) -> Result<TotpCodeResult, UtilityError> {
+    generate_otp_code_for_time(secret, params, unix_now()?)
+}
+
+// Generate a code for a specific time under any supported variant
+#[universal_function]
+pub fn generate_otp_code_for_time(
+    secret: String,
+    params: OtpParameters,
+    timestamp: u64,
+) -> Result<TotpCodeResult, UtilityError> {
+    if params.period == 0 {
+        return Err(UtilityError::InvalidInput(
+            "Period must be greater than zero".to_string(),
+        ));
+    }
+    if timestamp < params.t0 {
+        return Err(UtilityError::InvalidInput(
+            "Timestamp is before T0".to_string(),
+        ));
+    }
+    check_digits(params.digits)?;
+
+    let period = params.period as u64;
+    let elapsed = timestamp - params.t0;
+    let counter = elapsed / period;
+
+    let code = match params.variant {
+        OtpVariant::Rfc6238 | OtpVariant::Steam => {
+            let key = decode_secret(&secret)?;
+            let digest = hmac_digest(&params.algorithm, &key, &counter.to_be_bytes())?;
+            let value = dynamic_truncate(&digest) as u64;
+            match &params.alphabet {
+                Some(alphabet) => encode_alphabet(value, params.digits, alphabet)?,
+                None => encode_decimal(value, params.digits)?,
+            }
+        }
+        OtpVariant::Yandex => yandex_code(&secret, require_pin(&params)?, counter, params.digits)?,
+        OtpVariant::Motp => motp_code(&secret, require_pin(&params)?, counter, params.digits)?,
+    };
+
+    Ok(TotpCodeResult {
+        code,
+        time_remaining: period - elapsed % period,
+        time_used: counter,
+        algorithm: params.algorithm,
+        digits: params.digits,
+        period: params.period,
+    })
+}
+
+// Validate a code against the current time under any supported variant
+#[universal_function]
+pub fn validate_otp_code(
+    secret: String,
+    code: String,
+    params: OtpParameters,
+    window: u32, // Number of time windows to check (before and after current)
+) -> Result<TotpValidationResult, UtilityError> {
+    validate_otp_code_at(secret, code, params, window, unix_now()?)
+}
+
+pub(crate) fn validate_otp_code_at(
+    secret: String,
+    code: String,
+    params: OtpParameters,
+    window: u32,
+    now: u64,
+) -> Result<TotpValidationResult, UtilityError> {
+    let current = generate_otp_code_for_time(secret.clone(), params.clone(), now)?;
+    let current_time_window = current.time_used;
+    let period = params.period as u64;
+
+    if codes_match(&current.code, &code, &params) {
+        return Ok(TotpValidationResult {
+            is_valid: true,
+            time_offset: 0,
+            used_time_window: current_time_window,
+            current_time_window,
+            message: "Code is valid for current time window".to_string(),
+        });
+    }
+
+    for offset in 1..=window as u64 {
+        // Check past windows
+        if let Some(past_window) = current_time_window.checked_sub(offset) {
+            let at = params.t0 + past_window * period;
+            if let Ok(past) = generate_otp_code_for_time(secret.clone(), params.clone(), at) {
+                if codes_match(&past.code, &code, &params) {
+                    return Ok(TotpValidationResult {
+                        is_valid: true,
+                        time_offset: -(offset as i32),
+                        used_time_window: past_window,
+                        current_time_window,
+                        message: format!(
+                            "Code is valid for past time window (offset: -{})",
+                            offset
+                        ),
+                    });
+                }
+            }
+        }
+
+        // Check future windows
+        let future_window = current_time_window + offset;
+        let at = params.t0 + future_window * period;
+        if let Ok(future) = generate_otp_code_for_time(secret.clone(), params.clone(), at) {
+            if codes_match(&future.code, &code, &params) {
+                return Ok(TotpValidationResult {
+                    is_valid: true,
+                    time_offset: offset as i32,
+                    used_time_window: future_window,
+                    current_time_window,
+                    message: format!("Code is valid for future time window (offset: +{})", offset),
+                });
+            }
+        }
+    }
+
+    Ok(TotpValidationResult {
+        is_valid: false,
+        time_offset: 0,
+        used_time_window: current_time_window,
+        current_time_window,
+        message: "Code is not valid for any checked time window".to_string(),
+    })
+}
+
+pub(crate) fn unix_now() -> Result<u64, UtilityError> {
+    Ok(SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| UtilityError::Runtime(format!("Time error: {}", e)))?
+        .as_secs())
+}
+
+pub(crate) fn codes_match(expected: &str, entered: &str, params: &OtpParameters) -> bool {
+    let entered: String = entered.chars().filter(|c| !c.is_whitespace()).collect();
+    match params.variant {
+        // Shown in a single case, but users type them in either.
+        OtpVariant::Steam | OtpVariant::Yandex | OtpVariant::Motp => {
+            expected.eq_ignore_ascii_case(&entered)
+        }
+        OtpVariant::Rfc6238 => expected == entered,
+    }
+}
+
+fn require_pin(params: &OtpParameters) -> Result<&str, UtilityError> {
+    params
+        .pin
+        .as_deref()
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| UtilityError::InvalidInput("This variant requires a PIN".to_string()))
+}
+
+/// Yandex.Key: the HMAC key is SHA-256(PIN || secret) with a leading zero
+/// byte dropped, and the code is a 63-bit truncation spelled in base 26.
+fn yandex_code(secret: &str, pin: &str, counter: u64, digits: u32) -> Result<String, UtilityError> {
+    // Yandex secrets are 26 base32 characters; only the first 16 bytes are
+    // key material, the rest is a checksum.
+    let mut secret = decode_secret(secret)?;
+    if secret.len() < 16 {
+        return Err(UtilityError::InvalidInput(
+            "Yandex secret must decode to at least 16 bytes".to_string(),
+        ));
+    }
+    secret.truncate(16);
+
+    let mut hasher = Sha256::new();
+    hasher.update(pin.as_bytes());
+    hasher.update(&secret);
+    let mut key = hasher.finalize().to_vec();
+    if key[0] == 0 {
+        key.remove(0);
+    }
+
+    let digest = hmac_digest(&HashAlgorithm::SHA256, &key, &counter.to_be_bytes())?;
+    let offset = (digest[digest.len() - 1] & 0xf) as usize;
+    let mut value = [0u8; 8];
+    value.copy_from_slice(&digest[offset..offset + 8]);
+    value[0] &= 0x7f;
+
+    let base = YANDEX_ALPHABET.len() as u64;
+    let code = u64::from_be_bytes(value) % base.pow(digits);
+    // Most significant letter first, unlike Steam.
+    encode_alphabet(code, digits, YANDEX_ALPHABET).map(|c| c.chars().rev().collect())
+}
+
+/// Mobile-OTP: the first `digits` hex characters of
+/// MD5(time step || secret || PIN), all as text.
+fn motp_code(secret: &str, pin: &str, counter: u64, digits: u32) -> Result<String, UtilityError> {
+    let mut hasher = Md5::new();
+    hasher.update(counter.to_string().as_bytes());
+    hasher.update(secret.trim().as_bytes());
+    hasher.update(pin.as_bytes());
+    let digest = hex::encode(hasher.finalize());
+    Ok(digest[..digits as usize].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 6238 Appendix B seed for SHA1.
+    const SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    fn code_at(params: OtpParameters, secret: &str, timestamp: u64) -> String {
+        generate_otp_code_for_time(secret.to_string(), params, timestamp)
+            .unwrap()
+            .code
+    }
+
+    #[test]
+    fn matches_rfc6238_vectors() {
+        let params = OtpParameters {
+            digits: 8,
+            ..Default::default()
+        };
+        assert_eq!(code_at(params.clone(), SECRET, 59), "94287082");
+        assert_eq!(code_at(params.clone(), SECRET, 1111111109), "07081804");
+        assert_eq!(code_at(params, SECRET, 20000000000), "65353130");
+    }
+
+    #[test]
+    fn t0_shifts_the_counter() {
+        let shifted = OtpParameters {
+            digits: 8,
+            t0: 1000,
+            ..Default::default()
+        };
+        assert_eq!(code_at(shifted.clone(), SECRET, 1059), "94287082");
+        assert!(generate_otp_code_for_time(SECRET.to_string(), shifted, 999).is_err());
+    }
+
+    #[test]
+    fn steam_uses_its_alphabet() {
+        let code = code_at(OtpParameters::preset(OtpVariant::Steam), SECRET, 59);
+        assert_eq!(code.len(), 5);
+        assert!(code.chars().all(|c| STEAM_ALPHABET.contains(c)));
+        // 0x4c93cf18... at step 1 is 1094287082; spelled base 26 LSB-first.
+        assert_eq!(
+            encode_alphabet(1094287082, 5, STEAM_ALPHABET).unwrap(),
+            code
+        );
+    }
+
+    #[test]
+    fn yandex_needs_pin_and_yields_letters() {
+        let params = OtpParameters::preset(OtpVariant::Yandex);
+        let secret = "LA2V6KMCGYMWWVEW64RNP3JA3IAAAAAAHTSG4HRZPI";
+        assert!(generate_otp_code_for_time(secret.to_string(), params.clone(), 0).is_err());
+
+        let params = OtpParameters {
+            pin: Some("7586".to_string()),
+            ..params
+        };
+        assert_eq!(code_at(params.clone(), secret, 1581064020), "oactmacq");
+        assert_eq!(code_at(params, secret, 1581090810), "wemdwrix");
+
+        let params = OtpParameters {
+            pin: Some("5210481216086702".to_string()),
+            ..OtpParameters::preset(OtpVariant::Yandex)
+        };
+        let secret = "JBGSAU4G7IEZG6OY4UAXX62JU4AAAAAAHTSG4HXU3M";
+        assert_eq!(code_at(params, secret, 1581091469), "dfrpywob");
+    }
+
+    #[test]
+    fn motp_hashes_time_secret_and_pin() {
+        let params = OtpParameters {
+            pin: Some("1234".to_string()),
+            ..OtpParameters::preset(OtpVariant::Motp)
+        };
+        let code = code_at(params, "e3152afee62599c8", 1_700_000_000);
+
+        let mut hasher = Md5::new();
+        hasher.update(b"170000000e3152afee62599c81234");
+        assert_eq!(code, hex::encode(hasher.finalize())[..6]);
+    }
+
+    #[test]
+    fn validation_checks_neighbouring_windows() {
+        let params = OtpParameters::default();
+        let code = code_at(params.clone(), SECRET, 1000 - 30);
+        let r = validate_otp_code_at(SECRET.to_string(), code, params, 1, 1000).unwrap();
+        assert!(r.is_valid);
+        assert_eq!(r.time_offset, -1);
+    }
+
+    #[test]
+    fn rejects_invalid_digits() {
+        let params = OtpParameters {
+            digits: 11,
+            ..Default::default()
+        };
+        assert!(generate_otp_code_for_time(SECRET.to_string(), params, 59).is_err());
+    }
+}
@@ -0,0 +1,482 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+use super::{OtpAccount, OtpKind, OtpVault};
+use crate::core::cryptography::oath::{HashAlgorithm, OtpParameters, OtpVariant};
+use crate::error::UtilityError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use universal_function_macro::universal_function;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum OtpExportFormat {
+    OtpauthUri, // One otpauth:// URI per line
+    Aegis,      // Unencrypted Aegis JSON export
+    AndOtp,     // Unencrypted andOTP JSON backup
+}
+
+/// Read accounts from otpauth:// URIs (one per line), a plain Aegis export,
+/// or an andOTP backup. The format is detected from the content.
+#[universal_function]
+pub fn import_otp_accounts(data: String) -> Result<OtpVault, UtilityError> {
+    read_accounts(data.trim()).map(|accounts| OtpVault { accounts })
+}
+
+/// Write accounts in a format other authenticator apps can import.
+#[universal_function]
+pub fn export_otp_accounts(
+    vault: OtpVault,
+    format: OtpExportFormat,
+) -> Result<String, UtilityError> {
+    let accounts = vault.accounts;
+    let pretty = |value: Value| {
+        serde_json::to_string_pretty(&value).map_err(|e| UtilityError::Runtime(e.to_string()))
+    };
+    match format {
+        OtpExportFormat::OtpauthUri => Ok(accounts
+            .iter()
+            .map(otpauth_uri)
+            .collect::<Vec<_>>()
+            .join("\n")),
+        OtpExportFormat::Aegis => pretty(json!({
+            "version": 1,
+            "header": { "slots": null, "params": null },
+            "db": {
+                "version": 2,
+                "entries": accounts.iter().map(aegis_json).collect::<Vec<_>>(),
+            },
+        })),
+        OtpExportFormat::AndOtp => {
+            let entries = accounts
+                .iter()
+                .map(andotp_json)
+                .collect::<Result<Vec<_>, _>>()?;
+            pretty(Value::Array(entries))
+        }
+    }
+}
+
+fn read_accounts(trimmed: &str) -> Result<Vec<OtpAccount>, UtilityError> {
+    if trimmed.is_empty() {
+        return Err(UtilityError::InvalidInput("Nothing to import".to_string()));
+    }
+    if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
+        return trimmed
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| parse_otpauth_uri(line.to_string()))
+            .collect();
+    }
+
+    let value: Value = serde_json::from_str(trimmed)
+        .map_err(|e| UtilityError::ParseError(format!("Invalid JSON: {}", e)))?;
+    match &value {
+        Value::Array(entries) => entries.iter().map(andotp_entry).collect(),
+        Value::Object(root) => match root.get("db") {
+            Some(Value::String(_)) => Err(UtilityError::InvalidInput(
+                "Encrypted Aegis exports are not supported; export without encryption".to_string(),
+            )),
+            Some(db) => db
+                .get("entries")
+                .and_then(Value::as_array)
+                .ok_or_else(|| UtilityError::ParseError("Aegis export has no entries".to_string()))?
+                .iter()
+                .map(aegis_entry)
+                .collect(),
+            None => Err(UtilityError::ParseError(
+                "Unrecognized backup format".to_string(),
+            )),
+        },
+        _ => Err(UtilityError::ParseError(
+            "Unrecognized backup format".to_string(),
+        )),
+    }
+}
+
+/// Parse a Key URI Format string. Besides `totp` and `hotp`, the `steam`,
+/// `yaotp` and `motp` types used by Aegis are understood.
+#[universal_function]
+pub fn parse_otpauth_uri(uri: String) -> Result<OtpAccount, UtilityError> {
+    let invalid = |msg: &str| UtilityError::InvalidInput(format!("Invalid otpauth URI: {}", msg));
+    let rest = uri
+        .trim()
+        .strip_prefix("otpauth://")
+        .ok_or_else(|| invalid("missing otpauth:// scheme"))?;
+    let (kind_str, rest) = rest
+        .split_once('/')
+        .ok_or_else(|| invalid("missing type"))?;
+    let (label, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    let (kind, variant) = match kind_str.to_ascii_lowercase().as_str() {
+        "totp" => (OtpKind::Totp, OtpVariant::Rfc6238),
+        "hotp" => (OtpKind::Hotp, OtpVariant::Rfc6238),
+        "steam" => (OtpKind::Totp, OtpVariant::Steam),
+        "yaotp" => (OtpKind::Totp, OtpVariant::Yandex),
+        "motp" => (OtpKind::Totp, OtpVariant::Motp),
+        other => return Err(invalid(&format!("unsupported type '{}'", other))),
+    };
+    let mut params = OtpParameters::preset(variant);
+
+    let label = decode_component(label)?;
+    let (label_issuer, account) = match label.split_once(':') {
+        Some((issuer, account)) => (Some(issuer.trim().to_string()), account.trim().to_string()),
+        None => (None, label.trim().to_string()),
+    };
+
+    let mut secret = None;
+    let mut issuer = None;
+    let mut counter = 0;
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = decode_component(value)?;
+        match key.to_ascii_lowercase().as_str() {
+            "secret" => secret = Some(value.replace(' ', "").to_uppercase()),
+            "issuer" => issuer = Some(value),
+            "algorithm" => params.algorithm = parse_algorithm(&value)?,
+            "digits" => params.digits = parse_number(key, &value)?,
+            "period" => params.period = parse_number(key, &value)?,
+            "counter" => counter = parse_number(key, &value)?,
+            "pin" => params.pin = Some(value),
+            _ => {}
+        }
+    }
+    // mOTP secrets are hex text, not base32.
+    let secret = match (secret, variant) {
+        (Some(s), OtpVariant::Motp) => s.to_lowercase(),
+        (Some(s), _) => s,
+        (None, _) => return Err(invalid("missing secret")),
+    };
+    if params.period == 0 {
+        return Err(invalid("period must be positive"));
+    }
+
+    let issuer = issuer.or(label_issuer).unwrap_or_default();
+    let mut account = OtpAccount::new(kind, issuer, account, secret, params);
+    account.counter = counter;
+    Ok(account)
+}
+
+/// Build the otpauth:// URI for an account.
+pub fn otpauth_uri(account: &OtpAccount) -> String {
+    let params = &account.params;
+    let kind = match (account.kind, params.variant) {
+        (OtpKind::Hotp, _) => "hotp",
+        (OtpKind::Totp, OtpVariant::Rfc6238) => "totp",
+        (OtpKind::Totp, OtpVariant::Steam) => "steam",
+        (OtpKind::Totp, OtpVariant::Yandex) => "yaotp",
+        (OtpKind::Totp, OtpVariant::Motp) => "motp",
+    };
+    let label = if account.issuer.is_empty() {
+        account.account.clone()
+    } else {
+        format!("{}:{}", account.issuer, account.account)
+    };
+
+    let mut uri = format!(
+        "otpauth://{}/{}?secret={}",
+        kind,
+        urlencoding::encode(&label),
+        urlencoding::encode(&account.secret)
+    );
+    if !account.issuer.is_empty() {
+        uri.push_str(&format!("&issuer={}", urlencoding::encode(&account.issuer)));
+    }
+    uri.push_str(&format!(
+        "&algorithm={}&digits={}",
+        algorithm_name(&params.algorithm),
+        params.digits
+    ));
+    match account.kind {
+        OtpKind::Totp => uri.push_str(&format!("&period={}", params.period)),
+        OtpKind::Hotp => uri.push_str(&format!("&counter={}", account.counter)),
+    }
+    if let Some(pin) = &params.pin {
+        uri.push_str(&format!("&pin={}", urlencoding::encode(pin)));
+    }
+    uri
+}
+
+fn aegis_entry(entry: &Value) -> Result<OtpAccount, UtilityError> {
+    let info = entry
+        .get("info")
+        .ok_or_else(|| UtilityError::ParseError("Aegis entry has no info".to_string()))?;
+    let (kind, variant) = match str_field(entry, "type").to_lowercase().as_str() {
+        "totp" => (OtpKind::Totp, OtpVariant::Rfc6238),
+        "hotp" => (OtpKind::Hotp, OtpVariant::Rfc6238),
+        "steam" => (OtpKind::Totp, OtpVariant::Steam),
+        "yandex" => (OtpKind::Totp, OtpVariant::Yandex),
+        "motp" => (OtpKind::Totp, OtpVariant::Motp),
+        other => {
+            return Err(UtilityError::InvalidInput(format!(
+                "Unsupported Aegis entry type '{}'",
+                other
+            )))
+        }
+    };
+
+    let mut params = OtpParameters::preset(variant);
+    if let Some(algo) = info.get("algo").and_then(Value::as_str) {
+        params.algorithm = parse_algorithm(algo)?;
+    }
+    if let Some(digits) = info.get("digits").and_then(Value::as_u64) {
+        params.digits = digits as u32;
+    }
+    if let Some(period) = info.get("period").and_then(Value::as_u64) {
+        params.period = period as u32;
+    }
+    params.pin = info.get("pin").and_then(Value::as_str).map(str::to_string);
+
+    let mut account = OtpAccount::new(
+        kind,
+        str_field(entry, "issuer"),
+        str_field(entry, "name"),
+        str_field(info, "secret"),
+        params,
+    );
+    account.counter = info.get("counter").and_then(Value::as_u64).unwrap_or(0);
+    if let Some(note) = entry.get("note").and_then(Value::as_str) {
+        if !note.is_empty() {
+            account.label = Some(note.to_string());
+        }
+    }
+    Ok(account)
+}
+
+fn aegis_json(account: &OtpAccount) -> Value {
+    let params = &account.params;
+    let kind = match (account.kind, params.variant) {
+        (OtpKind::Hotp, _) => "hotp",
+        (OtpKind::Totp, OtpVariant::Rfc6238) => "totp",
+        (OtpKind::Totp, OtpVariant::Steam) => "steam",
+        (OtpKind::Totp, OtpVariant::Yandex) => "yandex",
+        (OtpKind::Totp, OtpVariant::Motp) => "motp",
+    };
+    let mut info = json!({
+        "secret": account.secret,
+        "algo": algorithm_name(&params.algorithm),
+        "digits": params.digits,
+    });
+    match account.kind {
+        OtpKind::Totp => info["period"] = json!(params.period),
+        OtpKind::Hotp => info["counter"] = json!(account.counter),
+    }
+    if let Some(pin) = &params.pin {
+        info["pin"] = json!(pin);
+    }
+    json!({
+        "type": kind,
+        "uuid": account.id,
+        "name": account.account,
+        "issuer": account.issuer,
+        "note": account.label.clone().unwrap_or_default(),
+        "icon": null,
+        "info": info,
+    })
+}
+
+fn andotp_entry(entry: &Value) -> Result<OtpAccount, UtilityError> {
+    let (kind, variant) = match str_field(entry, "type").to_uppercase().as_str() {
+        "TOTP" => (OtpKind::Totp, OtpVariant::Rfc6238),
+        "HOTP" => (OtpKind::Hotp, OtpVariant::Rfc6238),
+        "STEAM" => (OtpKind::Totp, OtpVariant::Steam),
+        other => {
+            return Err(UtilityError::InvalidInput(format!(
+                "Unsupported andOTP entry type '{}'",
+                other
+            )))
+        }
+    };
+
+    let mut params = OtpParameters::preset(variant);
+    if let Some(algo) = entry.get("algorithm").and_then(Value::as_str) {
+        params.algorithm = parse_algorithm(algo)?;
+    }
+    if let Some(digits) = entry.get("digits").and_then(Value::as_u64) {
+        params.digits = digits as u32;
+    }
+    if let Some(period) = entry.get("period").and_then(Value::as_u64) {
+        params.period = period as u32;
+    }
+
+    // Older backups have no issuer field and keep "Issuer:account" in the label.
+    let label = str_field(entry, "label");
+    let (issuer, name) = match (str_field(entry, "issuer"), label.split_once(':')) {
+        (issuer, _) if !issuer.is_empty() => (issuer, label.clone()),
+        (_, Some((issuer, name))) => (issuer.trim().to_string(), name.trim().to_string()),
+        (_, None) => (String::new(), label.clone()),
+    };
+
+    let mut account = OtpAccount::new(kind, issuer, name, str_field(entry, "secret"), params);
+    account.counter = entry.get("counter").and_then(Value::as_u64).unwrap_or(0);
+    account.last_used = entry
+        .get("last_used")
+        .and_then(Value::as_u64)
+        .filter(|ms| *ms > 0)
+        .map(|ms| ms / 1000);
+    Ok(account)
+}
+
+fn andotp_json(account: &OtpAccount) -> Result<Value, UtilityError> {
+    let params = &account.params;
+    let kind = match (account.kind, params.variant) {
+        (OtpKind::Hotp, _) => "HOTP",
+        (OtpKind::Totp, OtpVariant::Rfc6238) => "TOTP",
+        (OtpKind::Totp, OtpVariant::Steam) => "STEAM",
+        (OtpKind::Totp, other) => {
+            return Err(UtilityError::InvalidInput(format!(
+                "andOTP cannot store {:?} accounts",
+                other
+            )))
+        }
+    };
+    let mut entry = json!({
+        "secret": account.secret,
+        "issuer": account.issuer,
+        "label": account.account,
+        "digits": params.digits,
+        "type": kind,
+        "algorithm": algorithm_name(&params.algorithm),
+        "thumbnail": "Default",
+        "last_used": account.last_used.unwrap_or(0) * 1000,
+        "used_frequency": 0,
+        "tags": [],
+    });
+    match account.kind {
+        OtpKind::Totp => entry["period"] = json!(params.period),
+        OtpKind::Hotp => entry["counter"] = json!(account.counter),
+    }
+    Ok(entry)
+}
+
+fn str_field(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn decode_component(value: &str) -> Result<String, UtilityError> {
+    urlencoding::decode(&value.replace('+', " "))
+        .map(|s| s.into_owned())
+        .map_err(|e| UtilityError::DecodeError(format!("Invalid percent-encoding: {}", e)))
+}
+
+fn parse_number<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, UtilityError> {
+    value
+        .parse()
+        .map_err(|_| UtilityError::InvalidInput(format!("Invalid {}: '{}'", key, value)))
+}
+
+fn parse_algorithm(name: &str) -> Result<HashAlgorithm, UtilityError> {
+    match name.to_uppercase().replace('-', "").as_str() {
+        "SHA1" => Ok(HashAlgorithm::SHA1),
+        "SHA256" => Ok(HashAlgorithm::SHA256),
+        "SHA512" => Ok(HashAlgorithm::SHA512),
+        _ => Err(UtilityError::InvalidInput(format!(
+            "Unsupported algorithm '{}'",
+            name
+        ))),
+    }
+}
+
+fn algorithm_name(algorithm: &HashAlgorithm) -> &'static str {
+    match algorithm {
+        HashAlgorithm::SHA1 => "SHA1",
+        HashAlgorithm::SHA256 => "SHA256",
+        HashAlgorithm::SHA512 => "SHA512",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_key_uri() {
+        let account = parse_otpauth_uri(
+            "otpauth://totp/ACME%20Co:john@example.com?secret=hxdm vjec&issuer=ACME%20Co&algorithm=SHA256&digits=8&period=60"
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(account.kind, OtpKind::Totp);
+        assert_eq!(account.issuer, "ACME Co");
+        assert_eq!(account.account, "john@example.com");
+        assert_eq!(account.secret, "HXDMVJEC");
+        assert!(matches!(account.params.algorithm, HashAlgorithm::SHA256));
+        assert_eq!(account.params.digits, 8);
+        assert_eq!(account.params.period, 60);
+
+        let hotp =
+            parse_otpauth_uri("otpauth://hotp/alice?secret=JBSWY3DPEHPK3PXP&counter=7".to_string())
+                .unwrap();
+        assert_eq!(hotp.kind, OtpKind::Hotp);
+        assert_eq!(hotp.counter, 7);
+        assert_eq!(hotp.issuer, "");
+
+        assert!(parse_otpauth_uri("otpauth://totp/alice".to_string()).is_err());
+        assert!(parse_otpauth_uri("https://example.com".to_string()).is_err());
+    }
+
+    #[test]
+    fn uri_export_round_trips() {
+        let data = "otpauth://steam/Steam:gabe?secret=JBSWY3DPEHPK3PXP\n\
+                    otpauth://hotp/Corp:bob?secret=GEZDGNBVGY3TQOJQ&counter=3";
+        let vault = import_otp_accounts(data.to_string()).unwrap();
+        assert_eq!(vault.accounts[0].params.variant, OtpVariant::Steam);
+        assert_eq!(vault.accounts[0].params.digits, 5);
+
+        let exported = export_otp_accounts(vault, OtpExportFormat::OtpauthUri).unwrap();
+        let again = import_otp_accounts(exported).unwrap().accounts;
+        assert_eq!(again.len(), 2);
+        assert_eq!(again[0].params.variant, OtpVariant::Steam);
+        assert_eq!(again[1].issuer, "Corp");
+        assert_eq!(again[1].counter, 3);
+    }
+
+    #[test]
+    fn imports_aegis_and_andotp() {
+        let aegis = r#"{
+            "version": 1,
+            "header": {"slots": null, "params": null},
+            "db": {"version": 2, "entries": [
+                {"type": "totp", "name": "alice", "issuer": "GitHub", "note": "",
+                 "info": {"secret": "JBSWY3DPEHPK3PXP", "algo": "SHA1", "digits": 6, "period": 30}},
+                {"type": "hotp", "name": "bob", "issuer": "VPN",
+                 "info": {"secret": "GEZDGNBVGY3TQOJQ", "algo": "SHA512", "digits": 8, "counter": 12}}
+            ]}
+        }"#;
+        let vault = import_otp_accounts(aegis.to_string()).unwrap();
+        assert_eq!(vault.accounts.len(), 2);
+        assert_eq!(vault.accounts[1].kind, OtpKind::Hotp);
+        assert_eq!(vault.accounts[1].counter, 12);
+        assert!(matches!(
+            vault.accounts[1].params.algorithm,
+            HashAlgorithm::SHA512
+        ));
+
+        let andotp = export_otp_accounts(vault, OtpExportFormat::AndOtp).unwrap();
+        let again = import_otp_accounts(andotp).unwrap().accounts;
+        assert_eq!(again[0].issuer, "GitHub");
+        assert_eq!(again[0].account, "alice");
+        assert_eq!(again[1].params.digits, 8);
+
+        let legacy = r#"[{"secret": "JBSWY3DPEHPK3PXP", "label": "Corp:carol", "digits": 6,
+                          "type": "TOTP", "algorithm": "SHA1", "period": 30}]"#;
+        let legacy = import_otp_accounts(legacy.to_string()).unwrap().accounts;
+        assert_eq!(legacy[0].issuer, "Corp");
+        assert_eq!(legacy[0].account, "carol");
+
+        let encrypted = r#"{"version": 1, "header": {}, "db": "b64..."}"#;
+        assert!(import_otp_accounts(encrypted.to_string()).is_err());
+    }
+}
@@ -0,0 +1,532 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Password-protected store for OTP accounts.
+//!
+//! The vault is a JSON envelope holding Argon2id parameters, a random
+//! salt and nonce, and the account list encrypted with
+//! XChaCha20-Poly1305. The KDF parameters are bound to the ciphertext as
+//! associated data, so they cannot be weakened without breaking the tag.
+
+pub mod import;
+pub use import::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod session;
+#[cfg(not(target_arch = "wasm32"))]
+pub use session::*;
+
+use super::{
+    decode_secret, dynamic_truncate, encode_alphabet, encode_decimal, generate_otp_code,
+    hmac_digest, unix_now, OtpParameters,
+};
+use crate::error::UtilityError;
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use universal_function_macro::universal_function;
+use zeroize::Zeroizing;
+
+const VAULT_FORMAT: &str = "dev-utility-otp-vault";
+const VAULT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum OtpKind {
+    Totp,
+    Hotp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct OtpAccount {
+    pub id: String,             // Stable identifier
+    pub kind: OtpKind,          // Time- or counter-based
+    pub issuer: String,         // Service name
+    pub account: String,        // Account identifier
+    pub secret: String,         // Base32 secret (plain text for mOTP)
+    pub params: OtpParameters,  // Variant, algorithm, digits, period, ...
+    pub counter: u64,           // Next HOTP counter; unused for TOTP
+    pub label: Option<String>,  // Custom label for UI
+    pub created_at: u64,        // Creation timestamp
+    pub last_used: Option<u64>, // Last usage timestamp
+}
+
+impl OtpAccount {
+    pub fn new(
+        kind: OtpKind,
+        issuer: impl Into<String>,
+        account: impl Into<String>,
+        secret: impl Into<String>,
+        params: OtpParameters,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            issuer: issuer.into(),
+            account: account.into(),
+            secret: secret.into(),
+            params,
+            counter: 0,
+            label: None,
+            created_at: unix_now().unwrap_or_default(),
+            last_used: None,
+        }
+    }
+
+    /// Label to show in lists: the custom label, else `issuer (account)`.
+    pub fn display_name(&self) -> String {
+        match (&self.label, self.issuer.is_empty()) {
+            (Some(label), _) if !label.is_empty() => label.clone(),
+            (_, true) => self.account.clone(),
+            (_, false) => format!("{} ({})", self.issuer, self.account),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct OtpVault {
+    pub accounts: Vec<OtpAccount>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct VaultKdfParams {
+    pub memory_kib: u32,  // Argon2 memory cost
+    pub iterations: u32,  // Argon2 time cost
+    pub parallelism: u32, // Argon2 lanes
+}
+
+impl VaultKdfParams {
+    /// Ceiling on what a vault file may ask for. The parameters are read
+    /// before the password is checked, so without it a crafted file could
+    /// make unlocking allocate gigabytes or spin for hours.
+    const MAX: Self = Self {
+        memory_kib: 1024 * 1024,
+        iterations: 64,
+        parallelism: 16,
+    };
+}
+
+impl Default for VaultKdfParams {
+    /// OWASP's baseline Argon2id recommendation.
+    fn default() -> Self {
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct OtpAccountCode {
+    pub id: String,                  // Account id
+    pub code: String,                // Current code, empty on error
+    pub time_remaining: Option<u64>, // Seconds until the TOTP code rolls over
+    pub period: Option<u32>,         // TOTP period
+    pub counter: Option<u64>,        // HOTP counter the code was generated for
+    pub error: Option<String>,       // Why no code could be produced
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct OtpVaultCodes {
+    pub codes: Vec<OtpAccountCode>, // Same order as the vault's accounts
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct HotpAccountUse {
+    pub account: OtpAccount, // Account with the counter advanced
+    pub code: String,        // Code for the new counter value
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VaultFile {
+    format: String,
+    version: u32,
+    kdf: VaultKdf,
+    cipher: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct VaultKdf {
+    algorithm: String,
+    salt: String,
+    #[serde(flatten)]
+    params: VaultKdfParams,
+}
+
+/// A password-derived key together with the KDF settings that produced it,
+/// so the vault can be re-sealed without asking for the password again.
+pub(crate) struct VaultKey {
+    key: Zeroizing<[u8; 32]>,
+    kdf: VaultKdf,
+}
+
+impl VaultKey {
+    pub(crate) fn derive_new(password: &str, params: VaultKdfParams) -> Result<Self, UtilityError> {
+        let mut salt = [0u8; SALT_LEN];
+        random_bytes(&mut salt)?;
+        let kdf = VaultKdf {
+            algorithm: "argon2id".to_string(),
+            salt: BASE64.encode(salt),
+            params,
+        };
+        Self::derive(password, kdf)
+    }
+
+    fn derive(password: &str, kdf: VaultKdf) -> Result<Self, UtilityError> {
+        if kdf.algorithm != "argon2id" {
+            return Err(UtilityError::InvalidInput(format!(
+                "Unsupported vault KDF: {}",
+                kdf.algorithm
+            )));
+        }
+        let max = VaultKdfParams::MAX;
+        if kdf.params.memory_kib > max.memory_kib
+            || kdf.params.iterations > max.iterations
+            || kdf.params.parallelism > max.parallelism
+        {
+            return Err(UtilityError::InvalidInput(format!(
+                "Vault KDF parameters exceed the limit of {} KiB, {} iterations and {} lanes",
+                max.memory_kib, max.iterations, max.parallelism
+            )));
+        }
+        let salt = BASE64
+            .decode(&kdf.salt)
+            .map_err(|e| UtilityError::DecodeError(format!("Invalid vault salt: {}", e)))?;
+        let params = Params::new(
+            kdf.params.memory_kib,
+            kdf.params.iterations,
+            kdf.params.parallelism,
+            Some(32),
+        )
+        .map_err(|e| UtilityError::InvalidInput(format!("Invalid KDF parameters: {}", e)))?;
+
+        let mut key = Zeroizing::new([0u8; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.as_bytes(), &salt, key.as_mut())
+            .map_err(|e| UtilityError::Runtime(format!("Key derivation failed: {}", e)))?;
+        Ok(Self { key, kdf })
+    }
+
+    /// Derive the key for an existing vault file and decrypt it.
+    pub(crate) fn unlock(data: &str, password: &str) -> Result<(Self, OtpVault), UtilityError> {
+        let file: VaultFile = serde_json::from_str(data)
+            .map_err(|e| UtilityError::ParseError(format!("Not a vault file: {}", e)))?;
+        if file.format != VAULT_FORMAT {
+            return Err(UtilityError::ParseError("Not a vault file".to_string()));
+        }
+        if file.version != VAULT_VERSION {
+            return Err(UtilityError::InvalidInput(format!(
+                "Unsupported vault version {}",
+                file.version
+            )));
+        }
+        let key = Self::derive(password, file.kdf.clone())?;
+        let vault = key.open(&file)?;
+        Ok((key, vault))
+    }
+
+    pub(crate) fn seal(&self, vault: &OtpVault) -> Result<String, UtilityError> {
+        let plaintext = Zeroizing::new(
+            serde_json::to_vec(vault).map_err(|e| UtilityError::Runtime(e.to_string()))?,
+        );
+        let mut nonce = [0u8; NONCE_LEN];
+        random_bytes(&mut nonce)?;
+
+        let ciphertext = self
+            .cipher()
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: &self.associated_data()?,
+                },
+            )
+            .map_err(|_| UtilityError::Runtime("Vault encryption failed".to_string()))?;
+
+        let file = VaultFile {
+            format: VAULT_FORMAT.to_string(),
+            version: VAULT_VERSION,
+            kdf: self.kdf.clone(),
+            cipher: "xchacha20poly1305".to_string(),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        };
+        serde_json::to_string_pretty(&file).map_err(|e| UtilityError::Runtime(e.to_string()))
+    }
+
+    fn open(&self, file: &VaultFile) -> Result<OtpVault, UtilityError> {
+        if file.cipher != "xchacha20poly1305" {
+            return Err(UtilityError::InvalidInput(format!(
+                "Unsupported vault cipher: {}",
+                file.cipher
+            )));
+        }
+        let nonce = BASE64
+            .decode(&file.nonce)
+            .ok()
+            .filter(|n| n.len() == NONCE_LEN)
+            .ok_or_else(|| UtilityError::DecodeError("Invalid vault nonce".to_string()))?;
+        let ciphertext = BASE64
+            .decode(&file.ciphertext)
+            .map_err(|e| UtilityError::DecodeError(format!("Invalid vault data: {}", e)))?;
+
+        let plaintext = Zeroizing::new(
+            self.cipher()
+                .decrypt(
+                    XNonce::from_slice(&nonce),
+                    Payload {
+                        msg: &ciphertext,
+                        aad: &self.associated_data()?,
+                    },
+                )
+                .map_err(|_| {
+                    UtilityError::InvalidInput("Wrong password or corrupted vault".to_string())
+                })?,
+        );
+        serde_json::from_slice(&plaintext)
+            .map_err(|e| UtilityError::ParseError(format!("Corrupted vault contents: {}", e)))
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(self.key.as_ref().into())
+    }
+
+    fn associated_data(&self) -> Result<Vec<u8>, UtilityError> {
+        let mut aad = format!("{}/{}/", VAULT_FORMAT, VAULT_VERSION).into_bytes();
+        aad.extend(
+            serde_json::to_vec(&self.kdf).map_err(|e| UtilityError::Runtime(e.to_string()))?,
+        );
+        Ok(aad)
+    }
+}
+
+fn random_bytes(buf: &mut [u8]) -> Result<(), UtilityError> {
+    getrandom::fill(buf).map_err(|e| UtilityError::Runtime(format!("RNG error: {}", e)))
+}
+
+/// Encrypt a vault with a freshly salted, password-derived key.
+#[universal_function]
+pub fn encrypt_otp_vault(
+    vault: OtpVault,
+    password: String,
+    kdf: Option<VaultKdfParams>,
+) -> Result<String, UtilityError> {
+    check_password(&password)?;
+    VaultKey::derive_new(&password, kdf.unwrap_or_default())?.seal(&vault)
+}
+
+/// Decrypt a vault produced by `encrypt_otp_vault`.
+#[universal_function]
+pub fn decrypt_otp_vault(data: String, password: String) -> Result<OtpVault, UtilityError> {
+    VaultKey::unlock(&data, &password).map(|(_, vault)| vault)
+}
+
+/// Current code of every account. Failures are reported per account.
+#[universal_function]
+pub fn otp_vault_codes(vault: OtpVault) -> OtpVaultCodes {
+    OtpVaultCodes {
+        codes: vault.accounts.iter().map(account_code).collect(),
+    }
+}
+
+/// Advance an HOTP account's counter and return the code for the new value.
+#[universal_function]
+pub fn use_hotp_account(account: OtpAccount) -> Result<HotpAccountUse, UtilityError> {
+    let mut account = account;
+    if account.kind != OtpKind::Hotp {
+        return Err(UtilityError::InvalidInput(
+            "Only HOTP accounts have a counter".to_string(),
+        ));
+    }
+    account.counter = account.counter.wrapping_add(1);
+    account.last_used = unix_now().ok();
+    let code = hotp_code(&account)?;
+    Ok(HotpAccountUse { account, code })
+}
+
+pub(crate) fn check_password(password: &str) -> Result<(), UtilityError> {
+    if password.chars().count() < 8 {
+        return Err(UtilityError::InvalidInput(
+            "Master password must be at least 8 characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn account_code(account: &OtpAccount) -> OtpAccountCode {
+    let mut code = OtpAccountCode {
+        id: account.id.clone(),
+        code: String::new(),
+        time_remaining: None,
+        period: None,
+        counter: None,
+        error: None,
+    };
+    let result = match account.kind {
+        OtpKind::Totp => {
+            generate_otp_code(account.secret.clone(), account.params.clone()).map(|r| {
+                code.time_remaining = Some(r.time_remaining);
+                code.period = Some(r.period);
+                r.code
+            })
+        }
+        OtpKind::Hotp => {
+            code.counter = Some(account.counter);
+            hotp_code(account)
+        }
+    };
+    match result {
+        Ok(c) => code.code = c,
+        Err(e) => code.error = Some(e.to_string()),
+    }
+    code
+}
+
+fn hotp_code(account: &OtpAccount) -> Result<String, UtilityError> {
+    let params = &account.params;
+    let key = decode_secret(&account.secret)?;
+    let digest = hmac_digest(&params.algorithm, &key, &account.counter.to_be_bytes())?;
+    let value = dynamic_truncate(&digest) as u64;
+    match &params.alphabet {
+        Some(alphabet) => encode_alphabet(value, params.digits, alphabet),
+        None => encode_decimal(value, params.digits),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Cheap Argon2 settings so tests stay fast.
+    pub(crate) const TEST_KDF: VaultKdfParams = VaultKdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    pub(crate) fn sample_vault() -> OtpVault {
+        let mut hotp = OtpAccount::new(
+            OtpKind::Hotp,
+            "Staging",
+            "qa@example.com",
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ",
+            OtpParameters::default(),
+        );
+        hotp.counter = 1;
+        OtpVault {
+            accounts: vec![
+                OtpAccount::new(
+                    OtpKind::Totp,
+                    "GitHub",
+                    "alice",
+                    "JBSWY3DPEHPK3PXP",
+                    OtpParameters::default(),
+                ),
+                hotp,
+            ],
+        }
+    }
+
+    #[test]
+    fn round_trips_with_password() {
+        let sealed =
+            encrypt_otp_vault(sample_vault(), "correct horse".to_string(), Some(TEST_KDF)).unwrap();
+        assert!(!sealed.contains("JBSWY3DPEHPK3PXP"));
+
+        let opened = decrypt_otp_vault(sealed.clone(), "correct horse".to_string()).unwrap();
+        assert_eq!(opened.accounts.len(), 2);
+        assert_eq!(opened.accounts[0].issuer, "GitHub");
+
+        assert!(decrypt_otp_vault(sealed, "wrong password".to_string()).is_err());
+    }
+
+    #[test]
+    fn tampered_kdf_params_are_rejected() {
+        let sealed =
+            encrypt_otp_vault(sample_vault(), "correct horse".to_string(), Some(TEST_KDF)).unwrap();
+        let tampered = sealed.replace("\"iterations\": 1", "\"iterations\": 2");
+        assert_ne!(sealed, tampered);
+        assert!(decrypt_otp_vault(tampered, "correct horse".to_string()).is_err());
+    }
+
+    #[test]
+    fn oversized_kdf_params_are_rejected() {
+        let sealed =
+            encrypt_otp_vault(sample_vault(), "correct horse".to_string(), Some(TEST_KDF)).unwrap();
+        for (from, to) in [
+            ("\"memoryKib\": 64", "\"memoryKib\": 4294967295"),
+            ("\"iterations\": 1", "\"iterations\": 4294967295"),
+            ("\"parallelism\": 1", "\"parallelism\": 16777215"),
+        ] {
+            let tampered = sealed.replace(from, to);
+            assert_ne!(sealed, tampered);
+            let err = decrypt_otp_vault(tampered, "correct horse".to_string()).unwrap_err();
+            assert!(err.to_string().contains("exceed the limit"), "{}", err);
+        }
+
+        let oversized = VaultKdfParams {
+            iterations: 65,
+            ..TEST_KDF
+        };
+        assert!(
+            encrypt_otp_vault(sample_vault(), "correct horse".to_string(), Some(oversized))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn short_password_is_rejected() {
+        assert!(encrypt_otp_vault(sample_vault(), "short".to_string(), Some(TEST_KDF)).is_err());
+    }
+
+    #[test]
+    fn hotp_counter_advances() {
+        let vault = sample_vault();
+        let codes = otp_vault_codes(vault.clone()).codes;
+        // RFC 4226 Appendix D, counter 1.
+        assert_eq!(codes[1].code, "287082");
+        assert_eq!(codes[1].counter, Some(1));
+        assert!(codes[0].time_remaining.is_some());
+
+        let used = use_hotp_account(vault.accounts[1].clone()).unwrap();
+        assert_eq!(used.account.counter, 2);
+        assert_eq!(used.code, "359152");
+        assert!(used.account.last_used.is_some());
+
+        assert!(use_hotp_account(vault.accounts[0].clone()).is_err());
+    }
+}
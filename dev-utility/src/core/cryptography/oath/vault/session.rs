@@ -0,0 +1,180 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+use super::{
+    account_code, check_password, export_otp_accounts, otp_vault_codes, use_hotp_account,
+    OtpAccount, OtpAccountCode, OtpExportFormat, OtpVault, VaultKdfParams, VaultKey,
+};
+use crate::error::UtilityError;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// An unlocked vault file. The derived key stays in memory (zeroed on
+/// drop) so changes can be saved without re-entering the password.
+pub struct VaultSession {
+    path: PathBuf,
+    key: VaultKey,
+    vault: OtpVault,
+    last_activity: Instant,
+}
+
+impl VaultSession {
+    /// Create a new, empty vault file. Refuses to overwrite an existing one.
+    pub fn create(path: impl Into<PathBuf>, password: &str) -> Result<Self, UtilityError> {
+        Self::create_with_params(path, password, VaultKdfParams::default())
+    }
+
+    pub fn create_with_params(
+        path: impl Into<PathBuf>,
+        password: &str,
+        params: VaultKdfParams,
+    ) -> Result<Self, UtilityError> {
+        let path = path.into();
+        check_password(password)?;
+        if path.exists() {
+            return Err(UtilityError::InvalidInput(format!(
+                "{} already exists",
+                path.display()
+            )));
+        }
+        let session = Self {
+            key: VaultKey::derive_new(password, params)?,
+            path,
+            vault: OtpVault::default(),
+            last_activity: Instant::now(),
+        };
+        session.save()?;
+        Ok(session)
+    }
+
+    pub fn unlock(path: impl Into<PathBuf>, password: &str) -> Result<Self, UtilityError> {
+        let path = path.into();
+        let data = std::fs::read_to_string(&path)?;
+        let (key, vault) = VaultKey::unlock(&data, password)?;
+        Ok(Self {
+            path,
+            key,
+            vault,
+            last_activity: Instant::now(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn accounts(&self) -> &[OtpAccount] {
+        &self.vault.accounts
+    }
+
+    pub fn codes(&self) -> Vec<OtpAccountCode> {
+        otp_vault_codes(self.vault.clone()).codes
+    }
+
+    /// Record user activity, postponing auto-lock.
+    pub fn touch(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    pub fn should_lock(&self, timeout: Duration) -> bool {
+        self.last_activity.elapsed() >= timeout
+    }
+
+    /// Add accounts, skipping any whose secret and name are already stored.
+    /// Returns how many were added.
+    pub fn add_accounts(&mut self, accounts: Vec<OtpAccount>) -> Result<usize, UtilityError> {
+        self.touch();
+        let before = self.vault.accounts.len();
+        for account in accounts {
+            let duplicate = self.vault.accounts.iter().any(|a| {
+                a.secret == account.secret
+                    && a.issuer == account.issuer
+                    && a.account == account.account
+            });
+            if !duplicate {
+                self.vault.accounts.push(account);
+            }
+        }
+        let added = self.vault.accounts.len() - before;
+        if added > 0 {
+            self.save()?;
+        }
+        Ok(added)
+    }
+
+    pub fn remove_account(&mut self, id: &str) -> Result<(), UtilityError> {
+        self.touch();
+        let index = self.index_of(id)?;
+        self.vault.accounts.remove(index);
+        self.save()
+    }
+
+    /// Advance an HOTP account and persist the new counter before returning
+    /// its code, so a crash can never hand out the same code twice.
+    pub fn next_hotp_code(&mut self, id: &str) -> Result<OtpAccountCode, UtilityError> {
+        self.touch();
+        let index = self.index_of(id)?;
+        let used = use_hotp_account(self.vault.accounts[index].clone())?;
+        self.vault.accounts[index] = used.account;
+        self.save()?;
+        Ok(account_code(&self.vault.accounts[index]))
+    }
+
+    pub fn export(&mut self, format: OtpExportFormat) -> Result<String, UtilityError> {
+        self.touch();
+        export_otp_accounts(self.vault.clone(), format)
+    }
+
+    fn index_of(&self, id: &str) -> Result<usize, UtilityError> {
+        self.vault
+            .accounts
+            .iter()
+            .position(|a| a.id == id)
+            .ok_or_else(|| UtilityError::InvalidInput(format!("No account with id {}", id)))
+    }
+
+    /// Re-encrypt with a fresh nonce and atomically replace the file.
+    fn save(&self) -> Result<(), UtilityError> {
+        let sealed = self.key.seal(&self.vault)?;
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, sealed)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{sample_vault, TEST_KDF};
+    use super::*;
+
+    #[test]
+    fn persists_hotp_counter() {
+        let dir = std::env::temp_dir().join(format!("otp-vault-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("vault.json");
+
+        let mut session =
+            VaultSession::create_with_params(&path, "correct horse", TEST_KDF).unwrap();
+        assert!(VaultSession::create_with_params(&path, "correct horse", TEST_KDF).is_err());
+        assert_eq!(session.add_accounts(sample_vault().accounts).unwrap(), 2);
+        assert_eq!(session.add_accounts(sample_vault().accounts).unwrap(), 0);
+
+        let id = session.accounts()[1].id.clone();
+        assert_eq!(session.next_hotp_code(&id).unwrap().code, "359152");
+        drop(session);
+
+        assert!(VaultSession::unlock(&path, "wrong password").is_err());
+        let session = VaultSession::unlock(&path, "correct horse").unwrap();
+        assert_eq!(session.accounts()[1].counter, 2);
+        assert!(!session.should_lock(Duration::from_secs(60)));
+        assert!(session.should_lock(Duration::ZERO));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -11,34 +11,386 @@
 //
 // See LICENSE file for details or contact admin@aprilnea.com
 
-use lightningcss::stylesheet::{ParserOptions, PrinterOptions, StyleSheet};
+use std::sync::{Arc, RwLock};
+
+use lightningcss::css_modules::{self, CssModuleReference};
+use lightningcss::error::ErrorLocation;
+use lightningcss::stylesheet::{MinifyOptions, ParserOptions, PrinterOptions, StyleSheet};
+use lightningcss::targets::{Browsers, Targets};
+use serde::{Deserialize, Serialize};
 use universal_function_macro::universal_function;
 
-use crate::error::UtilityError;
+use super::IndentStyle;
+use crate::error::{line_start, Diagnostic, Severity, UtilityError};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct CssFormatOptions {
+    pub targets: Option<String>, // Browser queries such as "chrome 90, safari >= 14"
+    pub css_modules: bool,       // Hash class names, ids and keyframes as CSS modules do
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct CssModuleClass {
+    pub name: String,          // Name as written in the source
+    pub local: String,         // Hashed name in the output
+    pub composes: Vec<String>, // Names pulled in through `composes`
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct CssFormatResult {
+    pub code: String,
+    pub warnings: Vec<Diagnostic>, // Rules and declarations dropped by error recovery
+    pub exports: Vec<CssModuleClass>, // Sorted by name; empty unless CSS modules are on
+}
 
 /// Format CSS string using Lightning CSS
 ///
+/// With `targets`, the stylesheet is compiled down for those browsers:
+/// vendor prefixes are added and nesting, `color-mix()` and other newer
+/// syntax is lowered. Invalid rules are skipped rather than failing the
+/// whole sheet, and each one is reported in `warnings`.
+///
 /// # Arguments
 /// * `input` - Input CSS string
+/// * `style` - Indentation, or `Minified` to strip white space
+/// * `options` - Targets and CSS modules
 ///
 /// # Returns
-/// * `Result<String, UtilityError>` - Formatted CSS string or error
+/// * `Result<CssFormatResult, UtilityError>` - Formatted CSS and warnings, or error
 #[universal_function]
-pub fn format_css(input: String) -> Result<String, UtilityError> {
-    let stylesheet = StyleSheet::parse(
-        &input,
+pub fn format_css(
+    input: &str,
+    style: IndentStyle,
+    options: Option<CssFormatOptions>,
+) -> Result<CssFormatResult, UtilityError> {
+    let options = options.unwrap_or_default();
+    let targets = match options.targets.as_deref().map(str::trim) {
+        Some(query) if !query.is_empty() => Targets::from(parse_targets(query)?),
+        _ => Targets::default(),
+    };
+    let minify = matches!(style, IndentStyle::Minified);
+
+    let warnings = Arc::new(RwLock::new(Vec::new()));
+    let mut stylesheet = StyleSheet::parse(
+        input,
         ParserOptions {
+            filename: "input.css".to_string(),
+            css_modules: options.css_modules.then(css_modules::Config::default),
             error_recovery: true,
+            warnings: Some(warnings.clone()),
             ..Default::default()
         },
     )
-    .map_err(|e| UtilityError::ParseError(e.to_string()))?;
+    .map_err(|e| css_diagnostic(input, e.kind.to_string(), e.loc.as_ref()))?;
+
+    // Minifying is also the pass that applies target transforms; a plain
+    // beautify keeps rules as written.
+    if minify || targets.browsers.is_some() {
+        stylesheet
+            .minify(MinifyOptions {
+                targets,
+                ..Default::default()
+            })
+            .map_err(|e| css_diagnostic(input, e.kind.to_string(), e.loc.as_ref()))?;
+    }
 
     let result = stylesheet
         .to_css(PrinterOptions {
+            minify,
+            targets,
             ..Default::default()
         })
-        .map_err(|e| UtilityError::ParseError(e.to_string()))?;
+        .map_err(|e| css_diagnostic(input, e.kind.to_string(), e.loc.as_ref()))?;
+
+    let warnings = warnings
+        .read()
+        .map(|warnings| {
+            warnings
+                .iter()
+                .map(|w| {
+                    let offset = w.loc.as_ref().map_or(0, |loc| css_offset(input, loc));
+                    Diagnostic::new(input, offset, w.kind.to_string())
+                        .with_severity(Severity::Warning)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut exports: Vec<CssModuleClass> = result
+        .exports
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, export)| CssModuleClass {
+            name,
+            local: export.name,
+            composes: export
+                .composes
+                .into_iter()
+                .map(|reference| match reference {
+                    CssModuleReference::Local { name } | CssModuleReference::Global { name } => {
+                        name
+                    }
+                    CssModuleReference::Dependency { name, specifier } => {
+                        format!("{} from {}", name, specifier)
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+    exports.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let code = match style {
+        IndentStyle::Spaces(2) | IndentStyle::Minified => result.code,
+        IndentStyle::Spaces(width) => reindent(&result.code, &" ".repeat(width)),
+        IndentStyle::Tabs => reindent(&result.code, "\t"),
+    };
+
+    Ok(CssFormatResult {
+        code,
+        warnings,
+        exports,
+    })
+}
+
+/// Lightning CSS always indents by two spaces per block; swap each level
+/// for `unit`. Braces are counted outside strings and comments, so only the
+/// indent the printer added is replaced: continuation lines of comments keep
+/// their spaces, as does any alignment past the block's indent.
+fn reindent(code: &str, unit: &str) -> String {
+    let mut out = String::with_capacity(code.len());
+    let mut depth = 0usize;
+    let mut in_comment = false;
+    let mut quote = None;
+    for (i, line) in code.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let mut rest = line;
+        if !in_comment && quote.is_none() {
+            let spaces = line.len() - line.trim_start_matches(' ').len();
+            let level = if line[spaces..].starts_with('}') {
+                depth.saturating_sub(1)
+            } else {
+                depth
+            };
+            let levels = spaces.min(2 * level) / 2;
+            out.push_str(&unit.repeat(levels));
+            rest = &line[2 * levels..];
+        }
+        out.push_str(rest);
+
+        let mut bytes = line.bytes().peekable();
+        while let Some(b) = bytes.next() {
+            match (b, quote) {
+                (b'*', None) if in_comment && bytes.peek() == Some(&b'/') => {
+                    bytes.next();
+                    in_comment = false;
+                }
+                (_, None) if in_comment => {}
+                (b'\\', Some(_)) => {
+                    bytes.next();
+                }
+                (_, Some(q)) if b == q => quote = None,
+                (_, Some(_)) => {}
+                (b'/', None) if bytes.peek() == Some(&b'*') => {
+                    bytes.next();
+                    in_comment = true;
+                }
+                (b'"' | b'\'', None) => quote = Some(b),
+                (b'{', None) => depth += 1,
+                (b'}', None) => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+    }
+    out
+}
+
+/// Parse comma-separated `<browser> [>=] <version>` queries into the
+/// lowest version required per browser.
+///
+/// This is the subset of browserslist that maps directly onto Lightning
+/// CSS targets; usage-based queries such as `> 0.5%` need the full
+/// browser database and are rejected.
+fn parse_targets(query: &str) -> Result<Browsers, UtilityError> {
+    let mut browsers = Browsers::default();
+    for entry in query.split([',', '\n']).flat_map(|e| e.split(" or ")) {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let invalid = || {
+            UtilityError::InvalidInput(format!(
+                "Unsupported target \"{}\", expected e.g. \"chrome 90\" or \"safari >= 14\"",
+                entry
+            ))
+        };
+        let (name, version) = entry.split_once(char::is_whitespace).ok_or_else(invalid)?;
+        let version = version.trim().trim_start_matches(">=").trim();
+        let slot = match name.to_ascii_lowercase().as_str() {
+            "android" => &mut browsers.android,
+            "chrome" | "and_chr" => &mut browsers.chrome,
+            "edge" => &mut browsers.edge,
+            "firefox" | "ff" | "and_ff" => &mut browsers.firefox,
+            "ie" | "explorer" => &mut browsers.ie,
+            "ios" | "ios_saf" => &mut browsers.ios_saf,
+            "opera" => &mut browsers.opera,
+            "safari" => &mut browsers.safari,
+            "samsung" => &mut browsers.samsung,
+            _ => return Err(invalid()),
+        };
+        // Lightning CSS packs versions as major << 16 | minor << 8 | patch.
+        let mut parts = version.split('.');
+        let major: u16 = parts
+            .next()
+            .and_then(|p| p.parse().ok())
+            .ok_or_else(invalid)?;
+        let mut next = || match parts.next() {
+            Some(part) => part.parse::<u8>().map_err(|_| invalid()),
+            None => Ok(0),
+        };
+        let (minor, patch) = (next()?, next()?);
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        let encoded = (major as u32) << 16 | (minor as u32) << 8 | patch as u32;
+        *slot = Some(slot.map_or(encoded, |current| current.min(encoded)));
+    }
+    Ok(browsers)
+}
+
+fn css_diagnostic(input: &str, message: String, loc: Option<&ErrorLocation>) -> UtilityError {
+    match loc {
+        Some(loc) => Diagnostic::new(input, css_offset(input, loc), message).into(),
+        None => UtilityError::ParseError(message),
+    }
+}
+
+/// Lightning CSS reports 0-based lines and 1-based columns counted in UTF-16
+/// code units; convert that to a byte offset.
+fn css_offset(input: &str, loc: &ErrorLocation) -> usize {
+    let start = line_start(input, loc.line as usize);
+    let mut units = loc.column.saturating_sub(1) as usize;
+    let mut offset = start;
+    for c in input[start..].chars() {
+        if units == 0 || c == '\n' {
+            break;
+        }
+        units = units.saturating_sub(c.len_utf16());
+        offset += c.len_utf8();
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indents_minifies_and_reports_warnings() {
+        let input = ".a{color:red}\n..b{color:red}\n.c{color:blue}";
+        let result = format_css(input, IndentStyle::Tabs, None).unwrap();
+        assert_eq!(
+            result.code,
+            ".a {\n\tcolor: red;\n}\n\n.c {\n\tcolor: #00f;\n}\n"
+        );
+        assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+        assert_eq!(result.warnings[0].severity, Severity::Warning);
+        assert_eq!(result.warnings[0].line, 2);
+
+        let result = format_css(".a {\n  color: red;\n}\n", IndentStyle::Minified, None).unwrap();
+        assert_eq!(result.code, ".a{color:red}");
+    }
+
+    #[test]
+    fn minifies() {
+        let input = "@media (min-width: 600px) {\n  .a, .b { margin: 0px 0px; color: #ff0000 }\n}";
+        let result = format_css(input, IndentStyle::Minified, None).unwrap();
+        assert_eq!(
+            result.code,
+            "@media (width>=600px){.a,.b{color:red;margin:0}}"
+        );
+        assert!(result.exports.is_empty());
+    }
+
+    #[test]
+    fn compiles_for_targets() {
+        let options = CssFormatOptions {
+            targets: Some("chrome 90, safari >= 12".to_string()),
+            ..Default::default()
+        };
+        let result = format_css(
+            ".card { user-select: none; & .title { color: red } }",
+            IndentStyle::Spaces(2),
+            Some(options),
+        )
+        .unwrap();
+        assert!(
+            result.code.contains("-webkit-user-select: none"),
+            "{}",
+            result.code
+        );
+        assert!(!result.code.contains('&'), "{}", result.code);
+
+        assert!(matches!(
+            parse_targets("> 0.5%"),
+            Err(UtilityError::InvalidInput(_))
+        ));
+        let browsers = parse_targets("safari 15.4, safari 14").unwrap();
+        assert_eq!(browsers.safari, Some(14 << 16));
+        let browsers = parse_targets("chrome 120.1.2").unwrap();
+        assert_eq!(browsers.chrome, Some(120 << 16 | 1 << 8 | 2));
+        for query in [
+            "chrome 70000",
+            "safari 14.256",
+            "firefox 1.2.3.4",
+            "ie -1",
+            "edge",
+        ] {
+            assert!(parse_targets(query).is_err(), "{}", query);
+        }
+    }
+
+    #[test]
+    fn hashes_css_modules() {
+        let options = CssFormatOptions {
+            css_modules: true,
+            ..Default::default()
+        };
+        let result = format_css(
+            ".base { color: red }\n.card { composes: base; }\n.card .title { color: blue }",
+            IndentStyle::Spaces(2),
+            Some(options),
+        )
+        .unwrap();
+        let names: Vec<&str> = result.exports.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["base", "card", "title"]);
+        for export in &result.exports {
+            assert_ne!(export.local, export.name);
+            assert!(result.code.contains(&export.local), "{}", result.code);
+        }
+        assert_eq!(
+            result.exports[1].composes,
+            [result.exports[0].local.clone()]
+        );
+    }
 
-    Ok(result.code)
+    #[test]
+    fn reindents_outside_strings_and_comments() {
+        let input = "/*! {\n   keep } */\n.a{content:\"{\";& .b{content:'}'}}";
+        let result = format_css(input, IndentStyle::Spaces(4), None).unwrap();
+        assert_eq!(
+            result.code,
+            "/*! {\n   keep } */\n.a {\n    content: \"{\";\n\n    & .b {\n        content: \"}\";\n    }\n}\n"
+        );
+    }
 }
@@ -6,234 +6,567 @@
 //
 // See LICENSE file for details or contact admin@aprilnea.com
 
-use crate::error::UtilityError;
+pub(super) mod dom;
+
+use html5ever::tendril::TendrilSink;
+use html5ever::tokenizer::TokenizerOpts;
+use html5ever::{local_name, ns, parse_document, parse_fragment, ParseOpts, QualName};
+use serde::{Deserialize, Serialize};
 use universal_function_macro::universal_function;
 
-/// Reformat HTML with two-space indentation.
+use super::{format_css, format_json, IndentStyle};
+use crate::error::{Diagnostic, Severity, UtilityError};
+use dom::{Dom, Handle, NodeData};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct HtmlFormatOptions {
+    pub print_width: usize,    // Wrap text and attribute lists past this column
+    pub format_embedded: bool, // Format <style> as CSS and JSON <script> blocks
+}
+
+impl Default for HtmlFormatOptions {
+    fn default() -> Self {
+        Self {
+            print_width: 80,
+            format_embedded: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct HtmlFormatResult {
+    pub output: String,
+    pub errors: Vec<Diagnostic>, // Spec parse errors the parser recovered from, as warnings
+}
+
+/// Deeper documents are rejected, since the printers recurse; Chromium's
+/// parser stops nesting elements at twice this depth.
+const MAX_DEPTH: usize = 256;
+
+/// Elements whose content is laid out as text; they stay on the line
+/// they appear in when all of their own content is inline too.
+const INLINE: &[&str] = &[
+    "a", "abbr", "acronym", "b", "bdi", "bdo", "big", "br", "button", "cite", "code", "data",
+    "del", "dfn", "em", "font", "i", "img", "input", "ins", "kbd", "label", "mark", "meter",
+    "object", "output", "picture", "progress", "q", "ruby", "rp", "rt", "s", "samp", "select",
+    "small", "span", "strike", "strong", "sub", "sup", "svg", "time", "tt", "u", "var", "video",
+    "audio", "wbr", "math", "option",
+];
+
+const VOID: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements whose white space is significant and printed untouched.
+const PRESERVE: &[&str] = &["pre", "textarea", "listing", "plaintext"];
+
+/// Elements whose content the parser keeps as unescaped text.
+const RAW_TEXT: &[&str] = &[
+    "script", "style", "xmp", "iframe", "noembed", "noframes", "noscript",
+];
+
+const JSON_SCRIPTS: &[&str] = &["application/ld+json", "application/json", "importmap"];
+
+/// Parse a whole document when the input has `<html>` or a doctype, and a
+/// fragment otherwise; returns the nodes to print at the top level.
+pub(super) fn parse(input: &str) -> (Dom, Vec<Handle>) {
+    let opts = ParseOpts {
+        tokenizer: TokenizerOpts {
+            exact_errors: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let lower = input.to_ascii_lowercase();
+    if lower.contains("<!doctype") || lower.contains("<html") {
+        let dom = parse_document(Dom::default(), opts).one(input);
+        let roots = dom.document.content();
+        (dom, roots)
+    } else {
+        // A <template> context accepts any content, table parts included.
+        let context = QualName::new(None, ns!(html), local_name!("template"));
+        let dom = parse_fragment(Dom::default(), opts, context, Vec::new(), false).one(input);
+        // Fragments are parsed into a synthetic <html> root.
+        let roots = dom
+            .document
+            .content()
+            .first()
+            .map(|root| root.content())
+            .unwrap_or_default();
+        (dom, roots)
+    }
+}
+
+/// Reject trees nested deeper than `MAX_DEPTH`, pointing at the line of
+/// the first element past it.
+pub(super) fn check_depth(input: &str, roots: &[Handle]) -> Result<(), UtilityError> {
+    let mut stack: Vec<(Handle, usize)> = roots.iter().rev().map(|n| (n.clone(), 1)).collect();
+    while let Some((node, depth)) = stack.pop() {
+        if depth > MAX_DEPTH && node.element_name().is_some() {
+            return Err(Diagnostic::at_line_column(
+                input,
+                node.line,
+                1,
+                format!("Elements are nested more than {} levels deep", MAX_DEPTH),
+            )
+            .into());
+        }
+        stack.extend(node.content().into_iter().rev().map(|n| (n, depth + 1)));
+    }
+    Ok(())
+}
+
+/// Parse HTML the way browsers do and print it back formatted.
 ///
-/// Pragmatic single-pass tokenizer. Does not validate markup, does not
-/// resolve entities, does not re-wrap attributes — it just normalizes
-/// whitespace between tags and indents nested elements. Good enough for
-/// human-readable debug output; not a replacement for Prettier.
-#[universal_function(desktop_only)]
-pub fn format_html(input: &str) -> Result<String, UtilityError> {
-    let bytes = input.as_bytes();
-    let mut out = String::new();
-    let mut indent: i32 = 0;
-    let mut pos = 0;
-
-    while pos < bytes.len() {
-        // Skip leading whitespace between tokens.
-        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
-            pos += 1;
+/// Input containing `<html>` or a doctype is treated as a whole document,
+/// so implied `<head>` and `<body>` elements appear in the output; anything
+/// else is formatted as a fragment. `<pre>` and `<textarea>` content is kept
+/// exactly, as are scripts other than JSON. `Minified` collapses white
+/// space and drops comments other than conditional ones.
+#[universal_function]
+pub fn format_html(
+    input: &str,
+    style: IndentStyle,
+    options: Option<HtmlFormatOptions>,
+) -> Result<HtmlFormatResult, UtilityError> {
+    let options = options.unwrap_or_default();
+    let (dom, roots) = parse(input);
+    check_depth(input, &roots)?;
+
+    let mut printer = Printer {
+        input,
+        unit: match style {
+            IndentStyle::Spaces(width) => " ".repeat(width),
+            IndentStyle::Tabs => "\t".to_string(),
+            IndentStyle::Minified => String::new(),
+        },
+        minify: matches!(style, IndentStyle::Minified),
+        style,
+        options,
+        out: String::new(),
+        warnings: Vec::new(),
+    };
+    printer.children(&roots, 0);
+
+    let mut errors: Vec<Diagnostic> = dom
+        .errors
+        .borrow()
+        .iter()
+        .map(|(line, message)| {
+            Diagnostic::at_line_column(input, *line, 1, message.to_string())
+                .with_severity(Severity::Warning)
+        })
+        .collect();
+    errors.append(&mut printer.warnings);
+    errors.sort_by_key(|d| d.offset);
+
+    Ok(HtmlFormatResult {
+        output: printer.out,
+        errors,
+    })
+}
+
+/// A run of text and inline elements collapsed onto one logical line, with
+/// the positions where the source had white space and may be wrapped.
+#[derive(Default)]
+struct Inline {
+    text: String,
+    breaks: Vec<usize>,
+    pending_space: bool,
+}
+
+impl Inline {
+    fn space(&mut self) {
+        if self.pending_space && !self.text.is_empty() {
+            self.breaks.push(self.text.len());
+            self.text.push(' ');
         }
-        if pos >= bytes.len() {
-            break;
+        self.pending_space = false;
+    }
+
+    fn push_text(&mut self, text: &str) {
+        for c in text.chars() {
+            if c.is_ascii_whitespace() {
+                self.pending_space = true;
+            } else {
+                self.space();
+                escape_text(c, &mut self.text);
+            }
         }
+    }
 
-        if bytes[pos] == b'<' {
-            let start = pos;
+    fn push_markup(&mut self, markup: &str) {
+        self.space();
+        self.text.push_str(markup);
+    }
 
-            // --- CDATA section: <![CDATA[ ... ]]> ---
-            if bytes[pos..].starts_with(b"<![CDATA[") {
-                pos += 9; // skip "<![CDATA["
-                loop {
-                    if pos + 3 <= bytes.len() && bytes[pos..pos + 3] == *b"]]>" {
-                        pos += 3;
-                        break;
-                    }
-                    if pos >= bytes.len() {
-                        break;
-                    }
-                    pos += 1;
-                }
-                let cdata = std::str::from_utf8(&bytes[start..pos])
-                    .map_err(|e| UtilityError::ParseError(e.to_string()))?;
-                push_indent(&mut out, indent);
-                out.push_str(cdata);
-                out.push('\n');
-                continue;
+    /// Greedy word wrap into lines of at most `width` characters, only ever
+    /// breaking where the source had white space.
+    fn wrap(&self, width: usize) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut start = 0;
+        for &at in &self.breaks {
+            words.push(&self.text[start..at]);
+            start = at + 1;
+        }
+        words.push(&self.text[start..]);
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut current = String::new();
+        for word in words {
+            if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+                lines.push(std::mem::take(&mut current));
             }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        lines.push(current);
+        lines
+    }
+}
 
-            // --- HTML comment: <!-- ... --> ---
-            if bytes[pos..].starts_with(b"<!--") {
-                pos += 4;
-                loop {
-                    if pos + 3 <= bytes.len() && bytes[pos..pos + 3] == *b"-->" {
-                        pos += 3;
-                        break;
-                    }
-                    if pos >= bytes.len() {
-                        break;
+struct Printer<'a> {
+    input: &'a str,
+    unit: String,
+    minify: bool,
+    style: IndentStyle,
+    options: HtmlFormatOptions,
+    out: String,
+    warnings: Vec<Diagnostic>,
+}
+
+impl Printer<'_> {
+    fn indent_width(&self, depth: usize) -> usize {
+        // Count tabs as four columns when measuring against the width.
+        self.unit.replace('\t', "    ").len() * depth
+    }
+
+    fn line(&mut self, depth: usize, text: &str) {
+        if !self.minify {
+            self.out.push_str(&self.unit.repeat(depth));
+        }
+        self.out.push_str(text);
+        if !self.minify {
+            self.out.push('\n');
+        }
+    }
+
+    fn children(&mut self, children: &[Handle], depth: usize) {
+        let mut run = Inline::default();
+        for child in children {
+            if self.is_inline(child) {
+                self.inline(child, &mut run);
+            } else {
+                self.flush(std::mem::take(&mut run), depth);
+                self.block(child, depth);
+            }
+        }
+        self.flush(run, depth);
+    }
+
+    fn flush(&mut self, run: Inline, depth: usize) {
+        if run.text.is_empty() {
+            return;
+        }
+        if self.minify {
+            self.out.push_str(&run.text);
+            return;
+        }
+        let width = self
+            .options
+            .print_width
+            .saturating_sub(self.indent_width(depth));
+        for line in run.wrap(width) {
+            self.line(depth, &line);
+        }
+    }
+
+    fn is_inline(&self, node: &Handle) -> bool {
+        match &node.data {
+            NodeData::Text(_) | NodeData::Comment(_) => true,
+            NodeData::Element { .. } => {
+                let name = node.element_name().unwrap_or_default();
+                INLINE.contains(&name) && node.content().iter().all(|c| self.is_inline(c))
+            }
+            _ => false,
+        }
+    }
+
+    fn inline(&mut self, node: &Handle, run: &mut Inline) {
+        match &node.data {
+            NodeData::Text(text) => run.push_text(&text.borrow()),
+            // Minifying drops comments, except conditional ones.
+            NodeData::Comment(text) if !self.minify || text.trim_start().starts_with("[if") => {
+                run.push_markup(&format!("<!--{}-->", text));
+            }
+            NodeData::Element { .. } => {
+                run.push_markup(&open_tag(node));
+                let name = node.element_name().unwrap_or_default();
+                if !VOID.contains(&name) {
+                    for child in node.content() {
+                        self.inline(&child, run);
                     }
-                    pos += 1;
+                    run.push_markup(&format!("</{}>", name));
                 }
-                let tag = std::str::from_utf8(&bytes[start..pos])
-                    .map_err(|e| UtilityError::ParseError(e.to_string()))?;
-                push_indent(&mut out, indent);
-                out.push_str(tag);
-                out.push('\n');
-                continue;
             }
+            _ => {}
+        }
+    }
 
-            // --- Regular tag: scan to `>` respecting quoted attribute values ---
-            pos += 1; // skip the opening `<`
-            let mut in_quote: Option<u8> = None;
-            loop {
-                if pos >= bytes.len() {
-                    break;
-                }
-                let b = bytes[pos];
-                match in_quote {
-                    Some(q) if b == q => {
-                        in_quote = None;
-                        pos += 1;
-                    }
-                    Some(_) => {
-                        pos += 1;
-                    }
-                    None => {
-                        if b == b'"' || b == b'\'' {
-                            in_quote = Some(b);
-                            pos += 1;
-                        } else if b == b'>' {
-                            pos += 1;
-                            break;
-                        } else {
-                            pos += 1;
-                        }
+    fn block(&mut self, node: &Handle, depth: usize) {
+        match &node.data {
+            NodeData::Doctype {
+                name,
+                public_id,
+                system_id,
+            } => {
+                let mut doctype = format!("<!DOCTYPE {}", name);
+                if !public_id.is_empty() {
+                    doctype.push_str(&format!(" PUBLIC \"{}\"", public_id));
+                    if !system_id.is_empty() {
+                        doctype.push_str(&format!(" \"{}\"", system_id));
                     }
+                } else if !system_id.is_empty() {
+                    doctype.push_str(&format!(" SYSTEM \"{}\"", system_id));
                 }
+                doctype.push('>');
+                self.line(depth, &doctype);
             }
+            NodeData::Element { .. } => self.element(node, depth),
+            NodeData::Document => self.children(&node.content(), depth),
+            _ => {}
+        }
+    }
+
+    /// The open tag, with one attribute per line when it does not fit.
+    fn open_lines(&self, node: &Handle, depth: usize) -> Vec<(usize, String)> {
+        let single = open_tag(node);
+        let attrs = attributes(node);
+        if self.minify
+            || attrs.len() < 2
+            || self.indent_width(depth) + single.chars().count() <= self.options.print_width
+        {
+            return vec![(depth, single)];
+        }
+        let name = node.element_name().unwrap_or_default();
+        let mut lines = vec![(depth, format!("<{}", name))];
+        lines.extend(attrs.into_iter().map(|attr| (depth + 1, attr)));
+        lines.push((depth, ">".to_string()));
+        lines
+    }
+
+    /// Print the open tag lines, the last one followed by `rest`.
+    fn open_with(&mut self, mut open: Vec<(usize, String)>, rest: &str) {
+        let (depth, last) = open.pop().unwrap_or_default();
+        for (depth, line) in open {
+            self.line(depth, &line);
+        }
+        self.line(depth, &format!("{}{}", last, rest));
+    }
 
-            let tag = std::str::from_utf8(&bytes[start..pos])
-                .map_err(|e| UtilityError::ParseError(e.to_string()))?;
+    fn element(&mut self, node: &Handle, depth: usize) {
+        let name = node.element_name().unwrap_or_default().to_string();
+        let open = self.open_lines(node, depth);
+        let close = format!("</{}>", name);
+        let children = node.content();
 
-            let is_closing = tag.starts_with("</");
-            let is_comment_or_decl = tag.starts_with("<!") || tag.starts_with("<?");
-            let is_self_closing = tag.ends_with("/>")
-                || (!is_comment_or_decl && !is_closing && is_void_element(tag));
+        if VOID.contains(&name.as_str()) {
+            self.open_with(open, "");
+            return;
+        }
 
-            if is_closing {
-                indent = (indent - 1).max(0);
+        if PRESERVE.contains(&name.as_str()) {
+            let mut raw = String::new();
+            for child in &children {
+                serialize(child, &mut raw);
             }
+            // The parser drops one newline right after the open tag.
+            if name != "plaintext" && raw.starts_with('\n') {
+                raw.insert(0, '\n');
+            }
+            self.open_with(open, &format!("{}{}", raw, close));
+            return;
+        }
 
-            push_indent(&mut out, indent);
-            out.push_str(tag);
-            out.push('\n');
-
-            if !is_closing && !is_self_closing && !is_comment_or_decl {
-                indent += 1;
-
-                // --- Raw-text elements: <script> / <style> ---
-                let tag_name = raw_text_tag_name(tag);
-                if let Some(name) = tag_name {
-                    // Scan forward to the matching closing tag (case-insensitive).
-                    let close_pat = format!("</{}>", name);
-                    let close_pat_bytes: Vec<u8> = close_pat.bytes().map(|b| b.to_ascii_lowercase()).collect();
-                    let n = close_pat_bytes.len();
-                    let raw_start = pos;
-                    let mut found_close = false;
-                    while pos < bytes.len() {
-                        // Check for closing tag at current position using byte comparison
-                        // (avoids UTF-8 boundary issues from str::from_utf8).
-                        if pos + n <= bytes.len() {
-                            let matches = bytes[pos..pos + n]
-                                .iter()
-                                .zip(close_pat_bytes.iter())
-                                .all(|(a, b)| a.to_ascii_lowercase() == *b);
-                            if matches {
-                                found_close = true;
-                                break;
+        if RAW_TEXT.contains(&name.as_str()) {
+            let text: String = children
+                .iter()
+                .filter_map(|child| match &child.data {
+                    NodeData::Text(text) => Some(text.borrow().clone()),
+                    _ => None,
+                })
+                .collect();
+            match self.embedded(node, &name, &text) {
+                Some(code) => {
+                    self.open_with(open, "");
+                    for line in code.lines() {
+                        if line.is_empty() {
+                            if !self.minify {
+                                self.out.push('\n');
                             }
-                        }
-                        pos += 1;
-                    }
-                    // Emit the raw interior verbatim (no re-indenting).
-                    let raw_content = std::str::from_utf8(&bytes[raw_start..pos])
-                        .map_err(|e| UtilityError::ParseError(e.to_string()))?;
-                    if !raw_content.is_empty() {
-                        out.push_str(raw_content);
-                        if !raw_content.ends_with('\n') {
-                            out.push('\n');
+                        } else {
+                            self.line(depth + 1, line);
                         }
                     }
-                    if found_close {
-                        // Consume the closing tag.
-                        let close_start = pos;
-                        pos += n;
-                        let close_tag = std::str::from_utf8(&bytes[close_start..pos])
-                            .map_err(|e| UtilityError::ParseError(e.to_string()))?;
-                        indent = (indent - 1).max(0);
-                        push_indent(&mut out, indent);
-                        out.push_str(close_tag);
-                        out.push('\n');
-                    } else {
-                        // No closing tag found (truncated/malformed input): undo the
-                        // indent increment so subsequent tags are not corrupted.
-                        indent = (indent - 1).max(0);
-                    }
-                    continue;
+                    self.line(depth, &close);
+                }
+                None if text.trim().is_empty() => self.open_with(open, &close),
+                None => self.open_with(open, &format!("{}{}", text, close)),
+            }
+            return;
+        }
+
+        if children.iter().all(|child| self.is_inline(child)) {
+            let mut run = Inline::default();
+            for child in &children {
+                self.inline(child, &mut run);
+            }
+            let fits = open.len() == 1
+                && self.indent_width(depth)
+                    + open[0].1.chars().count()
+                    + run.text.chars().count()
+                    + close.len()
+                    <= self.options.print_width;
+            if self.minify || fits || run.text.is_empty() {
+                self.open_with(open, &format!("{}{}", run.text, close));
+                return;
+            }
+            self.open_with(open, "");
+            self.flush(run, depth + 1);
+            self.line(depth, &close);
+            return;
+        }
+
+        self.open_with(open, "");
+        self.children(&children, depth + 1);
+        self.line(depth, &close);
+    }
+
+    /// Formatted `<style>` or JSON `<script>` content, or `None` to print
+    /// the content as written.
+    fn embedded(&mut self, node: &Handle, name: &str, text: &str) -> Option<String> {
+        if !self.options.format_embedded || text.trim().is_empty() {
+            return None;
+        }
+        let style = self.style.clone();
+        let formatted = match name {
+            "style" => format_css(text, style, None).map(|r| r.code),
+            "script" => {
+                let kind = node.attr("type").unwrap_or_default();
+                if !JSON_SCRIPTS.contains(&kind.trim().to_ascii_lowercase().as_str()) {
+                    return None;
                 }
+                format_json(text, style, None)
             }
-        } else {
-            let start = pos;
-            while pos < bytes.len() && bytes[pos] != b'<' {
-                pos += 1;
+            _ => return None,
+        };
+        match formatted {
+            // Never let formatting introduce an end tag into the content.
+            Ok(code) if !code.to_ascii_lowercase().contains(&format!("</{}", name)) => {
+                Some(code.trim_end().to_string())
             }
-            let text = std::str::from_utf8(&bytes[start..pos])
-                .map_err(|e| UtilityError::ParseError(e.to_string()))?;
-            let trimmed = text.trim();
-            if !trimmed.is_empty() {
-                push_indent(&mut out, indent);
-                out.push_str(trimmed);
-                out.push('\n');
+            Ok(_) => None,
+            Err(e) => {
+                self.warnings.push(
+                    Diagnostic::at_line_column(
+                        self.input,
+                        node.line,
+                        1,
+                        format!("<{}> content left as is: {}", name, e),
+                    )
+                    .with_severity(Severity::Warning),
+                );
+                None
             }
         }
     }
+}
 
-    Ok(out)
+fn attributes(node: &Handle) -> Vec<String> {
+    let NodeData::Element { attrs, .. } = &node.data else {
+        return Vec::new();
+    };
+    attrs
+        .borrow()
+        .iter()
+        .map(|attr| {
+            let name = match &attr.name.prefix {
+                Some(prefix) => format!("{}:{}", prefix, attr.name.local),
+                None => attr.name.local.to_string(),
+            };
+            if attr.value.is_empty() {
+                return name;
+            }
+            let mut value = String::new();
+            for c in attr.value.chars() {
+                match c {
+                    '&' => value.push_str("&amp;"),
+                    '"' => value.push_str("&quot;"),
+                    '\u{a0}' => value.push_str("&nbsp;"),
+                    c => value.push(c),
+                }
+            }
+            format!("{}=\"{}\"", name, value)
+        })
+        .collect()
 }
 
-fn push_indent(out: &mut String, indent: i32) {
-    for _ in 0..indent {
-        out.push_str("  ");
+fn open_tag(node: &Handle) -> String {
+    let name = node.element_name().unwrap_or_default();
+    let attrs = attributes(node);
+    if attrs.is_empty() {
+        format!("<{}>", name)
+    } else {
+        format!("<{} {}>", name, attrs.join(" "))
     }
 }
 
-fn is_void_element(tag: &str) -> bool {
-    const VOID: &[&str] = &[
-        "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param",
-        "source", "track", "wbr",
-    ];
-    let inner = tag
-        .trim_start_matches('<')
-        .trim_end_matches('>')
-        .trim_end_matches('/');
-    let name = inner
-        .split(|c: char| c.is_whitespace())
-        .next()
-        .unwrap_or("")
-        .to_ascii_lowercase();
-    VOID.contains(&name.as_str())
+fn escape_text(c: char, out: &mut String) {
+    match c {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '\u{a0}' => out.push_str("&nbsp;"),
+        c => out.push(c),
+    }
 }
 
-/// If `tag` is an opening `<script>` or `<style>` tag, return the lowercase
-/// tag name; otherwise return `None`.
-fn raw_text_tag_name(tag: &str) -> Option<&'static str> {
-    let inner = tag
-        .trim_start_matches('<')
-        .trim_end_matches('>')
-        .trim_end_matches('/');
-    let name = inner
-        .split(|c: char| c.is_whitespace())
-        .next()
-        .unwrap_or("")
-        .to_ascii_lowercase();
-    match name.as_str() {
-        "script" => Some("script"),
-        "style" => Some("style"),
-        _ => None,
+/// Exact serialization for content whose white space matters.
+fn serialize(node: &Handle, out: &mut String) {
+    match &node.data {
+        NodeData::Text(text) => {
+            for c in text.borrow().chars() {
+                escape_text(c, out);
+            }
+        }
+        NodeData::Comment(text) => out.push_str(&format!("<!--{}-->", text)),
+        NodeData::Element { .. } => {
+            let name = node.element_name().unwrap_or_default();
+            out.push_str(&open_tag(node));
+            if VOID.contains(&name) {
+                return;
+            }
+            for child in node.content() {
+                match &child.data {
+                    NodeData::Text(text) if RAW_TEXT.contains(&name) => {
+                        out.push_str(&text.borrow())
+                    }
+                    _ => serialize(&child, out),
+                }
+            }
+            out.push_str(&format!("</{}>", name));
+        }
+        _ => {}
     }
 }
 
@@ -241,10 +574,14 @@ fn raw_text_tag_name(tag: &str) -> Option<&'static str> {
 mod tests {
     use super::*;
 
+    fn format_html(input: &str) -> Result<String, UtilityError> {
+        super::format_html(input, IndentStyle::Spaces(2), None).map(|r| r.output)
+    }
+
     #[test]
     fn indents_nested_elements() {
-        let input = "<div><p>hello</p></div>";
-        let expected = "<div>\n  <p>\n    hello\n  </p>\n</div>\n";
+        let input = "<div><p>hello</p><p>a <b>bold</b> word</p></div>";
+        let expected = "<div>\n  <p>hello</p>\n  <p>a <b>bold</b> word</p>\n</div>\n";
         assert_eq!(format_html(input).unwrap(), expected);
     }
 
@@ -263,124 +600,179 @@ mod tests {
         assert!(out.contains("<!-- hi -->"));
     }
 
-    // Bug fix 1: quoted attribute values containing `>`
     #[test]
     fn quoted_attribute_with_gt() {
         let input = r#"<div><input value=">"></div>"#;
         let out = format_html(input).unwrap();
-        // The input tag must be preserved intact, not split at the `>` inside quotes.
         assert!(out.contains(r#"<input value=">">"#));
-        // And the closing div must still appear.
         assert!(out.contains("</div>"));
     }
 
     #[test]
-    fn single_quoted_attribute_with_gt() {
-        let input = "<div><input value='>'></div>";
+    fn rejects_deep_nesting() {
+        for tag in ["<div>", "<b>", "<pre>"] {
+            assert!(format_html(&tag.repeat(MAX_DEPTH)).is_ok(), "{}", tag);
+        }
+        let error = |input: String| {
+            let err = format_html(&input).unwrap_err();
+            let d = err.diagnostic().cloned().expect("diagnostic");
+            (d.line, d.message)
+        };
+        let (line, message) = error("<div>\n".repeat(1000));
+        assert_eq!(line, MAX_DEPTH + 1);
+        assert_eq!(message, "Elements are nested more than 256 levels deep");
+        assert!(error("<!DOCTYPE html>".to_string() + &"<b>".repeat(1000))
+            .1
+            .contains("256"));
+    }
+
+    #[test]
+    fn single_quoted_attribute_is_requoted() {
+        let input = "<div><input value='>' title='say \"hi\"'></div>";
         let out = format_html(input).unwrap();
-        assert!(out.contains("<input value='>'>"));
+        assert!(out.contains(r#"<input value=">" title="say &quot;hi&quot;">"#));
         assert!(out.contains("</div>"));
     }
 
-    // Bug fix 2: HTML comments containing `>`
     #[test]
     fn comment_with_gt_inside() {
         let input = "<!-- x > y --><p>ok</p>";
         let out = format_html(input).unwrap();
         assert!(out.contains("<!-- x > y -->"));
-        assert!(out.contains("<p>"));
-        assert!(out.contains("ok"));
+        assert!(out.contains("<p>ok</p>"));
     }
 
-    // Bug fix 3: <script> and <style> raw-text content is emitted verbatim
     #[test]
     fn script_raw_text_not_reindented() {
         let input = "<html><head><script>if(a>b){alert(1);}</script></head></html>";
         let out = format_html(input).unwrap();
-        // Raw content must appear verbatim (not split on `>`).
-        assert!(out.contains("if(a>b){alert(1);}"));
-        // Closing tags must still be present and correctly indented.
-        assert!(out.contains("</script>"));
+        assert!(out.contains("<script>if(a>b){alert(1);}</script>"));
         assert!(out.contains("</head>"));
         assert!(out.contains("</html>"));
     }
 
     #[test]
-    fn style_raw_text_not_reindented() {
-        let input = "<html><head><style>a>b{color:red}</style></head></html>";
+    fn formats_embedded_css_and_json_ld() {
+        let input = concat!(
+            "<html><head><style>a>b{color:red}</style>",
+            r#"<script type="application/ld+json">{"@type":"Person","name":"x"}</script>"#,
+            "</head></html>"
+        );
         let out = format_html(input).unwrap();
-        assert!(out.contains("a>b{color:red}"));
-        assert!(out.contains("</style>"));
+        assert!(
+            out.contains(
+                "    <style>\n      a > b {\n        color: red;\n      }\n    </style>\n"
+            ),
+            "{out}"
+        );
+        assert!(
+            out.contains("      {\n        \"@type\": \"Person\",\n        \"name\": \"x\"\n      }\n    </script>\n"),
+            "{out}"
+        );
+
+        let options = HtmlFormatOptions {
+            format_embedded: false,
+            ..Default::default()
+        };
+        let out = super::format_html(input, IndentStyle::Spaces(2), Some(options))
+            .unwrap()
+            .output;
+        assert!(out.contains("<style>a>b{color:red}</style>"), "{out}");
     }
 
-    // Fix 1: unclosed <script> must not leak indent into subsequent tags
     #[test]
     fn unclosed_script_does_not_leak_indent() {
-        // No closing </script> — truncated input.
         let input = "<html><body><script>alert(1)";
         let out = format_html(input).unwrap();
-        // </body> and </html> are absent, but the emitted lines must not be
-        // over-indented.  The opening <html> must be at indent 0.
-        let first_line = out.lines().next().unwrap_or("");
-        assert_eq!(first_line, "<html>", "first tag must be at indent 0");
-        // No line should start with more than 4 leading spaces
-        // (i.e. indent must not have leaked beyond the expected nesting depth).
-        for line in out.lines() {
-            let leading = line.len() - line.trim_start_matches(' ').len();
-            assert!(
-                leading <= 4,
-                "unexpected over-indentation ({} spaces) in: {:?}",
-                leading,
-                line
-            );
-        }
+        assert_eq!(
+            out,
+            "<html>\n  <head></head>\n  <body>\n    <script>alert(1)</script>\n  </body>\n</html>\n"
+        );
     }
 
-    // Fix 2: CDATA block must be emitted verbatim, not re-indented or split
     #[test]
-    fn cdata_preserved() {
-        let input = "<root><![CDATA[hello > world & <tag>]]></root>";
+    fn preserves_whitespace_sensitive_elements() {
+        let input = "<div><pre>\n\n  a  <b> b </b>\n</pre><textarea>  x\n y</textarea></div>";
         let out = format_html(input).unwrap();
-        assert!(
-            out.contains("<![CDATA[hello > world & <tag>]]>"),
-            "CDATA block must appear verbatim; got:\n{out}"
+        assert_eq!(
+            out,
+            "<div>\n  <pre>\n\n  a  <b> b </b>\n</pre>\n  <textarea>  x\n y</textarea>\n</div>\n"
         );
-        assert!(out.contains("</root>"));
     }
 
-    // Fix 4: CDATA closing delimiter exactly at end of input must not be missed
     #[test]
-    fn cdata_close_at_end_of_input() {
-        // Minimum well-formed CDATA block: closing ]]> is the very last 3 bytes.
-        let input = "<![CDATA[x]]>";
-        let out = format_html(input).unwrap();
-        assert!(
-            out.contains("<![CDATA[x]]>"),
-            "CDATA block must be emitted intact when ]]> is at end of input; got:\n{out}"
+    fn wraps_long_text_and_attribute_lists() {
+        let options = HtmlFormatOptions {
+            print_width: 30,
+            ..Default::default()
+        };
+        let input = r#"<p>one two three four five six seven eight</p><nav class="links primary" id="site-nav">go</nav>"#;
+        let out = super::format_html(input, IndentStyle::Spaces(2), Some(options))
+            .unwrap()
+            .output;
+        assert_eq!(
+            out,
+            "<p>\n  one two three four five six\n  seven eight\n</p>\n\
+             <nav\n  class=\"links primary\"\n  id=\"site-nav\"\n>\n  go\n</nav>\n"
         );
     }
 
-    // Fix 3: UTF-8 multibyte characters inside raw text must not break close-tag detection
     #[test]
-    fn raw_text_close_at_utf8_boundary() {
-        // The raw content contains multibyte UTF-8 (Japanese).
-        let input = "<html><head><script>var s = \"日本語\"; </script></head></html>";
-        let out = format_html(input).unwrap();
+    fn minifies() {
+        let input = "<ul>\n  <li> a  <em>b</em> </li>\n  <!-- note -->\n  <li>c</li>\n</ul>\n<pre> x </pre>";
+        let out = super::format_html(input, IndentStyle::Minified, None)
+            .unwrap()
+            .output;
+        assert_eq!(
+            out,
+            "<ul><li>a <em>b</em></li><li>c</li></ul><pre> x </pre>"
+        );
+    }
+
+    #[test]
+    fn cdata_outside_foreign_content_is_a_comment() {
+        let result =
+            super::format_html("<root><![CDATA[x]]></root>", IndentStyle::Spaces(2), None).unwrap();
         assert!(
-            out.contains("var s = \"日本語\";"),
-            "multibyte content must be preserved; got:\n{out}"
+            result.output.contains("<!--[CDATA[x]]-->"),
+            "{}",
+            result.output
         );
+        assert!(!result.errors.is_empty());
+
+        let out = format_html("<svg><![CDATA[a < b]]></svg>").unwrap();
+        assert_eq!(out, "<svg>a &lt; b</svg>\n");
+    }
+
+    #[test]
+    fn reports_parse_errors_with_lines() {
+        let input = "<!DOCTYPE html>\n<p>ok</p>\n<p>bad</span>";
+        let result = super::format_html(input, IndentStyle::Spaces(2), None).unwrap();
         assert!(
-            out.contains("</script>"),
-            "close script tag must be found; got:\n{out}"
+            result
+                .errors
+                .iter()
+                .all(|d| d.severity == Severity::Warning),
+            "{:?}",
+            result.errors
         );
         assert!(
-            out.contains("</head>"),
-            "subsequent tags must still appear; got:\n{out}"
+            result.errors.iter().any(|d| d.line == 3),
+            "{:?}",
+            result.errors
         );
+        assert!(result.output.contains("<p>bad</p>"), "{}", result.output);
+    }
+
+    #[test]
+    fn raw_text_close_at_utf8_boundary() {
+        let input = "<html><head><script>var s = \"日本語\"; </script></head></html>";
+        let out = format_html(input).unwrap();
         assert!(
-            out.contains("</html>"),
-            "document close must appear; got:\n{out}"
+            out.contains("<script>var s = \"日本語\"; </script>"),
+            "multibyte content must be preserved; got:\n{out}"
         );
+        assert!(out.contains("</head>"));
+        assert!(out.contains("</html>"));
     }
 }
@@ -0,0 +1,335 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Minimal reference-counted DOM that html5ever builds into.
+//!
+//! `markup5ever_rcdom` tracks an older html5ever, so the formatter keeps
+//! its own sink. Each node remembers the input line it was created on and
+//! the sink collects parse errors with their lines.
+
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+use html5ever::interface::{ElementFlags, NodeOrText, QuirksMode, TreeSink};
+use html5ever::tendril::StrTendril;
+use html5ever::{Attribute, ExpandedName, QualName};
+
+pub(crate) type Handle = Rc<Node>;
+
+pub(crate) enum NodeData {
+    Document,
+    Doctype {
+        name: String,
+        public_id: String,
+        system_id: String,
+    },
+    Text(RefCell<String>),
+    Comment(String),
+    Element {
+        name: QualName,
+        attrs: RefCell<Vec<Attribute>>,
+        template_contents: Option<Handle>,
+    },
+    ProcessingInstruction,
+}
+
+pub(crate) struct Node {
+    pub data: NodeData,
+    pub line: usize,
+    parent: Cell<Option<Weak<Node>>>,
+    pub children: RefCell<Vec<Handle>>,
+}
+
+impl Node {
+    fn new(data: NodeData, line: usize) -> Handle {
+        Rc::new(Node {
+            data,
+            line,
+            parent: Cell::new(None),
+            children: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// Lowercase local name for elements, `None` for everything else.
+    pub fn element_name(&self) -> Option<&str> {
+        match &self.data {
+            NodeData::Element { name, .. } => Some(&name.local),
+            _ => None,
+        }
+    }
+
+    pub fn attr(&self, key: &str) -> Option<String> {
+        match &self.data {
+            NodeData::Element { attrs, .. } => attrs
+                .borrow()
+                .iter()
+                .find(|a| &*a.name.local == key)
+                .map(|a| a.value.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Children as the formatter should see them: a template's content
+    /// fragment stands in for its (always empty) child list.
+    pub fn content(&self) -> Vec<Handle> {
+        match &self.data {
+            NodeData::Element {
+                template_contents: Some(contents),
+                ..
+            } => contents.children.borrow().clone(),
+            _ => self.children.borrow().clone(),
+        }
+    }
+}
+
+/// Drops the subtree iteratively, so deep documents don't overflow the
+/// stack on the way out.
+impl Drop for Node {
+    fn drop(&mut self) {
+        let mut nodes = std::mem::take(self.children.get_mut());
+        while let Some(node) = nodes.pop() {
+            // Nodes still held elsewhere keep their subtree.
+            if Rc::strong_count(&node) > 1 {
+                continue;
+            }
+            nodes.append(&mut node.children.borrow_mut());
+            if let NodeData::Element {
+                template_contents: Some(contents),
+                ..
+            } = &node.data
+            {
+                nodes.push(contents.clone());
+            }
+        }
+    }
+}
+
+fn take_parent(node: &Handle) -> Option<(Handle, usize)> {
+    let parent = node.parent.take()?.upgrade()?;
+    let index = parent
+        .children
+        .borrow()
+        .iter()
+        .position(|child| Rc::ptr_eq(child, node))?;
+    Some((parent, index))
+}
+
+fn append(parent: &Handle, child: Handle) {
+    if let Some((old, index)) = take_parent(&child) {
+        old.children.borrow_mut().remove(index);
+    }
+    child.parent.set(Some(Rc::downgrade(parent)));
+    parent.children.borrow_mut().push(child);
+}
+
+pub(crate) struct Dom {
+    pub document: Handle,
+    pub errors: RefCell<Vec<(usize, Cow<'static, str>)>>,
+    line: Cell<usize>,
+}
+
+impl Default for Dom {
+    fn default() -> Self {
+        Self {
+            document: Node::new(NodeData::Document, 1),
+            errors: RefCell::new(Vec::new()),
+            line: Cell::new(1),
+        }
+    }
+}
+
+impl Dom {
+    fn text(&self, text: StrTendril) -> Handle {
+        Node::new(
+            NodeData::Text(RefCell::new(text.to_string())),
+            self.line.get(),
+        )
+    }
+}
+
+impl TreeSink for Dom {
+    type Handle = Handle;
+    type Output = Self;
+    type ElemName<'a> = ExpandedName<'a>;
+
+    fn finish(self) -> Self {
+        self
+    }
+
+    fn parse_error(&self, msg: Cow<'static, str>) {
+        self.errors.borrow_mut().push((self.line.get(), msg));
+    }
+
+    fn set_current_line(&self, line_number: u64) {
+        self.line.set(line_number as usize);
+    }
+
+    fn get_document(&self) -> Handle {
+        self.document.clone()
+    }
+
+    fn elem_name<'a>(&'a self, target: &'a Handle) -> ExpandedName<'a> {
+        match &target.data {
+            NodeData::Element { name, .. } => name.expanded(),
+            _ => panic!("not an element"),
+        }
+    }
+
+    fn create_element(&self, name: QualName, attrs: Vec<Attribute>, flags: ElementFlags) -> Handle {
+        let template_contents = flags
+            .template
+            .then(|| Node::new(NodeData::Document, self.line.get()));
+        Node::new(
+            NodeData::Element {
+                name,
+                attrs: RefCell::new(attrs),
+                template_contents,
+            },
+            self.line.get(),
+        )
+    }
+
+    fn create_comment(&self, text: StrTendril) -> Handle {
+        Node::new(NodeData::Comment(text.to_string()), self.line.get())
+    }
+
+    fn create_pi(&self, _target: StrTendril, _data: StrTendril) -> Handle {
+        Node::new(NodeData::ProcessingInstruction, self.line.get())
+    }
+
+    fn append(&self, parent: &Handle, child: NodeOrText<Handle>) {
+        match child {
+            NodeOrText::AppendNode(node) => append(parent, node),
+            NodeOrText::AppendText(text) => {
+                if let Some(last) = parent.children.borrow().last() {
+                    if let NodeData::Text(existing) = &last.data {
+                        existing.borrow_mut().push_str(&text);
+                        return;
+                    }
+                }
+                append(parent, self.text(text));
+            }
+        }
+    }
+
+    fn append_based_on_parent_node(
+        &self,
+        element: &Handle,
+        prev_element: &Handle,
+        child: NodeOrText<Handle>,
+    ) {
+        let has_parent = element
+            .parent
+            .take()
+            .inspect(|parent| element.parent.set(Some(parent.clone())))
+            .is_some();
+        if has_parent {
+            self.append_before_sibling(element, child);
+        } else {
+            self.append(prev_element, child);
+        }
+    }
+
+    fn append_doctype_to_document(
+        &self,
+        name: StrTendril,
+        public_id: StrTendril,
+        system_id: StrTendril,
+    ) {
+        let doctype = Node::new(
+            NodeData::Doctype {
+                name: name.to_string(),
+                public_id: public_id.to_string(),
+                system_id: system_id.to_string(),
+            },
+            self.line.get(),
+        );
+        append(&self.document, doctype);
+    }
+
+    fn get_template_contents(&self, target: &Handle) -> Handle {
+        match &target.data {
+            NodeData::Element {
+                template_contents: Some(contents),
+                ..
+            } => contents.clone(),
+            _ => panic!("not a template element"),
+        }
+    }
+
+    fn same_node(&self, x: &Handle, y: &Handle) -> bool {
+        Rc::ptr_eq(x, y)
+    }
+
+    fn set_quirks_mode(&self, _mode: QuirksMode) {}
+
+    fn append_before_sibling(&self, sibling: &Handle, child: NodeOrText<Handle>) {
+        let Some((parent, index)) = take_parent(sibling) else {
+            return;
+        };
+        sibling.parent.set(Some(Rc::downgrade(&parent)));
+        let node = match child {
+            NodeOrText::AppendText(text) => {
+                if let Some(previous) = index
+                    .checked_sub(1)
+                    .and_then(|i| parent.children.borrow().get(i).cloned())
+                {
+                    if let NodeData::Text(existing) = &previous.data {
+                        existing.borrow_mut().push_str(&text);
+                        return;
+                    }
+                }
+                self.text(text)
+            }
+            NodeOrText::AppendNode(node) => {
+                if let Some((old, old_index)) = take_parent(&node) {
+                    old.children.borrow_mut().remove(old_index);
+                }
+                node
+            }
+        };
+        // Removing the node above may have shifted the sibling.
+        let index = parent
+            .children
+            .borrow()
+            .iter()
+            .position(|child| Rc::ptr_eq(child, sibling))
+            .unwrap_or(index);
+        node.parent.set(Some(Rc::downgrade(&parent)));
+        parent.children.borrow_mut().insert(index, node);
+    }
+
+    fn add_attrs_if_missing(&self, target: &Handle, attrs: Vec<Attribute>) {
+        if let NodeData::Element {
+            attrs: existing, ..
+        } = &target.data
+        {
+            let mut existing = existing.borrow_mut();
+            for attr in attrs {
+                if !existing.iter().any(|a| a.name == attr.name) {
+                    existing.push(attr);
+                }
+            }
+        }
+    }
+
+    fn remove_from_parent(&self, target: &Handle) {
+        if let Some((parent, index)) = take_parent(target) {
+            parent.children.borrow_mut().remove(index);
+        }
+    }
+
+    fn reparent_children(&self, node: &Handle, new_parent: &Handle) {
+        let children = std::mem::take(&mut *node.children.borrow_mut());
+        for child in children {
+            child.parent.set(Some(Rc::downgrade(new_parent)));
+            new_parent.children.borrow_mut().push(child);
+        }
+    }
+}
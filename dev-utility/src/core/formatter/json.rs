@@ -11,14 +11,17 @@
 //
 // See LICENSE file for details or contact admin@aprilnea.com
 
-#[cfg(target_arch = "wasm32")]
-use serde_json::Value as JsonValue;
-#[cfg(not(target_arch = "wasm32"))]
-use sonic_rs::Value as JsonValue;
+mod diff;
+mod printer;
+mod repair;
 
-use crate::error::UtilityError;
+pub use diff::*;
+pub use repair::*;
+
+use crate::error::{strip_location, Diagnostic, UtilityError};
+use printer::Printer;
 use serde::{Deserialize, Serialize};
-use sonic_rs;
+use std::io::{BufRead, BufReader, BufWriter, Write};
 use universal_function_macro::universal_function;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,73 +34,212 @@ pub enum IndentStyle {
     Minified,
 }
 
-fn process_spaces_indentation(json: String, size: usize) -> String {
-    json.lines()
-        .map(|line| {
-            if size == 2 {
-                return line.to_string();
-            }
-            let leading_spaces = line.len() - line.trim_start().len();
-            let indent_level = leading_spaces / 2;
-            format!("{}{}", " ".repeat(indent_level * size), line.trim_start())
-        })
-        .collect::<Vec<_>>()
-        .join("\n")
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct JsonFormatOptions {
+    pub sort_keys: bool,        // Sort object keys at every level
+    pub compact_arrays: bool,   // Keep arrays of scalars on one line
+    pub ascii: bool,            // Escape non-ASCII characters as \uXXXX
+    pub trailing_newline: bool, // End the output with a newline
+}
+
+/// Fix hints for the parse failures people hit most.
+pub(crate) fn hint_for(message: &str) -> Option<&'static str> {
+    if message.contains("trailing comma") {
+        Some("Remove the comma before the closing bracket")
+    } else if message.contains("key must be a string") {
+        Some("Object keys must be wrapped in double quotes")
+    } else if message.contains("EOF while parsing") {
+        Some("The input ends early; check for a missing closing bracket or quote")
+    } else if message.contains("trailing characters") {
+        Some("Only one top-level value is allowed")
+    } else {
+        None
+    }
 }
 
-fn process_tabs_indentation(json: String) -> String {
-    json.lines()
-        .map(|line| {
-            let leading_spaces = line.len() - line.trim_start().len();
-            let indent_level = leading_spaces / 2;
-            format!("{}{}", "\t".repeat(indent_level), line.trim_start())
-        })
-        .collect::<Vec<_>>()
-        .join("\n")
+/// Locate a `serde_json` parse failure and attach a fix hint.
+pub(crate) fn json_diagnostic(
+    input: &str,
+    message: &str,
+    line: usize,
+    column: usize,
+) -> UtilityError {
+    let message = strip_location(message);
+    let diagnostic = Diagnostic::at_line_column(input, line, column, message);
+    match hint_for(message) {
+        Some(hint) => diagnostic.with_hint(hint).into(),
+        None => diagnostic.into(),
+    }
 }
 
+/// Re-print JSON in `style` without building a document tree.
+///
+/// Key order, number spelling and string escapes are kept exactly as
+/// written unless `options` asks otherwise.
 #[universal_function]
-pub fn format_json(input: &str, style: IndentStyle) -> Result<String, UtilityError> {
-    #[cfg(not(target_arch = "wasm32"))]
-    let value: JsonValue =
-        sonic_rs::from_str(input).map_err(|e| UtilityError::ParseError(e.to_string()))?;
+pub fn format_json(
+    input: &str,
+    style: IndentStyle,
+    options: Option<JsonFormatOptions>,
+) -> Result<String, UtilityError> {
+    let mut out = Vec::with_capacity(input.len() + input.len() / 4);
+    format_json_stream(
+        input.as_bytes(),
+        &mut out,
+        &style,
+        &options.unwrap_or_default(),
+    )?;
+    // Strings are validated as UTF-8 and everything else emitted is ASCII.
+    Ok(String::from_utf8(out).expect("printer emits UTF-8"))
+}
 
-    #[cfg(target_arch = "wasm32")]
-    let value: JsonValue =
-        serde_json::from_str(input).map_err(|e| UtilityError::ParseError(e.to_string()))?;
+/// Stream JSON from `reader` to `writer`. Memory use is bounded by the
+/// nesting depth, except for objects being sorted and arrays of scalars
+/// being compacted, which are held until they close.
+pub fn format_json_stream<R: BufRead, W: Write>(
+    reader: R,
+    writer: W,
+    style: &IndentStyle,
+    options: &JsonFormatOptions,
+) -> Result<(), UtilityError> {
+    Printer::new(reader, writer, style, options).print()
+}
 
-    match style {
-        IndentStyle::Minified => {
-            #[cfg(not(target_arch = "wasm32"))]
-            let result = sonic_rs::to_string(&value).unwrap_or_else(|_| "{}".to_string());
+/// Format a JSON file into `destination`, which may be the source itself.
+///
+/// Deliberately not a command: it writes to any path it is given, so only
+/// native callers that chose the paths themselves should reach it.
+pub fn format_json_file(
+    source: String,
+    destination: String,
+    style: IndentStyle,
+    options: Option<JsonFormatOptions>,
+) -> Result<(), UtilityError> {
+    let staging = format!("{}.tmp", destination);
+    let result = std::fs::File::open(&source)
+        .and_then(|input| Ok((input, std::fs::File::create(&staging)?)))
+        .map_err(UtilityError::from)
+        .and_then(|(input, output)| {
+            format_json_stream(
+                BufReader::with_capacity(1 << 16, input),
+                BufWriter::with_capacity(1 << 16, output),
+                &style,
+                &options.unwrap_or_default(),
+            )
+        });
+    match result {
+        Ok(()) => Ok(std::fs::rename(&staging, &destination)?),
+        Err(e) => {
+            let _ = std::fs::remove_file(&staging);
+            Err(e)
+        }
+    }
+}
 
-            #[cfg(target_arch = "wasm32")]
-            let result = serde_json::to_string(&value).unwrap_or_else(|_| "{}".to_string());
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            Ok(result)
-        }
-        IndentStyle::Spaces(size) => {
-            #[cfg(not(target_arch = "wasm32"))]
-            let pretty_json =
-                sonic_rs::to_string_pretty(&value).unwrap_or_else(|_| "{}".to_string());
+    const SAMPLE: &str = r#"{"z": 1, "a": {"y": [1, 2.50, 1e400], "x": "é\u00e9"},
+        "big": 123456789012345678901234567890, "list": [{"k": null}, true]}"#;
 
-            #[cfg(target_arch = "wasm32")]
-            let pretty_json =
-                serde_json::to_string_pretty(&value).unwrap_or_else(|_| "{}".to_string());
+    fn options(f: impl FnOnce(&mut JsonFormatOptions)) -> Option<JsonFormatOptions> {
+        let mut options = JsonFormatOptions::default();
+        f(&mut options);
+        Some(options)
+    }
 
-            Ok(process_spaces_indentation(pretty_json, size))
-        }
-        IndentStyle::Tabs => {
-            #[cfg(not(target_arch = "wasm32"))]
-            let pretty_json =
-                sonic_rs::to_string_pretty(&value).unwrap_or_else(|_| "{}".to_string());
+    #[test]
+    fn keeps_order_numbers_and_escapes() {
+        assert_eq!(
+            format_json(SAMPLE, IndentStyle::Minified, None).unwrap(),
+            r#"{"z":1,"a":{"y":[1,2.50,1e400],"x":"é\u00e9"},"big":123456789012345678901234567890,"list":[{"k":null},true]}"#
+        );
+        assert_eq!(
+            format_json(r#"{"a":[1,{}],"b":[]}"#, IndentStyle::Spaces(4), None).unwrap(),
+            "{\n    \"a\": [\n        1,\n        {}\n    ],\n    \"b\": []\n}"
+        );
+        assert_eq!(
+            format_json("[[1]]", IndentStyle::Tabs, None).unwrap(),
+            "[\n\t[\n\t\t1\n\t]\n]"
+        );
+    }
+
+    #[test]
+    fn sorts_compacts_and_escapes() {
+        let sorted = format_json(
+            SAMPLE,
+            IndentStyle::Minified,
+            options(|o| {
+                o.sort_keys = true;
+                o.ascii = true;
+                o.trailing_newline = true;
+            }),
+        )
+        .unwrap();
+        assert_eq!(
+            sorted,
+            "{\"a\":{\"x\":\"\\u00e9\\u00e9\",\"y\":[1,2.50,1e400]},\"big\":123456789012345678901234567890,\"list\":[{\"k\":null},true],\"z\":1}\n"
+        );
 
-            #[cfg(target_arch = "wasm32")]
-            let pretty_json =
-                serde_json::to_string_pretty(&value).unwrap_or_else(|_| "{}".to_string());
+        let compact = format_json(
+            r#"{"a":[1,"x",null],"b":[1,[2]]}"#,
+            IndentStyle::Spaces(2),
+            options(|o| o.compact_arrays = true),
+        )
+        .unwrap();
+        assert_eq!(
+            compact,
+            "{\n  \"a\": [1, \"x\", null],\n  \"b\": [\n    1,\n    [2]\n  ]\n}"
+        );
+    }
 
-            Ok(process_tabs_indentation(pretty_json))
+    #[test]
+    fn rejects_invalid_documents() {
+        for (input, message) in [
+            ("", "EOF while parsing a value"),
+            ("[1 2]", "expected ',' or ']'"),
+            ("{a:1}", "key must be a string"),
+            ("01", "trailing characters"),
+            ("[1.]", "invalid number"),
+            ("\"\\x\"", "invalid escape"),
+            ("[tru]", "expected value"),
+        ] {
+            let err = format_json(input, IndentStyle::Minified, None).unwrap_err();
+            assert_eq!(err.diagnostic().unwrap().message, message, "{}", input);
         }
+        let deep = "[".repeat(1000);
+        assert!(format_json(&deep, IndentStyle::Minified, None).is_err());
+    }
+
+    #[test]
+    fn formats_files_in_place() {
+        let path = std::env::temp_dir().join("dev-utility-format-json-file.json");
+        let path = path.to_string_lossy().to_string();
+        std::fs::write(&path, r#"{"b":1,"a":2}"#).unwrap();
+        format_json_file(
+            path.clone(),
+            path.clone(),
+            IndentStyle::Spaces(2),
+            options(|o| o.sort_keys = true),
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\n  \"a\": 2,\n  \"b\": 1\n}"
+        );
+        std::fs::remove_file(&path).unwrap();
     }
-}
 
+    #[test]
+    fn parse_errors_carry_location_and_hint() {
+        let err = format_json("{\n  \"a\": 1,\n}", IndentStyle::Minified, None).unwrap_err();
+        let diagnostic = err.diagnostic().expect("located error");
+        assert_eq!((diagnostic.line, diagnostic.column), (3, 1));
+        assert_eq!(diagnostic.message, "trailing comma");
+        assert!(diagnostic.hint.is_some());
+    }
+}
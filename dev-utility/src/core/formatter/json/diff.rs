@@ -0,0 +1,724 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Structural diff between two JSON documents, with RFC 6902 (JSON Patch)
+//! and RFC 7386 (JSON Merge Patch) output and application.
+
+use super::{format_json, json_diagnostic, IndentStyle};
+use crate::error::UtilityError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use universal_function_macro::universal_function;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct JsonDiffOptions {
+    pub array_key: Option<String>, // Match array items (objects) by this field instead of by index
+    pub ignore_order: bool,        // Treat arrays as unordered; reorderings are not changes
+    pub ignore_paths: Vec<String>, // JSON Pointers to skip; a `*` segment matches anything
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum JsonChangeKind {
+    Added,
+    Removed,
+    Changed,
+    Moved, // Keyed array item at a new position
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct JsonChange {
+    pub kind: JsonChangeKind,
+    pub path: String, // JSON Pointer, as used by the matching patch operation
+    pub from: Option<String>, // Previous pointer of a moved item
+    pub left: Option<String>, // Old value as compact JSON
+    pub right: Option<String>, // New value as compact JSON
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct JsonDiffResult {
+    pub changes: Vec<JsonChange>, // Empty when the documents are equivalent
+    pub json_patch: String,       // RFC 6902 operations turning left into right
+    pub merge_patch: String,      // RFC 7386 document turning left into right
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum PatchFormat {
+    JsonPatch,  // RFC 6902 array of operations
+    MergePatch, // RFC 7386 partial document
+}
+
+/// Compare two JSON documents.
+///
+/// Objects are compared by key and numbers by value (`1` equals `1.0`).
+/// Arrays are compared by index unless `array_key` names a field every
+/// item carries, in which case items are paired by that field and
+/// reorderings show up as moves; `ignore_order` pairs equal items wherever
+/// they are. Changes inside a paired item use its left-hand index.
+///
+/// Merge patches cannot express a `null` value or a partial array change,
+/// so arrays are replaced whole and a `null` on the right deletes the key.
+#[universal_function]
+pub fn diff_json(
+    left: &str,
+    right: &str,
+    options: Option<JsonDiffOptions>,
+) -> Result<JsonDiffResult, UtilityError> {
+    let left_value = parse(left, "Left document")?;
+    let right_value = parse(right, "Right document")?;
+    let options = options.unwrap_or_default();
+    let ignore = options
+        .ignore_paths
+        .iter()
+        .map(|path| path.trim())
+        .filter(|path| !path.is_empty())
+        .map(parse_pointer)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut differ = Differ::new(&options, &ignore);
+    differ.walk(&left_value, &right_value, &mut Vec::new());
+    let merge_patch = match differ.merge_patch(&left_value, &right_value, &mut Vec::new()) {
+        Some(patch) => patch,
+        None if right_value.is_object() => Value::Object(Map::new()),
+        // Only an object patch can leave a document alone; any other
+        // patch value replaces the target.
+        None => right_value.clone(),
+    };
+
+    Ok(JsonDiffResult {
+        changes: differ.changes,
+        json_patch: pretty(&Value::Array(differ.ops))?,
+        merge_patch: pretty(&merge_patch)?,
+    })
+}
+
+/// Apply a JSON Patch or Merge Patch to `document` and print the result.
+///
+/// JSON Patch operations run in order and the whole patch fails on the
+/// first operation that does not apply, including a failed `test`.
+#[universal_function]
+pub fn apply_json_patch(
+    document: &str,
+    patch: &str,
+    format: PatchFormat,
+    style: IndentStyle,
+) -> Result<String, UtilityError> {
+    let mut target = parse(document, "Document")?;
+    let patch: Value = serde_json::from_str(patch)
+        .map_err(|e| UtilityError::ParseError(format!("Invalid patch: {}", e)))?;
+
+    match format {
+        PatchFormat::JsonPatch => {
+            let operations = patch.as_array().ok_or_else(|| {
+                UtilityError::InvalidInput("A JSON Patch must be an array".to_string())
+            })?;
+            for (i, operation) in operations.iter().enumerate() {
+                apply_operation(&mut target, operation).map_err(|reason| {
+                    let op = operation.get("op").and_then(Value::as_str).unwrap_or("?");
+                    UtilityError::InvalidInput(format!(
+                        "Patch operation {} ({}) failed: {}",
+                        i, op, reason
+                    ))
+                })?;
+            }
+        }
+        PatchFormat::MergePatch => merge(&mut target, &patch),
+    }
+
+    format_json(&target.to_string(), style, None)
+}
+
+fn parse(input: &str, name: &str) -> Result<Value, UtilityError> {
+    serde_json::from_str(input).map_err(|e| {
+        let message = format!("{}: {}", name, e);
+        json_diagnostic(input, &message, e.line(), e.column())
+    })
+}
+
+fn pretty(value: &Value) -> Result<String, UtilityError> {
+    serde_json::to_string_pretty(value).map_err(|e| UtilityError::Runtime(e.to_string()))
+}
+
+fn compact(value: &Value) -> String {
+    value.to_string()
+}
+
+fn parse_pointer(pointer: &str) -> Result<Vec<String>, UtilityError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(UtilityError::InvalidInput(format!(
+            "'{}' is not a JSON Pointer; it must start with '/'",
+            pointer
+        )));
+    };
+    Ok(rest
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+fn to_pointer(path: &[String]) -> String {
+    path.iter()
+        .map(|token| format!("/{}", token.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+struct Differ<'a> {
+    options: &'a JsonDiffOptions,
+    ignore: &'a [Vec<String>],
+    changes: Vec<JsonChange>,
+    ops: Vec<Value>,
+}
+
+impl<'a> Differ<'a> {
+    fn new(options: &'a JsonDiffOptions, ignore: &'a [Vec<String>]) -> Self {
+        Differ {
+            options,
+            ignore,
+            changes: Vec::new(),
+            ops: Vec::new(),
+        }
+    }
+
+    fn ignored(&self, path: &[String]) -> bool {
+        self.ignore.iter().any(|pattern| {
+            pattern.len() == path.len()
+                && pattern
+                    .iter()
+                    .zip(path)
+                    .all(|(want, got)| want == "*" || want == got)
+        })
+    }
+
+    /// Whether two values compare equal under the current options.
+    fn same(&self, left: &Value, right: &Value, path: &[String]) -> bool {
+        let mut probe = Differ::new(self.options, self.ignore);
+        probe.walk(left, right, &mut path.to_vec());
+        probe.changes.is_empty()
+    }
+
+    fn record(
+        &mut self,
+        kind: JsonChangeKind,
+        path: &[String],
+        left: Option<&Value>,
+        right: Option<&Value>,
+    ) {
+        let pointer = to_pointer(path);
+        self.changes.push(JsonChange {
+            kind,
+            path: pointer,
+            from: None,
+            left: left.map(compact),
+            right: right.map(compact),
+        });
+    }
+
+    fn walk(&mut self, left: &Value, right: &Value, path: &mut Vec<String>) {
+        if self.ignored(path) {
+            return;
+        }
+        match (left, right) {
+            (Value::Object(l), Value::Object(r)) => {
+                for (key, lv) in l {
+                    path.push(key.clone());
+                    match r.get(key) {
+                        Some(rv) => self.walk(lv, rv, path),
+                        None if !self.ignored(path) => {
+                            self.record(JsonChangeKind::Removed, path, Some(lv), None);
+                            self.ops
+                                .push(json!({"op": "remove", "path": to_pointer(path)}));
+                        }
+                        None => {}
+                    }
+                    path.pop();
+                }
+                for (key, rv) in r {
+                    if l.contains_key(key) {
+                        continue;
+                    }
+                    path.push(key.clone());
+                    if !self.ignored(path) {
+                        self.record(JsonChangeKind::Added, path, None, Some(rv));
+                        self.ops
+                            .push(json!({"op": "add", "path": to_pointer(path), "value": rv}));
+                    }
+                    path.pop();
+                }
+            }
+            (Value::Array(l), Value::Array(r)) => self.walk_array(l, r, path),
+            (Value::Number(l), Value::Number(r))
+                if l == r || ((l.is_f64() || r.is_f64()) && l.as_f64() == r.as_f64()) => {}
+            _ if left == right => {}
+            _ => {
+                self.record(JsonChangeKind::Changed, path, Some(left), Some(right));
+                self.ops
+                    .push(json!({"op": "replace", "path": to_pointer(path), "value": right}));
+            }
+        }
+    }
+
+    fn walk_array(&mut self, left: &[Value], right: &[Value], path: &mut Vec<String>) {
+        let keyed = self
+            .options
+            .array_key
+            .as_deref()
+            .filter(|key| left.iter().chain(right).all(|item| item.get(key).is_some()));
+        if keyed.is_none() && !self.options.ignore_order {
+            self.walk_by_index(left, right, path);
+            return;
+        }
+
+        // Pair every right item with the first unused left item that has
+        // the same key (or is equal, without a key).
+        let mut used = vec![false; left.len()];
+        let mut pairs: Vec<Option<usize>> = Vec::with_capacity(right.len());
+        for (j, item) in right.iter().enumerate() {
+            path.push(j.to_string());
+            let found = (0..left.len()).find(|&i| {
+                !used[i]
+                    && match keyed {
+                        Some(key) => left[i].get(key) == item.get(key),
+                        None => self.same(&left[i], item, path),
+                    }
+            });
+            path.pop();
+            if let Some(i) = found {
+                used[i] = true;
+            }
+            pairs.push(found);
+        }
+
+        for i in pairs.iter().flatten().copied().collect::<Vec<_>>() {
+            let j = pairs.iter().position(|p| *p == Some(i)).expect("paired");
+            path.push(i.to_string());
+            self.walk(&left[i], &right[j], path);
+            path.pop();
+        }
+        for i in (0..left.len()).rev().filter(|&i| !used[i]) {
+            path.push(i.to_string());
+            if !self.ignored(path) {
+                self.record(JsonChangeKind::Removed, path, Some(&left[i]), None);
+                self.ops
+                    .push(json!({"op": "remove", "path": to_pointer(path)}));
+            }
+            path.pop();
+        }
+        for (j, item) in right
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| pairs[*j].is_none())
+        {
+            path.push(j.to_string());
+            if !self.ignored(path) {
+                self.record(JsonChangeKind::Added, path, None, Some(item));
+                path.pop();
+                path.push("-".to_string());
+                self.ops
+                    .push(json!({"op": "add", "path": to_pointer(path), "value": item}));
+            }
+            path.pop();
+        }
+        if self.options.ignore_order {
+            return;
+        }
+
+        // The patched array now holds the paired items in left order
+        // followed by the added ones; move each into its right-hand slot.
+        let mut current: Vec<usize> = left
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| used[*i])
+            .map(|(i, _)| pairs.iter().position(|p| *p == Some(i)).expect("paired"))
+            .chain((0..right.len()).filter(|j| pairs[*j].is_none()))
+            .collect();
+        for target in 0..current.len() {
+            let position = current
+                .iter()
+                .position(|&j| j == target)
+                .expect("every right item is present");
+            if position == target {
+                continue;
+            }
+            let j = current.remove(position);
+            current.insert(target, j);
+            path.push(position.to_string());
+            let from = to_pointer(path);
+            path.pop();
+            path.push(target.to_string());
+            let to = to_pointer(path);
+            path.pop();
+            self.changes.push(JsonChange {
+                kind: JsonChangeKind::Moved,
+                path: to.clone(),
+                from: Some(from.clone()),
+                left: None,
+                right: Some(compact(&right[j])),
+            });
+            self.ops
+                .push(json!({"op": "move", "from": from, "path": to}));
+        }
+    }
+
+    fn walk_by_index(&mut self, left: &[Value], right: &[Value], path: &mut Vec<String>) {
+        let common = left.len().min(right.len());
+        for i in 0..common {
+            path.push(i.to_string());
+            self.walk(&left[i], &right[i], path);
+            path.pop();
+        }
+        for (j, item) in right.iter().enumerate().skip(common) {
+            path.push(j.to_string());
+            if !self.ignored(path) {
+                self.record(JsonChangeKind::Added, path, None, Some(item));
+                self.ops
+                    .push(json!({"op": "add", "path": to_pointer(path), "value": item}));
+            }
+            path.pop();
+        }
+        // Remove from the end so earlier indices stay valid.
+        for i in (common..left.len()).rev() {
+            path.push(i.to_string());
+            if !self.ignored(path) {
+                self.record(JsonChangeKind::Removed, path, Some(&left[i]), None);
+                self.ops
+                    .push(json!({"op": "remove", "path": to_pointer(path)}));
+            }
+            path.pop();
+        }
+    }
+
+    /// The RFC 7386 patch for one value, or `None` when nothing changed.
+    fn merge_patch(&self, left: &Value, right: &Value, path: &mut Vec<String>) -> Option<Value> {
+        if self.ignored(path) {
+            return None;
+        }
+        let (Value::Object(l), Value::Object(r)) = (left, right) else {
+            return (!self.same(left, right, path)).then(|| right.clone());
+        };
+        let mut patch = Map::new();
+        for (key, lv) in l {
+            path.push(key.clone());
+            if !self.ignored(path) {
+                match r.get(key) {
+                    Some(rv) => {
+                        if let Some(child) = self.merge_patch(lv, rv, path) {
+                            patch.insert(key.clone(), child);
+                        }
+                    }
+                    None => {
+                        patch.insert(key.clone(), Value::Null);
+                    }
+                }
+            }
+            path.pop();
+        }
+        for (key, rv) in r {
+            path.push(key.clone());
+            if !l.contains_key(key) && !self.ignored(path) {
+                patch.insert(key.clone(), rv.clone());
+            }
+            path.pop();
+        }
+        (!patch.is_empty()).then_some(Value::Object(patch))
+    }
+}
+
+fn merge(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(map) = target else {
+        unreachable!("target was just made an object");
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            map.remove(key);
+        } else {
+            merge(map.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+fn apply_operation(document: &mut Value, operation: &Value) -> Result<(), String> {
+    let field = |name: &str| {
+        operation
+            .get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("missing \"{}\"", name))
+    };
+    let value = || {
+        operation
+            .get("value")
+            .cloned()
+            .ok_or_else(|| "missing \"value\"".to_string())
+    };
+    let path = parse_pointer(field("path")?).map_err(|e| e.to_string())?;
+
+    match field("op")? {
+        "add" => add(document, &path, value()?),
+        "remove" => remove(document, &path).map(drop),
+        "replace" => {
+            *resolve(document, &path)? = value()?;
+            Ok(())
+        }
+        "move" => {
+            let from = parse_pointer(field("from")?).map_err(|e| e.to_string())?;
+            if path.len() > from.len() && path.starts_with(&from) {
+                return Err("cannot move a value into itself".to_string());
+            }
+            let moved = remove(document, &from)?;
+            add(document, &path, moved)
+        }
+        "copy" => {
+            let from = parse_pointer(field("from")?).map_err(|e| e.to_string())?;
+            let copied = resolve(document, &from)?.clone();
+            add(document, &path, copied)
+        }
+        "test" => {
+            if *resolve(document, &path)? == value()? {
+                Ok(())
+            } else {
+                Err(format!("value at {} differs", to_pointer(&path)))
+            }
+        }
+        other => Err(format!("unknown op \"{}\"", other)),
+    }
+}
+
+fn resolve<'v>(document: &'v mut Value, path: &[String]) -> Result<&'v mut Value, String> {
+    let mut node = document;
+    for (depth, token) in path.iter().enumerate() {
+        let missing = || format!("{} does not exist", to_pointer(&path[..=depth]));
+        node = match node {
+            Value::Object(map) => map.get_mut(token).ok_or_else(missing)?,
+            Value::Array(items) => {
+                let index = array_index(token, items.len())?;
+                items.get_mut(index).ok_or_else(missing)?
+            }
+            _ => return Err(missing()),
+        };
+    }
+    Ok(node)
+}
+
+fn array_index(token: &str, len: usize) -> Result<usize, String> {
+    if token == "-" {
+        return Ok(len);
+    }
+    if (token.len() > 1 && token.starts_with('0')) || token.starts_with('+') {
+        return Err(format!("'{}' is not an array index", token));
+    }
+    token
+        .parse()
+        .map_err(|_| format!("'{}' is not an array index", token))
+}
+
+fn add(document: &mut Value, path: &[String], value: Value) -> Result<(), String> {
+    let Some((last, parent)) = path.split_last() else {
+        *document = value;
+        return Ok(());
+    };
+    match resolve(document, parent)? {
+        Value::Object(map) => {
+            map.insert(last.clone(), value);
+            Ok(())
+        }
+        Value::Array(items) => {
+            let index = array_index(last, items.len())?;
+            if index > items.len() {
+                return Err(format!("index {} is out of bounds", index));
+            }
+            items.insert(index, value);
+            Ok(())
+        }
+        _ => Err(format!("{} is not a container", to_pointer(parent))),
+    }
+}
+
+fn remove(document: &mut Value, path: &[String]) -> Result<Value, String> {
+    let Some((last, parent)) = path.split_last() else {
+        return Err("cannot remove the whole document".to_string());
+    };
+    let missing = || format!("{} does not exist", to_pointer(path));
+    match resolve(document, parent)? {
+        Value::Object(map) => map.shift_remove(last).ok_or_else(missing),
+        Value::Array(items) => {
+            let index = array_index(last, items.len())?;
+            if index >= items.len() {
+                return Err(missing());
+            }
+            Ok(items.remove(index))
+        }
+        _ => Err(missing()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(left: &str, patch: &str, format: PatchFormat) -> Value {
+        let out = apply_json_patch(left, patch, format, IndentStyle::Minified).unwrap();
+        serde_json::from_str(&out).unwrap()
+    }
+
+    fn round_trips(left: &str, right: &str, options: Option<JsonDiffOptions>) -> JsonDiffResult {
+        let diff = diff_json(left, right, options).unwrap();
+        let patched = apply_json_patch(
+            left,
+            &diff.json_patch,
+            PatchFormat::JsonPatch,
+            IndentStyle::Minified,
+        )
+        .unwrap();
+        let rest = diff_json(&patched, right, None).unwrap();
+        assert!(rest.changes.is_empty(), "{} -> {}", patched, right);
+        diff
+    }
+
+    #[test]
+    fn reports_paths_and_round_trips_patches() {
+        let left = r#"{"name":"a","tags":["x","y","z"],"n":1,"meta":{"old":true,"a/b":1}}"#;
+        let right = r#"{"name":"b","tags":["x","w"],"n":1.0,"meta":{"a/b":2,"new":null}}"#;
+        let diff = round_trips(left, right, None);
+        let summary: Vec<(JsonChangeKind, &str)> = diff
+            .changes
+            .iter()
+            .map(|c| (c.kind, c.path.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (JsonChangeKind::Changed, "/name"),
+                (JsonChangeKind::Changed, "/tags/1"),
+                (JsonChangeKind::Removed, "/tags/2"),
+                (JsonChangeKind::Removed, "/meta/old"),
+                (JsonChangeKind::Changed, "/meta/a~1b"),
+                (JsonChangeKind::Added, "/meta/new"),
+            ]
+        );
+        assert_eq!(diff.changes[0].left.as_deref(), Some("\"a\""));
+
+        let merge: Value = serde_json::from_str(&diff.merge_patch).unwrap();
+        assert_eq!(
+            merge,
+            json!({"name":"b","tags":["x","w"],"meta":{"old":null,"a/b":2,"new":null}})
+        );
+        // The null for "new" deletes it, as RFC 7386 specifies.
+        assert_eq!(
+            apply(left, &diff.merge_patch, PatchFormat::MergePatch),
+            json!({"name":"b","tags":["x","w"],"n":1,"meta":{"a/b":2}})
+        );
+    }
+
+    #[test]
+    fn matches_arrays_by_key_and_ignores_order() {
+        let left = r#"[{"id":1,"v":"a"},{"id":2,"v":"b"},{"id":3,"v":"c"}]"#;
+        let right = r#"[{"id":3,"v":"c"},{"id":1,"v":"A"},{"id":4,"v":"d"}]"#;
+        let keyed = Some(JsonDiffOptions {
+            array_key: Some("id".to_string()),
+            ..Default::default()
+        });
+        let diff = round_trips(left, right, keyed);
+        let kinds: Vec<JsonChangeKind> = diff.changes.iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                JsonChangeKind::Changed,
+                JsonChangeKind::Removed,
+                JsonChangeKind::Added,
+                JsonChangeKind::Moved,
+            ]
+        );
+        assert_eq!(diff.changes[0].path, "/0/v");
+        assert_eq!(diff.changes[3].from.as_deref(), Some("/1"));
+
+        let unordered = Some(JsonDiffOptions {
+            ignore_order: true,
+            ..Default::default()
+        });
+        let diff = diff_json("[1,[2,3],4]", "[4,[3,2],1]", unordered.clone()).unwrap();
+        assert!(diff.changes.is_empty());
+        assert_eq!(diff.json_patch, "[]");
+        let diff = diff_json("[1,2,2]", "[2,1,5]", unordered).unwrap();
+        assert_eq!(diff.changes.len(), 2);
+    }
+
+    #[test]
+    fn skips_ignored_paths() {
+        let options = Some(JsonDiffOptions {
+            ignore_paths: vec!["/items/*/updated".to_string(), "/etag".to_string()],
+            ..Default::default()
+        });
+        let left = r#"{"etag":"1","items":[{"updated":1,"v":1},{"updated":2,"v":2}]}"#;
+        let right = r#"{"etag":"2","items":[{"updated":9,"v":1},{"v":3}]}"#;
+        let diff = diff_json(left, right, options).unwrap();
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].path, "/items/1/v");
+        assert!(!diff.merge_patch.contains("etag"));
+
+        let bad = Some(JsonDiffOptions {
+            ignore_paths: vec!["items".to_string()],
+            ..Default::default()
+        });
+        assert!(diff_json("{}", "{}", bad).is_err());
+        assert_eq!(diff_json("1", "1", None).unwrap().merge_patch, "1");
+    }
+
+    #[test]
+    fn applies_rfc6902_operations() {
+        let doc = r#"{"a":{"b":[1,2]},"c":3}"#;
+        let patch = r#"[
+            {"op":"add","path":"/a/b/1","value":9},
+            {"op":"copy","from":"/c","path":"/a/d"},
+            {"op":"move","from":"/a/b/0","path":"/e"},
+            {"op":"test","path":"/e","value":1},
+            {"op":"remove","path":"/c"},
+            {"op":"replace","path":"/a/b/-1","value":0}
+        ]"#;
+        let err = apply_json_patch(doc, patch, PatchFormat::JsonPatch, IndentStyle::Minified)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("operation 5 (replace)"), "{}", err);
+
+        let patch = patch.replace("/a/b/-1", "/a/b/1");
+        assert_eq!(
+            apply(doc, &patch, PatchFormat::JsonPatch),
+            json!({"a":{"b":[9,0],"d":3},"e":1})
+        );
+        let failed = r#"[{"op":"test","path":"/c","value":4}]"#;
+        assert!(
+            apply_json_patch(doc, failed, PatchFormat::JsonPatch, IndentStyle::Minified).is_err()
+        );
+
+        let err = diff_json("{}", "{", None).unwrap_err();
+        assert!(err.to_string().starts_with("Parse error: Right document"));
+        assert!(err.diagnostic().is_some());
+    }
+}
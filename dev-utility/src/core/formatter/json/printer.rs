@@ -0,0 +1,478 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Single-pass JSON re-printer.
+//!
+//! Tokens are validated and copied from the reader straight to the writer:
+//! numbers and string escapes are kept byte for byte and no document tree
+//! is built. Only sorted objects and candidate compact arrays are buffered,
+//! and only for the container being printed.
+
+use super::{hint_for, IndentStyle, JsonFormatOptions};
+use crate::error::{Diagnostic, Severity, UtilityError};
+use std::io::{BufRead, Write};
+
+/// Deeper documents are rejected rather than risking the stack; this
+/// matches `serde_json`'s recursion limit.
+const MAX_DEPTH: usize = 128;
+
+pub(super) struct Printer<'a, R, W> {
+    input: R,
+    out: W,
+    style: &'a IndentStyle,
+    options: &'a JsonFormatOptions,
+    // Output of containers that cannot be written until they close
+    buffers: Vec<Vec<u8>>,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a, R: BufRead, W: Write> Printer<'a, R, W> {
+    pub(super) fn new(
+        input: R,
+        out: W,
+        style: &'a IndentStyle,
+        options: &'a JsonFormatOptions,
+    ) -> Self {
+        Self {
+            input,
+            out,
+            style,
+            options,
+            buffers: Vec::new(),
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    pub(super) fn print(mut self) -> Result<(), UtilityError> {
+        self.skip_whitespace()?;
+        self.value(0)?;
+        self.skip_whitespace()?;
+        if self.peek()?.is_some() {
+            return Err(self.error("trailing characters"));
+        }
+        if self.options.trailing_newline {
+            self.emit(b"\n")?;
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+
+    fn value(&mut self, depth: usize) -> Result<(), UtilityError> {
+        match self.peek()? {
+            Some(b'{') => self.object(depth),
+            Some(b'[') => self.array(depth),
+            Some(b'"') => {
+                let raw = self.string()?;
+                self.emit_string(&raw)
+            }
+            Some(b'-' | b'0'..=b'9') => {
+                let raw = self.number()?;
+                self.emit(&raw)
+            }
+            Some(b't') => self.literal(b"true"),
+            Some(b'f') => self.literal(b"false"),
+            Some(b'n') => self.literal(b"null"),
+            Some(_) => Err(self.error("expected value")),
+            None => Err(self.error("EOF while parsing a value")),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<(), UtilityError> {
+        if depth >= MAX_DEPTH {
+            return Err(self.error("recursion limit exceeded"));
+        }
+        self.bump()?;
+        self.skip_whitespace()?;
+        if self.peek()? == Some(b'}') {
+            self.bump()?;
+            return self.emit(b"{}");
+        }
+
+        // Sorted objects hold `(decoded key, raw key, printed value)` until
+        // the closing brace.
+        let mut members = Vec::new();
+        let mut first = true;
+        loop {
+            match self.peek()? {
+                Some(b'"') => {}
+                Some(_) => return Err(self.error("key must be a string")),
+                None => return Err(self.error("EOF while parsing an object")),
+            }
+            let key = self.string()?;
+            self.skip_whitespace()?;
+            if self.peek()? != Some(b':') {
+                return Err(self.error("expected ':'"));
+            }
+            self.bump()?;
+            self.skip_whitespace()?;
+
+            if self.options.sort_keys {
+                self.buffers.push(Vec::new());
+                let printed = self.value(depth + 1);
+                let value = self.buffers.pop().expect("pushed above");
+                printed?;
+                members.push((decode_key(&key), key, value));
+            } else {
+                self.emit(if first { b"{" } else { b"," })?;
+                self.newline(depth + 1)?;
+                self.emit_string(&key)?;
+                self.emit(self.colon())?;
+                self.value(depth + 1)?;
+            }
+            first = false;
+
+            self.skip_whitespace()?;
+            match self.peek()? {
+                Some(b',') => {
+                    self.bump()?;
+                    self.skip_whitespace()?;
+                    if self.peek()? == Some(b'}') {
+                        return Err(self.error("trailing comma"));
+                    }
+                }
+                Some(b'}') => {
+                    self.bump()?;
+                    break;
+                }
+                Some(_) => return Err(self.error("expected ',' or '}'")),
+                None => return Err(self.error("EOF while parsing an object")),
+            }
+        }
+
+        if self.options.sort_keys {
+            members.sort_by(|a, b| a.0.cmp(&b.0));
+            for (i, (_, key, value)) in members.iter().enumerate() {
+                self.emit(if i == 0 { b"{" } else { b"," })?;
+                self.newline(depth + 1)?;
+                self.emit_string(key)?;
+                self.emit(self.colon())?;
+                self.emit(value)?;
+            }
+        }
+        self.newline(depth)?;
+        self.emit(b"}")
+    }
+
+    fn array(&mut self, depth: usize) -> Result<(), UtilityError> {
+        if depth >= MAX_DEPTH {
+            return Err(self.error("recursion limit exceeded"));
+        }
+        self.bump()?;
+        self.skip_whitespace()?;
+        if self.peek()? == Some(b']') {
+            self.bump()?;
+            return self.emit(b"[]");
+        }
+
+        // While every element so far is a scalar the array may still fit on
+        // one line, so the elements are held back until that is decided.
+        let mut pending: Option<Vec<Vec<u8>>> =
+            (self.options.compact_arrays && self.is_pretty()).then(Vec::new);
+        let mut count = 0;
+        loop {
+            let container = matches!(self.peek()?, Some(b'{' | b'['));
+            match pending.as_mut() {
+                Some(scalars) if !container => {
+                    self.buffers.push(Vec::new());
+                    let printed = self.value(depth + 1);
+                    let scalar = self.buffers.pop().expect("pushed above");
+                    printed?;
+                    scalars.push(scalar);
+                }
+                _ => {
+                    if let Some(scalars) = pending.take() {
+                        for (i, scalar) in scalars.iter().enumerate() {
+                            self.emit(if i == 0 { b"[" } else { b"," })?;
+                            self.newline(depth + 1)?;
+                            self.emit(scalar)?;
+                        }
+                    }
+                    self.emit(if count == 0 { b"[" } else { b"," })?;
+                    self.newline(depth + 1)?;
+                    self.value(depth + 1)?;
+                }
+            }
+            count += 1;
+
+            self.skip_whitespace()?;
+            match self.peek()? {
+                Some(b',') => {
+                    self.bump()?;
+                    self.skip_whitespace()?;
+                    if self.peek()? == Some(b']') {
+                        return Err(self.error("trailing comma"));
+                    }
+                }
+                Some(b']') => {
+                    self.bump()?;
+                    break;
+                }
+                Some(_) => return Err(self.error("expected ',' or ']'")),
+                None => return Err(self.error("EOF while parsing a list")),
+            }
+        }
+
+        match pending {
+            Some(scalars) => {
+                self.emit(b"[")?;
+                for (i, scalar) in scalars.iter().enumerate() {
+                    if i > 0 {
+                        self.emit(b", ")?;
+                    }
+                    self.emit(scalar)?;
+                }
+                self.emit(b"]")
+            }
+            None => {
+                self.newline(depth)?;
+                self.emit(b"]")
+            }
+        }
+    }
+
+    /// Read a string token, quotes included, exactly as written.
+    fn string(&mut self) -> Result<Vec<u8>, UtilityError> {
+        let start = self.position();
+        let mut raw = vec![b'"'];
+        self.bump()?;
+        loop {
+            match self.peek()? {
+                None => return Err(self.error("EOF while parsing a string")),
+                Some(b'"') => {
+                    raw.push(b'"');
+                    self.bump()?;
+                    break;
+                }
+                Some(b'\\') => {
+                    raw.push(b'\\');
+                    self.bump()?;
+                    match self.peek()? {
+                        Some(c @ (b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't')) => {
+                            raw.push(c);
+                            self.bump()?;
+                        }
+                        Some(b'u') => {
+                            raw.push(b'u');
+                            self.bump()?;
+                            for _ in 0..4 {
+                                match self.peek()? {
+                                    Some(c) if c.is_ascii_hexdigit() => {
+                                        raw.push(c);
+                                        self.bump()?;
+                                    }
+                                    _ => return Err(self.error("invalid \\u escape")),
+                                }
+                            }
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    }
+                }
+                Some(c) if c < 0x20 => {
+                    return Err(self.error("control character found while parsing a string"))
+                }
+                Some(c) => {
+                    raw.push(c);
+                    self.bump()?;
+                }
+            }
+        }
+        if std::str::from_utf8(&raw).is_err() {
+            let (offset, line, column) = start;
+            return Err(diagnostic(
+                "invalid UTF-8 in string",
+                offset,
+                line,
+                column,
+                1,
+            ));
+        }
+        Ok(raw)
+    }
+
+    /// Read a number token verbatim after checking it against the grammar.
+    fn number(&mut self) -> Result<Vec<u8>, UtilityError> {
+        let mut raw = Vec::new();
+        if self.peek()? == Some(b'-') {
+            raw.push(b'-');
+            self.bump()?;
+        }
+        match self.peek()? {
+            Some(b'0') => {
+                raw.push(b'0');
+                self.bump()?;
+            }
+            Some(b'1'..=b'9') => self.digits(&mut raw)?,
+            _ => return Err(self.error("invalid number")),
+        }
+        if self.peek()? == Some(b'.') {
+            raw.push(b'.');
+            self.bump()?;
+            if !matches!(self.peek()?, Some(b'0'..=b'9')) {
+                return Err(self.error("invalid number"));
+            }
+            self.digits(&mut raw)?;
+        }
+        if let Some(e @ (b'e' | b'E')) = self.peek()? {
+            raw.push(e);
+            self.bump()?;
+            if let Some(sign @ (b'+' | b'-')) = self.peek()? {
+                raw.push(sign);
+                self.bump()?;
+            }
+            if !matches!(self.peek()?, Some(b'0'..=b'9')) {
+                return Err(self.error("invalid number"));
+            }
+            self.digits(&mut raw)?;
+        }
+        Ok(raw)
+    }
+
+    fn digits(&mut self, raw: &mut Vec<u8>) -> Result<(), UtilityError> {
+        while let Some(c @ b'0'..=b'9') = self.peek()? {
+            raw.push(c);
+            self.bump()?;
+        }
+        Ok(())
+    }
+
+    fn literal(&mut self, word: &'static [u8]) -> Result<(), UtilityError> {
+        for &expected in word {
+            if self.peek()? != Some(expected) {
+                return Err(self.error("expected value"));
+            }
+            self.bump()?;
+        }
+        self.emit(word)
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), UtilityError> {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek()? {
+            self.bump()?;
+        }
+        Ok(())
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, UtilityError> {
+        Ok(self.input.fill_buf()?.first().copied())
+    }
+
+    fn bump(&mut self) -> Result<(), UtilityError> {
+        if let Some(byte) = self.peek()? {
+            self.input.consume(1);
+            self.offset += 1;
+            if byte == b'\n' {
+                self.line += 1;
+                self.column = 1;
+            } else if byte & 0xC0 != 0x80 {
+                // Count characters, not UTF-8 continuation bytes.
+                self.column += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn position(&self) -> (usize, usize, usize) {
+        (self.offset, self.line, self.column)
+    }
+
+    fn error(&mut self, message: &str) -> UtilityError {
+        let length = match self.peek() {
+            Ok(Some(byte)) => utf8_len(byte),
+            _ => 0,
+        };
+        diagnostic(message, self.offset, self.line, self.column, length)
+    }
+
+    fn emit(&mut self, bytes: &[u8]) -> Result<(), UtilityError> {
+        match self.buffers.last_mut() {
+            Some(buffer) => buffer.extend_from_slice(bytes),
+            None => self.out.write_all(bytes)?,
+        }
+        Ok(())
+    }
+
+    fn emit_string(&mut self, raw: &[u8]) -> Result<(), UtilityError> {
+        if !self.options.ascii || raw.is_ascii() {
+            return self.emit(raw);
+        }
+        let text = std::str::from_utf8(raw).expect("validated while reading");
+        let mut escaped = String::with_capacity(raw.len() + 16);
+        for c in text.chars() {
+            if c.is_ascii() {
+                escaped.push(c);
+            } else {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    escaped.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+        }
+        self.emit(escaped.as_bytes())
+    }
+
+    fn newline(&mut self, depth: usize) -> Result<(), UtilityError> {
+        let unit = match self.style {
+            IndentStyle::Minified => return Ok(()),
+            IndentStyle::Spaces(size) => " ".repeat(*size),
+            IndentStyle::Tabs => "\t".to_string(),
+        };
+        self.emit(b"\n")?;
+        for _ in 0..depth {
+            self.emit(unit.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn colon(&self) -> &'static [u8] {
+        if self.is_pretty() {
+            b": "
+        } else {
+            b":"
+        }
+    }
+
+    fn is_pretty(&self) -> bool {
+        !matches!(self.style, IndentStyle::Minified)
+    }
+}
+
+fn diagnostic(
+    message: &str,
+    offset: usize,
+    line: usize,
+    column: usize,
+    length: usize,
+) -> UtilityError {
+    UtilityError::Diagnostic(Diagnostic {
+        message: message.to_string(),
+        offset,
+        line,
+        column,
+        length,
+        severity: Severity::Error,
+        hint: hint_for(message).map(str::to_string),
+    })
+}
+
+/// Keys compare by their decoded text, so `"\u0061"` sorts as `"a"`.
+fn decode_key(raw: &[u8]) -> String {
+    let text = std::str::from_utf8(raw).expect("validated while reading");
+    serde_json::from_str(text).unwrap_or_else(|_| text.to_string())
+}
+
+fn utf8_len(lead: u8) -> usize {
+    match lead {
+        0xF0..=0xFF => 4,
+        0xE0..=0xEF => 3,
+        0xC0..=0xDF => 2,
+        _ => 1,
+    }
+}
@@ -0,0 +1,630 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Lenient JSON repair.
+//!
+//! A forgiving scanner rewrites almost-JSON (JSON5, JSONC, JavaScript
+//! object literals, Python `repr` output, NDJSON, truncated payloads) into
+//! strict JSON text, noting every change it makes. The result then goes
+//! through the regular printer, so numbers and key order survive.
+
+use super::{format_json, IndentStyle, JsonFormatOptions};
+use crate::error::{Diagnostic, Severity, UtilityError};
+use serde::{Deserialize, Serialize};
+use universal_function_macro::universal_function;
+
+/// Deeper documents are rejected, as in the strict printer.
+const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct JsonRepairResult {
+    pub output: String, // Formatted strict JSON, one record per line for NDJSON
+    pub fixes: Vec<Diagnostic>, // Every change made, as warnings pointing into the input
+    pub records: usize, // Top-level values found; more than one means NDJSON
+}
+
+/// Repair almost-JSON and format the result.
+///
+/// Each top-level value is formatted on its own, so NDJSON stays one
+/// record per line in minified style. A top-level string that holds JSON
+/// is unwrapped, repeatedly if the payload was stringified more than once.
+#[universal_function]
+pub fn repair_json(
+    input: &str,
+    style: IndentStyle,
+    options: Option<JsonFormatOptions>,
+) -> Result<JsonRepairResult, UtilityError> {
+    let mut options = options.unwrap_or_default();
+    let trailing_newline = std::mem::take(&mut options.trailing_newline);
+
+    let mut scanner = Scanner::new(input);
+    if input.starts_with('\u{feff}') {
+        scanner.fix(0, 3, "Removed byte order mark");
+        scanner.pos = 3;
+    }
+    let mut records = Vec::new();
+    loop {
+        scanner.skip_trivia();
+        if scanner.peek().is_none() {
+            break;
+        }
+        if !records.is_empty() && scanner.peek() == Some(',') {
+            scanner.fix(scanner.pos, 1, "Removed comma between top-level values");
+            scanner.bump();
+            continue;
+        }
+        let start = scanner.pos;
+        scanner.out.clear();
+        scanner.value(0)?;
+        let mut record = std::mem::take(&mut scanner.out);
+        while let Some(inner) = stringified(&record) {
+            scanner.fix(start, 1, "Unwrapped stringified JSON");
+            record = inner;
+        }
+        records.push(format_json(&record, style.clone(), Some(options.clone()))?);
+    }
+    if records.is_empty() {
+        return Err(Diagnostic::new(input, input.len(), "EOF while parsing a value").into());
+    }
+
+    let mut output = records.join("\n");
+    if trailing_newline {
+        output.push('\n');
+    }
+    Ok(JsonRepairResult {
+        output,
+        fixes: scanner.fixes,
+        records: records.len(),
+    })
+}
+
+/// The JSON text inside a JSON string, when the string holds a container
+/// or another string.
+fn stringified(record: &str) -> Option<String> {
+    if !record.starts_with('"') {
+        return None;
+    }
+    let inner: String = serde_json::from_str(record).ok()?;
+    let trimmed = inner.trim();
+    let candidate = trimmed.starts_with(['{', '[', '"']);
+    (candidate && format_json(trimmed, IndentStyle::Minified, None).is_ok())
+        .then(|| trimmed.to_string())
+}
+
+struct Scanner<'a> {
+    input: &'a str,
+    pos: usize,
+    out: String,
+    fixes: Vec<Diagnostic>,
+    // Last located position, so fixes are located incrementally
+    cursor: (usize, usize, usize),
+}
+
+impl<'a> Scanner<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            out: String::with_capacity(input.len()),
+            fixes: Vec::new(),
+            cursor: (0, 1, 1),
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<(), UtilityError> {
+        self.skip_trivia();
+        match self.peek() {
+            Some('{') => self.object(depth),
+            Some('[') => self.array(depth, ']'),
+            Some('(') => {
+                self.fix(self.pos, 1, "Converted tuple to array");
+                self.array(depth, ')')
+            }
+            Some(quote @ ('"' | '\'' | '`')) => {
+                self.string(quote);
+                Ok(())
+            }
+            Some(c) if c == '-' || c == '+' || c == '.' || c.is_ascii_digit() => self.number(),
+            Some(c) if is_word_char(c) => {
+                self.word();
+                Ok(())
+            }
+            Some(_) => Err(self.error("expected value")),
+            None => {
+                self.fix(self.pos, 0, "Inserted missing value");
+                self.out.push_str("null");
+                Ok(())
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<(), UtilityError> {
+        if depth >= MAX_DEPTH {
+            return Err(self.error("recursion limit exceeded"));
+        }
+        let open = self.pos;
+        self.bump();
+        self.out.push('{');
+        let mut first = true;
+        loop {
+            self.skip_trivia();
+            match self.peek() {
+                Some('}') => {
+                    self.bump();
+                    break;
+                }
+                None => {
+                    self.fix(open, 1, "Closed unterminated object");
+                    break;
+                }
+                Some(',') => {
+                    self.fix(self.pos, 1, "Removed extra comma");
+                    self.bump();
+                    continue;
+                }
+                _ => {}
+            }
+            if !first {
+                self.out.push(',');
+            }
+            first = false;
+
+            match self.peek() {
+                Some(quote @ ('"' | '\'' | '`')) => self.string(quote),
+                Some(c) if is_word_char(c) => {
+                    let start = self.pos;
+                    let key = self.take_while(is_word_char);
+                    self.fix(start, key.len(), "Quoted key");
+                    push_quoted(&mut self.out, key);
+                }
+                _ => return Err(self.error("key must be a string")),
+            }
+
+            self.skip_trivia();
+            match self.peek() {
+                Some(':') => self.bump(),
+                Some('=') => {
+                    self.fix(self.pos, 1, "Replaced '=' with ':'");
+                    self.bump();
+                }
+                _ => self.fix(self.pos, 0, "Inserted missing colon"),
+            }
+            self.out.push(':');
+            self.value(depth + 1)?;
+
+            if !self.separator('}') {
+                break;
+            }
+        }
+        self.out.push('}');
+        Ok(())
+    }
+
+    fn array(&mut self, depth: usize, close: char) -> Result<(), UtilityError> {
+        if depth >= MAX_DEPTH {
+            return Err(self.error("recursion limit exceeded"));
+        }
+        let open = self.pos;
+        self.bump();
+        self.out.push('[');
+        let mut first = true;
+        loop {
+            self.skip_trivia();
+            match self.peek() {
+                Some(c) if c == close => {
+                    self.bump();
+                    break;
+                }
+                None => {
+                    self.fix(open, 1, "Closed unterminated array");
+                    break;
+                }
+                Some(',') => {
+                    self.fix(self.pos, 1, "Removed extra comma");
+                    self.bump();
+                    continue;
+                }
+                _ => {}
+            }
+            if !first {
+                self.out.push(',');
+            }
+            first = false;
+            self.value(depth + 1)?;
+
+            if !self.separator(close) {
+                break;
+            }
+        }
+        self.out.push(']');
+        Ok(())
+    }
+
+    /// Consume what follows a member: a comma, the closing bracket (left
+    /// for the caller), or nothing where a comma was forgotten. Returns
+    /// false once the container has been closed.
+    fn separator(&mut self, close: char) -> bool {
+        self.skip_trivia();
+        match self.peek() {
+            Some(',') => {
+                let comma = self.pos;
+                self.bump();
+                self.skip_trivia();
+                if self.peek() == Some(close) {
+                    self.fix(comma, 1, "Removed trailing comma");
+                }
+                true
+            }
+            Some(c) if c == close => true,
+            None => true,
+            Some('}' | ']' | ')') => {
+                // A mismatched bracket closes this container.
+                self.fix(self.pos, 1, "Replaced mismatched closing bracket");
+                self.bump();
+                false
+            }
+            Some(_) => {
+                self.fix(self.pos, 0, "Inserted missing comma");
+                true
+            }
+        }
+    }
+
+    fn string(&mut self, quote: char) {
+        let start = self.pos;
+        self.bump();
+        if quote != '"' {
+            self.fix(start, 1, "Replaced quotes with double quotes");
+        }
+        self.out.push('"');
+        let mut escaped_line_break = false;
+        loop {
+            let Some(c) = self.peek() else {
+                self.fix(start, 1, "Closed unterminated string");
+                break;
+            };
+            self.bump();
+            match c {
+                c if c == quote => break,
+                '"' => self.out.push_str("\\\""),
+                '\\' => self.escape(),
+                '\n' | '\r' | '\t' if quote == '`' => push_control(&mut self.out, c),
+                c if (c as u32) < 0x20 => {
+                    if !escaped_line_break {
+                        self.fix(self.pos - 1, 1, "Escaped control character in string");
+                        escaped_line_break = true;
+                    }
+                    push_control(&mut self.out, c);
+                }
+                c => self.out.push(c),
+            }
+        }
+        self.out.push('"');
+    }
+
+    fn escape(&mut self) {
+        let start = self.pos - 1;
+        let Some(c) = self.peek() else {
+            return;
+        };
+        self.bump();
+        match c {
+            '"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' => {
+                self.out.push('\\');
+                self.out.push(c);
+            }
+            'u' if self.hex_digits(4).is_some() => {
+                let digits = &self.input[self.pos..self.pos + 4];
+                self.out.push_str("\\u");
+                self.out.push_str(digits);
+                self.pos += 4;
+            }
+            'x' if self.hex_digits(2).is_some() => {
+                let digits = &self.input[self.pos..self.pos + 2];
+                self.out.push_str("\\u00");
+                self.out.push_str(digits);
+                self.pos += 2;
+                self.fix(start, 4, "Converted \\x escape");
+            }
+            '\'' | '`' => self.out.push(c),
+            'v' => self.out.push_str("\\u000b"),
+            '0' => self.out.push_str("\\u0000"),
+            // JSON5 line continuation.
+            '\n' => {}
+            '\r' => {
+                if self.peek() == Some('\n') {
+                    self.bump();
+                }
+            }
+            c => {
+                self.fix(start, 1 + c.len_utf8(), "Removed invalid escape");
+                push_quoted_char(&mut self.out, c);
+            }
+        }
+    }
+
+    fn hex_digits(&self, count: usize) -> Option<()> {
+        let digits = self.input.get(self.pos..self.pos + count)?;
+        digits.chars().all(|c| c.is_ascii_hexdigit()).then_some(())
+    }
+
+    fn number(&mut self) -> Result<(), UtilityError> {
+        let start = self.pos;
+        let token =
+            self.take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '+' | '-' | '_'));
+        let (sign, body) = match token.as_bytes().first() {
+            Some(b'-') => ("-", &token[1..]),
+            Some(b'+') => {
+                self.fix(start, 1, "Removed leading '+'");
+                ("", &token[1..])
+            }
+            _ => ("", token),
+        };
+
+        if body == "Infinity" || body == "NaN" {
+            self.fix(start, token.len(), "Replaced non-finite number with null");
+            self.out.push_str("null");
+            return Ok(());
+        }
+        if let Some(hex) = body.strip_prefix("0x").or_else(|| body.strip_prefix("0X")) {
+            let value = u128::from_str_radix(&hex.replace('_', ""), 16)
+                .map_err(|_| self.error_at(start, "invalid hexadecimal number"))?;
+            self.fix(start, token.len(), "Converted hexadecimal number");
+            self.out.push_str(sign);
+            self.out.push_str(&value.to_string());
+            return Ok(());
+        }
+
+        let mut number = body.replace('_', "");
+        if number.len() != body.len() {
+            self.fix(start, token.len(), "Removed digit separators");
+        }
+        if number.starts_with('.') {
+            self.fix(start, token.len(), "Added leading zero");
+            number.insert(0, '0');
+        }
+        if let Some(dot) = number.find('.') {
+            if !number[dot + 1..].starts_with(|c: char| c.is_ascii_digit()) {
+                self.fix(start, token.len(), "Added digit after decimal point");
+                number.insert(dot + 1, '0');
+            }
+        }
+        let digits = number.find(['.', 'e', 'E']).unwrap_or(number.len());
+        if digits > 1 && number.starts_with('0') {
+            self.fix(start, token.len(), "Removed leading zeros");
+            let zeros =
+                number[..digits - 1].len() - number[..digits - 1].trim_start_matches('0').len();
+            number.drain(..zeros);
+        }
+
+        self.out.push_str(sign);
+        self.out.push_str(&number);
+        let written = &self.out[self.out.len() - sign.len() - number.len()..];
+        if format_json(written, IndentStyle::Minified, None).is_err() {
+            return Err(self.error_at(start, "invalid number"));
+        }
+        Ok(())
+    }
+
+    /// Bare identifiers: literals from other languages, or unquoted text.
+    fn word(&mut self) {
+        let start = self.pos;
+        let word = self.take_while(is_word_char);
+        let replacement = match word {
+            "true" | "false" | "null" => {
+                self.out.push_str(word);
+                return;
+            }
+            "True" | "TRUE" => "true",
+            "False" | "FALSE" => "false",
+            "None" | "NULL" | "nil" | "undefined" => "null",
+            "NaN" | "Infinity" => {
+                self.fix(start, word.len(), "Replaced non-finite number with null");
+                self.out.push_str("null");
+                return;
+            }
+            _ => {
+                self.fix(start, word.len(), "Quoted bare word");
+                push_quoted(&mut self.out, word);
+                return;
+            }
+        };
+        self.fix(
+            start,
+            word.len(),
+            &format!("Replaced {} with {}", word, replacement),
+        );
+        self.out.push_str(replacement);
+    }
+
+    /// Skip whitespace and `//`, `/* */` and `#` comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            let rest = &self.input[self.pos..];
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            let comment = if trimmed.starts_with("//") || trimmed.starts_with('#') {
+                trimmed.find('\n').unwrap_or(trimmed.len())
+            } else if let Some(body) = trimmed.strip_prefix("/*") {
+                body.find("*/").map_or(trimmed.len(), |end| end + 4)
+            } else {
+                return;
+            };
+            self.fix(self.pos, comment, "Removed comment");
+            self.pos += comment;
+        }
+    }
+
+    fn take_while(&mut self, accept: impl Fn(char) -> bool) -> &'a str {
+        let input = self.input;
+        let rest = &input[self.pos..];
+        let end = rest.find(|c| !accept(c)).unwrap_or(rest.len());
+        self.pos += end;
+        &rest[..end]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) {
+        if let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    /// Record a change made at `offset`.
+    fn fix(&mut self, offset: usize, length: usize, message: &str) {
+        let (mut at, mut line, mut column) = self.cursor;
+        if offset < at {
+            (at, line, column) = (0, 1, 1);
+        }
+        for c in self.input[at..offset].chars() {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        self.cursor = (offset, line, column);
+        self.fixes.push(Diagnostic {
+            message: message.to_string(),
+            offset,
+            line,
+            column,
+            length,
+            severity: Severity::Warning,
+            hint: None,
+        });
+    }
+
+    fn error(&self, message: &str) -> UtilityError {
+        self.error_at(self.pos, message)
+    }
+
+    fn error_at(&self, offset: usize, message: &str) -> UtilityError {
+        Diagnostic::new(self.input, offset, message).into()
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn push_quoted(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        push_quoted_char(out, c);
+    }
+    out.push('"');
+}
+
+fn push_quoted_char(out: &mut String, c: char) {
+    match c {
+        '"' => out.push_str("\\\""),
+        '\\' => out.push_str("\\\\"),
+        c if (c as u32) < 0x20 => push_control(out, c),
+        c => out.push(c),
+    }
+}
+
+fn push_control(out: &mut String, c: char) {
+    match c {
+        '\n' => out.push_str("\\n"),
+        '\r' => out.push_str("\\r"),
+        '\t' => out.push_str("\\t"),
+        c => out.push_str(&format!("\\u{:04x}", c as u32)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repair(input: &str) -> JsonRepairResult {
+        repair_json(input, IndentStyle::Minified, None).unwrap()
+    }
+
+    fn messages(result: &JsonRepairResult) -> Vec<&str> {
+        result.fixes.iter().map(|f| f.message.as_str()).collect()
+    }
+
+    #[test]
+    fn repairs_json5_and_jsonc() {
+        let result = repair(
+            "// config\n{\n  name: 'dev', /* inline */\n  port: 0x1F,\n  ratio: .5,\n  tags: [\"a\", \"b\",],\n}",
+        );
+        assert_eq!(
+            result.output,
+            r#"{"name":"dev","port":31,"ratio":0.5,"tags":["a","b"]}"#
+        );
+        assert_eq!(
+            messages(&result),
+            [
+                "Removed comment",
+                "Quoted key",
+                "Replaced quotes with double quotes",
+                "Removed comment",
+                "Quoted key",
+                "Converted hexadecimal number",
+                "Quoted key",
+                "Added leading zero",
+                "Quoted key",
+                "Removed trailing comma",
+                "Removed trailing comma",
+            ]
+        );
+        let port = &result.fixes[5];
+        assert_eq!((port.line, port.column, port.length), (4, 9, 4));
+    }
+
+    #[test]
+    fn repairs_python_repr_and_truncation() {
+        let result = repair("{'ok': True, 'none': None, 'pair': (1, 2), 'msg': 'it\\'s \"x\"'}");
+        assert_eq!(
+            result.output,
+            r#"{"ok":true,"none":null,"pair":[1,2],"msg":"it's \"x\""}"#
+        );
+
+        let result = repair(r#"{"items": [1, 2 3], "next": {"a": "unterminated"#);
+        assert_eq!(
+            result.output,
+            r#"{"items":[1,2,3],"next":{"a":"unterminated"}}"#
+        );
+        assert!(messages(&result).contains(&"Inserted missing comma"));
+        assert!(messages(&result).contains(&"Closed unterminated string"));
+    }
+
+    #[test]
+    fn formats_ndjson_per_line_and_unwraps_strings() {
+        let result = repair("{\"a\":1}\n{\"a\":2}\n");
+        assert_eq!(result.records, 2);
+        assert_eq!(result.output, "{\"a\":1}\n{\"a\":2}");
+        assert!(result.fixes.is_empty());
+
+        let twice = serde_json::to_string(&serde_json::to_string(r#"{"a":[1]}"#).unwrap()).unwrap();
+        let result = repair(&twice);
+        assert_eq!(result.output, r#"{"a":[1]}"#);
+        assert_eq!(messages(&result), ["Unwrapped stringified JSON"; 2]);
+
+        // A plain string stays a string.
+        assert_eq!(repair(r#""hello""#).output, r#""hello""#);
+    }
+
+    #[test]
+    fn keeps_numbers_and_reports_unrepairable_input() {
+        let result = repair("[12345678901234567890123, 1.50, -Infinity, +1, 007]");
+        assert_eq!(result.output, "[12345678901234567890123,1.50,null,1,7]");
+        assert!(repair_json("{: 1}", IndentStyle::Minified, None).is_err());
+        assert!(repair_json("  ", IndentStyle::Minified, None).is_err());
+    }
+}
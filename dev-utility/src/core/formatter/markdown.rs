@@ -0,0 +1,384 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+mod from_html;
+mod table;
+
+use std::collections::HashMap;
+
+use pulldown_cmark::{CowStr, Event, LinkType, Options, Parser, Tag, TagEnd, TextMergeStream};
+use serde::{Deserialize, Serialize};
+use universal_function_macro::universal_function;
+
+use crate::error::UtilityError;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct MarkdownOptions {
+    pub sanitize: bool,          // Remove scripts, event handlers and unsafe URLs
+    pub heading_ids: bool,       // Give headings GitHub-style anchor ids
+    pub smart_punctuation: bool, // Curly quotes, en/em dashes and ellipses
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            sanitize: true,
+            heading_ids: true,
+            smart_punctuation: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct TocEntry {
+    pub level: u8, // 1 for h1 through 6 for h6
+    pub text: String,
+    pub id: String, // Anchor the rendered heading gets
+}
+
+fn parser_options(options: &MarkdownOptions) -> Options {
+    let mut opts = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_GFM;
+    if options.smart_punctuation {
+        opts |= Options::ENABLE_SMART_PUNCTUATION;
+    }
+    opts
+}
+
+/// Render CommonMark with the GitHub extensions to HTML.
+///
+/// Tables, task lists, strikethrough, footnotes, alerts and bare URL
+/// autolinks are supported. Sanitizing keeps the markup GitHub would
+/// display and drops everything that can run script; raw HTML in the
+/// input goes through the same filter.
+#[universal_function]
+pub fn render_markdown(
+    input: &str,
+    options: Option<MarkdownOptions>,
+) -> Result<String, UtilityError> {
+    let options = options.unwrap_or_default();
+    let mut events =
+        autolink(TextMergeStream::new(Parser::new_ext(input, parser_options(&options))).collect());
+    if options.heading_ids {
+        assign_heading_ids(&mut events);
+    }
+
+    let mut html = String::with_capacity(input.len() * 3 / 2);
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+    if options.sanitize {
+        html = sanitizer().clean(&html).to_string();
+    }
+    Ok(html)
+}
+
+/// Headings in document order, with the ids `render_markdown` gives them.
+#[universal_function]
+pub fn markdown_toc(input: &str) -> Result<Vec<TocEntry>, UtilityError> {
+    let mut events: Vec<Event> = TextMergeStream::new(Parser::new_ext(
+        input,
+        parser_options(&MarkdownOptions::default()),
+    ))
+    .collect();
+    Ok(assign_heading_ids(&mut events))
+}
+
+/// Convert HTML to GitHub Flavored Markdown.
+///
+/// Tables become pipe tables and checkbox inputs in list items become task
+/// list markers. Elements Markdown has no syntax for are reduced to their
+/// content; scripts, styles and comments are dropped. Elements nested more
+/// than 256 levels deep are an error, as in `format_html`.
+#[universal_function]
+pub fn html_to_markdown(input: &str) -> Result<String, UtilityError> {
+    let (_dom, roots) = super::html::parse(input);
+    super::html::check_depth(input, &roots)?;
+    Ok(from_html::convert(&roots))
+}
+
+/// Align the columns of every pipe table in a Markdown document.
+///
+/// Cells are padded to the widest one in their column, following the
+/// alignment in the delimiter row; wide characters count as two columns.
+/// Text outside tables, including tables inside code fences, is unchanged.
+#[universal_function]
+pub fn format_markdown_tables(input: &str) -> Result<String, UtilityError> {
+    Ok(table::format_tables(input))
+}
+
+fn sanitizer() -> ammonia::Builder<'static> {
+    let mut builder = ammonia::Builder::default();
+    builder
+        .add_tags(["input"])
+        .add_tag_attributes("input", ["checked", "disabled"])
+        .add_tag_attribute_values("input", "type", ["checkbox"])
+        .add_tag_attributes("th", ["style"])
+        .add_tag_attributes("td", ["style"])
+        .filter_style_properties(["text-align"].into())
+        .add_generic_attributes(["id", "class"]);
+    builder
+}
+
+/// GitHub-style anchor: lowercase, punctuation dropped, spaces to hyphens.
+fn slug(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Set the id of every heading without one and return the outline.
+fn assign_heading_ids(events: &mut [Event]) -> Vec<TocEntry> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut toc = Vec::new();
+    for i in 0..events.len() {
+        let Event::Start(Tag::Heading { level, id, .. }) = &events[i] else {
+            continue;
+        };
+        let level = *level as u8;
+        let explicit = id.as_ref().map(|id| id.to_string());
+
+        let mut text = String::new();
+        for event in &events[i + 1..] {
+            match event {
+                Event::End(TagEnd::Heading(_)) => break,
+                Event::Text(t) | Event::Code(t) => text.push_str(t),
+                _ => {}
+            }
+        }
+        let id = explicit.unwrap_or_else(|| {
+            let base = slug(&text);
+            let count = seen.entry(base.clone()).or_insert(0);
+            let id = if *count == 0 {
+                base
+            } else {
+                format!("{}-{}", base, count)
+            };
+            *count += 1;
+            id
+        });
+        if let Event::Start(Tag::Heading { id: slot, .. }) = &mut events[i] {
+            *slot = Some(CowStr::from(id.clone()));
+        }
+        toc.push(TocEntry {
+            level,
+            text: text.trim().to_string(),
+            id,
+        });
+    }
+    toc
+}
+
+/// Length of the URL or email autolink starting at `start`, per the GFM
+/// autolink extension, or 0.
+fn autolink_len(text: &str, start: usize) -> usize {
+    let rest = &text[start..];
+    let lower = rest.get(..8).unwrap_or(rest).to_ascii_lowercase();
+    let prefix = ["https://", "http://", "www."]
+        .iter()
+        .find(|p| lower.starts_with(**p))
+        .map(|p| p.len());
+    let mut end = match prefix {
+        Some(prefix) => {
+            let len = rest
+                .find(|c: char| c.is_whitespace() || c == '<')
+                .unwrap_or(rest.len());
+            if len <= prefix {
+                return 0;
+            }
+            len
+        }
+        None => {
+            let Some(at) = rest.find('@') else {
+                return 0;
+            };
+            let local = &rest[..at];
+            if local.is_empty()
+                || !local
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || ".+-_".contains(c))
+            {
+                return 0;
+            }
+            let domain = rest[at + 1..]
+                .find(|c: char| !(c.is_ascii_alphanumeric() || "-_.".contains(c)))
+                .unwrap_or(rest.len() - at - 1);
+            let domain = rest[at + 1..at + 1 + domain].trim_end_matches('.');
+            if !domain.contains('.') || domain.ends_with(['-', '_']) {
+                return 0;
+            }
+            return at + 1 + domain.len();
+        }
+    };
+
+    // Trailing punctuation and unbalanced closing parentheses are not part
+    // of the link.
+    loop {
+        let url = &rest[..end];
+        let Some(last) = url.chars().last() else {
+            return 0;
+        };
+        if "?!.,:*_~'\"".contains(last)
+            || (last == ')' && url.matches(')').count() > url.matches('(').count())
+        {
+            end -= last.len_utf8();
+        } else {
+            return end;
+        }
+    }
+}
+
+/// Turn bare URLs, `www.` hosts and email addresses in text into links.
+fn autolink(events: Vec<Event>) -> Vec<Event> {
+    let mut out = Vec::with_capacity(events.len());
+    let mut skip = 0; // Inside links, images and code blocks
+    for event in events {
+        match &event {
+            Event::Start(Tag::Link { .. } | Tag::Image { .. } | Tag::CodeBlock(_)) => skip += 1,
+            Event::End(TagEnd::Link | TagEnd::Image | TagEnd::CodeBlock) => skip -= 1,
+            _ => {}
+        }
+        let Event::Text(text) = &event else {
+            out.push(event);
+            continue;
+        };
+        if skip > 0 {
+            out.push(event);
+            continue;
+        }
+
+        let mut last = 0;
+        let mut prev: Option<char> = None;
+        let mut chars = text.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let boundary = prev.is_none_or(|p| p.is_whitespace() || "*_~(".contains(p));
+            prev = Some(c);
+            if !boundary || i < last {
+                continue;
+            }
+            let len = autolink_len(text, i);
+            if len == 0 {
+                continue;
+            }
+            let url = &text[i..i + len];
+            let (link_type, dest) = if url.contains("://") {
+                (LinkType::Autolink, url.to_string())
+            } else if url.to_ascii_lowercase().starts_with("www.") {
+                (LinkType::Autolink, format!("http://{}", url))
+            } else {
+                (LinkType::Email, format!("mailto:{}", url))
+            };
+            if i > last {
+                out.push(Event::Text(text[last..i].to_string().into()));
+            }
+            out.push(Event::Start(Tag::Link {
+                link_type,
+                dest_url: dest.into(),
+                title: CowStr::from(""),
+                id: CowStr::from(""),
+            }));
+            out.push(Event::Text(url.to_string().into()));
+            out.push(Event::End(TagEnd::Link));
+            last = i + len;
+            while chars.peek().is_some_and(|&(j, _)| j < last) {
+                prev = chars.next().map(|(_, c)| c);
+            }
+        }
+        if last == 0 {
+            out.push(event);
+        } else if last < text.len() {
+            out.push(Event::Text(text[last..].to_string().into()));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_gfm_and_sanitizes() {
+        let input = "# Intro\n\n- [x] done\n- [ ] todo\n\n| a | b |\n|:-|-:|\n| 1 | 2 |\n\n\
+                     See www.example.com, ~~old~~ and a note[^1].\n\n[^1]: Footnote.\n\n\
+                     <script>alert(1)</script><a href=\"javascript:x()\" onclick=\"y()\">bad</a>\n";
+        let html = render_markdown(input, None).unwrap();
+        assert!(html.contains("<h1 id=\"intro\">Intro</h1>"));
+        assert!(html.contains("<input disabled=\"\" type=\"checkbox\" checked=\"\">"));
+        assert!(html.contains("<th style=\"text-align:left\">a</th>"));
+        assert!(html.contains(
+            "<a href=\"http://www.example.com\" rel=\"noopener noreferrer\">www.example.com</a>,"
+        ));
+        assert!(html.contains("<del>old</del>"));
+        assert!(html.contains("class=\"footnote-reference\""));
+        assert!(
+            !html.contains("script") && !html.contains("onclick") && !html.contains("javascript")
+        );
+
+        let raw = render_markdown(
+            "<b onclick=\"x\">hi</b>",
+            Some(MarkdownOptions {
+                sanitize: false,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert_eq!(raw, "<p><b onclick=\"x\">hi</b></p>\n");
+    }
+
+    #[test]
+    fn extracts_toc_with_unique_ids() {
+        let toc = markdown_toc("# Hello, World!\n## `code` & more\n## Hello, World!\n").unwrap();
+        let ids: Vec<_> = toc.iter().map(|e| (e.level, e.id.as_str())).collect();
+        assert_eq!(
+            ids,
+            [(1, "hello-world"), (2, "code--more"), (2, "hello-world-1")]
+        );
+        assert_eq!(toc[1].text, "code & more");
+    }
+
+    #[test]
+    fn converts_html_to_markdown() {
+        let html = "<h2>Title</h2><p>Some <strong>bold</strong>, <em>snake_case</em> and \
+                    <a href=\"https://x.dev\" title=\"X\">a link</a>.<br>Next</p>\
+                    <ul><li><input type=\"checkbox\" checked> done</li><li>nested<ol start=\"3\"><li>three</li></ol></li></ul>\
+                    <blockquote><p>quote *x*</p></blockquote>\
+                    <pre><code class=\"language-rust\">fn main() {}\n</code></pre>\
+                    <table><tr><th>a</th><th align=\"right\">bb</th></tr><tr><td>1|2</td><td>3</td></tr></table>";
+        assert_eq!(
+            html_to_markdown(html).unwrap(),
+            "## Title\n\nSome **bold**, *snake_case* and [a link](https://x.dev \"X\").\\\nNext\n\n\
+             - [x] done\n- nested\n  3. three\n\n> quote \\*x\\*\n\n```rust\nfn main() {}\n```\n\n\
+             | a    |  bb |\n| ---- | --: |\n| 1\\|2 |   3 |\n"
+        );
+    }
+
+    #[test]
+    fn rejects_deeply_nested_html() {
+        for input in ["<ul><li>".repeat(128), "<blockquote>".repeat(256)] {
+            assert!(html_to_markdown(&input).is_ok());
+        }
+        let err = html_to_markdown(&"<ul><li>".repeat(1000)).unwrap_err();
+        let message = &err.diagnostic().expect("diagnostic").message;
+        assert_eq!(message, "Elements are nested more than 256 levels deep");
+    }
+}
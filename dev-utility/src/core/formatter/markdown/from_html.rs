@@ -0,0 +1,523 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! HTML to Markdown over the HTML formatter's DOM.
+//!
+//! Block elements become blocks separated by blank lines; runs of inline
+//! content between them become paragraphs with white space collapsed the
+//! way a browser would.
+
+use super::table::{self, Align};
+use crate::core::formatter::html::dom::{Handle, NodeData};
+
+const SKIP: &[&str] = &[
+    "head", "script", "style", "template", "noscript", "title", "meta", "link", "iframe", "object",
+    "embed", "svg", "math", "button", "select", "textarea",
+];
+
+const CONTAINERS: &[&str] = &[
+    "html",
+    "body",
+    "div",
+    "section",
+    "article",
+    "main",
+    "header",
+    "footer",
+    "nav",
+    "aside",
+    "figure",
+    "figcaption",
+    "details",
+    "summary",
+    "form",
+    "fieldset",
+    "address",
+    "dl",
+    "dt",
+    "dd",
+    "center",
+    "li",
+];
+
+const BLOCKS: &[&str] = &[
+    "p",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "pre",
+    "blockquote",
+    "ul",
+    "ol",
+    "hr",
+    "table",
+];
+
+fn name(node: &Handle) -> &str {
+    node.element_name().unwrap_or_default()
+}
+
+fn is_block(node: &Handle) -> bool {
+    let name = name(node);
+    BLOCKS.contains(&name) || CONTAINERS.contains(&name) || SKIP.contains(&name)
+}
+
+/// All descendant text, as written.
+fn text_content(node: &Handle, out: &mut String) {
+    match &node.data {
+        NodeData::Text(text) => out.push_str(&text.borrow()),
+        NodeData::Element { .. } => {
+            for child in node.content() {
+                text_content(&child, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Escape characters that would otherwise start Markdown syntax.
+fn escape(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let intraword = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric());
+        let escape = match c {
+            '\\' | '`' | '*' | '[' | ']' | '<' => true,
+            // GFM never reads an underscore between letters as emphasis.
+            '_' => !(i > 0 && intraword(chars.get(i - 1)) && intraword(chars.get(i + 1))),
+            _ => false,
+        };
+        if escape {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Escape what would make a paragraph start another kind of block.
+fn escape_block_start(text: &str) -> String {
+    if text.starts_with(['#', '>', '-', '+', '=', '|']) {
+        return format!("\\{}", text);
+    }
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 && text[digits..].starts_with(['.', ')']) {
+        return format!("{}\\{}", &text[..digits], &text[digits..]);
+    }
+    text.to_string()
+}
+
+fn collapse(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut space = false;
+    for c in text.chars() {
+        // Non-breaking spaces are content, not layout.
+        if c.is_whitespace() && c != '\u{a0}' {
+            space = true;
+        } else {
+            if space {
+                out.push(' ');
+                space = false;
+            }
+            out.push(c);
+        }
+    }
+    if space {
+        out.push(' ');
+    }
+    out
+}
+
+/// Wrap `inner` in an emphasis marker, keeping edge spaces outside it.
+fn wrap(inner: &str, marker: &str) -> String {
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        return inner.to_string();
+    }
+    let lead = if inner.starts_with(' ') { " " } else { "" };
+    let trail = if inner.ends_with(' ') { " " } else { "" };
+    format!("{}{}{}{}{}", lead, marker, trimmed, marker, trail)
+}
+
+fn code_span(code: &str) -> String {
+    let longest = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest + 1);
+    let pad = if code.starts_with('`') || code.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{}{}{}{}{}", fence, pad, code, pad, fence)
+}
+
+fn destination(url: &str, title: Option<String>) -> String {
+    let url = if url.contains([' ', '(', ')']) {
+        format!("<{}>", url.replace('>', "%3E"))
+    } else {
+        url.to_string()
+    };
+    match title.filter(|t| !t.is_empty()) {
+        Some(title) => format!("{} \"{}\"", url, title.replace('"', "\\\"")),
+        None => url,
+    }
+}
+
+fn inline(node: &Handle) -> String {
+    let children = || node.content().iter().map(inline).collect::<String>();
+    match &node.data {
+        NodeData::Text(text) => escape(&collapse(&text.borrow())),
+        NodeData::Element { .. } => match name(node) {
+            "br" => "\\\n".to_string(),
+            "strong" | "b" => wrap(&children(), "**"),
+            "em" | "i" | "cite" => wrap(&children(), "*"),
+            "del" | "s" | "strike" => wrap(&children(), "~~"),
+            "code" | "kbd" | "samp" | "tt" => {
+                let mut code = String::new();
+                text_content(node, &mut code);
+                code_span(&collapse(&code))
+            }
+            "a" => {
+                let text = children();
+                match node.attr("href") {
+                    Some(href) if text.trim() == escape(&href) && href.contains("://") => {
+                        format!("<{}>", href)
+                    }
+                    Some(href) => format!(
+                        "[{}]({})",
+                        text.trim(),
+                        destination(&href, node.attr("title"))
+                    ),
+                    None => text,
+                }
+            }
+            "img" => format!(
+                "![{}]({})",
+                escape(&node.attr("alt").unwrap_or_default()),
+                destination(&node.attr("src").unwrap_or_default(), node.attr("title"))
+            ),
+            "input" => String::new(),
+            name if SKIP.contains(&name) => String::new(),
+            _ => children(),
+        },
+        _ => String::new(),
+    }
+}
+
+/// Prefix every line, using `rest` after the first.
+fn indent_lines(text: &str, first: &str, rest: &str) -> String {
+    let mut out = String::new();
+    for (i, line) in text.lines().enumerate() {
+        let prefix = if i == 0 { first } else { rest };
+        if line.is_empty() {
+            out.push_str(prefix.trim_end());
+        } else {
+            out.push_str(prefix);
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+    out.pop();
+    out
+}
+
+fn list(node: &Handle) -> String {
+    let ordered = name(node) == "ol";
+    let start: usize = node
+        .attr("start")
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(1);
+    let mut items = Vec::new();
+    let mut loose = false;
+    let lis = node.content().into_iter().filter(|c| name(c) == "li");
+    for (number, item) in (start..).zip(lis) {
+        let mut children = item.content();
+        let mut task = String::new();
+        // A leading checkbox makes this a task list item.
+        if let Some(pos) = children.iter().position(|c| match &c.data {
+            NodeData::Text(text) => !text.borrow().trim().is_empty(),
+            _ => true,
+        }) {
+            let first = &children[pos];
+            if name(first) == "input"
+                && first
+                    .attr("type")
+                    .is_some_and(|t| t.eq_ignore_ascii_case("checkbox"))
+            {
+                task = if first.attr("checked").is_some() {
+                    "[x] ".to_string()
+                } else {
+                    "[ ] ".to_string()
+                };
+                children.drain(..=pos);
+            }
+        }
+        // Paragraphs make the list loose; otherwise nested blocks stay tight.
+        let paragraphs = children.iter().any(|c| name(c) == "p");
+        loose |= paragraphs;
+        let separator = if paragraphs { "\n\n" } else { "\n" };
+        let content = format!("{}{}", task, blocks(&children).join(separator));
+        let marker = if ordered {
+            format!("{}. ", number)
+        } else {
+            "- ".to_string()
+        };
+        items.push(indent_lines(&content, &marker, &" ".repeat(marker.len())));
+    }
+    items.join(if loose { "\n\n" } else { "\n" })
+}
+
+fn table(node: &Handle) -> String {
+    fn rows(node: &Handle, out: &mut Vec<Handle>) {
+        for child in node.content() {
+            match name(&child) {
+                "tr" => out.push(child),
+                "thead" | "tbody" | "tfoot" => rows(&child, out),
+                _ => {}
+            }
+        }
+    }
+    let mut trs = Vec::new();
+    rows(node, &mut trs);
+
+    let mut aligns = Vec::new();
+    let rows: Vec<Vec<String>> = trs
+        .iter()
+        .enumerate()
+        .map(|(r, tr)| {
+            tr.content()
+                .iter()
+                .filter(|c| matches!(name(c), "th" | "td"))
+                .map(|cell| {
+                    if r == 0 {
+                        let style = cell.attr("style").unwrap_or_default().replace(' ', "");
+                        let align = cell.attr("align").unwrap_or_default();
+                        aligns.push(match () {
+                            _ if align == "center" || style.contains("text-align:center") => {
+                                Align::Center
+                            }
+                            _ if align == "right" || style.contains("text-align:right") => {
+                                Align::Right
+                            }
+                            _ if align == "left" || style.contains("text-align:left") => {
+                                Align::Left
+                            }
+                            _ => Align::None,
+                        });
+                    }
+                    let text: String = cell.content().iter().map(inline).collect();
+                    text.trim()
+                        .replace("\\\n", "<br>")
+                        .replace('\n', " ")
+                        .replace('|', "\\|")
+                })
+                .collect()
+        })
+        .collect();
+    if rows.is_empty() {
+        return String::new();
+    }
+    table::render(&rows, &aligns, "").trim_end().to_string()
+}
+
+fn block(node: &Handle) -> Vec<String> {
+    let name = name(node);
+    let text = || {
+        let text: String = node.content().iter().map(inline).collect();
+        text.trim_matches(' ').to_string()
+    };
+    let block = match name {
+        "p" => escape_block_start(&text()),
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = name[1..].parse().unwrap_or(1);
+            format!("{} {}", "#".repeat(level), text().replace("\\\n", " "))
+        }
+        "hr" => "---".to_string(),
+        "pre" => {
+            let mut code = String::new();
+            text_content(node, &mut code);
+            let language = node
+                .content()
+                .iter()
+                .find(|c| self::name(c) == "code")
+                .and_then(|c| c.attr("class"))
+                .and_then(|class| {
+                    class
+                        .split_whitespace()
+                        .find_map(|c| c.strip_prefix("language-").map(str::to_string))
+                })
+                .unwrap_or_default();
+            let longest = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+            let fence = "`".repeat(longest.max(2) + 1);
+            format!(
+                "{}{}\n{}\n{}",
+                fence,
+                language,
+                code.trim_end_matches('\n'),
+                fence
+            )
+        }
+        "blockquote" => indent_lines(&blocks(&node.content()).join("\n\n"), "> ", "> "),
+        "ul" | "ol" => list(node),
+        "table" => table(node),
+        _ if SKIP.contains(&name) => String::new(),
+        _ => return blocks(&node.content()),
+    };
+    if block.trim().is_empty() {
+        Vec::new()
+    } else {
+        vec![block]
+    }
+}
+
+fn blocks(nodes: &[Handle]) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut paragraph = String::new();
+    let flush = |paragraph: &mut String, out: &mut Vec<String>| {
+        let text = paragraph.trim_matches(' ');
+        let text = text.trim_end_matches("\\\n");
+        if !text.is_empty() {
+            out.push(escape_block_start(text));
+        }
+        paragraph.clear();
+    };
+    for node in nodes {
+        if is_block(node) {
+            flush(&mut paragraph, &mut out);
+            out.extend(block(node));
+        } else {
+            let text = inline(node);
+            // Adjacent runs each keep their collapsed edge spaces.
+            if paragraph.ends_with([' ', '\n']) {
+                paragraph.push_str(text.trim_start_matches(' '));
+            } else {
+                paragraph.push_str(&text);
+            }
+        }
+    }
+    flush(&mut paragraph, &mut out);
+    out
+}
+
+pub(super) fn convert(roots: &[Handle]) -> String {
+    let mut out = blocks(roots).join("\n\n");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::formatter::html;
+
+    fn md(input: &str) -> String {
+        let (_dom, roots) = html::parse(input);
+        convert(&roots)
+    }
+
+    #[test]
+    fn tables() {
+        assert_eq!(
+            md("<table><thead><tr><th>a</th><th align=\"right\">b</th>\
+                <th style=\"text-align: center\">c</th></tr></thead>\
+                <tbody><tr><td>1 | 2</td><td><b>x</b><br>y</td><td></td></tr></tbody></table>"),
+            "| a      |          b |  c  |\n\
+             | ------ | ---------: | :-: |\n\
+             | 1 \\| 2 | **x**<br>y |     |\n"
+        );
+        // Rows outside a section count, and short rows are padded out.
+        assert_eq!(
+            md("<table><tr><td>a</td></tr><tr><td>b</td><td>c</td></tr></table>"),
+            "| a   |     |\n| --- | --- |\n| b   | c   |\n"
+        );
+        assert_eq!(md("<table></table>"), "");
+    }
+
+    #[test]
+    fn nested_lists() {
+        assert_eq!(
+            md(
+                "<ul><li>a<ul><li>b<ol start=\"9\"><li>c</li><li>d</li></ol></li></ul></li>\
+                <li>e</li></ul>"
+            ),
+            "- a\n  - b\n    9. c\n    10. d\n- e\n"
+        );
+        // A paragraph in any item makes the whole list loose.
+        assert_eq!(
+            md("<ol><li><p>one</p><p>more</p></li><li>two</li></ol>"),
+            "1. one\n\n   more\n\n2. two\n"
+        );
+        assert_eq!(
+            md("<ul><li><blockquote>q</blockquote></li></ul>"),
+            "- > q\n"
+        );
+    }
+
+    #[test]
+    fn task_list_checkboxes() {
+        assert_eq!(
+            md("<ul><li><input type=\"checkbox\" checked> done</li>\
+                <li> <input type=\"CHECKBOX\">todo</li>\
+                <li>plain <input type=\"checkbox\"></li>\
+                <li><input type=\"text\">field</li></ul>"),
+            "- [x] done\n- [ ] todo\n- plain\n- field\n"
+        );
+    }
+
+    #[test]
+    fn code_blocks_and_spans() {
+        assert_eq!(
+            md("<pre><code class=\"hl language-js\">a &lt; b\n\n  c\n</code></pre>"),
+            "```js\na < b\n\n  c\n```\n"
+        );
+        // The fence outgrows any backtick run inside.
+        assert_eq!(md("<pre>x ```` y</pre>"), "`````\nx ```` y\n`````\n");
+        assert_eq!(
+            md("<p><code>a`b</code> <code>`c</code> <kbd>*x*</kbd></p>"),
+            "``a`b`` `` `c `` `*x*`\n"
+        );
+        assert_eq!(
+            md("<ul><li><pre>one\ntwo</pre></li></ul>"),
+            "- ```\n  one\n  two\n  ```\n"
+        );
+    }
+
+    #[test]
+    fn escapes_metacharacters() {
+        assert_eq!(
+            md("<p>*a* _b_ snake_case [c](d) `e` &lt;f&gt; \\</p>"),
+            "\\*a\\* \\_b\\_ snake_case \\[c\\](d) \\`e\\` \\<f> \\\\\n"
+        );
+        // Only at the start of a paragraph do these begin another block.
+        assert_eq!(
+            md("<p># h</p><p>- i</p><p>+ j</p><p>&gt; k</p><p>12. l</p><p>3) m</p><p>a # - 1.</p>"),
+            "\\# h\n\n\\- i\n\n\\+ j\n\n\\> k\n\n12\\. l\n\n3\\) m\n\na # - 1.\n"
+        );
+        assert_eq!(
+            md("<h1>*x*</h1><p><a href=\"/a b\" title='say \"hi\"'>[t]</a></p>"),
+            "# \\*x\\*\n\n[\\[t\\]](</a b> \"say \\\"hi\\\"\")\n"
+        );
+    }
+
+    #[test]
+    fn inline_formatting_and_whitespace() {
+        assert_eq!(
+            md("<div>  a\n  <b> bold </b><i>it</i><s>gone</s>\u{a0}\u{a0}b<br>c</div><hr>"),
+            "a **bold** *it*~~gone~~\u{a0}\u{a0}b\\\nc\n\n---\n"
+        );
+        assert_eq!(
+            md("<p><a href=\"https://x.io\">https://x.io</a> <img src=\"i.png\" alt=\"*\"></p>"),
+            "<https://x.io> ![\\*](i.png)\n"
+        );
+        assert_eq!(md("<script>x</script><style>y</style>"), "");
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Pipe table alignment.
+
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Align {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// Split a table row on unescaped pipes, dropping the optional outer ones.
+fn cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                cell.push(c);
+                if let Some(next) = chars.next() {
+                    cell.push(next);
+                }
+            }
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            c => cell.push(c),
+        }
+    }
+    // A trailing pipe closes the last cell rather than opening a new one.
+    if !cell.trim().is_empty()
+        || !line.trim_end().ends_with('|')
+        || line.trim_end().ends_with("\\|")
+    {
+        cells.push(cell.trim().to_string());
+    }
+    cells
+}
+
+/// Parse a delimiter row such as `| :-- | :-: | --: |`.
+fn delimiter_row(line: &str) -> Option<Vec<Align>> {
+    if !line.contains('-') {
+        return None;
+    }
+    cells(line)
+        .iter()
+        .map(|cell| {
+            let left = cell.starts_with(':');
+            let right = cell.ends_with(':');
+            let dashes = cell.trim_matches(':');
+            if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                return None;
+            }
+            Some(match (left, right) {
+                (true, true) => Align::Center,
+                (true, false) => Align::Left,
+                (false, true) => Align::Right,
+                (false, false) => Align::None,
+            })
+        })
+        .collect()
+}
+
+/// Print rows as an aligned pipe table; the first row is the header.
+pub(super) fn render(rows: &[Vec<String>], aligns: &[Align], indent: &str) -> String {
+    let columns = rows
+        .iter()
+        .map(Vec::len)
+        .chain([aligns.len()])
+        .max()
+        .unwrap_or(0);
+    let mut widths = vec![3; columns];
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.width());
+        }
+    }
+    let align = |i: usize| aligns.get(i).copied().unwrap_or(Align::None);
+
+    let mut out = String::new();
+    let mut line = |cells: Vec<String>| {
+        out.push_str(indent);
+        out.push('|');
+        for cell in cells {
+            out.push(' ');
+            out.push_str(&cell);
+            out.push_str(" |");
+        }
+        out.push('\n');
+    };
+    let pad = |i: usize, cell: &str| {
+        let space = widths[i] - cell.width();
+        let (before, after) = match align(i) {
+            Align::Right => (space, 0),
+            Align::Center => (space / 2, space - space / 2),
+            Align::None | Align::Left => (0, space),
+        };
+        format!("{}{}{}", " ".repeat(before), cell, " ".repeat(after))
+    };
+
+    let empty = String::new();
+    let cells_of = |row: &Vec<String>| -> Vec<String> {
+        (0..columns)
+            .map(|i| pad(i, row.get(i).unwrap_or(&empty)))
+            .collect()
+    };
+    let Some((header, body)) = rows.split_first() else {
+        return out;
+    };
+    line(cells_of(header));
+    line(
+        (0..columns)
+            .map(|i| {
+                let w = widths[i];
+                match align(i) {
+                    Align::None => "-".repeat(w),
+                    Align::Left => format!(":{}", "-".repeat(w - 1)),
+                    Align::Right => format!("{}:", "-".repeat(w - 1)),
+                    Align::Center => format!(":{}:", "-".repeat(w - 2)),
+                }
+            })
+            .collect(),
+    );
+    for row in body {
+        line(cells_of(row));
+    }
+    out
+}
+
+pub(super) fn format_tables(input: &str) -> String {
+    let lines: Vec<&str> = input.split_inclusive('\n').collect();
+    let mut out = String::with_capacity(input.len());
+    let mut fence: Option<String> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim_start();
+
+        if let Some(marker) = &fence {
+            if trimmed.trim_end().starts_with(marker.as_str())
+                && trimmed
+                    .trim_end()
+                    .chars()
+                    .all(|c| c == marker.as_bytes()[0] as char)
+            {
+                fence = None;
+            }
+            out.push_str(line);
+            i += 1;
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            let c = trimmed.as_bytes()[0] as char;
+            fence = Some(trimmed.chars().take_while(|&x| x == c).collect());
+            out.push_str(line);
+            i += 1;
+            continue;
+        }
+
+        let header = cells(line);
+        let aligns = lines.get(i + 1).and_then(|next| delimiter_row(next));
+        match aligns {
+            Some(aligns) if line.contains('|') && header.len() == aligns.len() => {
+                let indent = &line[..line.len() - trimmed.len()];
+                let mut rows = vec![header];
+                let mut end = i + 2;
+                while let Some(row) = lines.get(end) {
+                    if row.trim().is_empty() || !row.contains('|') {
+                        break;
+                    }
+                    rows.push(cells(row));
+                    end += 1;
+                }
+                let mut table = render(&rows, &aligns, indent);
+                if !lines[end - 1].ends_with('\n') {
+                    table.pop();
+                }
+                out.push_str(&table);
+                i = end;
+            }
+            _ => {
+                out.push_str(line);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligns_tables_outside_code() {
+        let input = "Intro\n\n|Name|Qty|Note|\n|:-:|--:|---|\n|apple|3|red \\| green|\n|梨|12|\n\n```\n|a|b|\n|-|-|\n```\n";
+        assert_eq!(
+            format_tables(input),
+            "Intro\n\n| Name  | Qty | Note         |\n| :---: | --: | ------------ |\n\
+             | apple |   3 | red \\| green |\n|  梨   |  12 |              |\n\n```\n|a|b|\n|-|-|\n```\n"
+        );
+    }
+}
@@ -16,4 +16,18 @@ pub use css::*;
 pub mod json;
 pub use json::*;
 pub mod html;
-pub use html::*;
\ No newline at end of file
+pub use html::*;
+pub mod markdown;
+pub use markdown::*;
+pub mod query;
+pub use query::*;
+pub mod schema;
+pub use schema::*;
+pub mod sql;
+pub use sql::*;
+pub mod toml;
+pub use toml::*;
+pub mod xml;
+pub use xml::*;
+pub mod yaml;
+pub use yaml::*;
@@ -0,0 +1,1180 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! A JMESPath interpreter over `serde_json::Value`.
+//!
+//! Covers the whole expression grammar of the specification (projections,
+//! filters, slices, multi-selects, pipes, expression references) and its
+//! built-in function library. Parsing is a Pratt parser using the binding
+//! powers listed in the spec's grammar notes.
+
+use crate::error::UtilityError;
+use serde_json::{Map, Number, Value};
+use std::cmp::Ordering;
+
+/// Deeper expressions are rejected, since parsing and evaluation recurse.
+const MAX_DEPTH: usize = 64;
+
+pub(crate) fn search(expression: &str, data: &Value) -> Result<Value, UtilityError> {
+    let ast = Parser::new(expression)?.parse()?;
+    eval(&ast, data)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    Number(i64),
+    Literal(Value),
+    Dot,
+    Star,
+    Flatten,
+    LBracket,
+    Filter,
+    RBracket,
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    Comma,
+    Colon,
+    Pipe,
+    Or,
+    And,
+    Not,
+    At,
+    Ampersand,
+    Cmp(Comparator),
+    Eof,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Token {
+    /// Left binding power.
+    fn lbp(&self) -> u8 {
+        match self {
+            Token::Pipe => 1,
+            Token::Or => 2,
+            Token::And => 3,
+            Token::Cmp(_) => 5,
+            Token::Flatten => 9,
+            Token::Star => 20,
+            Token::Filter => 21,
+            Token::Dot => 40,
+            Token::Not => 45,
+            Token::LBrace => 50,
+            Token::LBracket => 55,
+            Token::LParen => 60,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Ast {
+    Identity,
+    Field(String),
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Subexpr(Box<Ast>, Box<Ast>),
+    Projection(Box<Ast>, Box<Ast>),
+    ObjectValues(Box<Ast>),
+    Flatten(Box<Ast>),
+    Condition(Box<Ast>, Box<Ast>),
+    MultiList(Vec<Ast>),
+    MultiHash(Vec<(String, Ast)>),
+    Literal(Value),
+    Not(Box<Ast>),
+    And(Box<Ast>, Box<Ast>),
+    Or(Box<Ast>, Box<Ast>),
+    Compare(Comparator, Box<Ast>, Box<Ast>),
+    Function(String, Vec<Ast>),
+    Expref(Box<Ast>),
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, UtilityError> {
+    let chars: Vec<(usize, char)> = input.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let peek = |i: usize| chars.get(i).map(|&(_, c)| c);
+
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        let two = |next: char| peek(i + 1) == Some(next);
+        let (token, len) = match c {
+            ' ' | '\t' | '\n' | '\r' => {
+                i += 1;
+                continue;
+            }
+            '.' => (Token::Dot, 1),
+            '*' => (Token::Star, 1),
+            '@' => (Token::At, 1),
+            ',' => (Token::Comma, 1),
+            ':' => (Token::Colon, 1),
+            '(' => (Token::LParen, 1),
+            ')' => (Token::RParen, 1),
+            '{' => (Token::LBrace, 1),
+            '}' => (Token::RBrace, 1),
+            ']' => (Token::RBracket, 1),
+            '[' if two(']') => (Token::Flatten, 2),
+            '[' if two('?') => (Token::Filter, 2),
+            '[' => (Token::LBracket, 1),
+            '|' if two('|') => (Token::Or, 2),
+            '|' => (Token::Pipe, 1),
+            '&' if two('&') => (Token::And, 2),
+            '&' => (Token::Ampersand, 1),
+            '!' if two('=') => (Token::Cmp(Comparator::Ne), 2),
+            '!' => (Token::Not, 1),
+            '=' if two('=') => (Token::Cmp(Comparator::Eq), 2),
+            '<' if two('=') => (Token::Cmp(Comparator::Le), 2),
+            '<' => (Token::Cmp(Comparator::Lt), 1),
+            '>' if two('=') => (Token::Cmp(Comparator::Ge), 2),
+            '>' => (Token::Cmp(Comparator::Gt), 1),
+            '-' | '0'..='9' => {
+                let mut end = i + 1;
+                while peek(end).is_some_and(|c| c.is_ascii_digit()) {
+                    end += 1;
+                }
+                let text: String = chars[i..end].iter().map(|&(_, c)| c).collect();
+                let n = text
+                    .parse()
+                    .map_err(|_| syntax_error(pos, &format!("invalid number '{}'", text)))?;
+                (Token::Number(n), end - i)
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut end = i + 1;
+                while peek(end).is_some_and(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    end += 1;
+                }
+                let name = chars[i..end].iter().map(|&(_, c)| c).collect();
+                (Token::Identifier(name), end - i)
+            }
+            '"' | '\'' | '`' => {
+                let mut end = i + 1;
+                let mut raw = String::new();
+                loop {
+                    match peek(end) {
+                        None => return Err(syntax_error(pos, "unterminated quote")),
+                        Some('\\') => match peek(end + 1) {
+                            // JSON identifiers keep every escape for serde_json;
+                            // raw strings and literals only unescape their delimiter.
+                            Some(next) if c == '"' => {
+                                raw.push('\\');
+                                raw.push(next);
+                                end += 2;
+                            }
+                            Some(next) if next == c => {
+                                raw.push(next);
+                                end += 2;
+                            }
+                            _ => {
+                                raw.push('\\');
+                                end += 1;
+                            }
+                        },
+                        Some(ch) if ch == c => break,
+                        Some(ch) => {
+                            raw.push(ch);
+                            end += 1;
+                        }
+                    }
+                }
+                let token = match c {
+                    '"' => {
+                        Token::Identifier(serde_json::from_str(&format!("\"{}\"", raw)).map_err(
+                            |e| syntax_error(pos, &format!("invalid identifier: {}", e)),
+                        )?)
+                    }
+                    '\'' => Token::Literal(Value::String(raw)),
+                    _ => Token::Literal(
+                        serde_json::from_str(raw.trim())
+                            .map_err(|e| syntax_error(pos, &format!("invalid literal: {}", e)))?,
+                    ),
+                };
+                (token, end + 1 - i)
+            }
+            other => {
+                return Err(syntax_error(
+                    pos,
+                    &format!("unexpected character '{}'", other),
+                ))
+            }
+        };
+        tokens.push((pos, token));
+        i += len;
+    }
+    tokens.push((input.len(), Token::Eof));
+    Ok(tokens)
+}
+
+fn syntax_error(pos: usize, msg: &str) -> UtilityError {
+    UtilityError::ParseError(format!(
+        "JMESPath syntax error at position {}: {}",
+        pos, msg
+    ))
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    depth: usize,  // Calls to `expr` currently open
+    height: usize, // Tallest tree returned by `expr` since it was reset
+}
+
+impl Parser {
+    fn new(expression: &str) -> Result<Self, UtilityError> {
+        Ok(Self {
+            tokens: tokenize(expression)?,
+            pos: 0,
+            depth: 0,
+            height: 0,
+        })
+    }
+
+    fn parse(mut self) -> Result<Ast, UtilityError> {
+        let ast = self.expr(0)?;
+        match self.peek() {
+            Token::Eof => Ok(ast),
+            other => Err(self.error(&format!("unexpected {:?}", other))),
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].1
+    }
+
+    fn peek_at(&self, offset: usize) -> &Token {
+        self.tokens
+            .get(self.pos + offset)
+            .map(|(_, t)| t)
+            .unwrap_or(&Token::Eof)
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].1.clone();
+        if token != Token::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// Undo the `advance` that returned `token`, so an error points at it.
+    /// `advance` never moves past the end, so there is nothing to undo then.
+    fn back(&mut self, token: &Token) {
+        if *token != Token::Eof {
+            self.pos -= 1;
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), UtilityError> {
+        if *self.peek() == expected {
+            self.advance();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {:?}, found {:?}", expected, self.peek())))
+        }
+    }
+
+    fn error(&self, msg: &str) -> UtilityError {
+        syntax_error(self.tokens[self.pos].0, msg)
+    }
+
+    /// Parse an expression, keeping both the nesting and the height of the
+    /// tree under `MAX_DEPTH`: operators chain to the left without nesting
+    /// calls, so the height is counted separately, one level per operator.
+    fn expr(&mut self, rbp: u8) -> Result<Ast, UtilityError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("expression nested too deeply"));
+        }
+        self.depth += 1;
+        let outer = std::mem::take(&mut self.height);
+        let mut left = self.nud()?;
+        let mut height = self.height + 1;
+        while rbp < self.peek().lbp() {
+            self.height = 0;
+            left = self.led(left)?;
+            height = height.max(self.height) + 1;
+            if height > MAX_DEPTH {
+                return Err(self.error("expression nested too deeply"));
+            }
+        }
+        self.depth -= 1;
+        self.height = outer.max(height);
+        Ok(left)
+    }
+
+    fn nud(&mut self) -> Result<Ast, UtilityError> {
+        match self.advance() {
+            Token::At => Ok(Ast::Identity),
+            Token::Identifier(name) => Ok(Ast::Field(name)),
+            Token::Literal(value) => Ok(Ast::Literal(value)),
+            Token::Star => self.wildcard_values(Ast::Identity),
+            Token::Flatten => self.flatten(Ast::Identity),
+            Token::Filter => self.filter(Ast::Identity),
+            Token::LBrace => self.multi_hash(),
+            Token::Ampersand => Ok(Ast::Expref(Box::new(self.expr(0)?))),
+            Token::Not => Ok(Ast::Not(Box::new(self.expr(Token::Not.lbp())?))),
+            Token::LParen => {
+                let inner = self.expr(0)?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Token::LBracket => match (self.peek(), self.peek_at(1)) {
+                (Token::Number(_) | Token::Colon, _) => self.index_or_slice(Ast::Identity),
+                (Token::Star, Token::RBracket) => {
+                    self.pos += 2;
+                    self.projection(Ast::Identity, Token::Star.lbp())
+                }
+                _ => self.multi_list(),
+            },
+            other => {
+                self.back(&other);
+                Err(self.error(&format!("unexpected {:?}", other)))
+            }
+        }
+    }
+
+    fn led(&mut self, left: Ast) -> Result<Ast, UtilityError> {
+        match self.advance() {
+            Token::Dot => {
+                if *self.peek() == Token::Star {
+                    self.advance();
+                    self.wildcard_values(left)
+                } else {
+                    let right = self.dot_rhs(Token::Dot.lbp())?;
+                    Ok(Ast::Subexpr(Box::new(left), Box::new(right)))
+                }
+            }
+            Token::LBracket => match (self.peek(), self.peek_at(1)) {
+                (Token::Star, Token::RBracket) => {
+                    self.pos += 2;
+                    self.projection(left, Token::Star.lbp())
+                }
+                _ => self.index_or_slice(left),
+            },
+            Token::Flatten => self.flatten(left),
+            Token::Filter => self.filter(left),
+            Token::Pipe => {
+                let right = self.expr(Token::Pipe.lbp())?;
+                Ok(Ast::Subexpr(Box::new(left), Box::new(right)))
+            }
+            Token::Or => Ok(Ast::Or(
+                Box::new(left),
+                Box::new(self.expr(Token::Or.lbp())?),
+            )),
+            Token::And => Ok(Ast::And(
+                Box::new(left),
+                Box::new(self.expr(Token::And.lbp())?),
+            )),
+            Token::Cmp(op) => Ok(Ast::Compare(
+                op,
+                Box::new(left),
+                Box::new(self.expr(Token::Cmp(op).lbp())?),
+            )),
+            Token::LParen => {
+                let Ast::Field(name) = left else {
+                    self.pos -= 1;
+                    return Err(self.error("only identifiers can be called"));
+                };
+                let mut args = Vec::new();
+                while *self.peek() != Token::RParen {
+                    args.push(self.expr(0)?);
+                    if *self.peek() == Token::Comma {
+                        self.advance();
+                    } else {
+                        break;
+                    }
+                }
+                self.expect(Token::RParen)?;
+                Ok(Ast::Function(name, args))
+            }
+            other => {
+                self.back(&other);
+                Err(self.error(&format!("unexpected {:?}", other)))
+            }
+        }
+    }
+
+    /// What may follow a `.`: an identifier-led expression or a multi-select.
+    fn dot_rhs(&mut self, bp: u8) -> Result<Ast, UtilityError> {
+        match self.peek() {
+            Token::LBracket => {
+                self.advance();
+                self.multi_list()
+            }
+            Token::LBrace => {
+                self.advance();
+                self.multi_hash()
+            }
+            Token::Identifier(_) | Token::Star | Token::Ampersand | Token::At => self.expr(bp),
+            other => Err(self.error(&format!("unexpected {:?} after '.'", other))),
+        }
+    }
+
+    /// The expression applied to each element of a projection.
+    fn projection_rhs(&mut self, bp: u8) -> Result<Ast, UtilityError> {
+        match self.peek() {
+            t if t.lbp() < 10 => Ok(Ast::Identity),
+            Token::LBracket | Token::Filter | Token::Flatten => self.expr(bp),
+            Token::Dot => {
+                self.advance();
+                self.dot_rhs(bp)
+            }
+            other => Err(self.error(&format!("unexpected {:?} after projection", other))),
+        }
+    }
+
+    fn projection(&mut self, lhs: Ast, bp: u8) -> Result<Ast, UtilityError> {
+        let rhs = self.projection_rhs(bp)?;
+        Ok(Ast::Projection(Box::new(lhs), Box::new(rhs)))
+    }
+
+    fn wildcard_values(&mut self, lhs: Ast) -> Result<Ast, UtilityError> {
+        self.projection(Ast::ObjectValues(Box::new(lhs)), Token::Star.lbp())
+    }
+
+    fn flatten(&mut self, lhs: Ast) -> Result<Ast, UtilityError> {
+        self.projection(Ast::Flatten(Box::new(lhs)), Token::Flatten.lbp())
+    }
+
+    fn filter(&mut self, lhs: Ast) -> Result<Ast, UtilityError> {
+        let predicate = self.expr(0)?;
+        self.expect(Token::RBracket)?;
+        let then = self.projection_rhs(Token::Filter.lbp())?;
+        Ok(Ast::Projection(
+            Box::new(lhs),
+            Box::new(Ast::Condition(Box::new(predicate), Box::new(then))),
+        ))
+    }
+
+    fn index_or_slice(&mut self, lhs: Ast) -> Result<Ast, UtilityError> {
+        let mut parts = [None, None, None];
+        let mut colons = 0;
+        loop {
+            match self.advance() {
+                Token::Number(n) => {
+                    if parts[colons].is_some() {
+                        return Err(self.error("expected ':' or ']'"));
+                    }
+                    parts[colons] = Some(n);
+                }
+                Token::Colon if colons < 2 => colons += 1,
+                Token::RBracket => break,
+                other => {
+                    self.back(&other);
+                    return Err(self.error(&format!("unexpected {:?} in index", other)));
+                }
+            }
+        }
+        if colons == 0 {
+            let index = parts[0].ok_or_else(|| self.error("empty index"))?;
+            return Ok(chain(lhs, Ast::Index(index)));
+        }
+        if parts[2] == Some(0) {
+            return Err(self.error("slice step cannot be 0"));
+        }
+        let slice = Ast::Slice(parts[0], parts[1], parts[2]);
+        self.projection(chain(lhs, slice), Token::Star.lbp())
+    }
+
+    fn multi_list(&mut self) -> Result<Ast, UtilityError> {
+        let mut items = Vec::new();
+        loop {
+            items.push(self.expr(0)?);
+            match self.advance() {
+                Token::Comma => continue,
+                Token::RBracket => break,
+                other => {
+                    self.back(&other);
+                    return Err(self.error(&format!("expected ',' or ']', found {:?}", other)));
+                }
+            }
+        }
+        Ok(Ast::MultiList(items))
+    }
+
+    fn multi_hash(&mut self) -> Result<Ast, UtilityError> {
+        let mut pairs = Vec::new();
+        loop {
+            let key = match self.advance() {
+                Token::Identifier(key) => key,
+                other => {
+                    self.back(&other);
+                    return Err(self.error("expected a key"));
+                }
+            };
+            self.expect(Token::Colon)?;
+            pairs.push((key, self.expr(0)?));
+            match self.advance() {
+                Token::Comma => continue,
+                Token::RBrace => break,
+                other => {
+                    self.back(&other);
+                    return Err(self.error(&format!("expected ',' or '}}', found {:?}", other)));
+                }
+            }
+        }
+        Ok(Ast::MultiHash(pairs))
+    }
+}
+
+fn chain(lhs: Ast, rhs: Ast) -> Ast {
+    match lhs {
+        Ast::Identity => rhs,
+        lhs => Ast::Subexpr(Box::new(lhs), Box::new(rhs)),
+    }
+}
+
+fn eval(ast: &Ast, data: &Value) -> Result<Value, UtilityError> {
+    Ok(match ast {
+        Ast::Identity => data.clone(),
+        Ast::Field(name) => data.get(name).cloned().unwrap_or(Value::Null),
+        Ast::Literal(value) => value.clone(),
+        Ast::Index(index) => match data {
+            Value::Array(items) => resolve_index(*index, items.len())
+                .map(|i| items[i].clone())
+                .unwrap_or(Value::Null),
+            _ => Value::Null,
+        },
+        Ast::Slice(start, stop, step) => match data {
+            Value::Array(items) => Value::Array(slice(items, *start, *stop, *step)),
+            _ => Value::Null,
+        },
+        Ast::Subexpr(lhs, rhs) => eval(rhs, &eval(lhs, data)?)?,
+        Ast::Projection(lhs, rhs) => match eval(lhs, data)? {
+            Value::Array(items) => {
+                let mut out = Vec::with_capacity(items.len());
+                for item in &items {
+                    let value = eval(rhs, item)?;
+                    if !value.is_null() {
+                        out.push(value);
+                    }
+                }
+                Value::Array(out)
+            }
+            _ => Value::Null,
+        },
+        Ast::ObjectValues(inner) => match eval(inner, data)? {
+            Value::Object(map) => Value::Array(map.into_iter().map(|(_, v)| v).collect()),
+            _ => Value::Null,
+        },
+        Ast::Flatten(inner) => match eval(inner, data)? {
+            Value::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .flat_map(|item| match item {
+                        Value::Array(nested) => nested,
+                        other => vec![other],
+                    })
+                    .collect(),
+            ),
+            _ => Value::Null,
+        },
+        Ast::Condition(predicate, then) => {
+            if is_truthy(&eval(predicate, data)?) {
+                eval(then, data)?
+            } else {
+                Value::Null
+            }
+        }
+        Ast::MultiList(items) if !data.is_null() => Value::Array(
+            items
+                .iter()
+                .map(|item| eval(item, data))
+                .collect::<Result<_, _>>()?,
+        ),
+        Ast::MultiHash(pairs) if !data.is_null() => {
+            let mut map = Map::new();
+            for (key, item) in pairs {
+                map.insert(key.clone(), eval(item, data)?);
+            }
+            Value::Object(map)
+        }
+        Ast::MultiList(_) | Ast::MultiHash(_) => Value::Null,
+        Ast::Not(inner) => Value::Bool(!is_truthy(&eval(inner, data)?)),
+        Ast::And(lhs, rhs) => {
+            let left = eval(lhs, data)?;
+            if is_truthy(&left) {
+                eval(rhs, data)?
+            } else {
+                left
+            }
+        }
+        Ast::Or(lhs, rhs) => {
+            let left = eval(lhs, data)?;
+            if is_truthy(&left) {
+                left
+            } else {
+                eval(rhs, data)?
+            }
+        }
+        Ast::Compare(op, lhs, rhs) => compare(*op, &eval(lhs, data)?, &eval(rhs, data)?),
+        Ast::Function(name, args) => call(name, args, data)?,
+        Ast::Expref(_) => {
+            return Err(runtime_error(
+                "expression references are only valid as function arguments",
+            ))
+        }
+    })
+}
+
+fn runtime_error(msg: &str) -> UtilityError {
+    UtilityError::InvalidInput(format!("JMESPath error: {}", msg))
+}
+
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let resolved = if index < 0 { len as i64 + index } else { index };
+    (0..len as i64)
+        .contains(&resolved)
+        .then_some(resolved as usize)
+}
+
+fn slice(items: &[Value], start: Option<i64>, stop: Option<i64>, step: Option<i64>) -> Vec<Value> {
+    let len = items.len() as i64;
+    let step = step.unwrap_or(1);
+    // Negative bounds count from the end, then clamp to the walkable range.
+    let adjust = |n: i64| {
+        let n = if n < 0 { n + len } else { n };
+        if step > 0 {
+            n.clamp(0, len)
+        } else {
+            n.clamp(-1, len - 1)
+        }
+    };
+    let (default_start, default_stop) = if step > 0 { (0, len) } else { (len - 1, -1) };
+    let start = start.map_or(default_start, adjust);
+    let stop = stop.map_or(default_stop, adjust);
+
+    let mut out = Vec::new();
+    let mut i = start;
+    while (step > 0 && i < stop) || (step < 0 && i > stop) {
+        out.push(items[i as usize].clone());
+        i += step;
+    }
+    out
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(o) => !o.is_empty(),
+        Value::Number(_) => true,
+    }
+}
+
+fn compare(op: Comparator, left: &Value, right: &Value) -> Value {
+    match op {
+        Comparator::Eq => Value::Bool(json_eq(left, right)),
+        Comparator::Ne => Value::Bool(!json_eq(left, right)),
+        _ => match (left.as_f64(), right.as_f64()) {
+            (Some(l), Some(r)) => Value::Bool(match op {
+                Comparator::Lt => l < r,
+                Comparator::Le => l <= r,
+                Comparator::Gt => l > r,
+                _ => l >= r,
+            }),
+            _ => Value::Null,
+        },
+    }
+}
+
+/// Equality where `1` and `1.0` are the same number.
+fn json_eq(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_f64() == r.as_f64(),
+        (Value::Array(l), Value::Array(r)) => {
+            l.len() == r.len() && l.iter().zip(r).all(|(a, b)| json_eq(a, b))
+        }
+        (Value::Object(l), Value::Object(r)) => {
+            l.len() == r.len()
+                && l.iter()
+                    .all(|(k, v)| r.get(k).is_some_and(|other| json_eq(v, other)))
+        }
+        _ => left == right,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        Value::Number((n as i64).into())
+    } else {
+        Number::from_f64(n).map_or(Value::Null, Value::Number)
+    }
+}
+
+fn call(name: &str, args: &[Ast], data: &Value) -> Result<Value, UtilityError> {
+    let arity = |n: usize| {
+        if args.len() == n {
+            Ok(())
+        } else {
+            Err(runtime_error(&format!(
+                "{}() takes {} argument(s), got {}",
+                name,
+                n,
+                args.len()
+            )))
+        }
+    };
+    let invalid = |expected: &str| {
+        runtime_error(&format!(
+            "invalid type for {}(), expected {}",
+            name, expected
+        ))
+    };
+    let arg = |i: usize| eval(&args[i], data);
+    let expref = |i: usize| match &args[i] {
+        Ast::Expref(ast) => Ok(ast.as_ref()),
+        _ => Err(invalid("an expression reference (&expr)")),
+    };
+    let array = |v: Value| match v {
+        Value::Array(items) => Ok(items),
+        _ => Err(invalid("array")),
+    };
+    let numbers = |items: &[Value]| {
+        items
+            .iter()
+            .map(|v| v.as_f64().ok_or_else(|| invalid("array of numbers")))
+            .collect::<Result<Vec<f64>, _>>()
+    };
+
+    match name {
+        "abs" | "ceil" | "floor" => {
+            arity(1)?;
+            let n = arg(0)?.as_f64().ok_or_else(|| invalid("number"))?;
+            Ok(number(match name {
+                "abs" => n.abs(),
+                "ceil" => n.ceil(),
+                _ => n.floor(),
+            }))
+        }
+        "avg" | "sum" => {
+            arity(1)?;
+            let items = numbers(&array(arg(0)?)?)?;
+            let total: f64 = items.iter().sum();
+            Ok(match name {
+                "sum" => number(total),
+                _ if items.is_empty() => Value::Null,
+                _ => number(total / items.len() as f64),
+            })
+        }
+        "contains" => {
+            arity(2)?;
+            let needle = arg(1)?;
+            Ok(Value::Bool(match arg(0)? {
+                Value::Array(items) => items.iter().any(|v| json_eq(v, &needle)),
+                Value::String(s) => needle.as_str().is_some_and(|n| s.contains(n)),
+                _ => return Err(invalid("array or string")),
+            }))
+        }
+        "starts_with" | "ends_with" => {
+            arity(2)?;
+            let (subject, affix) = (arg(0)?, arg(1)?);
+            let (Some(subject), Some(affix)) = (subject.as_str(), affix.as_str()) else {
+                return Err(invalid("string"));
+            };
+            Ok(Value::Bool(if name == "starts_with" {
+                subject.starts_with(affix)
+            } else {
+                subject.ends_with(affix)
+            }))
+        }
+        "join" => {
+            arity(2)?;
+            let glue = arg(0)?;
+            let glue = glue.as_str().ok_or_else(|| invalid("string"))?;
+            let parts = array(arg(1)?)?
+                .iter()
+                .map(|v| v.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| invalid("array of strings"))?;
+            Ok(Value::String(parts.join(glue)))
+        }
+        "keys" | "values" => {
+            arity(1)?;
+            let Value::Object(map) = arg(0)? else {
+                return Err(invalid("object"));
+            };
+            Ok(Value::Array(if name == "keys" {
+                map.into_iter().map(|(k, _)| Value::String(k)).collect()
+            } else {
+                map.into_iter().map(|(_, v)| v).collect()
+            }))
+        }
+        "length" => {
+            arity(1)?;
+            Ok(Value::from(match arg(0)? {
+                Value::String(s) => s.chars().count(),
+                Value::Array(a) => a.len(),
+                Value::Object(o) => o.len(),
+                _ => return Err(invalid("string, array or object")),
+            }))
+        }
+        "map" => {
+            arity(2)?;
+            let ast = expref(0)?;
+            Ok(Value::Array(
+                array(arg(1)?)?
+                    .iter()
+                    .map(|item| eval(ast, item))
+                    .collect::<Result<_, _>>()?,
+            ))
+        }
+        "max" | "min" => {
+            arity(1)?;
+            let items = array(arg(0)?)?;
+            let best = extreme(&items, |v| Ok(v.clone()), name == "max", &invalid)?;
+            Ok(best.cloned().unwrap_or(Value::Null))
+        }
+        "max_by" | "min_by" => {
+            arity(2)?;
+            let items = array(arg(0)?)?;
+            let ast = expref(1)?;
+            let best = extreme(&items, |v| eval(ast, v), name == "max_by", &invalid)?;
+            Ok(best.cloned().unwrap_or(Value::Null))
+        }
+        "merge" => {
+            let mut merged = Map::new();
+            for i in 0..args.len() {
+                let Value::Object(map) = arg(i)? else {
+                    return Err(invalid("object"));
+                };
+                merged.extend(map);
+            }
+            Ok(Value::Object(merged))
+        }
+        "not_null" => {
+            for i in 0..args.len() {
+                let value = arg(i)?;
+                if !value.is_null() {
+                    return Ok(value);
+                }
+            }
+            Ok(Value::Null)
+        }
+        "reverse" => {
+            arity(1)?;
+            Ok(match arg(0)? {
+                Value::Array(mut items) => {
+                    items.reverse();
+                    Value::Array(items)
+                }
+                Value::String(s) => Value::String(s.chars().rev().collect()),
+                _ => return Err(invalid("array or string")),
+            })
+        }
+        "sort" => {
+            arity(1)?;
+            let mut items = array(arg(0)?)?;
+            check_sortable(&items, &invalid)?;
+            items.sort_by(order);
+            Ok(Value::Array(items))
+        }
+        "sort_by" => {
+            arity(2)?;
+            let items = array(arg(0)?)?;
+            let ast = expref(1)?;
+            let mut keyed = items
+                .into_iter()
+                .map(|item| Ok((eval(ast, &item)?, item)))
+                .collect::<Result<Vec<_>, UtilityError>>()?;
+            let keys: Vec<Value> = keyed.iter().map(|(k, _)| k.clone()).collect();
+            check_sortable(&keys, &invalid)?;
+            keyed.sort_by(|a, b| order(&a.0, &b.0));
+            Ok(Value::Array(keyed.into_iter().map(|(_, v)| v).collect()))
+        }
+        "to_array" => {
+            arity(1)?;
+            Ok(match arg(0)? {
+                Value::Array(items) => Value::Array(items),
+                other => Value::Array(vec![other]),
+            })
+        }
+        "to_number" => {
+            arity(1)?;
+            Ok(match arg(0)? {
+                Value::Number(n) => Value::Number(n),
+                Value::String(s) => s.trim().parse::<f64>().map_or(Value::Null, number),
+                _ => Value::Null,
+            })
+        }
+        "to_string" => {
+            arity(1)?;
+            Ok(match arg(0)? {
+                Value::String(s) => Value::String(s),
+                other => Value::String(other.to_string()),
+            })
+        }
+        "type" => {
+            arity(1)?;
+            Ok(Value::String(type_name(&arg(0)?).to_string()))
+        }
+        _ => Err(runtime_error(&format!("unknown function {}()", name))),
+    }
+}
+
+/// Strings and numbers sort naturally; mixing them is an error.
+fn check_sortable(
+    items: &[Value],
+    invalid: &dyn Fn(&str) -> UtilityError,
+) -> Result<(), UtilityError> {
+    let all_numbers = items.iter().all(Value::is_number);
+    let all_strings = items.iter().all(Value::is_string);
+    if all_numbers || all_strings {
+        Ok(())
+    } else {
+        Err(invalid("array of numbers or array of strings"))
+    }
+}
+
+fn order(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        _ => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+    }
+}
+
+fn extreme<'a>(
+    items: &'a [Value],
+    key: impl Fn(&Value) -> Result<Value, UtilityError>,
+    want_max: bool,
+    invalid: &dyn Fn(&str) -> UtilityError,
+) -> Result<Option<&'a Value>, UtilityError> {
+    let keys = items.iter().map(&key).collect::<Result<Vec<_>, _>>()?;
+    check_sortable(&keys, invalid)?;
+    let mut best: Option<usize> = None;
+    for i in 0..items.len() {
+        let better = best.is_none_or(|b| {
+            let ord = order(&keys[i], &keys[b]);
+            if want_max {
+                ord == Ordering::Greater
+            } else {
+                ord == Ordering::Less
+            }
+        });
+        if better {
+            best = Some(i);
+        }
+    }
+    Ok(best.map(|i| &items[i]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(expr: &str, data: Value) -> Value {
+        search(expr, &data).unwrap()
+    }
+
+    #[test]
+    fn projections_and_filters() {
+        let data = json!({
+            "people": [
+                {"name": "a", "age": 30, "tags": ["x", "y"]},
+                {"name": "b", "age": 17, "tags": ["z"]},
+                {"name": "c", "tags": []}
+            ]
+        });
+        assert_eq!(run("people[*].name", data.clone()), json!(["a", "b", "c"]));
+        assert_eq!(run("people[?age > `18`].name", data.clone()), json!(["a"]));
+        assert_eq!(run("people[].tags[]", data.clone()), json!(["x", "y", "z"]));
+        assert_eq!(run("people[0].tags[-1]", data.clone()), json!("y"));
+        assert_eq!(run("people[*].age | [0]", data.clone()), json!(30));
+        assert_eq!(
+            run("people[:2].{n: name, adult: age >= `18`}", data.clone()),
+            json!([{"n": "a", "adult": true}, {"n": "b", "adult": false}])
+        );
+        assert_eq!(run("missing.field", data), Value::Null);
+    }
+
+    #[test]
+    fn object_and_nested_projections() {
+        let data = json!({"x": {"a": 1}, "y": {"a": 2}, "z": {}});
+        assert_eq!(run("*.a", data.clone()), json!([1, 2]));
+        assert_eq!(run("[x, y][*].a", data.clone()), json!([1, 2]));
+        assert_eq!(run("x.*", data), json!([1]));
+
+        let nested = json!({"rows": [[{"v": 1}, {"v": 2}], [{"v": 3}]]});
+        assert_eq!(run("rows[*][*].v", nested.clone()), json!([[1, 2], [3]]));
+        assert_eq!(run("rows[].v", nested.clone()), json!([1, 2, 3]));
+        assert_eq!(run("rows[][].v", nested), json!([1, 2, 3]));
+        assert_eq!(run("[]", json!([[1, 2], [3, [4]]])), json!([1, 2, 3, [4]]));
+        // Projecting over something that is not a list yields null.
+        assert_eq!(run("x[*].a", json!({"x": {"a": 1}})), Value::Null);
+    }
+
+    #[test]
+    fn filter_expressions() {
+        let data = json!({
+            "people": [
+                {"name": "a", "age": 30, "tags": ["x", "y"]},
+                {"name": "b", "age": 17, "tags": ["z"]},
+                {"name": "c", "tags": []}
+            ]
+        });
+        let names = |expr: &str| run(&format!("people[?{}].name", expr), data.clone());
+        assert_eq!(names("age > `18` || name == 'c'"), json!(["a", "c"]));
+        assert_eq!(names("age && age < `18`"), json!(["b"]));
+        assert_eq!(names("!age"), json!(["c"]));
+        assert_eq!(names("contains(tags, 'z')"), json!(["b"]));
+        assert_eq!(names("tags"), json!(["a", "b"]));
+        assert_eq!(names("name != 'a'"), json!(["b", "c"]));
+        // Ordering comparisons only hold between numbers.
+        assert_eq!(names("name > 'a'"), json!([]));
+        assert_eq!(run("people[?age] | [?age > `20`].name", data), json!(["a"]));
+    }
+
+    #[test]
+    fn slice_bounds() {
+        let data = json!([0, 1, 2, 3, 4, 5]);
+        assert_eq!(run("[10:]", data.clone()), json!([]));
+        assert_eq!(run("[:-10]", data.clone()), json!([]));
+        assert_eq!(run("[5:1:-2]", data.clone()), json!([5, 3]));
+        assert_eq!(run("[-10:2]", data.clone()), json!([0, 1]));
+        assert_eq!(run("[1:3].to_string(@)", data.clone()), json!(["1", "2"]));
+        assert_eq!(run("[1:2]", json!({"a": 1})), Value::Null);
+        assert!(search("[::0]", &data).is_err());
+        assert!(search("[1:2:3:4]", &data).is_err());
+    }
+
+    #[test]
+    fn more_functions() {
+        let data = json!({"items": [{"n": "b", "p": 3}, {"n": "a", "p": 1.5}], "s": "abc"});
+        assert_eq!(run("map(&n, items)", data.clone()), json!(["b", "a"]));
+        assert_eq!(run("min_by(items, &p).n", data.clone()), json!("a"));
+        assert_eq!(run("avg(items[*].p)", data.clone()), json!(2.25));
+        assert_eq!(run("reverse(s)", data.clone()), json!("cba"));
+        assert_eq!(run("starts_with(s, 'ab')", data.clone()), json!(true));
+        assert_eq!(run("abs(`-2`)", data.clone()), json!(2));
+        assert_eq!(run("to_number('4.5')", data.clone()), json!(4.5));
+        assert_eq!(run("type(items)", data.clone()), json!("array"));
+        assert_eq!(
+            run("merge(items[0], {n: 'z'})", data.clone()),
+            json!({"n": "z", "p": 3})
+        );
+        // `&p` is an expression reference, not a value.
+        assert!(search("sort_by(items, p)", &data).is_err());
+        assert!(search("abs(s)", &data).is_err());
+    }
+
+    #[test]
+    fn pipe_precedence() {
+        let data = json!({"a": [{"b": [1, 2]}, {"b": [3, 4]}]});
+        // Inside a projection `[0]` applies to every element...
+        assert_eq!(run("a[*].b[0]", data.clone()), json!([1, 3]));
+        // ...while a pipe ends the projection first.
+        assert_eq!(run("a[*].b | [0]", data.clone()), json!([1, 2]));
+        assert_eq!(run("a[0].b | length(@)", data.clone()), json!(2));
+        // `||` and `&&` bind tighter than `|`.
+        assert_eq!(run("missing || a | length(@)", data.clone()), json!(2));
+        assert_eq!(run("a[0] && a[1] | b", data), json!([3, 4]));
+    }
+
+    #[test]
+    fn slices() {
+        let data = json!([0, 1, 2, 3, 4, 5]);
+        assert_eq!(run("[1:3]", data.clone()), json!([1, 2]));
+        assert_eq!(run("[::2]", data.clone()), json!([0, 2, 4]));
+        assert_eq!(run("[::-1]", data.clone()), json!([5, 4, 3, 2, 1, 0]));
+        assert_eq!(run("[-2:]", data), json!([4, 5]));
+    }
+
+    #[test]
+    fn functions() {
+        let data = json!({"items": [{"n": "b", "p": 3}, {"n": "a", "p": 1.5}], "o": {"k": 1}});
+        assert_eq!(run("length(items)", data.clone()), json!(2));
+        assert_eq!(run("sort_by(items, &p)[0].n", data.clone()), json!("a"));
+        assert_eq!(run("max_by(items, &p).n", data.clone()), json!("b"));
+        assert_eq!(run("sum(items[*].p)", data.clone()), json!(4.5));
+        assert_eq!(
+            run("join(', ', sort(items[*].n))", data.clone()),
+            json!("a, b")
+        );
+        assert_eq!(run("keys(o)", data.clone()), json!(["k"]));
+        assert_eq!(run("not_null(missing, o.k)", data.clone()), json!(1));
+        assert_eq!(
+            run("contains('abc', 'b') && !`false`", data.clone()),
+            json!(true)
+        );
+        assert!(search("length(items, o)", &data).is_err());
+        assert!(search("nope(items)", &data).is_err());
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        let err = search("people[?age >", &json!({})).unwrap_err().to_string();
+        assert!(err.contains("position"), "{}", err);
+        assert!(search("a.", &json!({})).is_err());
+        assert!(search("`{bad`", &json!({})).is_err());
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let data = json!({"a": {"a": [1]}});
+        let under = MAX_DEPTH - 1;
+        for expression in [
+            "[".repeat(under) + "a" + &"]".repeat(under),
+            "(".repeat(under) + "a" + &")".repeat(under),
+            "!".repeat(under) + "a",
+            vec!["a"; under].join("."),
+            vec!["a"; under].join(" | "),
+            vec!["a"; under].join(" || "),
+            "a".to_string() + &"[?a]".repeat(under),
+            "a".to_string() + &"[*]".repeat(under),
+            "a".to_string() + &"[0:1]".repeat(under),
+            "a".to_string() + &".*".repeat(under),
+        ] {
+            assert!(search(&expression, &data).is_ok(), "{}", expression);
+        }
+        for expression in [
+            "[".repeat(500),
+            "(".repeat(500) + "a" + &")".repeat(500),
+            vec!["a"; 10_000].join("."),
+            vec!["a"; 10_000].join(" | "),
+            "a".to_string() + &"[?a]".repeat(1000),
+        ] {
+            let err = search(&expression, &data).unwrap_err().to_string();
+            assert!(err.contains("nested too deeply"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn incomplete_expressions_are_errors() {
+        for expr in [
+            "", "   ", "a |", "a.b.", "{", "{a: b,", "[1, 2", "foo(", "a[?b",
+        ] {
+            assert!(search(expr, &json!({})).is_err(), "{:?}", expr);
+        }
+    }
+}
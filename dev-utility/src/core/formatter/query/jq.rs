@@ -0,0 +1,87 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! jq filters, evaluated by jaq with its standard library. Everything that
+//! needs the outside world (`input`, `$ENV`, `@sh`-style I/O) is absent.
+
+use crate::error::UtilityError;
+use jaq_core::load::{Arena, File, Loader};
+use jaq_core::{Compiler, Ctx, RcIter};
+use jaq_json::Val;
+use serde_json::Value;
+
+/// Upper bound on emitted values, so a bare `repeat(.)` stops. Only
+/// top-level outputs count: values collected inside the filter, as in
+/// `[repeat(.)]` or `[range(1e12)]`, are not bounded.
+const MAX_OUTPUTS: usize = 10_000;
+
+pub(crate) fn run(filter: &str, data: Value) -> Result<Vec<Value>, UtilityError> {
+    let loader = Loader::new(jaq_std::defs().chain(jaq_json::defs()));
+    let arena = Arena::default();
+    let modules = loader
+        .load(
+            &arena,
+            File {
+                code: filter,
+                path: (),
+            },
+        )
+        .map_err(|errors| {
+            let messages: Vec<String> = errors
+                .into_iter()
+                .flat_map(|(_, error)| load_error_messages(filter, error))
+                .collect();
+            UtilityError::ParseError(format!("jq syntax error: {}", messages.join("; ")))
+        })?;
+
+    let program = Compiler::default()
+        .with_funs(jaq_std::funs().chain(jaq_json::funs()))
+        .compile(modules)
+        .map_err(|errors| {
+            let messages: Vec<String> = errors
+                .into_iter()
+                .flat_map(|(_, undefined)| undefined)
+                .map(|(name, kind)| format!("undefined {:?} '{}'", kind, name))
+                .collect();
+            UtilityError::ParseError(format!("jq error: {}", messages.join("; ")))
+        })?;
+
+    let inputs = RcIter::new(core::iter::empty());
+    let mut outputs = Vec::new();
+    for result in program.run((Ctx::new([], &inputs), Val::from(data))) {
+        let value = result.map_err(|e| UtilityError::InvalidInput(format!("jq error: {}", e)))?;
+        if outputs.len() == MAX_OUTPUTS {
+            return Err(UtilityError::InvalidInput(format!(
+                "jq filter produced more than {} values",
+                MAX_OUTPUTS
+            )));
+        }
+        outputs.push(Value::from(value));
+    }
+    Ok(outputs)
+}
+
+fn load_error_messages(code: &str, error: jaq_core::load::Error<&str>) -> Vec<String> {
+    // jaq reports positions as the unconsumed rest of the source.
+    let offset = |rest: &str| code.len() - rest.len();
+    match error {
+        jaq_core::load::Error::Io(errors) => errors.into_iter().map(|(_, e)| e).collect(),
+        jaq_core::load::Error::Lex(errors) => errors
+            .into_iter()
+            .map(|(expect, rest)| {
+                format!("expected {} at position {}", expect.as_str(), offset(rest))
+            })
+            .collect(),
+        jaq_core::load::Error::Parse(errors) => errors
+            .into_iter()
+            .map(|(expect, rest)| {
+                format!("expected {} at position {}", expect.as_str(), offset(rest))
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+mod jmespath;
+mod jq;
+
+use super::json::json_diagnostic;
+use crate::error::UtilityError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_json_path::JsonPath;
+use universal_function_macro::universal_function;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum QueryLanguage {
+    JsonPath, // RFC 9535, e.g. $.store.book[?@.price < 10].title
+    JmesPath, // e.g. store.book[?price < `10`].title
+    Jq,       // e.g. .store.book[] | select(.price < 10) | .title
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct JsonQueryMatch {
+    pub path: Option<String>, // Normalized path ($['a'][0]); JSONPath only
+    pub value: String,        // Matched value as compact JSON
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct JsonQueryResult {
+    pub matches: Vec<JsonQueryMatch>, // In document / emission order
+    pub output: String,               // Pretty-printed array of all matched values
+}
+
+/// Run a query against a JSON document.
+///
+/// JSONPath yields a node list with a normalized path per node. A JMESPath
+/// expression yields exactly one value (null when nothing matched), and a
+/// jq filter yields one match per emitted value; neither tracks paths.
+#[universal_function]
+pub fn query_json(
+    input: &str,
+    expression: &str,
+    language: QueryLanguage,
+) -> Result<JsonQueryResult, UtilityError> {
+    let document: Value = serde_json::from_str(input)
+        .map_err(|e| json_diagnostic(input, &e.to_string(), e.line(), e.column()))?;
+    let expression = expression.trim();
+
+    let located: Vec<(Option<String>, Value)> = match language {
+        QueryLanguage::JsonPath => {
+            let path = JsonPath::parse(expression)
+                .map_err(|e| UtilityError::ParseError(format!("JSONPath error: {}", e)))?;
+            path.query_located(&document)
+                .into_iter()
+                .map(|node| (Some(node.location().to_string()), node.node().clone()))
+                .collect()
+        }
+        QueryLanguage::JmesPath => vec![(None, jmespath::search(expression, &document)?)],
+        QueryLanguage::Jq => jq::run(expression, document)?
+            .into_iter()
+            .map(|value| (None, value))
+            .collect(),
+    };
+
+    let output = serde_json::to_string_pretty(&Value::Array(
+        located.iter().map(|(_, value)| value.clone()).collect(),
+    ))
+    .map_err(|e| UtilityError::Runtime(e.to_string()))?;
+    let matches = located
+        .into_iter()
+        .map(|(path, value)| JsonQueryMatch {
+            path,
+            value: value.to_string(),
+        })
+        .collect();
+
+    Ok(JsonQueryResult { matches, output })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORE: &str = r#"{
+        "store": {
+            "book": [
+                {"title": "Sayings", "price": 8.95},
+                {"title": "Sword", "price": 12.99},
+                {"title": "Moby Dick", "price": 8.99}
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn jsonpath_reports_normalized_paths() {
+        let result = query_json(
+            STORE,
+            "$.store.book[?@.price < 10].title",
+            QueryLanguage::JsonPath,
+        )
+        .unwrap();
+        assert_eq!(result.matches.len(), 2);
+        assert_eq!(
+            result.matches[0].path.as_deref(),
+            Some("$['store']['book'][0]['title']")
+        );
+        assert_eq!(result.matches[1].value, "\"Moby Dick\"");
+        assert!(query_json(STORE, "$.store[", QueryLanguage::JsonPath).is_err());
+    }
+
+    #[test]
+    fn jmespath_and_jq_agree() {
+        let jmes = query_json(
+            STORE,
+            "store.book[?price < `10`].title",
+            QueryLanguage::JmesPath,
+        )
+        .unwrap();
+        assert_eq!(jmes.matches.len(), 1);
+        assert_eq!(jmes.matches[0].value, r#"["Sayings","Moby Dick"]"#);
+
+        let jq = query_json(
+            STORE,
+            ".store.book[] | select(.price < 10) | .title",
+            QueryLanguage::Jq,
+        )
+        .unwrap();
+        let values: Vec<&str> = jq.matches.iter().map(|m| m.value.as_str()).collect();
+        assert_eq!(values, ["\"Sayings\"", "\"Moby Dick\""]);
+        assert!(jq.matches[0].path.is_none());
+    }
+
+    #[test]
+    fn jq_errors_are_reported() {
+        let err = query_json(STORE, ".store[", QueryLanguage::Jq).unwrap_err();
+        assert!(err.to_string().contains("position"), "{}", err);
+        assert!(query_json(STORE, "nope(1)", QueryLanguage::Jq).is_err());
+        assert!(query_json(STORE, ".store | error(\"x\")", QueryLanguage::Jq).is_err());
+        assert!(query_json("{", ".", QueryLanguage::Jq).is_err());
+    }
+}
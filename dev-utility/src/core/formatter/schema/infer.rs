@@ -0,0 +1,361 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Schema inference: every sample is folded into one `Shape`, which then
+//! prints as the narrowest draft 2020-12 schema all samples satisfy.
+
+use super::super::json::json_diagnostic;
+use crate::error::UtilityError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use universal_function_macro::universal_function;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct SchemaInferOptions {
+    pub detect_formats: bool, // Add `format` when every string sample matches one
+    pub title: Option<String>,
+}
+
+impl Default for SchemaInferOptions {
+    fn default() -> Self {
+        Self {
+            detect_formats: true,
+            title: None,
+        }
+    }
+}
+
+/// Infer a draft 2020-12 schema from one or more sample documents.
+///
+/// `input` holds the samples back to back (whitespace or newline
+/// separated). A property is required only when every sample object has
+/// it; values that differ in type across samples get a `type` array.
+#[universal_function]
+pub fn infer_json_schema(
+    input: &str,
+    options: Option<SchemaInferOptions>,
+) -> Result<String, UtilityError> {
+    let options = options.unwrap_or_default();
+    let mut shape = Shape::default();
+    let mut stream = serde_json::Deserializer::from_str(input).into_iter::<Value>();
+    for sample in stream.by_ref() {
+        let sample =
+            sample.map_err(|e| json_diagnostic(input, &e.to_string(), e.line(), e.column()))?;
+        shape.add(&sample, &options);
+    }
+    if shape.seen == 0 {
+        return Err(UtilityError::InvalidInput(
+            "No sample documents to infer a schema from".to_string(),
+        ));
+    }
+
+    let mut schema = Map::new();
+    schema.insert(
+        "$schema".to_string(),
+        json!("https://json-schema.org/draft/2020-12/schema"),
+    );
+    if let Some(title) = options.title.filter(|t| !t.trim().is_empty()) {
+        schema.insert("title".to_string(), json!(title));
+    }
+    if let Value::Object(body) = shape.to_schema() {
+        schema.extend(body);
+    }
+    serde_json::to_string_pretty(&Value::Object(schema))
+        .map_err(|e| UtilityError::Runtime(e.to_string()))
+}
+
+#[derive(Default)]
+struct Shape {
+    seen: usize,
+    null: bool,
+    boolean: bool,
+    integer: bool,
+    number: bool,
+    strings: usize,
+    // The format shared by every string so far; cleared on a mismatch
+    format: Option<&'static str>,
+    arrays: usize,
+    items: Option<Box<Shape>>,
+    objects: usize,
+    properties: Vec<(String, Shape)>,
+}
+
+impl Shape {
+    fn add(&mut self, value: &Value, options: &SchemaInferOptions) {
+        self.seen += 1;
+        match value {
+            Value::Null => self.null = true,
+            Value::Bool(_) => self.boolean = true,
+            Value::Number(n) if n.is_f64() => self.number = true,
+            Value::Number(_) => self.integer = true,
+            Value::String(s) => {
+                let format = if options.detect_formats {
+                    detect_format(s)
+                } else {
+                    None
+                };
+                self.format = if self.strings == 0 || self.format == format {
+                    format
+                } else {
+                    None
+                };
+                self.strings += 1;
+            }
+            Value::Array(items) => {
+                self.arrays += 1;
+                for item in items {
+                    self.items
+                        .get_or_insert_with(Default::default)
+                        .add(item, options);
+                }
+            }
+            Value::Object(map) => {
+                self.objects += 1;
+                for (key, item) in map {
+                    let index = match self.properties.iter().position(|(k, _)| k == key) {
+                        Some(index) => index,
+                        None => {
+                            self.properties.push((key.clone(), Shape::default()));
+                            self.properties.len() - 1
+                        }
+                    };
+                    self.properties[index].1.add(item, options);
+                }
+            }
+        }
+    }
+
+    fn to_schema(&self) -> Value {
+        let mut types = Vec::new();
+        let mut schema = Map::new();
+        if self.null {
+            types.push("null");
+        }
+        if self.boolean {
+            types.push("boolean");
+        }
+        if self.number {
+            types.push("number");
+        } else if self.integer {
+            types.push("integer");
+        }
+        if self.strings > 0 {
+            types.push("string");
+            if let Some(format) = self.format {
+                schema.insert("format".to_string(), json!(format));
+            }
+        }
+        if self.arrays > 0 {
+            types.push("array");
+            if let Some(items) = &self.items {
+                schema.insert("items".to_string(), items.to_schema());
+            }
+        }
+        if self.objects > 0 {
+            types.push("object");
+            let properties: Map<String, Value> = self
+                .properties
+                .iter()
+                .map(|(key, shape)| (key.clone(), shape.to_schema()))
+                .collect();
+            let required: Vec<&str> = self
+                .properties
+                .iter()
+                .filter(|(_, shape)| shape.seen == self.objects)
+                .map(|(key, _)| key.as_str())
+                .collect();
+            schema.insert("properties".to_string(), Value::Object(properties));
+            if !required.is_empty() {
+                schema.insert("required".to_string(), json!(required));
+            }
+        }
+
+        let kind = match types.as_slice() {
+            [single] => json!(single),
+            many => json!(many),
+        };
+        let mut ordered = Map::new();
+        ordered.insert("type".to_string(), kind);
+        ordered.extend(schema);
+        Value::Object(ordered)
+    }
+}
+
+fn detect_format(s: &str) -> Option<&'static str> {
+    if is_date_time(s) {
+        Some("date-time")
+    } else if is_date(s) {
+        Some("date")
+    } else if is_time(s) {
+        Some("time")
+    } else if is_uuid(s) {
+        Some("uuid")
+    } else if is_email(s) {
+        Some("email")
+    } else if s.parse::<Ipv4Addr>().is_ok() {
+        Some("ipv4")
+    } else if s.contains(':') && s.parse::<Ipv6Addr>().is_ok() {
+        Some("ipv6")
+    } else if is_uri(s) {
+        Some("uri")
+    } else {
+        None
+    }
+}
+
+fn digits(s: &str, range: std::ops::Range<usize>) -> Option<u32> {
+    let part = s.get(range)?;
+    part.bytes()
+        .all(|b| b.is_ascii_digit())
+        .then(|| part.parse().ok())?
+}
+
+/// `YYYY-MM-DD`
+fn is_date(s: &str) -> bool {
+    s.len() == 10
+        && s.as_bytes()[4] == b'-'
+        && s.as_bytes()[7] == b'-'
+        && digits(s, 0..4).is_some()
+        && digits(s, 5..7).is_some_and(|m| (1..=12).contains(&m))
+        && digits(s, 8..10).is_some_and(|d| (1..=31).contains(&d))
+}
+
+/// `HH:MM:SS[.frac](Z|±HH:MM)`, as RFC 3339 requires an offset.
+fn is_time(s: &str) -> bool {
+    let b = s.as_bytes();
+    if s.len() < 9 || b[2] != b':' || b[5] != b':' {
+        return false;
+    }
+    let clock = digits(s, 0..2).is_some_and(|h| h < 24)
+        && digits(s, 3..5).is_some_and(|m| m < 60)
+        && digits(s, 6..8).is_some_and(|sec| sec <= 60);
+    let mut rest = &s[8..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let end = fraction
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(fraction.len());
+        if end == 0 {
+            return false;
+        }
+        rest = &fraction[end..];
+    }
+    let offset = match rest.as_bytes() {
+        [b'Z' | b'z'] => true,
+        [b'+' | b'-', ..] if rest.len() == 6 => {
+            rest.as_bytes()[3] == b':'
+                && digits(rest, 1..3).is_some_and(|h| h < 24)
+                && digits(rest, 4..6).is_some_and(|m| m < 60)
+        }
+        _ => false,
+    };
+    clock && offset
+}
+
+fn is_date_time(s: &str) -> bool {
+    s.len() > 11
+        && is_date(&s[..10])
+        && matches!(s.as_bytes()[10], b'T' | b't')
+        && is_time(&s[11..])
+}
+
+fn is_uuid(s: &str) -> bool {
+    s.len() == 36
+        && s.bytes().enumerate().all(|(i, b)| match i {
+            8 | 13 | 18 | 23 => b == b'-',
+            _ => b.is_ascii_hexdigit(),
+        })
+}
+
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !s.chars().any(char::is_whitespace)
+        && !domain.contains('@')
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+}
+
+/// An absolute URI with an authority, e.g. `https://example.com/a`.
+fn is_uri(s: &str) -> bool {
+    let Some((scheme, rest)) = s.split_once("://") else {
+        return false;
+    };
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !rest.is_empty()
+        && !s.chars().any(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::validate_json_schema;
+    use super::*;
+
+    #[test]
+    fn infers_types_formats_and_required_fields() {
+        let samples = r#"
+            {"id": 1, "email": "a@example.com", "at": "2024-05-01T10:00:00Z", "tags": ["x"]}
+            {"id": 2.5, "email": "b@example.org", "at": "2024-05-02T11:30:00.5+02:00", "tags": [], "note": null}
+        "#;
+        let schema: Value =
+            serde_json::from_str(&infer_json_schema(samples, None).unwrap()).unwrap();
+        assert_eq!(
+            schema,
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "properties": {
+                    "id": {"type": "number"},
+                    "email": {"type": "string", "format": "email"},
+                    "at": {"type": "string", "format": "date-time"},
+                    "tags": {"type": "array", "items": {"type": "string"}},
+                    "note": {"type": "null"}
+                },
+                "required": ["id", "email", "at", "tags"]
+            })
+        );
+
+        // Every sample validates against the inferred schema.
+        let text = schema.to_string();
+        for sample in samples.trim().lines() {
+            assert!(validate_json_schema(sample, &text, None).unwrap().valid);
+        }
+    }
+
+    #[test]
+    fn mixes_types_and_drops_inconsistent_formats() {
+        let samples = r#"["6f1c2a9e-3b7d-4c1a-9f00-1234567890ab", "2024-01-31", 3, null]
+                         ["not a uuid"]"#;
+        let options = SchemaInferOptions {
+            title: Some("Row".to_string()),
+            ..Default::default()
+        };
+        let schema: Value =
+            serde_json::from_str(&infer_json_schema(samples, Some(options)).unwrap()).unwrap();
+        assert_eq!(schema["title"], "Row");
+        assert_eq!(
+            schema["items"],
+            json!({"type": ["null", "integer", "string"]})
+        );
+
+        assert_eq!(detect_format("10.0.0.1"), Some("ipv4"));
+        assert_eq!(detect_format("::1"), Some("ipv6"));
+        assert_eq!(detect_format("https://example.com/a?b"), Some("uri"));
+        assert_eq!(detect_format("12:30:00Z"), Some("time"));
+        assert_eq!(detect_format("2024-13-01"), None);
+        assert!(infer_json_schema("  ", None).is_err());
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! JSON Schema validation (draft-07, 2019-09 and 2020-12) and inference
+//! of a 2020-12 schema from sample documents.
+
+mod infer;
+
+pub use infer::*;
+
+use super::json::json_diagnostic;
+use crate::converter::{parse_document, DataFormat};
+use crate::error::UtilityError;
+use jsonschema::Draft;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use universal_function_macro::universal_function;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum SchemaDraft {
+    #[serde(rename = "draft-07")]
+    Draft7,
+    #[serde(rename = "2019-09")]
+    Draft201909,
+    #[serde(rename = "2020-12")]
+    Draft202012,
+}
+
+impl SchemaDraft {
+    fn to_draft(self) -> Draft {
+        match self {
+            SchemaDraft::Draft7 => Draft::Draft7,
+            SchemaDraft::Draft201909 => Draft::Draft201909,
+            SchemaDraft::Draft202012 => Draft::Draft202012,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct SchemaValidateOptions {
+    pub draft: Option<SchemaDraft>, // Overrides `$schema`; None = detect, falling back to 2020-12
+    pub document_format: DataFormat, // Any format `convert_data` reads, e.g. YAML
+    pub validate_formats: bool,     // Treat `format` as an assertion, not an annotation
+}
+
+impl Default for SchemaValidateOptions {
+    fn default() -> Self {
+        Self {
+            draft: None,
+            document_format: DataFormat::Json,
+            validate_formats: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct SchemaError {
+    pub instance_path: String, // JSON Pointer to the failing value; "" is the root
+    pub schema_path: String,   // JSON Pointer to the failing keyword in the schema
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct SchemaValidationResult {
+    pub valid: bool,
+    pub draft: SchemaDraft,       // The draft the schema was compiled against
+    pub errors: Vec<SchemaError>, // Every failure, in schema evaluation order
+}
+
+/// Validate `document` against a JSON Schema.
+///
+/// A document that does not satisfy the schema is a successful call with
+/// `valid: false`; only an unparsable document or an invalid schema is an
+/// error. Remote `$ref`s are not fetched.
+#[universal_function]
+pub fn validate_json_schema(
+    document: &str,
+    schema: &str,
+    options: Option<SchemaValidateOptions>,
+) -> Result<SchemaValidationResult, UtilityError> {
+    let options = options.unwrap_or_default();
+    let mut schema: Value = serde_json::from_str(schema).map_err(|e| {
+        let message = format!("Schema: {}", e);
+        json_diagnostic(schema, &message, e.line(), e.column())
+    })?;
+    let instance = parse_document(document, options.document_format)?;
+
+    let draft = match options.draft {
+        // An explicit draft wins; a conflicting `$schema` would otherwise
+        // switch off the 2019-09+ vocabularies.
+        Some(draft) => {
+            if let Some(root) = schema.as_object_mut() {
+                root.remove("$schema");
+            }
+            draft
+        }
+        None => match Draft::default().detect(&schema) {
+            Ok(Draft::Draft7) => SchemaDraft::Draft7,
+            Ok(Draft::Draft201909) => SchemaDraft::Draft201909,
+            Ok(Draft::Draft202012) => SchemaDraft::Draft202012,
+            Ok(_) => {
+                return Err(UtilityError::InvalidInput(
+                    "Draft-04 and draft-06 schemas are not supported; use draft-07 or later"
+                        .to_string(),
+                ))
+            }
+            Err(e) => return Err(UtilityError::InvalidInput(format!("Invalid schema: {}", e))),
+        },
+    };
+    let validator = jsonschema::options()
+        .with_draft(draft.to_draft())
+        .should_validate_formats(options.validate_formats)
+        .build(&schema)
+        .map_err(|e| {
+            UtilityError::InvalidInput(format!("Invalid schema at {}: {}", e.schema_path, e))
+        })?;
+
+    let errors: Vec<SchemaError> = validator
+        .iter_errors(&instance)
+        .map(|e| SchemaError {
+            instance_path: e.instance_path.to_string(),
+            schema_path: e.schema_path.to_string(),
+            message: e.to_string(),
+        })
+        .collect();
+    Ok(SchemaValidationResult {
+        valid: errors.is_empty(),
+        draft,
+        errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"{
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object",
+        "required": ["id", "tags"],
+        "properties": {
+            "id": {"type": "integer", "minimum": 1},
+            "email": {"type": "string", "format": "email"},
+            "tags": {"type": "array", "items": {"type": "string"}}
+        }
+    }"#;
+
+    #[test]
+    fn reports_every_error_with_paths() {
+        let result = validate_json_schema(
+            r#"{"id": 0, "email": "nope", "tags": ["a", 2]}"#,
+            SCHEMA,
+            None,
+        )
+        .unwrap();
+        assert!(!result.valid);
+        assert_eq!(result.draft, SchemaDraft::Draft7);
+        let paths: Vec<(&str, &str)> = result
+            .errors
+            .iter()
+            .map(|e| (e.instance_path.as_str(), e.schema_path.as_str()))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("/id", "/properties/id/minimum"),
+                ("/email", "/properties/email/format"),
+                ("/tags/1", "/properties/tags/items/type"),
+            ]
+        );
+
+        let ok = validate_json_schema(r#"{"id": 3, "tags": []}"#, SCHEMA, None).unwrap();
+        assert!(ok.valid && ok.errors.is_empty());
+    }
+
+    #[test]
+    fn validates_yaml_and_picks_drafts() {
+        let options = SchemaValidateOptions {
+            document_format: DataFormat::Yaml,
+            draft: Some(SchemaDraft::Draft201909),
+            ..Default::default()
+        };
+        let result = validate_json_schema("id: 2\n", SCHEMA, Some(options)).unwrap();
+        assert_eq!(result.draft, SchemaDraft::Draft201909);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].instance_path, "");
+        assert!(result.errors[0].message.contains("tags"));
+
+        let bare = validate_json_schema("1", r#"{"type": "string"}"#, None).unwrap();
+        assert_eq!(bare.draft, SchemaDraft::Draft202012);
+    }
+
+    #[test]
+    fn rejects_bad_schemas() {
+        assert!(validate_json_schema("1", r#"{"type": 5}"#, None).is_err());
+        let old = r#"{"$schema": "http://json-schema.org/draft-04/schema#"}"#;
+        assert!(validate_json_schema("1", old, None).is_err());
+        let err = validate_json_schema("1", "{", None).unwrap_err();
+        assert!(err.to_string().contains("Schema:"), "{}", err);
+    }
+}
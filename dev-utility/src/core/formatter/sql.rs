@@ -0,0 +1,841 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+mod lexer;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use universal_function_macro::universal_function;
+
+use super::IndentStyle;
+use crate::error::{Diagnostic, UtilityError};
+use lexer::{Kind, Token};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum SqlDialect {
+    PostgreSql, // $1 placeholders, dollar-quoted strings, :: casts
+    MySql,      // `quoted` names, "strings", # comments, ? placeholders
+    Sqlite,     // ?, ?NNN, :name, @name and $name placeholders
+    TSql,       // [quoted] names, @name parameters
+    BigQuery,   // `quoted` names, triple-quoted strings, @name parameters
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum KeywordCase {
+    #[default]
+    Upper,
+    Lower,
+    Preserve,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum CommaPosition {
+    #[default]
+    Trailing,
+    Leading, // Start continuation lines with ", "
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct SqlFormatOptions {
+    pub keyword_case: KeywordCase,
+    pub comma_position: CommaPosition,
+    pub line_width: usize, // Parenthesized groups and CASE stay inline up to this column
+    pub params: Option<String>, // JSON array or object whose values replace placeholders
+}
+
+impl Default for SqlFormatOptions {
+    fn default() -> Self {
+        Self {
+            keyword_case: KeywordCase::Upper,
+            comma_position: CommaPosition::Trailing,
+            line_width: 80,
+            params: None,
+        }
+    }
+}
+
+/// Keywords that start a clause; their content is indented below them.
+const CLAUSES: &[&str] = &[
+    "SELECT",
+    "SELECT DISTINCT",
+    "SELECT ALL",
+    "FROM",
+    "WHERE",
+    "GROUP BY",
+    "HAVING",
+    "ORDER BY",
+    "LIMIT",
+    "OFFSET",
+    "FETCH",
+    "INSERT INTO",
+    "REPLACE INTO",
+    "VALUES",
+    "UPDATE",
+    "SET",
+    "DELETE FROM",
+    "RETURNING",
+    "WINDOW",
+    "QUALIFY",
+    "WITH",
+    "WITH RECURSIVE",
+    "ON CONFLICT",
+    "ON DUPLICATE KEY UPDATE",
+];
+
+/// Keywords that join whole queries and sit at the level of the clauses.
+const SET_OPERATORS: &[&str] = &[
+    "UNION",
+    "UNION ALL",
+    "UNION DISTINCT",
+    "INTERSECT",
+    "EXCEPT",
+    "MINUS",
+];
+
+/// Keywords that start a new line at the current indentation.
+const LINE_BREAKS: &[&str] = &[
+    "AND",
+    "OR",
+    "XOR",
+    "WHEN",
+    "ELSE",
+    "JOIN",
+    "INNER JOIN",
+    "LEFT JOIN",
+    "LEFT OUTER JOIN",
+    "RIGHT JOIN",
+    "RIGHT OUTER JOIN",
+    "FULL JOIN",
+    "FULL OUTER JOIN",
+    "CROSS JOIN",
+    "NATURAL JOIN",
+    "CROSS APPLY",
+    "OUTER APPLY",
+    "STRAIGHT_JOIN",
+];
+
+/// Keywords that are called like functions.
+const CALLABLE: &[&str] = &["CAST", "SAFE_CAST", "EXTRACT", "STRUCT", "UNNEST", "FILTER"];
+
+/// Keywords that end an operand, so a following sign is binary.
+const OPERANDS: &[&str] = &[
+    "END",
+    "NULL",
+    "TRUE",
+    "FALSE",
+    "CURRENT_DATE",
+    "CURRENT_TIME",
+    "CURRENT_TIMESTAMP",
+];
+
+/// Statement keywords that force a parenthesized group onto its own lines.
+const STATEMENTS: &[&str] = &[
+    "SELECT",
+    "SELECT DISTINCT",
+    "SELECT ALL",
+    "WITH",
+    "WITH RECURSIVE",
+    "INSERT INTO",
+    "UPDATE",
+    "DELETE FROM",
+];
+
+fn is(token: &Token, table: &[&str]) -> bool {
+    token.kind == Kind::Keyword && table.contains(&token.upper().as_str())
+}
+
+/// Format SQL for one dialect.
+///
+/// Clauses start on their own line with their content indented below them,
+/// list items and `AND`/`OR` conditions one per line. Parenthesized groups
+/// and `CASE` expressions stay inline when they fit within `line_width`
+/// and contain no subquery. `Minified` puts everything on one line and
+/// drops comments.
+///
+/// With `params`, placeholders are replaced by SQL literals first: a JSON
+/// array supplies `$1`, `?` and `?1` in order, an object supplies `:name`,
+/// `@name` and `$name`.
+#[universal_function]
+pub fn format_sql(
+    input: &str,
+    dialect: SqlDialect,
+    style: IndentStyle,
+    options: Option<SqlFormatOptions>,
+) -> Result<String, UtilityError> {
+    let options = options.unwrap_or_default();
+    let mut tokens = lexer::tokenize(input, dialect)?;
+    if let Some(params) = options.params.as_deref().filter(|p| !p.trim().is_empty()) {
+        inline_params(input, &mut tokens, params, dialect)?;
+    }
+    for token in tokens.iter_mut().filter(|t| t.kind == Kind::Keyword) {
+        match options.keyword_case {
+            KeywordCase::Upper => token.text = token.upper(),
+            KeywordCase::Lower => token.text = token.text.to_ascii_lowercase(),
+            KeywordCase::Preserve => {}
+        }
+    }
+
+    if matches!(style, IndentStyle::Minified) {
+        tokens.retain(|t| !matches!(t.kind, Kind::LineComment | Kind::BlockComment));
+        let unary = unary_signs(&tokens);
+        return Ok(join(&tokens, &unary, false));
+    }
+
+    let unary = unary_signs(&tokens);
+    let mut printer = Printer {
+        tokens: &tokens,
+        unary: &unary,
+        unit: match style {
+            IndentStyle::Tabs => "\t".to_string(),
+            IndentStyle::Spaces(width) => " ".repeat(width),
+            IndentStyle::Minified => String::new(),
+        },
+        options: &options,
+        out: String::new(),
+        frames: Vec::new(),
+        line_start: true,
+        prev: None,
+        between: false,
+    };
+    printer.run();
+    let mut out = printer.out.trim_end().to_string();
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+enum Placeholder {
+    Positional(usize), // 1-based
+    Named(String),
+}
+
+/// Replace placeholder tokens by literals rendered from `params`.
+fn inline_params(
+    input: &str,
+    tokens: &mut [Token],
+    params: &str,
+    dialect: SqlDialect,
+) -> Result<(), UtilityError> {
+    let params: Value = serde_json::from_str(params)
+        .map_err(|e| UtilityError::InvalidInput(format!("Invalid parameters: {}", e)))?;
+    if !params.is_array() && !params.is_object() {
+        return Err(UtilityError::InvalidInput(
+            "Parameters must be a JSON array or object".to_string(),
+        ));
+    }
+
+    let mut sequence = 0;
+    for token in tokens.iter_mut().filter(|t| t.kind == Kind::Placeholder) {
+        let name = &token.text[1..];
+        let placeholder = if name.is_empty() {
+            sequence += 1;
+            Placeholder::Positional(sequence)
+        } else if let Ok(n) = name.parse::<usize>() {
+            Placeholder::Positional(n)
+        } else {
+            Placeholder::Named(name.to_string())
+        };
+        let value = match (&placeholder, &params) {
+            (Placeholder::Positional(n), Value::Array(items)) => {
+                n.checked_sub(1).and_then(|i| items.get(i))
+            }
+            (Placeholder::Positional(n), Value::Object(map)) => map.get(&n.to_string()),
+            (Placeholder::Named(name), Value::Object(map)) => map.get(name),
+            _ => None,
+        };
+        let Some(value) = value else {
+            let hint = match (&placeholder, &params) {
+                (Placeholder::Named(_), Value::Array(_)) => {
+                    "Named placeholders take their values from a JSON object"
+                }
+                _ => "Add a value for it to the parameters",
+            };
+            return Err(Diagnostic::new(
+                input,
+                token.offset,
+                format!("No value for parameter {}", token.text),
+            )
+            .with_hint(hint)
+            .into());
+        };
+        let (kind, text) = literal(value, dialect);
+        token.kind = kind;
+        token.text = text;
+    }
+    Ok(())
+}
+
+/// SQL literal for a JSON value.
+fn literal(value: &Value, dialect: SqlDialect) -> (Kind, String) {
+    match value {
+        Value::Null => (Kind::Keyword, "NULL".to_string()),
+        Value::Bool(b) if dialect == SqlDialect::TSql => (Kind::Number, (*b as u8).to_string()),
+        Value::Bool(b) => (Kind::Keyword, if *b { "TRUE" } else { "FALSE" }.to_string()),
+        Value::Number(n) => (Kind::Number, n.to_string()),
+        Value::String(s) => (Kind::String, quote(s, dialect)),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(|v| literal(v, dialect).1).collect();
+            let list = items.join(", ");
+            let text = match dialect {
+                SqlDialect::PostgreSql => format!("ARRAY[{}]", list),
+                SqlDialect::BigQuery => format!("[{}]", list),
+                _ => format!("({})", list),
+            };
+            (Kind::Word, text)
+        }
+        Value::Object(_) => (Kind::String, quote(&value.to_string(), dialect)),
+    }
+}
+
+fn quote(s: &str, dialect: SqlDialect) -> String {
+    match dialect {
+        // Backslash is an escape character in these string literals.
+        SqlDialect::MySql => format!("'{}'", s.replace('\\', "\\\\").replace('\'', "''")),
+        SqlDialect::BigQuery => format!(
+            "'{}'",
+            s.replace('\\', "\\\\")
+                .replace('\'', "\\'")
+                .replace('\n', "\\n")
+        ),
+        _ => format!("'{}'", s.replace('\'', "''")),
+    }
+}
+
+/// Which `+`/`-` tokens are signs rather than binary operators.
+fn unary_signs(tokens: &[Token]) -> Vec<bool> {
+    tokens
+        .iter()
+        .enumerate()
+        .map(|(i, token)| {
+            if token.kind != Kind::Operator || !matches!(token.text.as_str(), "-" | "+") {
+                return false;
+            }
+            let prev = tokens[..i]
+                .iter()
+                .rev()
+                .find(|t| !matches!(t.kind, Kind::LineComment | Kind::BlockComment));
+            match prev {
+                None => true,
+                Some(prev) => match prev.kind {
+                    Kind::Operator | Kind::Open | Kind::Comma | Kind::Semicolon => true,
+                    Kind::Keyword => !is(prev, OPERANDS),
+                    _ => false,
+                },
+            }
+        })
+        .collect()
+}
+
+fn is_operand(token: &Token) -> bool {
+    matches!(
+        token.kind,
+        Kind::Word | Kind::Keyword | Kind::Quoted | Kind::String | Kind::Number | Kind::Placeholder
+    )
+}
+
+/// Whether a space separates `prev` from `next`. Minified output only keeps
+/// the spaces needed to tokenize the same way.
+fn needs_space(prev: &Token, prev_unary: bool, next: &Token, pretty: bool) -> bool {
+    if matches!(prev.kind, Kind::LineComment | Kind::BlockComment)
+        || matches!(next.kind, Kind::LineComment | Kind::BlockComment)
+    {
+        return true;
+    }
+    if matches!(
+        next.kind,
+        Kind::Comma | Kind::Semicolon | Kind::Close | Kind::Dot
+    ) || matches!(prev.kind, Kind::Open | Kind::Dot)
+        || prev_unary
+        || prev.text == "::"
+        || next.text == "::"
+        || prev.text == "["
+        || next.text == "]"
+        || (next.text == "[" && is_operand(prev))
+    {
+        return false;
+    }
+    match (prev.kind, next.kind) {
+        // Calls keep the spacing they were written with.
+        (Kind::Word | Kind::Quoted, Kind::Open) => pretty && next.space_before,
+        (Kind::Keyword, Kind::Open) if is(prev, CALLABLE) => pretty && next.space_before,
+        (Kind::Operator, Kind::Operator)
+        | (Kind::Keyword, Kind::Operator)
+        | (Kind::Operator, Kind::Keyword) => true,
+        _ if is_operand(prev) && is_operand(next) => true,
+        _ => pretty,
+    }
+}
+
+/// Tokens on one line, as used for inline groups and minified output.
+fn join(tokens: &[Token], unary: &[bool], pretty: bool) -> String {
+    let mut out = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 && needs_space(&tokens[i - 1], unary[i - 1], token, pretty) {
+            out.push(' ');
+        }
+        out.push_str(&token.text);
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frame {
+    Clause,
+    Paren,
+    Case,
+}
+
+struct Printer<'a> {
+    tokens: &'a [Token],
+    unary: &'a [bool],
+    unit: String,
+    options: &'a SqlFormatOptions,
+    out: String,
+    frames: Vec<Frame>, // Each frame is one level of indentation
+    line_start: bool,
+    prev: Option<usize>, // Last token written
+    between: bool,       // The next AND belongs to BETWEEN
+}
+
+impl Printer<'_> {
+    fn run(&mut self) {
+        let tokens = self.tokens;
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = &tokens[i];
+            match token.kind {
+                Kind::LineComment => {
+                    self.write(i);
+                    self.newline();
+                }
+                Kind::BlockComment if token.text.contains('\n') => {
+                    self.newline();
+                    self.write(i);
+                    self.newline();
+                }
+                Kind::Keyword if self.starts_clause(i) => {
+                    self.end_clause();
+                    self.newline();
+                    self.write(i);
+                    self.frames.push(Frame::Clause);
+                    self.newline();
+                }
+                Kind::Keyword if is(token, SET_OPERATORS) => {
+                    self.end_clause();
+                    self.newline();
+                    self.write(i);
+                    self.newline();
+                }
+                Kind::Keyword if token.is_keyword("AND") && self.between => {
+                    self.between = false;
+                    self.write(i);
+                }
+                Kind::Keyword if is(token, LINE_BREAKS) => {
+                    self.newline();
+                    self.write(i);
+                }
+                Kind::Keyword if token.is_keyword("BETWEEN") => {
+                    self.between = true;
+                    self.write(i);
+                }
+                Kind::Keyword if token.is_keyword("CASE") => {
+                    if let Some(end) = self.inline_group(i) {
+                        i = end + 1;
+                        continue;
+                    }
+                    self.write(i);
+                    self.frames.push(Frame::Case);
+                }
+                Kind::Keyword if token.is_keyword("END") && self.frames.contains(&Frame::Case) => {
+                    self.close(Frame::Case);
+                    self.newline();
+                    self.write(i);
+                }
+                Kind::Open => {
+                    if let Some(end) = self.inline_group(i) {
+                        i = end + 1;
+                        continue;
+                    }
+                    self.write(i);
+                    self.frames.push(Frame::Paren);
+                    self.newline();
+                }
+                Kind::Close => {
+                    self.close(Frame::Paren);
+                    self.newline();
+                    self.write(i);
+                }
+                Kind::Comma if self.options.comma_position == CommaPosition::Leading => {
+                    self.newline();
+                    self.write(i);
+                }
+                Kind::Comma => {
+                    self.write(i);
+                    self.newline();
+                }
+                Kind::Semicolon => {
+                    self.write(i);
+                    self.frames.clear();
+                    self.between = false;
+                    self.newline();
+                    if i + 1 < tokens.len() {
+                        self.out.push('\n');
+                    }
+                }
+                _ => self.write(i),
+            }
+            i += 1;
+        }
+    }
+
+    fn starts_clause(&self, i: usize) -> bool {
+        let tokens = self.tokens;
+        is(&tokens[i], CLAUSES)
+            // ON CONFLICT ... DO UPDATE SET, and T-SQL WITH (NOLOCK) hints.
+            && !(tokens[i].is_keyword("UPDATE") && i > 0 && tokens[i - 1].is_keyword("DO"))
+            && !(tokens[i].is_keyword("WITH")
+                && tokens.get(i + 1).is_some_and(|t| t.kind == Kind::Open))
+    }
+
+    fn end_clause(&mut self) {
+        if self.frames.last() == Some(&Frame::Clause) {
+            self.frames.pop();
+        }
+    }
+
+    /// Pop frames up to and including the innermost `frame`.
+    fn close(&mut self, frame: Frame) {
+        if self.frames.contains(&frame) {
+            while self.frames.pop().is_some_and(|f| f != frame) {}
+        }
+    }
+
+    fn newline(&mut self) {
+        let trimmed = self.out.trim_end_matches([' ', '\t']).len();
+        self.out.truncate(trimmed);
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+        self.line_start = true;
+    }
+
+    /// Write `text` standing for the tokens `first..=last`.
+    fn write_span(&mut self, first: usize, last: usize, text: &str) {
+        if self.line_start {
+            self.out.push_str(&self.unit.repeat(self.frames.len()));
+            self.line_start = false;
+        } else if let Some(prev) = self.prev {
+            if needs_space(
+                &self.tokens[prev],
+                self.unary[prev],
+                &self.tokens[first],
+                true,
+            ) {
+                self.out.push(' ');
+            }
+        }
+        self.out.push_str(text);
+        self.prev = Some(last);
+    }
+
+    fn write(&mut self, i: usize) {
+        self.write_span(i, i, &self.tokens[i].text);
+    }
+
+    /// Write the parenthesized group or CASE expression starting at `start`
+    /// on the current line if it fits; returns the index of its last token.
+    fn inline_group(&mut self, start: usize) -> Option<usize> {
+        let tokens = self.tokens;
+        let case = tokens[start].kind == Kind::Keyword;
+        let mut depth = 0;
+        let mut end = None;
+        for (i, token) in tokens.iter().enumerate().skip(start) {
+            let (open, close) = if case {
+                (token.is_keyword("CASE"), token.is_keyword("END"))
+            } else {
+                (token.kind == Kind::Open, token.kind == Kind::Close)
+            };
+            if open {
+                depth += 1;
+            } else if close {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(i);
+                    break;
+                }
+            }
+            if matches!(
+                token.kind,
+                Kind::LineComment | Kind::BlockComment | Kind::Semicolon
+            ) || is(token, STATEMENTS)
+                || is(token, SET_OPERATORS)
+            {
+                return None;
+            }
+        }
+        let end = end?;
+        let text = join(&tokens[start..=end], &self.unary[start..=end], true);
+
+        let line = &self.out[self.out.rfind('\n').map_or(0, |i| i + 1)..];
+        let indent = if self.line_start {
+            self.unit.repeat(self.frames.len())
+        } else {
+            String::new()
+        };
+        let column = (indent + line).replace('\t', "    ").chars().count() + 1;
+        if column + text.chars().count() > self.options.line_width {
+            return None;
+        }
+        self.write_span(start, end, &text);
+        Some(end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pretty(input: &str, dialect: SqlDialect, options: SqlFormatOptions) -> String {
+        format_sql(input, dialect, IndentStyle::Spaces(2), Some(options)).unwrap()
+    }
+
+    #[test]
+    fn formats_clauses_and_subqueries() {
+        let input = "select a, count(*) as n, case when b > 0 then 'pos' else 'neg' end from t1 \
+                     left join t2 on t1.id = t2.id where x between 1 and 5 and y in (select id from t3) \
+                     group by a order by n desc; select 1";
+        assert_eq!(
+            pretty(input, SqlDialect::PostgreSql, SqlFormatOptions::default()),
+            "SELECT\n  a,\n  count(*) AS n,\n  CASE WHEN b > 0 THEN 'pos' ELSE 'neg' END\nFROM\n  t1\n  \
+             LEFT JOIN t2 ON t1.id = t2.id\nWHERE\n  x BETWEEN 1 AND 5\n  AND y IN (\n    SELECT\n      id\n    \
+             FROM\n      t3\n  )\nGROUP BY\n  a\nORDER BY\n  n DESC;\n\nSELECT\n  1\n"
+        );
+
+        let options = SqlFormatOptions {
+            keyword_case: KeywordCase::Lower,
+            comma_position: CommaPosition::Leading,
+            ..Default::default()
+        };
+        assert_eq!(
+            pretty(
+                "SELECT a, b::text FROM t WHERE c = -1",
+                SqlDialect::PostgreSql,
+                options
+            ),
+            "select\n  a\n  , b::text\nfrom\n  t\nwhere\n  c = -1\n"
+        );
+    }
+
+    #[test]
+    fn dialect_quoting_and_minify() {
+        let minify =
+            |input: &str, dialect| format_sql(input, dialect, IndentStyle::Minified, None).unwrap();
+        assert_eq!(
+            minify(
+                "select `a` -- note\n, \"it's\" # more\nfrom t",
+                SqlDialect::MySql
+            ),
+            "SELECT `a`,\"it's\" FROM t"
+        );
+        assert_eq!(
+            minify(
+                "select [order] /* c */ from [dbo].[t] where x = - 1",
+                SqlDialect::TSql
+            ),
+            "SELECT [order] FROM [dbo].[t] WHERE x= -1"
+        );
+        assert_eq!(
+            minify(
+                "select $fn$ select 'x' $fn$, a - -b",
+                SqlDialect::PostgreSql
+            ),
+            "SELECT $fn$ select 'x' $fn$,a- -b"
+        );
+
+        let err = format_sql(
+            "select 'open",
+            SqlDialect::Sqlite,
+            IndentStyle::Minified,
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(err.diagnostic().unwrap().column, 8);
+    }
+
+    #[test]
+    fn inlines_parameters() {
+        let with = |params: &str| SqlFormatOptions {
+            params: Some(params.to_string()),
+            ..Default::default()
+        };
+        let minify = |input: &str, dialect, params: &str| {
+            format_sql(input, dialect, IndentStyle::Minified, Some(with(params))).unwrap()
+        };
+        assert_eq!(
+            minify(
+                "select * from t where a = $2 and b = $1",
+                SqlDialect::PostgreSql,
+                r#"["it's", [1, 2]]"#
+            ),
+            "SELECT * FROM t WHERE a=ARRAY[1, 2] AND b='it''s'"
+        );
+        assert_eq!(
+            minify(
+                "insert into t values (?, ?, ?)",
+                SqlDialect::MySql,
+                r#"[null, true, "a\\b"]"#
+            ),
+            "INSERT INTO t VALUES(NULL,TRUE,'a\\\\b')"
+        );
+        assert_eq!(
+            minify(
+                "select * from t where id = @id",
+                SqlDialect::TSql,
+                r#"{"id": 7}"#
+            ),
+            "SELECT * FROM t WHERE id=7"
+        );
+
+        let err = format_sql(
+            "select :name",
+            SqlDialect::Sqlite,
+            IndentStyle::Minified,
+            Some(with("[1]")),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.diagnostic().unwrap().message,
+            "No value for parameter :name"
+        );
+    }
+
+    fn inline(input: &str, dialect: SqlDialect, params: &str) -> Result<String, UtilityError> {
+        let options = SqlFormatOptions {
+            params: Some(params.to_string()),
+            ..Default::default()
+        };
+        format_sql(input, dialect, IndentStyle::Minified, Some(options))
+    }
+
+    #[test]
+    fn inlines_postgresql_parameters() {
+        let dialect = SqlDialect::PostgreSql;
+        assert_eq!(
+            inline("select $1, $2, $1", dialect, r#"[true, {"k": "it's"}]"#).unwrap(),
+            r#"SELECT TRUE,'{"k":"it''s"}',TRUE"#
+        );
+        // Numbered placeholders can also come from an object.
+        assert_eq!(
+            inline("select $2", dialect, r#"{"2": 1.5}"#).unwrap(),
+            "SELECT 1.5"
+        );
+        // `$$` strings and `::` casts are left alone.
+        assert_eq!(
+            inline("select $1::int, $$ $1 $$", dialect, "[3]").unwrap(),
+            "SELECT 3::int,$$ $1 $$"
+        );
+        let err = inline("select $0", dialect, "[1]").unwrap_err();
+        assert_eq!(
+            err.diagnostic().unwrap().message,
+            "No value for parameter $0"
+        );
+    }
+
+    #[test]
+    fn inlines_mysql_parameters() {
+        let dialect = SqlDialect::MySql;
+        assert_eq!(
+            inline("select ?, '?', ? in ?", dialect, r#"["a'b", 1, [2, "c"]]"#).unwrap(),
+            "SELECT 'a''b','?',1 IN (2, 'c')"
+        );
+        // Session variables are not parameters.
+        assert_eq!(
+            inline("select @v, ?", dialect, "[false]").unwrap(),
+            "SELECT @v,FALSE"
+        );
+        let err = inline("select ?, ?", dialect, "[1]").unwrap_err();
+        assert_eq!(err.diagnostic().unwrap().column, 11);
+    }
+
+    #[test]
+    fn inlines_sqlite_parameters() {
+        let dialect = SqlDialect::Sqlite;
+        assert_eq!(
+            inline(
+                "select :a, @b, $c, ?2",
+                dialect,
+                r#"{"a": 1, "b": null, "c": "x", "2": true}"#
+            )
+            .unwrap(),
+            "SELECT 1,NULL,'x',TRUE"
+        );
+        assert_eq!(
+            inline("select ?, ?1", dialect, "[7]").unwrap(),
+            "SELECT 7,7"
+        );
+        let err = inline("select :name", dialect, "[1]").unwrap_err();
+        assert_eq!(
+            err.diagnostic().unwrap().hint.as_deref(),
+            Some("Named placeholders take their values from a JSON object")
+        );
+    }
+
+    #[test]
+    fn inlines_tsql_parameters() {
+        let dialect = SqlDialect::TSql;
+        // There is no boolean literal; bits stand in for them.
+        assert_eq!(
+            inline(
+                "select @p, @q from t where x in @r",
+                dialect,
+                r#"{"p": true, "q": "O'Neil", "r": [1, 2]}"#
+            )
+            .unwrap(),
+            "SELECT 1,'O''Neil' FROM t WHERE x IN (1, 2)"
+        );
+        // `@@rowcount` is a system function, not a parameter.
+        assert_eq!(
+            inline("select @@rowcount", dialect, "{}").unwrap(),
+            "SELECT @@rowcount"
+        );
+    }
+
+    #[test]
+    fn inlines_bigquery_parameters() {
+        let dialect = SqlDialect::BigQuery;
+        assert_eq!(
+            inline(
+                "select @s, @a",
+                dialect,
+                r#"{"s": "a\\b'c\nd", "a": ["x", 1]}"#
+            )
+            .unwrap(),
+            r"SELECT 'a\\b\'c\nd',['x', 1]"
+        );
+        assert_eq!(
+            inline("select ?, ?", dialect, "[1, false]").unwrap(),
+            "SELECT 1,FALSE"
+        );
+
+        assert!(inline("select 1", dialect, "1").is_err());
+        assert!(inline("select 1", dialect, "[").is_err());
+    }
+}
@@ -0,0 +1,807 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Dialect-aware SQL tokenizer.
+//!
+//! Only lexical structure is recognized: strings, quoted identifiers,
+//! comments and placeholders differ between dialects, everything else is a
+//! word, number, operator or punctuation token. Keywords are words found in
+//! the keyword table; the multi-word ones that start clauses are merged into
+//! a single token.
+
+use super::SqlDialect;
+use crate::error::{Diagnostic, UtilityError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Kind {
+    Word,
+    Keyword,
+    Quoted, // Quoted identifier
+    String,
+    Number,
+    Placeholder,
+    Operator,
+    Comma,
+    Open,
+    Close,
+    Semicolon,
+    Dot,
+    LineComment,
+    BlockComment,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Token {
+    pub kind: Kind,
+    pub text: String,
+    pub offset: usize,      // Byte offset into the input
+    pub space_before: bool, // Whether the input had white space before it
+}
+
+impl Token {
+    /// Keyword text normalized for table lookups.
+    pub fn upper(&self) -> String {
+        self.text.to_ascii_uppercase()
+    }
+
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == Kind::Keyword && self.text.eq_ignore_ascii_case(keyword)
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "ADD",
+    "ALL",
+    "ALTER",
+    "AND",
+    "ANY",
+    "AS",
+    "ASC",
+    "BETWEEN",
+    "BY",
+    "CASCADE",
+    "CASE",
+    "CAST",
+    "CHECK",
+    "COLLATE",
+    "COLUMN",
+    "CONFLICT",
+    "CONSTRAINT",
+    "CREATE",
+    "CROSS",
+    "CURRENT_DATE",
+    "CURRENT_TIME",
+    "CURRENT_TIMESTAMP",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DISTINCT",
+    "DO",
+    "DROP",
+    "ELSE",
+    "END",
+    "ESCAPE",
+    "EXCEPT",
+    "EXISTS",
+    "EXTRACT",
+    "FALSE",
+    "FETCH",
+    "FILTER",
+    "FIRST",
+    "FOLLOWING",
+    "FOR",
+    "FOREIGN",
+    "FROM",
+    "FULL",
+    "GROUP",
+    "HAVING",
+    "IF",
+    "IN",
+    "INDEX",
+    "INNER",
+    "INSERT",
+    "INTERSECT",
+    "INTERVAL",
+    "INTO",
+    "IS",
+    "JOIN",
+    "KEY",
+    "LAST",
+    "LATERAL",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "NATURAL",
+    "NEXT",
+    "NOT",
+    "NOTHING",
+    "NULL",
+    "NULLS",
+    "OF",
+    "OFFSET",
+    "ON",
+    "ONLY",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "OVER",
+    "PARTITION",
+    "PRECEDING",
+    "PRIMARY",
+    "RANGE",
+    "RECURSIVE",
+    "REFERENCES",
+    "RETURNING",
+    "RIGHT",
+    "ROW",
+    "ROWS",
+    "SELECT",
+    "SET",
+    "SOME",
+    "TABLE",
+    "THEN",
+    "TO",
+    "TRUE",
+    "TRUNCATE",
+    "UNBOUNDED",
+    "UNION",
+    "UNIQUE",
+    "UPDATE",
+    "USING",
+    "VALUES",
+    "VIEW",
+    "WHEN",
+    "WHERE",
+    "WINDOW",
+    "WITH",
+    "WITHIN",
+];
+
+fn dialect_keywords(dialect: SqlDialect) -> &'static [&'static str] {
+    match dialect {
+        SqlDialect::PostgreSql => &["ILIKE", "SIMILAR", "CONCURRENTLY", "MATERIALIZED"],
+        SqlDialect::MySql => &[
+            "REGEXP",
+            "RLIKE",
+            "DIV",
+            "MOD",
+            "XOR",
+            "STRAIGHT_JOIN",
+            "DUPLICATE",
+            "IGNORE",
+            "REPLACE",
+            "AUTO_INCREMENT",
+        ],
+        SqlDialect::Sqlite => &[
+            "GLOB",
+            "REGEXP",
+            "PRAGMA",
+            "AUTOINCREMENT",
+            "REPLACE",
+            "IGNORE",
+            "ABORT",
+            "VACUUM",
+        ],
+        SqlDialect::TSql => &[
+            "TOP", "DECLARE", "OUTPUT", "APPLY", "PIVOT", "UNPIVOT", "MERGE", "MATCHED", "GO",
+            "BEGIN", "EXEC", "NOLOCK",
+        ],
+        SqlDialect::BigQuery => &["QUALIFY", "STRUCT", "UNNEST", "SAFE_CAST", "REPLACE"],
+    }
+}
+
+/// Multi-word keywords, longest first within each leading word.
+const PHRASES: &[&[&str]] = &[
+    &["GROUP", "BY"],
+    &["ORDER", "BY"],
+    &["PARTITION", "BY"],
+    &["INSERT", "INTO"],
+    &["REPLACE", "INTO"],
+    &["DELETE", "FROM"],
+    &["SELECT", "DISTINCT"],
+    &["SELECT", "ALL"],
+    &["UNION", "ALL"],
+    &["UNION", "DISTINCT"],
+    &["ON", "DUPLICATE", "KEY", "UPDATE"],
+    &["ON", "CONFLICT"],
+    &["WITH", "RECURSIVE"],
+    &["INNER", "JOIN"],
+    &["LEFT", "OUTER", "JOIN"],
+    &["LEFT", "JOIN"],
+    &["RIGHT", "OUTER", "JOIN"],
+    &["RIGHT", "JOIN"],
+    &["FULL", "OUTER", "JOIN"],
+    &["FULL", "JOIN"],
+    &["CROSS", "JOIN"],
+    &["NATURAL", "JOIN"],
+    &["CROSS", "APPLY"],
+    &["OUTER", "APPLY"],
+];
+
+/// Longest operators first.
+const OPERATORS: &[&str] = &[
+    "->>", "#>>", "<=>", "!~*", "<>", "!=", "<=", ">=", "||", "::", ":=", "->", "#>", "=>", "<<",
+    ">>", "@>", "<@", "&&", "~*", "!~", "?|", "?&", "#-",
+];
+
+fn is_word_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_word_char(c: char, dialect: SqlDialect) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$' || (c == '#' && dialect == SqlDialect::TSql)
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    dialect: SqlDialect,
+    pos: usize,
+}
+
+impl Lexer<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) {
+        let len = self
+            .rest()
+            .char_indices()
+            .find(|&(_, c)| !f(c))
+            .map_or(self.rest().len(), |(i, _)| i);
+        self.pos += len;
+    }
+
+    fn unterminated(&self, start: usize, what: &str) -> UtilityError {
+        Diagnostic::new(self.input, start, format!("Unterminated {}", what)).into()
+    }
+
+    /// Scan to the closing `quote`; a doubled quote (or, where the dialect
+    /// allows it, a backslash) escapes it.
+    fn quoted(
+        &mut self,
+        start: usize,
+        quote: char,
+        backslash: bool,
+        what: &str,
+    ) -> Result<(), UtilityError> {
+        self.pos += quote.len_utf8();
+        loop {
+            let Some(c) = self.peek(0) else {
+                return Err(self.unterminated(start, what));
+            };
+            self.pos += c.len_utf8();
+            if c == '\\' && backslash {
+                if let Some(next) = self.peek(0) {
+                    self.pos += next.len_utf8();
+                }
+            } else if c == quote {
+                if self.peek(0) == Some(quote) {
+                    self.pos += quote.len_utf8();
+                } else {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn string(&mut self, start: usize, backslash: bool) -> Result<(), UtilityError> {
+        let quote = self.peek(0).unwrap_or('\'');
+        let triple = quote.to_string().repeat(3);
+        if self.dialect == SqlDialect::BigQuery && self.rest().starts_with(&triple) {
+            let Some(end) = self.rest()[3..].find(&triple) else {
+                return Err(self.unterminated(start, "string literal"));
+            };
+            self.pos += end + 6;
+            return Ok(());
+        }
+        self.quoted(start, quote, backslash, "string literal")
+    }
+
+    /// PostgreSQL `$tag$ ... $tag$`; returns false when `$` starts
+    /// something else.
+    fn dollar_quoted(&mut self, start: usize) -> Result<bool, UtilityError> {
+        let rest = self.rest();
+        let tag_len = rest[1..]
+            .char_indices()
+            .find(|&(_, c)| !(c.is_alphanumeric() || c == '_'))
+            .map_or(rest.len() - 1, |(i, _)| i);
+        let tag = &rest[..tag_len + 1];
+        if !rest[tag_len + 1..].starts_with('$')
+            || tag[1..].starts_with(|c: char| c.is_ascii_digit())
+        {
+            return Ok(false);
+        }
+        let delimiter = format!("{}$", tag);
+        let Some(end) = rest[delimiter.len()..].find(&delimiter) else {
+            return Err(self.unterminated(start, "dollar-quoted string"));
+        };
+        self.pos += delimiter.len() * 2 + end;
+        Ok(true)
+    }
+
+    fn block_comment(&mut self, start: usize) -> Result<(), UtilityError> {
+        // PostgreSQL block comments nest.
+        let nested = self.dialect == SqlDialect::PostgreSql;
+        let mut depth = 0;
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("/*") && (depth == 0 || nested) {
+                depth += 1;
+                self.pos += 2;
+            } else if rest.starts_with("*/") {
+                depth -= 1;
+                self.pos += 2;
+                if depth == 0 {
+                    return Ok(());
+                }
+            } else if let Some(c) = rest.chars().next() {
+                self.pos += c.len_utf8();
+            } else {
+                return Err(self.unterminated(start, "block comment"));
+            }
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<Kind>, UtilityError> {
+        use SqlDialect::*;
+        let dialect = self.dialect;
+        let start = self.pos;
+        let Some(c) = self.peek(0) else {
+            return Ok(None);
+        };
+        let next = self.peek(1);
+        let input = self.input;
+        let rest = &input[start..];
+
+        let kind = match c {
+            '-' if next == Some('-') => {
+                self.take_while(|c| c != '\n');
+                Kind::LineComment
+            }
+            '#' if matches!(dialect, MySql | BigQuery) => {
+                self.take_while(|c| c != '\n');
+                Kind::LineComment
+            }
+            '/' if next == Some('*') => {
+                self.block_comment(start)?;
+                Kind::BlockComment
+            }
+            '\'' => {
+                self.string(start, matches!(dialect, MySql | BigQuery))?;
+                Kind::String
+            }
+            '"' if matches!(dialect, MySql | BigQuery) => {
+                self.string(start, true)?;
+                Kind::String
+            }
+            '"' => {
+                self.quoted(start, '"', false, "quoted identifier")?;
+                Kind::Quoted
+            }
+            '`' if matches!(dialect, MySql | Sqlite | BigQuery) => {
+                self.quoted(start, '`', false, "quoted identifier")?;
+                Kind::Quoted
+            }
+            '[' if matches!(dialect, TSql | Sqlite) => {
+                let Some(end) = rest.find(']') else {
+                    return Err(self.unterminated(start, "quoted identifier"));
+                };
+                self.pos += end + 1;
+                while self.rest().starts_with(']') {
+                    // `]]` escapes a bracket inside the name.
+                    let Some(end) = self.rest()[1..].find(']') else {
+                        return Err(self.unterminated(start, "quoted identifier"));
+                    };
+                    self.pos += end + 2;
+                }
+                Kind::Quoted
+            }
+            '$' if dialect == PostgreSql && next.is_some_and(|c| c.is_ascii_digit()) => {
+                self.pos += 1;
+                self.take_while(|c| c.is_ascii_digit());
+                Kind::Placeholder
+            }
+            '$' if dialect == PostgreSql && self.dollar_quoted(start)? => Kind::String,
+            '$' if dialect == Sqlite && next.is_some_and(is_word_start) => {
+                self.pos += 1;
+                self.take_while(|c| is_word_char(c, dialect));
+                Kind::Placeholder
+            }
+            '?' if dialect != PostgreSql => {
+                self.pos += 1;
+                if dialect == Sqlite {
+                    self.take_while(|c| c.is_ascii_digit());
+                }
+                Kind::Placeholder
+            }
+            ':' if dialect != TSql && next.is_some_and(is_word_start) => {
+                self.pos += 1;
+                self.take_while(|c| is_word_char(c, dialect));
+                Kind::Placeholder
+            }
+            '@' if next == Some('@') && matches!(dialect, MySql | TSql | BigQuery) => {
+                self.pos += 2;
+                self.take_while(|c| is_word_char(c, dialect));
+                Kind::Word
+            }
+            '@' if next.is_some_and(is_word_start) && dialect != PostgreSql => {
+                self.pos += 1;
+                self.take_while(|c| is_word_char(c, dialect));
+                // MySQL @variables are session state, not bound parameters.
+                if dialect == MySql {
+                    Kind::Word
+                } else {
+                    Kind::Placeholder
+                }
+            }
+            '#' if dialect == TSql => {
+                self.take_while(|c| is_word_char(c, dialect));
+                Kind::Word
+            }
+            c if c.is_ascii_digit() || (c == '.' && next.is_some_and(|c| c.is_ascii_digit())) => {
+                if rest.starts_with("0x") || rest.starts_with("0X") {
+                    self.pos += 2;
+                    self.take_while(|c| c.is_ascii_hexdigit());
+                } else {
+                    self.take_while(|c| c.is_ascii_digit());
+                    if self.peek(0) == Some('.') {
+                        self.pos += 1;
+                        self.take_while(|c| c.is_ascii_digit());
+                    }
+                    if matches!(self.peek(0), Some('e' | 'E'))
+                        && (self.peek(1).is_some_and(|c| c.is_ascii_digit())
+                            || (matches!(self.peek(1), Some('+' | '-'))
+                                && self.peek(2).is_some_and(|c| c.is_ascii_digit())))
+                    {
+                        self.pos += 2;
+                        self.take_while(|c| c.is_ascii_digit());
+                    }
+                }
+                Kind::Number
+            }
+            c if is_word_start(c) => {
+                self.take_while(|c| is_word_char(c, dialect));
+                let word = self.input[start..self.pos].to_ascii_uppercase();
+                // Prefixed strings: E'..', N'..', X'..', B'..', r'..', b'..'.
+                let prefixed = match dialect {
+                    PostgreSql => matches!(word.as_str(), "E" | "X" | "B" | "N" | "U&"),
+                    BigQuery => matches!(word.as_str(), "R" | "B" | "RB" | "BR"),
+                    _ => matches!(word.as_str(), "N" | "X" | "B"),
+                };
+                let quote = self.peek(0);
+                if prefixed && (quote == Some('\'') || (dialect == BigQuery && quote == Some('"')))
+                {
+                    let backslash = matches!(dialect, MySql | BigQuery)
+                        || (dialect == PostgreSql && word == "E");
+                    self.string(start, backslash && !word.contains('R'))?;
+                    Kind::String
+                } else {
+                    Kind::Word
+                }
+            }
+            '(' => {
+                self.pos += 1;
+                Kind::Open
+            }
+            ')' => {
+                self.pos += 1;
+                Kind::Close
+            }
+            ',' => {
+                self.pos += 1;
+                Kind::Comma
+            }
+            ';' => {
+                self.pos += 1;
+                Kind::Semicolon
+            }
+            '.' => {
+                self.pos += 1;
+                Kind::Dot
+            }
+            _ => {
+                let len = OPERATORS
+                    .iter()
+                    .find(|op| rest.starts_with(*op))
+                    .map_or(c.len_utf8(), |op| op.len());
+                self.pos += len;
+                Kind::Operator
+            }
+        };
+        Ok(Some(kind))
+    }
+}
+
+pub(super) fn tokenize(input: &str, dialect: SqlDialect) -> Result<Vec<Token>, UtilityError> {
+    let mut lexer = Lexer {
+        input,
+        dialect,
+        pos: 0,
+    };
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut space_before = false;
+    loop {
+        let ws = lexer.rest().len() - lexer.rest().trim_start().len();
+        if ws > 0 {
+            lexer.pos += ws;
+            space_before = true;
+        }
+        let offset = lexer.pos;
+        let Some(kind) = lexer.next()? else {
+            break;
+        };
+        tokens.push(Token {
+            kind,
+            text: input[offset..lexer.pos].trim_end().to_string(),
+            offset,
+            space_before,
+        });
+        space_before = false;
+    }
+
+    // Classify keywords; a word next to `.` is always a name.
+    let keywords = dialect_keywords(dialect);
+    for i in 0..tokens.len() {
+        if tokens[i].kind != Kind::Word {
+            continue;
+        }
+        let dotted = (i > 0 && tokens[i - 1].kind == Kind::Dot)
+            || tokens.get(i + 1).is_some_and(|t| t.kind == Kind::Dot);
+        let upper = tokens[i].upper();
+        if !dotted && (KEYWORDS.contains(&upper.as_str()) || keywords.contains(&upper.as_str())) {
+            tokens[i].kind = Kind::Keyword;
+        }
+    }
+
+    // Merge multi-word keywords into one token.
+    let mut merged: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let phrase = (tokens[i].kind == Kind::Keyword)
+            .then(|| {
+                PHRASES.iter().find(|phrase| {
+                    phrase.iter().enumerate().all(|(j, word)| {
+                        tokens.get(i + j).is_some_and(|t| {
+                            matches!(t.kind, Kind::Word | Kind::Keyword)
+                                && t.text.eq_ignore_ascii_case(word)
+                        })
+                    })
+                })
+            })
+            .flatten();
+        match phrase {
+            Some(phrase) => {
+                let mut token = tokens[i].clone();
+                token.text = tokens[i..i + phrase.len()]
+                    .iter()
+                    .map(|t| t.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                merged.push(token);
+                i += phrase.len();
+            }
+            None => {
+                merged.push(tokens[i].clone());
+                i += 1;
+            }
+        }
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lex(input: &str, dialect: SqlDialect) -> Vec<(Kind, String)> {
+        tokenize(input, dialect)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.kind, t.text))
+            .collect()
+    }
+
+    fn texts(input: &str, dialect: SqlDialect) -> Vec<String> {
+        lex(input, dialect)
+            .into_iter()
+            .map(|(_, text)| text)
+            .collect()
+    }
+
+    fn error(input: &str, dialect: SqlDialect) -> (usize, String) {
+        let err = tokenize(input, dialect).unwrap_err();
+        let d = err.diagnostic().unwrap();
+        (d.column, d.message.clone())
+    }
+
+    #[test]
+    fn dollar_quoted_strings() {
+        use Kind::*;
+        assert_eq!(
+            lex("$$ it's $x$ $$ || $body$ $$ $body$", SqlDialect::PostgreSql),
+            [
+                (String, "$$ it's $x$ $$".to_string()),
+                (Operator, "||".to_string()),
+                (String, "$body$ $$ $body$".to_string()),
+            ]
+        );
+        // `$1` is a placeholder and `$1$` cannot be a tag.
+        assert_eq!(
+            lex("$1$", SqlDialect::PostgreSql),
+            [(Placeholder, "$1".to_string()), (Operator, "$".to_string())]
+        );
+        // Tags may be any identifier, and only the matching one closes.
+        assert_eq!(
+            texts("$é$ $x$ $é$", SqlDialect::PostgreSql),
+            ["$é$ $x$ $é$"]
+        );
+        // Elsewhere `$` is part of a word.
+        assert_eq!(texts("a$b $$", SqlDialect::MySql)[0], "a$b");
+        assert_eq!(
+            error("select $tag$ open $tga$", SqlDialect::PostgreSql),
+            (8, "Unterminated dollar-quoted string".to_string())
+        );
+    }
+
+    #[test]
+    fn bracket_and_backtick_identifiers() {
+        assert_eq!(
+            lex("[a]]b].[c d]", SqlDialect::TSql),
+            [
+                (Kind::Quoted, "[a]]b]".to_string()),
+                (Kind::Dot, ".".to_string()),
+                (Kind::Quoted, "[c d]".to_string()),
+            ]
+        );
+        assert_eq!(lex("[x]", SqlDialect::Sqlite)[0].0, Kind::Quoted);
+        // Brackets are array syntax in PostgreSQL.
+        assert_eq!(lex("a[1]", SqlDialect::PostgreSql)[1].0, Kind::Operator);
+        assert_eq!(
+            error("select [a]]", SqlDialect::TSql),
+            (8, "Unterminated quoted identifier".to_string())
+        );
+
+        assert_eq!(
+            lex("`a``b` `select`", SqlDialect::MySql),
+            [
+                (Kind::Quoted, "`a``b`".to_string()),
+                (Kind::Quoted, "`select`".to_string()),
+            ]
+        );
+        assert_eq!(lex("`x`", SqlDialect::BigQuery)[0].0, Kind::Quoted);
+        assert_eq!(lex("`x`", SqlDialect::TSql)[0].0, Kind::Operator);
+        assert_eq!(
+            error("`open", SqlDialect::Sqlite).1,
+            "Unterminated quoted identifier"
+        );
+    }
+
+    #[test]
+    fn strings_by_dialect() {
+        assert_eq!(
+            lex(r#"'a\'b' "c""#, SqlDialect::MySql),
+            [
+                (Kind::String, r"'a\'b'".to_string()),
+                (Kind::String, r#""c""#.to_string()),
+            ]
+        );
+        // Standard strings only escape by doubling.
+        assert_eq!(
+            texts(r"'a\' 'b''c' E'd\'e'", SqlDialect::PostgreSql),
+            [r"'a\'", "'b''c'", r"E'd\'e'"]
+        );
+        assert_eq!(
+            texts("'''a ' b''' r'\\' \"x\"", SqlDialect::BigQuery),
+            ["'''a ' b'''", "r'\\'", "\"x\""]
+        );
+        assert_eq!(lex("N'x'", SqlDialect::TSql)[0].0, Kind::String);
+        assert_eq!(lex("\"x\"", SqlDialect::Sqlite)[0].0, Kind::Quoted);
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(
+            lex("/* a /* b */ c */ x", SqlDialect::PostgreSql),
+            [
+                (Kind::BlockComment, "/* a /* b */ c */".to_string()),
+                (Kind::Word, "x".to_string()),
+            ]
+        );
+        // Only PostgreSQL nests them.
+        assert_eq!(
+            texts("/* a /* b */ c", SqlDialect::MySql),
+            ["/* a /* b */", "c"]
+        );
+        assert_eq!(
+            error("x /* a /* b */", SqlDialect::PostgreSql),
+            (3, "Unterminated block comment".to_string())
+        );
+        assert_eq!(
+            lex("# note\nx -- more", SqlDialect::MySql),
+            [
+                (Kind::LineComment, "# note".to_string()),
+                (Kind::Word, "x".to_string()),
+                (Kind::LineComment, "-- more".to_string()),
+            ]
+        );
+        assert_eq!(
+            lex("#temp", SqlDialect::TSql)[0],
+            (Kind::Word, "#temp".to_string())
+        );
+    }
+
+    #[test]
+    fn casts_and_operators() {
+        use Kind::*;
+        assert_eq!(
+            lex("a::text->>'k' <> b", SqlDialect::PostgreSql),
+            [
+                (Word, "a".to_string()),
+                (Operator, "::".to_string()),
+                (Word, "text".to_string()),
+                (Operator, "->>".to_string()),
+                (String, "'k'".to_string()),
+                (Operator, "<>".to_string()),
+                (Word, "b".to_string()),
+            ]
+        );
+        // `::` is not taken for an empty `:name` placeholder.
+        assert_eq!(texts("x::int", SqlDialect::Sqlite), ["x", "::", "int"]);
+        assert_eq!(
+            texts("1.5e-3 .5 0xFF 2e", SqlDialect::MySql),
+            ["1.5e-3", ".5", "0xFF", "2", "e"]
+        );
+    }
+
+    #[test]
+    fn placeholders_by_dialect() {
+        let placeholders = |input: &str, dialect| -> Vec<String> {
+            lex(input, dialect)
+                .into_iter()
+                .filter(|(kind, _)| *kind == Kind::Placeholder)
+                .map(|(_, text)| text)
+                .collect()
+        };
+        assert_eq!(
+            placeholders("$1 $12 ? :a", SqlDialect::PostgreSql),
+            ["$1", "$12", ":a"]
+        );
+        assert_eq!(placeholders("? :a @v @@g", SqlDialect::MySql), ["?", ":a"]);
+        assert_eq!(
+            placeholders("? ?2 :a @b $c", SqlDialect::Sqlite),
+            ["?", "?2", ":a", "@b", "$c"]
+        );
+        assert_eq!(placeholders("@p @@rowcount :a", SqlDialect::TSql), ["@p"]);
+        assert_eq!(placeholders("@p ?", SqlDialect::BigQuery), ["@p", "?"]);
+    }
+
+    #[test]
+    fn keywords_and_phrases() {
+        let tokens = tokenize(
+            "select t.from, x from t left  outer join u",
+            SqlDialect::Sqlite,
+        )
+        .unwrap();
+        let kinds: Vec<(Kind, &str)> = tokens.iter().map(|t| (t.kind, t.text.as_str())).collect();
+        // A word next to `.` is a name even when it is a keyword.
+        assert_eq!(kinds[3], (Kind::Word, "from"));
+        assert_eq!(kinds[6], (Kind::Keyword, "from"));
+        assert_eq!(kinds[8], (Kind::Keyword, "left outer join"));
+        assert!(tokens[8].is_keyword("LEFT OUTER JOIN"));
+        assert!(tokens[6].space_before && !tokens[4].space_before);
+
+        // Dialect keywords only count in their dialect.
+        assert_eq!(lex("ilike", SqlDialect::PostgreSql)[0].0, Kind::Keyword);
+        assert_eq!(lex("ilike", SqlDialect::MySql)[0].0, Kind::Word);
+    }
+}
@@ -0,0 +1,973 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+mod lexer;
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use universal_function_macro::universal_function;
+
+use super::QuoteStyle;
+use crate::error::{strip_location, Diagnostic, Severity, UtilityError};
+use lexer::{Kind, Token};
+
+/// Deeper arrays and inline tables are rejected, since printing recurses.
+const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct TomlFormatOptions {
+    pub indent: usize,           // Spaces for items of multi-line arrays
+    pub indent_tables: bool,     // Indent sub-tables and their keys by depth
+    pub quote_style: QuoteStyle, // Single means literal strings
+}
+
+impl Default for TomlFormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            indent_tables: false,
+            quote_style: QuoteStyle::Double,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct TomlFormatResult {
+    pub output: String,
+    pub diagnostics: Vec<Diagnostic>, // Redefinitions and table ordering problems
+}
+
+/// Normalize TOML spacing and quoting, keeping comments and key order.
+///
+/// Keys and dotted paths lose needless quotes, `=` and commas get single
+/// spaces, tables are separated by a blank line and multi-line arrays put
+/// one item per line with a trailing comma. Duplicate keys and tables,
+/// keys that collide with tables, sub-tables placed away from their parent
+/// and parents placed after their sub-tables are reported in
+/// `diagnostics`; syntax errors fail the call.
+#[universal_function]
+pub fn format_toml(
+    input: &str,
+    options: Option<TomlFormatOptions>,
+) -> Result<TomlFormatResult, UtilityError> {
+    let options = options.unwrap_or_default();
+    let tokens = lexer::tokenize(input)?;
+    let has_datetimes = tokens.iter().any(|t| {
+        t.kind == Kind::Atom
+            && (t.text.contains(':') || t.text.get(..10).is_some_and(lexer::is_date))
+    });
+
+    let mut printer = Printer {
+        input,
+        tokens,
+        pos: 0,
+        options,
+        out: String::with_capacity(input.len()),
+        pending: Vec::new(),
+        table_indent: 0,
+        table: Vec::new(),
+        defined: HashMap::new(),
+        headers: Vec::new(),
+        arrays: HashMap::new(),
+        diagnostics: Vec::new(),
+        depth: 0,
+    };
+    printer.document()?;
+    let Printer {
+        out,
+        mut diagnostics,
+        ..
+    } = printer;
+
+    // The parser has no date-time support, so documents with them are
+    // only checked by the printer.
+    if !has_datetimes {
+        match parse(input) {
+            Err(error) if !diagnostics.iter().any(|d| d.severity == Severity::Error) => {
+                return Err(error.into());
+            }
+            Ok(before) if parse(&out).ok().as_ref() != Some(&before) => {
+                return Err(UtilityError::Runtime(
+                    "Formatting would change the meaning of this document".to_string(),
+                ));
+            }
+            _ => {}
+        }
+    }
+    diagnostics.sort_by_key(|d| d.offset);
+    Ok(TomlFormatResult {
+        output: out,
+        diagnostics,
+    })
+}
+
+fn parse(input: &str) -> Result<serde_json::Value, Diagnostic> {
+    basic_toml::from_str(input).map_err(|e| {
+        let message = format!("Invalid TOML: {}", strip_location(&e.to_string()));
+        match e.line_col() {
+            Some((line, column)) => {
+                Diagnostic::at_line_column(input, line + 1, column + 1, message)
+            }
+            None => Diagnostic::new(input, 0, message),
+        }
+    })
+}
+
+/// How a dotted path got defined.
+#[derive(Debug, Clone, Copy)]
+enum Defined {
+    Implicit,          // As the parent of a header
+    Header(usize),     // By a [table] header at this offset
+    ArrayTable(usize), // By an [[array]] header
+    Dotted(usize),     // As the parent of a dotted key
+    Value(usize),      // By a key with a value
+}
+
+enum Pending {
+    Blank,
+    Comment(String),
+}
+
+/// One line of a multi-line array.
+enum Entry {
+    Item(String, Option<String>),
+    Comment(String),
+}
+
+struct Printer<'a> {
+    input: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    options: TomlFormatOptions,
+    out: String,
+    pending: Vec<Pending>,
+    table_indent: usize,
+    // Resolved path of the current table; array tables add an index part.
+    table: Vec<String>,
+    defined: HashMap<Vec<String>, Defined>,
+    headers: Vec<(Vec<String>, String, usize)>, // Path, display text and offset
+    arrays: HashMap<Vec<String>, usize>,        // Elements per array of tables
+    diagnostics: Vec<Diagnostic>,
+    depth: usize, // Arrays and inline tables currently open
+}
+
+impl Printer<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn error_at(&self, offset: usize, message: &str) -> UtilityError {
+        Diagnostic::new(self.input, offset, message).into()
+    }
+
+    fn here(&self) -> usize {
+        self.peek().map_or(self.input.len(), |t| t.offset)
+    }
+
+    fn line_of(&self, offset: usize) -> usize {
+        Diagnostic::new(self.input, offset, "").line
+    }
+
+    fn report(&mut self, offset: usize, length: usize, message: String, hint: Option<String>) {
+        let mut diagnostic = Diagnostic::new(self.input, offset, message).with_length(length);
+        if let Some(hint) = hint {
+            diagnostic = diagnostic.with_hint(hint);
+        }
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn push_line(&mut self, indent: usize, text: &str) {
+        self.out.extend(std::iter::repeat_n(' ', indent));
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn flush(&mut self, indent: usize) {
+        for pending in std::mem::take(&mut self.pending) {
+            match pending {
+                Pending::Blank => {
+                    if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+                        self.out.push('\n');
+                    }
+                }
+                Pending::Comment(text) => self.push_line(indent, &text),
+            }
+        }
+    }
+
+    fn document(&mut self) -> Result<(), UtilityError> {
+        while let Some(token) = self.peek() {
+            match token.kind {
+                Kind::Newline => {
+                    let blank = self.pos == 0 || self.tokens[self.pos - 1].kind == Kind::Newline;
+                    if blank && !matches!(self.pending.last(), Some(Pending::Blank)) {
+                        self.pending.push(Pending::Blank);
+                    }
+                    self.pos += 1;
+                }
+                Kind::Comment => {
+                    self.pending.push(Pending::Comment(token.text.clone()));
+                    self.pos += 1;
+                }
+                Kind::Punct if token.is('[') => self.header()?,
+                Kind::Atom | Kind::String => self.key_value()?,
+                _ => return Err(self.error_at(token.offset, "Expected a key or a [table] header")),
+            }
+        }
+        self.flush(0);
+        Ok(())
+    }
+
+    /// A trailing comment, then the end of the line.
+    fn end_of_line(&mut self) -> Result<String, UtilityError> {
+        let mut comment = String::new();
+        if let Some(token) = self.peek().filter(|t| t.kind == Kind::Comment) {
+            comment = format!(" {}", token.text);
+            self.pos += 1;
+        }
+        match self.peek() {
+            None => Ok(comment),
+            Some(token) if token.kind == Kind::Newline => Ok(comment),
+            Some(token) => Err(self.error_at(token.offset, "Expected a new line")),
+        }
+    }
+
+    /// Read a dotted key up to `=` or `]` and split it into parts.
+    fn key(&mut self) -> Result<(Vec<String>, usize, usize), UtilityError> {
+        let start = self.here();
+        let mut text = String::new();
+        let mut end = start;
+        while let Some(token) = self
+            .peek()
+            .filter(|t| matches!(t.kind, Kind::Atom | Kind::String))
+        {
+            if token.is_multiline() {
+                return Err(self.error_at(token.offset, "Keys cannot be multi-line strings"));
+            }
+            text.push_str(&token.text);
+            end = token.offset + token.text.len();
+            self.pos += 1;
+        }
+        let parts = key_parts(&text);
+        if parts.is_empty() || parts.iter().any(String::is_empty) {
+            return Err(self.error_at(start, "Expected a key"));
+        }
+        Ok((parts, start, end - start))
+    }
+
+    fn header(&mut self) -> Result<(), UtilityError> {
+        let start = self.here();
+        self.pos += 1;
+        let array = self
+            .peek()
+            .is_some_and(|t| t.is('[') && t.offset == start + 1);
+        if array {
+            self.pos += 1;
+        }
+        let (parts, key_offset, key_length) = self.key()?;
+        for _ in 0..1 + usize::from(array) {
+            match self.peek() {
+                Some(token) if token.is(']') => self.pos += 1,
+                _ => return Err(self.error_at(self.here(), "Expected `]` to close the header")),
+            }
+        }
+        let comment = self.end_of_line()?;
+
+        let names: Vec<String> = parts.iter().map(|p| unquote(p)).collect();
+        let display = parts
+            .iter()
+            .map(|p| bare_key(p))
+            .collect::<Vec<_>>()
+            .join(".");
+        self.define_table(&names, array, &display, key_offset, key_length);
+
+        self.table_indent = if self.options.indent_tables {
+            (parts.len() - 1) * self.options.indent
+        } else {
+            0
+        };
+        if !self.out.is_empty() && !matches!(self.pending.first(), Some(Pending::Blank)) {
+            self.pending.insert(0, Pending::Blank);
+        }
+        self.flush(self.table_indent);
+        let text = if array {
+            format!("[[{}]]{}", display, comment)
+        } else {
+            format!("[{}]{}", display, comment)
+        };
+        self.push_line(self.table_indent, &text);
+        Ok(())
+    }
+
+    fn key_value(&mut self) -> Result<(), UtilityError> {
+        let (parts, offset, length) = self.key()?;
+        match self.peek() {
+            Some(token) if token.is('=') => self.pos += 1,
+            _ => return Err(self.error_at(self.here(), "Expected `=` after the key")),
+        }
+        let value = self.value(self.table_indent)?;
+        let comment = self.end_of_line()?;
+
+        let names: Vec<String> = parts.iter().map(|p| unquote(p)).collect();
+        self.define_key(&names, offset, length);
+
+        let key = parts
+            .iter()
+            .map(|p| bare_key(p))
+            .collect::<Vec<_>>()
+            .join(".");
+        self.flush(self.table_indent);
+        self.push_line(
+            self.table_indent,
+            &format!("{} = {}{}", key, value, comment),
+        );
+        Ok(())
+    }
+
+    /// Print the value at the cursor; `indent` is where its line starts.
+    fn value(&mut self, indent: usize) -> Result<String, UtilityError> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error_at(self.input.len(), "Expected a value"));
+        };
+        self.pos += 1;
+        match token.kind {
+            Kind::String if token.is_multiline() => Ok(token.text),
+            Kind::String => Ok(requote(&token.text, self.options.quote_style)),
+            Kind::Atom => Ok(token.text),
+            Kind::Punct if token.is('[') || token.is('{') => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error_at(
+                        token.offset,
+                        &format!("Values are nested more than {} levels deep", MAX_DEPTH),
+                    ));
+                }
+                self.depth += 1;
+                let value = if token.is('[') {
+                    self.array(token.offset, indent)
+                } else {
+                    self.inline_table(indent)
+                };
+                self.depth -= 1;
+                value
+            }
+            _ => Err(self.error_at(token.offset, "Expected a value")),
+        }
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.peek().is_some_and(|t| t.kind == Kind::Newline) {
+            self.pos += 1;
+        }
+    }
+
+    fn array(&mut self, open: usize, indent: usize) -> Result<String, UtilityError> {
+        let item_indent = indent + self.options.indent;
+        let mut entries = Vec::new();
+        let mut multiline = false;
+        let mut separated = true;
+        loop {
+            if self.peek().is_some_and(|t| t.kind == Kind::Newline) {
+                multiline = true;
+                self.skip_newlines();
+            }
+            let Some(token) = self.peek() else {
+                return Err(self.error_at(open, "Unclosed array"));
+            };
+            if token.kind == Kind::Comment {
+                entries.push(Entry::Comment(token.text.clone()));
+                self.pos += 1;
+                continue;
+            }
+            if token.is(']') {
+                self.pos += 1;
+                break;
+            }
+            if !separated {
+                return Err(self.error_at(token.offset, "Expected `,` between array items"));
+            }
+            let item = self.value(item_indent)?;
+            self.skip_newlines_before_comma();
+            separated = self.peek().is_some_and(|t| t.is(','));
+            if separated {
+                self.pos += 1;
+            }
+            let comment = match self.peek() {
+                Some(t) if t.kind == Kind::Comment => {
+                    self.pos += 1;
+                    Some(self.tokens[self.pos - 1].text.clone())
+                }
+                _ => None,
+            };
+            entries.push(Entry::Item(item, comment));
+        }
+
+        let has_comments = entries.iter().any(|e| match e {
+            Entry::Comment(_) => true,
+            Entry::Item(_, comment) => comment.is_some(),
+        });
+        if !multiline && !has_comments {
+            let items: Vec<String> = entries
+                .into_iter()
+                .filter_map(|e| match e {
+                    Entry::Item(item, _) => Some(item),
+                    Entry::Comment(_) => None,
+                })
+                .collect();
+            return Ok(format!("[{}]", items.join(", ")));
+        }
+        let pad = " ".repeat(item_indent);
+        let mut out = String::from("[\n");
+        for entry in entries {
+            match entry {
+                Entry::Item(item, comment) => {
+                    out.push_str(&format!("{}{},", pad, item));
+                    if let Some(comment) = comment {
+                        out.push(' ');
+                        out.push_str(&comment);
+                    }
+                }
+                Entry::Comment(comment) => out.push_str(&format!("{}{}", pad, comment)),
+            }
+            out.push('\n');
+        }
+        out.push_str(&" ".repeat(indent));
+        out.push(']');
+        Ok(out)
+    }
+
+    /// A comma may follow an item on the next line.
+    fn skip_newlines_before_comma(&mut self) {
+        let mut pos = self.pos;
+        while self
+            .tokens
+            .get(pos)
+            .is_some_and(|t| t.kind == Kind::Newline)
+        {
+            pos += 1;
+        }
+        if self.tokens.get(pos).is_some_and(|t| t.is(',')) {
+            self.pos = pos;
+        }
+    }
+
+    fn inline_table(&mut self, indent: usize) -> Result<String, UtilityError> {
+        let mut entries = Vec::new();
+        let mut seen = HashSet::new();
+        if self.peek().is_some_and(|t| t.is('}')) {
+            self.pos += 1;
+            return Ok("{}".to_string());
+        }
+        loop {
+            let (parts, offset, length) = self.key()?;
+            let names: Vec<String> = parts.iter().map(|p| unquote(p)).collect();
+            if !seen.insert(names.clone()) {
+                self.report(
+                    offset,
+                    length,
+                    format!("Duplicate key `{}` in inline table", names.join(".")),
+                    None,
+                );
+            }
+            match self.peek() {
+                Some(token) if token.is('=') => self.pos += 1,
+                _ => return Err(self.error_at(self.here(), "Expected `=` after the key")),
+            }
+            let value = self.value(indent)?;
+            let key = parts
+                .iter()
+                .map(|p| bare_key(p))
+                .collect::<Vec<_>>()
+                .join(".");
+            entries.push(format!("{} = {}", key, value));
+            match self.peek() {
+                Some(token) if token.is(',') => self.pos += 1,
+                Some(token) if token.is('}') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return Err(self.error_at(self.here(), "Expected `,` or `}` in inline table")),
+            }
+        }
+        Ok(format!("{{ {} }}", entries.join(", ")))
+    }
+
+    /// The resolved path of `names`, following arrays of tables to their
+    /// last element.
+    fn resolve(&self, base: &[String], names: &[String]) -> Vec<String> {
+        let mut path = base.to_vec();
+        for name in names {
+            path.push(name.clone());
+            if let Some(count) = self.arrays.get(&path) {
+                path.push(format!("[{}]", count - 1));
+            }
+        }
+        path
+    }
+
+    fn define_table(
+        &mut self,
+        names: &[String],
+        array: bool,
+        display: &str,
+        offset: usize,
+        length: usize,
+    ) {
+        let (last, parents) = names.split_last().expect("keys have a part");
+        let mut path = self.resolve(&[], parents);
+        for i in 0..path.len() {
+            self.defined
+                .entry(path[..=i].to_vec())
+                .or_insert(Defined::Implicit);
+        }
+        path.push(last.clone());
+
+        let existing = self.defined.get(&path).copied();
+        let conflict = match (existing, array) {
+            (None, _) | (Some(Defined::Implicit), false) | (Some(Defined::ArrayTable(_)), true) => {
+                None
+            }
+            (Some(Defined::Header(at)), _) => {
+                Some((format!("Table [{}] is defined twice", display), at))
+            }
+            (Some(Defined::ArrayTable(at)), false) => {
+                Some((format!("[{}] is an array of tables", display), at))
+            }
+            (Some(Defined::Implicit), true) => Some((
+                format!("[[{}]] was already used as a table", display),
+                offset,
+            )),
+            (Some(Defined::Dotted(at)), _) => Some((
+                format!("Table [{}] was already created by dotted keys", display),
+                at,
+            )),
+            (Some(Defined::Value(at)), _) => {
+                Some((format!("`{}` already has a value", display), at))
+            }
+        };
+        if let Some((message, at)) = conflict {
+            let hint = format!("See line {}", self.line_of(at));
+            self.report(offset, length, message, Some(hint));
+        }
+
+        if matches!(existing, Some(Defined::Implicit)) && !array {
+            let child = self
+                .headers
+                .iter()
+                .find(|(other, _, _)| other.len() > path.len() && other.starts_with(&path));
+            if let Some((_, child, at)) = child {
+                let message = format!("Table [{}] comes after its sub-table [{}]", display, child);
+                let hint = format!("Move it before line {}", self.line_of(*at));
+                self.warn(offset, length, message, hint);
+            }
+        }
+
+        if array {
+            let count = self.arrays.entry(path.clone()).or_insert(0);
+            *count += 1;
+            let index = format!("[{}]", *count - 1);
+            self.defined
+                .insert(path.clone(), Defined::ArrayTable(offset));
+            path.push(index);
+        } else if existing.is_none() || matches!(existing, Some(Defined::Implicit)) {
+            self.defined.insert(path.clone(), Defined::Header(offset));
+        }
+
+        // A sub-table should follow its parent or the parent's other
+        // sub-tables.
+        let parent = self
+            .headers
+            .iter()
+            .filter(|(other, _, _)| other.len() < path.len() && path.starts_with(other))
+            .max_by_key(|(other, _, _)| other.len());
+        if let (Some((parent, parent_display, at)), Some((previous, previous_display, _))) =
+            (parent, self.headers.last())
+        {
+            if !previous.starts_with(parent) {
+                let message = format!(
+                    "Table [{}] is separated from [{}] by [{}]",
+                    display, parent_display, previous_display
+                );
+                let hint = format!(
+                    "Move it after [{}] on line {}",
+                    parent_display,
+                    self.line_of(*at)
+                );
+                self.warn(offset, length, message, hint);
+            }
+        }
+
+        self.headers
+            .push((path.clone(), display.to_string(), offset));
+        self.table = path;
+    }
+
+    fn warn(&mut self, offset: usize, length: usize, message: String, hint: String) {
+        self.diagnostics.push(
+            Diagnostic::new(self.input, offset, message)
+                .with_length(length)
+                .with_severity(Severity::Warning)
+                .with_hint(hint),
+        );
+    }
+
+    fn define_key(&mut self, names: &[String], offset: usize, length: usize) {
+        let (last, parents) = names.split_last().expect("keys have a part");
+        let mut path = self.table.clone();
+        for name in parents {
+            path.push(name.clone());
+            match self.defined.get(&path).copied() {
+                None | Some(Defined::Implicit) => {
+                    self.defined.insert(path.clone(), Defined::Dotted(offset));
+                }
+                Some(Defined::Dotted(_)) => {}
+                Some(Defined::Value(at) | Defined::Header(at) | Defined::ArrayTable(at)) => {
+                    let message = format!(
+                        "`{}` is already defined",
+                        path[self.table.len()..].join(".")
+                    );
+                    let hint = format!("See line {}", self.line_of(at));
+                    self.report(offset, length, message, Some(hint));
+                    return;
+                }
+            }
+        }
+        path.push(last.clone());
+        match self.defined.get(&path).copied() {
+            None => {
+                self.defined.insert(path, Defined::Value(offset));
+            }
+            Some(
+                Defined::Value(at)
+                | Defined::Dotted(at)
+                | Defined::Header(at)
+                | Defined::ArrayTable(at),
+            ) => {
+                let message = format!("Duplicate key `{}`", names.join("."));
+                let hint = format!("The first one is on line {}", self.line_of(at));
+                self.report(offset, length, message, Some(hint));
+            }
+            Some(Defined::Implicit) => {
+                let message = format!("`{}` is already a table", names.join("."));
+                self.report(offset, length, message, None);
+            }
+        }
+    }
+}
+
+/// Split a dotted key on the dots outside quotes.
+fn key_parts(text: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    let mut quote = None;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('"'), '\\') => {
+                part.push(c);
+                part.extend(chars.next());
+            }
+            (Some(q), c) if c == q => {
+                quote = None;
+                part.push(c);
+            }
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                part.push(c);
+            }
+            (None, '.') => parts.push(std::mem::take(&mut part).trim().to_string()),
+            _ => part.push(c),
+        }
+    }
+    parts.push(part.trim().to_string());
+    parts
+}
+
+fn is_bare(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+}
+
+/// The key without quotes it does not need.
+fn bare_key(part: &str) -> String {
+    let quoted = part.len() >= 2 && part.starts_with(['"', '\'']) && part.ends_with(&part[..1]);
+    match part
+        .get(1..part.len().saturating_sub(1))
+        .unwrap_or_default()
+    {
+        inner if quoted && is_bare(inner) => inner.to_string(),
+        _ => part.to_string(),
+    }
+}
+
+fn unquote(part: &str) -> String {
+    // Bare keys may hold any character here, since invalid ones are only
+    // rejected by the parser afterwards; only slice inside the quotes.
+    let inner = |quote| part.strip_prefix(quote)?.strip_suffix(quote);
+    if let Some(inner) = inner('\'') {
+        return inner.to_string();
+    }
+    let Some(inner) = inner('"') else {
+        return part.to_string();
+    };
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                out.extend(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32));
+            }
+            Some('U') => {
+                let hex: String = chars.by_ref().take(8).collect();
+                out.extend(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32));
+            }
+            Some(c) => out.push(c),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Switch a single-line string between basic and literal quotes when no
+/// escaping is involved.
+fn requote(text: &str, style: QuoteStyle) -> String {
+    let inner = &text[1..text.len() - 1];
+    match style {
+        QuoteStyle::Double if text.starts_with('\'') && !inner.contains(['"', '\\']) => {
+            format!("\"{}\"", inner)
+        }
+        QuoteStyle::Single if text.starts_with('"') && !inner.contains(['\'', '\\']) => {
+            format!("'{}'", inner)
+        }
+        _ => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(input: &str) -> TomlFormatResult {
+        format_toml(input, None).unwrap()
+    }
+
+    #[test]
+    fn normalizes_spacing_quotes_and_arrays() {
+        let input = "# top\ntitle='TOML'   # name\n\"bare\"=1\n[ server . \"http\" ]\nports=[ 80,443 ]\n\
+                     hosts = [\n\"a\", # first\n  'b'\n]\ninline={x=1,y='z'}\n\n\n[[items]]\nname = \"\"\"\nraw\n\"\"\"\n";
+        assert_eq!(
+            format(input).output,
+            "# top\ntitle = \"TOML\" # name\nbare = 1\n\n[server.http]\nports = [80, 443]\n\
+             hosts = [\n  \"a\", # first\n  \"b\",\n]\ninline = { x = 1, y = \"z\" }\n\n\
+             [[items]]\nname = \"\"\"\nraw\n\"\"\"\n"
+        );
+
+        let indented = format_toml(
+            "[a]\nx = 1\n[a.b]\ny = [\n1,\n]\n",
+            Some(TomlFormatOptions {
+                indent: 4,
+                indent_tables: true,
+                quote_style: QuoteStyle::Preserve,
+            }),
+        )
+        .unwrap();
+        assert_eq!(
+            indented.output,
+            "[a]\nx = 1\n\n    [a.b]\n    y = [\n        1,\n    ]\n"
+        );
+    }
+
+    #[test]
+    fn reports_redefinitions() {
+        let result = format("a = 1\na = 2\n[t]\nx.y = 1\n[t.x]\n[t]\n");
+        let found: Vec<_> = result
+            .diagnostics
+            .iter()
+            .map(|d| (d.line, d.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (2, "Duplicate key `a`"),
+                (5, "Table [t.x] was already created by dotted keys"),
+                (6, "Table [t] is defined twice"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_table_order() {
+        let result = format("[a.b]\n[a]\n[c]\n[a.d]\n[[p]]\n[p.q]\n[[p]]\n[p.q]\n");
+        let found: Vec<_> = result
+            .diagnostics
+            .iter()
+            .map(|d| (d.line, d.severity, d.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    2,
+                    Severity::Warning,
+                    "Table [a] comes after its sub-table [a.b]"
+                ),
+                (
+                    4,
+                    Severity::Warning,
+                    "Table [a.d] is separated from [a] by [c]"
+                ),
+            ]
+        );
+
+        let err = format_toml("a = [1, 2\nb = 3\n", None).unwrap_err();
+        assert_eq!(err.diagnostic().map(|d| d.line), Some(2));
+    }
+
+    fn error(input: &str) -> (usize, usize, String) {
+        let err = format_toml(input, None).unwrap_err();
+        let d = err.diagnostic().expect("diagnostic");
+        (d.line, d.column, d.message.clone())
+    }
+
+    #[test]
+    fn non_ascii_bare_keys_are_invalid() {
+        assert_eq!(
+            error("é=1"),
+            (
+                1,
+                1,
+                "Invalid TOML: unexpected character found: `\\u{e9}`".to_string()
+            )
+        );
+        assert_eq!(error("bé=+\\").1, 2);
+        assert_eq!(error("\u{202e}=1").1, 1);
+        // Quoted, they are fine and keep their quotes.
+        assert_eq!(
+            format("'é'=1\n\"b\\u00e9\".\"c d\"=2\n").output,
+            "'é' = 1\n\"b\\u00e9\".\"c d\" = 2\n"
+        );
+        assert_eq!(error("\"é").2, "Unterminated string");
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        assert_eq!(
+            error("a\n"),
+            (1, 2, "Expected `=` after the key".to_string())
+        );
+        assert_eq!(
+            error("[a\n"),
+            (1, 3, "Expected `]` to close the header".to_string())
+        );
+        assert_eq!(error("a = "), (1, 5, "Expected a value".to_string()));
+        assert_eq!(
+            error("= 1"),
+            (1, 1, "Expected a key or a [table] header".to_string())
+        );
+        assert_eq!(
+            error("a = [1 2]"),
+            (1, 8, "Expected `,` between array items".to_string())
+        );
+        assert_eq!(
+            error("a = {x=1 y=2}"),
+            (1, 10, "Expected `,` or `}` in inline table".to_string())
+        );
+        assert_eq!(
+            error("\"\"\"k\"\"\" = 1").2,
+            "Keys cannot be multi-line strings"
+        );
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let nested = |open: &str, close: &str, depth| {
+            format!("a = {}1{}\n", open.repeat(depth), close.repeat(depth))
+        };
+        let arrays = nested("[", "]", MAX_DEPTH);
+        assert_eq!(format(&arrays).output, arrays);
+        let tables = nested("{ b = ", " }", MAX_DEPTH);
+        assert_eq!(format(&tables).output, tables);
+        assert_eq!(
+            error(&nested("[", "]", 1000)),
+            (
+                1,
+                133,
+                "Values are nested more than 128 levels deep".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn quote_styles() {
+        let single = |input: &str| {
+            format_toml(
+                input,
+                Some(TomlFormatOptions {
+                    quote_style: QuoteStyle::Single,
+                    ..Default::default()
+                }),
+            )
+            .unwrap()
+            .output
+        };
+        // Only strings that need no escaping either way switch quotes.
+        assert_eq!(
+            single("a = \"x\"\nb = \"q'\"\nc = \"\\t\"\n"),
+            "a = 'x'\nb = \"q'\"\nc = \"\\t\"\n"
+        );
+        assert_eq!(
+            format("a = 'x'\nb = 'q\"'\nc = 'C:\\dir'\n").output,
+            "a = \"x\"\nb = 'q\"'\nc = 'C:\\dir'\n"
+        );
+        // Multi-line strings are printed as written.
+        assert_eq!(
+            format("a = '''x'''\nb = \"\"\"y\"\"\"\"\"\n").output,
+            "a = '''x'''\nb = \"\"\"y\"\"\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn arrays_and_inline_tables() {
+        assert_eq!(
+            format("a = [\n1\n,2]\nb = [ [1,2] , {x=[]} ]\nc = {}\n").output,
+            "a = [\n  1,\n  2,\n]\nb = [[1, 2], { x = [] }]\nc = {}\n"
+        );
+        let result = format("a = {x=1, 'x'=2}\n");
+        assert_eq!(result.output, "a = { x = 1, x = 2 }\n");
+        assert_eq!(
+            result.diagnostics[0].message,
+            "Duplicate key `x` in inline table"
+        );
+    }
+
+    #[test]
+    fn date_times_skip_the_parser_check() {
+        assert_eq!(
+            format("a = 1979-05-27 07:32:00Z\nb=1").output,
+            "a = 1979-05-27 07:32:00Z\nb = 1\n"
+        );
+        let result = format("[t]\nd = 07:32:00\n[t]\n");
+        assert_eq!(result.diagnostics[0].message, "Table [t] is defined twice");
+    }
+}
@@ -0,0 +1,242 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! TOML tokenizer.
+//!
+//! Bare keys, numbers, booleans and date-times are all atoms; dots stay
+//! inside atoms and are split where a key is expected. A date and a time
+//! separated by a space are merged into one atom.
+
+use crate::error::{Diagnostic, UtilityError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Kind {
+    Atom,
+    String, // Any of the four string forms, quotes included
+    Punct,  // One of [ ] { } , =
+    Comment,
+    Newline,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Token {
+    pub kind: Kind,
+    pub text: String,
+    pub offset: usize, // Byte offset into the input
+}
+
+impl Token {
+    pub fn is(&self, punct: char) -> bool {
+        self.kind == Kind::Punct && self.text.starts_with(punct)
+    }
+
+    /// Whether this is a multi-line string, which is printed as written.
+    pub fn is_multiline(&self) -> bool {
+        self.kind == Kind::String
+            && (self.text.starts_with("\"\"\"") || self.text.starts_with("'''"))
+    }
+}
+
+pub(super) fn is_date(text: &str) -> bool {
+    let b = text.as_bytes();
+    b.len() == 10
+        && b.iter().enumerate().all(|(i, c)| {
+            if i == 4 || i == 7 {
+                *c == b'-'
+            } else {
+                c.is_ascii_digit()
+            }
+        })
+}
+
+pub(super) fn tokenize(input: &str) -> Result<Vec<Token>, UtilityError> {
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let kind = match bytes[i] {
+            b' ' | b'\t' | b'\r' => {
+                i += 1;
+                continue;
+            }
+            b'\n' => {
+                i += 1;
+                Kind::Newline
+            }
+            b'#' => {
+                i = input[i..].find('\n').map_or(input.len(), |n| i + n);
+                Kind::Comment
+            }
+            b'[' | b']' | b'{' | b'}' | b',' | b'=' => {
+                i += 1;
+                Kind::Punct
+            }
+            quote @ (b'"' | b'\'') => {
+                i = string_end(input, i, quote)?;
+                Kind::String
+            }
+            _ => {
+                let end = |from: usize| {
+                    input[from..]
+                        .find(|c: char| c.is_whitespace() || "[]{},=#\"'".contains(c))
+                        .map_or(input.len(), |n| from + n)
+                };
+                i = end(i);
+                // `1979-05-27 07:32:00` is one value.
+                let rest = &bytes[i..];
+                if is_date(&input[start..i])
+                    && rest.len() > 3
+                    && rest[0] == b' '
+                    && rest[1].is_ascii_digit()
+                    && rest[2].is_ascii_digit()
+                    && rest[3] == b':'
+                {
+                    i = end(i + 1);
+                }
+                Kind::Atom
+            }
+        };
+        tokens.push(Token {
+            kind,
+            text: input[start..i].trim_end_matches('\r').to_string(),
+            offset: start,
+        });
+    }
+    Ok(tokens)
+}
+
+/// Byte offset just past the string starting at `start`.
+fn string_end(input: &str, start: usize, quote: u8) -> Result<usize, UtilityError> {
+    let bytes = input.as_bytes();
+    let escapes = quote == b'"';
+    let triple = bytes[start..].starts_with(&[quote; 3]);
+    let mut i = start + if triple { 3 } else { 1 };
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if escapes => i += 2,
+            b'\n' if !triple => break,
+            c if c == quote => {
+                if !triple {
+                    return Ok(i + 1);
+                }
+                if bytes[i..].starts_with(&[quote; 3]) {
+                    // Up to two quotes may sit right before the delimiter.
+                    let mut end = i + 3;
+                    while end < bytes.len() && bytes[end] == quote && end < i + 5 {
+                        end += 1;
+                    }
+                    return Ok(end);
+                }
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    let what = if triple {
+        "multi-line string"
+    } else {
+        "string"
+    };
+    Err(
+        Diagnostic::new(input, start, format!("Unterminated {}", what))
+            .with_hint("Add the closing quote")
+            .into(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lex(input: &str) -> Vec<(Kind, String)> {
+        tokenize(input)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.kind, t.text))
+            .collect()
+    }
+
+    fn texts(input: &str) -> Vec<String> {
+        lex(input).into_iter().map(|(_, text)| text).collect()
+    }
+
+    #[test]
+    fn splits_punctuation_comments_and_newlines() {
+        use Kind::*;
+        assert_eq!(
+            lex("a.b=[1,{x=2}] # c\r\n"),
+            [
+                (Atom, "a.b".to_string()),
+                (Punct, "=".to_string()),
+                (Punct, "[".to_string()),
+                (Atom, "1".to_string()),
+                (Punct, ",".to_string()),
+                (Punct, "{".to_string()),
+                (Atom, "x".to_string()),
+                (Punct, "=".to_string()),
+                (Atom, "2".to_string()),
+                (Punct, "}".to_string()),
+                (Punct, "]".to_string()),
+                (Comment, "# c".to_string()),
+                (Newline, "\n".to_string()),
+            ]
+        );
+        let tokens = tokenize("é = 1").unwrap();
+        assert_eq!((tokens[0].text.as_str(), tokens[1].offset), ("é", 3));
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(
+            texts(r#""a\"b" 'c\' "é\é""#),
+            [r#""a\"b""#, r"'c\'", r#""é\é""#]
+        );
+        // Up to two quotes may end the content of a multi-line string.
+        assert_eq!(
+            texts("'''a\n''b''''' \"\"\"\"x\"\"\"\"\""),
+            ["'''a\n''b'''''", "\"\"\"\"x\"\"\"\"\""]
+        );
+        assert!(tokenize("a = \"\"\"x\"\"\"").unwrap()[2].is_multiline());
+        assert!(!tokenize("a = \"\"").unwrap()[2].is_multiline());
+    }
+
+    #[test]
+    fn unterminated_strings() {
+        let error = |input: &str| {
+            let err = tokenize(input).unwrap_err();
+            let d = err.diagnostic().unwrap();
+            (d.column, d.message.clone())
+        };
+        assert_eq!(
+            error("a = \"x\ny\""),
+            (5, "Unterminated string".to_string())
+        );
+        assert_eq!(error("a = 'x"), (5, "Unterminated string".to_string()));
+        assert_eq!(error("a = \"\\"), (5, "Unterminated string".to_string()));
+        assert_eq!(
+            error("a = '''x''"),
+            (5, "Unterminated multi-line string".to_string())
+        );
+    }
+
+    #[test]
+    fn date_times() {
+        assert_eq!(
+            texts("1979-05-27 07:32:00Z 1979-05-27T00:32:00-07:00 1979-05-27 x"),
+            [
+                "1979-05-27 07:32:00Z",
+                "1979-05-27T00:32:00-07:00",
+                "1979-05-27",
+                "x"
+            ]
+        );
+        assert!(is_date("2024-02-29"));
+        assert!(!is_date("2024-2-29") && !is_date("2024/02/29") && !is_date("é024-02-2"));
+    }
+}
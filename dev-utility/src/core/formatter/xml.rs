@@ -0,0 +1,684 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+mod c14n;
+mod dom;
+mod xpath;
+
+use serde::{Deserialize, Serialize};
+use universal_function_macro::universal_function;
+
+use super::IndentStyle;
+use crate::error::UtilityError;
+use c14n::Canonicalizer;
+use dom::{Dom, Kind, XML_NAMESPACE};
+use xpath::{NodeRef, Value};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct XmlFormatOptions {
+    pub strip_comments: bool, // Drop comments from the output
+    pub cdata_to_text: bool,  // Print CDATA sections as escaped text
+    pub expand_empty: bool,   // Write empty elements as <a></a> instead of <a/>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum XPathResultKind {
+    NodeSet,
+    String,
+    Number,
+    Boolean,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct XmlQueryMatch {
+    pub path: String,  // Absolute location path, e.g. /catalog[1]/book[2]/@id
+    pub value: String, // Markup for elements, the string-value otherwise
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct XmlQueryResult {
+    pub kind: XPathResultKind,
+    pub matches: Vec<XmlQueryMatch>, // In document order; empty for scalar results
+    pub output: String,              // Matched values one per line, or the scalar
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum C14nMethod {
+    C14n,                      // http://www.w3.org/TR/2001/REC-xml-c14n-20010315
+    C14nWithComments,          // ...#WithComments
+    ExclusiveC14n,             // http://www.w3.org/2001/10/xml-exc-c14n#
+    ExclusiveC14nWithComments, // ...#WithComments
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct C14nOptions {
+    pub subset: Option<String>, // XPath selecting the element to canonicalize
+    pub inclusive_prefixes: Vec<String>, // Exclusive PrefixList; #default for xmlns="..."
+}
+
+/// Check that `input` is well-formed XML and print it back indented.
+///
+/// Whitespace-only text between elements is re-indented; elements holding
+/// only text stay on one line. Mixed content and anything under
+/// `xml:space="preserve"` is printed exactly, so no significant character
+/// data changes. Prefixes and namespace declarations are kept as written,
+/// including when `Minified`.
+#[universal_function]
+pub fn format_xml(
+    input: &str,
+    style: IndentStyle,
+    options: Option<XmlFormatOptions>,
+) -> Result<String, UtilityError> {
+    let dom = dom::parse(input)?;
+    let mut printer = Printer {
+        dom: &dom,
+        unit: match style {
+            IndentStyle::Spaces(width) => " ".repeat(width),
+            IndentStyle::Tabs => "\t".to_string(),
+            IndentStyle::Minified => String::new(),
+        },
+        minify: matches!(style, IndentStyle::Minified),
+        options: options.unwrap_or_default(),
+        out: String::new(),
+    };
+    if let Some(declaration) = &dom.declaration {
+        printer.line(0, declaration);
+    }
+    if let Some(doctype) = &dom.doctype {
+        printer.line(0, doctype);
+    }
+    for &child in &dom.nodes[0].children {
+        printer.block(child, 0);
+    }
+    Ok(printer.out)
+}
+
+/// Evaluate an XPath 1.0 expression against an XML document.
+///
+/// Prefixes declared anywhere in the document can be used in name tests,
+/// bound to the first URI declared for them. As XPath 1.0 has no default
+/// namespace, elements in one are matched with `*[local-name()='name']`.
+#[universal_function]
+pub fn query_xml(input: &str, expression: &str) -> Result<XmlQueryResult, UtilityError> {
+    let dom = dom::parse(input)?;
+    let value = xpath::evaluate(&dom, expression.trim(), &document_prefixes(&dom))?;
+    Ok(match value {
+        Value::Nodes(nodes) => {
+            let matches: Vec<XmlQueryMatch> = nodes
+                .into_iter()
+                .map(|node| XmlQueryMatch {
+                    path: location_path(&dom, node),
+                    value: match node {
+                        NodeRef::Node(i) if matches!(dom.nodes[i].kind, Kind::Element { .. }) => {
+                            markup(&dom, i)
+                        }
+                        node => xpath::string_value(&dom, node),
+                    },
+                })
+                .collect();
+            let output = matches
+                .iter()
+                .map(|m| m.value.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            XmlQueryResult {
+                kind: XPathResultKind::NodeSet,
+                matches,
+                output,
+            }
+        }
+        Value::String(s) => scalar(XPathResultKind::String, s),
+        Value::Number(n) => scalar(XPathResultKind::Number, xpath::number_to_string(n)),
+        Value::Boolean(b) => scalar(XPathResultKind::Boolean, b.to_string()),
+    })
+}
+
+/// Canonicalize an XML document, or the element selected by
+/// `options.subset`, with the given C14N method.
+///
+/// Comparing this output for the signed and the received document shows
+/// why an XML signature digest does not match: the result is the exact
+/// octet stream a verifier digests.
+#[universal_function]
+pub fn canonicalize_xml(
+    input: &str,
+    method: C14nMethod,
+    options: Option<C14nOptions>,
+) -> Result<String, UtilityError> {
+    let options = options.unwrap_or_default();
+    let dom = dom::parse(input)?;
+    let apex = match options.subset.as_deref().map(str::trim) {
+        None | Some("") => 0,
+        Some(expression) => match xpath::evaluate(&dom, expression, &document_prefixes(&dom))? {
+            Value::Nodes(nodes) => match nodes.first() {
+                Some(&NodeRef::Node(i))
+                    if i == 0 || matches!(dom.nodes[i].kind, Kind::Element { .. }) =>
+                {
+                    i
+                }
+                Some(_) => {
+                    return Err(UtilityError::InvalidInput(
+                        "The subset expression must select an element".to_string(),
+                    ))
+                }
+                None => {
+                    return Err(UtilityError::InvalidInput(
+                        "The subset expression selected nothing".to_string(),
+                    ))
+                }
+            },
+            _ => {
+                return Err(UtilityError::InvalidInput(
+                    "The subset expression must select a node-set".to_string(),
+                ))
+            }
+        },
+    };
+    let exclusive = matches!(
+        method,
+        C14nMethod::ExclusiveC14n | C14nMethod::ExclusiveC14nWithComments
+    );
+    let canonicalizer = Canonicalizer {
+        dom: &dom,
+        comments: matches!(
+            method,
+            C14nMethod::C14nWithComments | C14nMethod::ExclusiveC14nWithComments
+        ),
+        exclusive,
+        inclusive_prefixes: if exclusive {
+            &options.inclusive_prefixes
+        } else {
+            &[]
+        },
+    };
+    Ok(canonicalizer.run(apex))
+}
+
+fn scalar(kind: XPathResultKind, output: String) -> XmlQueryResult {
+    XmlQueryResult {
+        kind,
+        matches: Vec::new(),
+        output,
+    }
+}
+
+/// Prefix bindings for XPath name tests, first declaration wins.
+fn document_prefixes(dom: &Dom) -> Vec<(String, String)> {
+    let mut bindings = vec![("xml".to_string(), XML_NAMESPACE.to_string())];
+    for node in &dom.nodes {
+        if let Kind::Element { attrs, .. } = &node.kind {
+            for attr in attrs.iter().filter(|a| a.name.starts_with("xmlns:")) {
+                if !bindings.iter().any(|(p, _)| p == attr.local()) {
+                    bindings.push((attr.local().to_string(), attr.value.clone()));
+                }
+            }
+        }
+    }
+    bindings
+}
+
+/// An absolute path with a position on every step, e.g.
+/// `/feed[1]/entry[3]/title[1]/text()[1]`.
+fn location_path(dom: &Dom, node: NodeRef) -> String {
+    let index = match node {
+        NodeRef::Node(0) => return "/".to_string(),
+        NodeRef::Node(i) => i,
+        NodeRef::Attr(i, j) => {
+            let (_, _, attrs) = dom.element(i).unwrap_or_default();
+            return format!(
+                "{}/@{}",
+                location_path(dom, NodeRef::Node(i)),
+                attrs[j].name
+            );
+        }
+    };
+    let mut steps = Vec::new();
+    let mut current = index;
+    while let Some(parent) = dom.nodes[current].parent {
+        let step = |i: usize| -> Option<String> {
+            Some(match &dom.nodes[i].kind {
+                Kind::Element { name, .. } => name.clone(),
+                Kind::Text(_) | Kind::CData(_) => "text()".to_string(),
+                Kind::Comment(_) => "comment()".to_string(),
+                Kind::Pi { target, .. } => format!("processing-instruction('{}')", target),
+                Kind::Document => return None,
+            })
+        };
+        let name = step(current).unwrap_or_default();
+        let position = dom.nodes[parent]
+            .children
+            .iter()
+            .take_while(|&&i| i != current)
+            .filter(|&&i| step(i).as_deref() == Some(name.as_str()))
+            .count()
+            + 1;
+        steps.push(format!("{}[{}]", name, position));
+        current = parent;
+    }
+    steps.reverse();
+    format!("/{}", steps.join("/"))
+}
+
+/// Serialize an element as it appears in the document.
+fn markup(dom: &Dom, index: usize) -> String {
+    let printer = Printer {
+        dom,
+        unit: String::new(),
+        minify: true,
+        options: XmlFormatOptions::default(),
+        out: String::new(),
+    };
+    let mut out = String::new();
+    printer.exact(index, &mut out);
+    out
+}
+
+fn escape_text(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            // Only `]]>` is forbidden in character data.
+            '>' if out.ends_with("]]") => out.push_str("&gt;"),
+            '\r' => out.push_str("&#xD;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn escape_attr(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '"' => out.push_str("&quot;"),
+            // Literal white space would be normalized away on reparse.
+            '\t' => out.push_str("&#9;"),
+            '\n' => out.push_str("&#10;"),
+            '\r' => out.push_str("&#13;"),
+            c => out.push(c),
+        }
+    }
+}
+
+struct Printer<'a> {
+    dom: &'a Dom,
+    unit: String,
+    minify: bool,
+    options: XmlFormatOptions,
+    out: String,
+}
+
+impl Printer<'_> {
+    fn line(&mut self, depth: usize, text: &str) {
+        if !self.minify {
+            self.out.push_str(&self.unit.repeat(depth));
+        }
+        self.out.push_str(text);
+        if !self.minify {
+            self.out.push('\n');
+        }
+    }
+
+    fn is_whitespace_text(&self, index: usize) -> bool {
+        matches!(&self.dom.nodes[index].kind, Kind::Text(text) if text.trim().is_empty())
+    }
+
+    /// Whether `xml:space="preserve"` applies to the element.
+    fn preserves_space(&self, index: usize) -> bool {
+        let mut current = Some(index);
+        while let Some(i) = current {
+            if let Some((_, _, attrs)) = self.dom.element(i) {
+                if let Some(attr) = attrs.iter().find(|a| a.name == "xml:space") {
+                    return attr.value == "preserve";
+                }
+            }
+            current = self.dom.nodes[i].parent;
+        }
+        false
+    }
+
+    fn block(&mut self, index: usize, depth: usize) {
+        match &self.dom.nodes[index].kind {
+            Kind::Element { .. } => {
+                let children: Vec<usize> = self.dom.nodes[index]
+                    .children
+                    .iter()
+                    .copied()
+                    .filter(|&i| {
+                        !(self.options.strip_comments
+                            && matches!(self.dom.nodes[i].kind, Kind::Comment(_)))
+                    })
+                    .collect();
+                let is_text =
+                    |i: &usize| matches!(self.dom.nodes[*i].kind, Kind::Text(_) | Kind::CData(_));
+                let text_only = children.iter().all(is_text);
+                let mixed = children
+                    .iter()
+                    .any(|&i| is_text(&i) && !self.is_whitespace_text(i));
+                if text_only || mixed || self.preserves_space(index) {
+                    let mut line = String::new();
+                    self.exact(index, &mut line);
+                    self.line(depth, &line);
+                    return;
+                }
+                let mut open = String::new();
+                self.open_tag(index, &mut open);
+                self.line(depth, &open);
+                for child in children {
+                    if !self.is_whitespace_text(child) {
+                        self.block(child, depth + 1);
+                    }
+                }
+                let (name, _, _) = self.dom.element(index).unwrap_or_default();
+                self.line(depth, &format!("</{}>", name));
+            }
+            Kind::Comment(_) if self.options.strip_comments => {}
+            _ => {
+                let mut line = String::new();
+                self.exact(index, &mut line);
+                self.line(depth, &line);
+            }
+        }
+    }
+
+    fn open_tag(&self, index: usize, out: &mut String) {
+        let (name, _, attrs) = self.dom.element(index).unwrap_or_default();
+        out.push('<');
+        out.push_str(name);
+        for attr in attrs {
+            out.push(' ');
+            out.push_str(&attr.name);
+            out.push_str("=\"");
+            escape_attr(&attr.value, out);
+            out.push('"');
+        }
+        out.push('>');
+    }
+
+    /// Serialize a node and its content without changing any text.
+    fn exact(&self, index: usize, out: &mut String) {
+        match &self.dom.nodes[index].kind {
+            Kind::Element { name, .. } => {
+                let children = &self.dom.nodes[index].children;
+                let empty = children.iter().all(|&i| {
+                    self.options.strip_comments
+                        && matches!(self.dom.nodes[i].kind, Kind::Comment(_))
+                });
+                self.open_tag(index, out);
+                if empty && !self.options.expand_empty {
+                    out.pop();
+                    out.push_str("/>");
+                    return;
+                }
+                for &child in children {
+                    self.exact(child, out);
+                }
+                out.push_str("</");
+                out.push_str(name);
+                out.push('>');
+            }
+            Kind::Text(text) => escape_text(text, out),
+            Kind::CData(text) if self.options.cdata_to_text => escape_text(text, out),
+            Kind::CData(text) => {
+                out.push_str("<![CDATA[");
+                out.push_str(text);
+                out.push_str("]]>");
+            }
+            Kind::Comment(_) if self.options.strip_comments => {}
+            Kind::Comment(text) => {
+                out.push_str("<!--");
+                out.push_str(text);
+                out.push_str("-->");
+            }
+            Kind::Pi { target, data } => {
+                out.push_str("<?");
+                out.push_str(target);
+                if !data.is_empty() {
+                    out.push(' ');
+                    out.push_str(data);
+                }
+                out.push_str("?>");
+            }
+            Kind::Document => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_and_minifies_preserving_text() {
+        let input = concat!(
+            "<?xml version=\"1.0\"?>\n<!-- top -->",
+            "<a:root xmlns:a=\"urn:a\" id='1'><item>  one  </item><item/>",
+            "<p>mixed <b>bold</b>\n text</p><!-- note --><code><![CDATA[x < y]]></code>",
+            "<pre xml:space=\"preserve\"><l>1</l>\n<l>2</l></pre></a:root>"
+        );
+        let expected = concat!(
+            "<?xml version=\"1.0\"?>\n",
+            "<!-- top -->\n",
+            "<a:root xmlns:a=\"urn:a\" id=\"1\">\n",
+            "  <item>  one  </item>\n",
+            "  <item/>\n",
+            "  <p>mixed <b>bold</b>\n text</p>\n",
+            "  <!-- note -->\n",
+            "  <code><![CDATA[x < y]]></code>\n",
+            "  <pre xml:space=\"preserve\"><l>1</l>\n<l>2</l></pre>\n",
+            "</a:root>\n"
+        );
+        assert_eq!(
+            format_xml(input, IndentStyle::Spaces(2), None).unwrap(),
+            expected
+        );
+
+        let options = XmlFormatOptions {
+            strip_comments: true,
+            cdata_to_text: true,
+            expand_empty: true,
+        };
+        let minified = format_xml(expected, IndentStyle::Minified, Some(options)).unwrap();
+        assert_eq!(
+            minified,
+            concat!(
+                "<?xml version=\"1.0\"?><a:root xmlns:a=\"urn:a\" id=\"1\">",
+                "<item>  one  </item><item></item><p>mixed <b>bold</b>\n text</p>",
+                "<code>x &lt; y</code><pre xml:space=\"preserve\"><l>1</l>\n<l>2</l></pre>",
+                "</a:root>"
+            )
+        );
+    }
+
+    #[test]
+    fn reports_well_formedness_errors() {
+        let diagnostic = |input: &str| {
+            let err = format_xml(input, IndentStyle::Spaces(2), None).unwrap_err();
+            let d = err.diagnostic().cloned().expect("diagnostic");
+            (d.line, d.column, d.message)
+        };
+        let (line, column, message) = diagnostic("<a>\n  <b>\n</a>");
+        assert_eq!((line, column), (3, 1));
+        assert!(message.contains("opened on line 2"), "{}", message);
+        let (line, column, message) = diagnostic("<a>\n<x:b/></a>");
+        assert_eq!((line, column), (2, 1));
+        assert!(message.contains("\"x\""), "{}", message);
+        let (line, column, message) = diagnostic("<a>\n<b c=\"1\" c=\"2\"/></a>");
+        assert_eq!(
+            (line, column, message.as_str()),
+            (2, 10, "Duplicate attribute")
+        );
+        let (line, column, _) = diagnostic("<a/>\n<b/>");
+        assert_eq!((line, column), (2, 1));
+        let (_, _, message) = diagnostic("<a>\n<b>");
+        assert!(message.contains("never closed"), "{}", message);
+        assert!(format_xml("<a>&nope;</a>", IndentStyle::Tabs, None).is_err());
+        // The printers recurse, so the parser bounds the depth they see.
+        let nested = |depth| "<a>".repeat(depth) + &"</a>".repeat(depth);
+        assert!(format_xml(&nested(256), IndentStyle::Spaces(2), None).is_ok());
+        assert!(canonicalize_xml(&nested(256), C14nMethod::ExclusiveC14n, None).is_ok());
+        let (_, _, message) = diagnostic(&nested(5000));
+        assert!(message.contains("nested more than"), "{}", message);
+    }
+
+    #[test]
+    fn evaluates_xpath() {
+        let input = r#"<catalog xmlns:x="urn:x">
+            <book id="b1" lang="en"><title>Rust</title><price>30</price></book>
+            <book id="b2"><title>XML</title><price>12.5</price><x:tag>t</x:tag></book>
+            <!-- end -->
+        </catalog>"#;
+        let result = query_xml(input, "//book[price < 20]/title").unwrap();
+        assert_eq!(result.kind, XPathResultKind::NodeSet);
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].path, "/catalog[1]/book[2]/title[1]");
+        assert_eq!(result.matches[0].value, "<title>XML</title>");
+
+        let result = query_xml(input, "/catalog/book/@id | //x:tag/text()").unwrap();
+        let paths: Vec<&str> = result.matches.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "/catalog[1]/book[1]/@id",
+                "/catalog[1]/book[2]/@id",
+                "/catalog[1]/book[2]/x:tag[1]/text()[1]"
+            ]
+        );
+        assert_eq!(result.output, "b1\nb2\nt");
+
+        let scalar = |expression: &str| {
+            let result = query_xml(input, expression).unwrap();
+            (result.kind, result.output)
+        };
+        assert_eq!(
+            scalar("sum(//price) div count(//book)"),
+            (XPathResultKind::Number, "21.25".to_string())
+        );
+        assert_eq!(
+            scalar("concat(name(id('b2')/*[last()]), '-', local-name(//x:*))"),
+            (XPathResultKind::String, "x:tag-tag".to_string())
+        );
+        assert_eq!(
+            scalar("count(//title[1]/following::*) = 5 and not(//book[3])"),
+            (XPathResultKind::Boolean, "true".to_string())
+        );
+        assert_eq!(
+            scalar("string(//book[last()]/preceding-sibling::*[1]/@id)"),
+            (XPathResultKind::String, "b1".to_string())
+        );
+        assert_eq!(
+            scalar("substring('12345', 1.5, 2.6) + round(-2.5) * 2"),
+            (XPathResultKind::Number, "230".to_string())
+        );
+        assert_eq!(scalar("1 div 0").1, "Infinity");
+        assert_eq!(scalar("count(//comment())").1, "1");
+
+        assert!(query_xml(input, "//book[").is_err());
+        assert!(query_xml(input, "//y:book").is_err());
+        assert!(query_xml(input, "nope()").is_err());
+    }
+
+    #[test]
+    fn canonicalizes_documents_and_subsets() {
+        // From the C14N 1.0 specification, section 3.3.
+        let input = concat!(
+            "<!DOCTYPE doc [<!ATTLIST e9 attr CDATA \"default\">]>\n",
+            "<doc>\n",
+            "   <e1   />\n",
+            "   <e2   ></e2>\n",
+            "   <e3   name = \"elem3\"   id=\"elem3\"   />\n",
+            "   <e5 a:attr=\"out\" b:attr=\"sorted\" attr2=\"all\" attr=\"I'm\"\n",
+            "      xmlns:b=\"http://www.ietf.org\"\n",
+            "      xmlns:a=\"http://www.w3.org\"\n",
+            "      xmlns=\"http://example.org\"/>\n",
+            "   <e6 xmlns=\"\" xmlns:a=\"http://www.w3.org\">\n",
+            "      <e7 xmlns=\"http://www.ietf.org\">\n",
+            "         <e8 xmlns=\"\" xmlns:a=\"http://www.w3.org\">\n",
+            "            <e9 xmlns=\"\" xmlns:a=\"http://www.ietf.org\"/>\n",
+            "         </e8>\n",
+            "      </e7>\n",
+            "   </e6>\n",
+            "</doc>\n",
+            "<!-- epilog -->"
+        );
+        let expected = concat!(
+            "<doc>\n",
+            "   <e1></e1>\n",
+            "   <e2></e2>\n",
+            "   <e3 id=\"elem3\" name=\"elem3\"></e3>\n",
+            "   <e5 xmlns=\"http://example.org\" xmlns:a=\"http://www.w3.org\" ",
+            "xmlns:b=\"http://www.ietf.org\" attr=\"I'm\" attr2=\"all\" ",
+            "b:attr=\"sorted\" a:attr=\"out\"></e5>\n",
+            "   <e6 xmlns:a=\"http://www.w3.org\">\n",
+            "      <e7 xmlns=\"http://www.ietf.org\">\n",
+            "         <e8 xmlns=\"\">\n",
+            "            <e9 xmlns:a=\"http://www.ietf.org\"></e9>\n",
+            "         </e8>\n",
+            "      </e7>\n",
+            "   </e6>\n",
+            "</doc>"
+        );
+        // The DTD's default attribute is not applied.
+        assert_eq!(
+            canonicalize_xml(input, C14nMethod::C14n, None).unwrap(),
+            expected
+        );
+        assert!(canonicalize_xml(input, C14nMethod::C14nWithComments, None)
+            .unwrap()
+            .ends_with("</doc>\n<!-- epilog -->"));
+
+        let signed = concat!(
+            "<soap:Envelope xmlns:soap=\"urn:soap\" xmlns:u=\"urn:u\" xmlns:x=\"urn:x\" xml:lang=\"en\">",
+            "<soap:Body u:Id=\"body\"><m:Get xmlns:m=\"urn:m\">&#xD;1</m:Get></soap:Body>",
+            "</soap:Envelope>"
+        );
+        let subset = |method, prefixes: &[&str]| {
+            let options = C14nOptions {
+                subset: Some("//soap:Body".to_string()),
+                inclusive_prefixes: prefixes.iter().map(|p| p.to_string()).collect(),
+            };
+            canonicalize_xml(signed, method, Some(options)).unwrap()
+        };
+        assert_eq!(
+            subset(C14nMethod::C14n, &[]),
+            concat!(
+                "<soap:Body xmlns:soap=\"urn:soap\" xmlns:u=\"urn:u\" xmlns:x=\"urn:x\" ",
+                "xml:lang=\"en\" u:Id=\"body\"><m:Get xmlns:m=\"urn:m\">&#xD;1</m:Get></soap:Body>"
+            )
+        );
+        assert_eq!(
+            subset(C14nMethod::ExclusiveC14n, &[]),
+            concat!(
+                "<soap:Body xmlns:soap=\"urn:soap\" xmlns:u=\"urn:u\" ",
+                "u:Id=\"body\"><m:Get xmlns:m=\"urn:m\">&#xD;1</m:Get></soap:Body>"
+            )
+        );
+        assert!(subset(C14nMethod::ExclusiveC14n, &["x", "m"]).starts_with(
+            "<soap:Body xmlns:soap=\"urn:soap\" xmlns:u=\"urn:u\" xmlns:x=\"urn:x\" u:Id"
+        ));
+    }
+}
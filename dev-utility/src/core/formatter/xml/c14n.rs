@@ -0,0 +1,350 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Canonical XML 1.0 and Exclusive XML Canonicalization 1.0.
+//!
+//! Serializes a document, or the subtree under one element, exactly as a
+//! signature library would before digesting it. Namespace declarations
+//! are emitted only where they change what an output ancestor declared;
+//! the exclusive form further restricts them to visibly utilized prefixes.
+
+use super::dom::{prefix_part, Dom, Kind, XML_NAMESPACE};
+
+pub(super) struct Canonicalizer<'a> {
+    pub dom: &'a Dom,
+    pub comments: bool,
+    pub exclusive: bool,
+    pub inclusive_prefixes: &'a [String], // Exclusive only; `#default` for the default namespace
+}
+
+fn escape_text(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\r' => out.push_str("&#xD;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn escape_attr(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '"' => out.push_str("&quot;"),
+            '\t' => out.push_str("&#x9;"),
+            '\n' => out.push_str("&#xA;"),
+            '\r' => out.push_str("&#xD;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn lookup<'s>(bindings: &'s [(String, String)], prefix: &str) -> &'s str {
+    bindings
+        .iter()
+        .find(|(p, _)| p == prefix)
+        .map_or("", |(_, uri)| uri.as_str())
+}
+
+impl Canonicalizer<'_> {
+    /// Canonical form of the whole document (`apex` 0) or of one element.
+    pub fn run(&self, apex: usize) -> String {
+        let mut out = String::new();
+        if apex != 0 {
+            self.element(apex, &[], true, &mut out);
+            return out;
+        }
+        let mut after_root = false;
+        for &child in &self.dom.nodes[0].children {
+            let mut node = String::new();
+            match &self.dom.nodes[child].kind {
+                Kind::Element { .. } => {
+                    self.element(child, &[], false, &mut out);
+                    after_root = true;
+                    continue;
+                }
+                Kind::Comment(_) | Kind::Pi { .. } => self.node(child, &[], &mut node),
+                _ => {}
+            }
+            if node.is_empty() {
+                continue;
+            }
+            // Prolog nodes end with a newline, epilog nodes start with one.
+            if after_root {
+                out.push('\n');
+                out.push_str(&node);
+            } else {
+                out.push_str(&node);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    fn node(&self, index: usize, rendered: &[(String, String)], out: &mut String) {
+        match &self.dom.nodes[index].kind {
+            Kind::Element { .. } => self.element(index, rendered, false, out),
+            Kind::Text(text) | Kind::CData(text) => escape_text(text, out),
+            Kind::Comment(text) if self.comments => {
+                out.push_str("<!--");
+                out.push_str(text);
+                out.push_str("-->");
+            }
+            Kind::Pi { target, data } => {
+                out.push_str("<?");
+                out.push_str(target);
+                if !data.is_empty() {
+                    out.push(' ');
+                    out.push_str(data);
+                }
+                out.push_str("?>");
+            }
+            _ => {}
+        }
+    }
+
+    /// `rendered` holds the bindings declared by output ancestors.
+    fn element(&self, index: usize, rendered: &[(String, String)], apex: bool, out: &mut String) {
+        let Some((name, _, attrs)) = self.dom.element(index) else {
+            return;
+        };
+        let scope: Vec<(String, String)> = self
+            .dom
+            .in_scope(index)
+            .into_iter()
+            .filter(|(prefix, uri)| !(prefix == "xml" && uri == XML_NAMESPACE))
+            .collect();
+
+        let candidates: Vec<String> = if self.exclusive {
+            let mut used = vec![prefix_part(name).to_string()];
+            for attr in attrs.iter().filter(|a| !a.is_namespace_declaration()) {
+                let prefix = prefix_part(&attr.name);
+                if !prefix.is_empty() && prefix != "xml" {
+                    used.push(prefix.to_string());
+                }
+            }
+            for prefix in self.inclusive_prefixes {
+                let prefix = if prefix == "#default" {
+                    ""
+                } else {
+                    prefix.as_str()
+                };
+                if scope.iter().any(|(p, _)| p == prefix) {
+                    used.push(prefix.to_string());
+                }
+            }
+            used
+        } else {
+            scope.iter().map(|(prefix, _)| prefix.clone()).collect()
+        };
+
+        let mut declarations: Vec<(String, String)> = Vec::new();
+        for prefix in candidates {
+            let uri = lookup(&scope, &prefix);
+            // Only the default namespace can be undeclared.
+            let changed = lookup(rendered, &prefix) != uri;
+            if changed
+                && (!uri.is_empty() || prefix.is_empty())
+                && !declarations.iter().any(|(p, _)| *p == prefix)
+            {
+                declarations.push((prefix, uri.to_string()));
+            }
+        }
+        declarations.sort();
+
+        let mut attributes: Vec<(&str, &str, &str, &str)> = attrs
+            .iter()
+            .filter(|a| !a.is_namespace_declaration())
+            .map(|a| (a.ns.as_str(), a.local(), a.name.as_str(), a.value.as_str()))
+            .collect();
+        // An inclusive subset apex inherits xml:* attributes in scope.
+        if apex && !self.exclusive {
+            let mut parent = self.dom.nodes[index].parent;
+            while let Some(i) = parent {
+                if let Some((_, _, inherited)) = self.dom.element(i) {
+                    for attr in inherited.iter().filter(|a| a.ns == XML_NAMESPACE) {
+                        if !attributes.iter().any(|(_, _, name, _)| *name == attr.name) {
+                            attributes.push((XML_NAMESPACE, attr.local(), &attr.name, &attr.value));
+                        }
+                    }
+                }
+                parent = self.dom.nodes[i].parent;
+            }
+        }
+        attributes.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+        out.push('<');
+        out.push_str(name);
+        for (prefix, uri) in &declarations {
+            if prefix.is_empty() {
+                out.push_str(" xmlns=\"");
+            } else {
+                out.push_str(" xmlns:");
+                out.push_str(prefix);
+                out.push_str("=\"");
+            }
+            escape_attr(uri, out);
+            out.push('"');
+        }
+        for (_, _, name, value) in attributes {
+            out.push(' ');
+            out.push_str(name);
+            out.push_str("=\"");
+            escape_attr(value, out);
+            out.push('"');
+        }
+        out.push('>');
+
+        let mut inner = rendered.to_vec();
+        for (prefix, uri) in declarations {
+            inner.retain(|(p, _)| *p != prefix);
+            inner.push((prefix, uri));
+        }
+        for &child in &self.dom.nodes[index].children {
+            self.node(child, &inner, out);
+        }
+        out.push_str("</");
+        out.push_str(name);
+        out.push('>');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::dom;
+    use super::*;
+
+    /// Canonicalize `input`, or the first element named `apex`.
+    fn canonical(input: &str, exclusive: bool, prefixes: &[&str], apex: Option<&str>) -> String {
+        let dom = dom::parse(input).unwrap();
+        let apex = apex.map_or(0, |wanted| {
+            (0..dom.nodes.len())
+                .find(|&i| dom.element(i).is_some_and(|(name, _, _)| name == wanted))
+                .unwrap()
+        });
+        let inclusive_prefixes: Vec<String> = prefixes.iter().map(|p| p.to_string()).collect();
+        Canonicalizer {
+            dom: &dom,
+            comments: false,
+            exclusive,
+            inclusive_prefixes: &inclusive_prefixes,
+        }
+        .run(apex)
+    }
+
+    #[test]
+    fn exclusive_declares_only_visibly_used_prefixes() {
+        let input = r#"<r xmlns:a="urn:a" xmlns:b="urn:b" xmlns:c="urn:c"><a:x b:attr="1"/></r>"#;
+        assert_eq!(
+            canonical(input, false, &[], None),
+            r#"<r xmlns:a="urn:a" xmlns:b="urn:b" xmlns:c="urn:c"><a:x b:attr="1"></a:x></r>"#
+        );
+        assert_eq!(
+            canonical(input, true, &[], None),
+            r#"<r><a:x xmlns:a="urn:a" xmlns:b="urn:b" b:attr="1"></a:x></r>"#
+        );
+        // The PrefixList adds prefixes that are in scope, wherever used.
+        assert_eq!(
+            canonical(input, true, &["c", "missing"], None),
+            r#"<r xmlns:c="urn:c"><a:x xmlns:a="urn:a" xmlns:b="urn:b" b:attr="1"></a:x></r>"#
+        );
+    }
+
+    #[test]
+    fn exclusive_default_namespace() {
+        let input = r#"<r xmlns="urn:d" xmlns:p="urn:p"><p:x><y/></p:x></r>"#;
+        assert_eq!(
+            canonical(input, true, &[], Some("p:x")),
+            r#"<p:x xmlns:p="urn:p"><y xmlns="urn:d"></y></p:x>"#
+        );
+        assert_eq!(
+            canonical(input, true, &["#default"], Some("p:x")),
+            r#"<p:x xmlns="urn:d" xmlns:p="urn:p"><y></y></p:x>"#
+        );
+        // An undeclared default namespace is only output where it changes
+        // what the output parent has.
+        assert_eq!(
+            canonical(
+                r#"<r xmlns="urn:d"><x xmlns=""><y/></x></r>"#,
+                true,
+                &[],
+                None
+            ),
+            r#"<r xmlns="urn:d"><x xmlns=""><y></y></x></r>"#
+        );
+        assert_eq!(
+            canonical(r#"<r><x xmlns=""/></r>"#, true, &[], None),
+            "<r><x></x></r>"
+        );
+    }
+
+    #[test]
+    fn exclusive_redeclarations() {
+        let input = r#"<r xmlns:a="urn:1"><a:x><a:y/><a:z xmlns:a="urn:2"/></a:x></r>"#;
+        assert_eq!(
+            canonical(input, true, &[], None),
+            r#"<r><a:x xmlns:a="urn:1"><a:y></a:y><a:z xmlns:a="urn:2"></a:z></a:x></r>"#
+        );
+        // Siblings do not see each other's declarations.
+        let input = r#"<r xmlns:a="urn:a"><s><a:x/></s><s><a:x/></s></r>"#;
+        assert_eq!(
+            canonical(input, true, &[], None),
+            concat!(
+                r#"<r><s><a:x xmlns:a="urn:a"></a:x></s>"#,
+                r#"<s><a:x xmlns:a="urn:a"></a:x></s></r>"#
+            )
+        );
+    }
+
+    #[test]
+    fn subset_apex_and_xml_attributes() {
+        let input = r#"<r xml:lang="en" xml:space="preserve" xmlns:a="urn:a"><x a:k="v"/></r>"#;
+        assert_eq!(
+            canonical(input, false, &[], Some("x")),
+            // Attributes sort by namespace URI, and the XML one is http://.
+            r#"<x xmlns:a="urn:a" xml:lang="en" xml:space="preserve" a:k="v"></x>"#
+        );
+        assert_eq!(
+            canonical(input, true, &[], Some("x")),
+            r#"<x xmlns:a="urn:a" a:k="v"></x>"#
+        );
+    }
+
+    #[test]
+    fn escapes_text_and_attributes() {
+        let input = "<r a=\"&quot;&#9;&#10;&lt;>\" b=\"x\n y\">1 &amp; &lt; &gt; &#13;\"'</r>";
+        assert_eq!(
+            canonical(input, false, &[], None),
+            "<r a=\"&quot;&#x9;&#xA;&lt;>\" b=\"x  y\">1 &amp; &lt; &gt; &#xD;\"'</r>"
+        );
+    }
+
+    #[test]
+    fn comments_and_processing_instructions() {
+        let input = "<?pi a?><!--pre--><r><!--in--><?x  y ?></r><!--post-->";
+        let dom = dom::parse(input).unwrap();
+        let run = |comments| {
+            Canonicalizer {
+                dom: &dom,
+                comments,
+                exclusive: false,
+                inclusive_prefixes: &[],
+            }
+            .run(0)
+        };
+        assert_eq!(run(false), "<?pi a?>\n<r><?x y ?></r>");
+        assert_eq!(
+            run(true),
+            "<?pi a?>\n<!--pre-->\n<r><!--in--><?x y ?></r>\n<!--post-->"
+        );
+    }
+}
@@ -0,0 +1,472 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Arena DOM for XML, checked for well-formedness while it is built.
+//!
+//! Nodes are stored in document order, so comparing indices compares
+//! positions. Line breaks are normalized and references resolved the way
+//! an XML processor must before handing text to the application.
+
+use quick_xml::escape::unescape;
+use quick_xml::events::attributes::AttrError;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::error::{Diagnostic, UtilityError};
+
+pub(super) const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// Deeper documents are rejected, as libxml2 does by default, since the
+/// printers walk the tree recursively.
+const MAX_DEPTH: usize = 256;
+
+pub(super) struct Attr {
+    pub name: String, // Qualified, as written
+    pub ns: String,   // Namespace URI, empty for none
+    pub value: String,
+}
+
+impl Attr {
+    pub fn is_namespace_declaration(&self) -> bool {
+        self.name == "xmlns" || self.name.starts_with("xmlns:")
+    }
+
+    pub fn local(&self) -> &str {
+        local_part(&self.name)
+    }
+}
+
+pub(super) enum Kind {
+    Document,
+    Element {
+        name: String, // Qualified, as written
+        ns: String,   // Namespace URI, empty for none
+        attrs: Vec<Attr>,
+    },
+    Text(String),
+    CData(String),
+    Comment(String),
+    Pi {
+        target: String,
+        data: String,
+    },
+}
+
+pub(super) struct Node {
+    pub kind: Kind,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+pub(super) struct Dom {
+    pub nodes: Vec<Node>, // nodes[0] is the document
+    pub declaration: Option<String>,
+    pub doctype: Option<String>,
+}
+
+pub(super) fn local_part(name: &str) -> &str {
+    name.split_once(':').map_or(name, |(_, local)| local)
+}
+
+pub(super) fn prefix_part(name: &str) -> &str {
+    name.split_once(':').map_or("", |(prefix, _)| prefix)
+}
+
+/// XML 1.0 §2.11: CRLF and lone CR become LF.
+fn normalize_newlines(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+impl Dom {
+    pub fn element(&self, index: usize) -> Option<(&str, &str, &[Attr])> {
+        match &self.nodes[index].kind {
+            Kind::Element { name, ns, attrs } => Some((name, ns, attrs)),
+            _ => None,
+        }
+    }
+
+    /// Concatenated text of all descendants, the XPath string-value.
+    pub fn text_content(&self, index: usize) -> String {
+        let mut out = String::new();
+        self.collect_text(index, &mut out);
+        out
+    }
+
+    fn collect_text(&self, index: usize, out: &mut String) {
+        match &self.nodes[index].kind {
+            Kind::Text(text) | Kind::CData(text) => out.push_str(text),
+            Kind::Document | Kind::Element { .. } => {
+                for &child in &self.nodes[index].children {
+                    self.collect_text(child, out);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Prefix to namespace URI bindings in scope at `index`, innermost
+    /// first; the default namespace has the empty prefix.
+    pub fn in_scope(&self, index: usize) -> Vec<(String, String)> {
+        let mut scope: Vec<(String, String)> = Vec::new();
+        let mut current = Some(index);
+        while let Some(i) = current {
+            if let Some((_, _, attrs)) = self.element(i) {
+                for attr in attrs.iter().filter(|a| a.is_namespace_declaration()) {
+                    let prefix = if attr.name == "xmlns" {
+                        ""
+                    } else {
+                        attr.local()
+                    };
+                    if !scope.iter().any(|(p, _)| p == prefix) {
+                        scope.push((prefix.to_string(), attr.value.clone()));
+                    }
+                }
+            }
+            current = self.nodes[i].parent;
+        }
+        scope
+    }
+
+    fn push(&mut self, kind: Kind, parent: usize) -> usize {
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            kind,
+            parent: Some(parent),
+            children: Vec::new(),
+        });
+        self.nodes[parent].children.push(index);
+        index
+    }
+}
+
+pub(super) fn parse(input: &str) -> Result<Dom, UtilityError> {
+    let mut dom = Dom {
+        nodes: vec![Node {
+            kind: Kind::Document,
+            parent: None,
+            children: Vec::new(),
+        }],
+        declaration: None,
+        doctype: None,
+    };
+    let mut reader = Reader::from_str(input);
+    // Mismatched end tags are reported below with both names.
+    reader.config_mut().check_end_names = false;
+
+    // Open elements with their start offsets and namespace bindings.
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    let mut scopes: Vec<Vec<(String, String)>> = vec![vec![
+        ("xml".to_string(), XML_NAMESPACE.to_string()),
+        (String::new(), String::new()),
+    ]];
+    let mut seen_root = false;
+
+    loop {
+        let start = reader.buffer_position() as usize;
+        let event = reader
+            .read_event()
+            .map_err(|e| Diagnostic::new(input, reader.error_position() as usize, e.to_string()))?;
+        let parent = stack.last().map_or(0, |&(index, _)| index);
+        let at = |offset: usize, message: String| -> UtilityError {
+            Diagnostic::new(input, offset, message).into()
+        };
+
+        match event {
+            Event::Decl(e) => {
+                dom.declaration = Some(format!("<?{}?>", String::from_utf8_lossy(&e)));
+            }
+            Event::DocType(e) => {
+                if seen_root {
+                    return Err(at(start, "DOCTYPE after the root element".to_string()));
+                }
+                dom.doctype = Some(format!("<!DOCTYPE {}>", String::from_utf8_lossy(&e).trim()));
+            }
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                if stack.is_empty() {
+                    if seen_root {
+                        return Err(at(start, "Content after the root element".to_string()));
+                    }
+                    seen_root = true;
+                }
+                if stack.len() == MAX_DEPTH {
+                    return Err(at(
+                        start,
+                        format!("Elements are nested more than {} levels deep", MAX_DEPTH),
+                    ));
+                }
+                let (kind, scope) = element(input, start, e, scopes.last().unwrap())?;
+                let index = dom.push(kind, parent);
+                if matches!(event, Event::Start(_)) {
+                    stack.push((index, start));
+                    scopes.push(scope);
+                }
+            }
+            Event::End(e) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                let Some((index, open_at)) = stack.pop() else {
+                    return Err(at(start, format!("Unexpected closing tag </{}>", name)));
+                };
+                scopes.pop();
+                let (open, _, _) = dom.element(index).unwrap_or_default();
+                if open != name {
+                    let line = input[..open_at].matches('\n').count() + 1;
+                    return Err(at(
+                        start,
+                        format!(
+                            "Closing tag </{}> does not match <{}> opened on line {}",
+                            name, open, line
+                        ),
+                    ));
+                }
+            }
+            Event::Text(e) => {
+                let raw = normalize_newlines(&String::from_utf8_lossy(&e));
+                if stack.is_empty() {
+                    if let Some(offset) = raw.find(|c: char| !c.is_whitespace()) {
+                        let what = if seen_root { "after" } else { "before" };
+                        return Err(at(
+                            start + offset,
+                            format!("Text {} the root element", what),
+                        ));
+                    }
+                    continue;
+                }
+                let text = unescape(&raw).map_err(|e| at(start, e.to_string()))?;
+                dom.push(Kind::Text(text.into_owned()), parent);
+            }
+            Event::CData(e) => {
+                if stack.is_empty() {
+                    return Err(at(
+                        start,
+                        "CDATA section outside the root element".to_string(),
+                    ));
+                }
+                let text = normalize_newlines(&String::from_utf8_lossy(&e));
+                dom.push(Kind::CData(text), parent);
+            }
+            Event::Comment(e) => {
+                let text = normalize_newlines(&String::from_utf8_lossy(&e));
+                if text.contains("--") {
+                    return Err(at(
+                        start,
+                        "\"--\" is not allowed inside a comment".to_string(),
+                    ));
+                }
+                dom.push(Kind::Comment(text), parent);
+            }
+            Event::PI(e) => {
+                let target = String::from_utf8_lossy(e.target()).into_owned();
+                let data = normalize_newlines(String::from_utf8_lossy(e.content()).trim_start());
+                dom.push(Kind::Pi { target, data }, parent);
+            }
+            Event::Eof => break,
+        }
+    }
+
+    if let Some(&(index, open_at)) = stack.last() {
+        let (name, _, _) = dom.element(index).unwrap_or_default();
+        return Err(Diagnostic::new(
+            input,
+            open_at,
+            format!("Element <{}> is never closed", name),
+        )
+        .into());
+    }
+    if !seen_root {
+        return Err(Diagnostic::new(input, input.len(), "No root element").into());
+    }
+    Ok(dom)
+}
+
+/// Read a start tag, resolving its namespaces against the parent scope.
+fn element(
+    input: &str,
+    start: usize,
+    tag: &BytesStart,
+    parent_scope: &[(String, String)],
+) -> Result<(Kind, Vec<(String, String)>), UtilityError> {
+    let name = String::from_utf8_lossy(tag.name().as_ref()).into_owned();
+    let mut attrs = Vec::new();
+    for attr in tag.attributes() {
+        // quick-xml reports positions relative to the tag name.
+        let attr = attr.map_err(|e| {
+            let (offset, message) = match e {
+                AttrError::ExpectedEq(p) => {
+                    (p, "Expected '=' after the attribute name".to_string())
+                }
+                AttrError::ExpectedValue(p) => (p, "Expected a quoted attribute value".to_string()),
+                AttrError::UnquotedValue(p) => (p, "Attribute values must be quoted".to_string()),
+                AttrError::ExpectedQuote(p, quote) => (
+                    p,
+                    format!("Attribute value is missing its closing {}", quote as char),
+                ),
+                AttrError::Duplicated(p, _) => (p, "Duplicate attribute".to_string()),
+            };
+            Diagnostic::new(input, start + 1 + offset, message)
+        })?;
+        // XML 1.0 §3.3.3: literal white space becomes a space; character
+        // references survive as written.
+        let raw =
+            normalize_newlines(&String::from_utf8_lossy(&attr.value)).replace(['\t', '\n'], " ");
+        let value = unescape(&raw)
+            .map_err(|e| Diagnostic::new(input, start, e.to_string()))?
+            .into_owned();
+        attrs.push(Attr {
+            name: String::from_utf8_lossy(attr.key.as_ref()).into_owned(),
+            ns: String::new(),
+            value,
+        });
+    }
+
+    let mut scope = parent_scope.to_vec();
+    for attr in attrs.iter().filter(|a| a.is_namespace_declaration()) {
+        let prefix = if attr.name == "xmlns" {
+            ""
+        } else {
+            attr.local()
+        };
+        scope.retain(|(p, _)| p != prefix);
+        scope.push((prefix.to_string(), attr.value.clone()));
+    }
+    let resolve = |prefix: &str| -> Result<String, UtilityError> {
+        scope
+            .iter()
+            .find(|(p, _)| p == prefix)
+            .map(|(_, uri)| uri.clone())
+            .ok_or_else(|| {
+                Diagnostic::new(
+                    input,
+                    start,
+                    format!("Namespace prefix \"{}\" is not declared", prefix),
+                )
+                .into()
+            })
+    };
+
+    let ns = resolve(prefix_part(&name))?;
+    for attr in attrs.iter_mut() {
+        let prefix = prefix_part(&attr.name);
+        if !prefix.is_empty() && prefix != "xmlns" {
+            attr.ns = resolve(prefix)?;
+        }
+    }
+    Ok((Kind::Element { name, ns, attrs }, scope))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(input: &str) -> (usize, usize, String) {
+        let err = parse(input).err().expect("parse error");
+        let d = err.diagnostic().expect("diagnostic");
+        (d.line, d.column, d.message.clone())
+    }
+
+    #[test]
+    fn builds_nodes_in_document_order() {
+        let dom = parse(
+            "<?xml version=\"1.0\"?><!DOCTYPE r><r><a>x<![CDATA[<y>]]></a><!--c--><?t d?></r>",
+        )
+        .unwrap();
+        assert_eq!(dom.declaration.as_deref(), Some("<?xml version=\"1.0\"?>"));
+        assert_eq!(dom.doctype.as_deref(), Some("<!DOCTYPE r>"));
+        let kinds: Vec<&str> = dom
+            .nodes
+            .iter()
+            .map(|n| match &n.kind {
+                Kind::Document => "document",
+                Kind::Element { name, .. } => name,
+                Kind::Text(text) | Kind::CData(text) | Kind::Comment(text) => text,
+                Kind::Pi { target, .. } => target,
+            })
+            .collect();
+        assert_eq!(kinds, ["document", "r", "a", "x", "<y>", "c", "t"]);
+        assert_eq!(dom.nodes[3].parent, Some(2));
+        assert_eq!(dom.nodes[1].children, [2, 5, 6]);
+        assert_eq!(dom.text_content(1), "x<y>");
+    }
+
+    #[test]
+    fn normalizes_line_breaks_and_references() {
+        let dom = parse("<r a=\"1\r\n2\t3&#10;\">x\r\ny\rz &amp; &#x41;</r>").unwrap();
+        let (_, _, attrs) = dom.element(1).unwrap();
+        assert_eq!(attrs[0].value, "1 2 3\n");
+        assert_eq!(dom.text_content(1), "x\ny\nz & A");
+    }
+
+    #[test]
+    fn resolves_namespaces() {
+        let dom = parse(concat!(
+            "<r xmlns=\"urn:d\" xmlns:p=\"urn:p\">",
+            "<p:a p:k=\"1\" k=\"2\" xml:lang=\"en\"><b xmlns=\"\" xmlns:p=\"urn:q\"/></p:a>",
+            "</r>"
+        ))
+        .unwrap();
+        let (name, ns, attrs) = dom.element(2).unwrap();
+        assert_eq!((name, ns), ("p:a", "urn:p"));
+        let attr_ns: Vec<&str> = attrs.iter().map(|a| a.ns.as_str()).collect();
+        // Unprefixed attributes are in no namespace, whatever the default.
+        assert_eq!(attr_ns, ["urn:p", "", XML_NAMESPACE]);
+        assert_eq!(dom.element(1).unwrap().1, "urn:d");
+        assert_eq!(dom.element(3).unwrap().1, "");
+        assert_eq!(
+            dom.in_scope(3),
+            [
+                (String::new(), String::new()),
+                ("p".to_string(), "urn:q".to_string()),
+            ]
+        );
+        assert_eq!(local_part("p:a"), "a");
+        assert_eq!(prefix_part("p:a"), "p");
+        assert_eq!(prefix_part("a"), "");
+    }
+
+    #[test]
+    fn rejects_misplaced_content() {
+        assert_eq!(
+            error("x<r/>"),
+            (1, 1, "Text before the root element".to_string())
+        );
+        assert_eq!(
+            error("<r/>\n  y"),
+            (2, 3, "Text after the root element".to_string())
+        );
+        assert_eq!(
+            error("<r/><!DOCTYPE r>").2,
+            "DOCTYPE after the root element"
+        );
+        assert_eq!(
+            error("<r/><![CDATA[x]]>").2,
+            "CDATA section outside the root element"
+        );
+        assert!(error("</r>").2.contains("`</r>` does not match any open tag"));
+        assert_eq!(
+            error("<!-- a -- b --><r/>").2,
+            "\"--\" is not allowed inside a comment"
+        );
+        assert_eq!(error("<!-- only -->").2, "No root element");
+        let nested = |depth| "<a>".repeat(depth) + &"</a>".repeat(depth);
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            error(&nested(5000)),
+            (1, 769, "Elements are nested more than 256 levels deep".to_string())
+        );
+    }
+
+    #[test]
+    fn reports_attribute_errors_at_the_attribute() {
+        let (line, column, message) = error("<r>\n<a k=1/></r>");
+        assert_eq!((line, column), (2, 6));
+        assert_eq!(message, "Attribute values must be quoted");
+        let (_, column, message) = error("<r p:k=\"1\"/>");
+        assert_eq!(column, 1);
+        assert_eq!(message, "Namespace prefix \"p\" is not declared");
+        assert!(parse("<r>&undefined;</r>").is_err());
+    }
+}
@@ -0,0 +1,1544 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! An XPath 1.0 interpreter over the arena DOM.
+//!
+//! Implements the full expression grammar, every axis except `namespace`,
+//! and the core function library. Name tests compare namespace URIs, with
+//! prefixes resolved against the bindings passed in by the caller.
+
+use std::cmp::Ordering;
+
+use super::dom::{local_part, Dom, Kind};
+use crate::error::UtilityError;
+
+/// Deeper expressions are rejected, since parsing and evaluation recurse.
+const MAX_DEPTH: usize = 64;
+
+/// A node in the XPath data model: a DOM node or an element's attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum NodeRef {
+    Node(usize),
+    Attr(usize, usize), // Element index, attribute index
+}
+
+impl NodeRef {
+    /// Attributes sort after their element and before its children.
+    fn order(&self) -> (usize, usize) {
+        match *self {
+            NodeRef::Node(i) => (i, 0),
+            NodeRef::Attr(i, j) => (i, j + 1),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Value {
+    Nodes(Vec<NodeRef>), // In document order, without duplicates
+    String(String),
+    Number(f64),
+    Boolean(bool),
+}
+
+pub(super) fn evaluate(
+    dom: &Dom,
+    expression: &str,
+    namespaces: &[(String, String)],
+) -> Result<Value, UtilityError> {
+    let ast = Parser::new(expression, namespaces)?.parse()?;
+    let context = Context {
+        node: NodeRef::Node(0),
+        position: 1,
+        size: 1,
+    };
+    Evaluator { dom }.eval(&ast, &context)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Dot,
+    DotDot,
+    At,
+    Comma,
+    ColonColon,
+    Slash,
+    DoubleSlash,
+    Pipe,
+    Plus,
+    Minus,
+    Cmp(Comparator),
+    Multiply,
+    And,
+    Or,
+    Mod,
+    Div,
+    Literal(String),
+    Number(f64),
+    Variable(String),
+    Name(String), // NCName, QName, `*` or `prefix:*`
+    Eof,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Arith {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Axis {
+    Ancestor,
+    AncestorOrSelf,
+    Attribute,
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Following,
+    FollowingSibling,
+    Parent,
+    Preceding,
+    PrecedingSibling,
+    Self_,
+}
+
+impl Axis {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "ancestor" => Axis::Ancestor,
+            "ancestor-or-self" => Axis::AncestorOrSelf,
+            "attribute" => Axis::Attribute,
+            "child" => Axis::Child,
+            "descendant" => Axis::Descendant,
+            "descendant-or-self" => Axis::DescendantOrSelf,
+            "following" => Axis::Following,
+            "following-sibling" => Axis::FollowingSibling,
+            "parent" => Axis::Parent,
+            "preceding" => Axis::Preceding,
+            "preceding-sibling" => Axis::PrecedingSibling,
+            "self" => Axis::Self_,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+enum NodeTest {
+    Principal,         // *
+    Namespace(String), // prefix:*
+    Name { ns: String, local: String },
+    Node,
+    Text,
+    Comment,
+    Pi(Option<String>),
+}
+
+#[derive(Debug, Clone)]
+struct Step {
+    axis: Axis,
+    test: NodeTest,
+    predicates: Vec<Ast>,
+}
+
+#[derive(Debug, Clone)]
+enum Ast {
+    Or(Box<Ast>, Box<Ast>),
+    And(Box<Ast>, Box<Ast>),
+    Compare(Comparator, Box<Ast>, Box<Ast>),
+    Arith(Arith, Box<Ast>, Box<Ast>),
+    Negate(Box<Ast>),
+    Union(Box<Ast>, Box<Ast>),
+    Literal(String),
+    Number(f64),
+    Function(String, Vec<Ast>),
+    Filter(Box<Ast>, Vec<Ast>),
+    /// A location path, optionally continuing from a filter expression.
+    Path {
+        start: Option<Box<Ast>>,
+        absolute: bool,
+        steps: Vec<Step>,
+    },
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || (!c.is_ascii() && !c.is_whitespace())
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start(c) || c.is_ascii_digit() || c == '.' || c == '-'
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, UtilityError> {
+    let chars: Vec<(usize, char)> = input.char_indices().collect();
+    let mut tokens: Vec<(usize, Token)> = Vec::new();
+    let mut i = 0;
+    let peek = |i: usize| chars.get(i).map(|&(_, c)| c);
+    let name_at = |start: usize| {
+        let mut end = start;
+        while peek(end).is_some_and(is_name_char) {
+            end += 1;
+        }
+        end
+    };
+
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        let two = |next: char| peek(i + 1) == Some(next);
+        // §3.7: after anything but an operator or one of `@ :: ( [ ,`,
+        // `*` multiplies and a name must be an operator name.
+        let operator_position = tokens.last().is_some_and(|(_, t)| {
+            !matches!(
+                t,
+                Token::At
+                    | Token::ColonColon
+                    | Token::LParen
+                    | Token::LBracket
+                    | Token::Comma
+                    | Token::Slash
+                    | Token::DoubleSlash
+                    | Token::Pipe
+                    | Token::Plus
+                    | Token::Minus
+                    | Token::Cmp(_)
+                    | Token::Multiply
+                    | Token::And
+                    | Token::Or
+                    | Token::Mod
+                    | Token::Div
+            )
+        });
+        let (token, len) = match c {
+            ' ' | '\t' | '\n' | '\r' => {
+                i += 1;
+                continue;
+            }
+            '(' => (Token::LParen, 1),
+            ')' => (Token::RParen, 1),
+            '[' => (Token::LBracket, 1),
+            ']' => (Token::RBracket, 1),
+            '@' => (Token::At, 1),
+            ',' => (Token::Comma, 1),
+            '|' => (Token::Pipe, 1),
+            '+' => (Token::Plus, 1),
+            '-' => (Token::Minus, 1),
+            '=' => (Token::Cmp(Comparator::Eq), 1),
+            '!' if two('=') => (Token::Cmp(Comparator::Ne), 2),
+            '<' if two('=') => (Token::Cmp(Comparator::Le), 2),
+            '<' => (Token::Cmp(Comparator::Lt), 1),
+            '>' if two('=') => (Token::Cmp(Comparator::Ge), 2),
+            '>' => (Token::Cmp(Comparator::Gt), 1),
+            ':' if two(':') => (Token::ColonColon, 2),
+            '/' if two('/') => (Token::DoubleSlash, 2),
+            '/' => (Token::Slash, 1),
+            '*' if operator_position => (Token::Multiply, 1),
+            '*' => (Token::Name("*".to_string()), 1),
+            '.' if two('.') => (Token::DotDot, 2),
+            '.' | '0'..='9' if c != '.' || peek(i + 1).is_some_and(|c| c.is_ascii_digit()) => {
+                let mut end = i;
+                while peek(end).is_some_and(|c| c.is_ascii_digit()) {
+                    end += 1;
+                }
+                if peek(end) == Some('.') {
+                    end += 1;
+                    while peek(end).is_some_and(|c| c.is_ascii_digit()) {
+                        end += 1;
+                    }
+                }
+                let text: String = chars[i..end].iter().map(|&(_, c)| c).collect();
+                (Token::Number(text.parse().unwrap_or(f64::NAN)), end - i)
+            }
+            '.' => (Token::Dot, 1),
+            '"' | '\'' => {
+                let Some(close) = (i + 1..chars.len()).find(|&j| chars[j].1 == c) else {
+                    return Err(syntax_error(pos, "unterminated string literal"));
+                };
+                let text = chars[i + 1..close].iter().map(|&(_, c)| c).collect();
+                (Token::Literal(text), close + 1 - i)
+            }
+            '$' => {
+                let end = name_at(i + 1);
+                let name: String = chars[i + 1..end].iter().map(|&(_, c)| c).collect();
+                (Token::Variable(name), end - i)
+            }
+            c if is_name_start(c) => {
+                let mut end = name_at(i);
+                let name: String = chars[i..end].iter().map(|&(_, c)| c).collect();
+                if operator_position {
+                    let token = match name.as_str() {
+                        "and" => Token::And,
+                        "or" => Token::Or,
+                        "mod" => Token::Mod,
+                        "div" => Token::Div,
+                        _ => {
+                            return Err(syntax_error(
+                                pos,
+                                &format!("expected an operator, found '{}'", name),
+                            ))
+                        }
+                    };
+                    (token, end - i)
+                } else {
+                    // QName or `prefix:*`, but not an axis's `::`.
+                    if peek(end) == Some(':') && peek(end + 1) != Some(':') {
+                        if peek(end + 1) == Some('*') {
+                            end += 2;
+                        } else if peek(end + 1).is_some_and(is_name_start) {
+                            end = name_at(end + 1);
+                        }
+                    }
+                    let name = chars[i..end].iter().map(|&(_, c)| c).collect();
+                    (Token::Name(name), end - i)
+                }
+            }
+            other => {
+                return Err(syntax_error(
+                    pos,
+                    &format!("unexpected character '{}'", other),
+                ))
+            }
+        };
+        tokens.push((pos, token));
+        i += len;
+    }
+    tokens.push((input.len(), Token::Eof));
+    Ok(tokens)
+}
+
+fn syntax_error(pos: usize, msg: &str) -> UtilityError {
+    UtilityError::ParseError(format!("XPath syntax error at position {}: {}", pos, msg))
+}
+
+fn eval_error(msg: &str) -> UtilityError {
+    UtilityError::InvalidInput(format!("XPath error: {}", msg))
+}
+
+struct Parser<'a> {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    depth: usize, // Parentheses, arguments and predicates currently open
+    namespaces: &'a [(String, String)],
+}
+
+impl<'a> Parser<'a> {
+    fn new(expression: &str, namespaces: &'a [(String, String)]) -> Result<Self, UtilityError> {
+        Ok(Self {
+            tokens: tokenize(expression)?,
+            pos: 0,
+            depth: 0,
+            namespaces,
+        })
+    }
+
+    fn parse(mut self) -> Result<Ast, UtilityError> {
+        let ast = self.or_expr()?;
+        match self.peek() {
+            Token::Eof => Ok(ast),
+            other => Err(self.error(&format!("unexpected {:?}", other))),
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].1
+    }
+
+    fn peek_at(&self, offset: usize) -> &Token {
+        self.tokens
+            .get(self.pos + offset)
+            .map(|(_, t)| t)
+            .unwrap_or(&Token::Eof)
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].1.clone();
+        if token != Token::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// Undo the `advance` that returned `token`, so an error points at it.
+    /// `advance` never moves past the end, so there is nothing to undo then.
+    fn back(&mut self, token: &Token) {
+        if *token != Token::Eof {
+            self.pos -= 1;
+        }
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        if self.peek() == expected {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), UtilityError> {
+        if self.eat(&expected) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {:?}, found {:?}", expected, self.peek())))
+        }
+    }
+
+    fn error(&self, msg: &str) -> UtilityError {
+        syntax_error(self.tokens[self.pos].0, msg)
+    }
+
+    /// An expression nested inside parentheses, arguments or a predicate.
+    fn nested_expr(&mut self) -> Result<Ast, UtilityError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("expression nested too deeply"));
+        }
+        self.depth += 1;
+        let ast = self.or_expr()?;
+        self.depth -= 1;
+        Ok(ast)
+    }
+
+    fn or_expr(&mut self) -> Result<Ast, UtilityError> {
+        let mut left = self.and_expr()?;
+        while self.eat(&Token::Or) {
+            left = Ast::Or(Box::new(left), Box::new(self.and_expr()?));
+        }
+        Ok(left)
+    }
+
+    fn and_expr(&mut self) -> Result<Ast, UtilityError> {
+        let mut left = self.equality_expr()?;
+        while self.eat(&Token::And) {
+            left = Ast::And(Box::new(left), Box::new(self.equality_expr()?));
+        }
+        Ok(left)
+    }
+
+    fn equality_expr(&mut self) -> Result<Ast, UtilityError> {
+        let mut left = self.relational_expr()?;
+        while let Token::Cmp(op @ (Comparator::Eq | Comparator::Ne)) = *self.peek() {
+            self.advance();
+            left = Ast::Compare(op, Box::new(left), Box::new(self.relational_expr()?));
+        }
+        Ok(left)
+    }
+
+    fn relational_expr(&mut self) -> Result<Ast, UtilityError> {
+        let mut left = self.additive_expr()?;
+        while let Token::Cmp(
+            op @ (Comparator::Lt | Comparator::Le | Comparator::Gt | Comparator::Ge),
+        ) = *self.peek()
+        {
+            self.advance();
+            left = Ast::Compare(op, Box::new(left), Box::new(self.additive_expr()?));
+        }
+        Ok(left)
+    }
+
+    fn additive_expr(&mut self) -> Result<Ast, UtilityError> {
+        let mut left = self.multiplicative_expr()?;
+        loop {
+            let op = match self.peek() {
+                Token::Plus => Arith::Add,
+                Token::Minus => Arith::Sub,
+                _ => return Ok(left),
+            };
+            self.advance();
+            left = Ast::Arith(op, Box::new(left), Box::new(self.multiplicative_expr()?));
+        }
+    }
+
+    fn multiplicative_expr(&mut self) -> Result<Ast, UtilityError> {
+        let mut left = self.unary_expr()?;
+        loop {
+            let op = match self.peek() {
+                Token::Multiply => Arith::Mul,
+                Token::Div => Arith::Div,
+                Token::Mod => Arith::Mod,
+                _ => return Ok(left),
+            };
+            self.advance();
+            left = Ast::Arith(op, Box::new(left), Box::new(self.unary_expr()?));
+        }
+    }
+
+    fn unary_expr(&mut self) -> Result<Ast, UtilityError> {
+        let mut negations = 0;
+        while self.eat(&Token::Minus) {
+            negations += 1;
+        }
+        let mut left = self.path_expr()?;
+        while self.eat(&Token::Pipe) {
+            left = Ast::Union(Box::new(left), Box::new(self.path_expr()?));
+        }
+        // Fold runs of signs so they don't nest: `--x` is number(x), and
+        // every further pair cancels out.
+        if negations > 0 {
+            if negations % 2 == 0 {
+                left = Ast::Negate(Box::new(left));
+            }
+            left = Ast::Negate(Box::new(left));
+        }
+        Ok(left)
+    }
+
+    fn path_expr(&mut self) -> Result<Ast, UtilityError> {
+        let is_primary = match self.peek() {
+            Token::Variable(_) | Token::LParen | Token::Literal(_) | Token::Number(_) => true,
+            // A function call, unless the name is a node type test.
+            Token::Name(name) => {
+                *self.peek_at(1) == Token::LParen
+                    && !matches!(
+                        name.as_str(),
+                        "node" | "text" | "comment" | "processing-instruction"
+                    )
+            }
+            _ => false,
+        };
+        if !is_primary {
+            return self.location_path();
+        }
+
+        let primary = self.primary_expr()?;
+        let predicates = self.predicates()?;
+        let filter = if predicates.is_empty() {
+            primary
+        } else {
+            Ast::Filter(Box::new(primary), predicates)
+        };
+        let mut steps = Vec::new();
+        match self.peek() {
+            Token::Slash => {
+                self.advance();
+                self.relative_path(&mut steps)?;
+            }
+            Token::DoubleSlash => {
+                self.advance();
+                steps.push(descendant_or_self());
+                self.relative_path(&mut steps)?;
+            }
+            _ => return Ok(filter),
+        }
+        Ok(Ast::Path {
+            start: Some(Box::new(filter)),
+            absolute: false,
+            steps,
+        })
+    }
+
+    fn primary_expr(&mut self) -> Result<Ast, UtilityError> {
+        match self.advance() {
+            Token::Variable(name) => Err(eval_error(&format!(
+                "variable ${} is not bound; variables are not supported",
+                name
+            ))),
+            Token::LParen => {
+                let inner = self.nested_expr()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Token::Literal(text) => Ok(Ast::Literal(text)),
+            Token::Number(n) => Ok(Ast::Number(n)),
+            Token::Name(name) => {
+                self.expect(Token::LParen)?;
+                let mut args = Vec::new();
+                if !self.eat(&Token::RParen) {
+                    loop {
+                        args.push(self.nested_expr()?);
+                        if self.eat(&Token::RParen) {
+                            break;
+                        }
+                        self.expect(Token::Comma)?;
+                    }
+                }
+                Ok(Ast::Function(name, args))
+            }
+            other => {
+                self.back(&other);
+                Err(self.error(&format!("unexpected {:?}", other)))
+            }
+        }
+    }
+
+    fn predicates(&mut self) -> Result<Vec<Ast>, UtilityError> {
+        let mut predicates = Vec::new();
+        while self.eat(&Token::LBracket) {
+            predicates.push(self.nested_expr()?);
+            self.expect(Token::RBracket)?;
+        }
+        Ok(predicates)
+    }
+
+    fn location_path(&mut self) -> Result<Ast, UtilityError> {
+        let mut steps = Vec::new();
+        let absolute = match self.peek() {
+            Token::Slash => {
+                self.advance();
+                // A lone `/` selects the root node.
+                if self.starts_step() {
+                    self.relative_path(&mut steps)?;
+                }
+                true
+            }
+            Token::DoubleSlash => {
+                self.advance();
+                steps.push(descendant_or_self());
+                self.relative_path(&mut steps)?;
+                true
+            }
+            _ => {
+                self.relative_path(&mut steps)?;
+                false
+            }
+        };
+        Ok(Ast::Path {
+            start: None,
+            absolute,
+            steps,
+        })
+    }
+
+    fn starts_step(&self) -> bool {
+        matches!(
+            self.peek(),
+            Token::Name(_) | Token::At | Token::Dot | Token::DotDot
+        )
+    }
+
+    fn relative_path(&mut self, steps: &mut Vec<Step>) -> Result<(), UtilityError> {
+        steps.push(self.step()?);
+        loop {
+            match self.peek() {
+                Token::Slash => {
+                    self.advance();
+                }
+                Token::DoubleSlash => {
+                    self.advance();
+                    steps.push(descendant_or_self());
+                }
+                _ => return Ok(()),
+            }
+            steps.push(self.step()?);
+        }
+    }
+
+    fn step(&mut self) -> Result<Step, UtilityError> {
+        let test = |axis| Step {
+            axis,
+            test: NodeTest::Node,
+            predicates: Vec::new(),
+        };
+        if self.eat(&Token::Dot) {
+            return Ok(test(Axis::Self_));
+        }
+        if self.eat(&Token::DotDot) {
+            return Ok(test(Axis::Parent));
+        }
+
+        let axis = if self.eat(&Token::At) {
+            Axis::Attribute
+        } else if let (Token::Name(name), Token::ColonColon) = (self.peek(), self.peek_at(1)) {
+            let axis = match name.as_str() {
+                "namespace" => return Err(self.error("the namespace axis is not supported")),
+                name => Axis::from_name(name)
+                    .ok_or_else(|| self.error(&format!("unknown axis '{}'", name)))?,
+            };
+            self.pos += 2;
+            axis
+        } else {
+            Axis::Child
+        };
+
+        let test = match self.advance() {
+            Token::Name(name) if *self.peek() == Token::LParen => {
+                self.advance();
+                let test = match name.as_str() {
+                    "node" => NodeTest::Node,
+                    "text" => NodeTest::Text,
+                    "comment" => NodeTest::Comment,
+                    "processing-instruction" => match self.peek().clone() {
+                        Token::Literal(target) => {
+                            self.advance();
+                            NodeTest::Pi(Some(target))
+                        }
+                        _ => NodeTest::Pi(None),
+                    },
+                    _ => return Err(self.error(&format!("'{}' is not a node type", name))),
+                };
+                self.expect(Token::RParen)?;
+                test
+            }
+            Token::Name(name) => self.name_test(&name)?,
+            other => {
+                self.back(&other);
+                return Err(self.error(&format!("expected a node test, found {:?}", other)));
+            }
+        };
+
+        Ok(Step {
+            axis,
+            test,
+            predicates: self.predicates()?,
+        })
+    }
+
+    fn name_test(&self, name: &str) -> Result<NodeTest, UtilityError> {
+        if name == "*" {
+            return Ok(NodeTest::Principal);
+        }
+        let Some((prefix, local)) = name.split_once(':') else {
+            return Ok(NodeTest::Name {
+                ns: String::new(),
+                local: name.to_string(),
+            });
+        };
+        let ns = self
+            .namespaces
+            .iter()
+            .find(|(p, _)| p == prefix)
+            .map(|(_, uri)| uri.clone())
+            .ok_or_else(|| self.error(&format!("namespace prefix '{}' is not bound", prefix)))?;
+        Ok(if local == "*" {
+            NodeTest::Namespace(ns)
+        } else {
+            NodeTest::Name {
+                ns,
+                local: local.to_string(),
+            }
+        })
+    }
+}
+
+/// The expansion of `//`.
+fn descendant_or_self() -> Step {
+    Step {
+        axis: Axis::DescendantOrSelf,
+        test: NodeTest::Node,
+        predicates: Vec::new(),
+    }
+}
+
+struct Context {
+    node: NodeRef,
+    position: usize,
+    size: usize,
+}
+
+/// XPath 1.0 `number()` applied to a string.
+pub(super) fn string_to_number(text: &str) -> f64 {
+    let text = text.trim_matches([' ', '\t', '\n', '\r']);
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let valid = !(whole.is_empty() && fraction.is_empty())
+        && whole.chars().all(|c| c.is_ascii_digit())
+        && fraction.chars().all(|c| c.is_ascii_digit());
+    if valid {
+        text.parse().unwrap_or(f64::NAN)
+    } else {
+        f64::NAN
+    }
+}
+
+/// XPath 1.0 `string()` applied to a number.
+pub(super) fn number_to_string(n: f64) -> String {
+    if n.is_nan() {
+        "NaN".to_string()
+    } else if n.is_infinite() {
+        if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else if n == 0.0 {
+        "0".to_string()
+    } else {
+        format!("{}", n)
+    }
+}
+
+struct Evaluator<'a> {
+    dom: &'a Dom,
+}
+
+impl Evaluator<'_> {
+    fn eval(&self, ast: &Ast, context: &Context) -> Result<Value, UtilityError> {
+        Ok(match ast {
+            Ast::Or(a, b) => Value::Boolean(
+                self.boolean(self.eval(a, context)?) || self.boolean(self.eval(b, context)?),
+            ),
+            Ast::And(a, b) => Value::Boolean(
+                self.boolean(self.eval(a, context)?) && self.boolean(self.eval(b, context)?),
+            ),
+            Ast::Compare(op, a, b) => {
+                Value::Boolean(self.compare(*op, self.eval(a, context)?, self.eval(b, context)?))
+            }
+            Ast::Arith(op, a, b) => {
+                let a = self.number(self.eval(a, context)?);
+                let b = self.number(self.eval(b, context)?);
+                Value::Number(match op {
+                    Arith::Add => a + b,
+                    Arith::Sub => a - b,
+                    Arith::Mul => a * b,
+                    Arith::Div => a / b,
+                    Arith::Mod => a % b,
+                })
+            }
+            Ast::Negate(a) => Value::Number(-self.number(self.eval(a, context)?)),
+            Ast::Union(a, b) => {
+                let (Value::Nodes(mut a), Value::Nodes(b)) =
+                    (self.eval(a, context)?, self.eval(b, context)?)
+                else {
+                    return Err(eval_error("the operands of '|' must be node-sets"));
+                };
+                a.extend(b);
+                Value::Nodes(sort_unique(a))
+            }
+            Ast::Literal(text) => Value::String(text.clone()),
+            Ast::Number(n) => Value::Number(*n),
+            Ast::Function(name, args) => self.function(name, args, context)?,
+            Ast::Filter(primary, predicates) => {
+                let Value::Nodes(nodes) = self.eval(primary, context)? else {
+                    return Err(eval_error("predicates can only filter node-sets"));
+                };
+                Value::Nodes(self.filter(nodes, predicates)?)
+            }
+            Ast::Path {
+                start,
+                absolute,
+                steps,
+            } => {
+                let mut nodes = match start {
+                    Some(start) => match self.eval(start, context)? {
+                        Value::Nodes(nodes) => nodes,
+                        _ => return Err(eval_error("'/' can only follow a node-set")),
+                    },
+                    None if *absolute => vec![NodeRef::Node(0)],
+                    None => vec![context.node],
+                };
+                for step in steps {
+                    let mut next = Vec::new();
+                    for &node in &nodes {
+                        let candidates: Vec<NodeRef> = self
+                            .axis(step.axis, node)
+                            .into_iter()
+                            .filter(|&n| self.matches(&step.test, step.axis, n))
+                            .collect();
+                        next.extend(self.filter(candidates, &step.predicates)?);
+                    }
+                    nodes = sort_unique(next);
+                }
+                Value::Nodes(nodes)
+            }
+        })
+    }
+
+    /// Keep the nodes, in the given (axis) order, for which every predicate
+    /// holds; a number predicate compares against the position.
+    fn filter(
+        &self,
+        mut nodes: Vec<NodeRef>,
+        predicates: &[Ast],
+    ) -> Result<Vec<NodeRef>, UtilityError> {
+        for predicate in predicates {
+            let size = nodes.len();
+            let mut kept = Vec::new();
+            for (i, &node) in nodes.iter().enumerate() {
+                let context = Context {
+                    node,
+                    position: i + 1,
+                    size,
+                };
+                let keep = match self.eval(predicate, &context)? {
+                    Value::Number(n) => n == (i + 1) as f64,
+                    other => self.boolean(other),
+                };
+                if keep {
+                    kept.push(node);
+                }
+            }
+            nodes = kept;
+        }
+        Ok(nodes)
+    }
+
+    fn subtree_end(&self, index: usize) -> usize {
+        match self.dom.nodes[index].children.last() {
+            Some(&last) => self.subtree_end(last),
+            None => index,
+        }
+    }
+
+    fn ancestors(&self, node: NodeRef) -> Vec<NodeRef> {
+        let mut out = Vec::new();
+        let mut current = match node {
+            NodeRef::Node(i) => self.dom.nodes[i].parent,
+            NodeRef::Attr(i, _) => Some(i),
+        };
+        while let Some(i) = current {
+            out.push(NodeRef::Node(i));
+            current = self.dom.nodes[i].parent;
+        }
+        out
+    }
+
+    /// Nodes along `axis`, nearest first for reverse axes.
+    fn axis(&self, axis: Axis, node: NodeRef) -> Vec<NodeRef> {
+        let nodes = &self.dom.nodes;
+        let index = match node {
+            NodeRef::Node(i) | NodeRef::Attr(i, _) => i,
+        };
+        let is_attr = matches!(node, NodeRef::Attr(..));
+        match axis {
+            Axis::Self_ => vec![node],
+            Axis::Child if !is_attr => nodes[index]
+                .children
+                .iter()
+                .map(|&i| NodeRef::Node(i))
+                .collect(),
+            Axis::Descendant if !is_attr => (index + 1..=self.subtree_end(index))
+                .map(NodeRef::Node)
+                .collect(),
+            Axis::DescendantOrSelf if !is_attr => (index..=self.subtree_end(index))
+                .map(NodeRef::Node)
+                .collect(),
+            Axis::Child | Axis::Descendant => Vec::new(),
+            Axis::DescendantOrSelf => vec![node],
+            Axis::Parent => self.ancestors(node).into_iter().take(1).collect(),
+            Axis::Ancestor => self.ancestors(node),
+            Axis::AncestorOrSelf => {
+                let mut out = vec![node];
+                out.extend(self.ancestors(node));
+                out
+            }
+            Axis::Attribute => match self.dom.element(index) {
+                Some((_, _, attrs)) if !is_attr => attrs
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, a)| !a.is_namespace_declaration())
+                    .map(|(j, _)| NodeRef::Attr(index, j))
+                    .collect(),
+                _ => Vec::new(),
+            },
+            Axis::FollowingSibling | Axis::PrecedingSibling => {
+                let Some(parent) = nodes[index].parent.filter(|_| !is_attr) else {
+                    return Vec::new();
+                };
+                let siblings = &nodes[parent].children;
+                let at = siblings.iter().position(|&i| i == index).unwrap_or(0);
+                if axis == Axis::FollowingSibling {
+                    siblings[at + 1..]
+                        .iter()
+                        .map(|&i| NodeRef::Node(i))
+                        .collect()
+                } else {
+                    siblings[..at]
+                        .iter()
+                        .rev()
+                        .map(|&i| NodeRef::Node(i))
+                        .collect()
+                }
+            }
+            Axis::Following => {
+                // An attribute's following nodes include its element's content.
+                let from = if is_attr {
+                    index + 1
+                } else {
+                    self.subtree_end(index) + 1
+                };
+                (from..nodes.len()).map(NodeRef::Node).collect()
+            }
+            Axis::Preceding => {
+                let ancestors = self.ancestors(NodeRef::Node(index));
+                (1..index)
+                    .rev()
+                    .map(NodeRef::Node)
+                    .filter(|n| !ancestors.contains(n))
+                    .collect()
+            }
+        }
+    }
+
+    fn matches(&self, test: &NodeTest, axis: Axis, node: NodeRef) -> bool {
+        let principal_is_attr = axis == Axis::Attribute;
+        match node {
+            NodeRef::Attr(i, j) => {
+                let (_, _, attrs) = self.dom.element(i).unwrap_or_default();
+                let attr = &attrs[j];
+                match test {
+                    NodeTest::Node => true,
+                    NodeTest::Principal => principal_is_attr,
+                    NodeTest::Namespace(ns) => principal_is_attr && attr.ns == *ns,
+                    NodeTest::Name { ns, local } => {
+                        principal_is_attr && attr.ns == *ns && attr.local() == local
+                    }
+                    _ => false,
+                }
+            }
+            NodeRef::Node(i) => match (&self.dom.nodes[i].kind, test) {
+                (_, NodeTest::Node) => true,
+                (Kind::Element { .. }, NodeTest::Principal) => !principal_is_attr,
+                (Kind::Element { ns: uri, .. }, NodeTest::Namespace(ns)) => {
+                    !principal_is_attr && uri == ns
+                }
+                (Kind::Element { name, ns: uri, .. }, NodeTest::Name { ns, local }) => {
+                    !principal_is_attr && uri == ns && local_part(name) == local
+                }
+                (Kind::Text(_) | Kind::CData(_), NodeTest::Text) => true,
+                (Kind::Comment(_), NodeTest::Comment) => true,
+                (Kind::Pi { target, .. }, NodeTest::Pi(name)) => {
+                    name.as_ref().is_none_or(|name| name == target)
+                }
+                _ => false,
+            },
+        }
+    }
+
+    fn string_value(&self, node: NodeRef) -> String {
+        string_value(self.dom, node)
+    }
+
+    fn string(&self, value: Value) -> String {
+        match value {
+            Value::Nodes(nodes) => nodes
+                .first()
+                .map(|&n| self.string_value(n))
+                .unwrap_or_default(),
+            Value::String(s) => s,
+            Value::Number(n) => number_to_string(n),
+            Value::Boolean(b) => b.to_string(),
+        }
+    }
+
+    fn number(&self, value: Value) -> f64 {
+        match value {
+            Value::Number(n) => n,
+            Value::Boolean(b) => f64::from(u8::from(b)),
+            other => string_to_number(&self.string(other)),
+        }
+    }
+
+    fn boolean(&self, value: Value) -> bool {
+        match value {
+            Value::Nodes(nodes) => !nodes.is_empty(),
+            Value::String(s) => !s.is_empty(),
+            Value::Number(n) => n != 0.0 && !n.is_nan(),
+            Value::Boolean(b) => b,
+        }
+    }
+
+    /// §3.4: node-sets compare by any member's string-value, converted to
+    /// the other side's type; a node-set against a boolean is a boolean.
+    fn compare(&self, op: Comparator, a: Value, b: Value) -> bool {
+        let (a, b) = match (a, b) {
+            (nodes @ Value::Nodes(_), Value::Boolean(b)) => {
+                (Value::Boolean(self.boolean(nodes)), Value::Boolean(b))
+            }
+            (Value::Boolean(a), nodes @ Value::Nodes(_)) => {
+                (Value::Boolean(a), Value::Boolean(self.boolean(nodes)))
+            }
+            pair => pair,
+        };
+        let atoms = |value: Value| match value {
+            Value::Nodes(nodes) => nodes
+                .into_iter()
+                .map(|n| Value::String(self.string_value(n)))
+                .collect(),
+            other => vec![other],
+        };
+        let (a, b) = (atoms(a), atoms(b));
+        a.iter().any(|x| {
+            b.iter()
+                .any(|y| self.compare_atoms(op, x.clone(), y.clone()))
+        })
+    }
+
+    fn compare_atoms(&self, op: Comparator, a: Value, b: Value) -> bool {
+        match op {
+            Comparator::Eq | Comparator::Ne => {
+                let equal = match (&a, &b) {
+                    (Value::Boolean(_), _) | (_, Value::Boolean(_)) => {
+                        self.boolean(a) == self.boolean(b)
+                    }
+                    (Value::Number(_), _) | (_, Value::Number(_)) => {
+                        self.number(a) == self.number(b)
+                    }
+                    _ => self.string(a) == self.string(b),
+                };
+                equal == (op == Comparator::Eq)
+            }
+            _ => {
+                let ordering = self.number(a).partial_cmp(&self.number(b));
+                match op {
+                    Comparator::Lt => ordering == Some(Ordering::Less),
+                    Comparator::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                    Comparator::Gt => ordering == Some(Ordering::Greater),
+                    _ => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                }
+            }
+        }
+    }
+
+    fn function(&self, name: &str, args: &[Ast], context: &Context) -> Result<Value, UtilityError> {
+        let arity = |min: usize, max: usize| -> Result<(), UtilityError> {
+            if args.len() < min || args.len() > max {
+                Err(eval_error(&format!(
+                    "{}() takes {} argument{}, got {}",
+                    name,
+                    if min == max {
+                        min.to_string()
+                    } else {
+                        format!("{} to {}", min, max)
+                    },
+                    if max == 1 { "" } else { "s" },
+                    args.len()
+                )))
+            } else {
+                Ok(())
+            }
+        };
+        let arg = |i: usize| self.eval(&args[i], context);
+        let string_arg = |i: usize| -> Result<String, UtilityError> {
+            Ok(match args.get(i) {
+                Some(ast) => self.string(self.eval(ast, context)?),
+                None => self.string_value(context.node),
+            })
+        };
+        let number_arg = |i: usize| -> Result<f64, UtilityError> { Ok(self.number(arg(i)?)) };
+        let nodes_arg = |i: usize| -> Result<Vec<NodeRef>, UtilityError> {
+            match args.get(i) {
+                Some(ast) => match self.eval(ast, context)? {
+                    Value::Nodes(nodes) => Ok(nodes),
+                    _ => Err(eval_error(&format!("{}() expects a node-set", name))),
+                },
+                None => Ok(vec![context.node]),
+            }
+        };
+
+        Ok(match name {
+            "last" => {
+                arity(0, 0)?;
+                Value::Number(context.size as f64)
+            }
+            "position" => {
+                arity(0, 0)?;
+                Value::Number(context.position as f64)
+            }
+            "count" => {
+                arity(1, 1)?;
+                Value::Number(nodes_arg(0)?.len() as f64)
+            }
+            "id" => {
+                arity(1, 1)?;
+                let ids: Vec<String> = match arg(0)? {
+                    Value::Nodes(nodes) => {
+                        nodes.into_iter().map(|n| self.string_value(n)).collect()
+                    }
+                    other => vec![self.string(other)],
+                };
+                let wanted: Vec<&str> = ids.iter().flat_map(|s| s.split_whitespace()).collect();
+                Value::Nodes(
+                    (0..self.dom.nodes.len())
+                        .filter(|&i| {
+                            self.dom.element(i).is_some_and(|(_, _, attrs)| {
+                                attrs.iter().any(|a| {
+                                    matches!(a.name.as_str(), "id" | "ID" | "Id" | "xml:id")
+                                        && wanted.contains(&a.value.as_str())
+                                })
+                            })
+                        })
+                        .map(NodeRef::Node)
+                        .collect(),
+                )
+            }
+            "local-name" | "namespace-uri" | "name" => {
+                arity(0, 1)?;
+                let node = nodes_arg(0)?.first().copied();
+                let (qname, ns) = match node.map(|n| self.name_of(n)) {
+                    Some(Some((qname, ns))) => (qname, ns),
+                    _ => (String::new(), String::new()),
+                };
+                Value::String(match name {
+                    "local-name" => local_part(&qname).to_string(),
+                    "namespace-uri" => ns,
+                    _ => qname,
+                })
+            }
+            "string" => {
+                arity(0, 1)?;
+                Value::String(string_arg(0)?)
+            }
+            "concat" => {
+                if args.len() < 2 {
+                    return Err(eval_error("concat() takes at least 2 arguments"));
+                }
+                let mut out = String::new();
+                for i in 0..args.len() {
+                    out.push_str(&string_arg(i)?);
+                }
+                Value::String(out)
+            }
+            "starts-with" | "contains" | "substring-before" | "substring-after" => {
+                arity(2, 2)?;
+                let (s, t) = (string_arg(0)?, string_arg(1)?);
+                match name {
+                    "starts-with" => Value::Boolean(s.starts_with(&t)),
+                    "contains" => Value::Boolean(s.contains(&t)),
+                    "substring-before" => Value::String(
+                        s.split_once(&t)
+                            .map(|(a, _)| a.to_string())
+                            .unwrap_or_default(),
+                    ),
+                    _ => Value::String(
+                        s.split_once(&t)
+                            .map(|(_, b)| b.to_string())
+                            .unwrap_or_default(),
+                    ),
+                }
+            }
+            "substring" => {
+                arity(2, 3)?;
+                let s = string_arg(0)?;
+                let start = round(number_arg(1)?);
+                let end = match args.len() {
+                    3 => start + round(number_arg(2)?),
+                    _ => f64::INFINITY,
+                };
+                // Characters at 1-based position p with start <= p < end.
+                Value::String(
+                    s.chars()
+                        .enumerate()
+                        .filter(|&(i, _)| {
+                            let p = (i + 1) as f64;
+                            p >= start && p < end
+                        })
+                        .map(|(_, c)| c)
+                        .collect(),
+                )
+            }
+            "string-length" => {
+                arity(0, 1)?;
+                Value::Number(string_arg(0)?.chars().count() as f64)
+            }
+            "normalize-space" => {
+                arity(0, 1)?;
+                Value::String(
+                    string_arg(0)?
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )
+            }
+            "translate" => {
+                arity(3, 3)?;
+                let (s, from, to) = (string_arg(0)?, string_arg(1)?, string_arg(2)?);
+                let from: Vec<char> = from.chars().collect();
+                let to: Vec<char> = to.chars().collect();
+                Value::String(
+                    s.chars()
+                        .filter_map(|c| match from.iter().position(|&f| f == c) {
+                            Some(i) => to.get(i).copied(),
+                            None => Some(c),
+                        })
+                        .collect(),
+                )
+            }
+            "boolean" => {
+                arity(1, 1)?;
+                Value::Boolean(self.boolean(arg(0)?))
+            }
+            "not" => {
+                arity(1, 1)?;
+                Value::Boolean(!self.boolean(arg(0)?))
+            }
+            "true" | "false" => {
+                arity(0, 0)?;
+                Value::Boolean(name == "true")
+            }
+            "lang" => {
+                arity(1, 1)?;
+                let wanted = string_arg(0)?.to_lowercase();
+                // The nearest xml:lang on the node or an ancestor wins.
+                let lang = std::iter::once(context.node)
+                    .chain(self.ancestors(context.node))
+                    .filter_map(|n| match n {
+                        NodeRef::Node(i) => self.dom.element(i),
+                        NodeRef::Attr(..) => None,
+                    })
+                    .find_map(|(_, _, attrs)| attrs.iter().find(|a| a.name == "xml:lang"))
+                    .map(|a| a.value.to_lowercase());
+                Value::Boolean(lang.is_some_and(|lang| {
+                    lang == wanted
+                        || lang
+                            .strip_prefix(&wanted)
+                            .is_some_and(|rest| rest.starts_with('-'))
+                }))
+            }
+            "number" => {
+                arity(0, 1)?;
+                Value::Number(match args.len() {
+                    0 => string_to_number(&self.string_value(context.node)),
+                    _ => number_arg(0)?,
+                })
+            }
+            "sum" => {
+                arity(1, 1)?;
+                Value::Number(
+                    nodes_arg(0)?
+                        .into_iter()
+                        .map(|n| string_to_number(&self.string_value(n)))
+                        .sum(),
+                )
+            }
+            "floor" => {
+                arity(1, 1)?;
+                Value::Number(number_arg(0)?.floor())
+            }
+            "ceiling" => {
+                arity(1, 1)?;
+                Value::Number(number_arg(0)?.ceil())
+            }
+            "round" => {
+                arity(1, 1)?;
+                Value::Number(round(number_arg(0)?))
+            }
+            _ => return Err(eval_error(&format!("unknown function {}()", name))),
+        })
+    }
+
+    /// Qualified name and namespace URI of an element, attribute or
+    /// processing instruction.
+    fn name_of(&self, node: NodeRef) -> Option<(String, String)> {
+        match node {
+            NodeRef::Attr(i, j) => {
+                let (_, _, attrs) = self.dom.element(i)?;
+                Some((attrs[j].name.clone(), attrs[j].ns.clone()))
+            }
+            NodeRef::Node(i) => match &self.dom.nodes[i].kind {
+                Kind::Element { name, ns, .. } => Some((name.clone(), ns.clone())),
+                Kind::Pi { target, .. } => Some((target.clone(), String::new())),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// XPath rounds half-way cases towards positive infinity.
+fn round(n: f64) -> f64 {
+    if n.is_finite() {
+        (n + 0.5).floor()
+    } else {
+        n
+    }
+}
+
+pub(super) fn string_value(dom: &Dom, node: NodeRef) -> String {
+    match node {
+        NodeRef::Attr(i, j) => dom
+            .element(i)
+            .map(|(_, _, attrs)| attrs[j].value.clone())
+            .unwrap_or_default(),
+        NodeRef::Node(i) => match &dom.nodes[i].kind {
+            Kind::Comment(text) => text.clone(),
+            Kind::Pi { data, .. } => data.clone(),
+            _ => dom.text_content(i),
+        },
+    }
+}
+
+fn sort_unique(mut nodes: Vec<NodeRef>) -> Vec<NodeRef> {
+    nodes.sort_by_key(NodeRef::order);
+    nodes.dedup();
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::dom;
+    use super::*;
+
+    const DOC: &str = concat!(
+        "<r xmlns:x=\"urn:x\" xml:lang=\"en-GB\">",
+        "<a id=\"1\"><b>one</b><b>two</b></a>",
+        "<a id=\"2\"><c/><b>three</b></a>",
+        "<x:d>  ns  text </x:d><!--note--><?pi data?>",
+        "</r>"
+    );
+
+    fn eval(expression: &str) -> Result<Value, UtilityError> {
+        let dom = dom::parse(DOC).unwrap();
+        evaluate(&dom, expression, &[("x".to_string(), "urn:x".to_string())])
+    }
+
+    /// String-values of the selected nodes, in the order returned.
+    fn strings(expression: &str) -> Vec<String> {
+        let dom = dom::parse(DOC).unwrap();
+        match eval(expression).unwrap() {
+            Value::Nodes(nodes) => nodes.iter().map(|n| string_value(&dom, *n)).collect(),
+            other => panic!("{} is not a node-set: {:?}", expression, other),
+        }
+    }
+
+    fn scalar(expression: &str) -> String {
+        match eval(expression).unwrap() {
+            Value::String(s) => s,
+            Value::Number(n) => number_to_string(n),
+            Value::Boolean(b) => b.to_string(),
+            Value::Nodes(_) => panic!("{} is a node-set", expression),
+        }
+    }
+
+    #[test]
+    fn forward_axes() {
+        assert_eq!(strings("//b"), ["one", "two", "three"]);
+        assert_eq!(strings("/r/child::a/child::b"), ["one", "two", "three"]);
+        assert_eq!(strings("/r/descendant::b[2]"), ["two"]);
+        assert_eq!(scalar("count(/r/a/descendant-or-self::a)"), "2");
+        assert_eq!(scalar("count(//node())"), "14");
+        assert_eq!(strings("//c/following-sibling::*"), ["three"]);
+        assert_eq!(strings("//c/following::*"), ["three", "  ns  text "]);
+        assert_eq!(strings("//a/attribute::id"), ["1", "2"]);
+        assert_eq!(strings("//a/@*"), ["1", "2"]);
+        assert_eq!(strings("//b[1]/self::b"), ["one", "three"]);
+        assert_eq!(strings("//b/self::c"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn reverse_axes() {
+        assert_eq!(strings("//c/parent::a/@id"), ["2"]);
+        assert_eq!(strings("//c/../@id"), ["2"]);
+        assert_eq!(scalar("name(//b[. = 'three']/ancestor::*[1])"), "a");
+        assert_eq!(scalar("count(//b[. = 'three']/ancestor::*)"), "2");
+        assert_eq!(scalar("count(//b[. = 'three']/ancestor-or-self::*)"), "3");
+        // Reverse axes count proximity from the context node, but the
+        // resulting node-set is still in document order.
+        assert_eq!(strings("//c/preceding::b"), ["one", "two"]);
+        assert_eq!(strings("//c/preceding::b[1]"), ["two"]);
+        assert_eq!(strings("/r/a[1]/b[2]/preceding-sibling::b"), ["one"]);
+        assert_eq!(strings("//b/@id/.."), Vec::<String>::new());
+        assert_eq!(strings("//a/@id/parent::a/b[1]"), ["one", "three"]);
+    }
+
+    #[test]
+    fn root_and_node_type_tests() {
+        assert!(matches!(eval("/").unwrap(), Value::Nodes(n) if n == [NodeRef::Node(0)]));
+        assert_eq!(strings("//comment()"), ["note"]);
+        assert_eq!(strings("//processing-instruction('pi')"), ["data"]);
+        assert_eq!(
+            strings("//processing-instruction('other')"),
+            Vec::<String>::new()
+        );
+        assert_eq!(scalar("count(/r/a/b/text())"), "3");
+        assert_eq!(strings("//x:d"), ["  ns  text "]);
+        assert_eq!(strings("//x:*"), ["  ns  text "]);
+        assert!(eval("//y:d").is_err());
+    }
+
+    #[test]
+    fn predicates() {
+        assert_eq!(strings("//a[@id = '2']/b"), ["three"]);
+        assert_eq!(strings("//b[last()]"), ["two", "three"]);
+        assert_eq!(strings("//b[position() > 1]"), ["two"]);
+        assert_eq!(strings("(//b)[last()]"), ["three"]);
+        assert_eq!(strings("(//b)[2]"), ["two"]);
+        assert_eq!(strings("//a[b][c]/@id"), ["2"]);
+        assert_eq!(strings("//a[2]/@id"), ["2"]);
+        assert_eq!(strings("//b[0.5 + 0.5]"), ["one", "three"]);
+        assert_eq!(strings("//a[b = 'two' or c]/@id"), ["1", "2"]);
+        assert_eq!(strings("//b[contains(., 't')][1]"), ["two", "three"]);
+        assert_eq!(strings("//b | //b[2] | //c/../b"), ["one", "two", "three"]);
+    }
+
+    #[test]
+    fn string_functions() {
+        assert_eq!(scalar("string(//b)"), "one");
+        assert_eq!(scalar("normalize-space(//x:d)"), "ns text");
+        assert_eq!(scalar("string-length(normalize-space(//x:d))"), "7");
+        assert_eq!(scalar("translate('a-b-c', 'abc-', 'ABC')"), "ABC");
+        assert_eq!(scalar("substring-before('2024-01-02', '-')"), "2024");
+        assert_eq!(scalar("substring-after('2024-01-02', '-')"), "01-02");
+        assert_eq!(scalar("substring('12345', 0, 3)"), "12");
+        assert_eq!(scalar("substring('12345', 2)"), "2345");
+        assert_eq!(scalar("starts-with(//b[2], 'tw')"), "true");
+        assert_eq!(
+            scalar("concat(name(//x:d), '|', local-name(//x:d), '|', namespace-uri(//x:d))"),
+            "x:d|d|urn:x"
+        );
+        assert_eq!(scalar("string(number('x'))"), "NaN");
+        assert_eq!(scalar("string(-0)"), "0");
+        assert_eq!(scalar("string(1 div 0 < 0)"), "false");
+    }
+
+    #[test]
+    fn numeric_and_boolean_functions() {
+        assert_eq!(scalar("sum(//a/@id)"), "3");
+        assert_eq!(scalar("floor(-1.5) + ceiling(1.2)"), "0");
+        assert_eq!(scalar("round(2.5) + round(-0.5)"), "3");
+        assert_eq!(scalar("7 mod 3 * -1"), "-1");
+        assert_eq!(scalar("boolean(//nothing)"), "false");
+        assert_eq!(scalar("not(//b) or true()"), "true");
+        assert_eq!(scalar("//a/@id = 2"), "true");
+        assert_eq!(scalar("//a/@id != 1"), "true");
+        assert_eq!(scalar("lang('en')"), "false");
+        assert_eq!(scalar("boolean(//b[lang('EN')])"), "true");
+        // Without a DTD, attributes named `id` are taken as IDs.
+        assert_eq!(scalar("count(id('1 2 3'))"), "2");
+    }
+
+    #[test]
+    fn syntax_errors() {
+        for expression in [
+            "",
+            "   ",
+            "/r/",
+            "//",
+            "/r/a[",
+            "/r/a[1",
+            "a |",
+            "bogus::b",
+            "namespace::*",
+            "@",
+            "//node(",
+            "count(",
+            "'open",
+            "1 +",
+            "$var",
+        ] {
+            assert!(eval(expression).is_err(), "{:?}", expression);
+        }
+        let err = eval("/r/a/").unwrap_err();
+        assert!(err.to_string().contains("position 5"), "{}", err);
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let nested = |depth| "(".repeat(depth) + "1" + &")".repeat(depth);
+        assert_eq!(scalar(&nested(MAX_DEPTH)), "1");
+        for expression in [
+            nested(200),
+            "count(".repeat(200),
+            "//a[".repeat(MAX_DEPTH + 1) + "1" + &"]".repeat(MAX_DEPTH + 1),
+        ] {
+            let err = eval(&expression).unwrap_err();
+            assert!(err.to_string().contains("nested too deeply"), "{}", err);
+        }
+        // Runs of signs are folded rather than nested.
+        assert_eq!(scalar(&("-".repeat(10_001) + "1")), "-1");
+        assert_eq!(scalar(&("-".repeat(10_000) + "'2'")), "2");
+        assert_eq!(scalar("string(--'x')"), "NaN");
+    }
+}
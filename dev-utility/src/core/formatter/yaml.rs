@@ -0,0 +1,768 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+mod scalar;
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use universal_function_macro::universal_function;
+
+use crate::error::{strip_location, Diagnostic, Severity, UtilityError};
+use scalar::Scan;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum QuoteStyle {
+    #[default]
+    Double,
+    Single,   // Literal strings in TOML
+    Preserve, // Leave every string as written
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct YamlFormatOptions {
+    pub indent: usize,           // Spaces per nesting level
+    pub quote_style: QuoteStyle, // Applied where switching quotes needs no escapes
+    pub expand_aliases: bool,    // Inline aliases and merge keys; drops comments
+}
+
+impl Default for YamlFormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            quote_style: QuoteStyle::Double,
+            expand_aliases: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct YamlFormatResult {
+    pub output: String,
+    pub diagnostics: Vec<Diagnostic>, // Duplicate keys, aliases and implicit typing pitfalls
+    pub documents: usize,             // Documents in the stream
+}
+
+/// Re-indent YAML, keeping comments, key order and block scalars.
+///
+/// Sequences are indented under their parent key, `- ` and `: ` get a
+/// single space, and strings switch to the preferred quotes when no
+/// escaping is needed. Every document in a `---` stream is checked for
+/// duplicate keys, unknown aliases and plain scalars YAML 1.1 parsers
+/// read differently from YAML 1.2 ones, such as `no`, `0755` and `1.10`.
+/// Syntax errors fail the call; everything else is reported in
+/// `diagnostics`.
+#[universal_function]
+pub fn format_yaml(
+    input: &str,
+    options: Option<YamlFormatOptions>,
+) -> Result<YamlFormatResult, UtilityError> {
+    let options = options.unwrap_or_default();
+    if options.indent == 0 {
+        return Err(UtilityError::InvalidInput(
+            "Indent must be at least one space".to_string(),
+        ));
+    }
+
+    let mut printer = Printer::new(input, &options);
+    printer.run();
+    let Printer {
+        mut diagnostics,
+        documents,
+        ..
+    } = printer;
+
+    // The printer only follows indentation; the parser has the final say.
+    if let Err(error) = validate(input) {
+        if !diagnostics.iter().any(|d| d.severity == Severity::Error) {
+            return Err(error.into());
+        }
+    }
+
+    let source = if options.expand_aliases && input.contains('*') {
+        expand(input)?
+    } else {
+        input.to_string()
+    };
+    let mut printer = Printer::new(&source, &options);
+    printer.run();
+    if !parses_same(&source, &printer.out) {
+        return Err(UtilityError::Runtime(
+            "Formatting would change the meaning of this document".to_string(),
+        ));
+    }
+    diagnostics.sort_by_key(|d| d.offset);
+    Ok(YamlFormatResult {
+        output: printer.out,
+        diagnostics,
+        documents,
+    })
+}
+
+fn parse_error(input: &str, e: serde_yml::Error) -> Diagnostic {
+    let message = format!("Invalid YAML: {}", strip_location(&e.to_string()));
+    match e.location() {
+        Some(location) => Diagnostic::new(input, location.index(), message),
+        None => Diagnostic::new(input, 0, message),
+    }
+}
+
+fn validate(input: &str) -> Result<(), Diagnostic> {
+    for document in serde_yml::Deserializer::from_str(input) {
+        serde_yml::Value::deserialize(document).map_err(|e| parse_error(input, e))?;
+    }
+    Ok(())
+}
+
+/// Whether both texts hold the same documents; anything either side
+/// fails to parse counts as a match, since it was reported already.
+fn parses_same(input: &str, output: &str) -> bool {
+    let documents = |text| -> Result<Vec<serde_yml::Value>, serde_yml::Error> {
+        serde_yml::Deserializer::from_str(text)
+            .map(serde_yml::Value::deserialize)
+            .collect()
+    };
+    match (documents(input), documents(output)) {
+        (Ok(before), Ok(after)) => before == after,
+        (Ok(_), Err(_)) => false,
+        (Err(_), _) => true,
+    }
+}
+
+/// Re-serialize every document with aliases and merge keys resolved.
+fn expand(input: &str) -> Result<String, Diagnostic> {
+    let mut documents = Vec::new();
+    for document in serde_yml::Deserializer::from_str(input) {
+        let mut value =
+            serde_yml::Value::deserialize(document).map_err(|e| parse_error(input, e))?;
+        value.apply_merge().map_err(|e| parse_error(input, e))?;
+        documents.push(serde_yml::to_string(&value).map_err(|e| parse_error(input, e))?);
+    }
+    Ok(match documents.len() {
+        1 => documents.remove(0),
+        _ => documents
+            .iter()
+            .map(|document| format!("---\n{}", document))
+            .collect(),
+    })
+}
+
+/// A nesting level: nodes at `src` columns in the input print at `out`.
+struct Level {
+    src: usize,
+    out: usize,
+    seq_only: bool, // A sequence written level with its parent key
+    keys: HashMap<String, usize>,
+}
+
+impl Level {
+    fn new(src: usize, out: usize) -> Self {
+        Self {
+            src,
+            out,
+            seq_only: false,
+            keys: HashMap::new(),
+        }
+    }
+}
+
+enum Pending {
+    Blank,
+    Comment(String),
+}
+
+/// Content lines of a `|` or `>` scalar.
+struct Block {
+    parent: usize,         // Content is indented more than this column
+    out: usize,            // Where its first column prints
+    column: Option<usize>, // Content column, from an indicator or the first line
+}
+
+/// The node whose value continues on the next lines.
+struct Opener {
+    src: usize,
+    is_key: bool,
+}
+
+struct Printer<'a> {
+    input: &'a str,
+    indent: usize,
+    quote_style: QuoteStyle,
+    out: String,
+    levels: Vec<Level>,
+    fresh: bool, // No content yet in this document
+    pending: Vec<Pending>,
+    block: Option<Block>,
+    flow: Option<(Scan, isize)>, // Open flow collection or quote, and the column shift
+    opener: Option<Opener>,
+    anchors: HashMap<String, (usize, bool)>,
+    diagnostics: Vec<Diagnostic>,
+    documents: usize,
+}
+
+impl<'a> Printer<'a> {
+    fn new(input: &'a str, options: &YamlFormatOptions) -> Self {
+        Self {
+            input,
+            indent: options.indent,
+            quote_style: options.quote_style,
+            out: String::with_capacity(input.len()),
+            levels: vec![Level::new(0, 0)],
+            fresh: true,
+            pending: Vec::new(),
+            block: None,
+            flow: None,
+            opener: None,
+            anchors: HashMap::new(),
+            diagnostics: Vec::new(),
+            documents: 0,
+        }
+    }
+
+    fn run(&mut self) {
+        let mut offset = 0;
+        let input = self.input;
+        for raw in input.split_inclusive('\n') {
+            let line = raw.trim_end_matches(['\n', '\r']);
+            self.line(line, offset);
+            offset += raw.len();
+        }
+        self.end_document();
+        self.flush(0);
+        if self.out.ends_with("\n\n") {
+            self.out.truncate(self.out.trim_end_matches('\n').len() + 1);
+        }
+    }
+
+    fn push_line(&mut self, indent: usize, text: &str) {
+        self.out.extend(std::iter::repeat_n(' ', indent));
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn flush(&mut self, indent: usize) {
+        for pending in std::mem::take(&mut self.pending) {
+            match pending {
+                Pending::Blank => {
+                    if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+                        self.out.push('\n');
+                    }
+                }
+                Pending::Comment(text) => self.push_line(indent, &text),
+            }
+        }
+    }
+
+    fn end_document(&mut self) {
+        for (name, (offset, used)) in std::mem::take(&mut self.anchors) {
+            if !used {
+                self.diagnostics.push(
+                    Diagnostic::new(
+                        self.input,
+                        offset,
+                        format!("Anchor &{} is never used", name),
+                    )
+                    .with_length(name.len() + 1)
+                    .with_severity(Severity::Info),
+                );
+            }
+        }
+        if !self.fresh {
+            self.documents += 1;
+        }
+        self.levels = vec![Level::new(0, 0)];
+        self.fresh = true;
+        self.opener = None;
+    }
+
+    fn line(&mut self, line: &str, offset: usize) {
+        let trimmed = line.trim_start_matches([' ', '\t']);
+        let src = line.len() - trimmed.len();
+
+        if let Some(block) = &mut self.block {
+            if trimmed.is_empty() {
+                self.out.push('\n');
+                return;
+            }
+            let column = *block.column.get_or_insert(src);
+            if src > block.parent && src >= column {
+                let indent = block.out + (src - column);
+                self.push_line(indent, trimmed.trim_end_matches([' ', '\t']));
+                return;
+            }
+            self.block = None;
+        }
+
+        let trimmed = trimmed.trim_end();
+        if let Some((mut scan, shift)) = self.flow.take() {
+            let indent = (src as isize + shift).max(0) as usize;
+            self.push_line(indent, trimmed);
+            scan.feed(trimmed);
+            if scan.is_open() {
+                self.flow = Some((scan, shift));
+            }
+            return;
+        }
+
+        if trimmed.is_empty() {
+            if !matches!(self.pending.last(), Some(Pending::Blank)) {
+                self.pending.push(Pending::Blank);
+            }
+            return;
+        }
+        if trimmed.starts_with('#') {
+            self.pending.push(Pending::Comment(trimmed.to_string()));
+            return;
+        }
+        if src == 0 && (is_marker(trimmed, "---") || is_marker(trimmed, "...")) {
+            self.end_document();
+            self.flush(0);
+            self.push_line(0, trimmed);
+            // Content may follow `---` on the same line, as in `--- |`.
+            let rest = trimmed.strip_prefix("---").map(str::trim_start);
+            if let Some(rest) = rest.filter(|rest| !rest.is_empty() && !rest.starts_with('#')) {
+                self.fresh = false;
+                if rest.starts_with(['|', '>']) {
+                    self.block_header(rest, offset + (trimmed.len() - rest.len()), 0, 0);
+                }
+            }
+            return;
+        }
+        if src == 0 && trimmed.starts_with('%') {
+            self.flush(0);
+            self.push_line(0, trimmed);
+            return;
+        }
+        if line[..src].contains('\t') {
+            self.diagnostics.push(
+                Diagnostic::new(
+                    self.input,
+                    offset + line[..src].find('\t').unwrap_or(0),
+                    "Tab in indentation",
+                )
+                .with_hint("YAML only allows spaces for indentation"),
+            );
+        }
+
+        self.content(trimmed, src, offset);
+    }
+
+    fn content(&mut self, text: &str, src: usize, offset: usize) {
+        let dash = is_indicator(text);
+        if self.fresh {
+            self.levels = vec![Level::new(src, 0)];
+            self.fresh = false;
+        }
+        while self.levels.len() > 1 {
+            let top = self.levels.last().expect("root level");
+            if top.src > src || (top.src == src && top.seq_only && !dash) {
+                self.levels.pop();
+            } else {
+                break;
+            }
+        }
+        let top = self.levels.last().expect("root level");
+        let opener = self.opener.take();
+        let out = if top.src == src {
+            match &opener {
+                Some(Opener {
+                    src: key,
+                    is_key: true,
+                }) if dash && *key == src => {
+                    let out = top.out + self.indent;
+                    self.levels.push(Level {
+                        seq_only: true,
+                        ..Level::new(src, out)
+                    });
+                    out
+                }
+                _ => top.out,
+            }
+        } else if opener.is_some() {
+            let out = top.out + self.indent;
+            self.levels.push(Level::new(src, out));
+            out
+        } else {
+            // A plain scalar running over several lines.
+            let out = top.out + self.indent;
+            self.flush(out);
+            self.push_line(out, text);
+            return;
+        };
+        self.flush(out);
+
+        let mut printed = String::new();
+        let mut rest = text;
+        let mut column = src;
+        let (mut node_src, mut node_out) = (src, out);
+        while is_indicator(rest) {
+            printed.push_str(&rest[..1]);
+            let after = rest[1..].trim_start();
+            (node_src, node_out) = (column, out + printed.len() - 1);
+            column += rest.len() - after.len();
+            rest = after;
+            if rest.is_empty() {
+                break;
+            }
+            printed.push(' ');
+            self.levels.push(Level::new(column, out + printed.len()));
+        }
+        if rest.is_empty() {
+            self.push_line(out, &printed);
+            self.opener = Some(Opener {
+                src: node_src,
+                is_key: false,
+            });
+            return;
+        }
+        let (key_src, key_out) = (column, out + printed.len());
+
+        let (key, value) = match split_key(rest) {
+            Some((key, value)) => {
+                let name = scalar::unquote(key);
+                let key_offset = offset + column;
+                self.check_key(&name, key, key_offset);
+                self.lint_plain(key, key_offset);
+                printed.push_str(&self.requote(key));
+                printed.push(':');
+                (Some(key), value.trim_start())
+            }
+            None => (None, rest),
+        };
+        let value_offset = offset + column + (rest.len() - value.len());
+        if key.is_some() {
+            (node_src, node_out) = (key_src, key_out);
+        }
+
+        let value = self.value(value, value_offset, node_src, node_out);
+        if key.is_some() && !value.is_empty() {
+            printed.push(' ');
+        }
+        printed.push_str(&value);
+        self.push_line(out, &printed);
+        if self.block.is_none() && self.flow.is_none() && value_opens(&value) {
+            self.opener = Some(Opener {
+                src: node_src,
+                is_key: key.is_some(),
+            });
+        }
+        if let Some((_, shift)) = &mut self.flow {
+            *shift = out as isize - src as isize;
+        }
+    }
+
+    /// Print a node's value, after any key or `- `, and start tracking the
+    /// lines it continues on.
+    fn value(&mut self, text: &str, offset: usize, node_src: usize, node_out: usize) -> String {
+        let mut printed = String::new();
+        let mut rest = text;
+        // Anchors and tags come before the node itself.
+        while rest.starts_with(['&', '!']) {
+            let end = rest.find([' ', '\t']).unwrap_or(rest.len());
+            let token = &rest[..end];
+            if let Some(name) = token.strip_prefix('&') {
+                let at = offset + (text.len() - rest.len());
+                self.anchors.insert(name.to_string(), (at, false));
+            }
+            printed.push_str(token);
+            rest = rest[end..].trim_start();
+            if !rest.is_empty() {
+                printed.push(' ');
+            }
+        }
+        let rest_offset = offset + (text.len() - rest.len());
+
+        let mut scan = Scan::default();
+        let comment = scan.feed(rest);
+        let (node, comment) = match comment {
+            Some(at) => (rest[..at].trim_end(), &rest[at..]),
+            None => (rest, ""),
+        };
+
+        if node.starts_with(['|', '>']) {
+            self.block_header(node, rest_offset, node_src, node_out);
+            printed.push_str(node);
+        } else if let Some(name) = node.strip_prefix('*') {
+            self.alias(name, rest_offset);
+            printed.push_str(node);
+        } else if node.starts_with(['[', '{']) {
+            self.flow_aliases(node, rest_offset);
+            printed.push_str(node);
+            if scan.is_open() {
+                self.flow = Some((scan, 0));
+            }
+        } else if scan.is_open() {
+            printed.push_str(node);
+            self.flow = Some((scan, 0));
+        } else {
+            self.lint_plain(node, rest_offset);
+            printed.push_str(&self.requote(node));
+        }
+        if !comment.is_empty() {
+            if !printed.is_empty() {
+                printed.push(' ');
+            }
+            printed.push_str(comment);
+        }
+        printed
+    }
+
+    fn block_header(&mut self, header: &str, offset: usize, node_src: usize, node_out: usize) {
+        let digit = header[1..]
+            .chars()
+            .find_map(|c| c.to_digit(10))
+            .map(|d| d as usize);
+        if digit == Some(0) {
+            self.diagnostics.push(Diagnostic::new(
+                self.input,
+                offset,
+                "Block scalar indentation indicator must be 1 to 9",
+            ));
+        }
+        self.block = Some(match digit {
+            Some(d) => Block {
+                parent: node_src,
+                out: node_out + d,
+                column: Some(node_src + d),
+            },
+            None => Block {
+                parent: node_src,
+                out: node_out + self.indent,
+                column: None,
+            },
+        });
+    }
+
+    fn check_key(&mut self, name: &str, key: &str, offset: usize) {
+        let level = self.levels.last_mut().expect("root level");
+        if let Some(&first) = level.keys.get(name) {
+            let first_line = Diagnostic::new(self.input, first, "").line;
+            self.diagnostics.push(
+                Diagnostic::new(self.input, offset, format!("Duplicate key `{}`", name))
+                    .with_length(key.len())
+                    .with_hint(format!("The first `{}` is on line {}", name, first_line)),
+            );
+        } else {
+            level.keys.insert(name.to_string(), offset);
+        }
+        if name == "<<" {
+            self.diagnostics.push(
+                Diagnostic::new(self.input, offset, "Merge key copies entries from an alias")
+                    .with_length(2)
+                    .with_severity(Severity::Info)
+                    .with_hint("Enable alias expansion to see the merged mapping"),
+            );
+        }
+    }
+
+    fn alias(&mut self, name: &str, offset: usize) {
+        match self.anchors.get_mut(name) {
+            Some((anchor, used)) => {
+                *used = true;
+                let line = Diagnostic::new(self.input, *anchor, "").line;
+                self.diagnostics.push(
+                    Diagnostic::new(
+                        self.input,
+                        offset,
+                        format!("Alias *{} repeats the node anchored on line {}", name, line),
+                    )
+                    .with_length(name.len() + 1)
+                    .with_severity(Severity::Info),
+                );
+            }
+            None => self.diagnostics.push(
+                Diagnostic::new(self.input, offset, format!("Unknown alias *{}", name))
+                    .with_length(name.len() + 1)
+                    .with_hint("Anchors must be defined with & before they are used"),
+            ),
+        }
+    }
+
+    fn flow_aliases(&mut self, flow: &str, offset: usize) {
+        let mut start = 0;
+        for (i, c) in flow.char_indices().chain([(flow.len(), ' ')]) {
+            if !(c.is_whitespace() || "[]{},".contains(c)) {
+                continue;
+            }
+            let token = &flow[start..i];
+            if let Some(name) = token.strip_prefix('*') {
+                self.alias(name, offset + start);
+            } else if let Some(name) = token.strip_prefix('&') {
+                self.anchors
+                    .insert(name.to_string(), (offset + start, false));
+            }
+            start = i + c.len_utf8();
+        }
+    }
+
+    fn lint_plain(&mut self, text: &str, offset: usize) {
+        if let Some((message, hint)) = scalar::implicit_type(text) {
+            self.diagnostics.push(
+                Diagnostic::new(self.input, offset, message)
+                    .with_length(text.len())
+                    .with_severity(Severity::Warning)
+                    .with_hint(hint),
+            );
+        }
+    }
+
+    fn requote(&self, text: &str) -> String {
+        scalar::requote(text, self.quote_style).into_owned()
+    }
+}
+
+fn is_marker(line: &str, marker: &str) -> bool {
+    line.strip_prefix(marker)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+}
+
+/// A `- `, `? ` or `: ` indicator, which shifts its node right.
+fn is_indicator(text: &str) -> bool {
+    let mut chars = text.chars();
+    matches!(chars.next(), Some('-' | '?' | ':'))
+        && chars.next().is_none_or(|c| c == ' ' || c == '\t')
+}
+
+fn value_opens(value: &str) -> bool {
+    value.is_empty()
+        || value.starts_with('#')
+        || value
+            .split_whitespace()
+            .take_while(|token| !token.starts_with('#'))
+            .all(|token| token.starts_with(['&', '!']))
+}
+
+/// Split `key: value` on the mapping indicator.
+fn split_key(text: &str) -> Option<(&str, &str)> {
+    let key_end = if text.starts_with(['"', '\'']) {
+        let mut scan = Scan::default();
+        scan.feed(text);
+        let end = scan.quote_end?;
+        let after = text[end..].trim_start();
+        if !after.starts_with(':') {
+            return None;
+        }
+        text.len() - after.len()
+    } else {
+        if text.starts_with(['[', '{', '#', '&', '*', '!', '|', '>']) {
+            return None;
+        }
+        let bytes = text.as_bytes();
+        let mut found = None;
+        for (i, &b) in bytes.iter().enumerate() {
+            if b == b'#' && i > 0 && bytes[i - 1].is_ascii_whitespace() {
+                return None;
+            }
+            if b == b':' && bytes.get(i + 1).is_none_or(|c| c.is_ascii_whitespace()) {
+                found = Some(i);
+                break;
+            }
+        }
+        found?
+    };
+    let key = text[..key_end].trim_end();
+    (!key.is_empty()).then(|| (key, &text[key_end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(input: &str) -> YamlFormatResult {
+        format_yaml(input, None).unwrap()
+    }
+
+    #[test]
+    fn normalizes_indentation_and_keeps_comments() {
+        let input = "# config\nserver:\n    host:   'localhost'   # dev\n    ports:\n    - 80\n    -    443\n\n\n\
+                     items:\n-   name: a\n    tags: [x, \"y\"]\n- name: 'it''s'\n\
+                     script: |\n        echo hi\n          indented\n\nafter: 1\n";
+        assert_eq!(
+            format(input).output,
+            "# config\nserver:\n  host: \"localhost\" # dev\n  ports:\n    - 80\n    - 443\n\n\
+             items:\n  - name: a\n    tags: [x, \"y\"]\n  - name: \"it's\"\n\
+             script: |\n  echo hi\n    indented\n\nafter: 1\n"
+        );
+
+        let wide = format_yaml(
+            "a:\n  b:\n    - c: 1\n      d: 2\n",
+            Some(YamlFormatOptions {
+                indent: 4,
+                quote_style: QuoteStyle::Preserve,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert_eq!(wide.output, "a:\n    b:\n        - c: 1\n          d: 2\n");
+    }
+
+    #[test]
+    fn reports_duplicates_and_implicit_types() {
+        let result = format("country: NO\nversion: 1.10\nmode: 0755\nname: a\nname: b\nlist:\n  - name: first\n  - name: second\n");
+        let messages: Vec<_> = result
+            .diagnostics
+            .iter()
+            .map(|d| (d.line, d.severity))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                (1, Severity::Warning),
+                (2, Severity::Warning),
+                (3, Severity::Warning),
+                (5, Severity::Error),
+            ]
+        );
+        assert!(result.diagnostics[3]
+            .message
+            .contains("Duplicate key `name`"));
+    }
+
+    #[test]
+    fn handles_anchors_and_streams() {
+        let input = "base: &base\n  a: 1\nother:\n  <<: *base\n  b: 2\n---\nlist: [*missing]\n";
+        let result = format_yaml(input, None).unwrap();
+        assert_eq!(result.documents, 2);
+        assert_eq!(result.output, input);
+        let lines: Vec<_> = result
+            .diagnostics
+            .iter()
+            .map(|d| (d.line, d.severity))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (4, Severity::Info),
+                (4, Severity::Info),
+                (7, Severity::Error)
+            ]
+        );
+
+        let expanded = format_yaml(
+            "base: &base\n  a: 1\nother:\n  <<: *base\n  b: 2\n",
+            Some(YamlFormatOptions {
+                expand_aliases: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert_eq!(expanded.output, "base:\n  a: 1\nother:\n  b: 2\n  a: 1\n");
+
+        let err = format_yaml("a: [1, 2\nb: 3\n", None).unwrap_err();
+        assert!(err.diagnostic().is_some());
+    }
+}
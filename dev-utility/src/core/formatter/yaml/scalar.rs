@@ -0,0 +1,203 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Scalar-level helpers: quote tracking, requoting and YAML 1.1 pitfalls.
+
+use std::borrow::Cow;
+
+use super::QuoteStyle;
+
+/// Quote and bracket state carried across the lines of a flow collection
+/// or quoted scalar.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct Scan {
+    depth: usize,
+    quote: Option<char>,
+    pub quote_end: Option<usize>, // After the closing quote of a leading quoted scalar
+}
+
+impl Scan {
+    pub fn is_open(&self) -> bool {
+        self.depth > 0 || self.quote.is_some()
+    }
+
+    /// Advance over `text` and return where a trailing comment starts.
+    ///
+    /// Quotes and brackets only count at the start of a value or inside
+    /// a flow collection, so `it's` and `a[0]` stay plain.
+    pub fn feed(&mut self, text: &str) -> Option<usize> {
+        let mut prev: Option<char> = None;
+        let mut chars = text.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match self.quote {
+                Some('"') => {
+                    if c == '\\' {
+                        chars.next();
+                    } else if c == '"' {
+                        self.close_quote(i + 1);
+                    }
+                }
+                Some(_) => {
+                    if c == '\'' {
+                        if chars.peek().is_some_and(|&(_, next)| next == '\'') {
+                            chars.next();
+                        } else {
+                            self.close_quote(i + 1);
+                        }
+                    }
+                }
+                None => {
+                    let starts = i == 0 || self.depth > 0;
+                    let after_space = prev.is_none_or(|p| p.is_whitespace() || "[{,".contains(p));
+                    match c {
+                        '#' if prev.is_none_or(char::is_whitespace) => return Some(i),
+                        '"' | '\'' if starts && after_space => self.quote = Some(c),
+                        '[' | '{' if starts => self.depth += 1,
+                        ']' | '}' if self.depth > 0 => self.depth -= 1,
+                        _ => {}
+                    }
+                }
+            }
+            prev = Some(c);
+        }
+        None
+    }
+
+    fn close_quote(&mut self, end: usize) {
+        self.quote = None;
+        if self.depth == 0 && self.quote_end.is_none() {
+            self.quote_end = Some(end);
+        }
+    }
+}
+
+/// The text of a key, without quotes or escapes, for comparison.
+pub(super) fn unquote(text: &str) -> String {
+    if let Some(inner) = quoted(text, '\'') {
+        inner.replace("''", "'")
+    } else if let Some(inner) = quoted(text, '"') {
+        let mut out = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some(c) => out.push(c),
+                    None => {}
+                },
+                c => out.push(c),
+            }
+        }
+        out
+    } else {
+        text.to_string()
+    }
+}
+
+/// The inside of `text` when it is exactly one scalar quoted with `quote`.
+fn quoted(text: &str, quote: char) -> Option<&str> {
+    if !text.starts_with(quote) {
+        return None;
+    }
+    let mut scan = Scan::default();
+    scan.feed(text);
+    (scan.quote_end == Some(text.len())).then(|| &text[1..text.len() - 1])
+}
+
+/// Switch a quoted scalar to the preferred quotes when that needs no
+/// escaping.
+pub(super) fn requote(text: &str, style: QuoteStyle) -> Cow<'_, str> {
+    match style {
+        QuoteStyle::Double => match quoted(text, '\'') {
+            Some(inner) if !inner.contains(['"', '\\']) => {
+                format!("\"{}\"", inner.replace("''", "'")).into()
+            }
+            _ => text.into(),
+        },
+        QuoteStyle::Single => match quoted(text, '"') {
+            Some(inner) if !inner.contains(['\'', '\\']) => format!("'{}'", inner).into(),
+            _ => text.into(),
+        },
+        QuoteStyle::Preserve => text.into(),
+    }
+}
+
+const YAML11_BOOLEANS: &[&str] = &[
+    "y", "Y", "yes", "Yes", "YES", "n", "N", "no", "No", "NO", "on", "On", "ON", "off", "Off",
+    "OFF",
+];
+
+fn digits(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Plain scalars that YAML 1.1 and YAML 1.2 parsers read differently, or
+/// that lose what they look like; returns the message and a hint.
+pub(super) fn implicit_type(text: &str) -> Option<(String, String)> {
+    if YAML11_BOOLEANS.contains(&text) {
+        return Some((
+            format!(
+                "`{}` is a boolean in YAML 1.1 but a string in YAML 1.2",
+                text
+            ),
+            "Quote it to keep a string, or write true or false".to_string(),
+        ));
+    }
+    let parts: Vec<&str> = text.split(':').collect();
+    if parts.len() > 1 && digits(parts[0]) && parts[1..].iter().all(|p| digits(p) && p.len() <= 2) {
+        return Some((
+            format!("`{}` is a base 60 number in YAML 1.1", text),
+            "Quote it to keep a string".to_string(),
+        ));
+    }
+    if text.len() > 1 && text.starts_with('0') && digits(text) {
+        return Some((
+            format!("`{}` is octal in YAML 1.1 and decimal in YAML 1.2", text),
+            format!(
+                "Quote it to keep a string, or write 0o{} for octal",
+                &text[1..]
+            ),
+        ));
+    }
+    if let Some((whole, fraction)) = text.split_once('.') {
+        if digits(whole) && digits(fraction) && fraction.len() > 1 && fraction.ends_with('0') {
+            let number: f64 = text.parse().ok()?;
+            return Some((
+                format!("`{}` is read as the number {}", text, number),
+                "Quote it to keep the trailing zero".to_string(),
+            ));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scans_quotes_and_comments() {
+        let mut scan = Scan::default();
+        assert_eq!(scan.feed("it's # note"), Some(5));
+        let mut scan = Scan::default();
+        assert_eq!(scan.feed("'a # b''c' # d"), Some(11));
+        assert_eq!(scan.quote_end, Some(10));
+        let mut scan = Scan::default();
+        scan.feed("[a, \"b,");
+        assert!(scan.is_open());
+        scan.feed("c\"]");
+        assert!(!scan.is_open());
+
+        assert_eq!(requote("'say \"hi\"'", QuoteStyle::Double), "'say \"hi\"'");
+        assert_eq!(requote("\"plain\"", QuoteStyle::Single), "'plain'");
+        assert_eq!(unquote("\"a\\\"b\""), "a\"b");
+        assert!(implicit_type("12:30").is_some());
+        assert!(implicit_type("1.5").is_none());
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Go structs with `json` tags, laid out the way gofmt aligns them.
+
+use super::model::{dedupe, leading, words, Kind, Model, Struct, TypeRef};
+
+/// Words Go style keeps fully upper-case inside identifiers.
+const INITIALISMS: &[&str] = &[
+    "ACL", "API", "ASCII", "CPU", "CSS", "DNS", "EOF", "GUID", "HTML", "HTTP", "HTTPS", "ID", "IP",
+    "JSON", "LHS", "QPS", "RAM", "RHS", "RPC", "SLA", "SMTP", "SQL", "SSH", "TCP", "TLS", "TTL",
+    "UDP", "UI", "UID", "URI", "URL", "UTF8", "UUID", "VM", "XML", "XMPP", "XSRF", "XSS",
+];
+
+fn exported(key: &str) -> String {
+    let name: String = words(key)
+        .iter()
+        .map(|word| {
+            let upper = word.to_ascii_uppercase();
+            if INITIALISMS.contains(&upper.as_str()) {
+                upper
+            } else {
+                let lower = word.to_ascii_lowercase();
+                let mut chars = lower.chars();
+                chars
+                    .next()
+                    .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            }
+        })
+        .collect();
+    leading(name, "Field")
+}
+
+pub(super) fn emit(model: &Model, inline: bool) -> String {
+    let mut out = String::new();
+    if inline || !model.root_is_struct() {
+        let root = ty(model, &model.root, inline, 0);
+        out.push_str(&format!("type {} {}\n", model.name, root));
+        if inline {
+            return out;
+        }
+    }
+    for item in model.structs.iter().rev() {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!(
+            "type {} {}\n",
+            item.name,
+            body(model, item, false, 0)
+        ));
+    }
+    out
+}
+
+fn body(model: &Model, item: &Struct, inline: bool, depth: usize) -> String {
+    if item.fields.is_empty() {
+        return "struct{}".to_string();
+    }
+    let mut taken = Vec::new();
+    let rows: Vec<(String, String, String)> = item
+        .fields
+        .iter()
+        .map(|field| {
+            let name = dedupe(exported(&field.key), &mut taken);
+            let optional = field.optional || field.ty.nullable;
+            let tag = format!(
+                "`json:\"{}{}\"`",
+                field.key.replace('"', "\\\""),
+                if field.optional { ",omitempty" } else { "" }
+            );
+            (
+                name,
+                pointer(model, &field.ty, optional, inline, depth + 1),
+                tag,
+            )
+        })
+        .collect();
+
+    // gofmt aligns columns across runs of single-line fields.
+    let pad = "\t".repeat(depth + 1);
+    let mut out = String::from("struct {\n");
+    for run in rows.split_inclusive(|(_, ty, _)| ty.contains('\n')) {
+        let single: Vec<&(String, String, String)> =
+            run.iter().filter(|(_, ty, _)| !ty.contains('\n')).collect();
+        let name_width = single.iter().map(|(n, _, _)| n.len()).max().unwrap_or(0);
+        let ty_width = single.iter().map(|(_, t, _)| t.len()).max().unwrap_or(0);
+        for (name, ty, tag) in run {
+            if ty.contains('\n') {
+                out.push_str(&format!("{}{} {} {}\n", pad, name, ty, tag));
+            } else {
+                out.push_str(&format!(
+                    "{}{:name_width$} {:ty_width$} {}\n",
+                    pad, name, ty, tag
+                ));
+            }
+        }
+    }
+    out.push_str(&"\t".repeat(depth));
+    out.push('}');
+    out
+}
+
+/// Nullable or optional scalars and structs become pointers; slices and
+/// `any` already have a nil value.
+fn pointer(model: &Model, ty: &TypeRef, optional: bool, inline: bool, depth: usize) -> String {
+    let inner = self::ty(model, ty, inline, depth);
+    match ty.kind {
+        Kind::Any | Kind::Array(_) => inner,
+        _ if optional => format!("*{}", inner),
+        _ => inner,
+    }
+}
+
+fn ty(model: &Model, ty: &TypeRef, inline: bool, depth: usize) -> String {
+    match &ty.kind {
+        Kind::Any => "any".to_string(),
+        Kind::Bool => "bool".to_string(),
+        Kind::Int => "int64".to_string(),
+        Kind::Float => "float64".to_string(),
+        Kind::String => "string".to_string(),
+        Kind::Array(item) => format!("[]{}", pointer(model, item, item.nullable, inline, depth)),
+        Kind::Object(id) if inline => body(model, &model.structs[*id], true, depth),
+        Kind::Object(id) => model.structs[*id].name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{generate_code, CodegenLanguage, CodegenOptions};
+
+    #[test]
+    fn emits_aligned_structs_with_tags() {
+        let samples = r#"
+            {"id": 1, "avatar_url": "u", "owner": {"login": "a"}, "labels": ["x"], "score": 1.5}
+            {"id": 2, "avatar_url": null, "owner": {"login": "b"}, "labels": []}
+        "#;
+        let code = generate_code(samples, CodegenLanguage::Go, None).unwrap();
+        assert_eq!(
+            code,
+            "type Root struct {\n\
+             \tID        int64    `json:\"id\"`\n\
+             \tAvatarURL *string  `json:\"avatar_url\"`\n\
+             \tOwner     Owner    `json:\"owner\"`\n\
+             \tLabels    []string `json:\"labels\"`\n\
+             \tScore     *float64 `json:\"score,omitempty\"`\n\
+             }\n\
+             \n\
+             type Owner struct {\n\
+             \tLogin string `json:\"login\"`\n\
+             }\n"
+        );
+
+        let inline = Some(CodegenOptions {
+            inline: true,
+            root_name: "repo".to_string(),
+            ..Default::default()
+        });
+        let code = generate_code(
+            r#"{"a": {"b": 1}, "cc": true}"#,
+            CodegenLanguage::Go,
+            inline,
+        )
+        .unwrap();
+        assert_eq!(
+            code,
+            "type Repo struct {\n\
+             \tA struct {\n\
+             \t\tB int64 `json:\"b\"`\n\
+             \t} `json:\"a\"`\n\
+             \tCc bool `json:\"cc\"`\n\
+             }\n"
+        );
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Kotlin data classes for kotlinx.serialization.
+
+use super::model::{camel, dedupe, leading, quote, Field, Kind, Model, Struct, TypeRef};
+
+const KEYWORDS: &[&str] = &[
+    "as",
+    "break",
+    "class",
+    "continue",
+    "do",
+    "else",
+    "false",
+    "for",
+    "fun",
+    "if",
+    "in",
+    "interface",
+    "is",
+    "null",
+    "object",
+    "package",
+    "return",
+    "super",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typealias",
+    "typeof",
+    "val",
+    "var",
+    "when",
+    "while",
+];
+
+fn identifier(key: &str) -> String {
+    let name = leading(camel(key), "field");
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("`{}`", name)
+    } else {
+        name
+    }
+}
+
+pub(super) fn emit(model: &Model) -> String {
+    let mut classes = String::new();
+    let mut renamed = false;
+    if !model.root_is_struct() {
+        classes.push_str(&format!(
+            "\ntypealias {} = {}\n",
+            model.name,
+            ty(model, &model.root)
+        ));
+    }
+    for item in model.structs.iter().rev() {
+        classes.push('\n');
+        renamed |= emit_class(model, item, &mut classes);
+    }
+
+    let mut out = String::new();
+    if renamed {
+        out.push_str("import kotlinx.serialization.SerialName\n");
+    }
+    out.push_str("import kotlinx.serialization.Serializable\n");
+    if model.uses_any() {
+        out.push_str("import kotlinx.serialization.json.JsonElement\n");
+    }
+    out + &classes
+}
+
+/// Returns whether any property needed `@SerialName`.
+fn emit_class(model: &Model, item: &Struct, out: &mut String) -> bool {
+    let mut taken = Vec::new();
+    let fields: Vec<(String, &Field)> = item
+        .fields
+        .iter()
+        .map(|field| (dedupe(identifier(&field.key), &mut taken), field))
+        .collect();
+
+    out.push_str("@Serializable\n");
+    if fields.is_empty() {
+        out.push_str(&format!("class {}\n", item.name));
+        return false;
+    }
+    let mut renamed = false;
+    out.push_str(&format!("data class {}(\n", item.name));
+    for (name, field) in fields {
+        out.push_str("    ");
+        if name.trim_matches('`') != field.key {
+            out.push_str(&format!("@SerialName({}) ", quote(&field.key)));
+            renamed = true;
+        }
+        let mut ty = ty(model, &field.ty);
+        if field.optional {
+            if !ty.ends_with('?') {
+                ty.push('?');
+            }
+            out.push_str(&format!("val {}: {} = null,\n", name, ty));
+        } else {
+            out.push_str(&format!("val {}: {},\n", name, ty));
+        }
+    }
+    out.push_str(")\n");
+    renamed
+}
+
+fn ty(model: &Model, ty: &TypeRef) -> String {
+    let inner = match &ty.kind {
+        Kind::Any => "JsonElement".to_string(),
+        Kind::Bool => "Boolean".to_string(),
+        Kind::Int => "Long".to_string(),
+        Kind::Float => "Double".to_string(),
+        Kind::String => "String".to_string(),
+        Kind::Array(item) => format!("List<{}>", self::ty(model, item)),
+        Kind::Object(id) => model.structs[*id].name.clone(),
+    };
+    if ty.nullable || ty.kind == Kind::Any {
+        format!("{}?", inner)
+    } else {
+        inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{generate_code, CodegenLanguage, CodegenOptions};
+
+    #[test]
+    fn emits_serializable_data_classes() {
+        let samples = r#"
+            {"id": 1, "full_name": "a", "object": {"x": 1}, "tags": ["a"], "any": 1}
+            {"id": 2, "full_name": null, "object": {"x": 2}, "any": "s"}
+        "#;
+        let code = generate_code(samples, CodegenLanguage::Kotlin, None).unwrap();
+        assert_eq!(
+            code,
+            r#"import kotlinx.serialization.SerialName
+import kotlinx.serialization.Serializable
+import kotlinx.serialization.json.JsonElement
+
+@Serializable
+data class Root(
+    val id: Long,
+    @SerialName("full_name") val fullName: String?,
+    val `object`: Object,
+    val tags: List<String>? = null,
+    val any: JsonElement?,
+)
+
+@Serializable
+data class Object(
+    val x: Long,
+)
+"#
+        );
+
+        let options = Some(CodegenOptions {
+            root_name: "page".to_string(),
+            path_names: true,
+            ..Default::default()
+        });
+        let code =
+            generate_code(r#"{"items": [{"n": 1}]}"#, CodegenLanguage::Kotlin, options).unwrap();
+        assert!(code.contains("val items: List<PageItem>,"), "{}", code);
+        assert!(code.contains("data class PageItem("), "{}", code);
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Typed code generation from JSON samples.
+//!
+//! All samples fold into one language-neutral `Model`; each emitter turns
+//! that into idiomatic declarations, keeping the original JSON keys through
+//! the language's rename mechanism where identifiers differ.
+
+mod go;
+mod kotlin;
+mod model;
+mod python;
+mod rust;
+mod typescript;
+
+use crate::error::UtilityError;
+use crate::formatter::json_diagnostic;
+use model::Model;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use universal_function_macro::universal_function;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum CodegenLanguage {
+    Rust,       // serde structs
+    TypeScript, // Interfaces
+    Zod,        // zod schemas with inferred types
+    Go,         // Structs with json tags
+    Python,     // dataclasses
+    Pydantic,   // pydantic v2 models
+    Kotlin,     // kotlinx.serialization data classes
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct CodegenOptions {
+    pub root_name: String, // Name of the top-level type
+    pub path_names: bool,  // Name nested types by full path (UserAddress) instead of key (Address)
+    pub inline: bool,      // Inline nested objects where the language allows (TypeScript, zod, Go)
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        Self {
+            root_name: "Root".to_string(),
+            path_names: false,
+            inline: false,
+        }
+    }
+}
+
+/// Generate type declarations for `language` from JSON samples.
+///
+/// `input` holds one or more samples back to back. A field missing from
+/// some sample object is optional, one seen as `null` is nullable, and a
+/// value whose type differs between samples falls back to the language's
+/// dynamic JSON type. Objects with identical fields share one type.
+#[universal_function]
+pub fn generate_code(
+    input: &str,
+    language: CodegenLanguage,
+    options: Option<CodegenOptions>,
+) -> Result<String, UtilityError> {
+    let options = options.unwrap_or_default();
+    let samples = serde_json::Deserializer::from_str(input)
+        .into_iter::<Value>()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| json_diagnostic(input, &e.to_string(), e.line(), e.column()))?;
+    if samples.is_empty() {
+        return Err(UtilityError::InvalidInput(
+            "No sample documents to generate code from".to_string(),
+        ));
+    }
+
+    let model = Model::build(&samples, &options);
+    Ok(match language {
+        CodegenLanguage::Rust => rust::emit(&model),
+        CodegenLanguage::TypeScript => typescript::emit(&model, options.inline),
+        CodegenLanguage::Zod => typescript::emit_zod(&model, options.inline),
+        CodegenLanguage::Go => go::emit(&model, options.inline),
+        CodegenLanguage::Python => python::emit(&model, false),
+        CodegenLanguage::Pydantic => python::emit(&model, true),
+        CodegenLanguage::Kotlin => kotlin::emit(&model),
+    })
+}
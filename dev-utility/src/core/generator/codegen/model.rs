@@ -0,0 +1,385 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Language-neutral type model inferred from JSON samples, plus the word
+//! splitting every emitter uses to build identifiers.
+
+use super::CodegenOptions;
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Kind {
+    Any, // Mixed or unknown; the language's dynamic JSON type
+    Bool,
+    Int,
+    Float,
+    String,
+    Array(Box<TypeRef>),
+    Object(usize), // Index into `Model::structs`
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct TypeRef {
+    pub kind: Kind,
+    pub nullable: bool, // Seen as `null` in some sample
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Field {
+    pub key: String, // JSON key, verbatim
+    pub ty: TypeRef,
+    pub optional: bool, // Missing from some sample object
+}
+
+#[derive(Debug)]
+pub(super) struct Struct {
+    pub name: String, // PascalCase, unique within the model
+    pub fields: Vec<Field>,
+}
+
+/// Structs are stored dependencies first, so the root object (if any) is
+/// last.
+#[derive(Debug)]
+pub(super) struct Model {
+    pub name: String, // Root type name
+    pub root: TypeRef,
+    pub structs: Vec<Struct>,
+}
+
+impl Model {
+    pub(super) fn build(samples: &[Value], options: &CodegenOptions) -> Model {
+        let mut shape = Shape::default();
+        for sample in samples {
+            shape.add(sample);
+        }
+        let name = match pascal(&options.root_name) {
+            name if name.is_empty() => "Root".to_string(),
+            name => name,
+        };
+        let mut builder = Builder {
+            options,
+            structs: Vec::new(),
+        };
+        let root = builder.type_of(&shape, &name);
+        Model {
+            name,
+            root,
+            structs: builder.structs,
+        }
+    }
+
+    /// Whether the root is a struct named after the root type, so no alias
+    /// is needed.
+    pub(super) fn root_is_struct(&self) -> bool {
+        matches!(self.root.kind, Kind::Object(id) if self.structs[id].name == self.name)
+            && !self.root.nullable
+    }
+
+    pub(super) fn uses_any(&self) -> bool {
+        fn any(ty: &TypeRef) -> bool {
+            match &ty.kind {
+                Kind::Any => true,
+                Kind::Array(item) => any(item),
+                _ => false,
+            }
+        }
+        any(&self.root)
+            || self
+                .structs
+                .iter()
+                .any(|s| s.fields.iter().any(|f| any(&f.ty)))
+    }
+}
+
+#[derive(Default)]
+struct Shape {
+    seen: usize,
+    null: bool,
+    boolean: bool,
+    integer: bool,
+    float: bool,
+    string: bool,
+    arrays: usize,
+    items: Option<Box<Shape>>,
+    objects: usize,
+    properties: Vec<(String, Shape)>,
+}
+
+impl Shape {
+    fn add(&mut self, value: &Value) {
+        self.seen += 1;
+        match value {
+            Value::Null => self.null = true,
+            Value::Bool(_) => self.boolean = true,
+            Value::Number(n) if n.is_f64() => self.float = true,
+            Value::Number(_) => self.integer = true,
+            Value::String(_) => self.string = true,
+            Value::Array(items) => {
+                self.arrays += 1;
+                for item in items {
+                    self.items.get_or_insert_with(Default::default).add(item);
+                }
+            }
+            Value::Object(map) => {
+                self.objects += 1;
+                for (key, item) in map {
+                    match self.properties.iter_mut().find(|(k, _)| k == key) {
+                        Some((_, shape)) => shape.add(item),
+                        None => {
+                            let mut shape = Shape::default();
+                            shape.add(item);
+                            self.properties.push((key.clone(), shape));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct Builder<'a> {
+    options: &'a CodegenOptions,
+    structs: Vec<Struct>,
+}
+
+impl Builder<'_> {
+    fn type_of(&mut self, shape: &Shape, name: &str) -> TypeRef {
+        let number = shape.integer || shape.float;
+        let kinds = [
+            shape.boolean,
+            number,
+            shape.string,
+            shape.arrays > 0,
+            shape.objects > 0,
+        ]
+        .iter()
+        .filter(|&&seen| seen)
+        .count();
+
+        let kind = if kinds != 1 {
+            Kind::Any
+        } else if shape.boolean {
+            Kind::Bool
+        } else if shape.float {
+            Kind::Float
+        } else if shape.integer {
+            Kind::Int
+        } else if shape.string {
+            Kind::String
+        } else if shape.arrays > 0 {
+            let item = match &shape.items {
+                Some(items) => self.type_of(items, &singular(name)),
+                None => TypeRef {
+                    kind: Kind::Any,
+                    nullable: false,
+                },
+            };
+            Kind::Array(Box::new(item))
+        } else {
+            Kind::Object(self.object(shape, name))
+        };
+        TypeRef {
+            kind,
+            nullable: shape.null,
+        }
+    }
+
+    fn object(&mut self, shape: &Shape, name: &str) -> usize {
+        let fields: Vec<Field> = shape
+            .properties
+            .iter()
+            .map(|(key, property)| {
+                let child = if self.options.path_names {
+                    format!("{}{}", name, pascal(key))
+                } else {
+                    pascal(key)
+                };
+                let child = if child.is_empty() {
+                    format!("{}Field", name)
+                } else {
+                    child
+                };
+                Field {
+                    key: key.clone(),
+                    ty: self.type_of(property, &child),
+                    optional: property.seen < shape.objects,
+                }
+            })
+            .collect();
+
+        // Objects with the same fields share one type.
+        if let Some(id) = self.structs.iter().position(|s| s.fields == fields) {
+            return id;
+        }
+        let mut unique = name.to_string();
+        let mut n = 2;
+        while self.structs.iter().any(|s| s.name == unique) {
+            unique = format!("{}{}", name, n);
+            n += 1;
+        }
+        self.structs.push(Struct {
+            name: unique,
+            fields,
+        });
+        self.structs.len() - 1
+    }
+}
+
+/// `Users` → `User`, `Categories` → `Category`; otherwise append `Item`.
+fn singular(name: &str) -> String {
+    if let Some(stem) = name.strip_suffix("ies").filter(|s| !s.is_empty()) {
+        format!("{}y", stem)
+    } else if let Some(stem) = name
+        .strip_suffix('s')
+        .filter(|s| !s.is_empty() && !s.ends_with('s'))
+    {
+        stem.to_string()
+    } else {
+        format!("{}Item", name)
+    }
+}
+
+/// Split a JSON key into words at separators and
+/// case changes: `userID` → [user, ID], `HTTPServer` → [HTTP, Server].
+pub(super) fn words(key: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let chars: Vec<char> = key.chars().collect();
+    let mut current = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1).copied();
+        let boundary = c.is_ascii_uppercase()
+            && match prev {
+                Some(p) if p.is_ascii_lowercase() || p.is_ascii_digit() => true,
+                Some(p) if p.is_ascii_uppercase() => next.is_some_and(|n| n.is_ascii_lowercase()),
+                _ => false,
+            };
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let lower = word.to_ascii_lowercase();
+    let mut chars = lower.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+pub(super) fn pascal(key: &str) -> String {
+    words(key).iter().map(|w| capitalize(w)).collect()
+}
+
+pub(super) fn camel(key: &str) -> String {
+    let pascal = pascal(key);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+pub(super) fn snake(key: &str) -> String {
+    words(key)
+        .iter()
+        .map(|w| w.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Make `ident` unique among `taken` by appending a counter.
+pub(super) fn dedupe(ident: String, taken: &mut Vec<String>) -> String {
+    let mut unique = ident.clone();
+    let mut n = 2;
+    while taken.contains(&unique) {
+        unique = format!("{}{}", ident, n);
+        n += 1;
+    }
+    taken.push(unique.clone());
+    unique
+}
+
+/// Prefix identifiers that would start with a digit (or be empty).
+pub(super) fn leading(ident: String, prefix: &str) -> String {
+    match ident.chars().next() {
+        None => prefix.trim_end_matches('_').to_string(),
+        Some(c) if c.is_ascii_digit() => format!("{}{}", prefix, ident),
+        Some(_) => ident,
+    }
+}
+
+/// A double-quoted string literal, escaped the way C-family languages agree on.
+pub(super) fn quote(s: &str) -> String {
+    serde_json::to_string(s).expect("strings always serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn splits_words_and_names_types() {
+        assert_eq!(words("userID"), vec!["user", "ID"]);
+        assert_eq!(
+            words("HTTPServer_port-2"),
+            vec!["HTTP", "Server", "port", "2"]
+        );
+        assert_eq!(snake("createdAt"), "created_at");
+        assert_eq!(camel("first_name"), "firstName");
+        assert_eq!(pascal("ip-address"), "IpAddress");
+        assert_eq!(singular("Categories"), "Category");
+        assert_eq!(singular("Address"), "AddressItem");
+
+        let samples = [
+            json!({"id": 1, "billing": {"city": "A"}, "shipping": {"city": "B"}, "tags": ["x"]}),
+            json!({"id": 2.5, "billing": {"city": "C"}, "shipping": null, "note": "n"}),
+        ];
+        let model = Model::build(&samples, &CodegenOptions::default());
+        assert!(model.root_is_struct());
+        let root = model.structs.last().unwrap();
+        let fields: Vec<(&str, &Kind, bool, bool)> = root
+            .fields
+            .iter()
+            .map(|f| (f.key.as_str(), &f.ty.kind, f.ty.nullable, f.optional))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("id", &Kind::Float, false, false),
+                ("billing", &Kind::Object(0), false, false),
+                ("shipping", &Kind::Object(0), true, false),
+                (
+                    "tags",
+                    &Kind::Array(Box::new(TypeRef {
+                        kind: Kind::String,
+                        nullable: false
+                    })),
+                    false,
+                    true
+                ),
+                ("note", &Kind::String, false, true),
+            ]
+        );
+        assert_eq!(model.structs[0].name, "Billing");
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Python dataclasses and pydantic v2 models.
+
+use super::model::{dedupe, leading, quote, snake, Field, Kind, Model, Struct, TypeRef};
+
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+fn identifier(key: &str) -> String {
+    let name = leading(snake(key), "field_");
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+pub(super) fn emit(model: &Model, pydantic: bool) -> String {
+    let mut out = String::from("from __future__ import annotations\n\n");
+    if !pydantic {
+        out.push_str("from dataclasses import dataclass\n");
+    }
+    let optional = model
+        .structs
+        .iter()
+        .flat_map(|s| &s.fields)
+        .any(|f| f.optional || f.ty.nullable)
+        || model.root.nullable;
+    let typing: Vec<&str> = [(model.uses_any(), "Any"), (optional, "Optional")]
+        .into_iter()
+        .filter_map(|(used, name)| used.then_some(name))
+        .collect();
+    if !typing.is_empty() {
+        out.push_str(&format!("from typing import {}\n", typing.join(", ")));
+    }
+    if pydantic {
+        out.push_str("\nfrom pydantic import BaseModel, Field\n");
+    }
+
+    for item in &model.structs {
+        out.push_str("\n\n");
+        emit_class(model, item, pydantic, &mut out);
+    }
+    if !model.root_is_struct() {
+        out.push_str(&format!(
+            "\n\n{} = {}\n",
+            model.name,
+            ty(model, &model.root)
+        ));
+    }
+    out
+}
+
+fn emit_class(model: &Model, item: &Struct, pydantic: bool, out: &mut String) {
+    let mut taken = Vec::new();
+    let mut fields: Vec<(String, &Field)> = item
+        .fields
+        .iter()
+        .map(|field| (dedupe(identifier(&field.key), &mut taken), field))
+        .collect();
+    // Dataclass fields with defaults must come after those without.
+    if !pydantic {
+        fields.sort_by_key(|(_, field)| field.optional);
+    }
+
+    if pydantic {
+        out.push_str(&format!("class {}(BaseModel):\n", item.name));
+    } else {
+        out.push_str(&format!("@dataclass\nclass {}:\n", item.name));
+    }
+    if fields.is_empty() {
+        out.push_str("    pass\n");
+    }
+    for (name, field) in fields {
+        let mut ty = ty(model, &field.ty);
+        if field.optional && !field.ty.nullable && field.ty.kind != Kind::Any {
+            ty = format!("Optional[{}]", ty);
+        }
+        let aliased = name != field.key;
+        let line = match (pydantic, aliased, field.optional) {
+            (true, true, true) => format!(
+                "{}: {} = Field(default=None, alias={})",
+                name,
+                ty,
+                quote(&field.key)
+            ),
+            (true, true, false) => format!("{}: {} = Field(alias={})", name, ty, quote(&field.key)),
+            (_, _, true) => format!("{}: {} = None", name, ty),
+            (_, _, false) => format!("{}: {}", name, ty),
+        };
+        out.push_str("    ");
+        out.push_str(&line);
+        // Dataclasses have no alias; note the JSON key instead.
+        if aliased && !pydantic {
+            out.push_str(&format!("  # {}", quote(&field.key)));
+        }
+        out.push('\n');
+    }
+}
+
+fn ty(model: &Model, ty: &TypeRef) -> String {
+    let inner = match &ty.kind {
+        Kind::Any => return "Any".to_string(),
+        Kind::Bool => "bool".to_string(),
+        Kind::Int => "int".to_string(),
+        Kind::Float => "float".to_string(),
+        Kind::String => "str".to_string(),
+        Kind::Array(item) => format!("list[{}]", self::ty(model, item)),
+        Kind::Object(id) => model.structs[*id].name.clone(),
+    };
+    if ty.nullable {
+        format!("Optional[{}]", inner)
+    } else {
+        inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{generate_code, CodegenLanguage};
+
+    const SAMPLES: &str = r#"
+        {"userId": 1, "class": "a", "meta": {"ok": true}, "note": "x"}
+        {"userId": 2, "class": "b", "meta": {"ok": false}}
+    "#;
+
+    #[test]
+    fn emits_dataclasses() {
+        let code = generate_code(SAMPLES, CodegenLanguage::Python, None).unwrap();
+        assert_eq!(
+            code,
+            r#"from __future__ import annotations
+
+from dataclasses import dataclass
+from typing import Optional
+
+
+@dataclass
+class Meta:
+    ok: bool
+
+
+@dataclass
+class Root:
+    user_id: int  # "userId"
+    class_: str  # "class"
+    meta: Meta
+    note: Optional[str] = None
+"#
+        );
+    }
+
+    #[test]
+    fn emits_pydantic_models_with_aliases() {
+        let code = generate_code(SAMPLES, CodegenLanguage::Pydantic, None).unwrap();
+        assert!(
+            code.contains("from pydantic import BaseModel, Field\n"),
+            "{}",
+            code
+        );
+        assert!(
+            code.ends_with(
+                "class Root(BaseModel):\n    \
+                 user_id: int = Field(alias=\"userId\")\n    \
+                 class_: str = Field(alias=\"class\")\n    \
+                 meta: Meta\n    \
+                 note: Optional[str] = None\n"
+            ),
+            "{}",
+            code
+        );
+        let code = generate_code("[1.5]", CodegenLanguage::Pydantic, None).unwrap();
+        assert!(code.ends_with("\n\nRoot = list[float]\n"), "{}", code);
+    }
+}
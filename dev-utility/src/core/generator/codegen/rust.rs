@@ -0,0 +1,248 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! Rust structs deriving serde's `Serialize` and `Deserialize`.
+
+use super::model::{dedupe, leading, quote, snake, Field, Kind, Model, Struct, TypeRef};
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "gen", "macro", "override", "priv", "try",
+    "typeof", "unsized", "virtual", "yield",
+];
+
+type Rule = fn(&str) -> String;
+
+/// `rename_all` rules serde applies to a snake_case field name.
+const RULES: &[(&str, Rule)] = &[
+    ("camelCase", |s| {
+        let pascal = pascal_rule(s);
+        let mut chars = pascal.chars();
+        chars
+            .next()
+            .map(|c| c.to_ascii_lowercase().to_string() + chars.as_str())
+            .unwrap_or_default()
+    }),
+    ("PascalCase", pascal_rule),
+    ("kebab-case", |s| s.replace('_', "-")),
+    ("SCREAMING_SNAKE_CASE", |s| s.to_ascii_uppercase()),
+];
+
+fn pascal_rule(s: &str) -> String {
+    s.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+pub(super) fn emit(model: &Model) -> String {
+    let mut out = String::from("use serde::{Deserialize, Serialize};\n");
+    if model.uses_any() {
+        out.push_str("use serde_json::Value;\n");
+    }
+    if !model.root_is_struct() {
+        out.push_str(&format!(
+            "\npub type {} = {};\n",
+            model.name,
+            ty(model, &model.root, false)
+        ));
+    }
+    for item in model.structs.iter().rev() {
+        out.push('\n');
+        emit_struct(model, item, &mut out);
+    }
+    out
+}
+
+fn emit_struct(model: &Model, item: &Struct, out: &mut String) {
+    let mut taken = Vec::new();
+    let fields: Vec<(String, &Field)> = item
+        .fields
+        .iter()
+        .map(|field| {
+            (
+                raw(&dedupe(leading(snake(&field.key), "field_"), &mut taken)),
+                field,
+            )
+        })
+        .collect();
+
+    // serde's name for a field under `rule`, seeing through `r#`.
+    let renamed = |rule: Option<Rule>, ident: &str| {
+        let ident = ident.trim_start_matches("r#");
+        match rule {
+            Some(rule) => rule(ident),
+            None => ident.to_string(),
+        }
+    };
+    let matches = |rule: Option<Rule>| {
+        fields
+            .iter()
+            .filter(|(ident, field)| renamed(rule, ident) == field.key)
+            .count()
+    };
+    // Keep plain snake_case unless another rule covers more keys.
+    let (name, rule) = RULES
+        .iter()
+        .map(|(name, rule)| (Some(*name), Some(*rule)))
+        .fold((None, None), |best, candidate| {
+            if matches(candidate.1) > matches(best.1) {
+                candidate
+            } else {
+                best
+            }
+        });
+
+    out.push_str("#[derive(Debug, Clone, Serialize, Deserialize)]\n");
+    if let Some(name) = name {
+        out.push_str(&format!("#[serde(rename_all = \"{}\")]\n", name));
+    }
+    out.push_str(&format!("pub struct {} {{\n", item.name));
+    for (ident, field) in &fields {
+        if renamed(rule, ident) != field.key {
+            out.push_str(&format!("    #[serde(rename = {})]\n", quote(&field.key)));
+        }
+        let optional = field.optional || (field.ty.nullable && field.ty.kind != Kind::Any);
+        if field.optional {
+            out.push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+        }
+        out.push_str(&format!(
+            "    pub {}: {},\n",
+            ident,
+            ty(model, &field.ty, optional)
+        ));
+    }
+    out.push_str("}\n");
+}
+
+fn raw(ident: &str) -> String {
+    match ident {
+        "self" | "Self" | "super" | "crate" => format!("{}_", ident),
+        _ if KEYWORDS.contains(&ident) => format!("r#{}", ident),
+        _ => ident.to_string(),
+    }
+}
+
+fn ty(model: &Model, ty: &TypeRef, optional: bool) -> String {
+    let inner = match &ty.kind {
+        Kind::Any => "Value".to_string(),
+        Kind::Bool => "bool".to_string(),
+        Kind::Int => "i64".to_string(),
+        Kind::Float => "f64".to_string(),
+        Kind::String => "String".to_string(),
+        Kind::Array(item) => format!(
+            "Vec<{}>",
+            self::ty(model, item, item.nullable && item.kind != Kind::Any)
+        ),
+        Kind::Object(id) => model.structs[*id].name.clone(),
+    };
+    if optional {
+        format!("Option<{}>", inner)
+    } else {
+        inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{generate_code, CodegenLanguage};
+
+    #[test]
+    fn emits_serde_structs() {
+        let samples = r#"
+            {"userId": 1, "displayName": "a", "type": "x", "address": {"zip_code": "1"}, "tags": ["a"]}
+            {"userId": 2, "displayName": null, "type": "y", "address": {"zip_code": "2"}, "extra": [1, "b"]}
+        "#;
+        let code = generate_code(samples, CodegenLanguage::Rust, None).unwrap();
+        assert_eq!(
+            code,
+            r#"use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Root {
+    pub user_id: i64,
+    pub display_name: Option<String>,
+    pub r#type: String,
+    pub address: Address,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra: Option<Vec<Value>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Address {
+    pub zip_code: String,
+}
+"#
+        );
+
+        let code = generate_code("[1, 2]", CodegenLanguage::Rust, None).unwrap();
+        assert!(code.contains("pub type Root = Vec<i64>;"), "{}", code);
+        let code = generate_code(
+            r#"{"a-b": 1, "Weird Key": 2, "2fa": true}"#,
+            CodegenLanguage::Rust,
+            None,
+        )
+        .unwrap();
+        assert!(
+            code.contains("#[serde(rename_all = \"kebab-case\")]"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("    #[serde(rename = \"Weird Key\")]\n    pub weird_key: i64,"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("    #[serde(rename = \"2fa\")]\n    pub field_2fa: bool,"),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn renames_reserved_and_optional_any_fields() {
+        let samples = r#"[{"self":1,"opt":null,"x-y":{}},{"self":2,"x-y":{}}]"#;
+        let code = generate_code(samples, CodegenLanguage::Rust, None).unwrap();
+        assert!(code.contains("    pub self_: i64,\n"), "{}", code);
+        assert!(
+            code.contains("\"Option::is_none\")]\n    pub opt: Option<Value>,"),
+            "{}",
+            code
+        );
+
+        // Under kebab-case `self_` would serialize as "self-".
+        let code = generate_code(
+            r#"{"self": 1, "a-b": 2, "c-d": 3}"#,
+            CodegenLanguage::Rust,
+            None,
+        )
+        .unwrap();
+        assert!(
+            code.contains("#[serde(rename_all = \"kebab-case\")]"),
+            "{}",
+            code
+        );
+        assert!(
+            code.contains("    #[serde(rename = \"self\")]\n    pub self_: i64,"),
+            "{}",
+            code
+        );
+    }
+}
@@ -0,0 +1,242 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+//! TypeScript interfaces and zod schemas. Keys are kept verbatim and
+//! quoted when they are not valid identifiers.
+
+use super::model::{quote, Kind, Model, Struct, TypeRef};
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn property(key: &str) -> String {
+    if is_identifier(key) {
+        key.to_string()
+    } else {
+        quote(key)
+    }
+}
+
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth)
+}
+
+pub(super) fn emit(model: &Model, inline: bool) -> String {
+    let mut out = String::new();
+    if inline {
+        match model.root.kind {
+            Kind::Object(id) if !model.root.nullable => {
+                out.push_str(&format!("export interface {} ", model.name));
+                out.push_str(&body(model, &model.structs[id], true, 0));
+                out.push('\n');
+            }
+            _ => out.push_str(&format!(
+                "export type {} = {};\n",
+                model.name,
+                ty(model, &model.root, true, 0)
+            )),
+        }
+        return out;
+    }
+
+    if !model.root_is_struct() {
+        out.push_str(&format!(
+            "export type {} = {};\n",
+            model.name,
+            ty(model, &model.root, false, 0)
+        ));
+    }
+    for item in model.structs.iter().rev() {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("export interface {} ", item.name));
+        out.push_str(&body(model, item, false, 0));
+        out.push('\n');
+    }
+    out
+}
+
+fn body(model: &Model, item: &Struct, inline: bool, depth: usize) -> String {
+    let mut out = String::from("{\n");
+    for field in &item.fields {
+        out.push_str(&format!(
+            "{}{}{}: {};\n",
+            indent(depth + 1),
+            property(&field.key),
+            if field.optional { "?" } else { "" },
+            ty(model, &field.ty, inline, depth + 1)
+        ));
+    }
+    out.push_str(&indent(depth));
+    out.push('}');
+    out
+}
+
+fn ty(model: &Model, ty: &TypeRef, inline: bool, depth: usize) -> String {
+    let inner = match &ty.kind {
+        Kind::Any => return "unknown".to_string(),
+        Kind::Bool => "boolean".to_string(),
+        Kind::Int | Kind::Float => "number".to_string(),
+        Kind::String => "string".to_string(),
+        Kind::Array(item) => {
+            let item_ty = self::ty(model, item, inline, depth);
+            if item.nullable {
+                format!("({})[]", item_ty)
+            } else {
+                format!("{}[]", item_ty)
+            }
+        }
+        Kind::Object(id) if inline => body(model, &model.structs[*id], true, depth),
+        Kind::Object(id) => model.structs[*id].name.clone(),
+    };
+    if ty.nullable {
+        format!("{} | null", inner)
+    } else {
+        inner
+    }
+}
+
+pub(super) fn emit_zod(model: &Model, inline: bool) -> String {
+    let mut out = String::from("import { z } from \"zod\";\n");
+    if !inline {
+        for item in &model.structs {
+            out.push_str(&format!(
+                "\nexport const {}Schema = z.object({});\n",
+                item.name,
+                zod_body(model, item, false, 0)
+            ));
+            out.push_str(&format!(
+                "export type {0} = z.infer<typeof {0}Schema>;\n",
+                item.name
+            ));
+        }
+        if model.root_is_struct() {
+            return out;
+        }
+    }
+    out.push_str(&format!(
+        "\nexport const {}Schema = {};\n",
+        model.name,
+        zod(model, &model.root, inline, 0)
+    ));
+    out.push_str(&format!(
+        "export type {0} = z.infer<typeof {0}Schema>;\n",
+        model.name
+    ));
+    out
+}
+
+fn zod_body(model: &Model, item: &Struct, inline: bool, depth: usize) -> String {
+    let mut out = String::from("{\n");
+    for field in &item.fields {
+        out.push_str(&format!(
+            "{}{}: {}{},\n",
+            indent(depth + 1),
+            property(&field.key),
+            zod(model, &field.ty, inline, depth + 1),
+            if field.optional { ".optional()" } else { "" }
+        ));
+    }
+    out.push_str(&indent(depth));
+    out.push('}');
+    out
+}
+
+fn zod(model: &Model, ty: &TypeRef, inline: bool, depth: usize) -> String {
+    let inner = match &ty.kind {
+        Kind::Any => return "z.unknown()".to_string(),
+        Kind::Bool => "z.boolean()".to_string(),
+        Kind::Int => "z.number().int()".to_string(),
+        Kind::Float => "z.number()".to_string(),
+        Kind::String => "z.string()".to_string(),
+        Kind::Array(item) => format!("z.array({})", zod(model, item, inline, depth)),
+        Kind::Object(id) if inline => format!(
+            "z.object({})",
+            zod_body(model, &model.structs[*id], true, depth)
+        ),
+        Kind::Object(id) => format!("{}Schema", model.structs[*id].name),
+    };
+    if ty.nullable {
+        format!("{}.nullable()", inner)
+    } else {
+        inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{generate_code, CodegenLanguage, CodegenOptions};
+
+    const SAMPLES: &str = r#"
+        {"id": 1, "first-name": "a", "address": {"city": "x"}, "scores": [1, null]}
+        {"id": 2, "first-name": null, "address": {"city": "y"}}
+    "#;
+
+    #[test]
+    fn emits_interfaces() {
+        let code = generate_code(SAMPLES, CodegenLanguage::TypeScript, None).unwrap();
+        assert_eq!(
+            code,
+            r#"export interface Root {
+  id: number;
+  "first-name": string | null;
+  address: Address;
+  scores?: (number | null)[];
+}
+
+export interface Address {
+  city: string;
+}
+"#
+        );
+
+        let inline = Some(CodegenOptions {
+            inline: true,
+            ..Default::default()
+        });
+        let code = generate_code(
+            "[{\"a\": {\"b\": true}}]",
+            CodegenLanguage::TypeScript,
+            inline,
+        )
+        .unwrap();
+        assert_eq!(
+            code,
+            "export type Root = {\n  a: {\n    b: boolean;\n  };\n}[];\n"
+        );
+    }
+
+    #[test]
+    fn emits_zod_schemas_dependencies_first() {
+        let code = generate_code(SAMPLES, CodegenLanguage::Zod, None).unwrap();
+        assert_eq!(
+            code,
+            r#"import { z } from "zod";
+
+export const AddressSchema = z.object({
+  city: z.string(),
+});
+export type Address = z.infer<typeof AddressSchema>;
+
+export const RootSchema = z.object({
+  id: z.number().int(),
+  "first-name": z.string().nullable(),
+  address: AddressSchema,
+  scores: z.array(z.number().int().nullable()).optional(),
+});
+export type Root = z.infer<typeof RootSchema>;
+"#
+        );
+    }
+}
@@ -1,3 +1,8 @@
 pub mod id;
 pub use id::*;
 
+pub mod qrcode;
+pub use self::qrcode::*;
+
+pub mod codegen;
+pub use codegen::*;
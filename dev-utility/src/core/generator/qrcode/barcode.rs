@@ -0,0 +1,404 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+use super::encode_png;
+use crate::error::UtilityError;
+use serde::{Deserialize, Serialize};
+use universal_function_macro::universal_function;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum BarcodeFormat {
+    Code128,
+    Ean13,
+    UpcA,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct BarcodeImage {
+    pub svg: String,        // Standalone SVG document
+    pub png_base64: String, // PNG bytes, Base64 encoded (no data: prefix)
+    pub terminal: String,   // Block-character rendering for terminals
+    pub modules: u32,       // Symbol width in modules, excluding quiet zone
+    pub text: String,       // Human-readable text, including any check digit
+}
+
+/// Encode `data` as a linear barcode.
+///
+/// EAN-13 and UPC-A accept the payload with or without its check digit; a
+/// supplied check digit is verified. `size` is the minimum image width in
+/// pixels, capped at [`MAX_BARCODE_SIZE`].
+#[universal_function]
+pub fn generate_barcode(
+    data: &str,
+    format: BarcodeFormat,
+    size: u32,
+) -> Result<BarcodeImage, UtilityError> {
+    let (bars, text) = match format {
+        BarcodeFormat::Code128 => (encode_code128(data)?, data.to_string()),
+        BarcodeFormat::Ean13 => {
+            let digits = ean_digits(data, 13)?;
+            (encode_ean13(&digits), digits_to_string(&digits))
+        }
+        BarcodeFormat::UpcA => {
+            // UPC-A is EAN-13 with an implicit leading zero.
+            let mut digits = vec![0];
+            digits.extend(ean_digits(data, 12)?);
+            (encode_ean13(&digits), digits_to_string(&digits[1..]))
+        }
+    };
+    render_bars(&bars, size.min(MAX_BARCODE_SIZE), text)
+}
+
+/// Widest image `generate_barcode` will render; the PNG is a full bitmap,
+/// so this keeps a stray size from allocating gigabytes.
+pub const MAX_BARCODE_SIZE: u32 = 4096;
+
+// Bar/space widths for Code 128 symbol values 0-105. Every symbol is 11
+// modules wide; the stop pattern is handled separately.
+const CODE128_PATTERNS: [&str; 106] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232",
+];
+const CODE128_STOP: &str = "2331112";
+
+const CODE_C: u16 = 99;
+const CODE_B: u16 = 100;
+const CODE_A: u16 = 101;
+const START_A: u16 = 103;
+const START_B: u16 = 104;
+const START_C: u16 = 105;
+
+#[derive(Clone, Copy, PartialEq)]
+enum CodeSet {
+    A,
+    B,
+    C,
+}
+
+fn encode_code128(data: &str) -> Result<Vec<bool>, UtilityError> {
+    if data.is_empty() {
+        return Err(UtilityError::InvalidInput("Nothing to encode".to_string()));
+    }
+    if let Some(c) = data.chars().find(|c| !c.is_ascii()) {
+        return Err(UtilityError::InvalidInput(format!(
+            "Code 128 only encodes ASCII, found {:?}",
+            c
+        )));
+    }
+
+    let bytes = data.as_bytes();
+    let digit_run = |from: usize| bytes[from..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let set_for = |b: u8| if b < 32 { CodeSet::A } else { CodeSet::B };
+
+    // Code set C packs digit pairs; it only pays off for runs of four or
+    // more. An odd run gets its first digit encoded in A/B.
+    let wants_c = |from: usize| {
+        let run = digit_run(from);
+        run >= 4 && run % 2 == 0
+    };
+
+    let mut set = if wants_c(0) {
+        CodeSet::C
+    } else {
+        set_for(bytes[0])
+    };
+    let mut values = vec![match set {
+        CodeSet::A => START_A,
+        CodeSet::B => START_B,
+        CodeSet::C => START_C,
+    }];
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if set == CodeSet::C {
+            if digit_run(i) >= 2 {
+                values.push(((bytes[i] - b'0') * 10 + (bytes[i + 1] - b'0')) as u16);
+                i += 2;
+                continue;
+            }
+            set = set_for(bytes[i]);
+            values.push(if set == CodeSet::A { CODE_A } else { CODE_B });
+        }
+
+        if wants_c(i) {
+            set = CodeSet::C;
+            values.push(CODE_C);
+            continue;
+        }
+
+        let b = bytes[i];
+        let needed = if (32..96).contains(&b) { set } else { set_for(b) };
+        if needed != set {
+            set = needed;
+            values.push(if set == CodeSet::A { CODE_A } else { CODE_B });
+        }
+        values.push(match set {
+            CodeSet::A if b < 32 => b as u16 + 64,
+            _ => b as u16 - 32,
+        });
+        i += 1;
+    }
+
+    let checksum = values
+        .iter()
+        .enumerate()
+        .map(|(pos, v)| pos.max(1) as u32 * *v as u32)
+        .sum::<u32>()
+        % 103;
+    values.push(checksum as u16);
+
+    let mut bars = Vec::new();
+    for v in values {
+        push_widths(&mut bars, CODE128_PATTERNS[v as usize]);
+    }
+    push_widths(&mut bars, CODE128_STOP);
+    Ok(bars)
+}
+
+/// Append alternating bar/space runs, starting with a bar.
+fn push_widths(bars: &mut Vec<bool>, widths: &str) {
+    for (i, w) in widths.bytes().enumerate() {
+        let dark = i % 2 == 0;
+        bars.extend(std::iter::repeat_n(dark, (w - b'0') as usize));
+    }
+}
+
+// EAN left-hand odd-parity (L) codes; R codes are their complement and G
+// codes the reverse of R.
+const EAN_L: [u8; 10] = [
+    0b0001101, 0b0011001, 0b0010011, 0b0111101, 0b0100011, 0b0110001, 0b0101111, 0b0111011,
+    0b0110111, 0b0001011,
+];
+
+// Parity of the six left-hand digits, selected by the leading digit
+// (bit set = G code).
+const EAN_PARITY: [u8; 10] = [
+    0b000000, 0b001011, 0b001101, 0b001110, 0b010011, 0b011001, 0b011100, 0b010101, 0b010110,
+    0b011010,
+];
+
+/// Parse an EAN/UPC payload of `len` digits, computing or verifying the
+/// trailing check digit.
+fn ean_digits(data: &str, len: usize) -> Result<Vec<u8>, UtilityError> {
+    let trimmed = data.trim();
+    if !trimmed.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(UtilityError::InvalidInput(
+            "Only digits are allowed".to_string(),
+        ));
+    }
+    let mut digits: Vec<u8> = trimmed.bytes().map(|b| b - b'0').collect();
+    if digits.len() != len && digits.len() != len - 1 {
+        return Err(UtilityError::InvalidInput(format!(
+            "Expected {} digits (or {} without check digit), got {}",
+            len,
+            len - 1,
+            digits.len()
+        )));
+    }
+
+    // Weights alternate 3,1,3... counting from the rightmost payload digit.
+    let payload = &digits[..len - 1];
+    let sum: u32 = payload
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| *d as u32 * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    let check = ((10 - sum % 10) % 10) as u8;
+
+    if digits.len() == len {
+        if digits[len - 1] != check {
+            return Err(UtilityError::InvalidInput(format!(
+                "Invalid check digit {}, expected {}",
+                digits[len - 1],
+                check
+            )));
+        }
+    } else {
+        digits.push(check);
+    }
+    Ok(digits)
+}
+
+fn encode_ean13(digits: &[u8]) -> Vec<bool> {
+    let mut bars = Vec::with_capacity(95);
+    let push_bits = |bars: &mut Vec<bool>, code: u8| {
+        for shift in (0..7).rev() {
+            bars.push(code >> shift & 1 == 1);
+        }
+    };
+
+    bars.extend([true, false, true]);
+    let parity = EAN_PARITY[digits[0] as usize];
+    for (i, d) in digits[1..7].iter().enumerate() {
+        let l = EAN_L[*d as usize];
+        let code = if parity >> (5 - i) & 1 == 1 {
+            // G code: reversed complement of L.
+            (!l & 0x7f).reverse_bits() >> 1
+        } else {
+            l
+        };
+        push_bits(&mut bars, code);
+    }
+    bars.extend([false, true, false, true, false]);
+    for d in &digits[7..13] {
+        push_bits(&mut bars, !EAN_L[*d as usize] & 0x7f);
+    }
+    bars.extend([true, false, true]);
+    bars
+}
+
+fn digits_to_string(digits: &[u8]) -> String {
+    digits.iter().map(|d| (b'0' + d) as char).collect()
+}
+
+fn render_bars(bars: &[bool], size: u32, text: String) -> Result<BarcodeImage, UtilityError> {
+    const QUIET_ZONE: usize = 10;
+    let total = bars.len() + QUIET_ZONE * 2;
+    let scale = (size as usize).div_ceil(total).max(1);
+    let width = total * scale;
+    let height = (width / 3).max(24);
+
+    let mut svg = format!(
+        "<?xml version=\"1.0\" standalone=\"yes\"?><svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" shape-rendering=\"crispEdges\"><rect x=\"0\" y=\"0\" width=\"{w}\" height=\"{h}\" fill=\"#ffffff\"/><path fill=\"#000000\" d=\"",
+        w = width,
+        h = height
+    );
+    let mut x = 0;
+    while x < bars.len() {
+        if !bars[x] {
+            x += 1;
+            continue;
+        }
+        let run = bars[x..].iter().take_while(|b| **b).count();
+        svg.push_str(&format!(
+            "M{} 0h{}v{}h-{}z",
+            (x + QUIET_ZONE) * scale,
+            run * scale,
+            height,
+            run * scale
+        ));
+        x += run;
+    }
+    svg.push_str("\"/></svg>");
+
+    let row: Vec<u8> = (0..width)
+        .map(|px| {
+            let module = px / scale;
+            let dark = module >= QUIET_ZONE
+                && module < QUIET_ZONE + bars.len()
+                && bars[module - QUIET_ZONE];
+            if dark {
+                0
+            } else {
+                255
+            }
+        })
+        .collect();
+    let luma = row.repeat(height);
+    let png_base64 = encode_png(&luma, width as u32, height as u32)?;
+
+    let line: String = std::iter::repeat_n(' ', QUIET_ZONE / 2)
+        .chain(bars.iter().map(|b| if *b { '█' } else { ' ' }))
+        .chain(std::iter::repeat_n(' ', QUIET_ZONE / 2))
+        .collect();
+    let terminal = std::iter::repeat_n(line, 4).collect::<Vec<_>>().join("\n");
+
+    Ok(BarcodeImage {
+        svg,
+        png_base64,
+        terminal,
+        modules: bars.len() as u32,
+        text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code128_patterns_are_eleven_modules() {
+        for p in CODE128_PATTERNS {
+            let sum: u32 = p.bytes().map(|b| (b - b'0') as u32).sum();
+            assert_eq!(sum, 11, "pattern {}", p);
+        }
+    }
+
+    #[test]
+    fn code128_uses_set_c_for_digits() {
+        // Start C + 3 pairs + checksum + stop.
+        let r = generate_barcode("123456", BarcodeFormat::Code128, 0).unwrap();
+        assert_eq!(r.modules, 11 * 5 + 13);
+    }
+
+    #[test]
+    fn code128_mixed_content() {
+        // Start B + 7 chars + checksum + stop.
+        let r = generate_barcode("PJJ123C", BarcodeFormat::Code128, 0).unwrap();
+        assert_eq!(r.modules, 11 * 9 + 13);
+        assert!(generate_barcode("café", BarcodeFormat::Code128, 0).is_err());
+    }
+
+    #[test]
+    fn ean13_check_digit() {
+        let r = generate_barcode("400638133393", BarcodeFormat::Ean13, 0).unwrap();
+        assert_eq!(r.text, "4006381333931");
+        assert_eq!(r.modules, 95);
+        assert!(generate_barcode("4006381333932", BarcodeFormat::Ean13, 0).is_err());
+    }
+
+    #[test]
+    fn ean13_known_pattern() {
+        let bars = encode_ean13(&[5, 9, 0, 1, 2, 3, 4, 1, 2, 3, 4, 5, 7]);
+        let s: String = bars.iter().map(|b| if *b { '1' } else { '0' }).collect();
+        // Guard + first left digit (9, L code) + second (0, G code).
+        assert!(s.starts_with("10100010110100111"));
+        assert!(s.ends_with("1000100101"));
+    }
+
+    #[test]
+    fn upca_check_digit() {
+        let r = generate_barcode("03600029145", BarcodeFormat::UpcA, 0).unwrap();
+        assert_eq!(r.text, "036000291452");
+        assert_eq!(r.modules, 95);
+    }
+
+    #[test]
+    fn size_is_capped() {
+        let r = generate_barcode("123456", BarcodeFormat::Code128, u32::MAX).unwrap();
+        let width: u32 = r
+            .svg
+            .split("width=\"")
+            .nth(1)
+            .unwrap()
+            .split('"')
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((MAX_BARCODE_SIZE..MAX_BARCODE_SIZE * 2).contains(&width));
+        assert!(!r.png_base64.is_empty());
+    }
+}
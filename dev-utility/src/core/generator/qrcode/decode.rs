@@ -0,0 +1,103 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+use super::QrErrorCorrection;
+use crate::error::UtilityError;
+use serde::{Deserialize, Serialize};
+use universal_function_macro::universal_function;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct DecodedQrCode {
+    pub content: String,                     // Decoded payload
+    pub version: u32,                        // Symbol version (1-40)
+    pub error_correction: QrErrorCorrection, // ECC level read from format info
+    pub mask: u8,                            // Mask pattern (0-7)
+    pub corners: Vec<[i32; 2]>,              // Symbol corners in image pixels
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct QrDecodeResult {
+    pub codes: Vec<DecodedQrCode>, // Every symbol that decoded successfully
+    pub failed: u32,               // Symbols found but not decodable
+}
+
+/// Find and decode every QR code in a PNG or JPEG image.
+///
+/// Only regular QR symbols are recognised; Micro QR and linear barcodes
+/// are not decoded.
+#[universal_function]
+pub fn decode_qr_image(bytes: Vec<u8>) -> Result<QrDecodeResult, UtilityError> {
+    let gray = image::load_from_memory(&bytes)
+        .map_err(|e| UtilityError::DecodeError(format!("Cannot read image: {}", e)))?
+        .to_luma8();
+
+    let mut prepared = rqrr::PreparedImage::prepare(gray);
+    let mut codes = Vec::new();
+    let mut failed = 0;
+    for grid in prepared.detect_grids() {
+        match grid.decode() {
+            Ok((meta, content)) => codes.push(DecodedQrCode {
+                content,
+                version: meta.version.0 as u32,
+                // Format-info ECC bits: 01 = L, 00 = M, 11 = Q, 10 = H.
+                error_correction: match meta.ecc_level {
+                    1 => QrErrorCorrection::Low,
+                    3 => QrErrorCorrection::Quartile,
+                    2 => QrErrorCorrection::High,
+                    _ => QrErrorCorrection::Medium,
+                },
+                mask: meta.mask as u8,
+                corners: grid.bounds.iter().map(|p| [p.x, p.y]).collect(),
+            }),
+            Err(_) => failed += 1,
+        }
+    }
+
+    if codes.is_empty() && failed == 0 {
+        return Err(UtilityError::DecodeError(
+            "No QR code found in image".to_string(),
+        ));
+    }
+    Ok(QrDecodeResult { codes, failed })
+}
+
+/// Read a PNG or JPEG from disk and decode the QR codes in it.
+#[universal_function(desktop_only)]
+pub fn decode_qr_image_file(path: String) -> Result<QrDecodeResult, UtilityError> {
+    decode_qr_image(std::fs::read(&path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{generate_qr_code, QrErrorCorrection};
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+
+    #[test]
+    fn round_trips_generated_code() {
+        let image = generate_qr_code("WIFI:T:WPA;S:lab;P:secret;;", QrErrorCorrection::Quartile, 300)
+            .unwrap();
+        let png = BASE64.decode(image.png_base64).unwrap();
+
+        let result = decode_qr_image(png).unwrap();
+        assert_eq!(result.codes.len(), 1);
+        assert_eq!(result.codes[0].content, "WIFI:T:WPA;S:lab;P:secret;;");
+        assert_eq!(result.codes[0].error_correction, QrErrorCorrection::Quartile);
+    }
+
+    #[test]
+    fn rejects_non_image() {
+        assert!(decode_qr_image(b"not an image".to_vec()).is_err());
+    }
+}
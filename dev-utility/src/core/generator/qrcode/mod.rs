@@ -0,0 +1,346 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+pub mod barcode;
+pub use barcode::*;
+
+pub mod decode;
+pub use decode::*;
+
+pub mod payload;
+pub use payload::*;
+
+use crate::error::UtilityError;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use image::{codecs::png::PngEncoder, ImageEncoder, Luma};
+use qrcode::bits::Bits;
+use qrcode::canvas::{Canvas, MaskPattern};
+use qrcode::render::{svg, unicode, Renderer};
+use qrcode::{ec, Color, EcLevel, QrCode, Version};
+use serde::{Deserialize, Serialize};
+use universal_function_macro::universal_function;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum QrErrorCorrection {
+    Low,      // ~7% recovery
+    Medium,   // ~15% recovery
+    Quartile, // ~25% recovery
+    High,     // ~30% recovery
+}
+
+impl QrErrorCorrection {
+    pub(crate) fn to_ec_level(self) -> EcLevel {
+        match self {
+            QrErrorCorrection::Low => EcLevel::L,
+            QrErrorCorrection::Medium => EcLevel::M,
+            QrErrorCorrection::Quartile => EcLevel::Q,
+            QrErrorCorrection::High => EcLevel::H,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct QrCodeImage {
+    pub svg: String,        // Standalone SVG document
+    pub png_base64: String, // PNG bytes, Base64 encoded (no data: prefix)
+    pub terminal: String,   // Half-block rendering for terminals
+    pub modules: u32,       // Symbol width in modules, excluding quiet zone
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct QrCodeOptions {
+    pub error_correction: QrErrorCorrection,
+    pub micro: bool,          // Emit a Micro QR symbol (M1-M4) instead of QR
+    pub version: Option<u8>,  // 1-40 (QR) or 1-4 (Micro QR); None picks the smallest fit
+    pub mask: Option<u8>,     // 0-7 (QR) or 0-3 (Micro QR); None picks the lowest penalty
+    pub size: u32,            // Minimum edge length in pixels, capped at MAX_QR_SIZE
+}
+
+impl Default for QrCodeOptions {
+    fn default() -> Self {
+        Self {
+            error_correction: QrErrorCorrection::Medium,
+            micro: false,
+            version: None,
+            mask: None,
+            size: 256,
+        }
+    }
+}
+
+/// Render `data` as a QR code, entirely in-process.
+///
+/// `size` is the minimum edge length in pixels of the SVG and PNG outputs,
+/// capped at [`MAX_QR_SIZE`]; the actual size is rounded up to a whole number
+/// of pixels per module.
+#[universal_function]
+pub fn generate_qr_code(
+    data: &str,
+    error_correction: QrErrorCorrection,
+    size: u32,
+) -> Result<QrCodeImage, UtilityError> {
+    generate_qr_code_with_options(
+        data,
+        QrCodeOptions {
+            error_correction,
+            size,
+            ..Default::default()
+        },
+    )
+}
+
+/// Render `data` as a QR or Micro QR code with explicit version and mask
+/// control. Anything left unset falls back to the encoder's choice.
+#[universal_function]
+pub fn generate_qr_code_with_options(
+    data: &str,
+    options: QrCodeOptions,
+) -> Result<QrCodeImage, UtilityError> {
+    let ec_level = options.error_correction.to_ec_level();
+    let version = match options.version {
+        Some(v) => Some(parse_version(v, options.micro)?),
+        None if options.micro => Some(smallest_micro_version(data.as_bytes(), ec_level)?),
+        None => None,
+    };
+
+    let (colors, version) = match (version, options.mask) {
+        (None, None) => {
+            let code = QrCode::with_error_correction_level(data.as_bytes(), ec_level)
+                .map_err(encode_error)?;
+            (code.to_colors(), code.version())
+        }
+        (version, mask) => {
+            let version = match version {
+                Some(v) => v,
+                None => QrCode::with_error_correction_level(data.as_bytes(), ec_level)
+                    .map_err(encode_error)?
+                    .version(),
+            };
+            (encode_with_mask(data.as_bytes(), version, ec_level, mask)?, version)
+        }
+    };
+
+    render_modules(&colors, version, options.size)
+}
+
+fn parse_version(version: u8, micro: bool) -> Result<Version, UtilityError> {
+    match (micro, version) {
+        (false, 1..=40) => Ok(Version::Normal(version as i16)),
+        (true, 1..=4) => Ok(Version::Micro(version as i16)),
+        (false, _) => Err(UtilityError::InvalidInput(
+            "QR version must be between 1 and 40".to_string(),
+        )),
+        (true, _) => Err(UtilityError::InvalidInput(
+            "Micro QR version must be between 1 and 4".to_string(),
+        )),
+    }
+}
+
+fn smallest_micro_version(data: &[u8], ec_level: EcLevel) -> Result<Version, UtilityError> {
+    (1..=4)
+        .map(Version::Micro)
+        .find(|v| QrCode::with_version(data, *v, ec_level).is_ok())
+        .ok_or_else(|| {
+            UtilityError::InvalidInput(
+                "Data does not fit in any Micro QR version at this error correction level"
+                    .to_string(),
+            )
+        })
+}
+
+fn mask_pattern(mask: u8, version: Version) -> Result<MaskPattern, UtilityError> {
+    // Micro QR only defines four of the eight QR masks, numbered 0-3.
+    let pattern = match (version.is_micro(), mask) {
+        (false, 0) => MaskPattern::Checkerboard,
+        (false, 1) | (true, 0) => MaskPattern::HorizontalLines,
+        (false, 2) => MaskPattern::VerticalLines,
+        (false, 3) => MaskPattern::DiagonalLines,
+        (false, 4) | (true, 1) => MaskPattern::LargeCheckerboard,
+        (false, 5) => MaskPattern::Fields,
+        (false, 6) | (true, 2) => MaskPattern::Diamonds,
+        (false, 7) | (true, 3) => MaskPattern::Meadow,
+        (micro, _) => {
+            return Err(UtilityError::InvalidInput(format!(
+                "Mask must be between 0 and {}",
+                if micro { 3 } else { 7 }
+            )))
+        }
+    };
+    Ok(pattern)
+}
+
+fn encode_with_mask(
+    data: &[u8],
+    version: Version,
+    ec_level: EcLevel,
+    mask: Option<u8>,
+) -> Result<Vec<Color>, UtilityError> {
+    let mut bits = Bits::new(version);
+    bits.push_optimal_data(data).map_err(encode_error)?;
+    bits.push_terminator(ec_level).map_err(encode_error)?;
+    let codewords = bits.into_bytes();
+    let (encoded, ec_data) =
+        ec::construct_codewords(&codewords, version, ec_level).map_err(encode_error)?;
+
+    let mut canvas = Canvas::new(version, ec_level);
+    canvas.draw_all_functional_patterns();
+    canvas.draw_data(&encoded, &ec_data);
+    let canvas = match mask {
+        Some(m) => {
+            canvas.apply_mask(mask_pattern(m, version)?);
+            canvas
+        }
+        None => canvas.apply_best_mask(),
+    };
+    Ok(canvas.into_colors())
+}
+
+fn encode_error(e: qrcode::types::QrError) -> UtilityError {
+    UtilityError::InvalidInput(format!("Cannot encode QR code: {}", e))
+}
+
+/// Largest edge `render_modules` will render; the PNG is a full bitmap, so
+/// this keeps a stray size from allocating gigabytes.
+pub const MAX_QR_SIZE: u32 = 4096;
+
+fn render_modules(
+    colors: &[Color],
+    version: Version,
+    size: u32,
+) -> Result<QrCodeImage, UtilityError> {
+    let size = size.min(MAX_QR_SIZE);
+    let width = version.width() as usize;
+    let quiet_zone = if version.is_micro() { 2 } else { 4 };
+
+    let svg = Renderer::<svg::Color>::new(colors, width, quiet_zone)
+        .min_dimensions(size, size)
+        .dark_color(svg::Color("#000000"))
+        .light_color(svg::Color("#ffffff"))
+        .build();
+
+    let bitmap = Renderer::<Luma<u8>>::new(colors, width, quiet_zone)
+        .min_dimensions(size, size)
+        .build();
+    let png_base64 = encode_png(bitmap.as_raw(), bitmap.width(), bitmap.height())?;
+
+    // Terminals usually render light-on-dark, so invert the palette to keep
+    // the symbol scannable straight off the screen.
+    let terminal = Renderer::<unicode::Dense1x2>::new(colors, width, quiet_zone)
+        .dark_color(unicode::Dense1x2::Light)
+        .light_color(unicode::Dense1x2::Dark)
+        .build();
+
+    Ok(QrCodeImage {
+        svg,
+        png_base64,
+        terminal,
+        modules: width as u32,
+    })
+}
+
+/// Encode an 8-bit grayscale buffer as PNG and return it Base64 encoded.
+pub(crate) fn encode_png(luma: &[u8], width: u32, height: u32) -> Result<String, UtilityError> {
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png)
+        .write_image(luma, width, height, image::ExtendedColorType::L8)
+        .map_err(|e| UtilityError::Runtime(format!("PNG encode error: {}", e)))?;
+    Ok(BASE64.encode(png))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_all_formats() {
+        let r = generate_qr_code("hello", QrErrorCorrection::Medium, 200).unwrap();
+        assert!(r.svg.contains("<svg"));
+        assert_eq!(r.modules, 21);
+        assert!(!r.terminal.is_empty());
+
+        let png = BASE64.decode(&r.png_base64).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    }
+
+    #[test]
+    fn higher_correction_needs_larger_symbol() {
+        let data = "otpauth://totp/Example:alice@example.com?secret=JBSWY3DPEHPK3PXP";
+        let low = generate_qr_code(data, QrErrorCorrection::Low, 0).unwrap();
+        let high = generate_qr_code(data, QrErrorCorrection::High, 0).unwrap();
+        assert!(high.modules > low.modules);
+    }
+
+    #[test]
+    fn explicit_version_and_mask() {
+        let options = QrCodeOptions {
+            version: Some(5),
+            mask: Some(3),
+            ..Default::default()
+        };
+        let r = generate_qr_code_with_options("hello", options).unwrap();
+        assert_eq!(r.modules, 37);
+    }
+
+    #[test]
+    fn micro_qr_picks_smallest_version() {
+        let options = QrCodeOptions {
+            error_correction: QrErrorCorrection::Low,
+            micro: true,
+            ..Default::default()
+        };
+        let r = generate_qr_code_with_options("12345", options).unwrap();
+        assert_eq!(r.modules, 11);
+    }
+
+    #[test]
+    fn rejects_out_of_range_options() {
+        let bad_version = QrCodeOptions {
+            version: Some(41),
+            ..Default::default()
+        };
+        assert!(generate_qr_code_with_options("x", bad_version).is_err());
+
+        let bad_mask = QrCodeOptions {
+            micro: true,
+            mask: Some(4),
+            error_correction: QrErrorCorrection::Low,
+            ..Default::default()
+        };
+        assert!(generate_qr_code_with_options("1", bad_mask).is_err());
+    }
+
+    #[test]
+    fn size_is_capped() {
+        let options = QrCodeOptions {
+            size: u32::MAX,
+            ..Default::default()
+        };
+        let r = generate_qr_code_with_options("x", options).unwrap();
+        let width: u32 = r
+            .svg
+            .split("width=\"")
+            .nth(1)
+            .unwrap()
+            .split('"')
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((MAX_QR_SIZE..MAX_QR_SIZE * 2).contains(&width));
+        assert!(!r.png_base64.is_empty());
+    }
+}
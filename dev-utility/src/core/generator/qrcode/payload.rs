@@ -0,0 +1,209 @@
+// Copyright (c) 2023-2026, AprilNEA LLC.
+//
+// Dual licensed under:
+// - GPL-3.0 (open source)
+// - Commercial license (contact us)
+//
+// See LICENSE file for details or contact admin@aprilnea.com
+
+use serde::{Deserialize, Serialize};
+use universal_function_macro::universal_function;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum WifiSecurity {
+    Wpa, // WPA/WPA2/WPA3 personal
+    Wep,
+    Nopass,
+}
+
+impl WifiSecurity {
+    fn as_str(self) -> &'static str {
+        match self {
+            WifiSecurity::Wpa => "WPA",
+            WifiSecurity::Wep => "WEP",
+            WifiSecurity::Nopass => "nopass",
+        }
+    }
+}
+
+/// Structured content that scanners recognise and act on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum QrPayload {
+    Text {
+        text: String,
+    },
+    Wifi {
+        ssid: String,
+        password: String,
+        security: WifiSecurity,
+        hidden: bool,
+    },
+    MeCard {
+        name: String,
+        phone: Option<String>,
+        email: Option<String>,
+        url: Option<String>,
+        address: Option<String>,
+        note: Option<String>,
+    },
+    Geo {
+        latitude: f64,
+        longitude: f64,
+        query: Option<String>,
+    },
+    Mailto {
+        to: String,
+        subject: Option<String>,
+        body: Option<String>,
+    },
+}
+
+/// Serialize a structured payload into the string a QR code should carry.
+#[universal_function]
+pub fn build_qr_payload(payload: QrPayload) -> String {
+    match payload {
+        QrPayload::Text { text } => text,
+        QrPayload::Wifi {
+            ssid,
+            password,
+            security,
+            hidden,
+        } => {
+            let mut out = format!("WIFI:T:{};S:{};", security.as_str(), escape_field(&ssid));
+            if security != WifiSecurity::Nopass {
+                out.push_str(&format!("P:{};", escape_field(&password)));
+            }
+            if hidden {
+                out.push_str("H:true;");
+            }
+            out.push(';');
+            out
+        }
+        QrPayload::MeCard {
+            name,
+            phone,
+            email,
+            url,
+            address,
+            note,
+        } => {
+            let mut out = format!("MECARD:N:{};", escape_field(&name));
+            for (key, value) in [
+                ("TEL", phone),
+                ("EMAIL", email),
+                ("URL", url),
+                ("ADR", address),
+                ("NOTE", note),
+            ] {
+                if let Some(v) = value.filter(|v| !v.is_empty()) {
+                    out.push_str(&format!("{}:{};", key, escape_field(&v)));
+                }
+            }
+            out.push(';');
+            out
+        }
+        QrPayload::Geo {
+            latitude,
+            longitude,
+            query,
+        } => match query.filter(|q| !q.is_empty()) {
+            Some(q) => format!("geo:{},{}?q={}", latitude, longitude, urlencoding::encode(&q)),
+            None => format!("geo:{},{}", latitude, longitude),
+        },
+        QrPayload::Mailto { to, subject, body } => {
+            let params: Vec<String> = [("subject", subject), ("body", body)]
+                .into_iter()
+                .filter_map(|(k, v)| {
+                    v.filter(|v| !v.is_empty())
+                        .map(|v| format!("{}={}", k, urlencoding::encode(&v)))
+                })
+                .collect();
+            if params.is_empty() {
+                format!("mailto:{}", to)
+            } else {
+                format!("mailto:{}?{}", to, params.join("&"))
+            }
+        }
+    }
+}
+
+/// Backslash-escape the characters that delimit WIFI:/MECARD: fields.
+fn escape_field(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wifi_escapes_delimiters() {
+        let payload = QrPayload::Wifi {
+            ssid: "Cafe;Guest".to_string(),
+            password: "p:ss".to_string(),
+            security: WifiSecurity::Wpa,
+            hidden: true,
+        };
+        assert_eq!(
+            build_qr_payload(payload),
+            "WIFI:T:WPA;S:Cafe\\;Guest;P:p\\:ss;H:true;;"
+        );
+    }
+
+    #[test]
+    fn open_wifi_omits_password() {
+        let payload = QrPayload::Wifi {
+            ssid: "Open".to_string(),
+            password: "ignored".to_string(),
+            security: WifiSecurity::Nopass,
+            hidden: false,
+        };
+        assert_eq!(build_qr_payload(payload), "WIFI:T:nopass;S:Open;;");
+    }
+
+    #[test]
+    fn mecard_skips_empty_fields() {
+        let payload = QrPayload::MeCard {
+            name: "Doe,John".to_string(),
+            phone: Some("+1234".to_string()),
+            email: Some(String::new()),
+            url: None,
+            address: None,
+            note: None,
+        };
+        assert_eq!(build_qr_payload(payload), "MECARD:N:Doe\\,John;TEL:+1234;;");
+    }
+
+    #[test]
+    fn geo_and_mailto() {
+        let geo = QrPayload::Geo {
+            latitude: 37.7749,
+            longitude: -122.4194,
+            query: Some("San Francisco".to_string()),
+        };
+        assert_eq!(
+            build_qr_payload(geo),
+            "geo:37.7749,-122.4194?q=San%20Francisco"
+        );
+
+        let mail = QrPayload::Mailto {
+            to: "a@example.com".to_string(),
+            subject: Some("Hi there".to_string()),
+            body: None,
+        };
+        assert_eq!(build_qr_payload(mail), "mailto:a@example.com?subject=Hi%20there");
+    }
+}
@@ -11,6 +11,9 @@
 //
 // See LICENSE file for details or contact admin@aprilnea.com
 
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
 #[derive(Debug, thiserror::Error)]
 pub enum UtilityError {
     #[error(transparent)]
@@ -23,6 +26,148 @@ pub enum UtilityError {
     ParseError(String),
     #[error("Invalid input: {0}")]
     InvalidInput(String),
+    #[error("Parse error: {0}")]
+    Diagnostic(Diagnostic),
+}
+
+impl UtilityError {
+    /// The source location of the failure, when the producer knows it.
+    pub fn diagnostic(&self) -> Option<&Diagnostic> {
+        match self {
+            UtilityError::Diagnostic(diagnostic) => Some(diagnostic),
+            _ => None,
+        }
+    }
+}
+
+impl From<Diagnostic> for UtilityError {
+    fn from(diagnostic: Diagnostic) -> Self {
+        UtilityError::Diagnostic(diagnostic)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// A message pinned to a span of the input, so editors can underline it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(target_arch = "wasm32", derive(tsify::Tsify))]
+#[cfg_attr(target_arch = "wasm32", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct Diagnostic {
+    pub message: String,
+    pub offset: usize, // Byte offset into the input
+    pub line: usize,   // 1-based
+    pub column: usize, // 1-based, counted in characters
+    pub length: usize, // Span length in bytes, 0 at end of input
+    pub severity: Severity,
+    pub hint: Option<String>, // Suggested fix, if any
+}
+
+impl Diagnostic {
+    /// An error at byte `offset` of `source`, spanning one character.
+    ///
+    /// Offsets past the end or inside a UTF-8 sequence are clamped back to
+    /// the nearest character boundary.
+    pub fn new(source: &str, offset: usize, message: impl Into<String>) -> Self {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            message: message.into(),
+            offset,
+            line: source[..offset].matches('\n').count() + 1,
+            column: source[line_start..offset].chars().count() + 1,
+            length: source[offset..].chars().next().map_or(0, char::len_utf8),
+            severity: Severity::Error,
+            hint: None,
+        }
+    }
+
+    /// An error at a 1-based line and 1-based byte column, the convention
+    /// used by `serde_json` and most other parsers.
+    pub fn at_line_column(
+        source: &str,
+        line: usize,
+        column: usize,
+        message: impl Into<String>,
+    ) -> Self {
+        let line_start = line_start(source, line.saturating_sub(1));
+        Self::new(source, line_start + column.saturating_sub(1), message)
+    }
+
+    pub fn with_length(mut self, length: usize) -> Self {
+        self.length = length;
+        self
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// The full line of `source` the diagnostic points into, without its
+    /// line break.
+    pub fn source_line<'a>(&self, source: &'a str) -> &'a str {
+        let start = line_start(source, self.line - 1).min(source.len());
+        let line = &source[start..];
+        let line = line.split('\n').next().unwrap_or_default();
+        line.strip_suffix('\r').unwrap_or(line)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+/// Byte offset where the 0-based `line` begins, or the input length if the
+/// input has fewer lines.
+pub(crate) fn line_start(source: &str, line: usize) -> usize {
+    if line == 0 {
+        return 0;
+    }
+    source
+        .match_indices('\n')
+        .nth(line - 1)
+        .map_or(source.len(), |(i, _)| i + 1)
+}
+
+/// Byte offset of a 1-based line and 1-based column counted in characters,
+/// the convention used by pest-based parsers.
+pub(crate) fn char_offset(source: &str, line: usize, column: usize) -> usize {
+    let start = line_start(source, line.saturating_sub(1));
+    source[start..]
+        .char_indices()
+        .nth(column.saturating_sub(1))
+        .map_or(source.len(), |(i, _)| start + i)
+}
+
+/// Drop the " at line X column Y" suffix parsers append to their messages,
+/// since the diagnostic carries the position itself.
+pub(crate) fn strip_location(message: &str) -> &str {
+    message
+        .split_once(" at line ")
+        .map_or(message, |(message, _)| message)
 }
 
 impl serde::Serialize for UtilityError {
@@ -30,13 +175,84 @@ impl serde::Serialize for UtilityError {
     where
         S: serde::ser::Serializer,
     {
-        serializer.serialize_str(self.to_string().as_ref())
+        match self {
+            // Located errors cross the boundary as `{ message, diagnostic }`
+            // so the frontend can point at the input.
+            UtilityError::Diagnostic(diagnostic) => {
+                use serde::ser::SerializeStruct;
+                let mut state = serializer.serialize_struct("UtilityError", 2)?;
+                state.serialize_field("message", &self.to_string())?;
+                state.serialize_field("diagnostic", diagnostic)?;
+                state.end()
+            }
+            _ => serializer.serialize_str(self.to_string().as_ref()),
+        }
     }
 }
 
 #[cfg(target_arch = "wasm32")]
 impl From<UtilityError> for wasm_bindgen::prelude::JsValue {
     fn from(err: UtilityError) -> wasm_bindgen::prelude::JsValue {
+        if err.diagnostic().is_some() {
+            if let Ok(value) = serde_wasm_bindgen::to_value(&err) {
+                return value;
+            }
+        }
         wasm_bindgen::prelude::JsValue::from_str(&err.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locates_offsets_in_characters() {
+        let source = "first\nsé x\r\nlast";
+        let diagnostic = Diagnostic::new(source, 10, "bad");
+        assert_eq!((diagnostic.line, diagnostic.column), (2, 4));
+        assert_eq!(diagnostic.length, 1);
+        assert_eq!(diagnostic.source_line(source), "sé x");
+
+        // Inside "é" snaps back to its first byte; past the end clamps.
+        assert_eq!(Diagnostic::new(source, 8, "bad").offset, 7);
+        let end = Diagnostic::new(source, 99, "bad");
+        assert_eq!((end.line, end.column, end.length), (3, 5, 0));
+    }
+
+    #[test]
+    fn converts_line_columns() {
+        let source = "a\nbc\nd";
+        assert_eq!(Diagnostic::at_line_column(source, 2, 2, "x").offset, 3);
+        assert_eq!(Diagnostic::at_line_column(source, 9, 1, "x").offset, 6);
+        assert_eq!(char_offset("é\néa", 2, 2), 5);
+        assert_eq!(
+            strip_location("expected value at line 1 column 2"),
+            "expected value"
+        );
+    }
+
+    #[test]
+    fn serializes_located_errors_as_objects() {
+        let error: UtilityError = Diagnostic::new("{]", 1, "expected value")
+            .with_hint("Remove it")
+            .into();
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "message": "Parse error: expected value at line 1, column 2",
+                "diagnostic": {
+                    "message": "expected value",
+                    "offset": 1,
+                    "line": 1,
+                    "column": 2,
+                    "length": 1,
+                    "severity": "error",
+                    "hint": "Remove it",
+                },
+            })
+        );
+        let plain = UtilityError::InvalidInput("nope".to_string());
+        assert_eq!(serde_json::to_value(&plain).unwrap(), "Invalid input: nope");
+    }
+}
@@ -46,7 +46,7 @@ import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs";
 import { copyToClipboard } from "@/lib/copyboard";
 import { useUtilityInvoke } from "@/utilities/invoke";
 import { InvokeFunction } from "@/utilities/types";
-import { TotpHashAlgorithm } from "./types";
+import { QrErrorCorrection, TotpHashAlgorithm } from "./types";
 
 enum TotpDebuggerTab {
   Create = "create",
@@ -127,6 +127,11 @@ const CreateSecret = () => {
       period,
       image: image || undefined,
       addIssuerPrefix,
+      qr: {
+        errorCorrection: QrErrorCorrection.Medium,
+        micro: false,
+        size: 192,
+      },
     });
   };
 
@@ -342,7 +347,11 @@ const CreateSecret = () => {
             <Label>{t(msg`QR Code`)}</Label>
             <div className="flex justify-center">
               <img
-                src={totpSecret?.qrCodeUrl}
+                src={
+                  totpSecret
+                    ? `data:image/png;base64,${totpSecret.qrCode.pngBase64}`
+                    : undefined
+                }
                 alt={t(msg`TOTP QR Code`)}
                 className="w-48 h-48 border rounded-lg"
               />
@@ -13,11 +13,34 @@ export type TotpGenerateSecretParams = {
   period: number;
   image?: string;
   addIssuerPrefix: boolean;
+  qr?: QrCodeOptions;
+};
+
+export enum QrErrorCorrection {
+  Low = "low",
+  Medium = "medium",
+  Quartile = "quartile",
+  High = "high",
+}
+
+export type QrCodeOptions = {
+  errorCorrection: QrErrorCorrection;
+  micro: boolean;
+  version?: number;
+  mask?: number;
+  size: number;
+};
+
+export type QrCodeImage = {
+  svg: string;
+  pngBase64: string;
+  terminal: string;
+  modules: number;
 };
 
 export type TotpSecretResult = {
   secret: string;
-  qrCodeUrl: string;
+  qrCode: QrCodeImage;
   provisioningUri: string;
 };
 
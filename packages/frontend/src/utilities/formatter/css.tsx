@@ -49,11 +49,12 @@ export default function CssBeautifyMinifyTool() {
   const debouncedInput = useDebouncedValue(input, 100, false);
 
   const handleFormat = useCallback(
-    async (input: string, _style: IndentStyle) => {
+    async (input: string, style: IndentStyle) => {
       const result = await trigger({
         input,
+        style,
       });
-      setOutput(result);
+      setOutput(result.code);
     },
     [trigger],
   );
@@ -49,7 +49,7 @@ import {
   TooltipContent,
   TooltipTrigger,
 } from "@/components/ui/tooltip";
-import { useUtilityInvoke } from "@/utilities/invoke";
+import { invokeErrorMessage, useUtilityInvoke } from "@/utilities/invoke";
 import { type IndentStyle, IndentStyleEnum, InvokeFunction } from "../types";
 
 const jsonExampleInput = `{
@@ -83,7 +83,12 @@ const jsonExampleInput = `{
 }`;
 
 export default function JsonFormatterPage() {
-  const { trigger, error } = useUtilityInvoke(InvokeFunction.FormatJson);
+  const { trigger: format, error: formatError } = useUtilityInvoke(
+    InvokeFunction.FormatJson,
+  );
+  const { trigger: repair, error: repairError } = useUtilityInvoke(
+    InvokeFunction.RepairJson,
+  );
   const [input, setInput] = useState(jsonExampleInput);
   const [output, setOutput] = useState("");
 
@@ -101,19 +106,28 @@ export default function JsonFormatterPage() {
   const debouncedInput = useDebouncedValue(input, 100, false);
 
   const handleFormat = useCallback(
-    async (input: string, style: IndentStyle) => {
-      const result = await trigger({
-        input,
-        style,
-      });
-      setOutput(result);
+    async (
+      input: string,
+      style: IndentStyle,
+      sortKeys: boolean,
+      autoRepair: boolean,
+    ) => {
+      const args = { input, style, options: { sortKeys } };
+      if (autoRepair) {
+        const result = await repair(args);
+        setOutput(result.output);
+      } else {
+        setOutput(await format(args));
+      }
     },
-    [trigger],
+    [format, repair],
   );
 
   useEffect(() => {
-    handleFormat(debouncedInput, style);
-  }, [handleFormat, debouncedInput, style]);
+    handleFormat(debouncedInput, style, sortKeys, autoRepair);
+  }, [handleFormat, debouncedInput, style, sortKeys, autoRepair]);
+
+  const error = autoRepair ? repairError : formatError;
 
   const handleResetSettings = () => {
     setAutoDetect(true);
@@ -385,7 +399,7 @@ export default function JsonFormatterPage() {
   const inputBottombar = error ? (
     <div className="flex items-center gap-2 mt-2">
       <Callout variant="error" className="w-full">
-        {String(error)}
+        {invokeErrorMessage(error)}
       </Callout>
     </div>
   ) : null;
@@ -21,11 +21,63 @@ const wasmFunctions: Partial<WasmFunctions> = {
   [InvokeFunction.GenerateUuidV7]: (args) => wasm.generate_uuid_v7(args.count),
   [InvokeFunction.AnalyzeUuid]: (args) => wasm.analyze_uuid(args.input),
   [InvokeFunction.FormatJson]: (args) =>
-    wasm.format_json(args.input, args.style),
-  [InvokeFunction.FormatCss]: (args) => wasm.format_css(args.input),
+    wasm.format_json(args.input, args.style, args.options),
+  [InvokeFunction.RepairJson]: (args) =>
+    wasm.repair_json(args.input, args.style, args.options),
+  [InvokeFunction.DiffJson]: (args) =>
+    wasm.diff_json(args.left, args.right, args.options),
+  [InvokeFunction.ApplyJsonPatch]: (args) =>
+    wasm.apply_json_patch(args.document, args.patch, args.format, args.style),
+  [InvokeFunction.ValidateJsonSchema]: (args) =>
+    wasm.validate_json_schema(args.document, args.schema, args.options),
+  [InvokeFunction.InferJsonSchema]: (args) =>
+    wasm.infer_json_schema(args.input, args.options),
+  [InvokeFunction.FormatCss]: (args) =>
+    wasm.format_css(args.input, args.style, args.options),
+  [InvokeFunction.FormatHtml]: (args) =>
+    wasm.format_html(args.input, args.style, args.options),
+  [InvokeFunction.FormatXml]: (args) =>
+    wasm.format_xml(args.input, args.style, args.options),
+  [InvokeFunction.QueryXml]: (args) =>
+    wasm.query_xml(args.input, args.expression),
+  [InvokeFunction.CanonicalizeXml]: (args) =>
+    wasm.canonicalize_xml(args.input, args.method, args.options),
+  [InvokeFunction.FormatSql]: (args) =>
+    wasm.format_sql(args.input, args.dialect, args.style, args.options),
+  [InvokeFunction.RenderMarkdown]: (args) =>
+    wasm.render_markdown(args.input, args.options),
+  [InvokeFunction.MarkdownToc]: (args) => wasm.markdown_toc(args.input),
+  [InvokeFunction.HtmlToMarkdown]: (args) => wasm.html_to_markdown(args.input),
+  [InvokeFunction.FormatMarkdownTables]: (args) =>
+    wasm.format_markdown_tables(args.input),
+  [InvokeFunction.FormatYaml]: (args) =>
+    wasm.format_yaml(args.input, args.options),
+  [InvokeFunction.FormatToml]: (args) =>
+    wasm.format_toml(args.input, args.options),
+  [InvokeFunction.DiffText]: (args) =>
+    wasm.diff_text(args.left, args.right, args.options),
+  [InvokeFunction.ApplyTextPatch]: (args) =>
+    wasm.apply_text_patch(args.input, args.patch),
+  [InvokeFunction.TestRegex]: (args) =>
+    wasm.test_regex(args.pattern, args.text, args.options),
+  [InvokeFunction.ReplaceRegex]: (args) =>
+    wasm.replace_regex(args.pattern, args.text, args.replacement, args.options),
+  [InvokeFunction.ExplainRegex]: (args) =>
+    wasm.explain_regex(args.pattern, args.options),
+  [InvokeFunction.TransformText]: (args) =>
+    wasm.transform_text(args.input, args.steps),
+  [InvokeFunction.ConvertCase]: (args) =>
+    wasm.convert_case(args.input, args.case, args.preserveAcronyms),
+  [InvokeFunction.GenerateCode]: (args) =>
+    wasm.generate_code(args.input, args.language, args.options),
   // [InvokeFunction.GenerateHashes]: (args) => wasm.generate_hashes(args.input),
   [InvokeFunction.EncodeBase64]: (args) => wasm.encode_base64(args.input),
   [InvokeFunction.DecodeBase64]: (args) => wasm.decode_base64(args.input),
+  [InvokeFunction.DecodeText]: (args) =>
+    wasm.decode_text(args.input, args.format, args.encoding),
+  [InvokeFunction.EncodeText]: (args) =>
+    wasm.encode_text(args.input, args.encoding, args.bom),
+  [InvokeFunction.FixMojibake]: (args) => wasm.fix_mojibake(args.input),
   [InvokeFunction.GenerateTotpSecret]: (args) =>
     wasm.generate_totp_secret(
       args.issuer,
@@ -34,7 +86,8 @@ const wasmFunctions: Partial<WasmFunctions> = {
       args.digits,
       args.period,
       args.image,
-      args.add_issuer_prefix,
+      args.addIssuerPrefix,
+      args.qr,
     ),
   [InvokeFunction.GenerateTotpCode]: (args) =>
     wasm.generate_totp_code(
@@ -34,12 +34,52 @@ import type {
 import type { GenerateUuidV1Params, UuidNamespace } from "./generators/id";
 import {
   type Base64Engine,
+  type ByteFormat,
+  type C14nMethod,
+  type C14nOptions,
+  type CodegenLanguage,
+  type CodegenOptions,
+  type CssFormatOptions,
+  type CssFormatResult,
+  type DecodedText,
+  type Diagnostic,
+  type EncodedText,
   type HashResult,
+  type HtmlFormatOptions,
+  type HtmlFormatResult,
   type HidDeviceInfo,
   type IndentStyle,
   InvokeFunction,
+  type JsonDiffOptions,
+  type JsonDiffResult,
+  type JsonFormatOptions,
+  type JsonRepairResult,
   type JwtAlgorithm,
   type JwtDecodeResult,
+  type MarkdownOptions,
+  type MojibakeFix,
+  type PatchFormat,
+  type RegexOptions,
+  type RegexReplaceResult,
+  type RegexTestResult,
+  type RegexToken,
+  type SchemaInferOptions,
+  type SchemaValidateOptions,
+  type SchemaValidationResult,
+  type SqlDialect,
+  type SqlFormatOptions,
+  type TextCase,
+  type TextDiffOptions,
+  type TextDiffResult,
+  type TextEncoding,
+  type TextStep,
+  type TocEntry,
+  type TomlFormatOptions,
+  type TomlFormatResult,
+  type XmlFormatOptions,
+  type XmlQueryResult,
+  type YamlFormatOptions,
+  type YamlFormatResult,
   type RsaKeyAnalysis,
   type RsaKeyPair,
 } from "./types";
@@ -61,11 +101,136 @@ export interface UtilitiesArgs {
   };
   [InvokeFunction.GenerateUuidV7]: { count: number; timestamp?: number };
   [InvokeFunction.AnalyzeUuid]: { input: string };
-  [InvokeFunction.FormatJson]: { input: string; style: IndentStyle };
-  [InvokeFunction.FormatCss]: { input: string };
+  [InvokeFunction.FormatJson]: {
+    input: string;
+    style: IndentStyle;
+    options?: JsonFormatOptions;
+  };
+  [InvokeFunction.RepairJson]: {
+    input: string;
+    style: IndentStyle;
+    options?: JsonFormatOptions;
+  };
+  [InvokeFunction.DiffJson]: {
+    left: string;
+    right: string;
+    options?: JsonDiffOptions;
+  };
+  [InvokeFunction.ApplyJsonPatch]: {
+    document: string;
+    patch: string;
+    format: PatchFormat;
+    style: IndentStyle;
+  };
+  [InvokeFunction.ValidateJsonSchema]: {
+    document: string;
+    schema: string;
+    options?: SchemaValidateOptions;
+  };
+  [InvokeFunction.InferJsonSchema]: {
+    input: string;
+    options?: SchemaInferOptions;
+  };
+  [InvokeFunction.FormatCss]: {
+    input: string;
+    style: IndentStyle;
+    options?: CssFormatOptions;
+  };
+  [InvokeFunction.FormatHtml]: {
+    input: string;
+    style: IndentStyle;
+    options?: HtmlFormatOptions;
+  };
+  [InvokeFunction.FormatXml]: {
+    input: string;
+    style: IndentStyle;
+    options?: XmlFormatOptions;
+  };
+  [InvokeFunction.QueryXml]: { input: string; expression: string };
+  [InvokeFunction.CanonicalizeXml]: {
+    input: string;
+    method: C14nMethod;
+    options?: C14nOptions;
+  };
+  [InvokeFunction.FormatSql]: {
+    input: string;
+    dialect: SqlDialect;
+    style: IndentStyle;
+    options?: SqlFormatOptions;
+  };
+  [InvokeFunction.RenderMarkdown]: {
+    input: string;
+    options?: MarkdownOptions;
+  };
+  [InvokeFunction.MarkdownToc]: {
+    input: string;
+  };
+  [InvokeFunction.HtmlToMarkdown]: {
+    input: string;
+  };
+  [InvokeFunction.FormatMarkdownTables]: {
+    input: string;
+  };
+  [InvokeFunction.FormatYaml]: {
+    input: string;
+    options?: YamlFormatOptions;
+  };
+  [InvokeFunction.FormatToml]: {
+    input: string;
+    options?: TomlFormatOptions;
+  };
+  [InvokeFunction.DiffText]: {
+    left: string;
+    right: string;
+    options?: TextDiffOptions;
+  };
+  [InvokeFunction.ApplyTextPatch]: {
+    input: string;
+    patch: string;
+  };
+  [InvokeFunction.TestRegex]: {
+    pattern: string;
+    text: string;
+    options?: RegexOptions;
+  };
+  [InvokeFunction.ReplaceRegex]: {
+    pattern: string;
+    text: string;
+    replacement: string;
+    options?: RegexOptions;
+  };
+  [InvokeFunction.ExplainRegex]: {
+    pattern: string;
+    options?: RegexOptions;
+  };
+  [InvokeFunction.TransformText]: {
+    input: string;
+    steps: TextStep[];
+  };
+  [InvokeFunction.ConvertCase]: {
+    input: string;
+    case: TextCase;
+    preserveAcronyms: boolean;
+  };
+  [InvokeFunction.GenerateCode]: {
+    input: string;
+    language: CodegenLanguage;
+    options?: CodegenOptions;
+  };
   [InvokeFunction.GenerateHashes]: { input: string };
   [InvokeFunction.EncodeBase64]: { input: string };
   [InvokeFunction.DecodeBase64]: { input: string; engine: Base64Engine };
+  [InvokeFunction.DecodeText]: {
+    input: string;
+    format: ByteFormat;
+    encoding?: TextEncoding;
+  };
+  [InvokeFunction.EncodeText]: {
+    input: string;
+    encoding: TextEncoding;
+    bom: boolean;
+  };
+  [InvokeFunction.FixMojibake]: { input: string };
   [InvokeFunction.DecodeJwt]: {
     input: string;
     algorithm: JwtAlgorithm;
@@ -89,10 +254,37 @@ export interface UtilitiesReturns {
   [InvokeFunction.GenerateUuidV7]: string;
   [InvokeFunction.AnalyzeUuid]: any;
   [InvokeFunction.FormatJson]: string;
-  [InvokeFunction.FormatCss]: string;
+  [InvokeFunction.RepairJson]: JsonRepairResult;
+  [InvokeFunction.DiffJson]: JsonDiffResult;
+  [InvokeFunction.ApplyJsonPatch]: string;
+  [InvokeFunction.ValidateJsonSchema]: SchemaValidationResult;
+  [InvokeFunction.InferJsonSchema]: string;
+  [InvokeFunction.FormatCss]: CssFormatResult;
+  [InvokeFunction.FormatHtml]: HtmlFormatResult;
+  [InvokeFunction.FormatXml]: string;
+  [InvokeFunction.QueryXml]: XmlQueryResult;
+  [InvokeFunction.CanonicalizeXml]: string;
+  [InvokeFunction.FormatSql]: string;
+  [InvokeFunction.RenderMarkdown]: string;
+  [InvokeFunction.MarkdownToc]: TocEntry[];
+  [InvokeFunction.HtmlToMarkdown]: string;
+  [InvokeFunction.FormatMarkdownTables]: string;
+  [InvokeFunction.FormatYaml]: YamlFormatResult;
+  [InvokeFunction.FormatToml]: TomlFormatResult;
+  [InvokeFunction.DiffText]: TextDiffResult;
+  [InvokeFunction.ApplyTextPatch]: string;
+  [InvokeFunction.TestRegex]: RegexTestResult;
+  [InvokeFunction.ReplaceRegex]: RegexReplaceResult;
+  [InvokeFunction.ExplainRegex]: RegexToken[];
+  [InvokeFunction.TransformText]: string;
+  [InvokeFunction.ConvertCase]: string;
+  [InvokeFunction.GenerateCode]: string;
   [InvokeFunction.GenerateHashes]: HashResult;
   [InvokeFunction.EncodeBase64]: string;
   [InvokeFunction.DecodeBase64]: string;
+  [InvokeFunction.DecodeText]: DecodedText;
+  [InvokeFunction.EncodeText]: EncodedText;
+  [InvokeFunction.FixMojibake]: MojibakeFix;
   [InvokeFunction.DecodeJwt]: JwtDecodeResult;
   [InvokeFunction.GenerateRsaKey]: RsaKeyPair;
   [InvokeFunction.AnalyzeRsaKey]: RsaKeyAnalysis;
@@ -122,7 +314,19 @@ export async function utilityInvoke<T extends InvokeFunction>(
   throw new Error(`Function ${cmd} not found`);
 }
 
-export type UtilityInvokeError = string;
+/** Plain errors arrive as strings, located ones carry a diagnostic. */
+export type UtilityInvokeError =
+  | string
+  | { message: string; diagnostic: Diagnostic };
+
+export function invokeErrorMessage(error: UtilityInvokeError): string {
+  if (typeof error === "string") {
+    return error;
+  }
+  const { diagnostic } = error;
+  const hint = diagnostic.hint ? ` ${diagnostic.hint}.` : "";
+  return `${error.message}.${hint}`;
+}
 export function useUtilityInvoke<T extends InvokeFunction>(
   cmd: T,
   options?: SWRMutationConfiguration<
@@ -24,9 +24,36 @@ export enum InvokeFunction {
   AnalyzeUuid = "analyze_uuid",
   GenerateHashes = "generate_hashes",
   FormatJson = "format_json",
+  RepairJson = "repair_json",
+  DiffJson = "diff_json",
+  ApplyJsonPatch = "apply_json_patch",
+  ValidateJsonSchema = "validate_json_schema",
+  InferJsonSchema = "infer_json_schema",
   FormatCss = "format_css",
+  FormatHtml = "format_html",
+  FormatXml = "format_xml",
+  QueryXml = "query_xml",
+  CanonicalizeXml = "canonicalize_xml",
+  FormatSql = "format_sql",
+  RenderMarkdown = "render_markdown",
+  MarkdownToc = "markdown_toc",
+  HtmlToMarkdown = "html_to_markdown",
+  FormatMarkdownTables = "format_markdown_tables",
+  FormatYaml = "format_yaml",
+  FormatToml = "format_toml",
+  DiffText = "diff_text",
+  ApplyTextPatch = "apply_text_patch",
+  TestRegex = "test_regex",
+  ReplaceRegex = "replace_regex",
+  ExplainRegex = "explain_regex",
+  TransformText = "transform_text",
+  ConvertCase = "convert_case",
+  GenerateCode = "generate_code",
   EncodeBase64 = "encode_base64",
   DecodeBase64 = "decode_base64",
+  DecodeText = "decode_text",
+  EncodeText = "encode_text",
+  FixMojibake = "fix_mojibake",
   DecodeJwt = "decode_jwt",
   GenerateRsaKey = "generate_rsa_key",
   AnalyzeRsaKey = "analyze_rsa_key",
@@ -74,6 +101,363 @@ export type IndentStyle =
   | { [IndentStyleEnum.Spaces]: number }
   | IndentStyleEnum.Tabs
   | IndentStyleEnum.Minified;
+export type JsonFormatOptions = {
+  sortKeys?: boolean;
+  compactArrays?: boolean;
+  ascii?: boolean;
+  trailingNewline?: boolean;
+};
+export type JsonRepairResult = {
+  output: string;
+  /** One warning per rewrite, located in the original input. */
+  fixes: Diagnostic[];
+  /** Number of top-level values; above one for NDJSON input. */
+  records: number;
+};
+export type JsonDiffOptions = {
+  /** Match array items (objects) by this field instead of by index. */
+  arrayKey?: string;
+  ignoreOrder?: boolean;
+  /** JSON Pointers to skip; a `*` segment matches anything. */
+  ignorePaths?: string[];
+};
+export type JsonChange = {
+  kind: "added" | "removed" | "changed" | "moved";
+  /** JSON Pointer, as used by the matching patch operation. */
+  path: string;
+  /** Previous pointer of a moved item. */
+  from: string | null;
+  /** Values as compact JSON. */
+  left: string | null;
+  right: string | null;
+};
+export type JsonDiffResult = {
+  changes: JsonChange[];
+  /** RFC 6902 operations turning left into right. */
+  jsonPatch: string;
+  /** RFC 7386 document turning left into right. */
+  mergePatch: string;
+};
+export type PatchFormat = "jsonpatch" | "mergepatch";
+export type SchemaDraft = "draft-07" | "2019-09" | "2020-12";
+export type SchemaValidateOptions = {
+  /** Overrides `$schema`; detected when omitted. */
+  draft?: SchemaDraft;
+  /** Any format `convert_data` reads, e.g. "yaml". Defaults to "json". */
+  documentFormat?: string;
+  validateFormats?: boolean;
+};
+export type SchemaError = {
+  /** JSON Pointer to the failing value; "" is the root. */
+  instancePath: string;
+  /** JSON Pointer to the failing keyword in the schema. */
+  schemaPath: string;
+  message: string;
+};
+export type SchemaValidationResult = {
+  valid: boolean;
+  draft: SchemaDraft;
+  errors: SchemaError[];
+};
+export type SchemaInferOptions = {
+  detectFormats?: boolean;
+  title?: string;
+};
+export type CssFormatOptions = {
+  /** Browser queries such as "chrome 90, safari >= 14". */
+  targets?: string;
+  cssModules?: boolean;
+};
+export type CssModuleClass = {
+  name: string;
+  /** Hashed name in the output. */
+  local: string;
+  composes: string[];
+};
+export type CssFormatResult = {
+  code: string;
+  /** Rules and declarations skipped by error recovery. */
+  warnings: Diagnostic[];
+  exports: CssModuleClass[];
+};
+export type HtmlFormatOptions = {
+  /** Wrap text and attribute lists past this column. Defaults to 80. */
+  printWidth?: number;
+  /** Format <style> as CSS and JSON <script> blocks. Defaults to true. */
+  formatEmbedded?: boolean;
+};
+export type HtmlFormatResult = {
+  output: string;
+  /** Parse errors the parser recovered from, as warnings. */
+  errors: Diagnostic[];
+};
+export type XmlFormatOptions = {
+  stripComments?: boolean;
+  /** Print CDATA sections as escaped text. */
+  cdataToText?: boolean;
+  /** Write empty elements as <a></a> instead of <a/>. */
+  expandEmpty?: boolean;
+};
+export type XPathResultKind = "nodeset" | "string" | "number" | "boolean";
+export type XmlQueryMatch = {
+  /** Absolute location path, e.g. /catalog[1]/book[2]/@id. */
+  path: string;
+  /** Markup for elements, the string-value otherwise. */
+  value: string;
+};
+export type XmlQueryResult = {
+  kind: XPathResultKind;
+  /** In document order; empty for scalar results. */
+  matches: XmlQueryMatch[];
+  output: string;
+};
+export type C14nMethod =
+  | "c14n"
+  | "c14nwithcomments"
+  | "exclusivec14n"
+  | "exclusivec14nwithcomments";
+export type C14nOptions = {
+  /** XPath selecting the element to canonicalize. */
+  subset?: string;
+  /** Exclusive InclusiveNamespaces PrefixList; "#default" for the default namespace. */
+  inclusivePrefixes?: string[];
+};
+export type SqlDialect = "postgresql" | "mysql" | "sqlite" | "tsql" | "bigquery";
+export type KeywordCase = "upper" | "lower" | "preserve";
+export type CommaPosition = "trailing" | "leading";
+export type SqlFormatOptions = {
+  keywordCase?: KeywordCase;
+  commaPosition?: CommaPosition;
+  /** Parenthesized groups and CASE stay inline up to this column. */
+  lineWidth?: number;
+  /** JSON array or object whose values replace placeholders. */
+  params?: string;
+};
+export type MarkdownOptions = {
+  /** Remove scripts, event handlers and unsafe URLs. */
+  sanitize?: boolean;
+  /** Give headings GitHub-style anchor ids. */
+  headingIds?: boolean;
+  smartPunctuation?: boolean;
+};
+export type TocEntry = {
+  level: number;
+  text: string;
+  id: string;
+};
+export type QuoteStyle = "double" | "single" | "preserve";
+export type YamlFormatOptions = {
+  indent?: number;
+  quoteStyle?: QuoteStyle;
+  /** Inline aliases and merge keys; drops comments. */
+  expandAliases?: boolean;
+};
+export type YamlFormatResult = {
+  output: string;
+  diagnostics: Diagnostic[];
+  documents: number;
+};
+export type TomlFormatOptions = {
+  /** Spaces for items of multi-line arrays. */
+  indent?: number;
+  indentTables?: boolean;
+  /** "single" means literal strings. */
+  quoteStyle?: QuoteStyle;
+};
+export type TomlFormatResult = {
+  output: string;
+  diagnostics: Diagnostic[];
+};
+export type DiffGranularity = "line" | "word" | "char";
+export type TextDiffOptions = {
+  /** How changed lines are highlighted; the unified diff is always by line. */
+  granularity?: DiffGranularity;
+  ignoreWhitespace?: boolean;
+  ignoreCase?: boolean;
+  /** CRLF, LF and a missing final newline compare equal. */
+  ignoreLineEnding?: boolean;
+  /** Unchanged lines around each hunk. Defaults to 3. */
+  context?: number;
+};
+export type DiffSpan = {
+  tag: "equal" | "insert" | "delete";
+  text: string;
+};
+export type DiffRow = {
+  kind: "equal" | "added" | "removed" | "changed";
+  /** 1-based line numbers. */
+  leftLine: number | null;
+  rightLine: number | null;
+  left: DiffSpan[];
+  right: DiffSpan[];
+};
+export type DiffHunk = {
+  header: string;
+  rows: DiffRow[];
+};
+export type TextDiffResult = {
+  /** Empty when the texts compare equal. */
+  unified: string;
+  hunks: DiffHunk[];
+  additions: number;
+  deletions: number;
+};
+export type RegexEngine = "auto" | "standard" | "fancy";
+export type RegexOptions = {
+  /** "auto" uses "standard" unless the pattern needs look-around or back-references. */
+  engine?: RegexEngine;
+  caseInsensitive?: boolean;
+  /** ^ and $ match at line breaks. */
+  multiLine?: boolean;
+  dotMatchesNewLine?: boolean;
+  /** Whitespace and # comments in the pattern are ignored. */
+  ignoreWhitespace?: boolean;
+  /** Quantifiers are lazy unless followed by ?. */
+  swapGreed?: boolean;
+  /** Most matches to return. Defaults to 1000. */
+  limit?: number;
+};
+export type RegexGroup = {
+  index: number;
+  name: string | null;
+  /** Null when the group did not take part in the match. */
+  text: string | null;
+  /** Byte offsets into the text. */
+  start: number | null;
+  end: number | null;
+  /** Character (code point) offsets into the text. */
+  charStart: number | null;
+  charEnd: number | null;
+};
+export type RegexMatch = {
+  text: string;
+  start: number;
+  end: number;
+  charStart: number;
+  charEnd: number;
+  groups: RegexGroup[];
+};
+export type RegexTestResult = {
+  /** The engine that ran; never "auto". */
+  engine: RegexEngine;
+  matches: RegexMatch[];
+  truncated: boolean;
+};
+export type RegexReplaceResult = {
+  output: string;
+  replacements: number;
+};
+export type RegexTokenKind =
+  | "literal"
+  | "charactertype"
+  | "class"
+  | "anchor"
+  | "group"
+  | "quantifier"
+  | "alternation"
+  | "backreference"
+  | "flags"
+  | "comment";
+export type RegexToken = {
+  kind: RegexTokenKind;
+  text: string;
+  /** Byte offsets into the pattern. */
+  start: number;
+  end: number;
+  /** Group nesting. */
+  depth: number;
+  description: string;
+};
+export type TextCase =
+  | "lower"
+  | "upper"
+  | "camel"
+  | "pascal"
+  | "snake"
+  | "screamingSnake"
+  | "kebab"
+  | "title"
+  | "sentence";
+export type SortOrder = "lexical" | "natural" | "numeric" | "length" | "random";
+export type TrimSide = "both" | "start" | "end";
+export type ReverseUnit = "lines" | "words" | "characters";
+/** One step of a transformation recipe; steps run in order. */
+export type TextStep =
+  | { type: "case"; case: TextCase; preserveAcronyms: boolean }
+  | {
+      type: "sort";
+      order: SortOrder;
+      descending: boolean;
+      ignoreCase: boolean;
+      /** Set for a random order that replays the same shuffle. */
+      seed?: number | null;
+    }
+  | { type: "dedupe"; ignoreCase: boolean; adjacentOnly: boolean }
+  | { type: "trim"; side: TrimSide }
+  | { type: "reverse"; unit: ReverseUnit }
+  /** Width in display columns. */
+  | { type: "wrap"; width: number }
+  | { type: "unwrap" }
+  | { type: "prefix"; text: string }
+  | { type: "suffix"; text: string }
+  | { type: "numberLines"; start: number; separator: string; pad: boolean }
+  | { type: "removeBlankLines" };
+export type TextEncoding =
+  | "utf8"
+  | "utf16le"
+  | "utf16be"
+  | "utf32le"
+  | "utf32be"
+  | "latin1"
+  | "windows1252"
+  | "shiftJis"
+  | "gbk"
+  | "gb18030"
+  | "big5"
+  | "eucKr"
+  | "koi8r";
+export type ByteFormat = "hex" | "base64";
+export type DecodedText = {
+  text: string;
+  /** The encoding used, given or detected. */
+  encoding: TextEncoding;
+  /** The encoding was not given. */
+  detected: boolean;
+  /** False when detection was a best guess. */
+  confident: boolean;
+  /** A byte order mark was found and removed. */
+  bom: boolean;
+  /** Some bytes did not decode and became U+FFFD. */
+  malformed: boolean;
+  byteCount: number;
+};
+export type EncodedText = {
+  /** Space-separated bytes. */
+  hex: string;
+  base64: string;
+  byteCount: number;
+};
+export type MojibakeFix = {
+  text: string;
+  /** Layers of double encoding undone; 0 when unchanged. */
+  rounds: number;
+};
+export type CodegenLanguage =
+  | "rust"
+  | "typescript"
+  | "zod"
+  | "go"
+  | "python"
+  | "pydantic"
+  | "kotlin";
+export type CodegenOptions = {
+  /** Name of the top-level type. Defaults to "Root". */
+  rootName?: string;
+  /** Name nested types by full path (UserAddress) instead of key. */
+  pathNames?: boolean;
+  /** Inline nested objects (TypeScript, zod and Go only). */
+  inline?: boolean;
+};
 export type RsaKeyPair = {
   privateKey: string;
   publicKey: string;
@@ -178,3 +562,18 @@ export enum JwtAlgorithm {
   // PS384 = "PS384",
   // PS512 = "PS512",
 }
+
+export type DiagnosticSeverity = "error" | "warning" | "info";
+
+/** A parse failure pinned to a span of the input. */
+export type Diagnostic = {
+  message: string;
+  /** Byte offset into the input. */
+  offset: number;
+  line: number;
+  column: number;
+  /** Span length in bytes. */
+  length: number;
+  severity: DiagnosticSeverity;
+  hint: string | null;
+};